@@ -0,0 +1,271 @@
+// Background agent mode — the capture core runs independently of the UI
+//
+// In agent mode the process starts headless (tray only) and the review window
+// is created on demand and destroyed when closed, so its WebView2 processes
+// come and go without touching capture. A webview that crashes or is closed
+// leaves MIDI monitoring and any in-flight recording running; reopening from
+// the tray or launching Sacho again builds a fresh window.
+//
+// The agent also exposes a local control endpoint for scripts and other
+// front-ends: a loopback TCP socket speaking one JSON request per line,
+// authenticated with a random token. The port and token are written to
+// `agent.json` in the app config dir, readable only by the owning user's
+// profile. Note that Windows ends every process in an interactive session at
+// logoff, including this one; surviving logoff would need a session-0 service,
+// which can't open the logged-in user's audio/video devices.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::config::Config;
+use crate::recording::{MidiMonitor, RecordingState};
+use crate::remote::constant_time_eq;
+
+/// Command-line flag that starts the app in agent mode
+pub const AGENT_FLAG: &str = "--agent";
+
+/// Name of the endpoint file in the app config dir
+const ENDPOINT_FILE: &str = "agent.json";
+
+/// Longest time a client may take to send a request line
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest request line accepted; requests are a token and a command name
+const MAX_LINE_BYTES: u64 = 4096;
+
+/// Set once at startup from the flag or config
+static AGENT_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Endpoint file written by this process, removed on exit
+static ENDPOINT_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Contents of `agent.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentEndpoint {
+    pub port: u16,
+    pub token: String,
+    pub pid: u32,
+}
+
+/// A control request
+#[derive(Debug, Deserialize)]
+struct AgentRequest {
+    token: String,
+    #[serde(flatten)]
+    command: AgentCommand,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum AgentCommand {
+    /// Current recording state
+    Status,
+    StartRecording,
+    StopRecording,
+    /// Open (or focus) the review window
+    ShowUi,
+    /// Exit the agent (finalizing any recording first)
+    Quit,
+}
+
+#[derive(Debug, Serialize)]
+struct AgentResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<RecordingState>,
+}
+
+impl AgentResponse {
+    fn ok() -> Self {
+        Self { ok: true, error: None, state: None }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self { ok: false, error: Some(message.into()), state: None }
+    }
+}
+
+/// Decide whether this process runs as an agent. Call once during setup.
+pub fn init(config: &Config) -> bool {
+    let enabled = config.agent_mode || std::env::args().any(|arg| arg == AGENT_FLAG);
+    AGENT_MODE.store(enabled, Ordering::SeqCst);
+    enabled
+}
+
+pub fn is_agent_mode() -> bool {
+    AGENT_MODE.load(Ordering::SeqCst)
+}
+
+/// Show the main window, creating it if agent mode destroyed it
+pub fn show_ui(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        return;
+    }
+
+    let Some(window_config) = app.config().app.windows.iter().find(|w| w.label == "main") else {
+        println!("[Agent] No main window in the app config");
+        return;
+    };
+    match tauri::WebviewWindowBuilder::from_config(app, window_config).and_then(|b| b.build()) {
+        Ok(window) => {
+            let _ = window.show();
+            let _ = window.set_focus();
+            println!("[Agent] Review window opened");
+        }
+        Err(e) => println!("[Agent] Failed to open review window: {}", e),
+    }
+}
+
+/// Start the local control endpoint
+pub fn start_control_server(app: AppHandle) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", 0))
+        .map_err(|e| format!("Failed to bind agent socket: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let endpoint = AgentEndpoint {
+        port,
+        token: uuid::Uuid::new_v4().simple().to_string(),
+        pid: std::process::id(),
+    };
+
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(ENDPOINT_FILE);
+    let json = serde_json::to_string_pretty(&endpoint).map_err(|e| e.to_string())?;
+    write_endpoint_file(&path, &json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    let _ = ENDPOINT_PATH.set(path);
+
+    let token = Arc::new(endpoint.token);
+    std::thread::Builder::new()
+        .name("sacho-agent".into())
+        .spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let app = app.clone();
+                let token = token.clone();
+                std::thread::spawn(move || handle_connection(&app, stream, &token));
+            }
+        })
+        .map_err(|e| format!("Failed to spawn agent thread: {}", e))?;
+
+    println!("[Agent] Control endpoint listening on 127.0.0.1:{}", port);
+    Ok(())
+}
+
+/// Remove the endpoint file so clients don't connect to a dead port
+pub fn remove_endpoint_file() {
+    if let Some(path) = ENDPOINT_PATH.get() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Write the endpoint file so only the current user can read the token.
+/// On Windows the app config dir is already private to the user's profile.
+fn write_endpoint_file(path: &std::path::Path, json: &str) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    // A file left by a crashed run keeps its old permissions, so replace it
+    // rather than writing into it
+    if let Err(e) = std::fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            return Err(e);
+        }
+    }
+    options.open(path)?.write_all(json.as_bytes())
+}
+
+fn handle_connection(app: &AppHandle, stream: TcpStream, token: &str) {
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let Ok(mut writer) = stream.try_clone() else { return };
+    let mut reader = BufReader::new(stream);
+    let mut authenticated = false;
+
+    loop {
+        // Bounded read: a client can't grow the line forever
+        let mut line = String::new();
+        match (&mut reader).take(MAX_LINE_BYTES).read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if !line.ends_with('\n') {
+            let _ = write_response(&mut writer, &AgentResponse::error("Request too long"));
+            break;
+        }
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<AgentRequest>(&line) {
+            Ok(request) if constant_time_eq(request.token.as_bytes(), token.as_bytes()) => {
+                authenticated = true;
+                dispatch(app, request.command)
+            }
+            Ok(_) => {
+                // Don't keep talking to a client that doesn't know the token
+                let _ = write_response(&mut writer, &AgentResponse::error("Invalid token"));
+                break;
+            }
+            Err(e) => {
+                let response = AgentResponse::error(format!("Invalid request: {}", e));
+                // Same for a client that hasn't shown it knows the token yet
+                if !authenticated {
+                    let _ = write_response(&mut writer, &response);
+                    break;
+                }
+                response
+            }
+        };
+        if write_response(&mut writer, &response).is_err() {
+            break;
+        }
+    }
+}
+
+fn write_response(writer: &mut TcpStream, response: &AgentResponse) -> std::io::Result<()> {
+    let mut json = serde_json::to_string(response).unwrap_or_else(|_| "{\"ok\":false}".into());
+    json.push('\n');
+    writer.write_all(json.as_bytes())
+}
+
+fn dispatch(app: &AppHandle, command: AgentCommand) -> AgentResponse {
+    match command {
+        AgentCommand::Status => {
+            let state = app.state::<RwLock<RecordingState>>().read().clone();
+            AgentResponse { state: Some(state), ..AgentResponse::ok() }
+        }
+        AgentCommand::StartRecording => {
+            let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+            let result = monitor.lock().manual_start_recording();
+            result.map_or_else(AgentResponse::error, |_| AgentResponse::ok())
+        }
+        AgentCommand::StopRecording => {
+            let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+            let result = monitor.lock().manual_stop_recording();
+            result.map_or_else(AgentResponse::error, |_| AgentResponse::ok())
+        }
+        AgentCommand::ShowUi => {
+            // Window creation must happen on the main thread
+            let handle = app.clone();
+            let _ = app.run_on_main_thread(move || show_ui(&handle));
+            AgentResponse::ok()
+        }
+        AgentCommand::Quit => {
+            app.exit(0);
+            AgentResponse::ok()
+        }
+    }
+}
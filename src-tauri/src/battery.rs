@@ -0,0 +1,208 @@
+// Battery awareness — record-safe mode for laptops running low
+//
+// A laptop that dies mid-take loses whatever the writers were still holding:
+// buffered WAV data with a placeholder header, MIDI events not yet flushed
+// and the one open video file. A watcher thread polls the battery; when the
+// computer switches to battery during a session the user is warned, and once
+// the charge drops below the configured threshold Sacho enters record-safe
+// mode: audio headers and MIDI files are synced to disk every few seconds,
+// video encoding drops to a cheaper preset (less CPU, longer battery) and
+// video is split into segments so a sudden shutdown costs at most the last
+// one.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::Config;
+use crate::notifications;
+use crate::recording::{MidiMonitor, RecordingState};
+
+/// How often the battery is polled
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How often writers force their data to disk in record-safe mode
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Set while record-safe mode is active. Read by the writers on every write.
+static SAFE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Power source and charge, as reported by the OS
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct BatteryStatus {
+    pub on_battery: bool,
+    /// Remaining charge, None when unknown or there is no battery
+    pub percent: Option<u8>,
+}
+
+/// Whether writers should sync to disk aggressively
+pub fn is_safe_mode() -> bool {
+    SAFE_MODE.load(Ordering::Relaxed)
+}
+
+/// Current power source and charge
+pub fn read_status() -> BatteryStatus {
+    platform::read_status()
+}
+
+/// Start polling the battery. Must be called after the MIDI monitor is managed.
+pub fn start_battery_watcher(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("sacho-battery".into())
+        .spawn(move || battery_loop(app))
+        .expect("Failed to spawn battery thread");
+}
+
+fn battery_loop(app: AppHandle) {
+    let mut last_status: Option<BatteryStatus> = None;
+    // Session whose video was already lowered, so it happens once per take
+    let mut lowered_session: Option<PathBuf> = None;
+    let mut last_split = Instant::now();
+
+    loop {
+        let status = read_status();
+        let previous = last_status.replace(status);
+        if previous != Some(status) {
+            let _ = app.emit("battery-state", status);
+        }
+
+        let (enabled, low_percent, segment) = {
+            let config = app.state::<RwLock<Config>>();
+            let config = config.read();
+            (
+                config.battery_safe_mode,
+                config.battery_low_percent,
+                Duration::from_secs(config.battery_segment_minutes as u64 * 60),
+            )
+        };
+        let session = app.state::<RwLock<RecordingState>>().read().current_session_path.clone();
+
+        let switched_to_battery = status.on_battery && previous.is_some_and(|p| !p.on_battery);
+        if switched_to_battery && session.is_some() {
+            println!("[Power] Switched to battery during a recording");
+            notifications::notify_on_battery(&app);
+        }
+
+        let low = enabled && status.on_battery && status.percent.is_some_and(|p| p <= low_percent);
+        if low != SAFE_MODE.swap(low, Ordering::Relaxed) {
+            if low {
+                println!("[Power] Battery at {:?}%, entering record-safe mode", status.percent);
+                notifications::notify_battery_low(&app, status.percent.unwrap_or(0));
+            } else {
+                println!("[Power] Leaving record-safe mode");
+            }
+        }
+
+        match &session {
+            Some(path) if low => {
+                let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+                let video_manager = monitor.lock().video_manager();
+                if lowered_session.as_ref() != Some(path) {
+                    video_manager.lock().lower_for_battery();
+                    lowered_session = Some(path.clone());
+                    last_split = Instant::now();
+                } else if last_split.elapsed() >= segment {
+                    video_manager.lock().split_segments();
+                    last_split = Instant::now();
+                }
+            }
+            Some(_) => {}
+            None => lowered_session = None,
+        }
+
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    use super::BatteryStatus;
+
+    // From WinBase.h
+    const AC_LINE_OFFLINE: u8 = 0;
+    const BATTERY_FLAG_NO_BATTERY: u8 = 128;
+    const BATTERY_PERCENTAGE_UNKNOWN: u8 = 255;
+
+    pub fn read_status() -> BatteryStatus {
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0
+            || status.BatteryFlag & BATTERY_FLAG_NO_BATTERY != 0
+        {
+            return BatteryStatus::default();
+        }
+        BatteryStatus {
+            on_battery: status.ACLineStatus == AC_LINE_OFFLINE,
+            percent: (status.BatteryLifePercent != BATTERY_PERCENTAGE_UNKNOWN)
+                .then_some(status.BatteryLifePercent),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::BatteryStatus;
+
+    /// Parses `pmset -g batt`, whose output looks like:
+    ///   Now drawing from 'Battery Power'
+    ///    -InternalBattery-0 (id=1234)	85%; discharging; 4:12 remaining present: true
+    pub fn read_status() -> BatteryStatus {
+        let Ok(output) = std::process::Command::new("pmset").args(["-g", "batt"]).output() else {
+            return BatteryStatus::default();
+        };
+        let text = String::from_utf8_lossy(&output.stdout);
+        let percent = text
+            .lines()
+            .find(|l| l.contains("InternalBattery"))
+            .and_then(|l| l.split('\t').nth(1))
+            .and_then(|l| l.split('%').next())
+            .and_then(|p| p.trim().parse().ok());
+        BatteryStatus {
+            on_battery: percent.is_some() && text.contains("'Battery Power'"),
+            percent,
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use std::fs;
+    use std::path::Path;
+
+    use super::BatteryStatus;
+
+    const POWER_SUPPLY: &str = "/sys/class/power_supply";
+
+    fn read(dir: &Path, name: &str) -> Option<String> {
+        fs::read_to_string(dir.join(name)).ok().map(|s| s.trim().to_string())
+    }
+
+    pub fn read_status() -> BatteryStatus {
+        let Ok(entries) = fs::read_dir(POWER_SUPPLY) else {
+            return BatteryStatus::default();
+        };
+        let mut percent = None;
+        let mut discharging = false;
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            // Skip peripheral batteries (mice, headsets)
+            if read(&dir, "type").as_deref() != Some("Battery")
+                || read(&dir, "scope").as_deref() == Some("Device")
+            {
+                continue;
+            }
+            percent = percent.or_else(|| read(&dir, "capacity")?.parse().ok());
+            discharging |= read(&dir, "status").as_deref() == Some("Discharging");
+        }
+        BatteryStatus {
+            on_battery: percent.is_some() && discharging,
+            percent,
+        }
+    }
+}
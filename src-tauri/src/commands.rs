@@ -3,6 +3,7 @@
 use std::sync::Arc;
 use crate::config::Config;
 use crate::devices::{AudioDevice, MidiDevice, VideoDevice, DeviceManager};
+use crate::devices::aliases::DeviceAlias;
 use crate::recording::{RecordingState, RecordingStatus, MidiMonitor};
 use crate::session::{SessionDatabase, SessionSummary, SessionMetadata, SessionFilter};
 use crate::autostart::{self, AutostartInfo};
@@ -16,6 +17,7 @@ use serde::{Deserialize, Serialize};
 
 #[tauri::command]
 pub async fn refresh_devices(
+    app: tauri::AppHandle,
     device_manager: State<'_, RwLock<DeviceManager>>
 ) -> Result<(), String> {
     let (audio, midi, video) = tokio::task::spawn_blocking(|| {
@@ -29,6 +31,57 @@ pub async fn refresh_devices(
     dm.audio_devices = audio;
     dm.midi_devices = midi;
     dm.video_devices = video;
+    drop(dm);
+
+    crate::devices::aliases::apply(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_device_aliases() -> Vec<DeviceAlias> {
+    crate::devices::aliases::all()
+}
+
+/// Create (id 0) or update a device alias. Returns the alias as saved.
+#[tauri::command]
+pub fn save_device_alias(
+    app: tauri::AppHandle,
+    db: State<'_, SessionDatabase>,
+    alias: DeviceAlias,
+) -> Result<DeviceAlias, String> {
+    let name = alias.alias.trim();
+    if name.is_empty() {
+        return Err("Alias name cannot be empty".to_string());
+    }
+    if alias.name_pattern.trim().is_empty() {
+        return Err("Device name pattern cannot be empty".to_string());
+    }
+    let alias = DeviceAlias {
+        alias: name.to_string(),
+        name_pattern: alias.name_pattern.trim().to_string(),
+        usb_path: alias.usb_path.filter(|p| !p.trim().is_empty()),
+        ..alias
+    };
+
+    let id = db.save_device_alias(&alias).map_err(|e| e.to_string())?;
+    crate::devices::aliases::load(&db);
+    crate::devices::aliases::apply(&app);
+
+    crate::devices::aliases::all()
+        .into_iter()
+        .find(|a| a.id == id)
+        .ok_or_else(|| "Alias was not saved".to_string())
+}
+
+#[tauri::command]
+pub fn delete_device_alias(
+    app: tauri::AppHandle,
+    db: State<'_, SessionDatabase>,
+    id: i64,
+) -> Result<(), String> {
+    db.delete_device_alias(id).map_err(|e| e.to_string())?;
+    crate::devices::aliases::load(&db);
+    crate::devices::aliases::apply(&app);
     Ok(())
 }
 
@@ -39,6 +92,27 @@ pub fn get_audio_devices(
     device_manager.read().audio_devices.clone()
 }
 
+/// Rate mismatches among the audio devices selected for recording, checked
+/// against the configured output rate before a session starts
+#[tauri::command]
+pub fn get_sample_rate_warnings(
+    config: State<'_, RwLock<Config>>,
+    device_manager: State<'_, RwLock<DeviceManager>>,
+) -> Vec<crate::recording::sample_rate::SampleRateWarning> {
+    let config = config.read();
+    let devices: Vec<crate::recording::sample_rate::DeviceRate> = device_manager
+        .read()
+        .audio_devices
+        .iter()
+        .filter(|d| config.selected_audio_devices.contains(&d.name))
+        .map(|d| crate::recording::sample_rate::DeviceRate {
+            device_name: d.name.clone(),
+            rate: d.sample_rate,
+        })
+        .collect();
+    crate::recording::sample_rate::check(&config, &devices)
+}
+
 #[tauri::command]
 pub fn get_midi_devices(
     device_manager: State<'_, RwLock<DeviceManager>>
@@ -46,6 +120,24 @@ pub fn get_midi_devices(
     device_manager.read().midi_devices.clone()
 }
 
+/// MIDI access modes this platform's backend can provide
+#[tauri::command]
+pub fn get_supported_midi_access_modes() -> Vec<crate::config::MidiAccessMode> {
+    use crate::config::MidiAccessMode;
+    [MidiAccessMode::Exclusive, MidiAccessMode::Shared, MidiAccessMode::VirtualTap]
+        .into_iter()
+        .filter(|mode| mode.is_supported())
+        .collect()
+}
+
+/// MIDI output ports available as a thru destination
+#[tauri::command]
+pub async fn get_midi_output_ports() -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(crate::devices::enumeration::enumerate_midi_output_ports)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn get_video_devices(
     device_manager: State<'_, RwLock<DeviceManager>>
@@ -66,6 +158,39 @@ pub fn validate_video_device_config(
     crate::devices::enumeration::validate_video_config(&device_id, &format, width, height, fps)
 }
 
+/// Run a proposed video config on the device for a few seconds and report
+/// whether frames arrive at the requested size and rate and the encoder keeps up
+#[tauri::command]
+pub async fn preflight_video_device(
+    app: tauri::AppHandle,
+    recording_state: State<'_, RwLock<RecordingState>>,
+    midi_monitor: State<'_, Arc<Mutex<MidiMonitor>>>,
+    device_id: String,
+    config: crate::config::VideoDeviceConfig,
+) -> Result<crate::recording::preflight::VideoPreflightReport, String> {
+    {
+        let mut state = recording_state.write();
+        if state.status != RecordingStatus::Idle {
+            return Err("Cannot test while recording or reinitializing".to_string());
+        }
+        // Block triggers while the device is borrowed for the test
+        state.status = RecordingStatus::Initializing;
+    }
+    let _ = app.emit("recording-state-changed", "initializing");
+    crate::tray::update_tray_state(&app, crate::tray::TrayState::Initializing);
+
+    let monitor_arc = midi_monitor.inner().clone();
+    let result = tokio::task::spawn_blocking(move || {
+        monitor_arc.lock().preflight_video(&device_id, &config)
+    }).await.map_err(|e| e.to_string());
+
+    recording_state.write().status = RecordingStatus::Idle;
+    let _ = app.emit("recording-state-changed", "idle");
+    crate::tray::update_tray_state(&app, crate::tray::TrayState::Idle);
+
+    result?
+}
+
 // ============================================================================
 // Recording Commands
 // ============================================================================
@@ -77,6 +202,88 @@ pub fn get_recording_state(
     state.read().clone()
 }
 
+/// Live per-camera encoder statistics (bitrate, encode fps, queue, drops).
+/// Rates cover the time since the previous call, so poll at a steady interval.
+#[tauri::command]
+pub fn get_recording_stats(
+    monitor: State<'_, Arc<Mutex<MidiMonitor>>>,
+) -> Vec<crate::recording::video::VideoPipelineStats> {
+    let video_manager = monitor.lock().video_manager();
+    let mut mgr = video_manager.lock();
+    mgr.live_stats()
+}
+
+/// Trigger-to-first-write latency of one stream on one device, across
+/// recent sessions
+#[derive(Serialize)]
+pub struct StartupLatencyStats {
+    pub stream: crate::session::LatencyStream,
+    pub device_name: String,
+    /// Sessions with a measurement
+    pub sessions: usize,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    /// The most recent session's measurement
+    pub latest_ms: f64,
+}
+
+/// Startup latency per stream and device over the last `limit` sessions
+/// (default 50), so a slower start shows up before it eats into the pre-roll
+#[tauri::command]
+pub async fn get_startup_latency_stats(
+    config: State<'_, RwLock<Config>>,
+    db: State<'_, SessionDatabase>,
+    limit: Option<usize>,
+) -> Result<Vec<StartupLatencyStats>, String> {
+    let filter = SessionFilter {
+        limit: Some(limit.unwrap_or(50)),
+        ..Default::default()
+    };
+    let paths: Vec<std::path::PathBuf> = {
+        let config = config.read();
+        db.query_sessions(&filter)
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(|s| config.session_path(&s.id))
+            .collect()
+    };
+
+    tokio::task::spawn_blocking(move || {
+        // Newest first, so the first value per key is the latest
+        let mut samples: Vec<((crate::session::LatencyStream, String), Vec<f64>)> = Vec::new();
+        for path in &paths {
+            for latency in crate::session::read_startup_latency(path) {
+                let key = (latency.stream, latency.device_name);
+                match samples.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, values)) => values.push(latency.latency_ms),
+                    None => samples.push((key, vec![latency.latency_ms])),
+                }
+            }
+        }
+        samples
+            .into_iter()
+            .map(|((stream, device_name), values)| {
+                let latest_ms = values[0];
+                let mut sorted = values;
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let at = |q: f64| sorted[((sorted.len() - 1) as f64 * q).round() as usize];
+                StartupLatencyStats {
+                    stream,
+                    device_name,
+                    sessions: sorted.len(),
+                    median_ms: at(0.5),
+                    p95_ms: at(0.95),
+                    max_ms: sorted[sorted.len() - 1],
+                    latest_ms,
+                }
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 /// Manual recording now uses the same MidiMonitor infrastructure as MIDI-triggered recording
 /// This ensures all device types (MIDI, audio, video) are captured consistently
 
@@ -102,7 +309,9 @@ pub async fn start_recording(
     // Clone the Arc so we can move it into the blocking task
     let monitor_arc = midi_monitor.inner().clone();
 
-    // Pipeline creation is blocking (100ms+), offload to avoid blocking the IPC thread
+    // Pipeline creation is blocking (100ms+), offload to avoid blocking the IPC thread.
+    // The checks above can race a trigger; manual_start_recording claims the
+    // capture state atomically and the session folder lock is exclusive.
     tokio::task::spawn_blocking(move || {
         let monitor = monitor_arc.lock();
         monitor.manual_start_recording()
@@ -138,28 +347,71 @@ pub struct SessionFilterParams {
     pub has_video: Option<bool>,
     pub has_notes: Option<bool>,
     pub has_title: Option<bool>,
+    pub archived: Option<bool>,
+    pub favorite: Option<bool>,
+    /// Custom field values a session must have, e.g. {"student": "Ana"}
+    #[serde(default)]
+    pub fields: crate::session::CustomFields,
+    #[serde(default)]
+    pub sort: crate::session::SessionSort,
+    #[serde(default)]
+    pub ascending: bool,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
 }
 
+impl From<SessionFilterParams> for SessionFilter {
+    fn from(filter: SessionFilterParams) -> Self {
+        SessionFilter {
+            search_query: filter.search,
+            has_audio: filter.has_audio,
+            has_midi: filter.has_midi,
+            has_video: filter.has_video,
+            has_notes: filter.has_notes,
+            has_title: filter.has_title,
+            archived: filter.archived,
+            favorite: filter.favorite,
+            custom_fields: filter.fields,
+            sort: filter.sort,
+            ascending: filter.ascending,
+            limit: filter.limit,
+            offset: filter.offset,
+        }
+    }
+}
+
+/// One page of sessions; `limit` and `offset` page through the sorted list
 #[tauri::command]
 pub fn get_sessions(
+    config: State<'_, RwLock<Config>>,
     db: State<'_, SessionDatabase>,
     filter: SessionFilterParams,
 ) -> Result<Vec<SessionSummary>, String> {
-    let filter = SessionFilter {
-        search_query: filter.search,
-        has_audio: filter.has_audio,
-        has_midi: filter.has_midi,
-        has_video: filter.has_video,
-        has_notes: filter.has_notes,
-        has_title: filter.has_title,
-        limit: filter.limit,
-        offset: filter.offset,
-        ..Default::default()
-    };
-    
-    db.query_sessions(&filter)
+    let mut sessions = db.query_sessions(&filter.into())
+        .map_err(|e| e.to_string())?;
+    mark_offline_sessions(&config.read(), &mut sessions);
+    Ok(sessions)
+}
+
+/// Flag indexed sessions whose storage root is unreachable so the browser
+/// shows them as placeholders instead of missing. Only checks the folders
+/// when some configured root is actually offline.
+fn mark_offline_sessions(config: &Config, sessions: &mut [SessionSummary]) {
+    if config.storage_roots().iter().all(|root| root.exists()) {
+        return;
+    }
+    for session in sessions {
+        session.offline = config.session_offline(&session.id);
+    }
+}
+
+/// Number of sessions matching a filter, for sizing the virtualized list
+#[tauri::command]
+pub fn count_sessions(
+    db: State<'_, SessionDatabase>,
+    filter: SessionFilterParams,
+) -> Result<usize, String> {
+    db.count_sessions(&filter.into())
         .map_err(|e| e.to_string())
 }
 
@@ -172,7 +424,7 @@ pub fn get_session_detail(
     let config = config.read();
 
     // Session ID equals folder name, so construct path directly (O(1) instead of O(n))
-    let session_path = config.storage_path.join(&session_id);
+    let session_path = config.session_path(&session_id);
 
     if !session_path.exists() {
         return Ok(None);
@@ -247,12 +499,98 @@ pub fn get_session_detail(
             device_name: String::new(),
             event_count: 0,
             needs_repair: true,
+            programs: Vec::new(),
+            start_offset_secs: 0.0,
         });
     }
 
     Ok(Some(metadata))
 }
 
+/// Repeated practice attempts detected in a session's MIDI, for jumping
+/// between takes of the same passage
+#[tauri::command]
+pub async fn get_session_segments(
+    config: State<'_, RwLock<Config>>,
+    session_id: String,
+) -> Result<Vec<crate::similarity::segments::PracticeSegment>, String> {
+    let session_path = config.read().session_path(&session_id);
+    if !session_path.exists() {
+        return Err(format!("Session folder not found: {}", session_id));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let midi_paths: Vec<std::path::PathBuf> = std::fs::read_dir(&session_path)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("mid"))
+            .collect();
+        let refs: Vec<&std::path::Path> = midi_paths.iter().map(|p| p.as_path()).collect();
+        Ok(crate::similarity::segments::detect_practice_segments(&refs))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Velocity histogram and heat map from the session's MIDI and a loudness
+/// curve from its first audio file
+#[tauri::command]
+pub async fn get_session_dynamics(
+    config: State<'_, RwLock<Config>>,
+    session_id: String,
+) -> Result<crate::similarity::dynamics::SessionDynamics, String> {
+    let session_path = config.read().session_path(&session_id);
+    if !session_path.exists() {
+        return Err(format!("Session folder not found: {}", session_id));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let metadata = crate::session::build_session_from_directory(&session_path)
+            .map_err(|e| e.to_string())?;
+        let midi_paths: Vec<std::path::PathBuf> = metadata.midi_files.iter()
+            .map(|m| session_path.join(&m.filename))
+            .collect();
+        let refs: Vec<&std::path::Path> = midi_paths.iter().map(|p| p.as_path()).collect();
+        let audio_path = metadata.audio_files.first().map(|a| session_path.join(&a.filename));
+        Ok(crate::similarity::dynamics::analyze(&refs, audio_path.as_deref(), metadata.duration_secs))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Align the session's MIDI to a reference score and report per-measure
+/// accuracy, timing deviation and missed notes
+#[tauri::command]
+pub async fn score_against_reference(
+    config: State<'_, RwLock<Config>>,
+    session_id: String,
+    reference_midi_path: String,
+) -> Result<crate::similarity::accuracy::AccuracyReport, String> {
+    let session_path = config.read().session_path(&session_id);
+    if !session_path.exists() {
+        return Err(format!("Session folder not found: {}", session_id));
+    }
+    let reference = std::path::PathBuf::from(reference_midi_path);
+    if !reference.is_file() {
+        return Err(format!("Reference file not found: {}", reference.display()));
+    }
+
+    tokio::task::spawn_blocking(move || {
+        let midi_paths: Vec<std::path::PathBuf> = std::fs::read_dir(&session_path)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("mid"))
+            .collect();
+        if midi_paths.is_empty() {
+            return Err("This session has no MIDI recording".to_string());
+        }
+        let refs: Vec<&std::path::Path> = midi_paths.iter().map(|p| p.as_path()).collect();
+        crate::similarity::accuracy::score_against_reference(&refs, &reference).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 pub fn repair_session(
     config: State<'_, RwLock<Config>>,
@@ -260,10 +598,10 @@ pub fn repair_session(
     session_id: String,
 ) -> Result<SessionMetadata, String> {
     let config = config.read();
-    let session_path = config.storage_path.join(&session_id);
+    let session_path = config.session_path(&session_id);
 
     if !session_path.exists() {
-        return Err(format!("Session folder not found: {}", session_id));
+        return Err(crate::i18n::tf(crate::i18n::Text::SessionFolderNotFound, &[&session_id]));
     }
 
     // Guard: block repair if a fresh remote recording lock exists
@@ -275,7 +613,7 @@ pub fn repair_session(
             if let Ok(updated) = chrono::DateTime::parse_from_rfc3339(&lock.updated_at) {
                 let age = chrono::Utc::now() - updated.with_timezone(&chrono::Utc);
                 if age < chrono::Duration::hours(1) {
-                    return Err("Cannot repair: recording may be in progress on another device.".into());
+                    return Err(crate::i18n::t(crate::i18n::Text::RepairBlockedRemote).into());
                 }
             }
         }
@@ -357,6 +695,14 @@ pub fn repair_session(
         println!("[Sacho] Failed to update DB after repair: {}", e);
     }
 
+    // The checkpoint's video offsets outlive it once the files are whole
+    if crate::session::read_checkpoint(&session_path).is_some() {
+        if let Err(e) = crate::session::write_video_offsets(&session_path, &metadata.video_files) {
+            println!("[Sacho] Failed to save video offsets from checkpoint: {}", e);
+        }
+        crate::session::remove_checkpoint(&session_path);
+    }
+
     // Remove stale lock file after successful repair
     crate::session::remove_recording_lock(&session_path);
 
@@ -366,8 +712,129 @@ pub fn repair_session(
     Ok(metadata)
 }
 
+/// Rebuild a session from its media files alone, for a folder restored from
+/// a backup that kept the recordings but lost everything else. Durations are
+/// probed, devices inferred from file names and video stream details read
+/// from the files; the session is then reindexed and its similarity features
+/// recomputed. The folder must sit in one of the storage folders.
+#[tauri::command]
+pub async fn rebuild_session_metadata(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<SessionMetadata, String> {
+    tokio::task::spawn_blocking(move || {
+        let session_path = std::path::PathBuf::from(&path);
+        if !session_path.is_dir() {
+            return Err(format!("Folder not found: {}", path));
+        }
+        let session_id = session_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| format!("Invalid session folder: {}", path))?
+            .to_string();
+        let in_library = app
+            .state::<RwLock<Config>>()
+            .read()
+            .storage_roots()
+            .iter()
+            .any(|root| session_path.parent() == Some(root.as_path()));
+        if !in_library {
+            return Err("Move the folder into a storage folder before rebuilding it".to_string());
+        }
+        if crate::session::has_recording_lock(&session_path) {
+            return Err("Can't rebuild a session that is being recorded".to_string());
+        }
+
+        let mut metadata = crate::session::build_session_from_directory(&session_path)
+            .map_err(|e| e.to_string())?;
+        if metadata.audio_files.is_empty() && metadata.midi_files.is_empty() && metadata.video_files.is_empty() {
+            return Err("The folder contains no recordings".to_string());
+        }
+
+        // Stream details that weren't saved (or were lost) come from the files
+        if let Ok(discoverer) = crate::session::get_or_create_discoverer() {
+            for video in metadata.video_files.iter_mut().filter(|v| v.stream.is_none()) {
+                match crate::session::probe_video_stream(&session_path.join(&video.filename), &discoverer) {
+                    Ok(stream) => video.stream = Some(stream),
+                    Err(e) => println!("[Sacho] Couldn't probe {}: {}", video.filename, e),
+                }
+            }
+        }
+        if let Err(e) = crate::session::write_video_streams(&session_path, &metadata.video_files) {
+            println!("[Sacho] Failed to save video streams: {}", e);
+        }
+        metadata.schema_version = crate::session::metadata::migrate_session(&session_path, true);
+
+        let warnings = crate::session::validation::validate_timeline(&metadata);
+        if let Err(e) = crate::session::write_timeline_warnings(&session_path, &warnings) {
+            println!("[Sacho] Failed to save timeline warnings: {}", e);
+        }
+        metadata.timeline_warnings = warnings;
+
+        if let Err(e) = app.state::<SessionDatabase>().upsert_session(&metadata) {
+            println!("[Sacho] Failed to index rebuilt session {}: {}", session_id, e);
+        }
+        compute_and_cache_session_features(&app, &session_id, &session_path);
+
+        println!("[Sacho] Rebuilt session {}: {} MIDI, {} audio, {} video files",
+            session_id, metadata.midi_files.len(), metadata.audio_files.len(), metadata.video_files.len());
+        Ok(metadata)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Swap one damaged file in a session for a copy from a backup. The copy is
+/// checked against the session first (see `session::replace`); afterwards the
+/// session is rescanned, its timeline rechecked and reindexed.
+#[tauri::command]
+pub async fn replace_session_file(
+    app: tauri::AppHandle,
+    session_id: String,
+    filename: String,
+    source_path: String,
+) -> Result<SessionMetadata, String> {
+    tokio::task::spawn_blocking(move || {
+        let session_path = app.state::<RwLock<Config>>().read().session_path(&session_id);
+        if !session_path.exists() {
+            return Err(crate::i18n::tf(crate::i18n::Text::SessionFolderNotFound, &[&session_id]));
+        }
+        if crate::session::has_recording_lock(&session_path) {
+            return Err("Can't replace files while the session is being recorded".to_string());
+        }
+        if crate::session::archive::is_archived(&session_path) {
+            return Err("Restore the archived session before replacing its files".to_string());
+        }
+
+        let session = crate::session::build_session_from_directory(&session_path)
+            .map_err(|e| e.to_string())?;
+        release_cached_videos(&app, &session_path);
+        crate::session::replace::replace_file(&session, &filename, std::path::Path::new(&source_path))
+            .map_err(|e| e.to_string())?;
+        crate::video::proxy::remove_orphan_proxies(&session_path);
+
+        let mut metadata = crate::session::build_session_from_directory(&session_path)
+            .map_err(|e| e.to_string())?;
+        let warnings = crate::session::validation::validate_timeline(&metadata);
+        if let Err(e) = crate::session::write_timeline_warnings(&session_path, &warnings) {
+            println!("[Sacho] Failed to save timeline warnings: {}", e);
+        }
+        metadata.timeline_warnings = warnings;
+        if let Err(e) = app.state::<SessionDatabase>().upsert_session(&metadata) {
+            println!("[Sacho] Failed to update DB after replacing {}: {}", filename, e);
+        }
+        if filename.ends_with(".mid") {
+            compute_and_cache_session_features(&app, &session_id, &session_path);
+        }
+        Ok(metadata)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 pub fn delete_session(
+    app: tauri::AppHandle,
     db: State<'_, SessionDatabase>,
     config: State<'_, RwLock<Config>>,
     recording_cache: State<'_, Arc<RecordingSimilarityCache>>,
@@ -375,22 +842,50 @@ pub fn delete_session(
 ) -> Result<(), String> {
     let config = config.read();
 
+    // A session on an unplugged drive isn't gone; deleting would only drop it
+    // from the index until the next rescan
+    if config.session_offline(&session_id) {
+        return Err("This session is on a drive that is offline. Reconnect it to delete the session.".to_string());
+    }
+
     // Remove from database first (if this fails, filesystem stays intact)
     db.delete_session(&session_id)
         .map_err(|e| e.to_string())?;
 
     // Remove from recording similarity cache
-    recording_cache.remove(&session_id);
+    if recording_cache.remove(&session_id) {
+        emit_similarity_map_changed(&app, &recording_cache);
+    }
 
     // Session ID equals folder name, so construct path directly (O(1) instead of O(n))
-    let session_path = config.storage_path.join(&session_id);
+    let session_path = config.session_path(&session_id);
     if session_path.exists() {
+        release_cached_videos(&app, &session_path);
         std::fs::remove_dir_all(&session_path).map_err(|e| e.to_string())?;
     }
 
     Ok(())
 }
 
+/// Save a session's named loop regions (replaces the existing list)
+#[tauri::command]
+pub fn update_session_loops(
+    config: State<'_, RwLock<Config>>,
+    session_id: String,
+    loops: Vec<crate::session::LoopRegion>,
+) -> Result<(), String> {
+    if let Some(bad) = loops.iter().find(|l| {
+        !l.start_secs.is_finite() || !l.end_secs.is_finite() || l.start_secs < 0.0 || l.end_secs <= l.start_secs
+    }) {
+        return Err(format!("Invalid loop region \"{}\"", bad.name));
+    }
+    let session_path = config.read().session_path(&session_id);
+    if !session_path.exists() {
+        return Err(format!("Session folder not found: {}", session_id));
+    }
+    crate::session::write_session_loops(&session_path, &loops).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn update_session_notes(
     db: State<'_, SessionDatabase>,
@@ -400,7 +895,7 @@ pub fn update_session_notes(
 ) -> Result<(), String> {
     // Write notes.txt to the session folder (or delete if empty)
     let config = config.read();
-    let notes_path = config.storage_path.join(&session_id).join("notes.txt");
+    let notes_path = config.session_path(&session_id).join("notes.txt");
 
     if notes.is_empty() {
         // Delete notes.txt if notes are empty
@@ -431,6 +926,72 @@ pub fn update_session_notes(
     Ok(())
 }
 
+/// Mark or unmark a session as a favorite
+#[tauri::command]
+pub fn set_session_favorite(
+    db: State<'_, SessionDatabase>,
+    config: State<'_, RwLock<Config>>,
+    session_id: String,
+    favorite: bool,
+) -> Result<(), String> {
+    let session_path = config.read().session_path(&session_id);
+    if !session_path.exists() {
+        return Err(format!("Session folder not found: {}", session_id));
+    }
+    crate::session::set_favorite(&session_path, favorite).map_err(|e| e.to_string())?;
+    db.set_session_favorite(&session_id, favorite).map_err(|e| e.to_string())
+}
+
+/// Replace a session's tags. Returns the tags as saved (trimmed, deduplicated).
+#[tauri::command]
+pub fn set_session_tags(
+    db: State<'_, SessionDatabase>,
+    config: State<'_, RwLock<Config>>,
+    session_id: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let session_path = config.read().session_path(&session_id);
+    if !session_path.exists() {
+        return Err(format!("Session folder not found: {}", session_id));
+    }
+    let tags = crate::session::write_session_tags(&session_path, &tags).map_err(|e| e.to_string())?;
+    db.set_session_tags(&session_id, &tags).map_err(|e| e.to_string())?;
+    Ok(tags)
+}
+
+/// Replace a session's custom fields. Returns the fields as saved (trimmed,
+/// empty ones dropped).
+#[tauri::command]
+pub fn set_session_fields(
+    db: State<'_, SessionDatabase>,
+    config: State<'_, RwLock<Config>>,
+    session_id: String,
+    fields: crate::session::CustomFields,
+) -> Result<crate::session::CustomFields, String> {
+    let session_path = config.read().session_path(&session_id);
+    if !session_path.exists() {
+        return Err(format!("Session folder not found: {}", session_id));
+    }
+    let fields = crate::session::write_custom_fields(&session_path, &fields).map_err(|e| e.to_string())?;
+    db.set_session_fields(&session_id, &fields).map_err(|e| e.to_string())?;
+    Ok(fields)
+}
+
+/// Custom field names used across the library
+#[tauri::command]
+pub fn get_custom_field_keys(db: State<'_, SessionDatabase>) -> Result<Vec<String>, String> {
+    db.custom_field_keys().map_err(|e| e.to_string())
+}
+
+/// Values a custom field has across the library, for filtering by it
+#[tauri::command]
+pub fn get_custom_field_values(
+    db: State<'_, SessionDatabase>,
+    key: String,
+) -> Result<Vec<String>, String> {
+    db.custom_field_values(&key).map_err(|e| e.to_string())
+}
+
 /// Sanitize a title for use in folder names.
 /// Strips characters invalid on Windows/Mac/Linux filesystems.
 fn sanitize_title(title: &str) -> String {
@@ -444,6 +1005,7 @@ fn sanitize_title(title: &str) -> String {
 
 #[tauri::command]
 pub fn rename_session(
+    app: tauri::AppHandle,
     db: State<'_, SessionDatabase>,
     config: State<'_, RwLock<Config>>,
     recording_cache: State<'_, Arc<RecordingSimilarityCache>>,
@@ -451,7 +1013,7 @@ pub fn rename_session(
     new_title: String,
 ) -> Result<SessionSummary, String> {
     let config = config.read();
-    let old_path = config.storage_path.join(&session_id);
+    let old_path = config.session_path(&session_id);
     if !old_path.exists() {
         return Err("Session folder not found".to_string());
     }
@@ -471,41 +1033,349 @@ pub fn rename_session(
 
     if new_folder_name == session_id {
         // No change needed - query from DB and return current data
-        let filter = SessionFilter { search_query: None, ..Default::default() };
-        let sessions = db.query_sessions(&filter).map_err(|e| e.to_string())?;
-        return sessions.into_iter()
-            .find(|s| s.id == session_id)
+        return db.get_session_summary(&session_id)
+            .map_err(|e| e.to_string())?
             .ok_or_else(|| "Session not found in database".to_string());
     }
 
-    let new_path = config.storage_path.join(&new_folder_name);
-    if new_path.exists() {
-        return Err("A session with this name already exists".to_string());
-    }
+    // Stay in the same storage root; folder names must be unique across all roots
+    let new_path = old_path.with_file_name(&new_folder_name);
+    if new_path.exists() || config.session_path(&new_folder_name).exists() {
+        return Err("A session with this name already exists".to_string());
+    }
+
+    // Rename the folder on disk
+    release_cached_videos(&app, &old_path);
+    std::fs::rename(&old_path, &new_path).map_err(|e| e.to_string())?;
+
+    // Update DB: rename (ID changed, also updates session_features)
+    db.rename_session(&session_id, &new_folder_name, &new_path.to_string_lossy())
+        .map_err(|e| e.to_string())?;
+
+    // Update recording similarity cache
+    let new_title_opt = crate::session::extract_title_from_folder_name(&new_folder_name);
+    if recording_cache.rename(&session_id, &new_folder_name, new_title_opt) {
+        emit_similarity_map_changed(&app, &recording_cache);
+    }
+    app.state::<crate::share::ShareState>().session_moved(&app, &session_id, &new_folder_name, &new_path);
+
+    // Return new summary by querying DB
+    db.get_session_summary(&new_folder_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Session not found after rename".to_string())
+}
+
+/// Move a session folder to another configured storage root.
+///
+/// Uses a plain rename when both roots are on the same volume; otherwise the
+/// folder is copied to a hidden staging folder in the target root, verified by
+/// size, and only then swapped in and the original removed. Session IDs are
+/// folder names, so similarity features and the map position are preserved.
+#[tauri::command]
+pub async fn move_session(
+    app: tauri::AppHandle,
+    session_id: String,
+    target_root: String,
+) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let config = app.state::<RwLock<Config>>().read().clone();
+        let target_root = std::path::PathBuf::from(target_root);
+        if !config.storage_roots().contains(&target_root) {
+            return Err("Target is not a configured storage location".to_string());
+        }
+
+        let source = config.session_path(&session_id);
+        if !source.exists() {
+            return Err(format!("Session folder not found: {}", session_id));
+        }
+        if source.parent() == Some(target_root.as_path()) {
+            return Ok(());
+        }
+
+        {
+            let state = app.state::<RwLock<RecordingState>>();
+            let state = state.read();
+            if state.current_session_path.as_ref() == Some(&source) {
+                return Err("Cannot move a session while it is being recorded".to_string());
+            }
+        }
+
+        let destination = target_root.join(&session_id);
+        if destination.exists() {
+            return Err("A session with this name already exists in the target location".to_string());
+        }
+        std::fs::create_dir_all(&target_root).map_err(|e| e.to_string())?;
+
+        release_cached_videos(&app, &source);
+        if std::fs::rename(&source, &destination).is_err() {
+            // Different volumes: copy, verify, then remove the original
+            let staging = target_root.join(format!(".{}.moving", session_id));
+            let _ = std::fs::remove_dir_all(&staging);
+            let copy_result = copy_dir_recursive(&source, &staging).and_then(|copied| {
+                let expected = dir_size_recursive(&source);
+                if copied != expected {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("copied {} of {} bytes", copied, expected),
+                    ));
+                }
+                std::fs::rename(&staging, &destination)
+            });
+            if let Err(e) = copy_result {
+                let _ = std::fs::remove_dir_all(&staging);
+                return Err(format!("Failed to move session: {}", e));
+            }
+            if let Err(e) = std::fs::remove_dir_all(&source) {
+                println!("[Sacho] Moved session {} but failed to remove original: {}", session_id, e);
+            }
+        }
+
+        let db = app.state::<SessionDatabase>();
+        db.update_session_path(&session_id, &destination.to_string_lossy())
+            .map_err(|e| e.to_string())?;
+        app.state::<crate::share::ShareState>().session_moved(&app, &session_id, &session_id, &destination);
+
+        println!("[Sacho] Moved session {} to {}", session_id, target_root.display());
+        Ok(())
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Archive a session: replace its video with low-resolution proxies and move
+/// the originals to the configured archive folder, or delete them when
+/// `delete_originals` is set. Returns the number of bytes reclaimed.
+#[tauri::command]
+pub async fn archive_session(
+    app: tauri::AppHandle,
+    session_id: String,
+    delete_originals: bool,
+) -> Result<u64, String> {
+    tokio::task::spawn_blocking(move || {
+        let config = app.state::<RwLock<Config>>().read().clone();
+        let archive_root = if delete_originals {
+            None
+        } else {
+            Some(config.archive_path.clone()
+                .ok_or_else(|| "No archive location is configured".to_string())?)
+        };
+
+        let session_path = config.session_path(&session_id);
+        if !session_path.exists() {
+            return Err(format!("Session folder not found: {}", session_id));
+        }
+        if crate::session::has_recording_lock(&session_path) {
+            return Err("Cannot archive a session that is still recording".to_string());
+        }
+
+        release_cached_videos(&app, &session_path);
+        let manifest = crate::session::archive::archive_session(
+            &session_path,
+            archive_root.as_deref(),
+            crate::video::proxy::DEFAULT_PROXY_HEIGHT,
+        ).map_err(|e| e.to_string())?;
+
+        let db = app.state::<SessionDatabase>();
+        db.set_session_archived(&session_id, true).map_err(|e| e.to_string())?;
+
+        Ok(manifest.reclaimed_bytes(&session_path))
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Restore an archived session's original video from the archive folder
+#[tauri::command]
+pub async fn restore_archived_session(
+    app: tauri::AppHandle,
+    session_id: String,
+) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let session_path = app.state::<RwLock<Config>>().read().session_path(&session_id);
+        if !session_path.exists() {
+            return Err(format!("Session folder not found: {}", session_id));
+        }
+
+        crate::session::archive::restore_session(&session_path).map_err(|e| e.to_string())?;
+
+        let db = app.state::<SessionDatabase>();
+        db.set_session_archived(&session_id, false).map_err(|e| e.to_string())
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Find MJPEG recordings across all storage roots and queue them for
+/// re-encoding to `target` in idle time. With `delete_originals` off, each
+/// original is kept next to its re-encode. Returns the updated queue status.
+#[tauri::command]
+pub async fn queue_mjpeg_transcodes(
+    app: tauri::AppHandle,
+    target: crate::video::transcode::TranscodeTarget,
+    delete_originals: bool,
+) -> Result<crate::video::transcode::TranscodeStatus, String> {
+    tokio::task::spawn_blocking(move || {
+        let roots = app.state::<RwLock<Config>>().read().storage_roots();
+        let jobs = crate::video::transcode::find_mjpeg_recordings(&roots);
+        println!("[Sacho] Queued {} MJPEG recordings for re-encoding", jobs.len());
+
+        let queue = app.state::<crate::video::transcode::TranscodeQueue>();
+        queue.enqueue(jobs, target, delete_originals);
+        Ok(queue.status())
+    }).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub fn get_transcode_status(
+    queue: State<'_, crate::video::transcode::TranscodeQueue>,
+) -> crate::video::transcode::TranscodeStatus {
+    queue.status()
+}
+
+/// Pause or resume the re-encode queue. A file in progress is paused in
+/// place and continues where it left off.
+#[tauri::command]
+pub fn set_transcode_paused(
+    queue: State<'_, crate::video::transcode::TranscodeQueue>,
+    paused: bool,
+) -> crate::video::transcode::TranscodeStatus {
+    queue.set_paused(paused);
+    queue.status()
+}
+
+/// Empty the re-encode queue and abandon the file in progress (its original
+/// is left untouched)
+#[tauri::command]
+pub fn clear_transcode_queue(
+    queue: State<'_, crate::video::transcode::TranscodeQueue>,
+) -> crate::video::transcode::TranscodeStatus {
+    queue.clear();
+    queue.status()
+}
+
+/// Write a standalone HTML report into the session folder (for sharing with
+/// someone who doesn't have Sacho). Returns the report's path.
+#[tauri::command]
+pub async fn export_session_report(
+    app: tauri::AppHandle,
+    session_id: String,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let session_path = app.state::<RwLock<Config>>().read().session_path(&session_id);
+        if !session_path.exists() {
+            return Err(format!("Session folder not found: {}", session_id));
+        }
+        let metadata = crate::session::build_session_from_directory(&session_path)
+            .map_err(|e| e.to_string())?;
+        crate::session::report::write_session_report(&metadata)
+            .map(|path| path.to_string_lossy().to_string())
+            .map_err(|e| e.to_string())
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Create a tokenized LAN link to a read-only page for a session
+#[tauri::command]
+pub fn create_share_link(
+    app: tauri::AppHandle,
+    share: State<'_, crate::share::ShareState>,
+    session_id: String,
+) -> Result<crate::share::ShareLinkInfo, String> {
+    share.create_link(&app, &session_id)
+}
+
+#[tauri::command]
+pub fn revoke_share_link(
+    share: State<'_, crate::share::ShareState>,
+    token: String,
+) -> Result<(), String> {
+    if share.revoke_link(&token) {
+        Ok(())
+    } else {
+        Err("Share link not found".to_string())
+    }
+}
+
+#[tauri::command]
+pub fn list_share_links(share: State<'_, crate::share::ShareState>) -> Vec<crate::share::ShareLinkInfo> {
+    share.list_links()
+}
 
-    // Rename the folder on disk
-    std::fs::rename(&old_path, &new_path).map_err(|e| e.to_string())?;
+/// Show a pairing code for the companion phone app
+#[tauri::command]
+pub fn start_mobile_pairing(
+    remote: State<'_, crate::remote::mobile::MobileRemoteState>,
+) -> Result<crate::remote::mobile::PairingInfo, String> {
+    remote.start_pairing()
+}
 
-    // Update DB: rename (ID changed, also updates session_features)
-    db.rename_session(&session_id, &new_folder_name, &new_path.to_string_lossy())
-        .map_err(|e| e.to_string())?;
+#[tauri::command]
+pub fn cancel_mobile_pairing(remote: State<'_, crate::remote::mobile::MobileRemoteState>) {
+    remote.cancel_pairing();
+}
 
-    // Update recording similarity cache
-    let new_title_opt = crate::session::extract_title_from_folder_name(&new_folder_name);
-    recording_cache.rename(&session_id, &new_folder_name, new_title_opt);
+#[tauri::command]
+pub fn get_paired_devices(
+    remote: State<'_, crate::remote::mobile::MobileRemoteState>,
+) -> Vec<crate::remote::mobile::PairedDeviceInfo> {
+    remote.paired_devices()
+}
 
-    // Return new summary by querying DB
-    let filter = SessionFilter { search_query: None, ..Default::default() };
-    let sessions = db.query_sessions(&filter).map_err(|e| e.to_string())?;
-    sessions.into_iter()
-        .find(|s| s.id == new_folder_name)
-        .ok_or_else(|| "Session not found after rename".to_string())
+#[tauri::command]
+pub fn unpair_device(
+    remote: State<'_, crate::remote::mobile::MobileRemoteState>,
+    device_id: String,
+) -> Result<(), String> {
+    remote.unpair(&device_id)
+}
+
+/// Close warm demuxers holding files in a session folder open, so the folder
+/// can be deleted or moved (open handles block this on Windows)
+fn release_cached_videos(app: &tauri::AppHandle, session_path: &std::path::Path) {
+    if let Some(cache) = app.try_state::<crate::video::stream::DemuxerCache>() {
+        cache.evict_dir(session_path);
+    }
+}
+
+/// Recursively copy a directory. Returns the number of file bytes copied.
+fn copy_dir_recursive(src: &std::path::Path, dst: &std::path::Path) -> std::io::Result<u64> {
+    std::fs::create_dir_all(dst)?;
+    let mut total = 0;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            total += copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            total += std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(total)
 }
 
 // ============================================================================
 // Config Commands
 // ============================================================================
 
+/// Example file name for a template ("audio", "midi" or "video"), or why
+/// the template is invalid
+#[tauri::command]
+pub fn preview_filename_template(kind: String, template: String) -> Result<String, String> {
+    use crate::session::filenames;
+
+    filenames::validate_template(&template)?;
+    let templates = crate::config::FilenameTemplates {
+        audio: template.clone(),
+        midi: template.clone(),
+        video: template,
+    };
+    let ctx = |codec, channels| filenames::FileNameContext {
+        device: "Grand piano",
+        codec,
+        channels,
+        take: 2,
+    };
+    match kind.as_str() {
+        "audio" => Ok(filenames::audio_filename(&templates, &ctx("flac", Some(2)), "flac")),
+        "midi" => Ok(filenames::midi_filename(&templates, &ctx("midi", None))),
+        "video" => Ok(filenames::video_filename(&templates, &ctx("mjpeg", None), "mkv")),
+        _ => Err(format!("Unknown file kind: {}", kind)),
+    }
+}
+
 #[tauri::command]
 pub fn get_config(
     config: State<'_, RwLock<Config>>
@@ -529,10 +1399,17 @@ pub fn update_config(
         let current = config.read();
 
         let midi = current.selected_midi_devices != new_config.selected_midi_devices
-            || current.trigger_midi_devices != new_config.trigger_midi_devices;
+            || current.trigger_midi_devices != new_config.trigger_midi_devices
+            || current.midi_feedback != new_config.midi_feedback
+            || current.midi_thru != new_config.midi_thru
+            || current.midi_access_modes != new_config.midi_access_modes;
 
         let audio = current.selected_audio_devices != new_config.selected_audio_devices
-            || current.trigger_audio_devices != new_config.trigger_audio_devices;
+            || current.trigger_audio_devices != new_config.trigger_audio_devices
+            || current.audio_processing != new_config.audio_processing
+            || current.suppress_triggers_during_playback != new_config.suppress_triggers_during_playback
+            || current.playback_monitor_device != new_config.playback_monitor_device
+            || current.playback_monitor_threshold != new_config.playback_monitor_threshold;
 
         // Check if video device configs changed in a way that requires pipeline restart
         let video_devices_changed = current.selected_video_devices != new_config.selected_video_devices;
@@ -541,10 +1418,14 @@ pub fn update_config(
         }) || new_config.video_device_configs.iter().any(|(k, _)| {
             !current.video_device_configs.contains_key(k)
         });
-        let video = video_devices_changed || video_configs_pipeline_changed;
+        let video = video_devices_changed
+            || video_configs_pipeline_changed
+            || current.virtual_camera != new_config.virtual_camera;
 
-        let preroll = current.pre_roll_secs != new_config.pre_roll_secs
-            || current.encode_during_preroll != new_config.encode_during_preroll;
+        let preroll = current.audio_midi_pre_roll_secs != new_config.audio_midi_pre_roll_secs
+            || current.video_pre_roll_secs != new_config.video_pre_roll_secs
+            || current.encode_during_preroll != new_config.encode_during_preroll
+            || current.prewarm_video_writer != new_config.prewarm_video_writer;
 
         // Preset-only change: device configs differ only by preset_level/effort_level (no pipeline restart needed)
         let preset_only = !video && current.video_device_configs.iter().any(|(k, v)| {
@@ -577,12 +1458,42 @@ pub fn update_config(
         crate::tray::update_tray_state(&app, crate::tray::TrayState::Initializing);
     }
 
+    let old_share_port = config.read().lan_share_port;
+
+    let language_changed = config.read().language != new_config.language;
+
+    let storage_watch_changed = {
+        let current = config.read();
+        current.storage_roots() != new_config.storage_roots()
+            || current.watch_storage != new_config.watch_storage
+    };
+
     // Update in memory
     {
         let mut config_write = config.write();
         *config_write = new_config.clone();
     }
 
+    if storage_watch_changed {
+        crate::session::watcher::apply_storage_watch_config(&app);
+    }
+
+    if language_changed {
+        crate::i18n::set_language(new_config.language);
+        crate::tray::update_language(&app);
+    }
+
+    if let Some(remote) = app.try_state::<crate::remote::mobile::MobileRemoteState>() {
+        remote.apply_config(&app);
+    }
+
+    // Turning sharing off (or moving it to another port) revokes every link
+    if !new_config.lan_share_enabled || new_config.lan_share_port != old_share_port {
+        if let Some(share) = app.try_state::<crate::share::ShareState>() {
+            share.stop();
+        }
+    }
+
     // Save to disk (best-effort — don't block pipeline restart on save failure)
     if let Err(e) = new_config.save(&app) {
         println!("[Sacho] Warning: Failed to save config to disk: {}. Pipeline restart will still proceed.", e);
@@ -736,6 +1647,208 @@ pub fn update_audio_trigger_thresholds(
     Ok(())
 }
 
+/// Listen to an audio input for a few seconds of room noise and suggest a
+/// trigger threshold above it. Triggers are held off while listening. With
+/// `apply`, the suggestion is saved and takes effect immediately.
+#[tauri::command]
+pub async fn calibrate_audio_trigger(
+    app: tauri::AppHandle,
+    config: State<'_, RwLock<Config>>,
+    recording_state: State<'_, RwLock<RecordingState>>,
+    monitor: State<'_, Arc<Mutex<MidiMonitor>>>,
+    device: String,
+    duration_secs: Option<u32>,
+    apply: bool,
+) -> Result<crate::recording::noise_floor::NoiseFloorReport, String> {
+    use crate::recording::noise_floor::{self, DEFAULT_CALIBRATION_SECS, MAX_CALIBRATION_SECS};
+
+    {
+        let mut state = recording_state.write();
+        if state.status != RecordingStatus::Idle {
+            return Err("Cannot calibrate while recording or reinitializing".to_string());
+        }
+        // Keep the room noise from starting a recording while listening
+        state.status = RecordingStatus::Initializing;
+    }
+    let _ = app.emit("recording-state-changed", "initializing");
+
+    let duration = std::time::Duration::from_secs(
+        duration_secs.unwrap_or(DEFAULT_CALIBRATION_SECS).clamp(1, MAX_CALIBRATION_SECS) as u64,
+    );
+    let device_name = device.clone();
+    let result = tokio::task::spawn_blocking(move || noise_floor::measure_noise_floor(&device_name, duration))
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r.map_err(|e| e.to_string()));
+
+    recording_state.write().status = RecordingStatus::Idle;
+    let _ = app.emit("recording-state-changed", "idle");
+
+    let mut report = result?;
+    if apply {
+        {
+            let mut config_write = config.write();
+            config_write.audio_trigger_thresholds.insert(device.clone(), report.suggested_threshold);
+            config_write.save(&app).map_err(|e| e.to_string())?;
+        }
+        let monitor = monitor.lock();
+        let mut state = monitor.capture_state.lock();
+        for trigger_state in state.audio_trigger_states.iter_mut() {
+            if trigger_state.device_name == device {
+                trigger_state.threshold = report.suggested_threshold;
+            }
+        }
+        report.applied = true;
+    }
+
+    Ok(report)
+}
+
+/// Recent trigger evaluations (note-ons and above-threshold audio windows)
+/// with their measured value and what was done with them, newest first
+#[tauri::command]
+pub fn get_trigger_history(limit: Option<usize>) -> Vec<crate::recording::trigger_log::TriggerEvaluation> {
+    crate::recording::trigger_log::history(limit)
+}
+
+#[tauri::command]
+pub fn clear_trigger_history() {
+    crate::recording::trigger_log::clear();
+}
+
+/// Called by the session player when playback starts and stops, so audio
+/// triggers can ignore the take coming back through the speakers
+#[tauri::command]
+pub fn set_playback_active(active: bool) {
+    crate::recording::playback_guard::set_app_playback(active);
+}
+
+/// Disarm all triggers for a while, finalizing any recording in progress.
+/// `release_cameras` also shuts the camera pipelines down until resumed
+/// (defaults to the `privacy_pause_stops_cameras` setting).
+#[tauri::command]
+pub fn start_privacy_pause(
+    app: tauri::AppHandle,
+    config: State<'_, RwLock<Config>>,
+    length: crate::privacy::PauseLength,
+    release_cameras: Option<bool>,
+) -> crate::privacy::PrivacyStatus {
+    let release_cameras = release_cameras.unwrap_or(config.read().privacy_pause_stops_cameras);
+    crate::privacy::pause(&app, length, release_cameras)
+}
+
+#[tauri::command]
+pub fn resume_from_privacy_pause(app: tauri::AppHandle) -> crate::privacy::PrivacyStatus {
+    crate::privacy::resume(&app)
+}
+
+/// The active privacy pause, if any, and the log of past pauses
+#[tauri::command]
+pub fn get_privacy_status(app: tauri::AppHandle) -> crate::privacy::PrivacyStatus {
+    crate::privacy::status(&app)
+}
+
+/// Measure per-device latency from a calibration recording (a clap, flash or
+/// MIDI-lit LED seen by every device). With `apply`, the measured residuals
+/// are added to the saved corrections used when recording starts.
+#[tauri::command]
+pub async fn calibrate_sync_offsets(
+    app: tauri::AppHandle,
+    config: State<'_, RwLock<Config>>,
+    session_id: String,
+    apply: bool,
+) -> Result<crate::recording::calibration::CalibrationReport, String> {
+    let session_path = config.read().session_path(&session_id);
+    if !session_path.exists() {
+        return Err(format!("Session folder not found: {}", session_id));
+    }
+
+    let mut report = tokio::task::spawn_blocking(move || {
+        let session = crate::session::build_session_from_directory(&session_path)?;
+        Ok::<_, anyhow::Error>(crate::recording::calibration::analyze_session(&session))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())?;
+
+    if report.reference.is_none() {
+        return Err("No clap or MIDI note found to calibrate against".to_string());
+    }
+
+    if apply {
+        let mut config_write = config.write();
+        for m in &report.measurements {
+            let Some(residual) = m.offset_ms else { continue };
+            let offset = config_write.sync_offset_ms(&m.device_name) + residual;
+            config_write.set_sync_offset_ms(&m.device_name, offset);
+        }
+        config_write.save(&app).map_err(|e| e.to_string())?;
+        report.applied = true;
+    }
+
+    Ok(report)
+}
+
+/// Measure an audio input's latency with a loopback test (a click from an
+/// output, or a note to a synth that feeds the input). Triggers are held off
+/// while it runs. With `apply`, the measured latency becomes the input's
+/// sync correction.
+#[tauri::command]
+pub async fn measure_device_latency(
+    app: tauri::AppHandle,
+    config: State<'_, RwLock<Config>>,
+    recording_state: State<'_, RwLock<RecordingState>>,
+    input_device: String,
+    source: crate::recording::latency::LatencySource,
+    apply: bool,
+) -> Result<crate::recording::latency::LatencyReport, String> {
+    {
+        let mut state = recording_state.write();
+        if state.status != RecordingStatus::Idle {
+            return Err("Cannot measure latency while recording or reinitializing".to_string());
+        }
+        // The test clicks would otherwise start a recording
+        state.status = RecordingStatus::Initializing;
+    }
+    let _ = app.emit("recording-state-changed", "initializing");
+
+    let device_name = input_device.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        crate::recording::latency::measure_latency(&device_name, source)
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|r| r.map_err(|e| e.to_string()));
+
+    recording_state.write().status = RecordingStatus::Idle;
+    let _ = app.emit("recording-state-changed", "idle");
+
+    let mut report = result?;
+    if apply {
+        let Some(offset) = report.suggested_offset_ms else {
+            return Err("The test event was never detected on the input".to_string());
+        };
+        let mut config_write = config.write();
+        config_write.set_sync_offset_ms(&input_device, offset);
+        config_write.save(&app).map_err(|e| e.to_string())?;
+        report.applied = true;
+    }
+    Ok(report)
+}
+
+/// Set one device's sync correction in milliseconds (0 removes it)
+#[tauri::command]
+pub fn set_device_sync_offset(
+    app: tauri::AppHandle,
+    config: State<'_, RwLock<Config>>,
+    device_name: String,
+    offset_ms: i32,
+) -> Result<(), String> {
+    let mut config_write = config.write();
+    config_write.set_sync_offset_ms(&device_name, offset_ms);
+    config_write.save(&app).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn restart_midi_monitor(
     monitor: State<'_, Arc<Mutex<MidiMonitor>>>,
@@ -744,6 +1857,23 @@ pub fn restart_midi_monitor(
     monitor.start().map_err(|e| e.to_string())
 }
 
+/// Stream decoded MIDI events from the monitored devices as `midi-tap-events`
+/// for the inspector panel. Calling again replaces the filter.
+#[tauri::command]
+pub fn start_midi_event_tap(
+    app: tauri::AppHandle,
+    filter: Option<crate::recording::midi_tap::MidiTapFilter>,
+) -> Result<(), String> {
+    crate::recording::midi_tap::start(app, filter.unwrap_or_default());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_midi_event_tap() -> Result<(), String> {
+    crate::recording::midi_tap::stop();
+    Ok(())
+}
+
 // ============================================================================
 // Device Health Commands
 // ============================================================================
@@ -755,6 +1885,19 @@ pub fn get_disconnected_devices(
     health.read().disconnected.values().cloned().collect()
 }
 
+#[tauri::command]
+pub fn get_devices_in_use(
+    health: State<'_, RwLock<crate::devices::health::DeviceHealthState>>,
+) -> Vec<crate::devices::contention::DeviceContentionInfo> {
+    health.read().in_use.values().cloned().collect()
+}
+
+/// Power source and battery charge, for the record-safe mode setting
+#[tauri::command]
+pub fn get_battery_status() -> crate::battery::BatteryStatus {
+    crate::battery::read_status()
+}
+
 #[tauri::command]
 pub fn restart_device_pipelines(
     device_types: Vec<String>,
@@ -827,6 +1970,24 @@ impl SimilarityCache {
     }
 }
 
+/// Cancellation flag shared by feature extraction jobs (MIDI imports and
+/// session feature sync). Each job clears it when it starts.
+pub struct FeatureExtractionControl {
+    cancel: std::sync::atomic::AtomicBool,
+}
+
+impl FeatureExtractionControl {
+    pub fn new() -> Self {
+        Self { cancel: std::sync::atomic::AtomicBool::new(false) }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct FeatureExtractionProgress {
+    pub current: usize,
+    pub total: usize,
+}
+
 /// Load features from DB into the cache. Called on startup and can be called
 /// from a background thread.
 pub fn warm_similarity_cache(db: &SessionDatabase, cache: &SimilarityCache) {
@@ -896,10 +2057,10 @@ pub async fn import_midi_folder(
     path: String,
     db: State<'_, SessionDatabase>,
     cache: State<'_, SimilarityCache>,
+    control: State<'_, FeatureExtractionControl>,
 ) -> Result<Vec<MidiImportInfo>, String> {
-    use crate::similarity::{midi_parser, features};
-    use rayon::prelude::*;
-    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::similarity::features;
+    use std::sync::atomic::Ordering;
     use std::path::Path;
 
     let folder = Path::new(&path);
@@ -915,58 +2076,64 @@ pub async fn import_midi_folder(
         return Err("No MIDI files found in folder".to_string());
     }
 
-    // Clear old imports
-    db.clear_midi_imports().map_err(|e| e.to_string())?;
-
+    control.cancel.store(false, Ordering::SeqCst);
     let now = chrono::Utc::now().to_rfc3339();
-    let total = midi_paths.len();
-    let counter = AtomicUsize::new(0);
 
     // Parse MIDI files and extract features, keeping both the serialized form
     // (for DB storage) and the deserialized form (for the in-memory cache).
-    let parsed: Vec<(crate::session::MidiImport, Option<ChunkedFileFeatures>)> = midi_paths.par_iter().map(|midi_path| {
-        let file_name = midi_path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown.mid")
-            .to_string();
-
-        let current = counter.fetch_add(1, Ordering::Relaxed) + 1;
-        let _ = app.emit("midi-import-progress", MidiImportProgress {
-            current,
-            total,
-            file_name: file_name.clone(),
-        });
-
-        let file_path_str = midi_path.to_string_lossy().to_string();
-        let id = format!("{:x}", md5_hash(&file_path_str));
+    let parsed: Vec<(crate::session::MidiImport, Option<ChunkedFileFeatures>)> = features::extract_parallel(
+        &midi_paths,
+        &control.cancel,
+        |midi_path| {
+            let file_name = midi_path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown.mid")
+                .to_string();
+
+            let file_path_str = midi_path.to_string_lossy().to_string();
+            let id = format!("{:x}", md5_hash(&file_path_str));
+
+            let chunked = match features::extract_file_features(midi_path) {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    log::warn!("Failed to parse MIDI {}: {}", file_name, e);
+                    None
+                }
+            };
 
-        let chunked = match midi_parser::parse_midi(midi_path) {
-            Ok(midi_parser::MidiParseResult { events, ticks_per_beat, tempo_map }) => {
-                Some(features::extract_chunked_features(&events, ticks_per_beat, &tempo_map))
-            }
-            Err(e) => {
-                log::warn!("Failed to parse MIDI {}: {}", file_name, e);
-                None
-            }
-        };
+            let has_features = chunked.as_ref().is_some_and(|c| {
+                c.chunks.iter().any(|ch| ch.melodic.is_some() || ch.harmonic.is_some())
+            });
+            let chunked_bin = chunked.as_ref().and_then(|c| bincode::serialize(c).ok());
+
+            let import = crate::session::MidiImport {
+                id,
+                folder_path: path.clone(),
+                file_name,
+                file_path: file_path_str,
+                chunked_features: chunked_bin,
+                has_features,
+                imported_at: now.clone(),
+            };
 
-        let has_features = chunked.as_ref().is_some_and(|c| {
-            c.chunks.iter().any(|ch| ch.melodic.is_some() || ch.harmonic.is_some())
-        });
-        let chunked_bin = chunked.as_ref().and_then(|c| bincode::serialize(c).ok());
-
-        let import = crate::session::MidiImport {
-            id,
-            folder_path: path.clone(),
-            file_name,
-            file_path: file_path_str,
-            chunked_features: chunked_bin,
-            has_features,
-            imported_at: now.clone(),
-        };
+            (import, chunked)
+        },
+        |midi_path, current, total| {
+            let file_name = midi_path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown.mid")
+                .to_string();
+            let _ = app.emit("midi-import-progress", MidiImportProgress {
+                current,
+                total,
+                file_name,
+            });
+        },
+    ).map_err(|e| e.to_string())?;
 
-        (import, chunked)
-    }).collect();
+    // Replace old imports only once extraction has completed (a cancelled
+    // import leaves the previous library untouched)
+    db.clear_midi_imports().map_err(|e| e.to_string())?;
 
     // Split into DB imports and cache entries
     let imports: Vec<crate::session::MidiImport> = parsed.iter().map(|(imp, _)| imp.clone()).collect();
@@ -1004,6 +2171,14 @@ pub async fn import_midi_folder(
     Ok(result)
 }
 
+/// Cancel any running feature extraction (MIDI import or session feature sync)
+#[tauri::command]
+pub fn cancel_feature_extraction(
+    control: State<'_, FeatureExtractionControl>,
+) {
+    control.cancel.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
 fn collect_midi_files(dir: &std::path::Path, out: &mut Vec<std::path::PathBuf>) {
     if let Ok(entries) = std::fs::read_dir(dir) {
         for entry in entries.flatten() {
@@ -1129,6 +2304,8 @@ pub fn clear_midi_imports(
 /// In-memory cache of deserialized recording features + metadata
 pub struct RecordingSimilarityCache {
     inner: Mutex<Option<RecordingSimilarityCacheData>>,
+    /// Incrementally maintained 2D layout for the similarity map
+    layout: Mutex<crate::similarity::reduction::SimilarityLayout>,
 }
 
 struct RecordingSimilarityCacheData {
@@ -1145,19 +2322,29 @@ struct RecordingCachedMeta {
 
 impl RecordingSimilarityCache {
     pub fn new() -> Self {
-        Self { inner: Mutex::new(None) }
+        Self {
+            inner: Mutex::new(None),
+            layout: Mutex::new(crate::similarity::reduction::SimilarityLayout::new()),
+        }
+    }
+
+    /// Current similarity map layout version
+    pub fn layout_version(&self) -> u64 {
+        self.layout.lock().version()
     }
 
-    /// Remove a session from the cache
-    pub fn remove(&self, session_id: &str) {
+    /// Remove a session from the cache. Returns true if the similarity map changed.
+    pub fn remove(&self, session_id: &str) -> bool {
         if let Some(data) = self.inner.lock().as_mut() {
             data.features.retain(|(id, _)| id != session_id);
             data.metadata.remove(session_id);
         }
+        self.layout.lock().remove(session_id)
     }
 
-    /// Rename a session in the cache (update its key)
-    pub fn rename(&self, old_id: &str, new_id: &str, new_title: Option<String>) {
+    /// Rename a session in the cache (update its key).
+    /// Returns true if the similarity map changed.
+    pub fn rename(&self, old_id: &str, new_id: &str, new_title: Option<String>) -> bool {
         if let Some(data) = self.inner.lock().as_mut() {
             // Update features key
             for (id, _) in &mut data.features {
@@ -1172,9 +2359,15 @@ impl RecordingSimilarityCache {
                 data.metadata.insert(new_id.to_string(), meta);
             }
         }
+        self.layout.lock().rename(old_id, new_id)
     }
 }
 
+/// Notify the frontend that the similarity map layout changed
+fn emit_similarity_map_changed(app: &tauri::AppHandle, cache: &RecordingSimilarityCache) {
+    let _ = app.emit("similarity-map-changed", cache.layout_version());
+}
+
 /// Load session features from DB into the recording cache
 pub fn warm_recording_similarity_cache(db: &SessionDatabase, cache: &RecordingSimilarityCache) {
     use std::time::Instant;
@@ -1188,11 +2381,9 @@ pub fn warm_recording_similarity_cache(db: &SessionDatabase, cache: &RecordingSi
         }
     };
 
-    // Also need session metadata (title, timestamp, duration)
-    let sessions = match db.query_sessions(&crate::session::SessionFilter {
-        has_midi: Some(true),
-        ..Default::default()
-    }) {
+    // Also need session metadata (title, timestamp, duration). Audio-only
+    // sessions have features too, so don't filter on has_midi.
+    let sessions = match db.query_sessions(&crate::session::SessionFilter::default()) {
         Ok(s) => s,
         Err(e) => {
             log::error!("Failed to load sessions for recording cache: {}", e);
@@ -1228,6 +2419,7 @@ pub fn warm_recording_similarity_cache(db: &SessionDatabase, cache: &RecordingSi
 
     let t2 = Instant::now();
     let count = features.len();
+    cache.layout.lock().rebuild(&features);
     *cache.inner.lock() = Some(RecordingSimilarityCacheData { features, metadata });
 
     eprintln!(
@@ -1240,7 +2432,7 @@ pub fn warm_recording_similarity_cache(db: &SessionDatabase, cache: &RecordingSi
 
 /// Sync session features at startup: compute features for sessions that need them
 pub fn sync_session_features(app: &tauri::AppHandle) -> Result<usize, String> {
-    use rayon::prelude::*;
+    use std::sync::atomic::Ordering;
     use std::time::Instant;
 
     let t0 = Instant::now();
@@ -1248,13 +2440,15 @@ pub fn sync_session_features(app: &tauri::AppHandle) -> Result<usize, String> {
     let db = app.state::<SessionDatabase>();
     let config = app.state::<RwLock<Config>>();
     let recording_cache = app.state::<Arc<RecordingSimilarityCache>>();
-    let storage_path = config.read().storage_path.clone();
+    let config = config.read().clone();
 
-    // Get all sessions with MIDI
-    let midi_sessions = db.query_sessions(&crate::session::SessionFilter {
-        has_midi: Some(true),
-        ..Default::default()
-    }).map_err(|e| e.to_string())?;
+    // Get all sessions with MIDI, plus audio-only sessions (audio features)
+    let feature_sessions: Vec<crate::session::SessionSummary> = db
+        .query_sessions(&crate::session::SessionFilter::default())
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|s| s.has_midi || s.has_audio)
+        .collect();
 
     // Get existing features
     let existing = db.get_all_session_features().map_err(|e| e.to_string())?;
@@ -1263,10 +2457,10 @@ pub fn sync_session_features(app: &tauri::AppHandle) -> Result<usize, String> {
 
     // Find sessions needing computation
     let mut to_compute: Vec<(&crate::session::SessionSummary, std::path::PathBuf)> = Vec::new();
-    let valid_ids: std::collections::HashSet<&str> = midi_sessions.iter().map(|s| s.id.as_str()).collect();
+    let valid_ids: std::collections::HashSet<&str> = feature_sessions.iter().map(|s| s.id.as_str()).collect();
 
-    for session in &midi_sessions {
-        let session_path = storage_path.join(&session.id);
+    for session in &feature_sessions {
+        let session_path = config.session_path(&session.id);
         if !session_path.exists() { continue; }
 
         // Count MIDI files in directory
@@ -1295,6 +2489,7 @@ pub fn sync_session_features(app: &tauri::AppHandle) -> Result<usize, String> {
         // Just warm the cache
         warm_recording_similarity_cache(&db, &recording_cache);
         let _ = app.emit("recording-features-synced", ());
+        emit_similarity_map_changed(app, &recording_cache);
         eprintln!("[sync_session_features] nothing to compute, warmed cache in {:.0}ms",
             t0.elapsed().as_secs_f64() * 1000.0);
         return Ok(0);
@@ -1304,9 +2499,16 @@ pub fn sync_session_features(app: &tauri::AppHandle) -> Result<usize, String> {
     eprintln!("[sync_session_features] computing features for {} sessions", computed_count);
 
     // Parallel feature extraction
-    let results: Vec<crate::session::SessionFeatureRow> = to_compute.par_iter().filter_map(|(session, session_path)| {
-        compute_session_feature_row(&session.id, session_path)
-    }).collect();
+    let control = app.state::<FeatureExtractionControl>();
+    control.cancel.store(false, Ordering::SeqCst);
+    let results: Vec<crate::session::SessionFeatureRow> = crate::similarity::features::extract_parallel(
+        &to_compute,
+        &control.cancel,
+        |(session, session_path)| compute_session_feature_row(&session.id, session_path),
+        |_, current, total| {
+            let _ = app.emit("session-features-progress", FeatureExtractionProgress { current, total });
+        },
+    ).map_err(|e| e.to_string())?.into_iter().flatten().collect();
 
     // Batch upsert to DB
     if let Err(e) = db.upsert_session_features_batch(&results) {
@@ -1316,6 +2518,7 @@ pub fn sync_session_features(app: &tauri::AppHandle) -> Result<usize, String> {
     // Warm the cache
     warm_recording_similarity_cache(&db, &recording_cache);
     let _ = app.emit("recording-features-synced", ());
+    emit_similarity_map_changed(app, &recording_cache);
 
     eprintln!("[sync_session_features] computed={} total={:.0}ms",
         computed_count, t0.elapsed().as_secs_f64() * 1000.0);
@@ -1344,31 +2547,28 @@ pub fn compute_and_cache_session_features(app: &tauri::AppHandle, session_id: &s
                 .and_then(|b| bincode::deserialize::<ChunkedFileFeatures>(b).ok())
             {
                 // Get session metadata for cache
-                if let Ok(sessions) = db.query_sessions(&crate::session::SessionFilter {
-                    search_query: None,
-                    ..Default::default()
-                }) {
-                    if let Some(session) = sessions.iter().find(|s| s.id == session_id) {
-                        let meta = RecordingCachedMeta {
-                            title: session.title.clone(),
-                            timestamp: session.timestamp.to_rfc3339(),
-                            duration_secs: session.duration_secs,
-                        };
-
-                        let mut guard = recording_cache.inner.lock();
-                        if let Some(data) = guard.as_mut() {
-                            // Remove old entry if present
-                            data.features.retain(|(id, _)| id != session_id);
-                            data.features.push((session_id.to_string(), chunked));
-                            data.metadata.insert(session_id.to_string(), meta);
-                        } else {
-                            let mut metadata = std::collections::HashMap::new();
-                            metadata.insert(session_id.to_string(), meta);
-                            *guard = Some(RecordingSimilarityCacheData {
-                                features: vec![(session_id.to_string(), chunked)],
-                                metadata,
-                            });
-                        }
+                if let Ok(Some(session)) = db.get_session_summary(session_id) {
+                    let meta = RecordingCachedMeta {
+                        title: session.title.clone(),
+                        timestamp: session.timestamp.to_rfc3339(),
+                        duration_secs: session.duration_secs,
+                    };
+
+                    recording_cache.layout.lock().insert(session_id, &chunked);
+
+                    let mut guard = recording_cache.inner.lock();
+                    if let Some(data) = guard.as_mut() {
+                        // Remove old entry if present
+                        data.features.retain(|(id, _)| id != session_id);
+                        data.features.push((session_id.to_string(), chunked));
+                        data.metadata.insert(session_id.to_string(), meta);
+                    } else {
+                        let mut metadata = std::collections::HashMap::new();
+                        metadata.insert(session_id.to_string(), meta);
+                        *guard = Some(RecordingSimilarityCacheData {
+                            features: vec![(session_id.to_string(), chunked)],
+                            metadata,
+                        });
                     }
                 }
             }
@@ -1379,26 +2579,26 @@ pub fn compute_and_cache_session_features(app: &tauri::AppHandle, session_id: &s
 
         // Emit event for frontend
         let _ = app.emit("session-features-computed", session_id);
+        emit_similarity_map_changed(app, &recording_cache);
     }
 }
 
-/// Compute a SessionFeatureRow for a single session directory
+/// Compute a SessionFeatureRow for a single session directory.
+/// Sessions without MIDI fall back to audio chroma features.
 fn compute_session_feature_row(session_id: &str, session_path: &std::path::Path) -> Option<crate::session::SessionFeatureRow> {
-    use crate::similarity::{midi_parser, features};
+    use crate::similarity::features;
 
     let midi_files = collect_session_midi_files(session_path);
     if midi_files.is_empty() {
-        return None;
+        return compute_session_audio_feature_row(session_id, session_path);
     }
 
     let midi_count = midi_files.len() as i32;
 
     // Parse and extract features from each MIDI file
     let per_file_features: Vec<ChunkedFileFeatures> = midi_files.iter().filter_map(|path| {
-        match midi_parser::parse_midi(path) {
-            Ok(midi_parser::MidiParseResult { events, ticks_per_beat, tempo_map }) => {
-                Some(features::extract_chunked_features(&events, ticks_per_beat, &tempo_map))
-            }
+        match features::extract_file_features(path) {
+            Ok(chunked) => Some(chunked),
             Err(e) => {
                 log::warn!("Failed to parse MIDI {}: {}", path.display(), e);
                 None
@@ -1427,6 +2627,54 @@ fn compute_session_feature_row(session_id: &str, session_path: &std::path::Path)
     })
 }
 
+/// Compute a SessionFeatureRow from a session's audio files (no MIDI recorded)
+fn compute_session_audio_feature_row(session_id: &str, session_path: &std::path::Path) -> Option<crate::session::SessionFeatureRow> {
+    use crate::similarity::{audio_features, features};
+
+    let audio_files = collect_session_audio_files(session_path);
+    if audio_files.is_empty() {
+        return None;
+    }
+
+    let per_file_features: Vec<ChunkedFileFeatures> = audio_files.iter().filter_map(|path| {
+        match audio_features::extract_audio_file_features(path) {
+            Ok(chunked) => Some(chunked),
+            Err(e) => {
+                log::warn!("Failed to analyze audio {}: {}", path.display(), e);
+                None
+            }
+        }
+    }).collect();
+
+    // Still record a row on failure so the session isn't re-analyzed on every startup
+    let averaged = features::average_chunked_features(&per_file_features);
+    let has_features = averaged.chunks.iter().any(|c| c.harmonic.is_some());
+
+    Some(crate::session::SessionFeatureRow {
+        session_id: session_id.to_string(),
+        chunked_features: bincode::serialize(&averaged).ok(),
+        has_features,
+        midi_file_count: 0,
+        computed_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+/// Collect audio_*.wav / audio_*.flac files from a session directory
+fn collect_session_audio_files(session_path: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(session_path) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                if name.starts_with("audio_") && (name.ends_with(".wav") || name.ends_with(".flac")) {
+                    files.push(path);
+                }
+            }
+        }
+    }
+    files
+}
+
 /// Collect midi_*.mid files from a session directory
 fn collect_session_midi_files(session_path: &std::path::Path) -> Vec<std::path::PathBuf> {
     let mut files = Vec::new();
@@ -1593,6 +2841,62 @@ pub async fn get_session_similar_preview(
     }).await.map_err(|e| e.to_string())?
 }
 
+/// 2D similarity map of recorded sessions
+#[derive(Debug, Serialize)]
+pub struct SimilarityMap {
+    /// Layout version; increases every time any point is added, moved, or removed
+    pub version: u64,
+    /// False when the caller's version is already current (points is then empty)
+    pub changed: bool,
+    pub points: Vec<crate::similarity::reduction::MapPoint>,
+}
+
+/// Get reduced 2D coordinates for all sessions with MIDI features.
+/// Pass the last version seen to skip the payload when nothing changed.
+#[tauri::command]
+pub fn get_similarity_map(
+    version: Option<u64>,
+    cache: State<'_, Arc<RecordingSimilarityCache>>,
+) -> SimilarityMap {
+    let layout = cache.layout.lock();
+    let current = layout.version();
+    if version == Some(current) {
+        return SimilarityMap { version: current, changed: false, points: Vec::new() };
+    }
+    SimilarityMap { version: current, changed: true, points: layout.points() }
+}
+
+/// Write the similarity map's feature vectors, coordinates and k-means
+/// clusters to a CSV file. `clusters` defaults to about sqrt(n / 2).
+/// Returns the number of sessions written.
+#[tauri::command]
+pub async fn export_similarity_data(
+    cache: State<'_, Arc<RecordingSimilarityCache>>,
+    path: String,
+    clusters: Option<usize>,
+) -> Result<usize, String> {
+    let cache_arc = cache.inner().clone();
+    tokio::task::spawn_blocking(move || {
+        use crate::similarity::export::{self, ExportSessionInfo};
+
+        let sessions: std::collections::HashMap<String, ExportSessionInfo> = cache_arc.inner.lock()
+            .as_ref()
+            .map(|data| data.metadata.iter()
+                .map(|(id, meta)| (id.clone(), ExportSessionInfo {
+                    title: meta.title.clone(),
+                    timestamp: meta.timestamp.clone(),
+                    duration_secs: meta.duration_secs,
+                }))
+                .collect())
+            .unwrap_or_default();
+        let layout = cache_arc.layout.lock();
+        let rows = export::write_csv(std::path::Path::new(&path), &layout, &sessions, clusters)
+            .map_err(|e| e.to_string())?;
+        println!("[Sacho] Exported similarity data for {} sessions to {}", rows, path);
+        Ok(rows)
+    }).await.map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 pub async fn reset_cache(
     app: tauri::AppHandle,
@@ -1602,17 +2906,36 @@ pub async fn reset_cache(
 ) -> Result<usize, String> {
     *cache.inner.lock() = None;
     *recording_cache.inner.lock() = None;
+    recording_cache.layout.lock().rebuild(&[]);
     db.clear_sessions().map_err(|e| e.to_string())?;
     let app_clone = app.clone();
-    let count = tokio::task::spawn_blocking(move || {
-        let result = rescan_sessions_blocking(&app_clone);
+    let report = tokio::task::spawn_blocking(move || {
+        let result = rescan_sessions_blocking(&app_clone, false);
         // Re-sync recording features after rescan
         if let Err(e) = sync_session_features(&app_clone) {
             log::error!("Failed to re-sync session features after cache reset: {}", e);
         }
         result
     }).await.map_err(|e| e.to_string())??;
-    Ok(count)
+    Ok(report.indexed_count())
+}
+
+/// Check the update feed now
+#[tauri::command]
+pub async fn check_for_updates(app: tauri::AppHandle) -> Result<Option<crate::updates::AvailableUpdate>, String> {
+    crate::updates::check(&app).await
+}
+
+/// Update found by the last check, if any
+#[tauri::command]
+pub fn get_available_update(state: State<'_, crate::updates::UpdateState>) -> Option<crate::updates::AvailableUpdate> {
+    state.available()
+}
+
+/// Download, verify and install the available update, then restart
+#[tauri::command]
+pub async fn install_update(app: tauri::AppHandle) -> Result<(), String> {
+    crate::updates::install(&app).await
 }
 
 #[tauri::command]
@@ -1636,21 +2959,98 @@ pub fn reset_settings(
     Ok(())
 }
 
+/// A session folder found on disk that isn't in the index yet
+#[derive(Debug, Clone, Serialize)]
+pub struct RescanNewSession {
+    pub id: String,
+    pub has_audio: bool,
+    pub has_midi: bool,
+    pub has_video: bool,
+}
+
+/// An indexed session found under a different folder name
+#[derive(Debug, Clone, Serialize)]
+pub struct RescanMovedSession {
+    pub from: String,
+    pub to: String,
+}
+
+/// An indexed session whose folder contents changed since it was indexed
+#[derive(Debug, Clone, Serialize)]
+pub struct RescanChangedSession {
+    pub id: String,
+    /// Human-readable changes, e.g. "audio added", "notes changed"
+    pub changes: Vec<String>,
+}
+
+/// Reconciliation between the session index and the storage folder
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RescanReport {
+    /// True if the report was computed without modifying the database
+    pub dry_run: bool,
+    /// Folders on disk that will be / were added to the index
+    pub new_sessions: Vec<RescanNewSession>,
+    /// Indexed sessions whose folder no longer exists (removed from the index)
+    pub missing_sessions: Vec<String>,
+    /// Indexed sessions whose folder was renamed or moved on disk, recognized
+    /// by their uid and kept under the new folder name
+    pub moved_sessions: Vec<RescanMovedSession>,
+    /// Indexed sessions on a storage root that couldn't be read (drive
+    /// unplugged, NAS offline). Kept in the index until the drive returns.
+    pub offline_sessions: Vec<String>,
+    /// Indexed sessions whose files or notes changed on disk
+    pub changed_sessions: Vec<RescanChangedSession>,
+    /// Sessions left with a stale recording lock (interrupted recording).
+    /// These are indexed as-is; `repair_session` fixes their file headers.
+    pub needs_repair: Vec<String>,
+    /// Folders skipped because they contain no recognizable media
+    pub skipped_folders: Vec<String>,
+}
+
+impl RescanReport {
+    /// Number of sessions added or updated in the index
+    pub fn indexed_count(&self) -> usize {
+        self.new_sessions.len() + self.changed_sessions.len()
+    }
+}
+
+/// Reconcile the session index with the storage folder.
+///
+/// With `dry_run`, the folder is scanned and the report returned without
+/// touching the database, so users syncing the storage folder from another
+/// machine can review what changed first.
 #[tauri::command]
 pub async fn rescan_sessions(
     app: tauri::AppHandle,
-) -> Result<usize, String> {
+    dry_run: Option<bool>,
+) -> Result<RescanReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
     tokio::task::spawn_blocking(move || {
-        let result = rescan_sessions_blocking(&app);
+        let result = rescan_sessions_blocking(&app, dry_run);
         // Re-sync recording features after rescan (new folder, changed files, etc.)
-        if let Err(e) = sync_session_features(&app) {
-            log::error!("Failed to sync session features after rescan: {}", e);
+        if !dry_run {
+            if let Err(e) = sync_session_features(&app) {
+                log::error!("Failed to sync session features after rescan: {}", e);
+            }
         }
         result
     }).await.map_err(|e| e.to_string())?
 }
 
-fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
+/// Whether an indexed session folder sits in a configured storage root that
+/// wasn't readable during this scan
+fn is_on_offline_root(
+    session_path: &str,
+    configured_roots: &[std::path::PathBuf],
+    scanned_roots: &[std::path::PathBuf],
+) -> bool {
+    let Some(parent) = std::path::Path::new(session_path).parent() else {
+        return false;
+    };
+    configured_roots.iter().any(|root| root == parent) && !scanned_roots.iter().any(|root| root == parent)
+}
+
+pub(crate) fn rescan_sessions_blocking(app: &tauri::AppHandle, dry_run: bool) -> Result<RescanReport, String> {
     use std::collections::{HashMap, HashSet};
     use crate::session::{SessionIndexData, UpdatedSessionData, ExistingSessionRow};
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -1660,12 +3060,21 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
 
     let config = app.state::<RwLock<Config>>();
     let db = app.state::<SessionDatabase>();
-    let storage_path = config.read().storage_path.clone();
+    let configured_roots = config.read().storage_roots();
+    let storage_roots: Vec<std::path::PathBuf> = configured_roots.iter()
+        .filter(|root| root.exists())
+        .cloned()
+        .collect();
+    let show_other_users = config.read().show_other_users_sessions;
 
-    if !storage_path.exists() {
-        return Ok(0);
+    if storage_roots.is_empty() {
+        return Ok(RescanReport { dry_run, ..Default::default() });
     }
 
+    // Roots whose listing succeeded; sessions indexed under any other
+    // configured root are on an offline drive and are kept, not deleted
+    let mut scanned_roots: Vec<std::path::PathBuf> = Vec::new();
+
     // 1. Collect folder names from disk
     let mut disk_folders: HashSet<String> = HashSet::new();
     let mut existing_folders: Vec<(String, std::path::PathBuf)> = Vec::new();
@@ -1681,21 +3090,46 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
     let t_db_fetch = t0.elapsed();
 
     let t0 = Instant::now();
-    for entry in std::fs::read_dir(&storage_path).map_err(|e| e.to_string())? {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
+    for (root_index, storage_root) in storage_roots.iter().enumerate() {
+        // The primary root must be readable; an unreachable secondary root
+        // (e.g. NAS offline) is skipped rather than failing the whole rescan
+        let entries = match std::fs::read_dir(storage_root) {
+            Ok(entries) => entries,
+            Err(e) if root_index == 0 => return Err(e.to_string()),
+            Err(e) => {
+                log::warn!("Skipping storage root {}: {}", storage_root.display(), e);
+                continue;
+            }
         };
-        let path = entry.path();
-        if !path.is_dir() {
-            continue;
-        }
-        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-            disk_folders.insert(name.to_string());
-            if existing_map.contains_key(name) {
-                existing_folders.push((name.to_string(), path));
-            } else {
-                new_folders.push((name.to_string(), path));
+        scanned_roots.push(storage_root.clone());
+        for entry in entries {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                // Hidden folders are staging areas (e.g. move_session copies)
+                if name.starts_with('.') {
+                    continue;
+                }
+                // Other users' takes in a shared root are left out of the index
+                if !show_other_users && !crate::session::is_owned_by_current_user(&path) {
+                    continue;
+                }
+                // Earlier roots win if the same folder exists in several
+                if !disk_folders.insert(name.to_string()) {
+                    log::warn!("Duplicate session folder {} in {}, ignoring", name, storage_root.display());
+                    continue;
+                }
+                if existing_map.contains_key(name) {
+                    existing_folders.push((name.to_string(), path));
+                } else {
+                    new_folders.push((name.to_string(), path));
+                }
             }
         }
     }
@@ -1706,6 +3140,9 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
     let total = existing_folders.len() + new_folders.len();
     let progress_counter = std::sync::Arc::new(AtomicUsize::new(0));
 
+    // Folders containing a recording lock file (checked for staleness below)
+    let locked_folders: std::sync::Mutex<Vec<(String, std::path::PathBuf)>> = std::sync::Mutex::new(Vec::new());
+
     let t_3a_start = Instant::now();
     // 3a. Existing sessions — lightweight parallel check (metadata I/O only)
     //
@@ -1718,6 +3155,7 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
         let num_workers = 8.min(existing_folders.len());
         let work_queue = std::sync::Mutex::new(existing_folders.iter());
         let (tx, rx) = std::sync::mpsc::channel();
+        let locked_ref = &locked_folders;
 
         let existing_map_ref = &existing_map;
         std::thread::scope(|s| {
@@ -1749,7 +3187,14 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
                         let mut has_audio = false;
                         let mut has_midi = false;
                         let mut has_video = false;
+                        let mut archived = false;
+                        let mut favorite = false;
+                        let mut tags = Vec::new();
+                        let mut size_bytes = 0u64;
                         let mut notes_modified_at = String::new();
+                        let mut uid = None;
+                        let mut locked = false;
+                        let mut custom_fields = crate::session::CustomFields::new();
 
                         if let Ok(entries) = std::fs::read_dir(path) {
                             for entry in entries.flatten() {
@@ -1757,6 +3202,7 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
                                     Some(n) => n.to_string(),
                                     None => continue,
                                 };
+                                size_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
                                 if fname == "notes.txt" {
                                     if let Ok(meta) = entry.metadata() {
                                         if let Ok(modified) = meta.modified() {
@@ -1764,6 +3210,19 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
                                             notes_modified_at = dt.to_rfc3339();
                                         }
                                     }
+                                } else if fname == crate::session::LOCK_FILE_NAME {
+                                    locked = true;
+                                    locked_ref.lock().unwrap().push((folder_name.clone(), path.clone()));
+                                } else if fname == crate::session::archive::ARCHIVE_MANIFEST_NAME {
+                                    archived = true;
+                                } else if fname == crate::session::FAVORITE_MARKER_NAME {
+                                    favorite = true;
+                                } else if fname == crate::session::TAGS_FILE_NAME {
+                                    tags = crate::session::read_session_tags(path);
+                                } else if fname == crate::session::SESSION_UID_FILE_NAME {
+                                    uid = crate::session::read_session_uid(path);
+                                } else if fname == crate::session::CUSTOM_FIELDS_FILE_NAME {
+                                    custom_fields = crate::session::read_custom_fields(path);
                                 } else if fname.ends_with(".wav") || fname.ends_with(".flac") {
                                     has_audio = true;
                                 } else if fname.ends_with(".mid") {
@@ -1774,9 +3233,34 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
                             }
                         }
 
+                        // Proxies outlive their original if it was deleted
+                        // outside the app
+                        if !dry_run && !locked && has_video {
+                            crate::video::proxy::remove_orphan_proxies(path);
+                        }
+
+                        // The folder's uid is the identity; a folder without
+                        // one (indexed before uids existed) gets the index's
+                        let uid = match uid {
+                            Some(found) if found == db_row.uid => None,
+                            Some(found) => Some(found),
+                            None => {
+                                if !dry_run && !locked {
+                                    crate::session::write_session_uid(path, &db_row.uid);
+                                }
+                                None
+                            }
+                        };
+
                         let tags_changed = has_audio != db_row.has_audio
                             || has_midi != db_row.has_midi
-                            || has_video != db_row.has_video;
+                            || has_video != db_row.has_video
+                            || archived != db_row.archived
+                            || favorite != db_row.favorite
+                            || tags != db_row.tags
+                            || size_bytes != db_row.size_bytes
+                            || custom_fields != db_row.custom_fields
+                            || uid.is_some();
                         let notes_changed = notes_modified_at != db_row.notes_modified_at;
 
                         if tags_changed || notes_changed {
@@ -1785,6 +3269,8 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
 
                             let _ = tx.send(UpdatedSessionData {
                                 id: folder_name.clone(),
+                                uid,
+                                custom_fields,
                                 has_audio,
                                 has_midi,
                                 has_video,
@@ -1795,6 +3281,10 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
                                     db_row.notes_modified_at.clone()
                                 },
                                 title: crate::session::extract_title_from_folder_name(folder_name),
+                                archived,
+                                favorite,
+                                tags,
+                                size_bytes,
                             });
                         }
                     }
@@ -1814,6 +3304,7 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
     // back via a channel. This overlaps I/O-latency across folders, which is
     // the main bottleneck on cloud-backed filesystems like Google Drive.
     let discoverer_fallbacks = std::sync::Arc::new(AtomicUsize::new(0));
+    let mut skipped_folders: Vec<String> = Vec::new();
     let new_sessions: Vec<SessionIndexData> = if new_folders.is_empty() {
         Vec::new()
     } else {
@@ -1858,15 +3349,21 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
         drop(tx); // close sender so rx iterator terminates when workers finish
 
         let mut results = Vec::new();
-        for (_name, path, result) in rx {
+        for (name, path, result) in rx {
             match result {
                 Ok(index_data) => {
                     if index_data.has_audio || index_data.has_midi || index_data.has_video {
+                        if crate::session::has_recording_lock(&path) {
+                            locked_folders.lock().unwrap().push((name, path));
+                        }
                         results.push(index_data);
+                    } else {
+                        skipped_folders.push(name);
                     }
                 }
                 Err(e) => {
                     log::debug!("Skipping directory {}: {}", path.display(), e);
+                    skipped_folders.push(name);
                 }
             }
         }
@@ -1879,25 +3376,145 @@ fn rescan_sessions_blocking(app: &tauri::AppHandle) -> Result<usize, String> {
     };
 
     let t_new_scan = t_3b_start.elapsed();
+    let mut new_sessions = new_sessions;
 
-    // 4. Sessions in DB but not on disk -> deleted
-    let deleted_ids: Vec<&String> = existing_map.keys()
-        .filter(|id| !disk_folders.contains(id.as_str()))
+    // 4. Sessions in DB but not on disk -> moved if a new folder carries
+    //    their uid, otherwise deleted unless their root is offline
+    let mut gone_by_uid: HashMap<&str, &ExistingSessionRow> = existing_map.values()
+        .filter(|row| !disk_folders.contains(row.id.as_str()))
+        .map(|row| (row.uid.as_str(), row))
+        .collect();
+    let mut claimed_uids: HashSet<String> = existing_map.values()
+        .filter(|row| disk_folders.contains(row.id.as_str()))
+        .map(|row| row.uid.clone())
         .collect();
+    let mut moved: Vec<(String, String)> = Vec::new();
+    for session in &mut new_sessions {
+        let path = std::path::Path::new(&session.path);
+        match session.uid.clone() {
+            Some(uid) => {
+                if let Some(row) = gone_by_uid.remove(uid.as_str()) {
+                    moved.push((row.id.clone(), session.id.clone()));
+                } else if claimed_uids.contains(&uid) && !dry_run {
+                    // A copy of another session's folder; it's a session of
+                    // its own (the index generates a uid if none can be written)
+                    session.uid = crate::session::reset_session_uid(path);
+                }
+            }
+            None if !dry_run && !crate::session::has_recording_lock(path) => {
+                session.uid = crate::session::ensure_session_uid(path);
+            }
+            None => {}
+        }
+        if let Some(uid) = &session.uid {
+            claimed_uids.insert(uid.clone());
+        }
+    }
+    let moved_ids: HashSet<&str> = moved.iter().map(|(old_id, _)| old_id.as_str()).collect();
+    let (offline_rows, deleted_rows): (Vec<&ExistingSessionRow>, Vec<&ExistingSessionRow>) = existing_map.values()
+        .filter(|row| !disk_folders.contains(row.id.as_str()) && !moved_ids.contains(row.id.as_str()))
+        .partition(|row| is_on_offline_root(&row.path, &configured_roots, &scanned_roots));
+    let deleted_ids: Vec<&String> = deleted_rows.iter().map(|row| &row.id).collect();
+
+    // 5. Build the reconciliation report
+    let changed_sessions: Vec<RescanChangedSession> = updated_sessions.iter().filter_map(|u| {
+        let row = existing_map.get(&u.id)?;
+        let mut changes = Vec::new();
+        for (label, before, after) in [
+            ("audio", row.has_audio, u.has_audio),
+            ("midi", row.has_midi, u.has_midi),
+            ("video", row.has_video, u.has_video),
+        ] {
+            if before != after {
+                changes.push(format!("{} {}", label, if after { "added" } else { "removed" }));
+            }
+        }
+        if u.notes_modified_at != row.notes_modified_at {
+            changes.push("notes changed".to_string());
+        }
+        if u.archived != row.archived {
+            changes.push(if u.archived { "archived" } else { "restored from archive" }.to_string());
+        }
+        if u.favorite != row.favorite {
+            changes.push(if u.favorite { "marked favorite" } else { "unmarked favorite" }.to_string());
+        }
+        if u.custom_fields != row.custom_fields {
+            changes.push("fields changed".to_string());
+        }
+        // Indexing a newly assigned uid isn't a change to the session
+        if changes.is_empty() && u.uid.is_some() && u.size_bytes == row.size_bytes {
+            return None;
+        }
+        Some(RescanChangedSession { id: u.id.clone(), changes })
+    }).collect();
 
-    // 5. Batch sync in a single transaction
+    // A lock is stale unless it belongs to the recording in progress in this process
+    let active_session = app.state::<RwLock<RecordingState>>().read().current_session_path.clone();
+    let mut needs_repair: Vec<String> = locked_folders.into_inner().unwrap_or_default()
+        .into_iter()
+        .filter(|(_, path)| active_session.as_ref() != Some(path))
+        .map(|(name, _)| name)
+        .collect();
+    needs_repair.sort();
+
+    let mut report = RescanReport {
+        dry_run,
+        new_sessions: new_sessions.iter().filter(|s| !moved.iter().any(|(_, new_id)| *new_id == s.id)).map(|s| RescanNewSession {
+            id: s.id.clone(),
+            has_audio: s.has_audio,
+            has_midi: s.has_midi,
+            has_video: s.has_video,
+        }).collect(),
+        missing_sessions: deleted_ids.iter().map(|id| id.to_string()).collect(),
+        moved_sessions: moved.iter().map(|(from, to)| RescanMovedSession { from: from.clone(), to: to.clone() }).collect(),
+        offline_sessions: offline_rows.iter().map(|row| row.id.clone()).collect(),
+        changed_sessions,
+        needs_repair,
+        skipped_folders,
+    };
+    report.new_sessions.sort_by(|a, b| a.id.cmp(&b.id));
+    report.missing_sessions.sort();
+    report.moved_sessions.sort_by(|a, b| a.to.cmp(&b.to));
+    report.offline_sessions.sort();
+    report.changed_sessions.sort_by(|a, b| a.id.cmp(&b.id));
+    report.skipped_folders.sort();
+
+    // 6. Batch sync in a single transaction (skipped for dry runs)
     let t_sync_start = Instant::now();
-    let _count = db.batch_sync(&new_sessions, &updated_sessions, &deleted_ids)
-        .map_err(|e| e.to_string())?;
+    if !dry_run {
+        let count = db.batch_sync(&moved, &new_sessions, &updated_sessions, &deleted_ids)
+            .map_err(|e| e.to_string())?;
+
+        // State outside the index that's keyed by folder name follows the move
+        if !moved.is_empty() {
+            let recording_cache = app.state::<Arc<RecordingSimilarityCache>>();
+            let share = app.state::<crate::share::ShareState>();
+            let mut map_changed = false;
+            for (old_id, new_id) in &moved {
+                let title = crate::session::extract_title_from_folder_name(new_id);
+                map_changed |= recording_cache.rename(old_id, new_id, title);
+                if let Some(s) = new_sessions.iter().find(|s| s.id == *new_id) {
+                    share.session_moved(app, old_id, new_id, std::path::Path::new(&s.path));
+                }
+            }
+            if map_changed {
+                emit_similarity_map_changed(app, &recording_cache);
+            }
+        }
+        if count > 0 {
+            if let Err(e) = db.checkpoint() {
+                log::warn!("WAL checkpoint after rescan failed: {}", e);
+            }
+        }
+    }
     let t_batch_sync = t_sync_start.elapsed();
 
-    let result = new_sessions.len() + updated_sessions.len();
     let fallback_count = discoverer_fallbacks.load(Ordering::Relaxed);
     eprintln!("[rescan] db_fetch={:?}  read_dir={:?}  existing_check={:?}({} folders, {} updated)  new_scan={:?}({} folders, {} kept, {} discoverer_fallbacks)  batch_sync={:?}  deleted={}  total={:?}",
         t_db_fetch, t_read_dir, t_existing_check, existing_folders.len(), updated_sessions.len(),
         t_new_scan, new_folders_count, new_sessions.len(), fallback_count,
         t_batch_sync, deleted_ids.len(), t_start.elapsed());
-    Ok(result)
+    Ok(report)
 }
 
 // ============================================================================
@@ -1930,6 +3547,19 @@ pub struct VideoPlaybackInfo {
     pub codec: String,
 }
 
+impl From<&crate::video::VideoInfo> for VideoPlaybackInfo {
+    fn from(info: &crate::video::VideoInfo) -> Self {
+        Self {
+            width: info.width,
+            height: info.height,
+            fps: info.fps,
+            duration_ms: info.duration_ms,
+            frame_count: info.frame_count,
+            codec: info.codec.clone(),
+        }
+    }
+}
+
 /// Information about a video file's codec and playability
 #[derive(Debug, Serialize)]
 pub struct VideoCodecCheck {
@@ -1942,15 +3572,7 @@ pub struct VideoCodecCheck {
 }
 
 /// A single frame for playback
-#[derive(Debug, Serialize)]
-pub struct VideoFrameData {
-    /// Base64-encoded JPEG data
-    pub data_base64: String,
-    /// Timestamp in milliseconds
-    pub timestamp_ms: u64,
-    /// Duration in milliseconds
-    pub duration_ms: u64,
-}
+pub type VideoFrameData = crate::video::stream::StreamFrame;
 
 /// Check if a video file's codec is supported for playback
 /// This probes the actual codec from the file, not just the container
@@ -1971,94 +3593,271 @@ pub async fn check_video_codec(session_path: String, filename: String) -> Result
     }).await.map_err(|e| e.to_string())?
 }
 
+fn preview_options(config: &RwLock<Config>) -> crate::video::jpeg_scale::PreviewOptions {
+    crate::video::jpeg_scale::PreviewOptions::from_config(&config.read())
+}
+
 #[tauri::command]
-pub fn get_video_info(session_path: String, filename: String) -> Result<VideoPlaybackInfo, String> {
-    use std::path::Path;
-    use crate::video;
-    
-    let path = Path::new(&session_path).join(&filename);
-    let demuxer = video::open_video(&path).map_err(|e| e.to_string())?;
-    let info = demuxer.info();
-    
-    Ok(VideoPlaybackInfo {
-        width: info.width,
-        height: info.height,
-        fps: info.fps,
-        duration_ms: info.duration_ms,
-        frame_count: info.frame_count,
-        codec: info.codec.clone(),
-    })
+pub fn get_video_info(
+    cache: State<'_, crate::video::stream::DemuxerCache>,
+    config: State<'_, RwLock<Config>>,
+    session_path: String,
+    filename: String,
+) -> Result<VideoPlaybackInfo, String> {
+    let path = std::path::Path::new(&session_path).join(&filename);
+    let demuxer = cache.get(&path, preview_options(&config)).map_err(|e| e.to_string())?;
+    let demuxer = demuxer.lock();
+    Ok(VideoPlaybackInfo::from(demuxer.info()))
 }
 
 #[tauri::command]
 pub fn get_video_frame(
+    cache: State<'_, crate::video::stream::DemuxerCache>,
+    config: State<'_, RwLock<Config>>,
     session_path: String, 
     filename: String, 
     timestamp_ms: u64
 ) -> Result<VideoFrameData, String> {
-    use std::path::Path;
-    use crate::video;
-    use base64::Engine;
+    let path = std::path::Path::new(&session_path).join(&filename);
+    let demuxer = cache.get(&path, preview_options(&config)).map_err(|e| e.to_string())?;
     
-    let path = Path::new(&session_path).join(&filename);
-    let mut demuxer = video::open_video(&path).map_err(|e| e.to_string())?;
+    let frame = demuxer.lock().get_frame_at(timestamp_ms).map_err(|e| e.to_string())?;
     
-    let frame = demuxer.get_frame_at(timestamp_ms).map_err(|e| e.to_string())?;
-    
-    let data_base64 = base64::engine::general_purpose::STANDARD.encode(&frame.data);
-    
-    Ok(VideoFrameData {
-        data_base64,
-        timestamp_ms: frame.timestamp_ms,
-        duration_ms: frame.duration_ms,
-    })
+    Ok(frame.into())
+}
+
+/// Thumbnail sprite sheet for scrubbing the review timeline. Generated on
+/// first request if the post-recording pass hasn't produced one.
+#[tauri::command]
+pub async fn get_video_filmstrip(
+    session_path: String,
+    filename: String,
+) -> Result<crate::video::filmstrip::Filmstrip, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = std::path::Path::new(&session_path).join(&filename);
+        crate::video::filmstrip::ensure_filmstrip(&path).map_err(|e| e.to_string())
+    }).await.map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
 pub fn get_video_frames_batch(
+    cache: State<'_, crate::video::stream::DemuxerCache>,
+    config: State<'_, RwLock<Config>>,
     session_path: String,
     filename: String,
     start_ms: u64,
     end_ms: u64,
     max_frames: Option<usize>,
 ) -> Result<Vec<VideoFrameData>, String> {
-    use std::path::Path;
-    use crate::video;
-    use base64::Engine;
+    let path = std::path::Path::new(&session_path).join(&filename);
+    let demuxer = cache.get(&path, preview_options(&config)).map_err(|e| e.to_string())?;
     
-    let path = Path::new(&session_path).join(&filename);
-    let mut demuxer = video::open_video(&path).map_err(|e| e.to_string())?;
-    
-    let frames = demuxer.get_frames_range(start_ms, end_ms).map_err(|e| e.to_string())?;
+    let frames = demuxer.lock().get_frames_range(start_ms, end_ms).map_err(|e| e.to_string())?;
     
     let max = max_frames.unwrap_or(usize::MAX);
     
-    let result: Vec<VideoFrameData> = frames.into_iter()
-        .take(max)
-        .map(|frame| {
-            VideoFrameData {
-                data_base64: base64::engine::general_purpose::STANDARD.encode(&frame.data),
-                timestamp_ms: frame.timestamp_ms,
-                duration_ms: frame.duration_ms,
-            }
-        })
-        .collect();
-    
-    Ok(result)
+    Ok(frames.into_iter().take(max).map(VideoFrameData::from).collect())
 }
 
 #[tauri::command]
 pub fn get_video_frame_timestamps(
+    cache: State<'_, crate::video::stream::DemuxerCache>,
+    config: State<'_, RwLock<Config>>,
     session_path: String,
     filename: String,
 ) -> Result<Vec<u64>, String> {
-    use std::path::Path;
-    use crate::video;
+    let path = std::path::Path::new(&session_path).join(&filename);
+    let demuxer = cache.get(&path, preview_options(&config)).map_err(|e| e.to_string())?;
     
-    let path = Path::new(&session_path).join(&filename);
-    let mut demuxer = video::open_video(&path).map_err(|e| e.to_string())?;
-    
-    demuxer.get_frame_timestamps().map_err(|e| e.to_string())
+    let timestamps = demuxer.lock().get_frame_timestamps().map_err(|e| e.to_string())?;
+    Ok(timestamps)
+}
+
+/// An open frame stream
+#[derive(Debug, Serialize)]
+pub struct VideoStreamInfo {
+    pub stream_id: u32,
+    pub info: VideoPlaybackInfo,
+}
+
+/// Open a video for streamed playback. Frames are pushed to `channel` once
+/// `video_stream_play` or `video_stream_seek` is called; the decoder stays
+/// open until `close_video_stream`.
+#[tauri::command]
+pub async fn open_video_stream(
+    app: tauri::AppHandle,
+    session_path: String,
+    filename: String,
+    channel: tauri::ipc::Channel<crate::video::stream::StreamMessage>,
+) -> Result<VideoStreamInfo, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = std::path::Path::new(&session_path).join(&filename);
+        let options = preview_options(&app.state::<RwLock<Config>>());
+        let streams = app.state::<crate::video::stream::VideoStreams>();
+        let (stream_id, info) = streams.open(&path, options, channel).map_err(|e| e.to_string())?;
+        Ok(VideoStreamInfo { stream_id, info: VideoPlaybackInfo::from(&info) })
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Start delivering frames from `from_ms` at `rate` times real time
+#[tauri::command]
+pub fn video_stream_play(
+    streams: State<'_, crate::video::stream::VideoStreams>,
+    stream_id: u32,
+    from_ms: u64,
+    rate: Option<f64>,
+) -> Result<(), String> {
+    streams.play(stream_id, from_ms, rate.unwrap_or(1.0))
+}
+
+#[tauri::command]
+pub fn video_stream_pause(
+    streams: State<'_, crate::video::stream::VideoStreams>,
+    stream_id: u32,
+) -> Result<(), String> {
+    streams.pause(stream_id)
+}
+
+/// Deliver the single frame at `timestamp_ms` (stops playback)
+#[tauri::command]
+pub fn video_stream_seek(
+    streams: State<'_, crate::video::stream::VideoStreams>,
+    stream_id: u32,
+    timestamp_ms: u64,
+) -> Result<(), String> {
+    streams.seek(stream_id, timestamp_ms)
+}
+
+/// Cycle playback between `start_ms` and `end_ms`; omit both to clear
+#[tauri::command]
+pub fn video_stream_set_loop(
+    streams: State<'_, crate::video::stream::VideoStreams>,
+    stream_id: u32,
+    start_ms: Option<u64>,
+    end_ms: Option<u64>,
+) -> Result<(), String> {
+    let region = match (start_ms, end_ms) {
+        (Some(start), Some(end)) if end > start => Some((start, end)),
+        (None, None) => None,
+        _ => return Err("Invalid loop region".to_string()),
+    };
+    streams.set_loop(stream_id, region)
+}
+
+#[tauri::command]
+pub fn close_video_stream(
+    streams: State<'_, crate::video::stream::VideoStreams>,
+    stream_id: u32,
+) {
+    streams.close(stream_id);
+}
+
+// ============================================================================
+// Audio Playback Commands
+// ============================================================================
+
+/// Load an audio file for backend playback, replacing any loaded file
+#[tauri::command]
+pub async fn audio_playback_load(
+    app: tauri::AppHandle,
+    session_path: String,
+    filename: String,
+) -> Result<crate::playback::AudioPlaybackInfo, String> {
+    tokio::task::spawn_blocking(move || {
+        let path = std::path::Path::new(&session_path).join(&filename);
+        let state = app.state::<crate::playback::AudioPlaybackState>();
+        // Release the previous file first so only one pipeline holds the device
+        *state.lock() = None;
+        let player = crate::playback::AudioPlayer::load(app.clone(), &path).map_err(|e| e.to_string())?;
+        let info = player.info().clone();
+        *state.lock() = Some(player);
+        Ok(info)
+    }).await.map_err(|e| e.to_string())?
+}
+
+fn with_audio_player<T>(
+    state: &crate::playback::AudioPlaybackState,
+    f: impl FnOnce(&mut crate::playback::AudioPlayer) -> anyhow::Result<T>,
+) -> Result<T, String> {
+    let mut player = state.lock();
+    let player = player.as_mut().ok_or_else(|| "No audio loaded".to_string())?;
+    f(player).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn audio_playback_play(state: State<'_, crate::playback::AudioPlaybackState>) -> Result<(), String> {
+    with_audio_player(&state, |p| p.play())
+}
+
+#[tauri::command]
+pub fn audio_playback_pause(state: State<'_, crate::playback::AudioPlaybackState>) -> Result<(), String> {
+    with_audio_player(&state, |p| p.pause())
+}
+
+#[tauri::command]
+pub fn audio_playback_seek(
+    state: State<'_, crate::playback::AudioPlaybackState>,
+    position_secs: f64,
+) -> Result<(), String> {
+    with_audio_player(&state, |p| p.seek(position_secs))
+}
+
+/// Set the playback rate (clamped to 0.5–2.0). Returns the applied rate.
+#[tauri::command]
+pub fn audio_playback_set_rate(
+    state: State<'_, crate::playback::AudioPlaybackState>,
+    rate: f64,
+) -> Result<f64, String> {
+    with_audio_player(&state, |p| {
+        p.set_rate(rate)?;
+        Ok(p.rate())
+    })
+}
+
+/// Keep pitch constant (time-stretch) when playing at a rate other than 1
+#[tauri::command]
+pub fn audio_playback_set_preserve_pitch(
+    state: State<'_, crate::playback::AudioPlaybackState>,
+    preserve: bool,
+) -> Result<(), String> {
+    with_audio_player(&state, |p| p.set_preserve_pitch(preserve))
+}
+
+/// Loop between `start_secs` and `end_secs`; omit both to clear
+#[tauri::command]
+pub fn audio_playback_set_loop(
+    state: State<'_, crate::playback::AudioPlaybackState>,
+    start_secs: Option<f64>,
+    end_secs: Option<f64>,
+) -> Result<(), String> {
+    let region = match (start_secs, end_secs) {
+        (Some(start), Some(end)) => Some((start, end)),
+        (None, None) => None,
+        _ => return Err("Invalid loop region".to_string()),
+    };
+    with_audio_player(&state, |p| p.set_loop(region))
+}
+
+#[tauri::command]
+pub fn audio_playback_set_volume(
+    state: State<'_, crate::playback::AudioPlaybackState>,
+    volume_db: f64,
+) -> Result<(), String> {
+    with_audio_player(&state, |p| {
+        p.set_volume_db(volume_db);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub fn audio_playback_position(state: State<'_, crate::playback::AudioPlaybackState>) -> Result<f64, String> {
+    with_audio_player(&state, |p| Ok(p.position_secs()))
+}
+
+/// Stop playback and release the file
+#[tauri::command]
+pub fn audio_playback_unload(state: State<'_, crate::playback::AudioPlaybackState>) {
+    *state.lock() = None;
 }
 
 // ============================================================================
@@ -2122,6 +3921,12 @@ fn build_codec_encoder_info(codec: crate::encoding::VideoCodec) -> CodecEncoderI
     }
 }
 
+/// Whether virtual camera output can be enabled on this platform
+#[tauri::command]
+pub fn get_virtual_camera_support() -> crate::recording::virtual_camera::VirtualCameraSupport {
+    crate::recording::virtual_camera::support()
+}
+
 #[tauri::command]
 pub fn get_encoder_availability() -> EncoderAvailability {
     use crate::encoding::{VideoCodec, get_recommended_codec};
@@ -2145,6 +3950,15 @@ pub fn get_encoder_availability() -> EncoderAvailability {
     }
 }
 
+/// Check every GStreamer element Sacho can use on this platform against the
+/// registry, grouped by feature, with install hints for what is missing
+#[tauri::command]
+pub async fn audit_gstreamer() -> Result<crate::gstreamer_audit::GstAuditReport, String> {
+    tokio::task::spawn_blocking(|| crate::gstreamer_audit::audit().map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 // ============================================================================
 // Preset Bitrate Preview
 // ============================================================================
@@ -2246,7 +4060,7 @@ pub async fn test_encoder_preset(
             })
             .collect();
 
-        let pre_roll = cfg.pre_roll_secs.min(5);
+        let pre_roll = cfg.video_pre_roll_secs.min(5);
         let preferred_container = cfg.preferred_video_container;
 
         (info, pre_roll, preferred_container)
@@ -2365,7 +4179,10 @@ async fn run_pipeline_test(
         let use_target_fps = if (resolved.target_fps - capture.fps).abs() > 0.01 { Some(resolved.target_fps) } else { None };
         let effective_fps = use_target_fps.unwrap_or(capture.fps);
         let encoder_config = EncoderConfig {
-            keyframe_interval: (effective_fps * 2.0).round() as u32,
+            keyframe_interval: dev_config.keyframe_interval.frames(effective_fps),
+            b_frames: dev_config.b_frames,
+            content_type: dev_config.content_type,
+            roi: dev_config.roi,
             target_codec: codec,
             preset_level: dev_config.preset_level,
             effort_level: dev_config.effort_level,
@@ -2373,6 +4190,9 @@ async fn run_pipeline_test(
             target_width: use_target_w,
             target_height: use_target_h,
             target_fps: use_target_fps,
+            colorimetry: capture.color_info().and_then(|c| c.colorimetry),
+            constant_frame_rate: dev_config.constant_frame_rate,
+            tags: None,
         };
         match AsyncVideoEncoder::new(
             temp_file.clone(), capture.width, capture.height, capture.fps,
@@ -2581,7 +4401,7 @@ pub async fn auto_select_encoder_preset(
             })
             .collect();
 
-        let pre_roll = cfg.pre_roll_secs.min(5);
+        let pre_roll = cfg.video_pre_roll_secs.min(5);
         let preferred_container = cfg.preferred_video_container;
 
         (info, pre_roll, preferred_container)
@@ -2706,7 +4526,10 @@ async fn run_auto_select_test(
 
         // Create encoder with this preset
         let encoder_config = EncoderConfig {
-            keyframe_interval: (effective_fps * 2.0).round() as u32,
+            keyframe_interval: dev_config.keyframe_interval.frames(effective_fps),
+            b_frames: dev_config.b_frames,
+            content_type: dev_config.content_type,
+            roi: dev_config.roi,
             target_codec,
             preset_level: level,
             effort_level: dev_config.effort_level,
@@ -2714,6 +4537,9 @@ async fn run_auto_select_test(
             target_width: use_target_w,
             target_height: use_target_h,
             target_fps: use_target_fps,
+            colorimetry: capture.color_info().and_then(|c| c.colorimetry),
+            constant_frame_rate: dev_config.constant_frame_rate,
+            tags: None,
         };
         
         let encoder = match AsyncVideoEncoder::new(
@@ -2922,12 +4748,29 @@ pub fn set_all_users_autostart(enabled: bool) -> Result<(), String> {
     }
 }
 
-/// Dev-only: force a crash to test RegisterApplicationRestart
+/// Dev-only: force a crash to test crash reports and RegisterApplicationRestart.
+/// `kind` "panic" panics on the calling thread (report with a backtrace); the
+/// default aborts, which only the next launch notices.
 #[tauri::command]
-pub fn simulate_crash() {
+pub fn simulate_crash(kind: Option<String>) {
+    if kind.as_deref() == Some("panic") {
+        panic!("Simulated crash");
+    }
     std::process::abort();
 }
 
+/// Crash reports from earlier runs that haven't been dismissed, newest first
+#[tauri::command]
+pub fn get_pending_crash_reports() -> Vec<crate::crash::CrashReport> {
+    crate::crash::pending_reports()
+}
+
+/// Delete a crash report once the user has looked at (or sent) it
+#[tauri::command]
+pub fn dismiss_crash_report(id: String) -> Result<(), String> {
+    crate::crash::dismiss_report(&id).map_err(|e| e.to_string())
+}
+
 // ============================================================================
 // App Stats Commands
 // ============================================================================
@@ -2974,10 +4817,11 @@ pub async fn get_app_stats(
     };
 
     // --- Storage walk + disk free (potentially slow, run on blocking thread) ---
-    let storage_path = config.read().storage_path.clone();
+    let config_snapshot = config.read().clone();
     let (storage_used_bytes, disk_free_bytes) = tokio::task::spawn_blocking(move || {
-        let used = dir_size_recursive(&storage_path);
-        let free = disk_free_space(&storage_path);
+        let used = config_snapshot.storage_roots().iter().map(|root| dir_size_recursive(root)).sum::<u64>();
+        // Free space where the next session will be recorded
+        let free = crate::session::disk_free_space(&crate::session::select_new_session_root(&config_snapshot));
         (used, free)
     })
     .await
@@ -3010,37 +4854,35 @@ fn dir_size_recursive(path: &std::path::Path) -> u64 {
     total
 }
 
-/// Find the disk that contains `path` and return its available space.
-fn disk_free_space(path: &std::path::Path) -> u64 {
-    use sysinfo::Disks;
-    let disks = Disks::new_with_refreshed_list();
+// ============================================================================
+// Database Maintenance Commands
+// ============================================================================
 
-    // On Windows, std::fs::canonicalize returns \\?\C:\... (UNC prefix) but
-    // sysinfo mount points are plain C:\. Strip the prefix so starts_with works.
-    let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
-    let canonical_str = canonical.to_string_lossy();
-    let clean_path = if canonical_str.starts_with(r"\\?\") {
-        std::path::PathBuf::from(&canonical_str[4..])
-    } else {
-        canonical
-    };
+#[derive(Serialize)]
+pub struct VacuumResult {
+    pub schema_version: i32,
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+}
 
-    // Find the disk whose mount point is the longest prefix of our path
-    let mut best_mount: Option<&std::path::Path> = None;
-    let mut best_free: u64 = 0;
+/// Compact the session database and truncate its WAL
+#[tauri::command]
+pub async fn vacuum_database(app: tauri::AppHandle) -> Result<VacuumResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let db = app.state::<SessionDatabase>();
+        let (size_before_bytes, size_after_bytes) = db.vacuum().map_err(|e| e.to_string())?;
+        let schema_version = db.schema_version().map_err(|e| e.to_string())?;
+        println!("[Sacho] Database vacuumed: {} -> {} bytes", size_before_bytes, size_after_bytes);
+        Ok(VacuumResult { schema_version, size_before_bytes, size_after_bytes })
+    }).await.map_err(|e| e.to_string())?
+}
 
-    for disk in disks.list() {
-        let mount = disk.mount_point();
-        if clean_path.starts_with(mount) {
-            let is_better = match best_mount {
-                None => true,
-                Some(prev) => mount.as_os_str().len() > prev.as_os_str().len(),
-            };
-            if is_better {
-                best_mount = Some(mount);
-                best_free = disk.available_space();
-            }
-        }
-    }
-    best_free
+/// Repopulate a freshly recreated database from the session folders on disk.
+/// Notes come back from each folder's notes.txt; similarity features are
+/// recomputed afterwards.
+pub fn rebuild_session_index(app: &tauri::AppHandle) -> Result<usize, String> {
+    let count = rescan_sessions_blocking(app, false)?.indexed_count();
+    println!("[Sacho] Rebuilt session index from disk ({} sessions)", count);
+    let _ = app.emit("database-rebuilt", count);
+    Ok(count)
 }
@@ -18,6 +18,27 @@ pub const DEFAULT_TARGET_FPS: f64 = 30.0;
 /// Tolerance for comparing FPS to [`DEFAULT_TARGET_FPS`] (includes 30000/1001 ≈ 29.97).
 pub const DEFAULT_TARGET_FPS_TOLERANCE: f64 = 30.5;
 
+/// Largest per-device sync correction accepted, in milliseconds.
+pub const MAX_SYNC_OFFSET_MS: i32 = 2000;
+
+/// Widest stereo image accepted by [`AudioProcessing::stereo_width`].
+pub const MAX_STEREO_WIDTH: f32 = 2.0;
+
+/// Find a device's latency correction by name. Names are compared in their
+/// filename-safe form so names recovered from recorded files (e.g. by
+/// calibration) match the live device names.
+pub fn lookup_sync_offset_ms(offsets: &HashMap<String, i32>, device_name: &str) -> i32 {
+    if let Some(offset) = offsets.get(device_name) {
+        return *offset;
+    }
+    let safe_name = crate::session::sanitize_device_name(device_name);
+    offsets
+        .iter()
+        .find(|(name, _)| crate::session::sanitize_device_name(name) == safe_name)
+        .map(|(_, offset)| *offset)
+        .unwrap_or(0)
+}
+
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -27,10 +48,20 @@ pub struct Config {
     /// Idle timeout in seconds before recording stops
     pub idle_timeout_secs: u32,
 
-    /// Pre-roll buffer duration in seconds (0-5)
+    /// Audio and MIDI pre-roll in seconds (0-30)
     /// When recording starts, include this many seconds of prior data
     #[serde(default = "default_pre_roll_secs")]
-    pub pre_roll_secs: u32,
+    pub audio_midi_pre_roll_secs: u32,
+
+    /// Video pre-roll in seconds (0-5, or 0-30 when encoding during pre-roll).
+    /// May be shorter than the audio/MIDI pre-roll; the video file then starts
+    /// that much later in the session.
+    #[serde(default = "default_pre_roll_secs")]
+    pub video_pre_roll_secs: u32,
+
+    /// Legacy: single pre-roll for every device (migrated to the two above on load)
+    #[serde(default, skip_serializing)]
+    pre_roll_secs: Option<u32>,
 
     /// Audio format for recordings
     pub audio_format: AudioFormat,
@@ -47,10 +78,20 @@ pub struct Config {
     #[serde(default)]
     pub flac_bit_depth: AudioBitDepth,
 
+    /// Record each device at its native bit depth, with the format's bit
+    /// depth above as the upper limit instead of a fixed depth
+    #[serde(default = "default_true")]
+    pub native_bit_depth: bool,
+
     /// FLAC sample rate
     #[serde(default)]
     pub flac_sample_rate: AudioSampleRate,
 
+    /// With passthrough rates, write every file of a session at the highest
+    /// device rate when the devices' rates differ
+    #[serde(default)]
+    pub resample_to_common_rate: bool,
+
     /// Whether to use dark color scheme (default is light)
     #[serde(default)]
     pub dark_mode: bool,
@@ -62,9 +103,31 @@ pub struct Config {
     #[serde(default = "default_true")]
     pub start_minimized: bool,
 
+    /// Run the capture core headless and create the review window only on
+    /// demand, so closing or losing the UI never interrupts recording
+    /// (takes effect on next launch; `--agent` forces it for one run)
+    #[serde(default)]
+    pub agent_mode: bool,
+
+    /// Open the library for browsing and playback only: no monitoring and
+    /// no writes to session folders (takes effect on next launch;
+    /// `--read-only` forces it for one run)
+    #[serde(default)]
+    pub read_only_library: bool,
+
+    /// Which GStreamer install to load (takes effect on next launch; the
+    /// SACHO_GSTREAMER environment variable overrides it)
+    #[serde(default)]
+    pub gstreamer_source: GstreamerSource,
+
     /// Whether to minimize to tray on close
     pub minimize_to_tray: bool,
 
+    /// Language of notifications, the tray menu and other text the backend
+    /// shows directly
+    #[serde(default)]
+    pub language: Language,
+
     /// Whether to show notification when recording starts
     #[serde(default = "default_true")]
     pub notify_recording_start: bool,
@@ -73,6 +136,19 @@ pub struct Config {
     #[serde(default = "default_true")]
     pub notify_recording_stop: bool,
 
+    /// What to do when a recording stops, for a quick listen-and-discard loop
+    #[serde(default)]
+    pub quick_review: QuickReviewMode,
+
+    /// Release channel checked for updates
+    #[serde(default)]
+    pub update_channel: UpdateChannel,
+
+    /// Release feed to check instead of the channel's (e.g. a mirror inside
+    /// a studio network)
+    #[serde(default)]
+    pub update_feed_url: Option<String>,
+
     /// Whether to play a sound when recording starts
     #[serde(default)]
     pub sound_recording_start: bool,
@@ -130,6 +206,22 @@ pub struct Config {
     #[serde(default)]
     pub audio_trigger_thresholds: HashMap<String, f64>,
 
+    /// Ignore audio triggers while a session is playing back in Sacho or the
+    /// playback monitor device has signal, so a take played through the
+    /// speakers doesn't record itself
+    #[serde(default)]
+    pub suppress_triggers_during_playback: bool,
+
+    /// Device that carries what the speakers play: a loopback input such as
+    /// "Stereo Mix", or an output device where the backend supports loopback
+    /// capture (WASAPI)
+    #[serde(default)]
+    pub playback_monitor_device: Option<String>,
+
+    /// RMS level (0.0-1.0) above which the playback monitor device counts as playing
+    #[serde(default = "default_playback_monitor_threshold")]
+    pub playback_monitor_threshold: f64,
+
     /// Selected video device IDs
     pub selected_video_devices: Vec<String>,
 
@@ -144,6 +236,12 @@ pub struct Config {
     #[serde(default)]
     pub encode_during_preroll: bool,
 
+    /// Build each camera's file writer pipeline while armed instead of at the
+    /// trigger, shortening trigger-to-first-frame latency at the cost of an
+    /// idle pipeline per camera. Not used for raw video encoded at record time.
+    #[serde(default)]
+    pub prewarm_video_writer: bool,
+
     /// Whether to combine audio and video into a single container file.
     /// When enabled (and exactly 1 video + 1 audio device are selected),
     /// the separate audio file is muxed into the video container after recording stops.
@@ -156,6 +254,172 @@ pub struct Config {
     #[serde(default = "default_preferred_video_container")]
     pub preferred_video_container: ContainerFormat,
 
+    /// MIDI feedback sent back to trigger devices when recording starts/stops
+    /// (e.g. to light a pad LED on a Launchpad or foot controller)
+    #[serde(default)]
+    pub midi_feedback: MidiFeedbackConfig,
+
+    /// Forward one MIDI input to an output port while monitoring, so a
+    /// controller can play a software instrument while Sacho records it
+    #[serde(default)]
+    pub midi_thru: MidiThruConfig,
+
+    /// How each MIDI input port is opened (device ID -> mode); devices not
+    /// listed are held exclusively while monitoring
+    #[serde(default)]
+    pub midi_access_modes: HashMap<String, MidiAccessMode>,
+
+    /// Timing resolution and tempo of recorded MIDI files
+    #[serde(default)]
+    pub midi_file: MidiFileConfig,
+
+    /// Live stream pushed to an RTMP server while recording
+    #[serde(default)]
+    pub rtmp_stream: RtmpStreamConfig,
+
+    /// Republish a camera as a virtual camera so other apps can share it
+    #[serde(default)]
+    pub virtual_camera: VirtualCameraConfig,
+
+    /// Watch the storage folder and index sessions added or removed outside
+    /// the app (e.g. copied in from another machine) without a manual rescan
+    #[serde(default = "default_true")]
+    pub watch_storage: bool,
+
+    /// Additional storage roots (e.g. a NAS archive). Sessions in every root
+    /// are indexed; `storage_path` remains the primary root.
+    #[serde(default)]
+    pub additional_storage_paths: Vec<PathBuf>,
+
+    /// Storage root shared by every user of this machine. Each user records
+    /// into their own subfolder, which replaces `storage_path` as the primary
+    /// root; `storage_path` is still indexed so earlier takes stay visible.
+    #[serde(default)]
+    pub shared_storage_path: Option<PathBuf>,
+
+    /// Index sessions recorded by other users when they share a root
+    #[serde(default)]
+    pub show_other_users_sessions: bool,
+
+    /// Rule for choosing the root that new sessions are recorded into
+    #[serde(default)]
+    pub storage_routing: StorageRouting,
+
+    /// Free space (GB) a root needs to receive new sessions under
+    /// `StorageRouting::FirstWithFreeSpace`
+    #[serde(default = "default_storage_min_free_gb")]
+    pub storage_min_free_gb: u32,
+
+    /// Cold-storage folder that archived sessions' original video is moved to
+    #[serde(default)]
+    pub archive_path: Option<PathBuf>,
+
+    /// Let paired phones see recording state and start/stop recording
+    #[serde(default)]
+    pub mobile_remote_enabled: bool,
+
+    /// Port for the mobile remote (0 = any free port; phones find it via mDNS)
+    #[serde(default)]
+    pub mobile_remote_port: u16,
+
+    /// Allow sessions to be shared on the local network via tokenized links
+    #[serde(default)]
+    pub lan_share_enabled: bool,
+
+    /// Port for the share server (0 = any free port)
+    #[serde(default = "default_lan_share_port")]
+    pub lan_share_port: u16,
+
+    /// Hours until a share link stops working
+    #[serde(default = "default_share_link_expiry_hours")]
+    pub share_link_expiry_hours: u32,
+
+    /// Generate low-resolution proxies after recording for video codecs that
+    /// are slow to scrub (FFV1, raw, AV1)
+    #[serde(default = "default_true")]
+    pub generate_video_proxies: bool,
+
+    /// Generate a thumbnail filmstrip for each video after recording, for
+    /// previews while scrubbing the review timeline
+    #[serde(default = "default_true")]
+    pub generate_video_filmstrips: bool,
+
+    /// Detect chapters (pieces, key changes) in recorded MIDI and mark them
+    /// in the session and its Matroska files
+    #[serde(default = "default_true")]
+    pub detect_chapters: bool,
+
+    /// Names of recorded files (see [`crate::session::filenames`])
+    #[serde(default)]
+    pub filename_templates: FilenameTemplates,
+
+    /// MJPEG frames taller than this are downscaled before being sent to the
+    /// review player (0 = full resolution)
+    #[serde(default = "default_video_preview_max_height")]
+    pub video_preview_max_height: u32,
+
+    /// Decode JPEG frames on the GPU (nvjpeg/D3D11/VA) when downscaling
+    #[serde(default = "default_true")]
+    pub hardware_jpeg_decode: bool,
+
+    /// Rebuild device pipelines automatically when the computer wakes from sleep
+    #[serde(default = "default_true")]
+    pub recover_after_sleep: bool,
+
+    /// Seconds to wait after waking before rebuilding pipelines, so USB
+    /// devices have time to re-enumerate
+    #[serde(default = "default_resume_grace_secs")]
+    pub resume_grace_secs: u32,
+
+    /// Sync writers to disk more often, lower video quality and split video
+    /// into segments when a laptop's battery runs low during a recording
+    #[serde(default = "default_true")]
+    pub battery_safe_mode: bool,
+
+    /// Battery charge (percent) at which record-safe mode kicks in
+    #[serde(default = "default_battery_low_percent")]
+    pub battery_low_percent: u8,
+
+    /// Length of video segments in record-safe mode, in minutes
+    #[serde(default = "default_battery_segment_minutes")]
+    pub battery_segment_minutes: u32,
+
+    /// Shut down camera pipelines during a privacy pause started from the tray
+    #[serde(default = "default_true")]
+    pub privacy_pause_stops_cameras: bool,
+
+    /// Longest time to wait on quit for in-flight recordings to finish
+    /// writing before exiting anyway
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u32,
+
+    /// Per-device latency corrections in milliseconds (device name -> offset).
+    /// Positive means the device's content arrives late and is pulled earlier
+    /// when recording starts. Measured by `calibrate_sync_offsets`.
+    #[serde(default)]
+    pub device_sync_offsets_ms: HashMap<String, i32>,
+
+    /// Per-device channel splits (device name -> stems). A device with stems
+    /// records one mono or stereo file per stem instead of one interleaved file.
+    #[serde(default)]
+    pub audio_channel_splits: HashMap<String, Vec<AudioChannelStem>>,
+
+    /// Per-device processing applied to incoming audio before it is buffered
+    /// or written (device name -> settings)
+    #[serde(default)]
+    pub audio_processing: HashMap<String, AudioProcessing>,
+
+    /// Per-device record schedules (video device ID or audio device name ->
+    /// schedule). A scheduled device is only opened inside its windows;
+    /// unscheduled devices are always on.
+    #[serde(default)]
+    pub device_schedules: HashMap<String, DeviceSchedule>,
+
+    /// Tags applied to a session when it's finalized, by its length, start
+    /// time and which kinds of files it has
+    #[serde(default)]
+    pub auto_tag_rules: Vec<AutoTagRule>,
+
     /// Device presets
     pub device_presets: Vec<DevicePreset>,
 
@@ -163,6 +427,123 @@ pub struct Config {
     pub current_preset: Option<String>,
 }
 
+/// Simple per-device processing for stereo and M/S rigs. Steps run in order:
+/// polarity inversion, M/S decode, then width.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AudioProcessing {
+    /// Treat channels 1 and 2 as mid and side and decode them to left/right
+    #[serde(default)]
+    pub ms_decode: bool,
+    /// Stereo width of channels 1 and 2: 0 = mono, 1 = unchanged, 2 = widest
+    #[serde(default = "default_stereo_width")]
+    pub stereo_width: f32,
+    /// Zero-based input channels whose polarity is flipped
+    #[serde(default)]
+    pub invert_polarity: Vec<u16>,
+}
+
+fn default_stereo_width() -> f32 {
+    1.0
+}
+
+impl Default for AudioProcessing {
+    fn default() -> Self {
+        Self {
+            ms_decode: false,
+            stereo_width: default_stereo_width(),
+            invert_polarity: Vec::new(),
+        }
+    }
+}
+
+impl AudioProcessing {
+    /// True if the settings leave the audio untouched
+    pub fn is_identity(&self) -> bool {
+        !self.ms_decode && self.stereo_width == 1.0 && self.invert_polarity.is_empty()
+    }
+}
+
+/// Minutes in a day; window bounds run 0..=MINUTES_PER_DAY
+pub const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// When a device records. The device is on while any window is open.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeviceSchedule {
+    #[serde(default)]
+    pub windows: Vec<ScheduleWindow>,
+}
+
+/// A recurring weekly time window in local time
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduleWindow {
+    /// Days the window opens on, 0 = Monday .. 6 = Sunday
+    pub days: Vec<u8>,
+    /// Minutes after midnight the window opens
+    pub start_minute: u16,
+    /// Minutes after midnight the window closes. Earlier than the start
+    /// means the window runs past midnight into the next day.
+    pub end_minute: u16,
+}
+
+impl DeviceSchedule {
+    /// Whether the device should be on at a local date and time
+    pub fn is_open_at(&self, now: chrono::NaiveDateTime) -> bool {
+        self.windows.iter().any(|w| w.contains(now))
+    }
+}
+
+impl ScheduleWindow {
+    /// Whether a local date and time falls inside the window
+    pub fn contains(&self, now: chrono::NaiveDateTime) -> bool {
+        use chrono::{Datelike, Timelike};
+        let day = now.weekday().num_days_from_monday() as u8;
+        let yesterday = (day + 6) % 7;
+        let minute = (now.hour() * 60 + now.minute()) as u16;
+        if self.start_minute <= self.end_minute {
+            self.days.contains(&day) && (self.start_minute..self.end_minute).contains(&minute)
+        } else {
+            // Overnight: the evening part today, or the morning part of
+            // a window that opened yesterday
+            (self.days.contains(&day) && minute >= self.start_minute)
+                || (self.days.contains(&yesterday) && minute < self.end_minute)
+        }
+    }
+}
+
+/// Tag a finalized session gets when every condition that's set holds
+/// (e.g. shorter than a minute -> "noodle", 6-8am -> "morning practice")
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AutoTagRule {
+    pub tag: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Session at least this long
+    #[serde(default)]
+    pub min_duration_secs: Option<f64>,
+    /// Session shorter than this
+    #[serde(default)]
+    pub max_duration_secs: Option<f64>,
+    /// Session started inside this window (local time)
+    #[serde(default)]
+    pub started_within: Option<ScheduleWindow>,
+    /// Session has (or lacks) audio, MIDI or video files
+    #[serde(default)]
+    pub has_audio: Option<bool>,
+    #[serde(default)]
+    pub has_midi: Option<bool>,
+    #[serde(default)]
+    pub has_video: Option<bool>,
+}
+
+/// One output file cut from a multichannel input
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AudioChannelStem {
+    /// Appended to the filename (e.g. "kick" -> audio_Interface_kick.wav)
+    pub label: String,
+    /// Zero-based input channels, one for mono or two for stereo
+    pub channels: Vec<u16>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum AudioFormat {
@@ -170,8 +551,84 @@ pub enum AudioFormat {
     Flac,
 }
 
-/// Audio bit depth for recorded files
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Language for backend-generated text
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// Follow the operating system's language, English when unsupported
+    #[default]
+    System,
+    En,
+    De,
+    Fr,
+    Es,
+}
+
+/// Review step offered when a recording stops
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum QuickReviewMode {
+    #[default]
+    Off,
+    /// Show the window (from the tray if hidden) with the new session open
+    Open,
+    /// Replace the "Recording Saved" notification with one offering
+    /// Play, Favorite and Delete
+    Notify,
+}
+
+/// Release channel for update checks
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    /// Don't check for updates
+    Off,
+    #[default]
+    Stable,
+    /// Releases staged ahead of stable
+    Beta,
+}
+
+/// GStreamer install to use
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GstreamerSource {
+    /// The copy bundled with Sacho when there is one, else the system install
+    #[default]
+    Auto,
+    /// Only the bundled copy; system plugins are never loaded
+    Bundled,
+    /// The system install (GSTREAMER_1_0_ROOT_* on Windows)
+    System,
+}
+
+impl GstreamerSource {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Some(GstreamerSource::Auto),
+            "bundled" => Some(GstreamerSource::Bundled),
+            "system" => Some(GstreamerSource::System),
+            _ => None,
+        }
+    }
+}
+
+/// Where new sessions are created when multiple storage roots are configured
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageRouting {
+    /// Always record to the primary storage path
+    #[default]
+    Primary,
+    /// First root (primary, then additional in order) with at least
+    /// `storage_min_free_gb` free
+    FirstWithFreeSpace,
+    /// Root with the most free space
+    MostFreeSpace,
+}
+
+/// Audio bit depth for recorded files. Ordered from least to most precise.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "snake_case")]
 pub enum AudioBitDepth {
     /// 16-bit integer
@@ -188,6 +645,27 @@ impl Default for AudioBitDepth {
     }
 }
 
+impl AudioBitDepth {
+    /// Converter depth of a device from the sample formats it supports: the
+    /// deepest integer format. Float formats are the OS mixer's (WASAPI shared
+    /// mode and CoreAudio always offer F32) and say nothing about the
+    /// hardware, so a device offering only those has no known depth.
+    pub fn for_sample_formats(
+        sample_formats: impl IntoIterator<Item = cpal::SampleFormat>,
+    ) -> Option<Self> {
+        use cpal::SampleFormat;
+        sample_formats
+            .into_iter()
+            .filter_map(|format| match format {
+                SampleFormat::I8 | SampleFormat::U8 | SampleFormat::I16 | SampleFormat::U16 => Some(Self::Int16),
+                SampleFormat::F32 | SampleFormat::F64 => None,
+                // 24-bit converters deliver 32-bit integer samples
+                _ => Some(Self::Int24),
+            })
+            .max()
+    }
+}
+
 /// Audio sample rate for recorded files
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -226,6 +704,276 @@ impl AudioSampleRate {
     }
 }
 
+/// A single MIDI message sent as recording-state feedback.
+/// Channels are 0-based (0-15); data bytes are 0-127.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MidiFeedbackMessage {
+    /// Note-on with the given velocity (velocity 0 turns most pad LEDs off)
+    Note { channel: u8, note: u8, velocity: u8 },
+    /// Control change
+    ControlChange { channel: u8, controller: u8, value: u8 },
+    /// System exclusive message. The F0/F7 framing bytes are optional.
+    Sysex { data: Vec<u8> },
+}
+
+impl MidiFeedbackMessage {
+    /// Encode as raw MIDI bytes ready to send on an output port
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            MidiFeedbackMessage::Note { channel, note, velocity } => {
+                vec![0x90 | (channel & 0x0F), note & 0x7F, velocity & 0x7F]
+            }
+            MidiFeedbackMessage::ControlChange { channel, controller, value } => {
+                vec![0xB0 | (channel & 0x0F), controller & 0x7F, value & 0x7F]
+            }
+            MidiFeedbackMessage::Sysex { data } => {
+                let body = data.iter().filter(|&&b| b != 0xF0 && b != 0xF7).map(|b| b & 0x7F);
+                let mut bytes = vec![0xF0];
+                bytes.extend(body);
+                bytes.push(0xF7);
+                bytes
+            }
+        }
+    }
+}
+
+/// MIDI output feedback settings.
+/// When enabled, the stop message is also sent when trigger devices connect,
+/// so the controller shows the armed state before the first recording.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MidiFeedbackConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Sent to trigger devices when recording starts
+    #[serde(default)]
+    pub start_message: Option<MidiFeedbackMessage>,
+
+    /// Sent to trigger devices when recording stops (and on connect)
+    #[serde(default)]
+    pub stop_message: Option<MidiFeedbackMessage>,
+}
+
+impl Default for MidiFeedbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start_message: Some(MidiFeedbackMessage::Note { channel: 0, note: 0, velocity: 127 }),
+            stop_message: Some(MidiFeedbackMessage::Note { channel: 0, note: 0, velocity: 0 }),
+        }
+    }
+}
+
+/// MIDI thru routing from an input device to an output port
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MidiThruConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// MIDI device ID to forward from
+    #[serde(default)]
+    pub input_device: Option<String>,
+
+    /// Output port name to forward to (e.g. a virtual port a synth listens on)
+    #[serde(default)]
+    pub output_port: Option<String>,
+
+    /// 0-based channels to forward; empty forwards all of them
+    #[serde(default)]
+    pub channels: Vec<u8>,
+}
+
+/// How Sacho opens a MIDI input port. Some drivers allow only one client,
+/// so holding the port locks other MIDI software out of it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MidiAccessMode {
+    /// Hold the port while monitoring (needed for triggers and pre-roll)
+    Exclusive,
+    /// Open the port only while a recording runs, leaving it free in
+    /// between. The device can't trigger and has no pre-roll.
+    Shared,
+    /// Hold the port and republish its messages on a virtual port that
+    /// other software opens instead (macOS and Linux only)
+    VirtualTap,
+}
+
+impl Default for MidiAccessMode {
+    fn default() -> Self {
+        Self::Exclusive
+    }
+}
+
+impl MidiAccessMode {
+    /// Whether this platform's MIDI backend can provide the mode
+    pub fn is_supported(&self) -> bool {
+        match self {
+            MidiAccessMode::Exclusive | MidiAccessMode::Shared => true,
+            MidiAccessMode::VirtualTap => cfg!(unix),
+        }
+    }
+}
+
+/// Bounds for recorded MIDI file resolution (ticks per quarter note)
+pub const MIN_MIDI_TICKS_PER_QUARTER: u16 = 96;
+pub const MAX_MIDI_TICKS_PER_QUARTER: u16 = 960;
+
+/// Standard MIDI File timing for recorded MIDI.
+/// Files are written at 120 BPM while recording; with `tempo_map` set, the
+/// performance's tempo is detected afterwards and the file is rewritten so
+/// its bars and beats line up with the playing when imported into a DAW.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MidiFileConfig {
+    /// Ticks per quarter note (PPQ)
+    #[serde(default = "default_midi_ticks_per_quarter")]
+    pub ticks_per_quarter: u16,
+
+    /// Detect the tempo after recording and write it as the file's tempo map
+    #[serde(default)]
+    pub tempo_map: bool,
+}
+
+fn default_midi_ticks_per_quarter() -> u16 {
+    480
+}
+
+impl Default for MidiFileConfig {
+    fn default() -> Self {
+        Self {
+            ticks_per_quarter: default_midi_ticks_per_quarter(),
+            tempo_map: false,
+        }
+    }
+}
+
+/// Bitrate bounds for the live stream (kbit/s)
+pub const MIN_STREAM_VIDEO_BITRATE_KBPS: u32 = 300;
+pub const MAX_STREAM_VIDEO_BITRATE_KBPS: u32 = 20000;
+pub const MIN_STREAM_AUDIO_BITRATE_KBPS: u32 = 64;
+pub const MAX_STREAM_AUDIO_BITRATE_KBPS: u32 = 320;
+
+/// RTMP simulcast settings.
+/// The stream is encoded separately from the recording (H.264 + AAC in FLV),
+/// so a dropped connection or slow uplink never affects the files on disk.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RtmpStreamConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Server URL including the stream key (rtmp:// or rtmps://)
+    #[serde(default)]
+    pub url: String,
+
+    /// Video device to stream (None = first recording video device)
+    #[serde(default)]
+    pub video_device: Option<String>,
+
+    /// Audio device to stream (None = no audio)
+    #[serde(default)]
+    pub audio_device: Option<String>,
+
+    #[serde(default = "default_stream_video_bitrate_kbps")]
+    pub video_bitrate_kbps: u32,
+
+    #[serde(default = "default_stream_audio_bitrate_kbps")]
+    pub audio_bitrate_kbps: u32,
+}
+
+fn default_stream_video_bitrate_kbps() -> u32 {
+    2500
+}
+
+fn default_stream_audio_bitrate_kbps() -> u32 {
+    128
+}
+
+impl Default for RtmpStreamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            video_device: None,
+            audio_device: None,
+            video_bitrate_kbps: default_stream_video_bitrate_kbps(),
+            audio_bitrate_kbps: default_stream_audio_bitrate_kbps(),
+        }
+    }
+}
+
+/// Virtual camera output settings.
+/// Video-conference apps open the virtual camera instead of the physical one,
+/// which stays held by Sacho's capture pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VirtualCameraConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Video device to publish (None = first selected video device)
+    #[serde(default)]
+    pub video_device: Option<String>,
+
+    /// Loopback device to write to (v4l2loopback on Linux)
+    #[serde(default = "default_virtual_camera_device")]
+    pub output_device: String,
+
+    /// Draw live capture fps, encode fps and bitrate onto the published
+    /// frames, to check the camera is delivering the selected mode
+    #[serde(default)]
+    pub stats_overlay: bool,
+}
+
+fn default_virtual_camera_device() -> String {
+    "/dev/video10".to_string()
+}
+
+impl Default for VirtualCameraConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            video_device: None,
+            output_device: default_virtual_camera_device(),
+            stats_overlay: false,
+        }
+    }
+}
+
+/// File name templates for recordings, without extension.
+/// Placeholders: {device}, {codec}, {channels}, {take}.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FilenameTemplates {
+    #[serde(default = "default_audio_filename_template")]
+    pub audio: String,
+
+    #[serde(default = "default_midi_filename_template")]
+    pub midi: String,
+
+    #[serde(default = "default_video_filename_template")]
+    pub video: String,
+}
+
+fn default_audio_filename_template() -> String {
+    crate::session::filenames::DEFAULT_AUDIO_TEMPLATE.to_string()
+}
+
+fn default_midi_filename_template() -> String {
+    crate::session::filenames::DEFAULT_MIDI_TEMPLATE.to_string()
+}
+
+fn default_video_filename_template() -> String {
+    crate::session::filenames::DEFAULT_VIDEO_TEMPLATE.to_string()
+}
+
+impl Default for FilenameTemplates {
+    fn default() -> Self {
+        Self {
+            audio: default_audio_filename_template(),
+            midi: default_midi_filename_template(),
+            video: default_video_filename_template(),
+        }
+    }
+}
+
 /// Per-device video source configuration.
 /// Stores the selected source codec, source resolution/fps, encoding settings,
 /// and target encoding resolution/fps.
@@ -266,6 +1014,25 @@ pub struct VideoDeviceConfig {
     /// Only meaningful when encoding_codec = FFV1 and passthrough = false.
     #[serde(default)]
     pub video_bit_depth: Option<u8>,
+    /// Distance between keyframes when encoding. Default: 2 seconds.
+    #[serde(default)]
+    pub keyframe_interval: KeyframeInterval,
+    /// Maximum consecutive B-frames. None = encoder/preset default.
+    /// Ignored by encoders without B-frame support (VP8/VP9, SVT-AV1, FFV1).
+    #[serde(default)]
+    pub b_frames: Option<u32>,
+    /// Conform encoded video to a constant frame rate by duplicating or
+    /// dropping frames, for editors that handle variable frame rate poorly
+    #[serde(default)]
+    pub constant_frame_rate: bool,
+    /// What the device is pointed at (camera, screen capture, static shot),
+    /// used to tune the encoder. Only AV1 encoders act on it.
+    #[serde(default)]
+    pub content_type: crate::encoding::ContentType,
+    /// Region to keep sharp (e.g. the keyboard), in normalized 0–1 frame
+    /// coordinates. Supported encoders (QuickSync, VA) give it more bits.
+    #[serde(default)]
+    pub roi: Option<crate::encoding::RegionOfInterest>,
 
     // ── Target resolution/fps ──────────────────────────────────────────
     /// Target encoding width. 0 = smart default (match source if ≤1080p, else 1080p).
@@ -276,6 +1043,48 @@ pub struct VideoDeviceConfig {
     pub target_fps: f64,
 }
 
+/// Keyframe interval, as a duration or an exact frame count
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "unit", content = "value", rename_all = "lowercase")]
+pub enum KeyframeInterval {
+    Seconds(f64),
+    Frames(u32),
+}
+
+impl Default for KeyframeInterval {
+    fn default() -> Self {
+        KeyframeInterval::Seconds(2.0)
+    }
+}
+
+/// Bounds for `KeyframeInterval`; beyond 30 s pre-roll trimming has to hold
+/// an unreasonable amount of encoded video to land on a keyframe
+const MIN_KEYFRAME_INTERVAL_SECS: f64 = 0.1;
+const MAX_KEYFRAME_INTERVAL_SECS: f64 = 30.0;
+const MAX_KEYFRAME_INTERVAL_FRAMES: u32 = 3600;
+const MAX_B_FRAMES: u32 = 4;
+
+impl KeyframeInterval {
+    /// Interval in frames at the given encoding fps (at least 1)
+    pub fn frames(&self, fps: f64) -> u32 {
+        match *self {
+            KeyframeInterval::Seconds(secs) => ((fps * secs).round() as u32).max(1),
+            KeyframeInterval::Frames(frames) => frames.max(1),
+        }
+    }
+
+    /// Interval as a duration at the given encoding fps
+    pub fn duration(&self, fps: f64) -> std::time::Duration {
+        let secs = match *self {
+            KeyframeInterval::Seconds(secs) => secs,
+            KeyframeInterval::Frames(frames) if fps > 0.0 => frames as f64 / fps,
+            // Unknown fps: assume the default spacing
+            KeyframeInterval::Frames(_) => 2.0,
+        };
+        std::time::Duration::from_secs_f64(secs.max(0.0))
+    }
+}
+
 impl PartialEq for VideoDeviceConfig {
     fn eq(&self, other: &Self) -> bool {
         self.source_format == other.source_format
@@ -288,6 +1097,11 @@ impl PartialEq for VideoDeviceConfig {
             && self.preset_level == other.preset_level
             && self.effort_level == other.effort_level
             && self.video_bit_depth == other.video_bit_depth
+            && self.keyframe_interval == other.keyframe_interval
+            && self.b_frames == other.b_frames
+            && self.constant_frame_rate == other.constant_frame_rate
+            && self.content_type == other.content_type
+            && self.roi == other.roi
             && self.target_width == other.target_width
             && self.target_height == other.target_height
             && (self.target_fps - other.target_fps).abs() < 0.001
@@ -390,6 +1204,11 @@ impl VideoDeviceConfig {
             && self.encoding_codec == other.encoding_codec
             && self.encoder_type == other.encoder_type
             && self.video_bit_depth == other.video_bit_depth
+            && self.keyframe_interval == other.keyframe_interval
+            && self.b_frames == other.b_frames
+            && self.constant_frame_rate == other.constant_frame_rate
+            && self.content_type == other.content_type
+            && self.roi == other.roi
             && self.target_width == other.target_width
             && self.target_height == other.target_height
             && (self.target_fps - other.target_fps).abs() < 0.001
@@ -412,18 +1231,29 @@ impl Default for Config {
         Self {
             storage_path: get_default_storage_path(),
             idle_timeout_secs: 5,
-            pre_roll_secs: 2, // Default to 2 seconds of pre-roll
+            audio_midi_pre_roll_secs: 2, // Default to 2 seconds of pre-roll
+            video_pre_roll_secs: 2,
+            pre_roll_secs: None,
             audio_format: AudioFormat::Flac,
             wav_bit_depth: AudioBitDepth::default(),
             wav_sample_rate: AudioSampleRate::default(),
             flac_bit_depth: AudioBitDepth::default(),
+            native_bit_depth: true,
             flac_sample_rate: AudioSampleRate::default(),
+            resample_to_common_rate: false,
             dark_mode: false,
+            agent_mode: false,
+            read_only_library: false,
+            gstreamer_source: GstreamerSource::default(),
             auto_start: true,
             start_minimized: true,
             minimize_to_tray: true,
+            language: Language::System,
             notify_recording_start: false,
             notify_recording_stop: true,
+            quick_review: QuickReviewMode::Off,
+            update_channel: UpdateChannel::default(),
+            update_feed_url: None,
             sound_recording_start: false,
             sound_recording_stop: false,
             sound_volume_start: 1.0,
@@ -439,11 +1269,51 @@ impl Default for Config {
             trigger_midi_devices: Vec::new(),
             trigger_audio_devices: Vec::new(),
             audio_trigger_thresholds: HashMap::new(),
+            suppress_triggers_during_playback: false,
+            playback_monitor_device: None,
+            playback_monitor_threshold: default_playback_monitor_threshold(),
             selected_video_devices: Vec::new(),
             video_device_configs: HashMap::new(),
             encode_during_preroll: false,
+            prewarm_video_writer: false,
             combine_audio_video: false,
             preferred_video_container: ContainerFormat::Mp4,
+            midi_feedback: MidiFeedbackConfig::default(),
+            midi_thru: MidiThruConfig::default(),
+            midi_access_modes: HashMap::new(),
+            midi_file: MidiFileConfig::default(),
+            rtmp_stream: RtmpStreamConfig::default(),
+            virtual_camera: VirtualCameraConfig::default(),
+            watch_storage: true,
+            additional_storage_paths: Vec::new(),
+            shared_storage_path: None,
+            show_other_users_sessions: false,
+            storage_routing: StorageRouting::default(),
+            storage_min_free_gb: default_storage_min_free_gb(),
+            archive_path: None,
+            mobile_remote_enabled: false,
+            mobile_remote_port: 0,
+            lan_share_enabled: false,
+            lan_share_port: default_lan_share_port(),
+            share_link_expiry_hours: default_share_link_expiry_hours(),
+            generate_video_proxies: true,
+            generate_video_filmstrips: true,
+            detect_chapters: true,
+            filename_templates: FilenameTemplates::default(),
+            video_preview_max_height: default_video_preview_max_height(),
+            hardware_jpeg_decode: true,
+            recover_after_sleep: true,
+            resume_grace_secs: default_resume_grace_secs(),
+            battery_safe_mode: true,
+            battery_low_percent: default_battery_low_percent(),
+            battery_segment_minutes: default_battery_segment_minutes(),
+            privacy_pause_stops_cameras: true,
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            device_sync_offsets_ms: HashMap::new(),
+            audio_channel_splits: HashMap::new(),
+            audio_processing: HashMap::new(),
+            device_schedules: HashMap::new(),
+            auto_tag_rules: Vec::new(),
             device_presets: Vec::new(),
             current_preset: None,
         }
@@ -465,10 +1335,76 @@ impl Config {
             ));
         }
 
-        if self.pre_roll_secs > 30 {
-            let old = self.pre_roll_secs;
-            self.pre_roll_secs = self.pre_roll_secs.clamp(0, 30);
-            clamped.push(format!("pre_roll_secs: {} -> {}", old, self.pre_roll_secs));
+        if self.resume_grace_secs > 60 {
+            let old = self.resume_grace_secs;
+            self.resume_grace_secs = 60;
+            clamped.push(format!("resume_grace_secs: {} -> {}", old, self.resume_grace_secs));
+        }
+
+        if !(5..=80).contains(&self.battery_low_percent) {
+            let old = self.battery_low_percent;
+            self.battery_low_percent = self.battery_low_percent.clamp(5, 80);
+            clamped.push(format!("battery_low_percent: {} -> {}", old, self.battery_low_percent));
+        }
+
+        if !(1..=60).contains(&self.battery_segment_minutes) {
+            let old = self.battery_segment_minutes;
+            self.battery_segment_minutes = self.battery_segment_minutes.clamp(1, 60);
+            clamped.push(format!("battery_segment_minutes: {} -> {}", old, self.battery_segment_minutes));
+        }
+
+        if !(1..=24 * 30).contains(&self.share_link_expiry_hours) {
+            let old = self.share_link_expiry_hours;
+            self.share_link_expiry_hours = self.share_link_expiry_hours.clamp(1, 24 * 30);
+            clamped.push(format!("share_link_expiry_hours: {} -> {}", old, self.share_link_expiry_hours));
+        }
+
+        if !(10..=600).contains(&self.shutdown_timeout_secs) {
+            let old = self.shutdown_timeout_secs;
+            self.shutdown_timeout_secs = self.shutdown_timeout_secs.clamp(10, 600);
+            clamped.push(format!("shutdown_timeout_secs: {} -> {}", old, self.shutdown_timeout_secs));
+        }
+
+        if !(MIN_MIDI_TICKS_PER_QUARTER..=MAX_MIDI_TICKS_PER_QUARTER).contains(&self.midi_file.ticks_per_quarter) {
+            let old = self.midi_file.ticks_per_quarter;
+            self.midi_file.ticks_per_quarter = old.clamp(MIN_MIDI_TICKS_PER_QUARTER, MAX_MIDI_TICKS_PER_QUARTER);
+            clamped.push(format!("midi_file.ticks_per_quarter: {} -> {}", old, self.midi_file.ticks_per_quarter));
+        }
+
+        if self.midi_thru.channels.iter().any(|&c| c > 15) {
+            let old = self.midi_thru.channels.clone();
+            self.midi_thru.channels.retain(|&c| c <= 15);
+            clamped.push(format!("midi_thru.channels: {:?} -> {:?}", old, self.midi_thru.channels));
+        }
+
+        let defaults = FilenameTemplates::default();
+        for (field, template, default) in [
+            ("audio", &mut self.filename_templates.audio, defaults.audio),
+            ("midi", &mut self.filename_templates.midi, defaults.midi),
+            ("video", &mut self.filename_templates.video, defaults.video),
+        ] {
+            if let Err(e) = crate::session::filenames::validate_template(template) {
+                clamped.push(format!("filename_templates.{}: {} ({}) -> {}", field, template, e, default));
+                *template = default;
+            }
+        }
+
+        // Migrate legacy single pre_roll_secs to separate audio/MIDI and video
+        if let Some(legacy) = self.pre_roll_secs.take() {
+            self.audio_midi_pre_roll_secs = legacy;
+            self.video_pre_roll_secs = legacy;
+        }
+
+        if self.audio_midi_pre_roll_secs > 30 {
+            let old = self.audio_midi_pre_roll_secs;
+            self.audio_midi_pre_roll_secs = self.audio_midi_pre_roll_secs.clamp(0, 30);
+            clamped.push(format!("audio_midi_pre_roll_secs: {} -> {}", old, self.audio_midi_pre_roll_secs));
+        }
+
+        if self.video_pre_roll_secs > 30 {
+            let old = self.video_pre_roll_secs;
+            self.video_pre_roll_secs = self.video_pre_roll_secs.clamp(0, 30);
+            clamped.push(format!("video_pre_roll_secs: {} -> {}", old, self.video_pre_roll_secs));
         }
 
         // Migrate legacy single sound_volume to per-sound volumes
@@ -496,6 +1432,17 @@ impl Config {
             clamped.push(format!("sound_volume_disconnect: {} -> {}", old, self.sound_volume_disconnect));
         }
 
+        if self.video_preview_max_height != 0
+            && (self.video_preview_max_height < 240 || self.video_preview_max_height > 4320)
+        {
+            let old = self.video_preview_max_height;
+            self.video_preview_max_height = self.video_preview_max_height.clamp(240, 4320);
+            clamped.push(format!(
+                "video_preview_max_height: {} -> {}",
+                old, self.video_preview_max_height
+            ));
+        }
+
         for (key, value) in self.audio_trigger_thresholds.iter_mut() {
             if *value < 0.0 || *value > 1.0 {
                 let old = *value;
@@ -507,6 +1454,91 @@ impl Config {
             }
         }
 
+        if !(0.0..=1.0).contains(&self.playback_monitor_threshold) {
+            let old = self.playback_monitor_threshold;
+            self.playback_monitor_threshold = old.clamp(0.0, 1.0);
+            clamped.push(format!(
+                "playback_monitor_threshold: {} -> {}",
+                old, self.playback_monitor_threshold
+            ));
+        }
+
+        for (key, value) in self.device_sync_offsets_ms.iter_mut() {
+            if value.abs() > MAX_SYNC_OFFSET_MS {
+                let old = *value;
+                *value = (*value).clamp(-MAX_SYNC_OFFSET_MS, MAX_SYNC_OFFSET_MS);
+                clamped.push(format!("device_sync_offsets_ms[{}]: {} -> {}", key, old, *value));
+            }
+        }
+
+        for (key, stems) in self.audio_channel_splits.iter_mut() {
+            stems.retain(|stem| {
+                let valid = !stem.label.trim().is_empty()
+                    && (1..=2).contains(&stem.channels.len());
+                if !valid {
+                    clamped.push(format!(
+                        "audio_channel_splits[{}]: dropped stem '{}' with {} channels",
+                        key, stem.label, stem.channels.len()
+                    ));
+                }
+                valid
+            });
+        }
+        self.audio_channel_splits.retain(|_, stems| !stems.is_empty());
+
+        for (key, processing) in self.audio_processing.iter_mut() {
+            if !(0.0..=MAX_STEREO_WIDTH).contains(&processing.stereo_width) {
+                let old = processing.stereo_width;
+                processing.stereo_width = if old.is_finite() { old.clamp(0.0, MAX_STEREO_WIDTH) } else { 1.0 };
+                clamped.push(format!(
+                    "audio_processing[{}].stereo_width: {} -> {}",
+                    key, old, processing.stereo_width
+                ));
+            }
+            processing.invert_polarity.sort_unstable();
+            processing.invert_polarity.dedup();
+        }
+
+        for (key, schedule) in self.device_schedules.iter_mut() {
+            for window in &mut schedule.windows {
+                if window.start_minute > MINUTES_PER_DAY || window.end_minute > MINUTES_PER_DAY {
+                    let old = (window.start_minute, window.end_minute);
+                    window.start_minute = window.start_minute.min(MINUTES_PER_DAY);
+                    window.end_minute = window.end_minute.min(MINUTES_PER_DAY);
+                    clamped.push(format!(
+                        "device_schedules[{}] window: {:?} -> {:?}",
+                        key, old, (window.start_minute, window.end_minute)
+                    ));
+                }
+                window.days.retain(|&d| d < 7);
+                window.days.sort_unstable();
+                window.days.dedup();
+            }
+            schedule.windows.retain(|w| !w.days.is_empty() && w.start_minute != w.end_minute);
+        }
+        // A schedule with no windows would keep the device off for good;
+        // removing the schedule is how a device goes back to always-on
+        self.device_schedules.retain(|_, schedule| !schedule.windows.is_empty());
+
+        for rule in &mut self.auto_tag_rules {
+            rule.tag = rule.tag.trim().to_string();
+            for bound in [&mut rule.min_duration_secs, &mut rule.max_duration_secs] {
+                if bound.is_some_and(|secs| !secs.is_finite() || secs < 0.0) {
+                    clamped.push(format!("auto_tag_rules[{}] duration bound: {:?} -> None", rule.tag, bound));
+                    *bound = None;
+                }
+            }
+            if let Some(window) = &mut rule.started_within {
+                window.start_minute = window.start_minute.min(MINUTES_PER_DAY);
+                window.end_minute = window.end_minute.min(MINUTES_PER_DAY);
+                window.days.retain(|&d| d < 7);
+                window.days.sort_unstable();
+                window.days.dedup();
+            }
+        }
+        // A rule without a tag has nothing to apply
+        self.auto_tag_rules.retain(|rule| !rule.tag.is_empty());
+
         // Validate per-device preset levels and effort levels
         for (key, dev_config) in self.video_device_configs.iter_mut() {
             if dev_config.preset_level < 1 || dev_config.preset_level > 5 {
@@ -525,7 +1557,83 @@ impl Config {
                     key, old, dev_config.effort_level
                 ));
             }
+            let interval = dev_config.keyframe_interval;
+            dev_config.keyframe_interval = match interval {
+                KeyframeInterval::Seconds(secs) if !secs.is_finite() => KeyframeInterval::default(),
+                KeyframeInterval::Seconds(secs) => KeyframeInterval::Seconds(
+                    secs.clamp(MIN_KEYFRAME_INTERVAL_SECS, MAX_KEYFRAME_INTERVAL_SECS),
+                ),
+                KeyframeInterval::Frames(frames) => {
+                    KeyframeInterval::Frames(frames.clamp(1, MAX_KEYFRAME_INTERVAL_FRAMES))
+                }
+            };
+            if dev_config.keyframe_interval != interval {
+                clamped.push(format!(
+                    "video_device_configs[{}].keyframe_interval: {:?} -> {:?}",
+                    key, interval, dev_config.keyframe_interval
+                ));
+            }
+            if let Some(b_frames) = dev_config.b_frames.filter(|&b| b > MAX_B_FRAMES) {
+                dev_config.b_frames = Some(MAX_B_FRAMES);
+                clamped.push(format!(
+                    "video_device_configs[{}].b_frames: {} -> {}",
+                    key, b_frames, MAX_B_FRAMES
+                ));
+            }
+            if let Some(roi) = dev_config.roi {
+                dev_config.roi = roi.clipped();
+                if dev_config.roi != Some(roi) {
+                    clamped.push(format!(
+                        "video_device_configs[{}].roi: {:?} -> {:?}",
+                        key, roi, dev_config.roi
+                    ));
+                }
+            }
+        }
+
+        // Validate MIDI feedback channels and data bytes
+        for (label, message) in [
+            ("start_message", &mut self.midi_feedback.start_message),
+            ("stop_message", &mut self.midi_feedback.stop_message),
+        ] {
+            let (channel, data1, data2) = match message {
+                Some(MidiFeedbackMessage::Note { channel, note, velocity }) => (channel, note, velocity),
+                Some(MidiFeedbackMessage::ControlChange { channel, controller, value }) => (channel, controller, value),
+                _ => continue,
+            };
+            if *channel > 15 || *data1 > 127 || *data2 > 127 {
+                let old = (*channel, *data1, *data2);
+                *channel = (*channel).min(15);
+                *data1 = (*data1).min(127);
+                *data2 = (*data2).min(127);
+                clamped.push(format!(
+                    "midi_feedback.{}: {:?} -> {:?}",
+                    label, old, (*channel, *data1, *data2)
+                ));
+            }
+        }
+
+        let stream = &mut self.rtmp_stream;
+        if stream.video_bitrate_kbps < MIN_STREAM_VIDEO_BITRATE_KBPS
+            || stream.video_bitrate_kbps > MAX_STREAM_VIDEO_BITRATE_KBPS
+        {
+            let old = stream.video_bitrate_kbps;
+            stream.video_bitrate_kbps = old.clamp(MIN_STREAM_VIDEO_BITRATE_KBPS, MAX_STREAM_VIDEO_BITRATE_KBPS);
+            clamped.push(format!("rtmp_stream.video_bitrate_kbps: {} -> {}", old, stream.video_bitrate_kbps));
         }
+        if stream.audio_bitrate_kbps < MIN_STREAM_AUDIO_BITRATE_KBPS
+            || stream.audio_bitrate_kbps > MAX_STREAM_AUDIO_BITRATE_KBPS
+        {
+            let old = stream.audio_bitrate_kbps;
+            stream.audio_bitrate_kbps = old.clamp(MIN_STREAM_AUDIO_BITRATE_KBPS, MAX_STREAM_AUDIO_BITRATE_KBPS);
+            clamped.push(format!("rtmp_stream.audio_bitrate_kbps: {} -> {}", old, stream.audio_bitrate_kbps));
+        }
+        stream.url = stream.url.trim().to_string();
+
+        // Drop duplicate or primary entries from the additional storage roots
+        let primary = self.storage_path.clone();
+        let mut seen = std::collections::HashSet::new();
+        self.additional_storage_paths.retain(|p| *p != primary && seen.insert(p.clone()));
 
         if !clamped.is_empty() {
             println!("[Sacho] Config validation clamped: {:?}", clamped);
@@ -558,6 +1666,292 @@ impl Config {
         Self::default()
     }
 
+    /// Root new sessions are recorded into: the user's folder under the
+    /// shared root if one is set, otherwise `storage_path`
+    pub fn primary_storage_root(&self) -> PathBuf {
+        match &self.shared_storage_path {
+            Some(shared) => shared.join(user_folder_name(&crate::session::current_user())),
+            None => self.storage_path.clone(),
+        }
+    }
+
+    /// All storage roots, primary first
+    pub fn storage_roots(&self) -> Vec<PathBuf> {
+        let mut roots = vec![self.primary_storage_root()];
+        for root in std::iter::once(&self.storage_path).chain(&self.additional_storage_paths) {
+            if !roots.contains(root) {
+                roots.push(root.clone());
+            }
+        }
+        roots
+    }
+
+    /// Folder of a session in whichever root holds it.
+    /// Falls back to the primary root if the session isn't found on disk.
+    pub fn session_path(&self, session_id: &str) -> PathBuf {
+        self.storage_roots()
+            .into_iter()
+            .map(|root| root.join(session_id))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| self.primary_storage_root().join(session_id))
+    }
+
+    /// Whether a session that isn't found on disk may live on a storage root
+    /// that is currently unreachable (drive unplugged, NAS offline)
+    pub fn session_offline(&self, session_id: &str) -> bool {
+        let (reachable, unreachable): (Vec<PathBuf>, Vec<PathBuf>) =
+            self.storage_roots().into_iter().partition(|root| root.exists());
+        !unreachable.is_empty() && !reachable.iter().any(|root| root.join(session_id).exists())
+    }
+
+    /// Latency correction for a device (see [`lookup_sync_offset_ms`])
+    pub fn sync_offset_ms(&self, device_name: &str) -> i32 {
+        lookup_sync_offset_ms(&self.device_sync_offsets_ms, device_name)
+    }
+
+    /// Replace a device's latency correction (0 removes it), clamped to
+    /// [`MAX_SYNC_OFFSET_MS`]
+    pub fn set_sync_offset_ms(&mut self, device_name: &str, offset_ms: i32) {
+        let safe_name = crate::session::sanitize_device_name(device_name);
+        self.device_sync_offsets_ms
+            .retain(|name, _| crate::session::sanitize_device_name(name) != safe_name);
+        if offset_ms != 0 {
+            self.device_sync_offsets_ms.insert(
+                device_name.to_string(),
+                offset_ms.clamp(-MAX_SYNC_OFFSET_MS, MAX_SYNC_OFFSET_MS),
+            );
+        }
+    }
+
+    /// Bit depth to record a device at: its native depth when known (and
+    /// native recording is on), never above the audio format's setting
+    pub fn bit_depth_for(&self, native: Option<&AudioBitDepth>) -> AudioBitDepth {
+        let cap = match self.audio_format {
+            AudioFormat::Wav => &self.wav_bit_depth,
+            AudioFormat::Flac => &self.flac_bit_depth,
+        };
+        match native {
+            Some(native) if self.native_bit_depth => native.min(cap).clone(),
+            _ => cap.clone(),
+        }
+    }
+
+    /// Access mode for a MIDI device, falling back to exclusive where the
+    /// chosen mode isn't supported
+    pub fn midi_access_mode(&self, device_id: &str) -> MidiAccessMode {
+        self.midi_access_modes
+            .get(device_id)
+            .copied()
+            .filter(|mode| mode.is_supported())
+            .unwrap_or_default()
+    }
+
+    /// Output sample rate setting for the current audio format
+    pub fn audio_sample_rate(&self) -> &AudioSampleRate {
+        match self.audio_format {
+            AudioFormat::Wav => &self.wav_sample_rate,
+            AudioFormat::Flac => &self.flac_sample_rate,
+        }
+    }
+
+    /// Stems configured for a device, limited to those whose channels exist
+    /// on an input with `source_channels` channels. Empty means record one
+    /// interleaved file.
+    pub fn audio_channel_stems(&self, device_name: &str, source_channels: u16) -> Vec<AudioChannelStem> {
+        let safe_name = crate::session::sanitize_device_name(device_name);
+        self.audio_channel_splits
+            .get(device_name)
+            .or_else(|| {
+                self.audio_channel_splits
+                    .iter()
+                    .find(|(name, _)| crate::session::sanitize_device_name(name) == safe_name)
+                    .map(|(_, stems)| stems)
+            })
+            .map(|stems| {
+                stems
+                    .iter()
+                    .filter(|stem| stem.channels.iter().all(|&ch| ch < source_channels))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Point every reference to device `old_id` at `new_id`, for a device that
+    /// came back under a different ID (see [`crate::devices::aliases`]).
+    /// Name-keyed settings move too, since audio IDs are device names.
+    /// Returns true if anything changed.
+    pub fn rename_device_id(&mut self, old_id: &str, new_id: &str) -> bool {
+        fn replace_in(list: &mut Vec<String>, old_id: &str, new_id: &str) -> bool {
+            if !list.iter().any(|id| id == old_id) {
+                return false;
+            }
+            if list.iter().any(|id| id == new_id) {
+                list.retain(|id| id != old_id);
+            } else {
+                for id in list.iter_mut().filter(|id| *id == old_id) {
+                    *id = new_id.to_string();
+                }
+            }
+            true
+        }
+        fn rekey<V>(map: &mut HashMap<String, V>, old_id: &str, new_id: &str) -> bool {
+            if map.contains_key(new_id) {
+                return false;
+            }
+            match map.remove(old_id) {
+                Some(value) => {
+                    map.insert(new_id.to_string(), value);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        let mut changed = false;
+        changed |= replace_in(&mut self.selected_audio_devices, old_id, new_id);
+        changed |= replace_in(&mut self.selected_midi_devices, old_id, new_id);
+        changed |= replace_in(&mut self.trigger_midi_devices, old_id, new_id);
+        changed |= replace_in(&mut self.trigger_audio_devices, old_id, new_id);
+        changed |= replace_in(&mut self.selected_video_devices, old_id, new_id);
+        for preset in &mut self.device_presets {
+            changed |= replace_in(&mut preset.audio_devices, old_id, new_id);
+            changed |= replace_in(&mut preset.midi_devices, old_id, new_id);
+            changed |= replace_in(&mut preset.trigger_midi_devices, old_id, new_id);
+            changed |= replace_in(&mut preset.trigger_audio_devices, old_id, new_id);
+            changed |= replace_in(&mut preset.video_devices, old_id, new_id);
+        }
+        changed |= rekey(&mut self.video_device_configs, old_id, new_id);
+        changed |= rekey(&mut self.audio_trigger_thresholds, old_id, new_id);
+        changed |= rekey(&mut self.device_sync_offsets_ms, old_id, new_id);
+        changed |= rekey(&mut self.audio_channel_splits, old_id, new_id);
+        changed |= rekey(&mut self.audio_processing, old_id, new_id);
+        changed |= rekey(&mut self.device_schedules, old_id, new_id);
+        changed |= rekey(&mut self.midi_access_modes, old_id, new_id);
+        if self.midi_thru.input_device.as_deref() == Some(old_id) {
+            self.midi_thru.input_device = Some(new_id.to_string());
+            changed = true;
+        }
+        changed
+    }
+
+    /// Every reference to a legacy port-index MIDI ID ("midi-2"), across the
+    /// same fields `rename_device_id` updates
+    fn legacy_midi_ids(&self) -> Vec<String> {
+        let lists = [
+            &self.selected_audio_devices,
+            &self.selected_midi_devices,
+            &self.trigger_midi_devices,
+            &self.trigger_audio_devices,
+            &self.selected_video_devices,
+        ];
+        let preset_lists = self.device_presets.iter().flat_map(|preset| {
+            [
+                &preset.audio_devices,
+                &preset.midi_devices,
+                &preset.trigger_midi_devices,
+                &preset.trigger_audio_devices,
+                &preset.video_devices,
+            ]
+        });
+        let mut ids: Vec<String> = lists
+            .into_iter()
+            .chain(preset_lists)
+            .flatten()
+            .chain(self.video_device_configs.keys())
+            .chain(self.audio_trigger_thresholds.keys())
+            .chain(self.device_sync_offsets_ms.keys())
+            .chain(self.audio_channel_splits.keys())
+            .chain(self.audio_processing.keys())
+            .chain(self.device_schedules.keys())
+            .chain(self.midi_access_modes.keys())
+            .chain(self.midi_thru.input_device.as_ref())
+            .filter(|id| crate::devices::is_legacy_midi_id(id))
+            .cloned()
+            .collect();
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    /// Move MIDI references saved under port-index IDs ("midi-2") to the
+    /// stable name-based IDs, using the ports listed at startup. Runs once:
+    /// after a hot-plug the indices point at other devices, so IDs of ports
+    /// that weren't connected are dropped rather than migrated later.
+    /// Returns true if anything changed.
+    pub fn migrate_legacy_midi_ids(&mut self, midi_devices: &[crate::devices::MidiDevice]) -> bool {
+        if self.legacy_midi_ids().is_empty() {
+            return false;
+        }
+        for device in midi_devices {
+            let legacy_id = format!("midi-{}", device.port_index);
+            if self.rename_device_id(&legacy_id, &device.id) {
+                println!("[Sacho] Migrated MIDI device {} to {}", legacy_id, device.id);
+            }
+        }
+
+        let dropped = self.legacy_midi_ids();
+        if dropped.is_empty() {
+            return true;
+        }
+        println!("[Sacho] Dropped MIDI devices not connected at migration: {:?}", dropped);
+        let is_legacy = |id: &String| crate::devices::is_legacy_midi_id(id);
+        for list in [
+            &mut self.selected_audio_devices,
+            &mut self.selected_midi_devices,
+            &mut self.trigger_midi_devices,
+            &mut self.trigger_audio_devices,
+            &mut self.selected_video_devices,
+        ] {
+            list.retain(|id| !is_legacy(id));
+        }
+        for preset in &mut self.device_presets {
+            for list in [
+                &mut preset.audio_devices,
+                &mut preset.midi_devices,
+                &mut preset.trigger_midi_devices,
+                &mut preset.trigger_audio_devices,
+                &mut preset.video_devices,
+            ] {
+                list.retain(|id| !is_legacy(id));
+            }
+        }
+        self.video_device_configs.retain(|id, _| !is_legacy(id));
+        self.audio_trigger_thresholds.retain(|id, _| !is_legacy(id));
+        self.device_sync_offsets_ms.retain(|id, _| !is_legacy(id));
+        self.audio_channel_splits.retain(|id, _| !is_legacy(id));
+        self.audio_processing.retain(|id, _| !is_legacy(id));
+        self.device_schedules.retain(|id, _| !is_legacy(id));
+        self.midi_access_modes.retain(|id, _| !is_legacy(id));
+        if self.midi_thru.input_device.as_ref().is_some_and(is_legacy) {
+            self.midi_thru.input_device = None;
+        }
+        true
+    }
+
+    /// Video pre-roll in effect, given the encode-during-pre-roll limit
+    pub fn effective_video_pre_roll_secs(&self) -> u32 {
+        let limit = if self.encode_during_preroll {
+            crate::recording::preroll::MAX_PRE_ROLL_SECS_ENCODED
+        } else {
+            crate::recording::preroll::MAX_PRE_ROLL_SECS
+        };
+        self.video_pre_roll_secs.min(limit)
+    }
+
+    /// True when a device has a schedule and is outside its windows now
+    pub fn is_scheduled_off(&self, device_key: &str) -> bool {
+        self.device_schedules
+            .get(device_key)
+            .is_some_and(|schedule| !schedule.is_open_at(chrono::Local::now().naive_local()))
+    }
+
+    /// Audio/MIDI pre-roll in effect
+    pub fn effective_audio_midi_pre_roll_secs(&self) -> u32 {
+        self.audio_midi_pre_roll_secs
+            .min(crate::recording::preroll::MAX_AUDIO_MIDI_PRE_ROLL_SECS)
+    }
+
     /// Save config to disk
     pub fn save(&self, app_handle: &AppHandle) -> anyhow::Result<()> {
         let config_path = get_config_path(app_handle);
@@ -582,6 +1976,15 @@ fn get_default_storage_path() -> PathBuf {
         .join("Sacho")
 }
 
+/// Per-user folder name under the shared storage root
+fn user_folder_name(user: &str) -> String {
+    let name: String = user
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    if name.trim_matches('.').is_empty() { "default".to_string() } else { name }
+}
+
 /// Get the config file path
 fn get_config_path(app_handle: &AppHandle) -> PathBuf {
     app_handle
@@ -596,6 +1999,46 @@ fn default_pre_roll_secs() -> u32 {
     2
 }
 
+/// Default playback monitor level (-50 dBFS)
+fn default_playback_monitor_threshold() -> f64 {
+    0.003
+}
+
+/// Default free space required for a storage root to receive new sessions
+fn default_storage_min_free_gb() -> u32 {
+    20
+}
+
+fn default_video_preview_max_height() -> u32 {
+    1080
+}
+
+/// Default wait after resume before rebuilding device pipelines
+fn default_resume_grace_secs() -> u32 {
+    5
+}
+
+/// Default battery charge that enables record-safe mode
+fn default_battery_low_percent() -> u8 {
+    20
+}
+
+fn default_battery_segment_minutes() -> u32 {
+    5
+}
+
+fn default_lan_share_port() -> u16 {
+    8765
+}
+
+fn default_share_link_expiry_hours() -> u32 {
+    24
+}
+
+fn default_shutdown_timeout_secs() -> u32 {
+    120
+}
+
 /// Default true value (for serde)
 fn default_true() -> bool {
     true
@@ -0,0 +1,395 @@
+// Crash reports
+//
+// Three ways a run can end badly, each leaving a report folder under the app
+// data dir's `crash-reports/` with a report.json and the last log lines:
+//
+// - a Rust panic (on any thread): the panic hook writes the message,
+//   location and a backtrace before the default hook runs;
+// - a native exception on Windows (access violation and the like): an
+//   unhandled-exception filter also writes a minidump next to the report;
+// - anything that kills the process without either hook running (abort,
+//   fast-fail, power loss, a kill from Task Manager): the `running` marker
+//   left behind by the run is found on the next launch, and its log, kept
+//   on disk line by line, becomes an "unexpected exit" report.
+//
+// The UI lists reports with `get_pending_crash_reports` on launch so the user
+// can look at them, file an issue, and dismiss them. This sits alongside the
+// Windows Error Reporting restart registration, which brings Sacho back up.
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Folder (inside the app data dir) that reports are written to
+pub const CRASH_DIR_NAME: &str = "crash-reports";
+
+/// Log lines kept in memory and copied into each report
+const LOG_TAIL_LINES: usize = 200;
+
+/// The current run's log, appended line by line
+const RUN_LOG_NAME: &str = "current-run.log";
+
+/// Present while the app runs; holds the run id
+const RUNNING_MARKER_NAME: &str = "running";
+
+const REPORT_FILE_NAME: &str = "report.json";
+const REPORT_LOG_NAME: &str = "log.txt";
+const MINIDUMP_NAME: &str = "minidump.dmp";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CrashKind {
+    Panic,
+    /// Unhandled native exception (Windows), with a minidump
+    NativeException,
+    /// The previous run ended without shutting down or reporting a crash
+    UnexpectedExit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Folder name of the report
+    #[serde(default)]
+    pub id: String,
+    pub kind: CrashKind,
+    pub timestamp: DateTime<Utc>,
+    pub app_version: String,
+    /// Operating system and architecture, e.g. "windows x86_64"
+    pub os: String,
+    pub message: String,
+    pub backtrace: Option<String>,
+    pub has_minidump: bool,
+    /// Run the report belongs to (matches the `running` marker)
+    pub run_id: String,
+    /// Folder holding the report, log and minidump
+    #[serde(default)]
+    pub path: String,
+}
+
+/// Report folder, set once the app data dir is known
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Id of this run, written to the marker and to every report
+static RUN_ID: OnceLock<String> = OnceLock::new();
+
+static LOG_TAIL: Mutex<LogTail> = Mutex::new(LogTail { lines: VecDeque::new(), file: None });
+
+struct LogTail {
+    lines: VecDeque<String>,
+    /// The run log on disk, once the crash folder is known
+    file: Option<File>,
+}
+
+/// Print a line to stdout and keep it in the crash log. The crate's
+/// `println!` expands to this.
+pub fn print_line(line: String) {
+    std::println!("{}", line);
+    log_line(format!("{} {}", Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"), line));
+}
+
+/// Record a log line in the crash log (also done for every `log` record)
+pub fn log_line(line: String) {
+    let Ok(mut tail) = LOG_TAIL.lock() else {
+        return;
+    };
+    if let Some(file) = tail.file.as_mut() {
+        let _ = writeln!(file, "{}", line);
+    }
+    if tail.lines.len() == LOG_TAIL_LINES {
+        tail.lines.pop_front();
+    }
+    tail.lines.push_back(line);
+}
+
+fn log_tail() -> String {
+    match LOG_TAIL.lock() {
+        Ok(tail) => tail.lines.iter().map(|l| format!("{}\n", l)).collect(),
+        // Poisoned by a panic while logging; the report goes out without it
+        Err(_) => String::new(),
+    }
+}
+
+/// `log` backend that keeps the crash log and, with `--console`, also
+/// prints through env_logger
+struct CrashLogger {
+    console: Option<env_logger::Logger>,
+}
+
+impl log::Log for CrashLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Info
+            || self.console.as_ref().is_some_and(|c| c.enabled(metadata))
+    }
+
+    fn log(&self, record: &log::Record) {
+        if record.level() <= log::Level::Info {
+            log_line(format!(
+                "{} {:<5} {}: {}",
+                Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                record.level(),
+                record.target(),
+                record.args()
+            ));
+        }
+        if let Some(console) = &self.console {
+            if console.matches(record) {
+                console.log(record);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(console) = &self.console {
+            console.flush();
+        }
+    }
+}
+
+/// Install the logger. `console` also prints log output (the `--console` flag).
+pub fn init_logger(console: bool) {
+    let console = console.then(|| {
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).build()
+    });
+    let max_level = console
+        .as_ref()
+        .map(|c| c.filter().max(log::LevelFilter::Info))
+        .unwrap_or(log::LevelFilter::Info);
+    if log::set_boxed_logger(Box::new(CrashLogger { console })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// Start crash reporting for this run: report an unclean end of the last
+/// run, start the run log and marker, and install the crash hooks
+pub fn install(app_data_dir: &Path) {
+    let dir = app_data_dir.join(CRASH_DIR_NAME);
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        println!("[Sacho] Crash reports disabled, can't create {}: {}", dir.display(), e);
+        return;
+    }
+    let run_id = uuid::Uuid::new_v4().to_string();
+
+    // A marker left behind means the last run never reached a clean exit
+    let marker = dir.join(RUNNING_MARKER_NAME);
+    if let Ok(previous_run) = std::fs::read_to_string(&marker) {
+        let previous_run = previous_run.trim().to_string();
+        let already_reported = list_reports(&dir).iter().any(|r| r.run_id == previous_run);
+        if !already_reported {
+            let log = std::fs::read_to_string(dir.join(RUN_LOG_NAME)).unwrap_or_default();
+            let tail = last_lines(&log, LOG_TAIL_LINES);
+            let report = new_report(
+                CrashKind::UnexpectedExit,
+                "Sacho stopped without shutting down (the process was killed, aborted or the computer lost power)".to_string(),
+                None,
+                previous_run,
+            );
+            match write_report(&dir, report, &tail) {
+                Ok(path) => println!("[Sacho] Previous run ended unexpectedly, report in {}", path.display()),
+                Err(e) => println!("[Sacho] Failed to write crash report: {}", e),
+            }
+        }
+    }
+
+    if let Err(e) = std::fs::write(&marker, &run_id) {
+        println!("[Sacho] Failed to write run marker: {}", e);
+    }
+    if let Ok(mut tail) = LOG_TAIL.lock() {
+        if let Ok(mut file) = File::create(dir.join(RUN_LOG_NAME)) {
+            // Lines logged before the folder was known
+            for line in &tail.lines {
+                let _ = writeln!(file, "{}", line);
+            }
+            tail.file = Some(file);
+        }
+    }
+    let _ = RUN_ID.set(run_id);
+    let _ = CRASH_DIR.set(dir);
+
+    install_panic_hook();
+    #[cfg(windows)]
+    native::install();
+}
+
+/// The run ended normally: no report on the next launch
+pub fn mark_clean_exit() {
+    if let Some(dir) = CRASH_DIR.get() {
+        let _ = std::fs::remove_file(dir.join(RUNNING_MARKER_NAME));
+    }
+}
+
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        let thread = std::thread::current();
+        let message = match info.location() {
+            Some(location) => format!(
+                "thread '{}' panicked at {}:{}: {}",
+                thread.name().unwrap_or("<unnamed>"),
+                location.file(),
+                location.line(),
+                payload
+            ),
+            None => format!("thread '{}' panicked: {}", thread.name().unwrap_or("<unnamed>"), payload),
+        };
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        if let Some(dir) = CRASH_DIR.get() {
+            let report = new_report(CrashKind::Panic, message, Some(backtrace), run_id());
+            if let Err(e) = write_report(dir, report, &log_tail()) {
+                eprintln!("[Sacho] Failed to write crash report: {}", e);
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+fn run_id() -> String {
+    RUN_ID.get().cloned().unwrap_or_default()
+}
+
+fn new_report(kind: CrashKind, message: String, backtrace: Option<String>, run_id: String) -> CrashReport {
+    CrashReport {
+        id: String::new(),
+        kind,
+        timestamp: Utc::now(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        message,
+        backtrace,
+        has_minidump: false,
+        run_id,
+        path: String::new(),
+    }
+}
+
+/// Create a report folder with report.json and the log tail. Returns the folder.
+fn write_report(dir: &Path, mut report: CrashReport, log: &str) -> std::io::Result<PathBuf> {
+    let kind = match report.kind {
+        CrashKind::Panic => "panic",
+        CrashKind::NativeException => "exception",
+        CrashKind::UnexpectedExit => "unexpected-exit",
+    };
+    let stamp = report.timestamp.format("%Y%m%d-%H%M%S").to_string();
+    // Several threads can panic in the same second
+    let mut folder = dir.join(format!("{}-{}", stamp, kind));
+    let mut n = 2;
+    while folder.exists() {
+        folder = dir.join(format!("{}-{}-{}", stamp, kind, n));
+        n += 1;
+    }
+    std::fs::create_dir_all(&folder)?;
+    report.id = folder.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    std::fs::write(folder.join(REPORT_LOG_NAME), log)?;
+    let json = serde_json::to_string_pretty(&report).map_err(std::io::Error::other)?;
+    std::fs::write(folder.join(REPORT_FILE_NAME), json)?;
+    Ok(folder)
+}
+
+fn last_lines(text: &str, count: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(count)..].iter().map(|l| format!("{}\n", l)).collect()
+}
+
+fn list_reports(dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let text = std::fs::read_to_string(e.path().join(REPORT_FILE_NAME)).ok()?;
+            let mut report: CrashReport = serde_json::from_str(&text).ok()?;
+            report.id = e.file_name().to_string_lossy().to_string();
+            report.path = e.path().to_string_lossy().to_string();
+            report.has_minidump = e.path().join(MINIDUMP_NAME).exists();
+            Some(report)
+        })
+        .collect();
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    reports
+}
+
+/// Reports the user hasn't dismissed, newest first
+pub fn pending_reports() -> Vec<CrashReport> {
+    CRASH_DIR.get().map(|dir| list_reports(dir)).unwrap_or_default()
+}
+
+/// Delete a report folder
+pub fn dismiss_report(id: &str) -> std::io::Result<()> {
+    let dir = CRASH_DIR.get().ok_or_else(|| std::io::Error::other("Crash reports are disabled"))?;
+    // Ids are folder names; refuse anything that would leave the folder
+    if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid report id"));
+    }
+    std::fs::remove_dir_all(dir.join(id))
+}
+
+#[cfg(windows)]
+mod native {
+    use std::os::windows::io::AsRawHandle;
+
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        MiniDumpNormal, MiniDumpWriteDump, SetUnhandledExceptionFilter, EXCEPTION_POINTERS,
+        MINIDUMP_EXCEPTION_INFORMATION,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId};
+
+    use super::*;
+
+    /// Let the exception continue to WER (which restarts the app)
+    const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+
+    pub fn install() {
+        unsafe {
+            SetUnhandledExceptionFilter(Some(on_unhandled_exception));
+        }
+    }
+
+    unsafe extern "system" fn on_unhandled_exception(info: *const EXCEPTION_POINTERS) -> i32 {
+        let Some(dir) = CRASH_DIR.get() else {
+            return EXCEPTION_CONTINUE_SEARCH;
+        };
+        let code = if info.is_null() || (*info).ExceptionRecord.is_null() {
+            0
+        } else {
+            (*(*info).ExceptionRecord).ExceptionCode as u32
+        };
+        let report = new_report(
+            CrashKind::NativeException,
+            format!("Unhandled exception 0x{:08X}", code),
+            None,
+            run_id(),
+        );
+        let Ok(folder) = write_report(dir, report, &log_tail()) else {
+            return EXCEPTION_CONTINUE_SEARCH;
+        };
+        if let Ok(file) = File::create(folder.join(MINIDUMP_NAME)) {
+            let exception = MINIDUMP_EXCEPTION_INFORMATION {
+                ThreadId: GetCurrentThreadId(),
+                ExceptionPointers: info as *mut EXCEPTION_POINTERS,
+                ClientPointers: 0,
+            };
+            MiniDumpWriteDump(
+                GetCurrentProcess(),
+                GetCurrentProcessId(),
+                file.as_raw_handle() as _,
+                MiniDumpNormal,
+                &exception,
+                std::ptr::null(),
+                std::ptr::null(),
+            );
+        }
+        EXCEPTION_CONTINUE_SEARCH
+    }
+}
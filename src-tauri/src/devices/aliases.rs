@@ -0,0 +1,215 @@
+// Persistent device aliases
+//
+// Device names and IDs shift when a device moves to another USB port or a
+// second one like it is plugged in ("USB Camera" becomes "USB Camera (2)").
+// An alias gives a device a stable, user-chosen name ("Hands cam", "Grand
+// piano") and a rule for recognizing it: a name pattern (`*` matches any run
+// of characters, case-insensitive) and, for video devices, optionally the
+// USB path. Recordings are named and labeled with the alias, and when an
+// aliased device shows up under a new ID its configuration moves to it.
+//
+// Aliases are stored in the session database; the loaded set is cached here
+// so the recording path can resolve labels without touching the database.
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::config::Config;
+use crate::session::SessionDatabase;
+
+use super::DeviceManager;
+
+/// Loaded aliases, in creation order
+static ALIASES: RwLock<Vec<DeviceAlias>> = RwLock::new(Vec::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceKind {
+    Audio,
+    Midi,
+    Video,
+}
+
+impl DeviceKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeviceKind::Audio => "audio",
+            DeviceKind::Midi => "midi",
+            DeviceKind::Video => "video",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "audio" => Some(DeviceKind::Audio),
+            "midi" => Some(DeviceKind::Midi),
+            "video" => Some(DeviceKind::Video),
+            _ => None,
+        }
+    }
+}
+
+/// A stable name for a device and the rule that recognizes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceAlias {
+    /// Database row ID (0 for an alias not yet saved)
+    #[serde(default)]
+    pub id: i64,
+    pub alias: String,
+    pub device_type: DeviceKind,
+    /// Device name to match; `*` matches any run of characters
+    pub name_pattern: String,
+    /// USB path the device must be on (video only; None = any port)
+    #[serde(default)]
+    pub usb_path: Option<String>,
+    /// ID of the device this alias last matched, so settings saved under
+    /// that ID can follow the device to a new one
+    #[serde(default)]
+    pub last_device_id: Option<String>,
+}
+
+impl DeviceAlias {
+    pub fn matches(&self, kind: DeviceKind, name: &str, usb_path: Option<&str>) -> bool {
+        if self.device_type != kind || !pattern_matches(&self.name_pattern, name) {
+            return false;
+        }
+        match self.usb_path.as_deref() {
+            Some(required) if !required.is_empty() => usb_path == Some(required),
+            _ => true,
+        }
+    }
+}
+
+/// Case-insensitive match with `*` wildcards
+fn pattern_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.trim().to_lowercase();
+    let name = name.to_lowercase();
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == name;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || !name[first.len()..].ends_with(last) {
+        return false;
+    }
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Replace the cached aliases with the database's
+pub fn load(db: &SessionDatabase) {
+    match db.get_device_aliases() {
+        Ok(aliases) => *ALIASES.write() = aliases,
+        Err(e) => println!("[Sacho] Failed to load device aliases: {}", e),
+    }
+}
+
+/// All aliases, in creation order
+pub fn all() -> Vec<DeviceAlias> {
+    ALIASES.read().clone()
+}
+
+/// The first alias matching a device
+pub fn find(kind: DeviceKind, name: &str, usb_path: Option<&str>) -> Option<DeviceAlias> {
+    ALIASES
+        .read()
+        .iter()
+        .find(|alias| alias.matches(kind, name, usb_path))
+        .cloned()
+}
+
+/// Name to record a device under: its alias, or its own name
+pub fn label(kind: DeviceKind, name: &str, usb_path: Option<&str>) -> String {
+    find(kind, name, usb_path)
+        .map(|alias| alias.alias)
+        .unwrap_or_else(|| name.to_string())
+}
+
+/// USB path of a video device, from its GStreamer properties
+pub fn video_usb_path(device_id: &str) -> Option<String> {
+    super::enumeration::device_usb_path(&super::enumeration::get_gst_device(device_id)?)
+}
+
+/// Set the `alias` field of every enumerated device
+pub fn annotate(dm: &mut DeviceManager) {
+    for device in &mut dm.audio_devices {
+        device.alias = find(DeviceKind::Audio, &device.name, None).map(|a| a.alias);
+    }
+    for device in &mut dm.midi_devices {
+        device.alias = find(DeviceKind::Midi, &device.name, None).map(|a| a.alias);
+    }
+    for device in &mut dm.video_devices {
+        device.alias = find(DeviceKind::Video, &device.name, device.usb_path.as_deref()).map(|a| a.alias);
+    }
+}
+
+/// Move settings saved under an aliased device's previous ID to the ID it
+/// has now, and remember the new ID. Returns true if the config changed.
+pub fn reconcile(db: &SessionDatabase, config: &mut Config, dm: &DeviceManager) -> bool {
+    let mut config_changed = false;
+    let mut aliases = ALIASES.write();
+    for alias in aliases.iter_mut() {
+        let current: Vec<&str> = match alias.device_type {
+            DeviceKind::Audio => dm
+                .audio_devices
+                .iter()
+                .filter(|d| alias.matches(DeviceKind::Audio, &d.name, None))
+                .map(|d| d.id.as_str())
+                .collect(),
+            DeviceKind::Midi => dm
+                .midi_devices
+                .iter()
+                .filter(|d| alias.matches(DeviceKind::Midi, &d.name, None))
+                .map(|d| d.id.as_str())
+                .collect(),
+            DeviceKind::Video => dm
+                .video_devices
+                .iter()
+                .filter(|d| alias.matches(DeviceKind::Video, &d.name, d.usb_path.as_deref()))
+                .map(|d| d.id.as_str())
+                .collect(),
+        };
+        // Only an unambiguous match can take over another device's settings
+        let [new_id] = current.as_slice() else { continue };
+        if alias.last_device_id.as_deref() == Some(*new_id) {
+            continue;
+        }
+        if let Some(old_id) = alias.last_device_id.as_deref() {
+            if config.rename_device_id(old_id, new_id) {
+                println!("[Sacho] Device alias '{}' moved from {} to {}", alias.alias, old_id, new_id);
+                config_changed = true;
+            }
+        }
+        alias.last_device_id = Some(new_id.to_string());
+        if let Err(e) = db.set_device_alias_last_id(alias.id, new_id) {
+            println!("[Sacho] Failed to update device alias '{}': {}", alias.alias, e);
+        }
+    }
+    config_changed
+}
+
+/// Re-apply the cached aliases to the managed device list and config,
+/// saving the config if settings moved to a new device ID
+pub fn apply(app: &AppHandle) {
+    let db = app.state::<SessionDatabase>();
+    let config_state = app.state::<RwLock<Config>>();
+    let dm_state = app.state::<RwLock<DeviceManager>>();
+
+    let mut dm = dm_state.write();
+    annotate(&mut dm);
+
+    let mut config = config_state.write();
+    if reconcile(&db, &mut config, &dm) {
+        if let Err(e) = config.save(app) {
+            println!("[Sacho] Failed to save config after device alias update: {}", e);
+        }
+    }
+}
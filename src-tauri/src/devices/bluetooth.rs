@@ -0,0 +1,33 @@
+// Bluetooth LE MIDI
+//
+// BLE-MIDI keyboards appear as ordinary MIDI input ports once the OS has
+// connected them: through the WinRT MIDI API on Windows (midir is built with
+// its `winrt` backend there, since WinMM never lists them) and through
+// CoreMIDI on macOS after the keyboard is connected in Audio MIDI Setup's
+// Bluetooth window. Pairing stays with the OS; this module recognizes the
+// ports so they get a latency default and ride out the short connection
+// drops Bluetooth keyboards have when idle or out of range for a moment.
+
+/// GATT service UUID of the BLE-MIDI specification, part of the device
+/// interface path WinRT reports for Bluetooth MIDI ports
+const BLE_MIDI_SERVICE_UUID: &str = "03b80e5a-ede8-4b33-a751-6ce34ec4c700";
+
+/// Typical delay of a BLE-MIDI note: a connection interval or two plus the
+/// stack's buffering. Applied unless the device has a calibrated offset.
+pub const DEFAULT_LATENCY_MS: i32 = 15;
+
+/// How long a Bluetooth port may be missing before it's reported as
+/// disconnected. Shorter drops reconnect on their own.
+pub const RECONNECT_GRACE_SECS: u32 = 10;
+
+/// Whether a MIDI port (by midir port id and name) is a Bluetooth LE device
+pub fn is_bluetooth_port(port_id: &str, name: &str) -> bool {
+    let id = port_id.to_ascii_lowercase();
+    if id.contains(BLE_MIDI_SERVICE_UUID) || id.contains("bthledevice") {
+        return true;
+    }
+    // CoreMIDI ids are plain numbers; the names macOS and most keyboards
+    // give BLE endpoints say so
+    let name = name.to_ascii_lowercase();
+    name.contains("bluetooth") || name.contains("ble-midi") || name.contains("ble midi")
+}
@@ -0,0 +1,355 @@
+// Device contention detection — explains start failures caused by another
+// application holding a camera or audio input exclusively
+//
+// GStreamer and cpal only report these as generic errors ("Failed to start
+// pipeline", "0xC00D3704", "Device or resource busy"). When a pipeline or
+// stream fails to start, the error is classified here and, if it looks like
+// contention, the OS is asked which process is currently using that class of
+// device:
+// - Windows: the privacy consent store records per-app camera/microphone use;
+//   an app with a start time and no stop time is using the device right now.
+// - Linux: /proc/*/fd is scanned for open /dev/video* or ALSA capture nodes.
+// - macOS: no public API exposes this, so the holder is left unknown.
+
+use std::time::Duration;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::RwLock;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::devices::health::DeviceHealthState;
+
+/// A device that could not be opened because another application holds it
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceContentionInfo {
+    pub id: String,
+    pub name: String,
+    pub device_type: String, // "audio", "video"
+    /// Best guess at the application holding the device (None if unknown)
+    pub likely_holder: Option<String>,
+    /// The underlying error, for the tooltip / logs
+    pub detail: String,
+}
+
+/// Event payload for `device-contention-changed`
+#[derive(Clone, Serialize)]
+struct DeviceContentionPayload {
+    in_use_devices: Vec<DeviceContentionInfo>,
+}
+
+/// Error fragments that indicate the device is held by someone else rather
+/// than missing or misconfigured
+const CONTENTION_MARKERS: &[&str] = &[
+    "busy",
+    "in use",
+    "being used",
+    "exclusive",
+    "already open",
+    "locked",
+    // MF_E_HW_MFT_FAILED_START_STREAMING: camera opened by another process
+    "0xc00d3704",
+    // MF_E_VIDEO_RECORDING_DEVICE_INVALIDATED / PREEMPTED
+    "0xc00d3e85",
+    "0xc00d3ea3",
+    // AUDCLNT_E_DEVICE_IN_USE (WASAPI exclusive mode)
+    "0x8889000a",
+    // ERROR_SHARING_VIOLATION
+    "0x80070020",
+];
+
+/// Whether an error message looks like device contention
+pub fn is_contention_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    CONTENTION_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// Build a contention record if `error` looks like the device is held elsewhere
+pub fn classify_start_failure(
+    id: &str,
+    name: &str,
+    device_type: &str,
+    error: &str,
+) -> Option<DeviceContentionInfo> {
+    if !is_contention_error(error) {
+        return None;
+    }
+    Some(DeviceContentionInfo {
+        id: id.to_string(),
+        name: name.to_string(),
+        device_type: device_type.to_string(),
+        likely_holder: likely_holder(device_type),
+        detail: error.to_string(),
+    })
+}
+
+/// Try to open a video source on its own (source -> fakesink) and report the
+/// error if the device refuses. Used after a capture pipeline fails to start,
+/// since the full pipeline's error is usually a downstream negotiation failure
+/// that hides the real cause.
+pub fn probe_video_source(source: gst::Element) -> Option<String> {
+    let pipeline = gst::Pipeline::new();
+    let sink = gst::ElementFactory::make("fakesink")
+        .property("sync", false)
+        .build()
+        .ok()?;
+    pipeline.add_many([&source, &sink]).ok()?;
+    source.link(&sink).ok()?;
+
+    let state_result = pipeline.set_state(gst::State::Playing);
+    let mut error = None;
+    if let Some(bus) = pipeline.bus() {
+        if let Some(msg) = bus.timed_pop_filtered(
+            gst::ClockTime::from_mseconds(1500),
+            &[gst::MessageType::Error],
+        ) {
+            if let gst::MessageView::Error(err) = msg.view() {
+                let busy = err.error().matches(gst::ResourceError::Busy);
+                let mut text = err.error().to_string();
+                if let Some(debug) = err.debug() {
+                    text.push_str(&format!(" ({})", debug));
+                }
+                // Busy is GStreamer's own contention code; say so explicitly
+                // so the marker check picks it up even if the text doesn't
+                if busy && !is_contention_error(&text) {
+                    text.push_str(" [device busy]");
+                }
+                error = Some(text);
+            }
+        }
+    }
+    let _ = pipeline.set_state(gst::State::Null);
+    // Give the OS a moment to release the handle before the caller retries
+    std::thread::sleep(Duration::from_millis(50));
+
+    error.or_else(|| state_result.err().map(|e| e.to_string()))
+}
+
+/// Replace the recorded contention entries for one device type and notify the
+/// frontend. Called after every audio or video start, so entries clear once
+/// the device opens successfully.
+pub fn report(app: &AppHandle, device_type: &str, entries: Vec<DeviceContentionInfo>) {
+    for entry in &entries {
+        println!(
+            "[Health] {} '{}' is in use by {}: {}",
+            entry.device_type,
+            entry.name,
+            entry.likely_holder.as_deref().unwrap_or("another application"),
+            entry.detail
+        );
+    }
+
+    let health_state = app.state::<RwLock<DeviceHealthState>>();
+    let all = {
+        let mut health = health_state.write();
+        let had_entries = health.in_use.values().any(|e| e.device_type == device_type);
+        if !had_entries && entries.is_empty() {
+            return;
+        }
+        health.in_use.retain(|_, e| e.device_type != device_type);
+        for entry in entries {
+            health.in_use.insert(entry.id.clone(), entry);
+        }
+        health.in_use.values().cloned().collect::<Vec<_>>()
+    };
+
+    let _ = app.emit(
+        "device-contention-changed",
+        DeviceContentionPayload { in_use_devices: all },
+    );
+}
+
+// ============================================================================
+// Holder lookup
+// ============================================================================
+
+/// Best guess at which application is using a device of this type right now
+pub fn likely_holder(device_type: &str) -> Option<String> {
+    let holders = platform::current_users(device_type);
+    if holders.is_empty() {
+        None
+    } else {
+        Some(holders.join(", "))
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use windows_sys::Win32::System::Registry::{
+        RegCloseKey, RegEnumKeyExW, RegOpenKeyExW, RegQueryValueExW, HKEY, HKEY_CURRENT_USER,
+        KEY_READ,
+    };
+
+    const CONSENT_STORE: &str =
+        "Software\\Microsoft\\Windows\\CurrentVersion\\CapabilityAccessManager\\ConsentStore";
+
+    /// Apps the privacy consent store lists as currently using the camera or
+    /// microphone (LastUsedTimeStart set, LastUsedTimeStop == 0)
+    pub fn current_users(device_type: &str) -> Vec<String> {
+        let capability = match device_type {
+            "video" => "webcam",
+            "audio" => "microphone",
+            _ => return Vec::new(),
+        };
+        let own_exe = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_lowercase()));
+
+        let mut users = Vec::new();
+        let base = format!("{}\\{}", CONSENT_STORE, capability);
+        // Packaged (Store) apps are direct subkeys; desktop apps live under
+        // NonPackaged with their exe path ('\' replaced by '#') as the key name
+        for (key, packaged) in [(base.clone(), true), (format!("{}\\NonPackaged", base), false)] {
+            for app_key in enum_subkeys(&key) {
+                if packaged && app_key == "NonPackaged" {
+                    continue;
+                }
+                if !is_in_use(&format!("{}\\{}", key, app_key)) {
+                    continue;
+                }
+                let name = display_name(&app_key, packaged);
+                if own_exe.as_deref() == Some(name.to_lowercase().as_str()) {
+                    continue;
+                }
+                users.push(name);
+            }
+        }
+        users
+    }
+
+    /// "C:#Program Files#Zoom#bin#Zoom.exe" -> "Zoom.exe",
+    /// "Microsoft.WindowsCamera_8wekyb3d8bbwe" -> "Microsoft.WindowsCamera"
+    fn display_name(key: &str, packaged: bool) -> String {
+        if packaged {
+            key.split('_').next().unwrap_or(key).to_string()
+        } else {
+            key.rsplit('#').next().unwrap_or(key).to_string()
+        }
+    }
+
+    fn enum_subkeys(path: &str) -> Vec<String> {
+        let mut names = Vec::new();
+        unsafe {
+            let Some(hkey) = open_key(path) else {
+                return names;
+            };
+            let mut index = 0u32;
+            loop {
+                let mut buf = [0u16; 512];
+                let mut len = buf.len() as u32;
+                let result = RegEnumKeyExW(
+                    hkey,
+                    index,
+                    buf.as_mut_ptr(),
+                    &mut len,
+                    std::ptr::null(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                );
+                if result != 0 {
+                    break;
+                }
+                names.push(String::from_utf16_lossy(&buf[..len as usize]));
+                index += 1;
+            }
+            RegCloseKey(hkey);
+        }
+        names
+    }
+
+    fn is_in_use(path: &str) -> bool {
+        unsafe {
+            let Some(hkey) = open_key(path) else {
+                return false;
+            };
+            let start = query_u64(hkey, "LastUsedTimeStart");
+            let stop = query_u64(hkey, "LastUsedTimeStop");
+            RegCloseKey(hkey);
+            matches!((start, stop), (Some(s), Some(0)) if s > 0)
+        }
+    }
+
+    unsafe fn open_key(path: &str) -> Option<HKEY> {
+        let subkey = to_wide(path);
+        let mut hkey: HKEY = std::ptr::null_mut();
+        if RegOpenKeyExW(HKEY_CURRENT_USER, subkey.as_ptr(), 0, KEY_READ, &mut hkey) != 0 {
+            return None;
+        }
+        Some(hkey)
+    }
+
+    unsafe fn query_u64(hkey: HKEY, name: &str) -> Option<u64> {
+        let value_name = to_wide(name);
+        let mut value: u64 = 0;
+        let mut size = std::mem::size_of::<u64>() as u32;
+        let result = RegQueryValueExW(
+            hkey,
+            value_name.as_ptr(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            &mut value as *mut u64 as *mut u8,
+            &mut size,
+        );
+        (result == 0).then_some(value)
+    }
+
+    fn to_wide(s: &str) -> Vec<u16> {
+        use std::os::windows::ffi::OsStrExt;
+        std::ffi::OsStr::new(s)
+            .encode_wide()
+            .chain(std::iter::once(0))
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    /// Processes (other than this one) with a camera or ALSA capture node open
+    pub fn current_users(device_type: &str) -> Vec<String> {
+        let is_device_node = |target: &str| match device_type {
+            "video" => target.starts_with("/dev/video"),
+            // Capture PCM nodes are /dev/snd/pcmC<card>D<dev>c
+            "audio" => target.starts_with("/dev/snd/pcm") && target.ends_with('c'),
+            _ => false,
+        };
+        let own_pid = std::process::id().to_string();
+
+        let mut users: Vec<String> = Vec::new();
+        let Ok(procs) = std::fs::read_dir("/proc") else {
+            return users;
+        };
+        for entry in procs.flatten() {
+            let pid = entry.file_name().to_string_lossy().to_string();
+            if !pid.chars().all(|c| c.is_ascii_digit()) || pid == own_pid {
+                continue;
+            }
+            // Other users' fd directories aren't readable; skip them silently
+            let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+                continue;
+            };
+            let holds_device = fds.flatten().any(|fd| {
+                std::fs::read_link(fd.path())
+                    .map(|t| is_device_node(&t.to_string_lossy()))
+                    .unwrap_or(false)
+            });
+            if !holds_device {
+                continue;
+            }
+            let comm = std::fs::read_to_string(entry.path().join("comm"))
+                .map(|c| c.trim().to_string())
+                .unwrap_or_default();
+            let name = if comm.is_empty() { format!("pid {}", pid) } else { format!("{} (pid {})", comm, pid) };
+            users.push(name);
+        }
+        users
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod platform {
+    pub fn current_users(_device_type: &str) -> Vec<String> {
+        Vec::new()
+    }
+}
@@ -5,6 +5,7 @@ use crate::encoding::{has_av1_encoder, has_vp9_encoder, has_vp8_encoder};
 use cpal::traits::{DeviceTrait, HostTrait};
 use gstreamer as gst;
 use gstreamer::prelude::*;
+use std::cell::Cell;
 use std::collections::HashMap;
 use std::sync::Mutex;
 
@@ -17,6 +18,33 @@ use std::sync::Mutex;
 /// only 59.94 supported by KS) and ensures we always use the correct provider.
 static GST_DEVICE_STORE: Mutex<Option<HashMap<String, Vec<gst::Device>>>> = Mutex::new(None);
 
+thread_local! {
+    /// Number of matching providers to pass over in the lookups below, so a
+    /// pipeline can be retried on the next provider when the first one fails
+    static PROVIDER_SKIP: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Run `f` with provider lookups on this thread skipping the first `skip`
+/// matching providers
+pub fn with_provider_skip<T>(skip: usize, f: impl FnOnce() -> T) -> T {
+    let previous = PROVIDER_SKIP.with(|s| s.replace(skip));
+    let result = f();
+    PROVIDER_SKIP.with(|s| s.set(previous));
+    result
+}
+
+/// Number of stored providers with exact caps for the requested mode
+pub fn matching_provider_count(device_id: &str, format: &str, width: u32, height: u32, fps: f64) -> usize {
+    let store = GST_DEVICE_STORE.lock().ok();
+    let Some(total) = store.as_ref().and_then(|s| s.as_ref()?.get(device_id).map(Vec::len)) else {
+        return 0;
+    };
+    drop(store);
+    (0..total)
+        .take_while(|&skip| with_provider_skip(skip, || get_device_for_format(device_id, format, width, height, fps).is_some()))
+        .count()
+}
+
 /// Retrieve the first (fallback) GStreamer Device object by device ID.
 /// Prefer `get_device_for_caps` when you have a specific mode to match.
 pub fn get_gst_device(device_id: &str) -> Option<gst::Device> {
@@ -30,6 +58,10 @@ pub fn get_gst_device(device_id: &str) -> Option<gst::Device> {
 /// matching the requested format, resolution, and framerate. Returns true if at
 /// least one provider can handle the configuration.
 pub fn validate_video_config(device_id: &str, format: &str, width: u32, height: u32, fps: f64) -> bool {
+    #[cfg(feature = "test-harness")]
+    if let Some(valid) = crate::test_harness::fake_devices::validate_video_config(device_id, format, width, height, fps) {
+        return valid;
+    }
     get_device_for_format(device_id, format, width, height, fps).is_some()
 }
 
@@ -60,10 +92,15 @@ pub fn get_device_for_format(
             .field("framerate", target_fps)
             .build();
 
+        let mut skip = PROVIDER_SKIP.with(Cell::get);
         for gst_dev in devices {
             if let Some(device_caps) = gst_dev.caps() {
                 let matched = device_caps.intersect_with_mode(&filter, gst::CapsIntersectMode::First);
                 if !matched.is_empty() {
+                    if skip > 0 {
+                        skip -= 1;
+                        continue;
+                    }
                     println!("[Video] Found exact caps for format '{}' via provider '{}': {}",
                         format, gst_dev.device_class(), matched);
                     return Some((matched, gst_dev.clone()));
@@ -106,10 +143,15 @@ pub fn get_device_for_caps(
         .field("framerate", target_fps)
         .build();
 
+    let mut skip = PROVIDER_SKIP.with(Cell::get);
     for gst_dev in devices {
         if let Some(device_caps) = gst_dev.caps() {
             let matched = device_caps.intersect_with_mode(&filter, gst::CapsIntersectMode::First);
             if !matched.is_empty() {
+                if skip > 0 {
+                    skip -= 1;
+                    continue;
+                }
                 println!("[Video] Found exact caps via provider '{}': {}",
                     gst_dev.device_class(), matched);
                 return Some((matched, gst_dev.clone()));
@@ -133,6 +175,10 @@ pub fn get_device_for_caps(
                 if let Some(device_caps) = gst_dev.caps() {
                     let matched = device_caps.intersect_with_mode(&raw_h264_filter, gst::CapsIntersectMode::First);
                     if !matched.is_empty() {
+                        if skip > 0 {
+                            skip -= 1;
+                            continue;
+                        }
                         println!("[Video] Found H.264-as-raw caps via provider '{}': {}",
                             gst_dev.device_class(), matched);
                         return Some((matched, gst_dev.clone()));
@@ -395,6 +441,15 @@ fn extract_framerate_values(structure: &gst::StructureRef) -> Vec<f64> {
 }
 
 
+/// Where a capture device is connected, from whichever path property its
+/// provider sets (the symbolic link on Windows, the node path on Linux)
+pub fn device_usb_path(device: &gst::Device) -> Option<String> {
+    let props = device.properties()?;
+    ["device.path", "api.v4l2.path", "device.bus_path"]
+        .iter()
+        .find_map(|key| props.get::<String>(key).ok())
+}
+
 /// Enumerate all available audio input devices
 pub fn enumerate_audio_devices() -> Vec<AudioDevice> {
     let mut devices = Vec::new();
@@ -407,10 +462,17 @@ pub fn enumerate_audio_devices() -> Vec<AudioDevice> {
     if let Ok(input_devices) = host.input_devices() {
         for device in input_devices {
             if let Ok(name) = device.name() {
-                let config = device
-                    .default_input_config()
+                let default_config = device.default_input_config().ok();
+                let config = default_config
+                    .as_ref()
                     .map(|c| (c.channels(), c.sample_rate().0))
                     .unwrap_or((2, 44100));
+                let native_bit_depth = device
+                    .supported_input_configs()
+                    .ok()
+                    .and_then(|configs| {
+                        crate::config::AudioBitDepth::for_sample_formats(configs.map(|c| c.sample_format()))
+                    });
                 
                 let is_default = default_device_name
                     .as_ref()
@@ -423,36 +485,107 @@ pub fn enumerate_audio_devices() -> Vec<AudioDevice> {
                     channels: config.0,
                     sample_rate: config.1,
                     is_default,
+                    alias: None,
+                    native_bit_depth,
                 });
             }
         }
     }
+
+    #[cfg(feature = "test-harness")]
+    devices.extend(crate::test_harness::fake_devices::enumerate_audio());
     
     devices
 }
 
+/// Stable ID of a MIDI input port: its name, plus which of the ports sharing
+/// that name it is (interfaces that expose several ports under one name).
+/// Port indices shift whenever a device is plugged in or removed, so they
+/// aren't part of the ID.
+pub fn midi_port_id(name: &str, occurrence: usize) -> String {
+    if occurrence == 0 {
+        format!("midi:{}", name)
+    } else {
+        format!("midi:{}#{}", name, occurrence + 1)
+    }
+}
+
+/// Stable IDs for MIDI port names listed in port order
+pub fn midi_port_ids(names: &[String]) -> Vec<String> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    names
+        .iter()
+        .map(|name| {
+            let occurrence = seen.entry(name.as_str()).or_insert(0);
+            let id = midi_port_id(name, *occurrence);
+            *occurrence += 1;
+            id
+        })
+        .collect()
+}
+
+/// Whether `id` is a MIDI device ID from before stable IDs ("midi-{port index}")
+pub fn is_legacy_midi_id(id: &str) -> bool {
+    id.strip_prefix("midi-").is_some_and(|index| index.parse::<usize>().is_ok())
+}
+
 /// Enumerate all available MIDI input devices
 pub fn enumerate_midi_devices() -> Vec<MidiDevice> {
     let mut devices = Vec::new();
     
     if let Ok(midi_in) = midir::MidiInput::new("sacho-probe") {
-        let ports = midi_in.ports();
-        for (index, port) in ports.iter().enumerate() {
-            if let Ok(name) = midi_in.port_name(port) {
-                devices.push(MidiDevice {
-                    id: format!("midi-{}", index),
-                    name,
-                    port_index: index,
-                });
-            }
+        let ports: Vec<(usize, midir::MidiInputPort, String)> = midi_in
+            .ports()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, port)| midi_in.port_name(&port).ok().map(|name| (index, port, name)))
+            .collect();
+        let names: Vec<String> = ports.iter().map(|(_, _, name)| name.clone()).collect();
+        for ((index, port, name), id) in ports.into_iter().zip(midi_port_ids(&names)) {
+            let bluetooth = super::bluetooth::is_bluetooth_port(&port.id(), &name);
+            devices.push(MidiDevice {
+                id,
+                name,
+                port_index: index,
+                alias: None,
+                bluetooth,
+                default_latency_ms: bluetooth.then_some(super::bluetooth::DEFAULT_LATENCY_MS),
+            });
         }
     }
+
+    #[cfg(feature = "test-harness")]
+    devices.extend(crate::test_harness::fake_devices::enumerate_midi());
     
     devices
 }
 
-/// Enumerate all available video capture devices (webcams) using GStreamer
+/// Names of the MIDI output ports (synths, virtual ports) that can receive
+/// MIDI thru
+pub fn enumerate_midi_output_ports() -> Vec<String> {
+    let Ok(midi_out) = midir::MidiOutput::new("sacho-probe") else {
+        return Vec::new();
+    };
+    midi_out
+        .ports()
+        .iter()
+        .filter_map(|port| midi_out.port_name(port).ok())
+        .collect()
+}
+
+/// Enumerate all available video capture devices (webcams)
 pub fn enumerate_video_devices() -> Vec<VideoDevice> {
+    #[allow(unused_mut)]
+    let mut devices = enumerate_gst_video_devices();
+
+    #[cfg(feature = "test-harness")]
+    devices.extend(crate::test_harness::fake_devices::enumerate_video());
+
+    devices
+}
+
+/// Enumerate video capture devices using GStreamer
+fn enumerate_gst_video_devices() -> Vec<VideoDevice> {
     println!("[Sacho] Enumerating video devices with GStreamer...");
     
     // Initialize GStreamer
@@ -652,6 +785,9 @@ pub fn enumerate_video_devices() -> Vec<VideoDevice> {
             else if class == "Video/Source" { 2 } // KS last
             else { 1 }                           // anything else in between
         });
+        // Where it's plugged in, for device aliases that pin a USB port
+        let usb_path = sorted_devices.iter().find_map(device_usb_path);
+
         if let Ok(mut store) = GST_DEVICE_STORE.lock() {
             if let Some(map) = store.as_mut() {
                 map.insert(device_id.clone(), sorted_devices);
@@ -674,6 +810,8 @@ pub fn enumerate_video_devices() -> Vec<VideoDevice> {
             id: device_id,
             name,
             capabilities,
+            usb_path,
+            alias: None,
         });
     }
     
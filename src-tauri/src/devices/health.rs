@@ -10,6 +10,7 @@ use serde::Serialize;
 use tauri::{AppHandle, Emitter, Manager};
 
 use crate::config::Config;
+use crate::devices::contention::DeviceContentionInfo;
 use crate::devices::DeviceManager;
 use crate::notifications;
 use crate::recording::video::VideoCaptureManager;
@@ -25,12 +26,15 @@ pub struct DisconnectedDeviceInfo {
 /// Managed state holding the current set of disconnected devices
 pub struct DeviceHealthState {
     pub disconnected: HashMap<String, DisconnectedDeviceInfo>,
+    /// Devices that failed to open because another application holds them
+    pub in_use: HashMap<String, DeviceContentionInfo>,
 }
 
 impl DeviceHealthState {
     pub fn new() -> Self {
         Self {
             disconnected: HashMap::new(),
+            in_use: HashMap::new(),
         }
     }
 }
@@ -56,6 +60,8 @@ fn enumerate_midi_port_names() -> HashSet<String> {
             }
         }
     }
+    #[cfg(feature = "test-harness")]
+    names.extend(crate::test_harness::fake_devices::midi_port_names());
     names
 }
 
@@ -71,6 +77,8 @@ fn enumerate_audio_device_names() -> HashSet<String> {
             }
         }
     }
+    #[cfg(feature = "test-harness")]
+    names.extend(crate::test_harness::fake_devices::audio_device_names());
     names
 }
 
@@ -83,12 +91,14 @@ fn enumerate_video_device_names() -> HashSet<String> {
     let monitor = gstreamer::DeviceMonitor::new();
     monitor.add_filter(Some("Video/Source"), None);
     monitor.add_filter(Some("Source/Video"), None);
+    let mut names = HashSet::new();
+    #[cfg(feature = "test-harness")]
+    names.extend(crate::test_harness::fake_devices::video_device_names());
     if monitor.start().is_err() {
-        return HashSet::new();
+        return names;
     }
     let devices = monitor.devices();
     monitor.stop();
-    let mut names = HashSet::new();
     for device in devices {
         names.insert(device.display_name().to_string());
     }
@@ -136,7 +146,7 @@ pub fn check_active_device_health(app: &AppHandle) -> HashSet<String> {
 
     let mut disconnected = HashSet::new();
 
-    // Check MIDI devices: IDs are "midi-{index}", names come from DeviceManager cache.
+    // Check MIDI devices: IDs are derived from port names (see `midi_port_id`), names come from DeviceManager cache.
     // We enumerate current port names and check if the cached name is still present.
     if !active_midi_ids.is_empty() {
         let current_midi_names = enumerate_midi_port_names();
@@ -214,8 +224,8 @@ struct DeviceHealthChangedPayload {
 
 /// Event payload for `_device-needs-restart` (internal, triggers frontend round-trip)
 #[derive(Clone, Serialize)]
-struct DeviceNeedsRestartPayload {
-    device_types: Vec<String>,
+pub(crate) struct DeviceNeedsRestartPayload {
+    pub(crate) device_types: Vec<String>,
 }
 
 /// Video stall detection state for one pipeline
@@ -239,6 +249,8 @@ pub fn health_check_loop(
     stop_flag: Arc<AtomicBool>,
 ) {
     let mut previous_disconnected: HashSet<String> = HashSet::new();
+    // Seconds each Bluetooth MIDI device has been missing (within its grace period)
+    let mut bluetooth_absent: HashMap<String, u32> = HashMap::new();
     // Video frame counter tracking: device_id -> stall state
     let mut video_stall: HashMap<String, VideoStallState> = HashMap::new();
     // Tick counter for rate-limiting video reconnection enumeration
@@ -256,6 +268,40 @@ pub fn health_check_loop(
         // Check MIDI + audio via enumeration
         let mut current_disconnected = check_active_device_health(&app);
 
+        // Bluetooth MIDI drops out for a moment when idle or briefly out of
+        // range and reconnects by itself; only report it once it stays gone
+        {
+            let dm = app.state::<RwLock<DeviceManager>>();
+            let dm_read = dm.read();
+            let bluetooth_ids: HashSet<&str> = dm_read.midi_devices.iter()
+                .filter(|d| d.bluetooth)
+                .map(|d| d.id.as_str())
+                .collect();
+            // A port that came back within its grace period was never reported
+            // as disconnected, so the reconnect handling below won't see it;
+            // the monitor's midir connection to it is still dead all the same
+            let reappeared: Vec<String> = bluetooth_absent.keys()
+                .filter(|id| !current_disconnected.contains(*id))
+                .cloned()
+                .collect();
+            if !reappeared.is_empty() {
+                println!("[Health] Bluetooth MIDI back, reconnecting: {:?}", reappeared);
+                let _ = app.emit(
+                    "_device-needs-restart",
+                    DeviceNeedsRestartPayload { device_types: vec!["midi".to_string()] },
+                );
+            }
+            bluetooth_absent.retain(|id, _| current_disconnected.contains(id));
+            current_disconnected.retain(|id| {
+                if !bluetooth_ids.contains(id.as_str()) || previous_disconnected.contains(id) {
+                    return true;
+                }
+                let secs = bluetooth_absent.entry(id.clone()).or_insert(0);
+                *secs += 1;
+                *secs > crate::devices::bluetooth::RECONNECT_GRACE_SECS
+            });
+        }
+
         // Skip video stall detection while pipelines are intentionally stopped
         // (e.g. during encoder test or auto-select). The test commands set status
         // to Initializing before stopping pipelines and reset to Idle afterward.
@@ -265,8 +311,10 @@ pub fn health_check_loop(
             status == crate::recording::RecordingStatus::Initializing
         };
 
-        // Check video via frame counter stall detection
-        if is_initializing {
+        // Check video via frame counter stall detection. Pipelines are also
+        // intentionally down while recovering from sleep (see power.rs) and
+        // during a privacy pause that released the cameras.
+        if is_initializing || crate::power::is_recovering() || crate::privacy::cameras_released() {
             // Reset stall state so restarted pipelines get a clean slate
             video_stall.clear();
         } else {
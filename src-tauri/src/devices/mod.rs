@@ -1,5 +1,8 @@
 // Device discovery and enumeration
 
+pub mod aliases;
+pub mod bluetooth;
+pub mod contention;
 pub mod enumeration;
 pub mod health;
 
@@ -34,6 +37,7 @@ impl DeviceManager {
         self.audio_devices = enumeration::enumerate_audio_devices();
         self.midi_devices = enumeration::enumerate_midi_devices();
         self.video_devices = enumeration::enumerate_video_devices();
+        aliases::annotate(self);
     }
 }
 
@@ -51,6 +55,12 @@ pub struct AudioDevice {
     pub channels: u16,
     pub sample_rate: u32,
     pub is_default: bool,
+    /// User-chosen name from a matching device alias
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// Bit depth of the converter (None if the driver only offers float mixer formats)
+    #[serde(default)]
+    pub native_bit_depth: Option<crate::config::AudioBitDepth>,
 }
 
 /// Represents a MIDI input device
@@ -59,6 +69,15 @@ pub struct MidiDevice {
     pub id: String,
     pub name: String,
     pub port_index: usize,
+    /// User-chosen name from a matching device alias
+    #[serde(default)]
+    pub alias: Option<String>,
+    /// Bluetooth LE MIDI port (see `bluetooth`)
+    #[serde(default)]
+    pub bluetooth: bool,
+    /// Latency compensated for this port unless it has a calibrated offset
+    #[serde(default)]
+    pub default_latency_ms: Option<i32>,
 }
 
 /// Per-codec resolution capability: a resolution and its available framerates
@@ -71,6 +90,13 @@ pub struct CodecCapability {
     pub framerates: Vec<f64>,
 }
 
+/// Source formats in order of preference: raw pixel formats first (highest
+/// quality, we encode ourselves), then pre-encoded formats for passthrough
+const FORMAT_PRIORITY: &[&str] = &[
+    "YUY2", "NV12", "I420", "YV12", "BGR", "BGRx",
+    "MJPEG", "H264", "AV1", "VP9", "VP8",
+];
+
 /// Represents a video capture device
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoDevice {
@@ -79,6 +105,12 @@ pub struct VideoDevice {
     /// Per-format capabilities: format string -> list of resolutions with available framerates.
     /// Format strings are the actual pixel/codec names from GStreamer (e.g. "YUY2", "NV12", "MJPEG", "H264").
     pub capabilities: HashMap<String, Vec<CodecCapability>>,
+    /// Where the device is connected (GStreamer "device.path"), if reported
+    #[serde(default)]
+    pub usb_path: Option<String>,
+    /// User-chosen name from a matching device alias
+    #[serde(default)]
+    pub alias: Option<String>,
 }
 
 impl VideoDevice {
@@ -93,12 +125,7 @@ impl VideoDevice {
     /// Raw pixel formats first (highest quality, we encode ourselves),
     /// then pre-encoded formats for passthrough.
     pub fn preferred_format(&self) -> Option<&str> {
-        const PRIORITY: &[&str] = &[
-            "YUY2", "NV12", "I420", "YV12", "BGR", "BGRx",
-            "MJPEG", "H264", "AV1", "VP9", "VP8",
-        ];
-
-        for fmt in PRIORITY {
+        for fmt in FORMAT_PRIORITY {
             if self.capabilities.contains_key(*fmt) {
                 return Some(fmt);
             }
@@ -118,6 +145,103 @@ impl VideoDevice {
         Some((best.width, best.height, fps))
     }
 
+    /// Modes to try, in order, when `config` fails to start: lower framerates,
+    /// lower resolutions, other source formats, then the same mode through the
+    /// device's other providers (e.g. Media Foundation instead of Kernel
+    /// Streaming).
+    pub fn fallback_ladder(&self, config: &crate::config::VideoDeviceConfig) -> Vec<crate::recording::video::VideoFallbackAttempt> {
+        use crate::recording::video::VideoFallbackAttempt;
+        use crate::session::VideoFallbackStep;
+
+        /// Rungs per step, so a broken device doesn't stall startup for long
+        const MAX_PER_STEP: usize = 2;
+
+        let mut ladder = Vec::new();
+        let mut push = |step, format: &str, width: u32, height: u32, fps: f64| {
+            let mut cfg = config.clone();
+            cfg.source_format = format.to_string();
+            cfg.source_width = width;
+            cfg.source_height = height;
+            cfg.source_fps = fps;
+            if format != config.source_format {
+                // Raw formats can't be passed through; encoded ones can be
+                // decoded, so an encoding config stays an encoding config
+                cfg.passthrough = config.passthrough && !crate::encoding::is_raw_format(format);
+            }
+            // Don't upscale past the new source
+            if cfg.target_width > width || cfg.target_height > height {
+                cfg.target_width = 0;
+                cfg.target_height = 0;
+            }
+            if cfg.target_fps > fps {
+                cfg.target_fps = 0.0;
+            }
+            ladder.push(VideoFallbackAttempt { step, config: cfg, provider_skip: 0 });
+        };
+
+        // Highest framerate at or below `fps`, else the lowest available
+        let fps_at_most = |cap: &CodecCapability, fps: f64| {
+            cap.framerates.iter().copied()
+                .find(|&f| f <= fps + 0.01)
+                .or_else(|| cap.framerates.last().copied())
+        };
+
+        let format_caps = self.capabilities.get(&config.source_format);
+        let current = format_caps.and_then(|caps| {
+            caps.iter().find(|c| c.width == config.source_width && c.height == config.source_height)
+        });
+
+        if let Some(cap) = current {
+            for fps in cap.framerates.iter().filter(|&&f| f < config.source_fps - 0.01).take(MAX_PER_STEP) {
+                push(VideoFallbackStep::LowerFps, &config.source_format, cap.width, cap.height, *fps);
+            }
+        }
+
+        if let Some(caps) = format_caps {
+            let pixels = config.source_width as u64 * config.source_height as u64;
+            for cap in caps.iter().filter(|c| (c.width as u64 * c.height as u64) < pixels).take(MAX_PER_STEP) {
+                if let Some(fps) = fps_at_most(cap, config.source_fps) {
+                    push(VideoFallbackStep::LowerResolution, &config.source_format, cap.width, cap.height, fps);
+                }
+            }
+        }
+
+        let mut formats: Vec<&String> = self.capabilities.keys()
+            .filter(|f| **f != config.source_format)
+            .collect();
+        formats.sort_by_key(|f| FORMAT_PRIORITY.iter().position(|p| *p == f.as_str()).unwrap_or(usize::MAX));
+        for format in formats.into_iter().take(MAX_PER_STEP) {
+            let caps = &self.capabilities[format];
+            // Same resolution if available, else the largest that isn't bigger
+            let cap = caps.iter()
+                .find(|c| c.width == config.source_width && c.height == config.source_height)
+                .or_else(|| caps.iter().find(|c| c.height <= config.source_height))
+                .or_else(|| caps.last());
+            if let Some(cap) = cap {
+                if let Some(fps) = fps_at_most(cap, config.source_fps) {
+                    push(VideoFallbackStep::AlternateFormat, format, cap.width, cap.height, fps);
+                }
+            }
+        }
+
+        let providers = crate::devices::enumeration::matching_provider_count(
+            &self.id,
+            &config.source_format,
+            config.source_width,
+            config.source_height,
+            config.source_fps,
+        );
+        for skip in 1..providers {
+            ladder.push(VideoFallbackAttempt {
+                step: VideoFallbackStep::AlternateProvider,
+                config: config.clone(),
+                provider_skip: skip,
+            });
+        }
+
+        ladder
+    }
+
     /// Compute the smart default configuration for this device.
     ///
     /// Defaults:
@@ -160,6 +284,11 @@ impl VideoDevice {
             preset_level: crate::encoding::DEFAULT_PRESET,
             effort_level: crate::encoding::DEFAULT_PRESET,
             video_bit_depth: None,
+            keyframe_interval: Default::default(),
+            b_frames: None,
+            constant_frame_rate: false,
+            content_type: Default::default(),
+            roi: None,
             target_width: 0,   // "Match Source"
             target_height: 0,  // "Match Source"
             target_fps: 0.0,   // "Match Source"
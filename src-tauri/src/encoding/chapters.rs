@@ -0,0 +1,68 @@
+// Chapter markers in recorded video files
+//
+// Detected chapters are written into Matroska files as a table of contents
+// (an edition with one entry per chapter), so players show them as chapter
+// stops. Other containers have no chapter support in GStreamer's muxers and
+// keep the chapters in the session metadata only.
+
+use std::path::Path;
+
+use gstreamer as gst;
+
+use super::{AsyncVideoEncoder, ContainerFormat};
+use crate::session::Chapter;
+
+/// Table of contents for `chapters`
+pub fn build_toc(chapters: &[Chapter]) -> gst::Toc {
+    let mut edition = gst::TocEntry::new(gst::TocEntryType::Edition, "edition");
+    {
+        let edition = edition.get_mut().expect("new TOC entry is writable");
+        for (i, chapter) in chapters.iter().enumerate() {
+            let mut entry = gst::TocEntry::new(gst::TocEntryType::Chapter, &format!("chapter{}", i + 1));
+            {
+                let entry = entry.get_mut().expect("new TOC entry is writable");
+                entry.set_start_stop_times(
+                    (chapter.start_secs.max(0.0) * 1e9) as i64,
+                    (chapter.end_secs.max(chapter.start_secs) * 1e9) as i64,
+                );
+                let mut tags = gst::TagList::new();
+                tags.get_mut()
+                    .expect("new tag list is writable")
+                    .add::<gst::tags::Title>(&chapter.title.as_str(), gst::TagMergeMode::Replace);
+                entry.set_tags(tags);
+            }
+            edition.append_sub_entry(entry);
+        }
+    }
+
+    let mut toc = gst::Toc::new(gst::TocScope::Global);
+    toc.get_mut().expect("new TOC is writable").append_entry(edition);
+    toc
+}
+
+/// Whether chapters can be written into this video file
+pub fn supports_chapters(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("mkv"))
+}
+
+/// Rewrite a Matroska file with `chapters` as its table of contents.
+/// `offset_secs` is where the file starts on the session timeline.
+pub fn embed_chapters(path: &Path, chapters: &[Chapter], offset_secs: f64) -> anyhow::Result<()> {
+    let shifted: Vec<Chapter> = chapters
+        .iter()
+        .filter(|c| c.end_secs > offset_secs)
+        .map(|c| Chapter {
+            title: c.title.clone(),
+            start_secs: (c.start_secs - offset_secs).max(0.0),
+            end_secs: c.end_secs - offset_secs,
+        })
+        .collect();
+    if shifted.is_empty() {
+        return Ok(());
+    }
+
+    let toc = build_toc(&shifted);
+    AsyncVideoEncoder::remux_to_container_with_toc(&path.to_path_buf(), ContainerFormat::Mkv, Some(&toc))
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!("{}", e))
+}
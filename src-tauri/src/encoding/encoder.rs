@@ -19,6 +19,7 @@ use std::time::{Duration, Instant};
 use gstreamer as gst;
 use gstreamer::prelude::*;
 use gstreamer_app as gst_app;
+use gstreamer_video as gst_video;
 
 use super::{ContainerFormat, VideoCodec};
 
@@ -46,8 +47,9 @@ pub type Result<T> = std::result::Result<T, EncoderError>;
 /// Represents a raw video frame to be encoded
 #[derive(Clone)]
 pub struct RawVideoFrame {
-    /// Raw pixel data (typically NV12, I420, or similar)
-    pub data: Vec<u8>,
+    /// Raw pixel data (typically NV12, I420, or similar), shared with the
+    /// capture pre-roll buffer
+    pub data: crate::recording::frame_pool::FrameData,
     /// Presentation timestamp in nanoseconds
     pub pts: u64,
     /// Duration in nanoseconds
@@ -80,6 +82,12 @@ pub struct EncodedFrame {
 pub struct EncoderConfig {
     /// Keyframe interval in frames (0 = automatic)
     pub keyframe_interval: u32,
+    /// Maximum consecutive B-frames (None = preset default)
+    pub b_frames: Option<u32>,
+    /// What the source shows, for content-specific encoder tuning
+    pub content_type: super::ContentType,
+    /// Region to encode at higher quality (ignored by encoders without ROI support)
+    pub roi: Option<RegionOfInterest>,
     /// Target codec for encoding
     pub target_codec: VideoCodec,
     /// Quality preset level (1 = lightest, 5 = maximum quality)
@@ -96,12 +104,23 @@ pub struct EncoderConfig {
     pub target_height: Option<u32>,
     /// Target encoding fps (if different from source, videorate is inserted)
     pub target_fps: Option<f64>,
+    /// Source colorimetry (e.g. "bt2100-pq"). Set on the input caps so the
+    /// encoder and muxer tag the stream instead of assuming BT.709.
+    pub colorimetry: Option<String>,
+    /// Always insert videorate so the output is constant frame rate, even
+    /// when no fps conversion is needed
+    pub constant_frame_rate: bool,
+    /// Provenance tags for the muxer (see [`super::tags`])
+    pub tags: Option<gst::TagList>,
 }
 
 impl Default for EncoderConfig {
     fn default() -> Self {
         Self {
             keyframe_interval: 60, // Every 2 seconds at 30fps
+            b_frames: None,
+            content_type: Default::default(),
+            roi: None,
             target_codec: VideoCodec::Av1,
             preset_level: super::presets::DEFAULT_PRESET,
             effort_level: super::presets::DEFAULT_PRESET,
@@ -109,8 +128,86 @@ impl Default for EncoderConfig {
             target_width: None,
             target_height: None,
             target_fps: None,
+            colorimetry: None,
+            constant_frame_rate: false,
+            tags: None,
+        }
+    }
+}
+
+/// A rectangle in normalized (0–1) frame coordinates that supported
+/// encoders give more bits, e.g. the keyboard and the player's hands
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct RegionOfInterest {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl RegionOfInterest {
+    /// Clip to the frame. None if nothing of the rectangle is left.
+    pub fn clipped(&self) -> Option<Self> {
+        if ![self.x, self.y, self.width, self.height].iter().all(|v| v.is_finite()) {
+            return None;
         }
+        let x = self.x.clamp(0.0, 1.0);
+        let y = self.y.clamp(0.0, 1.0);
+        let width = (self.x + self.width).clamp(0.0, 1.0) - x;
+        let height = (self.y + self.height).clamp(0.0, 1.0) - y;
+        (width > 0.0 && height > 0.0).then_some(Self { x, y, width, height })
+    }
+
+    /// Pixel rectangle (x, y, width, height) in a frame of the given size
+    pub fn to_pixels(&self, frame_width: u32, frame_height: u32) -> (u32, u32, u32, u32) {
+        let x = (self.x * frame_width as f64).round() as u32;
+        let y = (self.y * frame_height as f64).round() as u32;
+        let width = ((self.width * frame_width as f64).round() as u32).min(frame_width - x.min(frame_width));
+        let height = ((self.height * frame_height as f64).round() as u32).min(frame_height - y.min(frame_height));
+        (x, y, width.max(1), height.max(1))
+    }
+}
+
+/// QP offset for the region of interest; negative spends more bits there
+const ROI_DELTA_QP: i32 = -8;
+
+/// Tag every frame entering the encoder with a region-of-interest meta.
+///
+/// Only the VA and QuickSync encoders read the meta (each from its own
+/// parameter structure); others ignore it, so the probe isn't installed.
+/// The rectangle is scaled to the negotiated caps, so it follows any
+/// resolution change made before the encoder.
+fn attach_roi_probe(encoder: &gst::Element, hw_type: HardwareEncoderType, roi: Option<RegionOfInterest>) {
+    let Some(roi) = roi.and_then(|r| r.clipped()) else {
+        return;
+    };
+    if !hw_type.supports_roi() {
+        println!("[Encoder] {} ignores the region of interest", hw_type.display_name());
+        return;
     }
+    let Some(sink) = encoder.static_pad("sink") else {
+        return;
+    };
+    sink.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+        let Some(video_info) = pad.current_caps().and_then(|c| gst_video::VideoInfo::from_caps(&c).ok()) else {
+            return gst::PadProbeReturn::Ok;
+        };
+        if let Some(gst::PadProbeData::Buffer(buffer)) = info.data.as_mut() {
+            let buffer = buffer.make_mut();
+            let mut meta = gst_video::VideoRegionOfInterestMeta::add(
+                buffer,
+                "sacho-roi",
+                roi.to_pixels(video_info.width(), video_info.height()),
+            );
+            meta.add_param(gst::Structure::builder("roi/va").field("delta_qp", ROI_DELTA_QP).build());
+            meta.add_param(gst::Structure::builder("roi/qsv").field("delta_qp", ROI_DELTA_QP).build());
+        }
+        gst::PadProbeReturn::Ok
+    });
+    println!(
+        "[Encoder] Region of interest: x={:.2} y={:.2} w={:.2} h={:.2} (delta QP {})",
+        roi.x, roi.y, roi.width, roi.height, ROI_DELTA_QP
+    );
 }
 
 /// Convert an f64 framerate to a GStreamer Fraction for use in caps.
@@ -168,6 +265,11 @@ pub enum HardwareEncoderType {
 }
 
 impl HardwareEncoderType {
+    /// Whether the encoders of this backend honour region-of-interest metas
+    pub fn supports_roi(&self) -> bool {
+        matches!(self, HardwareEncoderType::Qsv | HardwareEncoderType::VaApi)
+    }
+
     /// Get the GStreamer element name for AV1 encoding
     /// Supports both hardware and software (SVT-AV1) encoders
     pub fn av1_encoder_element(&self) -> Option<&'static str> {
@@ -494,6 +596,25 @@ pub fn available_encoders_for_codec(codec: super::VideoCodec) -> Vec<(HardwareEn
     result
 }
 
+/// Pick a replacement for an encoder that failed mid-recording: the software
+/// encoder if the codec has one, else the next available backend. Software is
+/// the last resort, so nothing is returned when it was the one that failed.
+pub fn failover_encoder_for_codec(codec: VideoCodec, failed: HardwareEncoderType) -> Option<HardwareEncoderType> {
+    if failed == HardwareEncoderType::Software {
+        return None;
+    }
+    let available: Vec<HardwareEncoderType> = available_encoders_for_codec(codec)
+        .into_iter()
+        .map(|(hw, _)| hw)
+        .filter(|hw| *hw != failed)
+        .collect();
+    available
+        .iter()
+        .copied()
+        .find(|hw| *hw == HardwareEncoderType::Software)
+        .or_else(|| available.first().copied())
+}
+
 /// Legacy function - detect best AV1 encoder
 pub fn detect_best_encoder() -> HardwareEncoderType {
     detect_best_av1_encoder()
@@ -559,8 +680,7 @@ pub struct AsyncVideoEncoder {
     /// Encoder configuration (stored for potential diagnostics)
     #[allow(dead_code)]
     config: EncoderConfig,
-    /// Hardware encoder type being used (stored for potential diagnostics)
-    #[allow(dead_code)]
+    /// Hardware encoder type being used
     hw_type: HardwareEncoderType,
     /// Shared state for checking encoder status
     state: Arc<Mutex<EncoderState>>,
@@ -580,6 +700,131 @@ struct EncoderState {
     bytes_written: u64,
     is_finished: bool,
     last_error: Option<String>,
+    /// Preset (quality, effort) to switch to before the next frame. Kept
+    /// out of the frame channel so a full channel can't delay or drop it.
+    pending_preset: Option<(u8, u8)>,
+}
+
+/// Name of the videorate element in the common encoding chain
+const RATE_ELEMENT_NAME: &str = "rate";
+
+/// Name of the encoder element in every encoding pipeline
+const ENCODER_ELEMENT_NAME: &str = "encoder";
+
+/// Name of the queue in front of the encoder in the common encoding chain
+const QUEUE_ELEMENT_NAME: &str = "encode_queue";
+
+/// Frames waiting in the queue in front of the encoder, and how many it
+/// holds before dropping. None if the pipeline has no such queue.
+pub(crate) fn encode_queue_level(pipeline: &gst::Pipeline) -> Option<(u64, u64)> {
+    let queue = pipeline.by_name(QUEUE_ELEMENT_NAME)?;
+    Some((
+        queue.property::<u32>("current-level-buffers") as u64,
+        queue.property::<u32>("max-size-buffers") as u64,
+    ))
+}
+
+/// How a running encoder took a new preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetSwitch {
+    /// The new preset sets nothing differently
+    Unchanged,
+    /// Properties were changed in place and a keyframe requested, so the new
+    /// settings start on a clean GOP
+    Live,
+    /// The preset changes properties the encoder can't change while
+    /// running. Nothing was changed; the next file or segment uses it.
+    Deferred,
+}
+
+/// Switch a running encoding pipeline to the preset in `config` without
+/// ending its output.
+///
+/// The preset is applied to a spare encoder element to find which
+/// properties change. If every one of them can be changed while PLAYING,
+/// they are set in place. Otherwise the running encoder is left alone: a new
+/// element would negotiate new caps and codec data, which the muxer can't
+/// take once the file header is written.
+pub(crate) fn switch_preset(
+    pipeline: &gst::Pipeline,
+    config: &EncoderConfig,
+    hw_type: HardwareEncoderType,
+) -> Result<PresetSwitch> {
+    let encoder = pipeline
+        .by_name(ENCODER_ELEMENT_NAME)
+        .ok_or_else(|| EncoderError::Pipeline("Could not find encoder element".into()))?;
+    let spare = AsyncVideoEncoder::create_encoder_for_codec(hw_type, config)?;
+
+    let mut changed = Vec::new();
+    let mut needs_restart = false;
+    for pspec in spare.list_properties().iter() {
+        let flags = pspec.flags();
+        if !flags.contains(gst::glib::ParamFlags::READWRITE)
+            || flags.contains(gst::glib::ParamFlags::CONSTRUCT_ONLY)
+            || pspec.name() == "name"
+        {
+            continue;
+        }
+        let wanted = spare.property_value(pspec.name());
+        let current = encoder.property_value(pspec.name());
+        if wanted.serialize().ok() == current.serialize().ok() {
+            continue;
+        }
+        needs_restart |= !flags.contains(gst::PARAM_FLAG_MUTABLE_PLAYING);
+        changed.push((pspec.name().to_string(), wanted));
+    }
+
+    if changed.is_empty() {
+        return Ok(PresetSwitch::Unchanged);
+    }
+    let names = changed.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(", ");
+    if needs_restart {
+        println!("[Encoder] Preset needs an encoder restart ({}), applies from the next file", names);
+        return Ok(PresetSwitch::Deferred);
+    }
+    for (name, value) in &changed {
+        encoder.set_property_from_value(name, value);
+    }
+    if let Some(src) = encoder.static_pad("src") {
+        src.send_event(gst_video::UpstreamForceKeyUnitEvent::builder().all_headers(true).build());
+    }
+    println!("[Encoder] Preset switched live ({})", names);
+    Ok(PresetSwitch::Live)
+}
+
+/// Counters from the videorate stage: frames in and out, and how many were
+/// duplicated to fill gaps or dropped because they arrived too close together
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameRateStats {
+    pub frames_in: u64,
+    pub frames_out: u64,
+    pub duplicated: u64,
+    pub dropped: u64,
+}
+
+impl FrameRateStats {
+    /// Read the counters from a pipeline built by
+    /// `create_common_pipeline_start_with_target`. None if it has no
+    /// videorate stage.
+    pub fn from_pipeline(pipeline: &gst::Pipeline) -> Option<Self> {
+        let rate = pipeline.by_name(RATE_ELEMENT_NAME)?;
+        Some(Self {
+            frames_in: rate.property::<u64>("in"),
+            frames_out: rate.property::<u64>("out"),
+            duplicated: rate.property::<u64>("duplicate"),
+            dropped: rate.property::<u64>("drop"),
+        })
+    }
+
+    /// Counters accumulated since `earlier` was read
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            frames_in: self.frames_in.saturating_sub(earlier.frames_in),
+            frames_out: self.frames_out.saturating_sub(earlier.frames_out),
+            duplicated: self.duplicated.saturating_sub(earlier.duplicated),
+            dropped: self.dropped.saturating_sub(earlier.dropped),
+        }
+    }
 }
 
 /// Statistics from encoding session
@@ -591,6 +836,8 @@ pub struct EncoderStats {
     /// Actual video content duration (from PTS of first to last frame)
     pub content_duration: Duration,
     pub average_fps: f64,
+    /// videorate counters, when the pipeline converted or conformed the rate
+    pub frame_rate: Option<FrameRateStats>,
 }
 
 impl AsyncVideoEncoder {
@@ -629,6 +876,7 @@ impl AsyncVideoEncoder {
             bytes_written: 0,
             is_finished: false,
             last_error: None,
+            pending_preset: None,
         }));
 
         let state_clone = state.clone();
@@ -687,6 +935,7 @@ impl AsyncVideoEncoder {
             bytes_written: 0,
             is_finished: false,
             last_error: None,
+            pending_preset: None,
         }));
 
         let state_clone = state.clone();
@@ -780,11 +1029,31 @@ impl AsyncVideoEncoder {
         (state.frames_encoded, state.bytes_written)
     }
 
+    /// Frames waiting for the encoder thread, and how many fit before
+    /// frames are dropped
+    pub fn queue_level(&self) -> (u64, u64) {
+        (
+            self.frame_sender.len() as u64,
+            self.frame_sender.capacity().unwrap_or(0) as u64,
+        )
+    }
+
     /// Check if the encoder has encountered an error
     pub fn has_error(&self) -> Option<String> {
         self.state.lock().last_error.clone()
     }
 
+    /// Encoder backend this instance is running on
+    pub fn hw_type(&self) -> HardwareEncoderType {
+        self.hw_type
+    }
+
+    /// Switch to a new preset before the next frame where the encoder can
+    /// change it while running (see [`switch_preset`])
+    pub fn request_preset(&self, preset_level: u8, effort_level: u8) {
+        self.state.lock().pending_preset = Some((preset_level, effort_level));
+    }
+
     /// Main function for the encoder thread
     fn encoder_thread_main(
         receiver: Receiver<EncoderMessage>,
@@ -792,7 +1061,7 @@ impl AsyncVideoEncoder {
         width: u32,
         height: u32,
         fps: f64,
-        config: EncoderConfig,
+        mut config: EncoderConfig,
         hw_type: HardwareEncoderType,
         state: Arc<Mutex<EncoderState>>,
     ) -> Result<EncoderStats> {
@@ -800,6 +1069,9 @@ impl AsyncVideoEncoder {
 
         // Create GStreamer encoding pipeline
         let pipeline = Self::create_pipeline(&output_path, width, height, fps, &config, hw_type)?;
+        if let Some(tags) = &config.tags {
+            super::tags::apply_to_pipeline(&pipeline, tags);
+        }
 
         // Get appsrc element
         let appsrc = pipeline
@@ -830,6 +1102,10 @@ impl AsyncVideoEncoder {
             }
         }
 
+        // Watched while encoding so a driver reset is reported immediately
+        // rather than at EOS
+        let bus = pipeline.bus();
+
         let mut frames_encoded = 0u64;
         let mut frames_dropped_stale = 0u64;
         let mut first_pts: Option<u64> = None;
@@ -870,6 +1146,15 @@ impl AsyncVideoEncoder {
                         continue;
                     }
 
+                    let pending_preset = state.lock().pending_preset.take();
+                    if let Some((preset_level, effort_level)) = pending_preset {
+                        config.preset_level = preset_level;
+                        config.effort_level = effort_level;
+                        if let Err(e) = switch_preset(&pipeline, &config, hw_type) {
+                            println!("[Encoder] Failed to switch preset: {}", e);
+                        }
+                    }
+
                     // Normalize PTS relative to first frame
                     let pts = if let Some(base) = first_pts {
                         frame.pts.saturating_sub(base)
@@ -899,6 +1184,19 @@ impl AsyncVideoEncoder {
                         return Err(EncoderError::Pipeline(err_msg));
                     }
 
+                    let pipeline_error = bus
+                        .as_ref()
+                        .and_then(|bus| bus.pop_filtered(&[gst::MessageType::Error]));
+                    if let Some(msg) = pipeline_error {
+                        if let gst::MessageView::Error(err) = msg.view() {
+                            let err_msg = format!("Encoder error: {} ({:?})", err.error(), err.debug());
+                            println!("[Encoder] {}", err_msg);
+                            state.lock().last_error = Some(err_msg.clone());
+                            pipeline.set_state(gst::State::Null).ok();
+                            return Err(EncoderError::Pipeline(err_msg));
+                        }
+                    }
+
                     frames_encoded += 1;
                     state.lock().frames_encoded = frames_encoded;
 
@@ -969,6 +1267,8 @@ impl AsyncVideoEncoder {
             println!("[Encoder] Warning: Did not receive EOS, forcing stop");
         }
 
+        let frame_rate = FrameRateStats::from_pipeline(&pipeline);
+
         // Stop pipeline gracefully
         pipeline.set_state(gst::State::Null).ok();
 
@@ -1011,6 +1311,7 @@ impl AsyncVideoEncoder {
             encoding_duration,
             content_duration,
             average_fps,
+            frame_rate,
         })
     }
 
@@ -1024,6 +1325,16 @@ impl AsyncVideoEncoder {
     pub(crate) fn remux_to_container(
         input_path: &PathBuf,
         target_container: ContainerFormat,
+    ) -> Result<(PathBuf, u64)> {
+        Self::remux_to_container_with_toc(input_path, target_container, None)
+    }
+
+    /// [`Self::remux_to_container`], writing `toc` (chapters) into the output
+    /// if the target muxer supports it
+    pub(crate) fn remux_to_container_with_toc(
+        input_path: &PathBuf,
+        target_container: ContainerFormat,
+        toc: Option<&gst::Toc>,
     ) -> Result<(PathBuf, u64)> {
         let output_path = if target_container == ContainerFormat::Mkv {
             // MKV→MKV: remux in-place to fix duration header
@@ -1063,6 +1374,10 @@ impl AsyncVideoEncoder {
             mux.set_property("writing-app", "Sacho");
         }
 
+        if let (Some(toc), Some(setter)) = (toc, mux.dynamic_cast_ref::<gst::TocSetter>()) {
+            setter.set_toc(Some(toc));
+        }
+
         let filesink = gst::ElementFactory::make("filesink")
             .property("location", temp_path.to_string_lossy().to_string())
             .build()
@@ -1144,8 +1459,12 @@ impl AsyncVideoEncoder {
 
         if new_size > 0 {
             if target_container == ContainerFormat::Mkv {
-                // MKV→MKV: replace original with remuxed version (fixes duration)
-                std::fs::remove_file(input_path).map_err(|e| EncoderError::Io(e))?;
+                // MKV→MKV: replace original with remuxed version (fixes duration).
+                // The original may be open elsewhere (a player on Windows).
+                if let Err(e) = std::fs::remove_file(input_path) {
+                    let _ = std::fs::remove_file(&temp_path);
+                    return Err(EncoderError::Io(e));
+                }
                 std::fs::rename(&temp_path, &output_path).map_err(|e| EncoderError::Io(e))?;
             } else {
                 // MKV→MP4/WebM: write to new file, delete original MKV
@@ -1241,17 +1560,24 @@ impl AsyncVideoEncoder {
         target_height: Option<u32>,
         target_fps: Option<f64>,
         pixel_format: &str,
+        colorimetry: Option<&str>,
+        constant_frame_rate: bool,
     ) -> Result<(gst::Pipeline, gst_app::AppSrc, gst::Element)> {
         let pipeline = gst::Pipeline::new();
 
         // Create appsrc with raw video caps - must specify format for proper negotiation
         // Format is determined by intermediate_format_for_codec() (NV12 or P010_10LE)
-        let caps = gst::Caps::builder("video/x-raw")
+        let mut caps_builder = gst::Caps::builder("video/x-raw")
             .field("format", pixel_format)
             .field("width", width as i32)
             .field("height", height as i32)
-            .field("framerate", fps_to_gst_fraction(fps))
-            .build();
+            .field("framerate", fps_to_gst_fraction(fps));
+        // Without colorimetry the caps default to BT.709 for HD, which would
+        // mistag (and videoconvert would remap) BT.2020/PQ sources
+        if let Some(colorimetry) = colorimetry {
+            caps_builder = caps_builder.field("colorimetry", colorimetry);
+        }
+        let caps = caps_builder.build();
 
         let appsrc = gst_app::AppSrc::builder()
             .name("src")
@@ -1269,6 +1595,7 @@ impl AsyncVideoEncoder {
         // leaky=downstream: if the encoder truly can't keep up, drop oldest
         // frames rather than blocking the capture pipeline.
         let queue = gst::ElementFactory::make("queue")
+            .name(QUEUE_ELEMENT_NAME)
             .property("max-size-buffers", 1200u32) // 2× max pre-roll at 120fps
             .property("max-size-time", 10_000_000_000u64) // 10 seconds of PTS span
             .property("max-size-bytes", 0u32) // No byte limit
@@ -1319,8 +1646,10 @@ impl AsyncVideoEncoder {
             );
         }
 
-        if (tf - fps).abs() > 0.01 {
+        if (tf - fps).abs() > 0.01 || constant_frame_rate {
+            // Named so FrameRateStats can read its duplicate/drop counters
             let videorate = gst::ElementFactory::make("videorate")
+                .name(RATE_ELEMENT_NAME)
                 .build()
                 .map_err(|e| {
                     EncoderError::Pipeline(format!("Failed to create videorate: {}", e))
@@ -1341,8 +1670,10 @@ impl AsyncVideoEncoder {
             chain_tail = rate_capsfilter;
 
             println!(
-                "[Encoder] Rate conversion from {:.2}fps to {:.2}fps",
-                fps, tf
+                "[Encoder] Rate conversion from {:.2}fps to {:.2}fps{}",
+                fps,
+                tf,
+                if constant_frame_rate { " (constant frame rate)" } else { "" }
             );
         }
 
@@ -1376,6 +1707,8 @@ impl AsyncVideoEncoder {
             config.target_height,
             config.target_fps,
             pixel_format,
+            config.colorimetry.as_deref(),
+            config.constant_frame_rate,
         )?;
 
         let encoder = Self::create_av1_encoder(hw_type, config)?;
@@ -1411,6 +1744,7 @@ impl AsyncVideoEncoder {
         })?;
 
         let encoder = gst::ElementFactory::make(encoder_name)
+            .name(ENCODER_ELEMENT_NAME)
             .build()
             .map_err(|e| {
                 EncoderError::NotAvailable(format!("Failed to create {}: {}", encoder_name, e))
@@ -1424,6 +1758,9 @@ impl AsyncVideoEncoder {
             config.effort_level,
             config.keyframe_interval,
         );
+        super::presets::apply_b_frames(&encoder, config.b_frames);
+        super::presets::apply_content_type(&encoder, VideoCodec::Av1, config.content_type);
+        attach_roi_probe(&encoder, hw_type, config.roi);
 
         Ok(encoder)
     }
@@ -1446,6 +1783,8 @@ impl AsyncVideoEncoder {
             config.target_height,
             config.target_fps,
             pixel_format,
+            config.colorimetry.as_deref(),
+            config.constant_frame_rate,
         )?;
 
         let encoder = Self::create_vp8_encoder(hw_type, config)?;
@@ -1477,6 +1816,7 @@ impl AsyncVideoEncoder {
         })?;
 
         let encoder = gst::ElementFactory::make(encoder_name)
+            .name(ENCODER_ELEMENT_NAME)
             .build()
             .map_err(|e| {
                 EncoderError::NotAvailable(format!("Failed to create {}: {}", encoder_name, e))
@@ -1503,6 +1843,7 @@ impl AsyncVideoEncoder {
             config.effort_level,
             config.keyframe_interval,
         );
+        attach_roi_probe(&encoder, hw_type, config.roi);
 
         Ok(encoder)
     }
@@ -1525,6 +1866,8 @@ impl AsyncVideoEncoder {
             config.target_height,
             config.target_fps,
             pixel_format,
+            config.colorimetry.as_deref(),
+            config.constant_frame_rate,
         )?;
 
         let encoder = Self::create_vp9_encoder(hw_type, config)?;
@@ -1556,6 +1899,7 @@ impl AsyncVideoEncoder {
         })?;
 
         let encoder = gst::ElementFactory::make(encoder_name)
+            .name(ENCODER_ELEMENT_NAME)
             .build()
             .map_err(|e| {
                 EncoderError::NotAvailable(format!("Failed to create {}: {}", encoder_name, e))
@@ -1582,6 +1926,7 @@ impl AsyncVideoEncoder {
             config.effort_level,
             config.keyframe_interval,
         );
+        attach_roi_probe(&encoder, hw_type, config.roi);
 
         Ok(encoder)
     }
@@ -1606,6 +1951,8 @@ impl AsyncVideoEncoder {
             config.target_height,
             config.target_fps,
             pixel_format,
+            config.colorimetry.as_deref(),
+            config.constant_frame_rate,
         )?;
 
         let encoder = Self::create_h264_encoder(hw_type, config)?;
@@ -1641,6 +1988,7 @@ impl AsyncVideoEncoder {
         })?;
 
         let encoder = gst::ElementFactory::make(encoder_name)
+            .name(ENCODER_ELEMENT_NAME)
             .build()
             .map_err(|e| {
                 EncoderError::NotAvailable(format!("Failed to create {}: {}", encoder_name, e))
@@ -1654,6 +2002,8 @@ impl AsyncVideoEncoder {
             config.effort_level,
             config.keyframe_interval,
         );
+        super::presets::apply_b_frames(&encoder, config.b_frames);
+        attach_roi_probe(&encoder, hw_type, config.roi);
 
         Ok(encoder)
     }
@@ -1676,6 +2026,8 @@ impl AsyncVideoEncoder {
             config.target_height,
             config.target_fps,
             pixel_format,
+            config.colorimetry.as_deref(),
+            config.constant_frame_rate,
         )?;
 
         let encoder = Self::create_ffv1_encoder(hw_type, config)?;
@@ -1690,12 +2042,31 @@ impl AsyncVideoEncoder {
         Ok(pipeline)
     }
 
+    /// Create the encoder element for `config.target_codec`
+    pub(crate) fn create_encoder_for_codec(
+        hw_type: HardwareEncoderType,
+        config: &EncoderConfig,
+    ) -> Result<gst::Element> {
+        match config.target_codec {
+            VideoCodec::Av1 => Self::create_av1_encoder(hw_type, config),
+            VideoCodec::Vp8 => Self::create_vp8_encoder(hw_type, config),
+            VideoCodec::Vp9 => Self::create_vp9_encoder(hw_type, config),
+            VideoCodec::H264 => Self::create_h264_encoder(hw_type, config),
+            VideoCodec::Ffv1 => Self::create_ffv1_encoder(hw_type, config),
+            codec => Err(EncoderError::NotAvailable(format!(
+                "Encoding not supported for codec: {:?}",
+                codec
+            ))),
+        }
+    }
+
     /// Create the FFV1 encoder element (avenc_ffv1, software only)
     pub(crate) fn create_ffv1_encoder(
         hw_type: HardwareEncoderType,
         config: &EncoderConfig,
     ) -> Result<gst::Element> {
         let encoder = gst::ElementFactory::make("avenc_ffv1")
+            .name(ENCODER_ELEMENT_NAME)
             .build()
             .map_err(|e| {
                 EncoderError::NotAvailable(format!("Failed to create avenc_ffv1: {}", e))
@@ -8,10 +8,13 @@
 
 pub mod encoder;
 pub mod presets;
+pub mod stream_out;
+pub mod tags;
+pub mod chapters;
 
 pub use encoder::{
-    AsyncVideoEncoder, EncoderConfig, EncoderError, EncoderStats,
-    HardwareEncoderType, RawVideoFrame,
+    AsyncVideoEncoder, EncoderConfig, EncoderError, EncoderStats, FrameRateStats,
+    HardwareEncoderType, RawVideoFrame, RegionOfInterest,
     detect_best_encoder, detect_best_encoder_for_codec, detect_best_av1_encoder, detect_best_vp8_encoder, detect_best_vp9_encoder,
     detect_best_h264_encoder, has_h264_encoder, has_hardware_h264_encoder,
     has_hardware_av1_encoder, has_hardware_vp9_encoder, has_hardware_vp8_encoder,
@@ -20,7 +23,7 @@ pub use encoder::{
     get_recommended_codec,
     available_encoders_for_codec,
 };
-pub use presets::{DEFAULT_PRESET, MIN_PRESET, MAX_PRESET};
+pub use presets::{ContentType, DEFAULT_PRESET, MIN_PRESET, MAX_PRESET};
 
 use serde::{Deserialize, Serialize};
 
@@ -290,8 +293,33 @@ pub fn intermediate_format_for_codec(codec: VideoCodec, video_bit_depth: Option<
 }
 
 /// Returns true if the GStreamer pixel format string represents 10-bit or higher.
+/// Covers the P010/P012/P016 semi-planar family and the `_10LE`/`_12LE`/`_16LE`
+/// planar variants. Packed RGB16/BGR16 are 5:6:5 and not deep color.
 pub fn is_10bit_format(format: &str) -> bool {
-    format.contains("10")
+    format.starts_with("P01")
+        || format.contains("10")
+        || format.contains("_12")
+        || format.contains("_16")
+        || format.starts_with("GRAY16")
+}
+
+/// Bits per component for a raw pixel format: 10 for any deep format (the
+/// intermediate is P010 at most), 8 otherwise.
+pub fn bit_depth_for_format(format: &str) -> u8 {
+    if is_10bit_format(format) { 10 } else { 8 }
+}
+
+/// Returns true if a GStreamer colorimetry string uses an HDR transfer
+/// function (PQ / SMPTE ST 2084 or HLG / ARIB STD-B67).
+///
+/// Colorimetry is either a named preset ("bt2100-pq", "bt2100-hlg") or the
+/// numeric `range:matrix:transfer:primaries` form, where transfer 14 is PQ
+/// and 15 is HLG.
+pub fn is_hdr_colorimetry(colorimetry: &str) -> bool {
+    if colorimetry.starts_with("bt2100") {
+        return true;
+    }
+    matches!(colorimetry.split(':').nth(2), Some("14") | Some("15"))
 }
 
 // ============================================================================
@@ -35,9 +35,15 @@
 //! 3. Each level must produce output suitable for real-time encoding at
 //!    common resolutions (720p–1080p, 30 fps).
 //! 4. Document which GStreamer element properties you set and why.
+//!
+//! ## Content type
+//!
+//! A per-device [`ContentType`] hint tunes the encoder for what the camera
+//! is looking at, on top of the preset. See [`apply_content_type()`].
 
 use gstreamer as gst;
 use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use super::encoder::HardwareEncoderType;
 use super::VideoCodec;
@@ -193,6 +199,72 @@ pub fn apply_preset(
     }
 }
 
+/// Override the preset's B-frame count with a per-device setting.
+///
+/// Call after [`apply_preset()`]. `None` keeps the preset's choice. Encoders
+/// name the property differently (`bframes` on Media Foundation, `b-frames`
+/// on NVENC/QSV/VA) and VideoToolbox only offers an on/off switch. Encoders
+/// without B-frame support are left untouched.
+pub fn apply_b_frames(encoder: &gst::Element, b_frames: Option<u32>) {
+    let Some(b_frames) = b_frames else {
+        return;
+    };
+    if try_set_u32_clamped(encoder, "bframes", b_frames)
+        || try_set_u32_clamped(encoder, "b-frames", b_frames)
+    {
+        println!("[Preset] B-frames: {}", b_frames);
+    } else if encoder.find_property("allow-frame-reordering").is_some() {
+        encoder.set_property("allow-frame-reordering", b_frames > 0);
+        println!("[Preset] Frame reordering: {}", b_frames > 0);
+    }
+}
+
+/// What a video device is pointed at, used to tune the encoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentType {
+    /// Natural camera footage with motion (encoder defaults)
+    #[default]
+    Camera,
+    /// Screen capture: flat colours, sharp text, few distinct colours
+    Screen,
+    /// Fixed camera on a mostly still scene, e.g. an overhead keyboard shot
+    Static,
+}
+
+/// Tune the encoder for the device's content type.
+///
+/// Call after [`apply_preset()`]. Only AV1 encoders have tools worth
+/// switching; everything else is left untouched.
+///
+/// - **Screen** turns on SVT-AV1's screen content coding (palette and intra
+///   block copy), which compresses text and UI far better than the
+///   camera-oriented tools.
+/// - **Static** lets SVT-AV1 decide per frame whether screen content tools
+///   pay off, since a still shot of keys and a panel often behaves like one.
+/// - Both turn off NVENC temporal AQ, which otherwise spends bits
+///   re-sharpening a background that hasn't changed.
+pub fn apply_content_type(encoder: &gst::Element, codec: VideoCodec, content_type: ContentType) {
+    if codec != VideoCodec::Av1 || content_type == ContentType::Camera {
+        return;
+    }
+
+    // SVT-AV1 (svtav1enc) takes extra options as a "key=value:key=value" string
+    if encoder.find_property("parameters-string").is_some() {
+        let parameters = match content_type {
+            ContentType::Screen => "scm=1",
+            _ => "scm=2",
+        };
+        encoder.set_property("parameters-string", parameters);
+        println!("[Preset] Content type {:?}: {}", content_type, parameters);
+    }
+    // NVENC (nvav1enc)
+    if encoder.find_property("temporal-aq").is_some() {
+        encoder.set_property("temporal-aq", false);
+        println!("[Preset] Content type {:?}: temporal AQ off", content_type);
+    }
+}
+
 // ═════════════════════════════════════════════════════════════════════════════
 // AV1 Encoders
 // ═════════════════════════════════════════════════════════════════════════════
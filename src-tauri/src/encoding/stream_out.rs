@@ -0,0 +1,622 @@
+//! RTMP simulcast alongside recording
+//!
+//! The live stream is a separate GStreamer pipeline fed with copies of the
+//! captured frames and samples through a bounded channel. It never shares
+//! elements with the recording pipelines, so a dropped connection, a stalled
+//! uplink or a crashed streaming encoder only affects the stream: taps drop
+//! data when the channel is full, and the worker thread tears the pipeline
+//! down and reconnects with backoff while the recording carries on.
+//!
+//! Pipeline:
+//!   video appsrc -> [decoder] -> videoconvert [-> videoscale] -> H.264 -> h264parse \
+//!                                                                                      flvmux -> rtmp sink
+//!   audio appsrc -> audioconvert -> audioresample -> AAC -> aacparse ----------------/
+//!
+//! H.264 sources are forwarded without re-encoding.
+
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError, Sender};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use gstreamer_audio as gst_audio;
+
+use super::encoder::{fps_to_gst_fraction, AsyncVideoEncoder, EncoderConfig, EncoderError, Result};
+use super::VideoCodec;
+
+/// Messages buffered between the capture threads and the stream worker.
+/// About two seconds of video at 30fps; audio chunks are much smaller.
+const STREAM_QUEUE_CAPACITY: usize = 256;
+
+/// Frames taller than this are scaled down before streaming
+const MAX_STREAM_HEIGHT: u32 = 1080;
+
+/// Reconnect backoff bounds
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(2);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// A connection that stayed up this long resets the backoff
+const STABLE_CONNECTION: Duration = Duration::from_secs(30);
+
+/// How long to wait for the muxer to flush on stop
+const EOS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Audio source for the stream
+#[derive(Debug, Clone)]
+pub struct StreamAudioSettings {
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub bitrate_kbps: u32,
+}
+
+/// Everything the worker needs to (re)build the stream pipeline
+#[derive(Debug, Clone)]
+pub struct StreamSettings {
+    /// rtmp:// or rtmps:// URL including the stream key
+    pub url: String,
+    /// Capture dimensions of the streamed video device
+    pub width: u32,
+    pub height: u32,
+    pub fps: f64,
+    pub video_bitrate_kbps: u32,
+    pub audio: Option<StreamAudioSettings>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamState {
+    /// Waiting for the first frame or the server handshake
+    Connecting,
+    Live,
+    /// The connection failed; retrying after a delay
+    Reconnecting,
+    Stopped,
+}
+
+/// Stream health reported to the UI
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamStatus {
+    pub state: StreamState,
+    /// Last error, if any
+    pub message: Option<String>,
+    /// Number of times the stream was rebuilt after a failure
+    pub reconnects: u32,
+}
+
+enum StreamMessage {
+    Video {
+        data: Vec<u8>,
+        /// Source format string (e.g. "NV12", "MJPEG", "H264")
+        format: String,
+        wall_time: Instant,
+    },
+    Audio {
+        samples: Vec<f32>,
+        arrived: Instant,
+    },
+}
+
+/// Cheap handle given to capture code to feed the stream.
+/// Pushing never blocks: data is dropped when the stream falls behind.
+#[derive(Clone)]
+pub struct StreamTap {
+    sender: Sender<StreamMessage>,
+    /// Frames captured before this (pre-roll) are not streamed
+    since: Instant,
+}
+
+impl StreamTap {
+    /// Queue a captured video frame (raw or encoded as captured)
+    pub fn push_video(&self, data: &[u8], format: &str, wall_time: Instant) {
+        if wall_time < self.since || self.sender.is_full() {
+            return;
+        }
+        let _ = self.sender.try_send(StreamMessage::Video {
+            data: data.to_vec(),
+            format: format.to_string(),
+            wall_time,
+        });
+    }
+
+    /// Queue interleaved F32 samples from the streamed audio device
+    pub fn push_audio(&self, samples: &[f32]) {
+        if self.sender.is_full() {
+            return;
+        }
+        let _ = self.sender.try_send(StreamMessage::Audio {
+            samples: samples.to_vec(),
+            arrived: Instant::now(),
+        });
+    }
+}
+
+/// Owns the stream worker thread for one recording
+pub struct RtmpStreamer {
+    sender: Sender<StreamMessage>,
+    stop: Arc<AtomicBool>,
+    started: Instant,
+}
+
+impl RtmpStreamer {
+    /// Spawn the stream worker. The pipeline is built when the first video
+    /// frame arrives, since its format decides the decode chain.
+    pub fn start<F>(settings: StreamSettings, on_status: F) -> Result<Self>
+    where
+        F: Fn(&StreamStatus) + Send + 'static,
+    {
+        if !(settings.url.starts_with("rtmp://") || settings.url.starts_with("rtmps://")) {
+            return Err(EncoderError::Pipeline("Stream URL must start with rtmp:// or rtmps://".into()));
+        }
+        gst::init().map_err(|e| EncoderError::Gst(e.to_string()))?;
+
+        let (sender, receiver) = bounded::<StreamMessage>(STREAM_QUEUE_CAPACITY);
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = stop.clone();
+
+        std::thread::Builder::new()
+            .name("sacho-rtmp-stream".into())
+            .spawn(move || stream_worker(receiver, settings, stop_flag, on_status))
+            .map_err(|e| EncoderError::Pipeline(format!("Failed to spawn stream thread: {}", e)))?;
+
+        Ok(Self { sender, stop, started: Instant::now() })
+    }
+
+    pub fn tap(&self) -> StreamTap {
+        StreamTap { sender: self.sender.clone(), since: self.started }
+    }
+
+    /// Ask the worker to flush and disconnect. Returns immediately; the
+    /// worker finishes on its own so stopping never delays finalization.
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for RtmpStreamer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Why a live pipeline ended
+enum SessionEnd {
+    Stopped,
+    Failed(String),
+}
+
+fn stream_worker<F>(receiver: Receiver<StreamMessage>, settings: StreamSettings, stop: Arc<AtomicBool>, on_status: F)
+where
+    F: Fn(&StreamStatus),
+{
+    let mut status = StreamStatus { state: StreamState::Connecting, message: None, reconnects: 0 };
+    on_status(&status);
+    let mut delay = MIN_RECONNECT_DELAY;
+
+    'outer: loop {
+        // The first frame decides the input caps
+        let (first_data, first_format, first_time) = loop {
+            if stop.load(Ordering::Relaxed) {
+                break 'outer;
+            }
+            match receiver.recv_timeout(Duration::from_millis(100)) {
+                Ok(StreamMessage::Video { data, format, wall_time }) => break (data, format, wall_time),
+                Ok(StreamMessage::Audio { .. }) | Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break 'outer,
+            }
+        };
+
+        let started = Instant::now();
+        let end = match StreamPipeline::build(&settings, &first_format) {
+            Ok(mut pipeline) => {
+                let end = pipeline.run(&receiver, &stop, (first_data, first_time), &mut status, &on_status);
+                pipeline.shutdown();
+                end
+            }
+            Err(e) => SessionEnd::Failed(e.to_string()),
+        };
+
+        match end {
+            SessionEnd::Stopped => break,
+            SessionEnd::Failed(message) => {
+                println!("[Stream] {}; reconnecting in {:?}", message, delay);
+                if started.elapsed() >= STABLE_CONNECTION {
+                    delay = MIN_RECONNECT_DELAY;
+                }
+                status.state = StreamState::Reconnecting;
+                status.message = Some(message);
+                status.reconnects += 1;
+                on_status(&status);
+
+                // Discard everything captured while waiting
+                let retry_at = Instant::now() + delay;
+                while Instant::now() < retry_at {
+                    if stop.load(Ordering::Relaxed) {
+                        break 'outer;
+                    }
+                    if let Err(RecvTimeoutError::Disconnected) = receiver.recv_timeout(Duration::from_millis(100)) {
+                        break 'outer;
+                    }
+                }
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+                status.state = StreamState::Connecting;
+                on_status(&status);
+            }
+        }
+    }
+
+    status.state = StreamState::Stopped;
+    on_status(&status);
+    println!("[Stream] Stopped");
+}
+
+struct StreamPipeline {
+    pipeline: gst::Pipeline,
+    video_src: gst_app::AppSrc,
+    audio_src: Option<gst_app::AppSrc>,
+    /// Format the video appsrc was built for
+    video_format: String,
+    audio_channels: u16,
+    audio_rate: u32,
+    /// Wall-clock origin of the stream's timestamps
+    epoch: Option<Instant>,
+    last_video_pts: Option<u64>,
+    /// Audio PTS origin (ns) and samples pushed since then
+    audio_origin_ns: Option<u64>,
+    audio_frames_pushed: u64,
+}
+
+impl StreamPipeline {
+    fn build(settings: &StreamSettings, format: &str) -> Result<Self> {
+        let pipeline = gst::Pipeline::new();
+        let make = |name: &str| {
+            gst::ElementFactory::make(name)
+                .build()
+                .map_err(|e| EncoderError::NotAvailable(format!("Failed to create {}: {}", name, e)))
+        };
+
+        // Video input caps: raw pixel format or the device's encoded format
+        let (caps_name, pixel_format) = super::format_to_gst_caps(format);
+        let mut caps = gst::Caps::builder(caps_name)
+            .field("width", settings.width as i32)
+            .field("height", settings.height as i32)
+            .field("framerate", fps_to_gst_fraction(settings.fps));
+        if let Some(pixel_format) = pixel_format {
+            caps = caps.field("format", pixel_format);
+        }
+        if format == "H264" {
+            caps = caps.field("stream-format", "byte-stream");
+        }
+        let video_src = gst_app::AppSrc::builder()
+            .caps(&caps.build())
+            .format(gst::Format::Time)
+            .is_live(true)
+            .stream_type(gst_app::AppStreamType::Stream)
+            .build();
+
+        let muxer = gst::ElementFactory::make("flvmux")
+            .property("streamable", true)
+            .build()
+            .map_err(|e| EncoderError::NotAvailable(format!("Failed to create flvmux: {}", e)))?;
+        let sink_name = if gst::ElementFactory::find("rtmp2sink").is_some() { "rtmp2sink" } else { "rtmpsink" };
+        let sink = gst::ElementFactory::make(sink_name)
+            .property("location", settings.url.as_str())
+            .build()
+            .map_err(|e| EncoderError::NotAvailable(format!("Failed to create {}: {}", sink_name, e)))?;
+        pipeline
+            .add_many([video_src.upcast_ref(), &muxer, &sink])
+            .map_err(|e| EncoderError::Pipeline(format!("Failed to add stream elements: {}", e)))?;
+        muxer
+            .link(&sink)
+            .map_err(|e| EncoderError::Pipeline(format!("Failed to link flvmux->sink: {}", e)))?;
+
+        // Video branch
+        let mut chain: Vec<gst::Element> = vec![video_src.clone().upcast()];
+        if format == "H264" {
+            // Already H.264: forward as-is, repeating SPS/PPS for late joiners
+            let parser = make("h264parse")?;
+            parser.set_property("config-interval", -1i32);
+            chain.push(parser);
+        } else {
+            if let Some(decoder) = super::decoder_for_format(format) {
+                chain.push(make(decoder)?);
+            }
+            chain.push(make("queue")?);
+            chain.push(make("videoconvert")?);
+            let (out_width, out_height) = stream_dimensions(settings.width, settings.height);
+            if out_height != settings.height {
+                chain.push(make("videoscale")?);
+            }
+            let scaled_caps = gst::Caps::builder("video/x-raw")
+                .field("format", "NV12")
+                .field("width", out_width as i32)
+                .field("height", out_height as i32)
+                .build();
+            chain.push(
+                gst::ElementFactory::make("capsfilter")
+                    .property("caps", scaled_caps)
+                    .build()
+                    .map_err(|e| EncoderError::Pipeline(format!("Failed to create capsfilter: {}", e)))?,
+            );
+            chain.push(create_stream_video_encoder(settings)?);
+            let parser = make("h264parse")?;
+            parser.set_property("config-interval", -1i32);
+            chain.push(parser);
+        }
+        pipeline
+            .add_many(&chain[1..])
+            .map_err(|e| EncoderError::Pipeline(format!("Failed to add video elements: {}", e)))?;
+        chain.push(muxer.clone());
+        gst::Element::link_many(&chain)
+            .map_err(|e| EncoderError::Pipeline(format!("Failed to link video branch: {}", e)))?;
+
+        // Audio branch
+        let (audio_src, audio_channels, audio_rate) = match &settings.audio {
+            Some(audio) => {
+                let info = gst_audio::AudioInfo::builder(
+                    gst_audio::AudioFormat::F32le,
+                    audio.sample_rate,
+                    audio.channels as u32,
+                )
+                .build()
+                .map_err(|e| EncoderError::Pipeline(format!("Failed to create audio info: {}", e)))?;
+                let audio_caps = info
+                    .to_caps()
+                    .map_err(|e| EncoderError::Pipeline(format!("Failed to create audio caps: {}", e)))?;
+                let src = gst_app::AppSrc::builder()
+                    .caps(&audio_caps)
+                    .format(gst::Format::Time)
+                    .is_live(true)
+                    .stream_type(gst_app::AppStreamType::Stream)
+                    .build();
+                // FLV only carries 44.1kHz AAC reliably across servers
+                let rate_caps = gst::Caps::builder("audio/x-raw").field("rate", 44100i32).build();
+                let rate_filter = gst::ElementFactory::make("capsfilter")
+                    .property("caps", rate_caps)
+                    .build()
+                    .map_err(|e| EncoderError::Pipeline(format!("Failed to create capsfilter: {}", e)))?;
+                let audio_chain = [
+                    src.clone().upcast(),
+                    make("audioconvert")?,
+                    make("audioresample")?,
+                    rate_filter,
+                    create_stream_audio_encoder(audio.bitrate_kbps)?,
+                    make("aacparse")?,
+                ];
+                pipeline
+                    .add_many(&audio_chain)
+                    .map_err(|e| EncoderError::Pipeline(format!("Failed to add audio elements: {}", e)))?;
+                let mut linked = audio_chain.to_vec();
+                linked.push(muxer.clone());
+                gst::Element::link_many(&linked)
+                    .map_err(|e| EncoderError::Pipeline(format!("Failed to link audio branch: {}", e)))?;
+                (Some(src), audio.channels.max(1), audio.sample_rate)
+            }
+            None => (None, 1, 0),
+        };
+
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|e| EncoderError::Pipeline(format!("Failed to start stream pipeline: {:?}", e)))?;
+        println!("[Stream] Connecting to {} via {} ({} source)", redact_url(&settings.url), sink_name, format);
+
+        Ok(Self {
+            pipeline,
+            video_src,
+            audio_src,
+            video_format: format.to_string(),
+            audio_channels,
+            audio_rate,
+            epoch: None,
+            last_video_pts: None,
+            audio_origin_ns: None,
+            audio_frames_pushed: 0,
+        })
+    }
+
+    /// Feed the pipeline until stopped or the connection fails
+    fn run<F>(
+        &mut self,
+        receiver: &Receiver<StreamMessage>,
+        stop: &AtomicBool,
+        first: (Vec<u8>, Instant),
+        status: &mut StreamStatus,
+        on_status: &F,
+    ) -> SessionEnd
+    where
+        F: Fn(&StreamStatus),
+    {
+        self.epoch = Some(first.1);
+        if let Err(e) = self.push_video(first.0, first.1) {
+            return SessionEnd::Failed(e);
+        }
+
+        let bus = match self.pipeline.bus() {
+            Some(bus) => bus,
+            None => return SessionEnd::Failed("Stream pipeline has no bus".into()),
+        };
+
+        loop {
+            while let Some(msg) = bus.pop() {
+                match msg.view() {
+                    gst::MessageView::Error(err) => {
+                        return SessionEnd::Failed(format!("Stream error: {}", err.error()));
+                    }
+                    gst::MessageView::Eos(_) => {
+                        return SessionEnd::Failed("Stream ended unexpectedly".into());
+                    }
+                    gst::MessageView::StateChanged(change)
+                        if change.src() == Some(self.pipeline.upcast_ref::<gst::Object>())
+                            && change.current() == gst::State::Playing
+                            && status.state != StreamState::Live =>
+                    {
+                        status.state = StreamState::Live;
+                        status.message = None;
+                        on_status(status);
+                        println!("[Stream] Live");
+                    }
+                    _ => {}
+                }
+            }
+
+            if stop.load(Ordering::Relaxed) {
+                self.finish(&bus);
+                return SessionEnd::Stopped;
+            }
+
+            let result = match receiver.recv_timeout(Duration::from_millis(100)) {
+                Ok(StreamMessage::Video { data, format, wall_time }) => {
+                    if format != self.video_format {
+                        // Capture format changed mid-recording; rebuild for the new caps
+                        return SessionEnd::Failed(format!("Video format changed to {}", format));
+                    }
+                    self.push_video(data, wall_time)
+                }
+                Ok(StreamMessage::Audio { samples, arrived }) => self.push_audio(samples, arrived),
+                Err(RecvTimeoutError::Timeout) => Ok(()),
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.finish(&bus);
+                    return SessionEnd::Stopped;
+                }
+            };
+            if let Err(e) = result {
+                return SessionEnd::Failed(e);
+            }
+        }
+    }
+
+    fn push_video(&mut self, data: Vec<u8>, wall_time: Instant) -> std::result::Result<(), String> {
+        let Some(epoch) = self.epoch else { return Ok(()) };
+        let Some(offset) = wall_time.checked_duration_since(epoch) else { return Ok(()) };
+        let mut pts = offset.as_nanos() as u64;
+        if let Some(last) = self.last_video_pts {
+            if pts <= last {
+                pts = last + 1;
+            }
+        }
+        self.last_video_pts = Some(pts);
+
+        let mut buffer = gst::Buffer::from_mut_slice(data);
+        if let Some(buffer) = buffer.get_mut() {
+            buffer.set_pts(gst::ClockTime::from_nseconds(pts));
+        }
+        self.video_src
+            .push_buffer(buffer)
+            .map(|_| ())
+            .map_err(|e| format!("Video push failed: {:?}", e))
+    }
+
+    fn push_audio(&mut self, samples: Vec<f32>, arrived: Instant) -> std::result::Result<(), String> {
+        let (Some(src), Some(epoch)) = (&self.audio_src, self.epoch) else { return Ok(()) };
+        let rate = self.audio_rate as u64;
+        let frames = (samples.len() / self.audio_channels as usize) as u64;
+        if frames == 0 || rate == 0 {
+            return Ok(());
+        }
+        // Anchor the first chunk at its capture time, then count samples so
+        // audio stays continuous regardless of callback jitter
+        let origin = *self.audio_origin_ns.get_or_insert_with(|| {
+            let chunk = Duration::from_nanos(frames * 1_000_000_000 / rate);
+            arrived
+                .checked_duration_since(epoch)
+                .map(|d| d.saturating_sub(chunk).as_nanos() as u64)
+                .unwrap_or(0)
+        });
+        let pts = origin + self.audio_frames_pushed * 1_000_000_000 / rate;
+        let duration = frames * 1_000_000_000 / rate;
+        self.audio_frames_pushed += frames;
+
+        let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        let mut buffer = gst::Buffer::from_mut_slice(bytes);
+        if let Some(buffer) = buffer.get_mut() {
+            buffer.set_pts(gst::ClockTime::from_nseconds(pts));
+            buffer.set_duration(gst::ClockTime::from_nseconds(duration));
+        }
+        src.push_buffer(buffer)
+            .map(|_| ())
+            .map_err(|e| format!("Audio push failed: {:?}", e))
+    }
+
+    /// Send EOS so the muxer closes the FLV stream cleanly
+    fn finish(&self, bus: &gst::Bus) {
+        let _ = self.video_src.end_of_stream();
+        if let Some(src) = &self.audio_src {
+            let _ = src.end_of_stream();
+        }
+        let _ = bus.timed_pop_filtered(
+            gst::ClockTime::from_nseconds(EOS_TIMEOUT.as_nanos() as u64),
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        );
+    }
+
+    fn shutdown(&self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+/// Output size: source size, scaled down to at most `MAX_STREAM_HEIGHT`
+fn stream_dimensions(width: u32, height: u32) -> (u32, u32) {
+    if height <= MAX_STREAM_HEIGHT || height == 0 {
+        return (width, height);
+    }
+    let scaled_width = (width as u64 * MAX_STREAM_HEIGHT as u64 / height as u64) as u32;
+    // Encoders need even dimensions for 4:2:0
+    (scaled_width & !1, MAX_STREAM_HEIGHT)
+}
+
+/// x264 tuned for low latency when installed, else the platform H.264 encoder
+fn create_stream_video_encoder(settings: &StreamSettings) -> Result<gst::Element> {
+    let keyframe_interval = (settings.fps.round() as u32).max(1) * 2;
+    if gst::ElementFactory::find("x264enc").is_some() {
+        let encoder = gst::ElementFactory::make("x264enc")
+            .build()
+            .map_err(|e| EncoderError::NotAvailable(format!("Failed to create x264enc: {}", e)))?;
+        encoder.set_property_from_str("tune", "zerolatency");
+        encoder.set_property_from_str("speed-preset", "veryfast");
+        encoder.set_property("bitrate", settings.video_bitrate_kbps);
+        encoder.set_property("key-int-max", keyframe_interval);
+        return Ok(encoder);
+    }
+
+    let hw_type = super::detect_best_h264_encoder()
+        .ok_or_else(|| EncoderError::NotAvailable("No H.264 encoder available for streaming".into()))?;
+    let config = EncoderConfig {
+        keyframe_interval,
+        target_codec: VideoCodec::H264,
+        ..EncoderConfig::default()
+    };
+    let encoder = AsyncVideoEncoder::create_h264_encoder(hw_type, &config)?;
+    // mfh264enc and vtenc_h264 both take kbit/s
+    if encoder.find_property("bitrate").is_some() {
+        encoder.set_property_from_str("bitrate", &settings.video_bitrate_kbps.to_string());
+    }
+    Ok(encoder)
+}
+
+/// First available AAC encoder
+fn create_stream_audio_encoder(bitrate_kbps: u32) -> Result<gst::Element> {
+    for name in ["fdkaacenc", "avenc_aac", "voaacenc", "faac"] {
+        if gst::ElementFactory::find(name).is_none() {
+            continue;
+        }
+        let encoder = gst::ElementFactory::make(name)
+            .build()
+            .map_err(|e| EncoderError::NotAvailable(format!("Failed to create {}: {}", name, e)))?;
+        if encoder.find_property("bitrate").is_some() {
+            encoder.set_property_from_str("bitrate", &(bitrate_kbps * 1000).to_string());
+        }
+        return Ok(encoder);
+    }
+    Err(EncoderError::NotAvailable("No AAC encoder available for streaming".into()))
+}
+
+/// Strip the stream key from a URL for logging
+fn redact_url(url: &str) -> String {
+    match url.rfind('/') {
+        Some(i) if i > url.find("//").map(|j| j + 2).unwrap_or(0) => format!("{}/****", &url[..i]),
+        _ => url.to_string(),
+    }
+}
@@ -0,0 +1,94 @@
+// Provenance tags embedded in recorded media files
+//
+// So a file copied out of its session folder still says where it came from:
+// session title, recording date, device and Sacho version are set on every
+// tag-aware element of a writer pipeline, which flacenc writes as Vorbis
+// comments and matroskamux as Matroska tags. FLAC writes its comments with
+// the stream header, so tags must be applied before the first buffer. WAV
+// files are written by `recording::wav`, which puts the same tags in a RIFF
+// INFO chunk.
+
+use std::path::Path;
+
+use chrono::Local;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+
+/// Tags for one recorded file
+#[derive(Debug, Clone)]
+pub struct MediaTags {
+    /// Session title, or the folder name for untitled sessions
+    pub title: String,
+    pub session_id: String,
+    pub device_name: String,
+    pub recorded_at: chrono::DateTime<Local>,
+}
+
+impl MediaTags {
+    /// Tags for a file written into `session_path` by `device_name`, recorded now
+    pub fn new(session_path: &Path, device_name: &str) -> Self {
+        let session_id = session_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        Self {
+            title: crate::session::extract_title_from_folder_name(&session_id)
+                .unwrap_or_else(|| session_id.clone()),
+            session_id,
+            device_name: device_name.to_string(),
+            recorded_at: Local::now(),
+        }
+    }
+
+    /// Writing application, e.g. "Sacho 1.2.0"
+    pub fn software() -> String {
+        format!("Sacho {}", env!("CARGO_PKG_VERSION"))
+    }
+
+    pub fn comment(&self) -> String {
+        format!(
+            "Recorded by {} from {} (session {})",
+            Self::software(), self.device_name, self.session_id
+        )
+    }
+
+    /// The tags as RIFF INFO entries
+    pub fn riff_info(&self) -> Vec<([u8; 4], String)> {
+        vec![
+            (*b"INAM", self.title.clone()),
+            (*b"IPRD", self.session_id.clone()),
+            (*b"IART", self.device_name.clone()),
+            (*b"ISFT", Self::software()),
+            (*b"ICMT", self.comment()),
+            (*b"ICRD", self.recorded_at.format("%Y-%m-%d").to_string()),
+        ]
+    }
+
+    pub fn to_tag_list(&self) -> gst::TagList {
+        let version = Self::software();
+        let comment = self.comment();
+        let mut list = gst::TagList::new();
+        {
+            let tags = list.get_mut().expect("new tag list is writable");
+            tags.add::<gst::tags::Title>(&self.title.as_str(), gst::TagMergeMode::Replace);
+            tags.add::<gst::tags::Album>(&self.session_id.as_str(), gst::TagMergeMode::Replace);
+            tags.add::<gst::tags::Artist>(&self.device_name.as_str(), gst::TagMergeMode::Replace);
+            tags.add::<gst::tags::Encoder>(&version.as_str(), gst::TagMergeMode::Replace);
+            tags.add::<gst::tags::Comment>(&comment.as_str(), gst::TagMergeMode::Replace);
+            if let Ok(date) = gst::DateTime::from_iso8601_string(&self.recorded_at.to_rfc3339()) {
+                tags.add::<gst::tags::DateTime>(&date, gst::TagMergeMode::Replace);
+            }
+        }
+        list
+    }
+}
+
+/// Set `tags` on every element in `pipeline` that writes tags (muxers and
+/// encoders implementing GstTagSetter)
+pub fn apply_to_pipeline(pipeline: &gst::Pipeline, tags: &gst::TagList) {
+    for element in pipeline.iterate_all_by_interface(gst::TagSetter::static_type()).into_iter().flatten() {
+        if let Some(setter) = element.dynamic_cast_ref::<gst::TagSetter>() {
+            setter.merge_tags(tags, gst::TagMergeMode::Replace);
+        }
+    }
+}
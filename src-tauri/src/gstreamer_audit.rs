@@ -0,0 +1,380 @@
+//! GStreamer plugin availability audit
+//!
+//! Pipelines create their elements on demand, so a missing plugin normally
+//! only shows up as a failure when a recording or playback starts. This
+//! checks every element Sacho can request on this platform against the
+//! registry up front and groups the results by feature, with a hint for
+//! installing whatever is missing.
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::Serialize;
+
+/// Where an element comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GstPackage {
+    Core,
+    Base,
+    Good,
+    Bad,
+    Ugly,
+    Libav,
+    Vaapi,
+}
+
+impl GstPackage {
+    pub fn display_name(self) -> &'static str {
+        match self {
+            GstPackage::Core => "GStreamer core",
+            GstPackage::Base => "gst-plugins-base",
+            GstPackage::Good => "gst-plugins-good",
+            GstPackage::Bad => "gst-plugins-bad",
+            GstPackage::Ugly => "gst-plugins-ugly",
+            GstPackage::Libav => "gst-libav",
+            GstPackage::Vaapi => "gstreamer-vaapi",
+        }
+    }
+
+    /// Debian/Ubuntu and Fedora package names
+    fn linux_packages(self) -> (&'static str, &'static str) {
+        match self {
+            GstPackage::Core => ("libgstreamer1.0-0", "gstreamer1"),
+            GstPackage::Base => ("gstreamer1.0-plugins-base", "gstreamer1-plugins-base"),
+            GstPackage::Good => ("gstreamer1.0-plugins-good", "gstreamer1-plugins-good"),
+            GstPackage::Bad => ("gstreamer1.0-plugins-bad", "gstreamer1-plugins-bad-free"),
+            GstPackage::Ugly => ("gstreamer1.0-plugins-ugly", "gstreamer1-plugins-ugly"),
+            GstPackage::Libav => ("gstreamer1.0-libav", "gstreamer1-libav"),
+            GstPackage::Vaapi => ("gstreamer1.0-vaapi", "gstreamer1-vaapi"),
+        }
+    }
+}
+
+/// What an element does in a pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElementRole {
+    Source,
+    Sink,
+    Encoder,
+    Decoder,
+    Parser,
+    Muxer,
+    Demuxer,
+    Utility,
+}
+
+/// One element Sacho may create
+struct ElementSpec {
+    name: &'static str,
+    package: GstPackage,
+    role: ElementRole,
+    feature: &'static str,
+    /// One of several interchangeable elements; the feature needs any one
+    alternative: bool,
+    /// Hardware the element needs besides the plugin (GPU vendor, etc.)
+    hardware: Option<&'static str>,
+    /// Operating systems the element is used on (empty = all)
+    platforms: &'static [&'static str],
+}
+
+const fn element(
+    name: &'static str,
+    package: GstPackage,
+    role: ElementRole,
+    feature: &'static str,
+) -> ElementSpec {
+    ElementSpec { name, package, role, feature, alternative: false, hardware: None, platforms: &[] }
+}
+
+impl ElementSpec {
+    const fn alternative(mut self) -> Self {
+        self.alternative = true;
+        self
+    }
+
+    const fn hardware(mut self, hardware: &'static str) -> Self {
+        self.hardware = Some(hardware);
+        self
+    }
+
+    const fn on(mut self, platforms: &'static [&'static str]) -> Self {
+        self.platforms = platforms;
+        self
+    }
+}
+
+const WINDOWS: &[&str] = &["windows"];
+const MACOS: &[&str] = &["macos"];
+const LINUX: &[&str] = &["linux"];
+const WINDOWS_LINUX: &[&str] = &["windows", "linux"];
+
+const CORE_PIPELINES: &str = "Core pipelines";
+const CAMERA_CAPTURE: &str = "Camera capture";
+const MJPEG: &str = "MJPEG recording and playback";
+const MKV: &str = "MKV/WebM files";
+const MP4: &str = "MP4 files";
+const AV1_ENCODING: &str = "AV1 encoding";
+const VP8_ENCODING: &str = "VP8 encoding";
+const VP9_ENCODING: &str = "VP9 encoding";
+const H264_ENCODING: &str = "H.264 encoding";
+const FFV1: &str = "FFV1 lossless video";
+const AV1_DECODING: &str = "AV1 decoding";
+const VP8_VP9_DECODING: &str = "VP8/VP9 decoding";
+const H264_DECODING: &str = "H.264 decoding";
+const HW_JPEG_DECODING: &str = "Hardware JPEG decoding";
+const FLAC_AUDIO: &str = "FLAC audio";
+const WAV_AUDIO: &str = "WAV audio";
+const AUDIO_PLAYBACK: &str = "Audio review playback";
+const LIVE_STREAMING: &str = "Live streaming (RTMP)";
+const STREAM_AUDIO: &str = "Live streaming audio (AAC)";
+const SHARE_AUDIO: &str = "Shared session audio (MP3)";
+const VIRTUAL_CAMERA: &str = "Virtual camera output";
+const STATS_OVERLAY: &str = "Virtual camera stats overlay";
+const ENCODER_TEST: &str = "Encoder preset test";
+
+/// Every element any pipeline in the crate can request
+const ELEMENTS: &[ElementSpec] = &[
+    element("queue", GstPackage::Core, ElementRole::Utility, CORE_PIPELINES),
+    element("capsfilter", GstPackage::Core, ElementRole::Utility, CORE_PIPELINES),
+    element("filesrc", GstPackage::Core, ElementRole::Source, CORE_PIPELINES),
+    element("filesink", GstPackage::Core, ElementRole::Sink, CORE_PIPELINES),
+    element("fakesink", GstPackage::Core, ElementRole::Sink, CORE_PIPELINES),
+    element("identity", GstPackage::Core, ElementRole::Utility, CORE_PIPELINES),
+    element("appsrc", GstPackage::Base, ElementRole::Source, CORE_PIPELINES),
+    element("appsink", GstPackage::Base, ElementRole::Sink, CORE_PIPELINES),
+    element("videoconvert", GstPackage::Base, ElementRole::Utility, CORE_PIPELINES),
+    element("videoscale", GstPackage::Base, ElementRole::Utility, CORE_PIPELINES),
+    element("videorate", GstPackage::Base, ElementRole::Utility, CORE_PIPELINES),
+    element("audioconvert", GstPackage::Base, ElementRole::Utility, CORE_PIPELINES),
+    element("audioresample", GstPackage::Base, ElementRole::Utility, CORE_PIPELINES),
+    element("decodebin", GstPackage::Base, ElementRole::Decoder, CORE_PIPELINES),
+    element("parsebin", GstPackage::Base, ElementRole::Parser, CORE_PIPELINES),
+    element("capssetter", GstPackage::Good, ElementRole::Utility, CORE_PIPELINES),
+    // Camera sources
+    element("mfvideosrc", GstPackage::Bad, ElementRole::Source, CAMERA_CAPTURE).alternative().on(WINDOWS),
+    element("dshowvideosrc", GstPackage::Bad, ElementRole::Source, CAMERA_CAPTURE).alternative().on(WINDOWS),
+    element("ksvideosrc", GstPackage::Bad, ElementRole::Source, CAMERA_CAPTURE).alternative().on(WINDOWS),
+    element("avfvideosrc", GstPackage::Bad, ElementRole::Source, CAMERA_CAPTURE).on(MACOS),
+    element("v4l2src", GstPackage::Good, ElementRole::Source, CAMERA_CAPTURE).on(LINUX),
+    // MJPEG
+    element("jpegparse", GstPackage::Bad, ElementRole::Parser, MJPEG),
+    element("jpegdec", GstPackage::Good, ElementRole::Decoder, MJPEG),
+    element("jpegenc", GstPackage::Good, ElementRole::Encoder, MJPEG),
+    // Containers
+    element("matroskamux", GstPackage::Good, ElementRole::Muxer, MKV),
+    element("matroskademux", GstPackage::Good, ElementRole::Demuxer, MKV),
+    element("webmmux", GstPackage::Good, ElementRole::Muxer, MKV),
+    element("mp4mux", GstPackage::Good, ElementRole::Muxer, MP4),
+    element("qtdemux", GstPackage::Good, ElementRole::Demuxer, MP4),
+    // AV1
+    element("av1parse", GstPackage::Bad, ElementRole::Parser, AV1_ENCODING),
+    element("nvav1enc", GstPackage::Bad, ElementRole::Encoder, AV1_ENCODING)
+        .alternative().hardware("NVIDIA RTX 40-series GPU").on(WINDOWS_LINUX),
+    element("amfav1enc", GstPackage::Bad, ElementRole::Encoder, AV1_ENCODING)
+        .alternative().hardware("AMD RX 7000-series GPU").on(WINDOWS),
+    element("qsvav1enc", GstPackage::Bad, ElementRole::Encoder, AV1_ENCODING)
+        .alternative().hardware("Intel Arc GPU").on(WINDOWS_LINUX),
+    element("vaav1enc", GstPackage::Bad, ElementRole::Encoder, AV1_ENCODING)
+        .alternative().hardware("VA-API driver with AV1 encode").on(LINUX),
+    element("vaapiav1enc", GstPackage::Vaapi, ElementRole::Encoder, AV1_ENCODING)
+        .alternative().hardware("VA-API driver with AV1 encode").on(LINUX),
+    element("svtav1enc", GstPackage::Bad, ElementRole::Encoder, AV1_ENCODING).alternative(),
+    // VP8
+    element("qsvvp8enc", GstPackage::Bad, ElementRole::Encoder, VP8_ENCODING)
+        .alternative().hardware("Intel GPU").on(WINDOWS_LINUX),
+    element("vavp8enc", GstPackage::Bad, ElementRole::Encoder, VP8_ENCODING)
+        .alternative().hardware("VA-API driver with VP8 encode").on(LINUX),
+    element("vaapivp8enc", GstPackage::Vaapi, ElementRole::Encoder, VP8_ENCODING)
+        .alternative().hardware("VA-API driver with VP8 encode").on(LINUX),
+    element("vp8enc", GstPackage::Good, ElementRole::Encoder, VP8_ENCODING).alternative(),
+    // VP9
+    element("qsvvp9enc", GstPackage::Bad, ElementRole::Encoder, VP9_ENCODING)
+        .alternative().hardware("Intel GPU").on(WINDOWS_LINUX),
+    element("vavp9enc", GstPackage::Bad, ElementRole::Encoder, VP9_ENCODING)
+        .alternative().hardware("VA-API driver with VP9 encode").on(LINUX),
+    element("vaapivp9enc", GstPackage::Vaapi, ElementRole::Encoder, VP9_ENCODING)
+        .alternative().hardware("VA-API driver with VP9 encode").on(LINUX),
+    element("vp9enc", GstPackage::Good, ElementRole::Encoder, VP9_ENCODING).alternative(),
+    // H.264 (platform-native encoders only)
+    element("h264parse", GstPackage::Bad, ElementRole::Parser, H264_ENCODING).on(&["windows", "macos"]),
+    element("mfh264enc", GstPackage::Bad, ElementRole::Encoder, H264_ENCODING).on(WINDOWS),
+    element("vtenc_h264", GstPackage::Bad, ElementRole::Encoder, H264_ENCODING).on(MACOS),
+    // FFV1
+    element("avenc_ffv1", GstPackage::Libav, ElementRole::Encoder, FFV1),
+    element("avdec_ffv1", GstPackage::Libav, ElementRole::Decoder, FFV1),
+    // Decoders for re-encoding and playback
+    element("av1dec", GstPackage::Bad, ElementRole::Decoder, AV1_DECODING),
+    element("vp8dec", GstPackage::Good, ElementRole::Decoder, VP8_VP9_DECODING),
+    element("vp9dec", GstPackage::Good, ElementRole::Decoder, VP8_VP9_DECODING),
+    element("d3d11h264dec", GstPackage::Bad, ElementRole::Decoder, H264_DECODING).on(WINDOWS),
+    element("vtdec", GstPackage::Bad, ElementRole::Decoder, H264_DECODING).on(MACOS),
+    element("nvjpegdec", GstPackage::Bad, ElementRole::Decoder, HW_JPEG_DECODING)
+        .alternative().hardware("NVIDIA GPU").on(WINDOWS_LINUX),
+    element("d3d11jpegdec", GstPackage::Bad, ElementRole::Decoder, HW_JPEG_DECODING)
+        .alternative().hardware("Direct3D 11 GPU").on(WINDOWS),
+    element("vajpegdec", GstPackage::Bad, ElementRole::Decoder, HW_JPEG_DECODING)
+        .alternative().hardware("VA-API driver with JPEG decode").on(LINUX),
+    // Audio files
+    element("flacenc", GstPackage::Good, ElementRole::Encoder, FLAC_AUDIO),
+    element("flacparse", GstPackage::Good, ElementRole::Parser, FLAC_AUDIO),
+    element("wavparse", GstPackage::Good, ElementRole::Parser, WAV_AUDIO),
+    element("playbin", GstPackage::Base, ElementRole::Utility, AUDIO_PLAYBACK),
+    element("scaletempo", GstPackage::Good, ElementRole::Utility, AUDIO_PLAYBACK),
+    // Live streaming
+    element("flvmux", GstPackage::Good, ElementRole::Muxer, LIVE_STREAMING),
+    element("aacparse", GstPackage::Good, ElementRole::Parser, LIVE_STREAMING),
+    element("rtmp2sink", GstPackage::Bad, ElementRole::Sink, LIVE_STREAMING).alternative(),
+    element("rtmpsink", GstPackage::Bad, ElementRole::Sink, LIVE_STREAMING).alternative(),
+    element("fdkaacenc", GstPackage::Bad, ElementRole::Encoder, STREAM_AUDIO).alternative(),
+    element("avenc_aac", GstPackage::Libav, ElementRole::Encoder, STREAM_AUDIO).alternative(),
+    element("voaacenc", GstPackage::Bad, ElementRole::Encoder, STREAM_AUDIO).alternative(),
+    element("faac", GstPackage::Bad, ElementRole::Encoder, STREAM_AUDIO).alternative(),
+    element("x264enc", GstPackage::Ugly, ElementRole::Encoder, LIVE_STREAMING).alternative(),
+    element("lamemp3enc", GstPackage::Good, ElementRole::Encoder, SHARE_AUDIO),
+    element("v4l2sink", GstPackage::Good, ElementRole::Sink, VIRTUAL_CAMERA).on(LINUX),
+    element("textoverlay", GstPackage::Base, ElementRole::Utility, STATS_OVERLAY).on(LINUX),
+    element("videotestsrc", GstPackage::Base, ElementRole::Source, ENCODER_TEST),
+];
+
+/// Registry status of one element
+#[derive(Debug, Clone, Serialize)]
+pub struct ElementStatus {
+    pub name: String,
+    pub package: GstPackage,
+    pub role: ElementRole,
+    pub feature: String,
+    pub alternative: bool,
+    pub present: bool,
+    /// Plugin the element was found in, and that plugin's file
+    pub plugin: Option<String>,
+    pub plugin_file: Option<String>,
+    /// How to get the element, when it is missing
+    pub hint: Option<String>,
+}
+
+/// Whether a feature has what it needs
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureStatus {
+    pub feature: String,
+    pub available: bool,
+    /// Elements present for this feature
+    pub present: Vec<String>,
+    /// Required elements that are missing (alternatives are only listed
+    /// when none of them is present)
+    pub missing: Vec<String>,
+}
+
+/// Result of [`audit`]
+#[derive(Debug, Clone, Serialize)]
+pub struct GstAuditReport {
+    pub gstreamer_version: String,
+    pub platform: String,
+    /// Which install is loaded and what its startup checks found
+    pub runtime: Option<crate::gstreamer_init::GstRuntimeInfo>,
+    pub elements: Vec<ElementStatus>,
+    pub features: Vec<FeatureStatus>,
+    pub missing_count: usize,
+}
+
+/// How to get a missing element on this platform
+fn remediation_hint(spec: &ElementSpec) -> String {
+    let package = spec.package.display_name();
+    let install = match std::env::consts::OS {
+        "windows" => format!(
+            "{} ships with Sacho's bundled GStreamer; reinstall Sacho, and make sure no other GStreamer install is on PATH",
+            package
+        ),
+        "macos" => format!("Install {} (Homebrew: brew install gstreamer)", package),
+        _ => {
+            let (deb, rpm) = spec.package.linux_packages();
+            format!("Install {} (Debian/Ubuntu: {}, Fedora: {})", package, deb, rpm)
+        }
+    };
+    match spec.hardware {
+        Some(hardware) => format!("Needs a {} with current drivers. {}", hardware, install),
+        None => install,
+    }
+}
+
+/// Check every element used on this platform against the GStreamer registry
+pub fn audit() -> anyhow::Result<GstAuditReport> {
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+    let os = std::env::consts::OS;
+    let specs: Vec<&ElementSpec> = ELEMENTS
+        .iter()
+        .filter(|spec| spec.platforms.is_empty() || spec.platforms.contains(&os))
+        .collect();
+
+    let elements: Vec<ElementStatus> = specs
+        .iter()
+        .map(|spec| {
+            let factory = gst::ElementFactory::find(spec.name);
+            let plugin = factory.as_ref().and_then(|f| f.plugin());
+            ElementStatus {
+                name: spec.name.to_string(),
+                package: spec.package,
+                role: spec.role,
+                feature: spec.feature.to_string(),
+                alternative: spec.alternative,
+                present: factory.is_some(),
+                plugin: plugin.as_ref().map(|p| p.plugin_name().to_string()),
+                plugin_file: plugin
+                    .as_ref()
+                    .and_then(|p| p.filename())
+                    .map(|f| f.to_string_lossy().to_string()),
+                hint: factory.is_none().then(|| remediation_hint(spec)),
+            }
+        })
+        .collect();
+
+    let mut features: Vec<FeatureStatus> = Vec::new();
+    for status in &elements {
+        if features.iter().any(|f| f.feature == status.feature) {
+            continue;
+        }
+        let members: Vec<&ElementStatus> = elements.iter().filter(|e| e.feature == status.feature).collect();
+        let required_missing: Vec<String> = members
+            .iter()
+            .filter(|e| !e.alternative && !e.present)
+            .map(|e| e.name.clone())
+            .collect();
+        let alternatives: Vec<&&ElementStatus> = members.iter().filter(|e| e.alternative).collect();
+        let alternative_found = alternatives.is_empty() || alternatives.iter().any(|e| e.present);
+
+        let mut missing = required_missing;
+        if !alternative_found {
+            missing.extend(alternatives.iter().map(|e| e.name.clone()));
+        }
+        features.push(FeatureStatus {
+            feature: status.feature.clone(),
+            available: missing.is_empty(),
+            present: members.iter().filter(|e| e.present).map(|e| e.name.clone()).collect(),
+            missing,
+        });
+    }
+
+    let (major, minor, micro, _) = gst::version();
+    let report = GstAuditReport {
+        gstreamer_version: format!("{}.{}.{}", major, minor, micro),
+        platform: os.to_string(),
+        runtime: crate::gstreamer_init::runtime_info(),
+        missing_count: elements.iter().filter(|e| !e.present).count(),
+        elements,
+        features,
+    };
+
+    let unavailable: Vec<&str> = report
+        .features
+        .iter()
+        .filter(|f| !f.available)
+        .map(|f| f.feature.as_str())
+        .collect();
+    println!(
+        "[Sacho] GStreamer audit: {} elements missing, unavailable features: {}",
+        report.missing_count,
+        if unavailable.is_empty() { "none".to_string() } else { unavailable.join(", ") }
+    );
+    Ok(report)
+}
@@ -3,87 +3,332 @@
 //! On Windows, GStreamer runtime and plugin DLLs are bundled in the same
 //! directory as the executable. This module sets up the environment so
 //! GStreamer can find its plugins.
+//!
+//! Which install is used can be chosen explicitly: `gstreamer_source` in the
+//! config, or the SACHO_GSTREAMER environment variable (`auto`, `bundled` or
+//! `system`), which wins over the config. With the bundled copy selected,
+//! system plugin paths are masked so a system install can't mix its plugins
+//! in. After init, the runtime version is checked against the minimum and
+//! every official plugin is checked against the core library's version; the
+//! outcome is kept for diagnostics.
 
 use std::env;
-use std::sync::Once;
+use std::path::PathBuf;
+use std::sync::{Once, OnceLock};
+
+use gstreamer::prelude::*;
+use serde::Serialize;
+
+use crate::config::GstreamerSource;
 
 static GSTREAMER_INIT: Once = Once::new();
 
+static RUNTIME_INFO: OnceLock<GstRuntimeInfo> = OnceLock::new();
+
+/// Overrides `gstreamer_source` from the config
+const SOURCE_ENV_VAR: &str = "SACHO_GSTREAMER";
+
+/// Oldest GStreamer release Sacho's pipelines are written against
+pub const MIN_GSTREAMER_VERSION: (u32, u32, u32) = (1, 22, 0);
+
+/// Plugin modules released in lockstep with the core library. A plugin from
+/// one of these with a different major.minor comes from another install.
+const LOCKSTEP_SOURCES: &[&str] = &[
+    "gstreamer",
+    "gst-plugins-base",
+    "gst-plugins-good",
+    "gst-plugins-bad",
+    "gst-plugins-ugly",
+    "gst-libav",
+    "gstreamer-vaapi",
+];
+
+/// Install GStreamer was loaded from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActiveGstreamer {
+    Bundled,
+    System,
+}
+
+/// Which GStreamer is in use and whether it checked out, for diagnostics
+#[derive(Debug, Clone, Serialize)]
+pub struct GstRuntimeInfo {
+    pub requested: GstreamerSource,
+    /// "environment", "config" or "default"
+    pub requested_by: String,
+    pub active: ActiveGstreamer,
+    pub version: Option<String>,
+    pub minimum_version: String,
+    pub version_ok: bool,
+    pub plugin_path: Option<String>,
+    pub registry_path: Option<String>,
+    /// Official plugins whose version doesn't match the core library
+    pub mismatched_plugins: Vec<String>,
+    /// Plugins loaded from outside the bundled copy (bundled only)
+    pub foreign_plugins: Vec<String>,
+    pub warnings: Vec<String>,
+    pub init_error: Option<String>,
+}
+
+/// Outcome of startup, or None before `init_gstreamer_env` has run
+pub fn runtime_info() -> Option<GstRuntimeInfo> {
+    RUNTIME_INFO.get().cloned()
+}
+
 /// Initialize GStreamer environment
-/// 
+///
 /// On Windows, this sets GST_PLUGIN_PATH to the executable's directory
 /// where the plugin DLLs are located alongside the runtime DLLs.
 pub fn init_gstreamer_env() {
     GSTREAMER_INIT.call_once(|| {
-        #[cfg(target_os = "windows")]
-        setup_gstreamer_windows();
-        
+        let (requested, requested_by) = requested_source();
+        let mut warnings = Vec::new();
+        let (active, plugin_path, registry_path) = select_install(requested, &mut warnings);
+
         // Initialize GStreamer
-        match gstreamer::init() {
+        let init_error = match gstreamer::init() {
             Ok(_) => {
                 log::info!("GStreamer initialized successfully");
                 log_gstreamer_version();
+                None
             }
             Err(e) => {
                 log::error!("Failed to initialize GStreamer: {}", e);
                 log::error!("Video capture and playback will not be available");
+                Some(e.to_string())
             }
+        };
+
+        let mut info = GstRuntimeInfo {
+            requested,
+            requested_by: requested_by.to_string(),
+            active,
+            version: None,
+            minimum_version: format_version(MIN_GSTREAMER_VERSION),
+            version_ok: false,
+            plugin_path: plugin_path.map(|p| p.to_string_lossy().to_string()),
+            registry_path: registry_path.map(|p| p.to_string_lossy().to_string()),
+            mismatched_plugins: Vec::new(),
+            foreign_plugins: Vec::new(),
+            warnings,
+            init_error,
+        };
+        if info.init_error.is_none() {
+            check_runtime(&mut info);
         }
+
+        log::info!(
+            "GStreamer install: {:?} (requested {:?} by {})",
+            info.active, info.requested, info.requested_by
+        );
+        for warning in &info.warnings {
+            log::warn!("GStreamer: {}", warning);
+        }
+        let _ = RUNTIME_INFO.set(info);
     });
 }
 
+/// Source asked for by the environment, else the config file, else Auto
+fn requested_source() -> (GstreamerSource, &'static str) {
+    if let Ok(value) = env::var(SOURCE_ENV_VAR) {
+        match GstreamerSource::parse(&value) {
+            Some(source) => return (source, "environment"),
+            None => log::warn!("Ignoring {}={}: expected auto, bundled or system", SOURCE_ENV_VAR, value),
+        }
+    }
+
+    // The config proper needs the Tauri app handle, which doesn't exist yet,
+    // so read just this key from the file the config is saved to
+    let configured = dirs::config_dir()
+        .map(|dir| dir.join("com.sacho.app").join("config.toml"))
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| contents.parse::<toml::Table>().ok())
+        .and_then(|table| table.get("gstreamer_source")?.as_str().and_then(GstreamerSource::parse));
+    match configured {
+        Some(source) => (source, "config"),
+        None => (GstreamerSource::Auto, "default"),
+    }
+}
+
+/// Point GStreamer at the requested install. Returns the install in use and
+/// the plugin path and registry that were set, if any.
+fn select_install(
+    requested: GstreamerSource,
+    warnings: &mut Vec<String>,
+) -> (ActiveGstreamer, Option<PathBuf>, Option<PathBuf>) {
+    let bundled = bundled_dir();
+    match (requested, bundled) {
+        (GstreamerSource::Auto | GstreamerSource::Bundled, Some(dir)) => {
+            let registry = use_bundled_gstreamer(&dir);
+            (ActiveGstreamer::Bundled, Some(dir), registry)
+        }
+        (GstreamerSource::Bundled, None) => {
+            warnings.push("This build has no bundled GStreamer; using the system install".to_string());
+            (ActiveGstreamer::System, None, None)
+        }
+        (GstreamerSource::System, Some(_)) => {
+            // The runtime DLLs next to the executable are loaded before any
+            // code runs, so only the plugins can come from the system install
+            warnings.push(
+                "The core library is still loaded from the bundled copy; system plugins must match its version"
+                    .to_string(),
+            );
+            (ActiveGstreamer::System, use_system_gstreamer(), None)
+        }
+        (_, None) => (ActiveGstreamer::System, None, None),
+    }
+}
+
+/// Folder holding the bundled runtime and plugins, if this build has one
+fn bundled_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        let exe_dir = env::current_exe().ok()?.parent()?.to_path_buf();
+        exe_dir.join("gstreamer-1.0-0.dll").exists().then_some(exe_dir)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        None
+    }
+}
+
+/// Load plugins only from the bundled folder, with a private registry.
+/// Returns the registry path.
 #[cfg(target_os = "windows")]
-fn setup_gstreamer_windows() {
-    // Get the executable's directory - this is where all DLLs (runtime + plugins) are located
-    let exe_dir = match env::current_exe() {
-        Ok(path) => path.parent().map(|p| p.to_path_buf()),
-        Err(e) => {
-            log::warn!("Failed to get executable path: {}", e);
-            None
+fn use_bundled_gstreamer(app_dir: &std::path::Path) -> Option<PathBuf> {
+    // Tell GStreamer to look for plugins in the exe directory
+    let plugin_path = app_dir.to_str().unwrap_or_default();
+    env::set_var("GST_PLUGIN_PATH", plugin_path);
+    log::debug!("Set GST_PLUGIN_PATH: {}", plugin_path);
+
+    // Mask the system plugin paths, including the _1_0 variants GStreamer
+    // prefers, so a system install's plugins never load alongside ours
+    env::set_var("GST_PLUGIN_SYSTEM_PATH", plugin_path);
+    env::set_var("GST_PLUGIN_SYSTEM_PATH_1_0", plugin_path);
+    env::set_var("GST_PLUGIN_PATH_1_0", plugin_path);
+    env::remove_var("GST_REGISTRY_1_0");
+
+    // Use a private, versioned registry file to avoid conflicts with system GStreamer.
+    // The registry caches plugin load results (including failures). When bundled DLLs
+    // change between versions (e.g., new dependencies added), a stale registry will
+    // still report plugins as failed. Using a version-specific registry path ensures
+    // a fresh scan after every update.
+    let registry_path = private_registry_path("gst-registry")?;
+    env::set_var("GST_REGISTRY", registry_path.to_str().unwrap_or_default());
+    log::debug!("Set GST_REGISTRY: {}", registry_path.display());
+    Some(registry_path)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn use_bundled_gstreamer(_app_dir: &std::path::Path) -> Option<PathBuf> {
+    None
+}
+
+/// Load plugins from the system install named by the GStreamer installer's
+/// root variable. Returns the plugin folder.
+#[cfg(target_os = "windows")]
+fn use_system_gstreamer() -> Option<PathBuf> {
+    let root = ["GSTREAMER_1_0_ROOT_MSVC_X86_64", "GSTREAMER_1_0_ROOT_X86_64"]
+        .iter()
+        .find_map(|name| env::var_os(name))?;
+    let plugin_dir = PathBuf::from(root).join("lib").join("gstreamer-1.0");
+    env::set_var("GST_PLUGIN_SYSTEM_PATH_1_0", &plugin_dir);
+    env::remove_var("GST_PLUGIN_PATH");
+    env::remove_var("GST_PLUGIN_PATH_1_0");
+    if let Some(registry_path) = private_registry_path("gst-registry-system") {
+        env::set_var("GST_REGISTRY", &registry_path);
+    }
+    Some(plugin_dir)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn use_system_gstreamer() -> Option<PathBuf> {
+    None
+}
+
+/// Versioned registry file under the app's local data folder, removing
+/// registries left by other versions
+#[cfg(target_os = "windows")]
+fn private_registry_path(prefix: &str) -> Option<PathBuf> {
+    let local_app_data = dirs::data_local_dir()?;
+    let app_version = env!("CARGO_PKG_VERSION");
+    let registry_path = local_app_data
+        .join("com.sacho.app")
+        .join(format!("{}-v{}.bin", prefix, app_version));
+    let parent = registry_path.parent()?;
+    let _ = std::fs::create_dir_all(parent);
+
+    // Clean up old registry files from previous versions
+    if let Ok(entries) = std::fs::read_dir(parent) {
+        let current = registry_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name_str = name.to_string_lossy();
+            let is_other_version = name_str
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with("-v") && rest.ends_with(".bin"));
+            if is_other_version && name_str != current {
+                log::debug!("Removing old registry: {}", name_str);
+                let _ = std::fs::remove_file(entry.path());
+            }
         }
-    };
-    
-    if let Some(app_dir) = exe_dir {
-        // Tell GStreamer to look for plugins in the exe directory
-        let plugin_path = app_dir.to_str().unwrap_or_default();
-        env::set_var("GST_PLUGIN_PATH", plugin_path);
-        log::debug!("Set GST_PLUGIN_PATH: {}", plugin_path);
-        
-        // Use a private, versioned registry file to avoid conflicts with system GStreamer.
-        // The registry caches plugin load results (including failures). When bundled DLLs
-        // change between versions (e.g., new dependencies added), a stale registry will
-        // still report plugins as failed. Using a version-specific registry path ensures
-        // a fresh scan after every update.
-        if let Some(local_app_data) = dirs::data_local_dir() {
-            let app_version = env!("CARGO_PKG_VERSION");
-            let registry_path = local_app_data
-                .join("com.sacho.app")
-                .join(format!("gst-registry-v{}.bin", app_version));
-            
-            if let Some(parent) = registry_path.parent() {
-                let _ = std::fs::create_dir_all(parent);
+    }
+    Some(registry_path)
+}
+
+/// Check the runtime version and look for plugins from another install
+fn check_runtime(info: &mut GstRuntimeInfo) {
+    let (major, minor, micro, _) = gstreamer::version();
+    info.version = Some(format_version((major, minor, micro)));
+    info.version_ok = (major, minor, micro) >= MIN_GSTREAMER_VERSION;
+    if !info.version_ok {
+        info.warnings.push(format!(
+            "GStreamer {}.{}.{} is older than the minimum supported {}",
+            major, minor, micro, info.minimum_version
+        ));
+    }
+
+    let bundled = (info.active == ActiveGstreamer::Bundled)
+        .then(|| info.plugin_path.clone())
+        .flatten()
+        .map(PathBuf::from);
+    for plugin in gstreamer::Registry::get().plugins() {
+        let name = plugin.plugin_name().to_string();
+        let source = plugin.source().to_string();
+        if LOCKSTEP_SOURCES.contains(&source.as_str()) {
+            let version = plugin.version().to_string();
+            let mut parts = version.split('.').filter_map(|p| p.parse::<u32>().ok());
+            if (parts.next(), parts.next()) != (Some(major), Some(minor)) {
+                info.mismatched_plugins.push(format!("{} {} ({})", name, version, source));
             }
-            
-            // Clean up old registry files from previous versions
-            if let Some(parent) = registry_path.parent() {
-                if let Ok(entries) = std::fs::read_dir(parent) {
-                    for entry in entries.flatten() {
-                        let name = entry.file_name();
-                        let name_str = name.to_string_lossy();
-                        if name_str.starts_with("gst-registry") && name_str.ends_with(".bin") 
-                            && name_str != registry_path.file_name().unwrap_or_default().to_string_lossy() 
-                        {
-                            log::debug!("Removing old registry: {}", name_str);
-                            let _ = std::fs::remove_file(entry.path());
-                        }
-                    }
-                }
+        }
+        if let (Some(bundled), Some(file)) = (&bundled, plugin.filename()) {
+            if !file.starts_with(bundled) {
+                info.foreign_plugins.push(format!("{} ({})", name, file.display()));
             }
-            
-            env::set_var("GST_REGISTRY", registry_path.to_str().unwrap_or_default());
-            log::debug!("Set GST_REGISTRY: {}", registry_path.display());
         }
     }
+    if !info.mismatched_plugins.is_empty() {
+        info.warnings.push(format!(
+            "{} plugins don't match GStreamer {}.{}: {}",
+            info.mismatched_plugins.len(),
+            major,
+            minor,
+            info.mismatched_plugins.join(", ")
+        ));
+    }
+    if !info.foreign_plugins.is_empty() {
+        info.warnings.push(format!(
+            "{} plugins were loaded from outside the bundled copy: {}",
+            info.foreign_plugins.len(),
+            info.foreign_plugins.join(", ")
+        ));
+    }
+}
+
+fn format_version((major, minor, micro): (u32, u32, u32)) -> String {
+    format!("{}.{}.{}", major, minor, micro)
 }
 
 fn log_gstreamer_version() {
@@ -0,0 +1,400 @@
+// Backend text translations
+//
+// The UI localizes its own strings, but notifications, the tray menu, startup
+// dialogs and some command errors are assembled here in Rust. Those go
+// through `t` / `tf` with a `Text` key, looked up in the language chosen in
+// Config (or the OS language). Every language table is an exhaustive match,
+// so adding a key without translating it fails to compile. Templates use `{}`
+// placeholders filled in order.
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::config::Language;
+
+/// A language with a translation table (`Language` minus `System`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum Lang {
+    En = 0,
+    De = 1,
+    Fr = 2,
+    Es = 3,
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(Lang::En as u8);
+
+/// Switch the language of backend text. Called when config is loaded and
+/// whenever it is saved.
+pub fn set_language(language: Language) {
+    let lang = match language {
+        Language::System => system_lang(),
+        Language::En => Lang::En,
+        Language::De => Lang::De,
+        Language::Fr => Lang::Fr,
+        Language::Es => Lang::Es,
+    };
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+}
+
+fn current() -> Lang {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Lang::De,
+        2 => Lang::Fr,
+        3 => Lang::Es,
+        _ => Lang::En,
+    }
+}
+
+/// OS language from its BCP 47 tag ("de-AT" → German)
+fn system_lang() -> Lang {
+    let Some(tag) = sys_locale::get_locale() else {
+        return Lang::En;
+    };
+    let primary = tag.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    match primary.as_str() {
+        "de" => Lang::De,
+        "fr" => Lang::Fr,
+        "es" => Lang::Es,
+        _ => Lang::En,
+    }
+}
+
+/// Translatable backend text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    // Notifications
+    RecordingStartedTitle,
+    RecordingStartedBody,
+    NoDevices,
+    DevicesAndMore,
+    RecordingSavedTitle,
+    RecordingSavedBody,
+    DeviceDisconnectedTitle,
+    DeviceDisconnectedOne,
+    DeviceDisconnectedMany,
+    DeviceNotRecoveredTitle,
+    DeviceNotRecoveredOne,
+    DeviceNotRecoveredMany,
+    VideoFallbackTitle,
+    VideoFallbackBody,
+    EncoderSwitchedTitle,
+    EncoderSwitchedSegment,
+    EncoderSwitchedBody,
+    EncodingReducedTitle,
+    EncodingReducedBody,
+    PresetLowered,
+    FrameRateLowered,
+    BlackFramesTitle,
+    BlackFramesBody,
+    PrivacyResumedTitle,
+    PrivacyResumedBody,
+    OnBatteryTitle,
+    OnBatteryBody,
+    BatteryLowTitle,
+    BatteryLowBody,
+    UpdateAvailableTitle,
+    UpdateAvailableBody,
+    ErrorTitle,
+
+    // Notification buttons
+    ActionOpenSession,
+    ActionPlay,
+    ActionFavorite,
+    ActionDelete,
+
+    // Tray
+    TrayOpen,
+    TrayStartRecording,
+    TrayStopRecording,
+    TrayQuit,
+    TrayPrivacyPause,
+    TrayPause30Minutes,
+    TrayPause2Hours,
+    TrayPauseUntilTomorrow,
+    TrayResumeNow,
+    TooltipIdle,
+    TooltipPaused,
+    TooltipRecording,
+    TooltipStopping,
+    TooltipInitializing,
+
+    // Dialogs and command errors
+    DatabaseErrorTitle,
+    DatabaseErrorBody,
+    SessionFolderNotFound,
+    RepairBlockedRemote,
+}
+
+/// Text in the current language
+pub fn t(text: Text) -> &'static str {
+    match current() {
+        Lang::En => english(text),
+        Lang::De => german(text),
+        Lang::Fr => french(text),
+        Lang::Es => spanish(text),
+    }
+}
+
+/// Text in the current language with its `{}` placeholders filled in order
+pub fn tf(text: Text, args: &[&dyn Display]) -> String {
+    let mut out = String::new();
+    let mut args = args.iter();
+    for (i, part) in t(text).split("{}").enumerate() {
+        if i > 0 {
+            if let Some(arg) = args.next() {
+                out.push_str(&arg.to_string());
+            }
+        }
+        out.push_str(part);
+    }
+    out
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::RecordingStartedTitle => "Recording Started",
+        Text::RecordingStartedBody => "Recording on: {}",
+        Text::NoDevices => "No devices",
+        Text::DevicesAndMore => "{} and {} more",
+        Text::RecordingSavedTitle => "Recording Saved",
+        Text::RecordingSavedBody => "Duration: {} • Saved to: {}",
+        Text::DeviceDisconnectedTitle => "Device Disconnected",
+        Text::DeviceDisconnectedOne => "{} has disconnected",
+        Text::DeviceDisconnectedMany => "{} devices have disconnected: {}",
+        Text::DeviceNotRecoveredTitle => "Device Not Recovered",
+        Text::DeviceNotRecoveredOne => "{} did not come back after sleep",
+        Text::DeviceNotRecoveredMany => "{} devices did not come back after sleep: {}",
+        Text::VideoFallbackTitle => "Video Settings Adjusted",
+        Text::VideoFallbackBody => "{} couldn't start at {}, recording at {} instead",
+        Text::EncoderSwitchedTitle => "Video Encoder Switched",
+        Text::EncoderSwitchedSegment => "{}: {} failed, recording continues with {} in {}",
+        Text::EncoderSwitchedBody => "{}: {} failed, now using {}",
+        Text::EncodingReducedTitle => "Video Encoding Reduced",
+        Text::EncodingReducedBody => "{}: {}, {}",
+        Text::PresetLowered => "encoding preset lowered to {}",
+        Text::FrameRateLowered => "frame rate lowered to {} fps",
+        Text::BlackFramesTitle => "Camera Recording Black Frames",
+        Text::BlackFramesBody => "{} is recording an almost completely black picture. Check the lens cap and that the camera is on.",
+        Text::PrivacyResumedTitle => "Recording Triggers Resumed",
+        Text::PrivacyResumedBody => "The privacy pause has ended. Sacho will record when you play again.",
+        Text::OnBatteryTitle => "Running on Battery",
+        Text::OnBatteryBody => "Recording continues, but keep an eye on the charge.",
+        Text::BatteryLowTitle => "Battery Low",
+        Text::BatteryLowBody => "Battery at {}%. Sacho is saving recordings more often and lowering video quality.",
+        Text::UpdateAvailableTitle => "Update Available",
+        Text::UpdateAvailableBody => "Sacho {} is available. Open Settings to see what's new and install it.",
+        Text::ErrorTitle => "Sacho Error",
+
+        Text::ActionOpenSession => "Open Session",
+        Text::ActionPlay => "Play",
+        Text::ActionFavorite => "Favorite",
+        Text::ActionDelete => "Delete",
+
+        Text::TrayOpen => "Open Sacho",
+        Text::TrayStartRecording => "Start Recording",
+        Text::TrayStopRecording => "Stop Recording",
+        Text::TrayQuit => "Quit",
+        Text::TrayPrivacyPause => "Privacy Pause",
+        Text::TrayPause30Minutes => "For 30 Minutes",
+        Text::TrayPause2Hours => "For 2 Hours",
+        Text::TrayPauseUntilTomorrow => "Until Tomorrow",
+        Text::TrayResumeNow => "Resume Now",
+        Text::TooltipIdle => "Sacho - Idle",
+        Text::TooltipPaused => "Sacho - Paused (privacy)",
+        Text::TooltipRecording => "Sacho - Recording",
+        Text::TooltipStopping => "Sacho - Stopping...",
+        Text::TooltipInitializing => "Sacho - Initializing...",
+
+        Text::DatabaseErrorTitle => "Database Error",
+        Text::DatabaseErrorBody => "Failed to initialize database: {}\n\nThe application may not function correctly.",
+        Text::SessionFolderNotFound => "Session folder not found: {}",
+        Text::RepairBlockedRemote => "Cannot repair: recording may be in progress on another device.",
+    }
+}
+
+fn german(text: Text) -> &'static str {
+    match text {
+        Text::RecordingStartedTitle => "Aufnahme gestartet",
+        Text::RecordingStartedBody => "Aufnahme mit: {}",
+        Text::NoDevices => "Keine Geräte",
+        Text::DevicesAndMore => "{} und {} weitere",
+        Text::RecordingSavedTitle => "Aufnahme gespeichert",
+        Text::RecordingSavedBody => "Dauer: {} • Gespeichert in: {}",
+        Text::DeviceDisconnectedTitle => "Gerät getrennt",
+        Text::DeviceDisconnectedOne => "{} wurde getrennt",
+        Text::DeviceDisconnectedMany => "{} Geräte wurden getrennt: {}",
+        Text::DeviceNotRecoveredTitle => "Gerät nicht wiederhergestellt",
+        Text::DeviceNotRecoveredOne => "{} ist nach dem Ruhezustand nicht zurückgekehrt",
+        Text::DeviceNotRecoveredMany => "{} Geräte sind nach dem Ruhezustand nicht zurückgekehrt: {}",
+        Text::VideoFallbackTitle => "Videoeinstellungen angepasst",
+        Text::VideoFallbackBody => "{} konnte nicht mit {} starten, Aufnahme stattdessen mit {}",
+        Text::EncoderSwitchedTitle => "Video-Encoder gewechselt",
+        Text::EncoderSwitchedSegment => "{}: {} ist ausgefallen, Aufnahme läuft mit {} in {} weiter",
+        Text::EncoderSwitchedBody => "{}: {} ist ausgefallen, jetzt mit {}",
+        Text::EncodingReducedTitle => "Videokodierung reduziert",
+        Text::EncodingReducedBody => "{}: {}, {}",
+        Text::PresetLowered => "Kodierungsstufe auf {} gesenkt",
+        Text::FrameRateLowered => "Bildrate auf {} fps gesenkt",
+        Text::BlackFramesTitle => "Kamera nimmt schwarze Bilder auf",
+        Text::BlackFramesBody => "{} nimmt ein fast vollständig schwarzes Bild auf. Prüfe den Objektivdeckel und ob die Kamera eingeschaltet ist.",
+        Text::PrivacyResumedTitle => "Aufnahme-Auslöser wieder aktiv",
+        Text::PrivacyResumedBody => "Die Privatsphäre-Pause ist beendet. Sacho nimmt auf, sobald du wieder spielst.",
+        Text::OnBatteryTitle => "Akkubetrieb",
+        Text::OnBatteryBody => "Die Aufnahme läuft weiter, aber behalte den Ladestand im Blick.",
+        Text::BatteryLowTitle => "Akku schwach",
+        Text::BatteryLowBody => "Akku bei {}%. Sacho sichert Aufnahmen häufiger und senkt die Videoqualität.",
+        Text::UpdateAvailableTitle => "Update verfügbar",
+        Text::UpdateAvailableBody => "Sacho {} ist verfügbar. In den Einstellungen siehst du die Neuerungen und kannst es installieren.",
+        Text::ErrorTitle => "Sacho-Fehler",
+
+        Text::ActionOpenSession => "Session öffnen",
+        Text::ActionPlay => "Abspielen",
+        Text::ActionFavorite => "Favorit",
+        Text::ActionDelete => "Löschen",
+
+        Text::TrayOpen => "Sacho öffnen",
+        Text::TrayStartRecording => "Aufnahme starten",
+        Text::TrayStopRecording => "Aufnahme beenden",
+        Text::TrayQuit => "Beenden",
+        Text::TrayPrivacyPause => "Privatsphäre-Pause",
+        Text::TrayPause30Minutes => "Für 30 Minuten",
+        Text::TrayPause2Hours => "Für 2 Stunden",
+        Text::TrayPauseUntilTomorrow => "Bis morgen",
+        Text::TrayResumeNow => "Jetzt fortsetzen",
+        Text::TooltipIdle => "Sacho - Bereit",
+        Text::TooltipPaused => "Sacho - Pausiert (Privatsphäre)",
+        Text::TooltipRecording => "Sacho - Aufnahme läuft",
+        Text::TooltipStopping => "Sacho - Wird beendet...",
+        Text::TooltipInitializing => "Sacho - Wird initialisiert...",
+
+        Text::DatabaseErrorTitle => "Datenbankfehler",
+        Text::DatabaseErrorBody => "Die Datenbank konnte nicht initialisiert werden: {}\n\nDie Anwendung funktioniert möglicherweise nicht richtig.",
+        Text::SessionFolderNotFound => "Session-Ordner nicht gefunden: {}",
+        Text::RepairBlockedRemote => "Reparatur nicht möglich: Auf einem anderen Gerät läuft möglicherweise eine Aufnahme.",
+    }
+}
+
+fn french(text: Text) -> &'static str {
+    match text {
+        Text::RecordingStartedTitle => "Enregistrement démarré",
+        Text::RecordingStartedBody => "Enregistrement sur : {}",
+        Text::NoDevices => "Aucun appareil",
+        Text::DevicesAndMore => "{} et {} autres",
+        Text::RecordingSavedTitle => "Enregistrement sauvegardé",
+        Text::RecordingSavedBody => "Durée : {} • Enregistré dans : {}",
+        Text::DeviceDisconnectedTitle => "Appareil déconnecté",
+        Text::DeviceDisconnectedOne => "{} s'est déconnecté",
+        Text::DeviceDisconnectedMany => "{} appareils se sont déconnectés : {}",
+        Text::DeviceNotRecoveredTitle => "Appareil non récupéré",
+        Text::DeviceNotRecoveredOne => "{} n'est pas revenu après la mise en veille",
+        Text::DeviceNotRecoveredMany => "{} appareils ne sont pas revenus après la mise en veille : {}",
+        Text::VideoFallbackTitle => "Réglages vidéo ajustés",
+        Text::VideoFallbackBody => "{} n'a pas pu démarrer en {}, enregistrement en {} à la place",
+        Text::EncoderSwitchedTitle => "Encodeur vidéo changé",
+        Text::EncoderSwitchedSegment => "{} : {} a échoué, l'enregistrement continue avec {} dans {}",
+        Text::EncoderSwitchedBody => "{} : {} a échoué, {} est utilisé désormais",
+        Text::EncodingReducedTitle => "Encodage vidéo allégé",
+        Text::EncodingReducedBody => "{} : {}, {}",
+        Text::PresetLowered => "préréglage d'encodage abaissé à {}",
+        Text::FrameRateLowered => "fréquence d'images abaissée à {} ips",
+        Text::BlackFramesTitle => "La caméra enregistre des images noires",
+        Text::BlackFramesBody => "{} enregistre une image presque entièrement noire. Vérifiez le cache de l'objectif et que la caméra est allumée.",
+        Text::PrivacyResumedTitle => "Déclenchement réactivé",
+        Text::PrivacyResumedBody => "La pause de confidentialité est terminée. Sacho enregistrera dès que vous jouerez.",
+        Text::OnBatteryTitle => "Sur batterie",
+        Text::OnBatteryBody => "L'enregistrement continue, mais surveillez la charge.",
+        Text::BatteryLowTitle => "Batterie faible",
+        Text::BatteryLowBody => "Batterie à {}%. Sacho sauvegarde les enregistrements plus souvent et réduit la qualité vidéo.",
+        Text::UpdateAvailableTitle => "Mise à jour disponible",
+        Text::UpdateAvailableBody => "Sacho {} est disponible. Ouvrez les réglages pour voir les nouveautés et l'installer.",
+        Text::ErrorTitle => "Erreur Sacho",
+
+        Text::ActionOpenSession => "Ouvrir la session",
+        Text::ActionPlay => "Lire",
+        Text::ActionFavorite => "Favori",
+        Text::ActionDelete => "Supprimer",
+
+        Text::TrayOpen => "Ouvrir Sacho",
+        Text::TrayStartRecording => "Démarrer l'enregistrement",
+        Text::TrayStopRecording => "Arrêter l'enregistrement",
+        Text::TrayQuit => "Quitter",
+        Text::TrayPrivacyPause => "Pause de confidentialité",
+        Text::TrayPause30Minutes => "Pendant 30 minutes",
+        Text::TrayPause2Hours => "Pendant 2 heures",
+        Text::TrayPauseUntilTomorrow => "Jusqu'à demain",
+        Text::TrayResumeNow => "Reprendre maintenant",
+        Text::TooltipIdle => "Sacho - En attente",
+        Text::TooltipPaused => "Sacho - En pause (confidentialité)",
+        Text::TooltipRecording => "Sacho - Enregistrement",
+        Text::TooltipStopping => "Sacho - Arrêt...",
+        Text::TooltipInitializing => "Sacho - Initialisation...",
+
+        Text::DatabaseErrorTitle => "Erreur de base de données",
+        Text::DatabaseErrorBody => "Impossible d'initialiser la base de données : {}\n\nL'application risque de ne pas fonctionner correctement.",
+        Text::SessionFolderNotFound => "Dossier de session introuvable : {}",
+        Text::RepairBlockedRemote => "Réparation impossible : un enregistrement est peut-être en cours sur un autre appareil.",
+    }
+}
+
+fn spanish(text: Text) -> &'static str {
+    match text {
+        Text::RecordingStartedTitle => "Grabación iniciada",
+        Text::RecordingStartedBody => "Grabando en: {}",
+        Text::NoDevices => "Ningún dispositivo",
+        Text::DevicesAndMore => "{} y {} más",
+        Text::RecordingSavedTitle => "Grabación guardada",
+        Text::RecordingSavedBody => "Duración: {} • Guardada en: {}",
+        Text::DeviceDisconnectedTitle => "Dispositivo desconectado",
+        Text::DeviceDisconnectedOne => "{} se ha desconectado",
+        Text::DeviceDisconnectedMany => "{} dispositivos se han desconectado: {}",
+        Text::DeviceNotRecoveredTitle => "Dispositivo no recuperado",
+        Text::DeviceNotRecoveredOne => "{} no volvió tras la suspensión",
+        Text::DeviceNotRecoveredMany => "{} dispositivos no volvieron tras la suspensión: {}",
+        Text::VideoFallbackTitle => "Ajustes de vídeo modificados",
+        Text::VideoFallbackBody => "{} no pudo iniciarse a {}, grabando a {} en su lugar",
+        Text::EncoderSwitchedTitle => "Codificador de vídeo cambiado",
+        Text::EncoderSwitchedSegment => "{}: {} falló, la grabación continúa con {} en {}",
+        Text::EncoderSwitchedBody => "{}: {} falló, ahora se usa {}",
+        Text::EncodingReducedTitle => "Codificación de vídeo reducida",
+        Text::EncodingReducedBody => "{}: {}, {}",
+        Text::PresetLowered => "preajuste de codificación reducido a {}",
+        Text::FrameRateLowered => "velocidad de fotogramas reducida a {} fps",
+        Text::BlackFramesTitle => "La cámara graba fotogramas negros",
+        Text::BlackFramesBody => "{} está grabando una imagen casi totalmente negra. Comprueba la tapa del objetivo y que la cámara esté encendida.",
+        Text::PrivacyResumedTitle => "Disparadores de grabación reanudados",
+        Text::PrivacyResumedBody => "La pausa de privacidad ha terminado. Sacho grabará cuando vuelvas a tocar.",
+        Text::OnBatteryTitle => "Usando batería",
+        Text::OnBatteryBody => "La grabación continúa, pero vigila la carga.",
+        Text::BatteryLowTitle => "Batería baja",
+        Text::BatteryLowBody => "Batería al {}%. Sacho guarda las grabaciones con más frecuencia y reduce la calidad de vídeo.",
+        Text::UpdateAvailableTitle => "Actualización disponible",
+        Text::UpdateAvailableBody => "Sacho {} está disponible. Abre los ajustes para ver las novedades e instalarla.",
+        Text::ErrorTitle => "Error de Sacho",
+
+        Text::ActionOpenSession => "Abrir sesión",
+        Text::ActionPlay => "Reproducir",
+        Text::ActionFavorite => "Favorito",
+        Text::ActionDelete => "Eliminar",
+
+        Text::TrayOpen => "Abrir Sacho",
+        Text::TrayStartRecording => "Iniciar grabación",
+        Text::TrayStopRecording => "Detener grabación",
+        Text::TrayQuit => "Salir",
+        Text::TrayPrivacyPause => "Pausa de privacidad",
+        Text::TrayPause30Minutes => "Durante 30 minutos",
+        Text::TrayPause2Hours => "Durante 2 horas",
+        Text::TrayPauseUntilTomorrow => "Hasta mañana",
+        Text::TrayResumeNow => "Reanudar ahora",
+        Text::TooltipIdle => "Sacho - En espera",
+        Text::TooltipPaused => "Sacho - En pausa (privacidad)",
+        Text::TooltipRecording => "Sacho - Grabando",
+        Text::TooltipStopping => "Sacho - Deteniendo...",
+        Text::TooltipInitializing => "Sacho - Inicializando...",
+
+        Text::DatabaseErrorTitle => "Error de base de datos",
+        Text::DatabaseErrorBody => "No se pudo inicializar la base de datos: {}\n\nEs posible que la aplicación no funcione correctamente.",
+        Text::SessionFolderNotFound => "No se encontró la carpeta de la sesión: {}",
+        Text::RepairBlockedRemote => "No se puede reparar: puede haber una grabación en curso en otro dispositivo.",
+    }
+}
@@ -1,17 +1,43 @@
 // Sacho - Automatic Recording Studio Companion
 // Main library entry point
 
+/// Diagnostics are printed with `println!` throughout the crate. This
+/// shadows the std macro for every module declared below, so each line also
+/// lands in the crash log tail (see `crash::print_line`).
+macro_rules! println {
+    () => {
+        $crate::crash::print_line(String::new())
+    };
+    ($($arg:tt)*) => {
+        $crate::crash::print_line(format!($($arg)*))
+    };
+}
+
+pub mod agent;
 pub mod autostart;
+pub mod battery;
 pub mod config;
+pub mod crash;
 pub mod devices;
 pub mod encoding;
+pub mod gstreamer_audit;
 pub mod gstreamer_init;
+pub mod i18n;
 pub mod recording;
+pub mod remote;
 pub mod session;
 pub mod similarity;
 pub mod tray;
 pub mod notifications;
+pub mod playback;
+pub mod power;
+pub mod privacy;
+pub mod read_only;
+pub mod schedule;
 pub mod commands;
+pub mod share;
+pub mod shutdown;
+pub mod updates;
 #[cfg(feature = "test-harness")]
 pub mod test_harness;
 pub mod video;
@@ -34,12 +60,10 @@ pub fn run() {
             use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
             AttachConsole(ATTACH_PARENT_PROCESS);
         }
-        
-        // Initialize logger with a sensible default level if RUST_LOG isn't set
-        env_logger::Builder::from_env(
-            env_logger::Env::default().default_filter_or("info")
-        ).init();
     }
+    // Log records are kept for crash reports; with --console they're also
+    // printed (default level info if RUST_LOG isn't set)
+    crash::init_logger(enable_console);
     
     // Register with Windows Error Reporting for automatic restart on crash/hang
     #[cfg(windows)]
@@ -62,6 +86,12 @@ pub fn run() {
             Some(vec!["--autostarted"]),
         ))
         .plugin(tauri_plugin_dialog::init())
+        .plugin(
+            // The signing key comes from the build environment, not tauri.conf.json
+            tauri_plugin_updater::Builder::new()
+                .pubkey(updates::UPDATE_PUBLIC_KEY.unwrap_or_default())
+                .build(),
+        )
         .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
             // The NSIS uninstaller (PREUNINSTALL hook) launches a second
             // instance with --quit to ask us to shut down gracefully.  This
@@ -79,16 +109,18 @@ pub fn run() {
             // HKCU and HKLM Run entries exist from a legacy install).
             let is_autostart = args.iter().any(|a| a == "--autostarted");
             if !is_autostart {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
+                agent::show_ui(app);
             }
         }))
         .on_window_event(|window, event| {
             // Handle window close - always minimize to tray
             // App can only be quit via tray icon context menu
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // In agent mode the window is destroyed so the webview
+                // doesn't linger; the tray recreates it on demand
+                if agent::is_agent_mode() {
+                    return;
+                }
                 // Hide window instead of closing
                 let _ = window.hide();
                 api.prevent_close();
@@ -106,15 +138,24 @@ pub fn run() {
 
             // Initialize application state
             let app_handle = app.handle().clone();
+
+            // Crash reports first, so a panic anywhere below is captured
+            match app_handle.path().app_data_dir() {
+                Ok(dir) => crash::install(&dir),
+                Err(e) => println!("[Sacho] Crash reports disabled, no app data dir: {}", e),
+            }
             
             // Initialize config
             let config = config::Config::load_or_default(&app_handle);
+            i18n::set_language(config.language);
             
             // Window starts hidden (visible: false in tauri.conf.json) to prevent
             // a flash on screen when auto-starting. Show it now unless the app
             // was auto-started and the user wants to start hidden.
             let was_autostarted = std::env::args().any(|arg| arg == "--autostarted");
-            let should_hide = was_autostarted && config.start_minimized;
+            let is_agent = agent::init(&config);
+            let is_read_only = read_only::init(&config);
+            let should_hide = is_agent || (was_autostarted && config.start_minimized);
             if !should_hide {
                 if let Some(window) = app.get_webview_window("main") {
                     let _ = window.show();
@@ -129,6 +170,16 @@ pub fn run() {
             
             // Initialize device manager
             let device_manager = devices::DeviceManager::new();
+            {
+                // MIDI IDs used to be port indices; move saved selections over
+                let config_state = app.state::<RwLock<config::Config>>();
+                let mut config = config_state.write();
+                if config.migrate_legacy_midi_ids(&device_manager.midi_devices) {
+                    if let Err(e) = config.save(&app_handle) {
+                        println!("[Sacho] Failed to save config after MIDI ID migration: {}", e);
+                    }
+                }
+            }
             app.manage(RwLock::new(device_manager));
             
             // Initialize session database
@@ -139,8 +190,8 @@ pub fn run() {
                     // Show error to user via dialog
                     let _ = tauri::async_runtime::block_on(async {
                         tauri_plugin_dialog::DialogExt::dialog(app)
-                            .message(format!("Failed to initialize database: {}\n\nThe application may not function correctly.", e))
-                            .title("Database Error")
+                            .message(i18n::tf(i18n::Text::DatabaseErrorBody, &[&e]))
+                            .title(i18n::t(i18n::Text::DatabaseErrorTitle))
                             .blocking_show();
                     });
                     // Create an in-memory fallback so app can still run
@@ -150,13 +201,46 @@ pub fn run() {
             };
             app.manage(session_db);
 
+            // Device aliases live in the database, so label devices now
+            devices::aliases::load(&app.state::<session::SessionDatabase>());
+            devices::aliases::apply(&app_handle);
+
+            // Watch the storage folder for sessions added or removed externally
+            app.manage(session::watcher::StorageWatcherState::new(None));
+            session::watcher::apply_storage_watch_config(&app_handle);
+
+            // Background proxy generation for heavy video codecs
+            app.manage(video::proxy::ProxyQueue::new(app_handle.clone()));
+
+            // Idle-time re-encoding of MJPEG recordings (queued from Settings)
+            app.manage(video::transcode::TranscodeQueue::new(app_handle.clone()));
+
+            // Warm decoders for the custom video player
+            app.manage(video::stream::DemuxerCache::new());
+            app.manage(video::stream::VideoStreams::new());
+
+            // LAN session sharing (server starts with the first link)
+            app.manage(share::ShareState::new());
+
+            // Review audio playback
+            app.manage(playback::AudioPlaybackState::new(None));
+
             // Initialize similarity caches and warm them in the background
             app.manage(commands::SimilarityCache::new());
             app.manage(Arc::new(commands::RecordingSimilarityCache::new()));
+            app.manage(commands::FeatureExtractionControl::new());
             let handle = app_handle.clone();
             std::thread::spawn(move || {
                 let db = handle.state::<session::SessionDatabase>();
                 let cache = handle.state::<commands::SimilarityCache>();
+
+                // A corrupt database was replaced with an empty one: re-index from disk
+                if db.was_recovered_from_corruption() {
+                    if let Err(e) = commands::rebuild_session_index(&handle) {
+                        log::error!("Failed to rebuild session index: {}", e);
+                    }
+                }
+
                 commands::warm_similarity_cache(&db, &cache);
 
                 // Sync session features (compute for new sessions, warm cache)
@@ -168,12 +252,38 @@ pub fn run() {
             // Initialize device health state (before MIDI monitor so it's available)
             app.manage(RwLock::new(devices::health::DeviceHealthState::new()));
 
+            // A privacy pause from the last run keeps triggers and cameras off
+            privacy::restore(&app_handle);
+
             // Initialize and start MIDI monitor
             let mut midi_monitor = recording::MidiMonitor::new(app_handle.clone());
             if let Err(e) = midi_monitor.start() {
                 log::error!("Failed to start MIDI monitor: {}", e);
             }
             app.manage(Arc::new(Mutex::new(midi_monitor)));
+
+            // Companion phone remote (needs the monitor for start/stop)
+            let mobile_remote = remote::mobile::MobileRemoteState::new(&app_handle);
+            if !is_read_only {
+                mobile_remote.apply_config(&app_handle);
+            }
+            app.manage(mobile_remote);
+
+            // Nothing is captured in read-only mode, so the capture watchers stay off
+            if !is_read_only {
+                // Rebuild pipelines after sleep/resume
+                power::start_power_watcher(app_handle.clone());
+
+                // Record-safe mode when a laptop's battery runs low
+                battery::start_battery_watcher(app_handle.clone());
+
+                // Open and close scheduled devices as their windows pass
+                schedule::start_schedule_watcher(app_handle.clone());
+            }
+
+            // Background update checks (announced through notifications)
+            app.manage(updates::UpdateState::new());
+            updates::start_update_checker(app_handle.clone());
             
             // Initialize sysinfo for process stats (CPU/RAM monitoring)
             let mut sys = System::new();
@@ -188,6 +298,13 @@ pub fn run() {
                 log::error!("Failed to setup tray: {}", e);
             }
 
+            // Local control endpoint for scripts and other front-ends
+            if is_agent {
+                if let Err(e) = agent::start_control_server(app_handle.clone()) {
+                    log::error!("Failed to start agent control endpoint: {}", e);
+                }
+            }
+
             // Handle Ctrl+C (e.g. during development) so RunEvent::Exit cleanup runs.
             let ctrlc_handle = app_handle.clone();
             ctrlc::set_handler(move || {
@@ -198,24 +315,71 @@ pub fn run() {
             
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(read_only::guard(tauri::generate_handler![
             commands::refresh_devices,
+            commands::get_device_aliases,
+            commands::save_device_alias,
+            commands::delete_device_alias,
             commands::get_audio_devices,
+            commands::get_sample_rate_warnings,
             commands::get_midi_devices,
+            commands::get_midi_output_ports,
+            commands::get_supported_midi_access_modes,
             commands::get_video_devices,
             commands::validate_video_device_config,
+            commands::preflight_video_device,
             commands::get_recording_state,
+            commands::get_recording_stats,
+            commands::get_startup_latency_stats,
             commands::start_recording,
             commands::stop_recording,
             commands::get_sessions,
+            commands::count_sessions,
             commands::get_session_detail,
+            commands::get_session_segments,
+            commands::get_session_dynamics,
+            commands::score_against_reference,
             commands::repair_session,
+            commands::rebuild_session_metadata,
+            commands::replace_session_file,
             commands::delete_session,
             commands::rename_session,
+            commands::move_session,
+            commands::archive_session,
+            commands::restore_archived_session,
+            commands::queue_mjpeg_transcodes,
+            commands::get_transcode_status,
+            commands::set_transcode_paused,
+            commands::clear_transcode_queue,
+            commands::export_session_report,
+            commands::create_share_link,
+            commands::revoke_share_link,
+            commands::list_share_links,
+            commands::start_mobile_pairing,
+            commands::cancel_mobile_pairing,
+            commands::get_paired_devices,
+            commands::unpair_device,
             commands::update_session_notes,
+            commands::set_session_favorite,
+            commands::set_session_tags,
+            commands::set_session_fields,
+            commands::get_custom_field_keys,
+            commands::get_custom_field_values,
+            commands::update_session_loops,
             commands::get_config,
             commands::update_config,
+            commands::preview_filename_template,
             commands::update_audio_trigger_thresholds,
+            commands::calibrate_sync_offsets,
+            commands::calibrate_audio_trigger,
+            commands::measure_device_latency,
+            commands::set_device_sync_offset,
+            commands::get_trigger_history,
+            commands::clear_trigger_history,
+            commands::set_playback_active,
+            commands::start_privacy_pause,
+            commands::resume_from_privacy_pause,
+            commands::get_privacy_status,
             commands::import_midi_folder,
             commands::get_midi_imports,
             commands::get_similar_files,
@@ -223,14 +387,38 @@ pub fn run() {
             commands::rescan_sessions,
             commands::reset_cache,
             commands::reset_settings,
+            commands::check_for_updates,
+            commands::get_available_update,
+            commands::install_update,
             commands::restart_midi_monitor,
+            commands::start_midi_event_tap,
+            commands::stop_midi_event_tap,
             commands::read_session_file,
             commands::check_video_codec,
             commands::get_video_info,
             commands::get_video_frame,
+            commands::get_video_filmstrip,
             commands::get_video_frames_batch,
             commands::get_video_frame_timestamps,
+            commands::open_video_stream,
+            commands::video_stream_play,
+            commands::video_stream_pause,
+            commands::video_stream_seek,
+            commands::video_stream_set_loop,
+            commands::close_video_stream,
+            commands::audio_playback_load,
+            commands::audio_playback_play,
+            commands::audio_playback_pause,
+            commands::audio_playback_seek,
+            commands::audio_playback_set_rate,
+            commands::audio_playback_set_preserve_pitch,
+            commands::audio_playback_set_loop,
+            commands::audio_playback_set_volume,
+            commands::audio_playback_position,
+            commands::audio_playback_unload,
             commands::get_encoder_availability,
+            commands::audit_gstreamer,
+            commands::get_virtual_camera_support,
             commands::test_encoder_preset,
             commands::auto_select_encoder_preset,
             commands::set_custom_sound,
@@ -238,22 +426,57 @@ pub fn run() {
             commands::get_autostart_info,
             commands::set_all_users_autostart,
             commands::simulate_crash,
+            commands::get_pending_crash_reports,
+            commands::dismiss_crash_report,
             commands::get_app_stats,
             commands::get_disconnected_devices,
+            commands::get_devices_in_use,
+            commands::get_battery_status,
             commands::restart_device_pipelines,
             commands::get_recording_similarity_files,
             commands::get_similar_sessions,
             commands::get_session_similar_preview,
-        ])
+            commands::get_similarity_map,
+            commands::export_similarity_data,
+            commands::cancel_feature_extraction,
+            commands::vacuum_database,
+        ]))
         .build(tauri::generate_context!())
         .expect("error while building Sacho")
-        .run(|app, event| {
-            if let tauri::RunEvent::Exit = event {
+        .run(|app, event| match event {
+            tauri::RunEvent::ExitRequested { api, code, .. } => {
+                // Closing the last window isn't a quit in agent mode
+                if code.is_none() && agent::is_agent_mode() {
+                    api.prevent_exit();
+                    return;
+                }
+                // Finish writing any in-flight recording before exiting; the
+                // shutdown thread calls exit again once files are complete.
+                if shutdown::defer_exit(app, code) {
+                    api.prevent_exit();
+                }
+            }
+            tauri::RunEvent::Exit => {
                 // Single cleanup point for all exit paths (tray quit, --quit
                 // flag, etc.).  Stops all pipelines and ensures midir closes
                 // WinMM MIDI handles before the process exits.
                 let midi_monitor = app.state::<Arc<Mutex<recording::MidiMonitor>>>();
-                midi_monitor.lock().stop();
+                // Bounded so a finalization that outlived the shutdown timeout
+                // can't hang the exit
+                match midi_monitor.try_lock_for(std::time::Duration::from_secs(5)) {
+                    Some(mut monitor) => monitor.stop(),
+                    None => println!("[Sacho] Monitor still busy at exit, skipping pipeline cleanup"),
+                }
+
+                agent::remove_endpoint_file();
+
+                // Fold the WAL back into sessions.db so the file is self-contained
+                if let Some(db) = app.try_state::<session::SessionDatabase>() {
+                    let _ = db.checkpoint();
+                }
+
+                crash::mark_clean_exit();
             }
+            _ => {}
         });
 }
@@ -1,56 +1,315 @@
 // Desktop notifications
 
-use tauri::AppHandle;
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_notification::NotificationExt;
 
+use crate::i18n::{t, tf, Text};
+
 /// Send a notification when recording starts
 pub fn notify_recording_started(app: &AppHandle, devices: &[String]) {
     let device_list = if devices.is_empty() {
-        "No devices".to_string()
+        t(Text::NoDevices).to_string()
     } else if devices.len() <= 3 {
         devices.join(", ")
     } else {
-        format!("{} and {} more", devices[..2].join(", "), devices.len() - 2)
+        tf(Text::DevicesAndMore, &[&devices[..2].join(", "), &(devices.len() - 2)])
     };
     
     let _ = app.notification()
         .builder()
-        .title("Recording Started")
-        .body(format!("Recording on: {}", device_list))
+        .title(t(Text::RecordingStartedTitle))
+        .body(tf(Text::RecordingStartedBody, &[&device_list]))
         .show();
 }
 
-/// Send a notification when recording stops
-pub fn notify_recording_stopped(app: &AppHandle, duration_secs: f64, folder_name: &str) {
+/// Send a notification when recording stops, with Open Session and
+/// Favorite buttons where the platform supports them
+pub fn notify_recording_stopped(app: &AppHandle, session_id: &str, duration_secs: f64, folder_name: &str) {
     let duration_str = format_duration(duration_secs);
-    
+    let body = tf(Text::RecordingSavedBody, &[&duration_str, &folder_name]);
+    let title = t(Text::RecordingSavedTitle);
+    if show_action_toast(app, title, &body, session_id, &[ReviewAction::Open, ReviewAction::Favorite]) {
+        return;
+    }
+
     let _ = app.notification()
         .builder()
-        .title("Recording Saved")
-        .body(format!("Duration: {} • Saved to: {}", duration_str, folder_name))
+        .title(title)
+        .body(body)
         .show();
 }
 
 /// Send a notification when a device disconnects
 pub fn notify_device_disconnected(app: &AppHandle, device_names: &[String]) {
     let body = if device_names.len() == 1 {
-        format!("{} has disconnected", device_names[0])
+        tf(Text::DeviceDisconnectedOne, &[&device_names[0]])
     } else {
-        format!("{} devices have disconnected: {}", device_names.len(), device_names.join(", "))
+        tf(Text::DeviceDisconnectedMany, &[&device_names.len(), &device_names.join(", ")])
     };
 
     let _ = app.notification()
         .builder()
-        .title("Device Disconnected")
+        .title(t(Text::DeviceDisconnectedTitle))
+        .body(body)
+        .show();
+}
+
+/// Send a notification when devices didn't come back after the computer woke
+pub fn notify_devices_not_recovered(app: &AppHandle, device_names: &[String]) {
+    let body = if device_names.len() == 1 {
+        tf(Text::DeviceNotRecoveredOne, &[&device_names[0]])
+    } else {
+        tf(Text::DeviceNotRecoveredMany, &[&device_names.len(), &device_names.join(", ")])
+    };
+
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::DeviceNotRecoveredTitle))
+        .body(body)
+        .show();
+}
+
+/// Send a notification when a video device started in a fallback mode
+pub fn notify_video_fallback(app: &AppHandle, fallback: &crate::session::VideoFallback) {
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::VideoFallbackTitle))
+        .body(tf(
+            Text::VideoFallbackBody,
+            &[&fallback.device_name, &fallback.requested, &fallback.used],
+        ))
+        .show();
+}
+
+/// Send a notification when a video encoder failed and was replaced
+pub fn notify_encoder_failover(app: &AppHandle, failover: &crate::recording::video::EncoderFailover) {
+    let body = match failover.segment_filename {
+        Some(ref segment) => tf(
+            Text::EncoderSwitchedSegment,
+            &[&failover.device_name, &failover.failed_encoder, &failover.fallback_encoder, segment],
+        ),
+        None => tf(
+            Text::EncoderSwitchedBody,
+            &[&failover.device_name, &failover.failed_encoder, &failover.fallback_encoder],
+        ),
+    };
+
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::EncoderSwitchedTitle))
+        .body(body)
+        .show();
+}
+
+/// Send a notification when video encoding was lightened mid-recording
+pub fn notify_encoding_adapted(app: &AppHandle, adaptation: &crate::session::EncodingAdaptation) {
+    let change = match adaptation.step {
+        crate::session::AdaptationStep::Preset { to_preset, .. } => {
+            tf(Text::PresetLowered, &[&crate::encoding::presets::preset_label(to_preset)])
+        }
+        crate::session::AdaptationStep::FrameRate { to_fps, .. } => {
+            tf(Text::FrameRateLowered, &[&format!("{:.0}", to_fps)])
+        }
+    };
+
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::EncodingReducedTitle))
+        .body(tf(
+            Text::EncodingReducedBody,
+            &[&adaptation.device_name, &adaptation.reason.to_lowercase(), &change],
+        ))
+        .show();
+}
+
+/// Send a notification when a camera records black frames
+pub fn notify_video_blank(app: &AppHandle, device_name: &str) {
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::BlackFramesTitle))
+        .body(tf(Text::BlackFramesBody, &[&device_name]))
+        .show();
+}
+
+/// Send a notification when a privacy pause ends on its own
+pub fn notify_privacy_resumed(app: &AppHandle) {
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::PrivacyResumedTitle))
+        .body(t(Text::PrivacyResumedBody))
+        .show();
+}
+
+/// Send a notification when the computer switches to battery mid-session
+pub fn notify_on_battery(app: &AppHandle) {
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::OnBatteryTitle))
+        .body(t(Text::OnBatteryBody))
+        .show();
+}
+
+/// Send a notification when record-safe mode kicks in on low battery
+pub fn notify_battery_low(app: &AppHandle, percent: u8) {
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::BatteryLowTitle))
+        .body(tf(Text::BatteryLowBody, &[&percent]))
+        .show();
+}
+
+/// Send a notification when a newer version is on the update feed
+pub fn notify_update_available(app: &AppHandle, version: &str) {
+    let _ = app.notification()
+        .builder()
+        .title(t(Text::UpdateAvailableTitle))
+        .body(tf(Text::UpdateAvailableBody, &[&version]))
+        .show();
+}
+
+/// Buttons on a session notification ("Recording Saved" and quick review)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewAction {
+    /// The notification body was clicked
+    Open,
+    Play,
+    Favorite,
+    Delete,
+}
+
+impl ReviewAction {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn id(self) -> &'static str {
+        match self {
+            ReviewAction::Open => "open",
+            ReviewAction::Play => "play",
+            ReviewAction::Favorite => "favorite",
+            ReviewAction::Delete => "delete",
+        }
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    fn parse(id: &str) -> Option<Self> {
+        match id {
+            "open" => Some(ReviewAction::Open),
+            "play" => Some(ReviewAction::Play),
+            "favorite" => Some(ReviewAction::Favorite),
+            "delete" => Some(ReviewAction::Delete),
+            _ => None,
+        }
+    }
+}
+
+/// Payload of the `quick-review` event, asking the UI to show a session
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct QuickReviewRequest {
+    pub session_id: String,
+    /// Start playback once the session is loaded
+    pub play: bool,
+}
+
+/// Show the window with a session open (and playing, with `play`)
+pub fn open_session_for_review(app: &AppHandle, session_id: &str, play: bool) {
+    crate::agent::show_ui(app);
+    let _ = app.emit("quick-review", QuickReviewRequest {
+        session_id: session_id.to_string(),
+        play,
+    });
+}
+
+/// Send the "Recording Saved" notification with Play, Favorite and Delete
+/// buttons
+pub fn notify_quick_review(app: &AppHandle, session_id: &str, duration_secs: f64) {
+    let body = tf(Text::RecordingSavedBody, &[&format_duration(duration_secs), &session_id]);
+    let title = t(Text::RecordingSavedTitle);
+    let actions = [ReviewAction::Play, ReviewAction::Favorite, ReviewAction::Delete];
+    if show_action_toast(app, title, &body, session_id, &actions) {
+        return;
+    }
+
+    let _ = app.notification()
+        .builder()
+        .title(title)
         .body(body)
         .show();
 }
 
+/// Show a notification about a session with action buttons. Windows toasts
+/// carry the buttons and report which was pressed (clicking the body opens
+/// the session); other platforms' notifications can't, so this returns false
+/// and the caller sends a plain notification instead.
+#[cfg_attr(not(windows), allow(unused_variables))]
+fn show_action_toast(app: &AppHandle, title: &str, body: &str, session_id: &str, actions: &[ReviewAction]) -> bool {
+    #[cfg(windows)]
+    {
+        use tauri_winrt_notification::Toast;
+
+        let handler_app = app.clone();
+        let handler_session = session_id.to_string();
+        let mut toast = Toast::new(&app.config().identifier)
+            .title(title)
+            .text1(body);
+        for &action in actions {
+            toast = toast.add_button(review_action_label(action), action.id());
+        }
+        let result = toast
+            .on_activated(move |action| {
+                let action = action.as_deref().and_then(ReviewAction::parse).unwrap_or(ReviewAction::Open);
+                run_review_action(&handler_app, &handler_session, action);
+                Ok(())
+            })
+            .show();
+        match result {
+            Ok(()) => return true,
+            Err(e) => println!("[Sacho] Action toast failed, sending a plain notification: {}", e),
+        }
+    }
+
+    false
+}
+
+#[cfg(windows)]
+fn review_action_label(action: ReviewAction) -> &'static str {
+    match action {
+        ReviewAction::Open => t(Text::ActionOpenSession),
+        ReviewAction::Play => t(Text::ActionPlay),
+        ReviewAction::Favorite => t(Text::ActionFavorite),
+        ReviewAction::Delete => t(Text::ActionDelete),
+    }
+}
+
+/// Carry out a session notification button press
+pub fn run_review_action(app: &AppHandle, session_id: &str, action: ReviewAction) {
+    match action {
+        ReviewAction::Open => open_session_for_review(app, session_id, false),
+        ReviewAction::Play => open_session_for_review(app, session_id, true),
+        ReviewAction::Favorite => {
+            match crate::commands::set_session_favorite(app.state(), app.state(), session_id.to_string(), true) {
+                Ok(()) => {
+                    let _ = app.emit("session-favorite-changed", serde_json::json!({
+                        "session_id": session_id,
+                        "favorite": true,
+                    }));
+                }
+                Err(e) => println!("[Sacho] Failed to favorite {}: {}", session_id, e),
+            }
+        }
+        ReviewAction::Delete => {
+            match crate::commands::delete_session(app.clone(), app.state(), app.state(), app.state(), session_id.to_string()) {
+                Ok(()) => {
+                    let _ = app.emit("session-deleted", session_id);
+                }
+                Err(e) => println!("[Sacho] Failed to delete {}: {}", session_id, e),
+            }
+        }
+    }
+}
+
 /// Send a notification for errors
 pub fn notify_error(app: &AppHandle, message: &str) {
     let _ = app.notification()
         .builder()
-        .title("Sacho Error")
+        .title(t(Text::ErrorTitle))
         .body(message)
         .show();
 }
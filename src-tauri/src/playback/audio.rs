@@ -0,0 +1,320 @@
+// Audio playback engine for session review
+//
+// Pipeline: playbin (uri = recorded FLAC/WAV) → default audio sink
+//
+// Seeks are ACCURATE so scrubbing lands on the requested sample rather than
+// the nearest FLAC frame. Playback rate is applied with a rate seek from the
+// current position; with pitch preservation on, a scaletempo audio filter
+// time-stretches instead of resampling. A/B loops use segment seeks: the
+// monitor thread answers each SEGMENT_DONE with a non-flushing seek back to
+// the loop start, so the loop is gapless. The monitor thread also emits
+// "audio-playback-position" while playing and "audio-playback-ended" at end
+// of stream.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::Emitter;
+
+/// How often the position is reported while playing
+const POSITION_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Supported playback rate range
+pub const MIN_PLAYBACK_RATE: f64 = 0.5;
+pub const MAX_PLAYBACK_RATE: f64 = 2.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioPlaybackInfo {
+    pub filename: String,
+    pub duration_secs: f64,
+}
+
+/// Payload of the "audio-playback-position" event
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioPlaybackPosition {
+    pub position_secs: f64,
+    pub playing: bool,
+}
+
+/// Seek parameters, shared with the monitor thread so it can re-arm loops
+#[derive(Debug, Clone, Copy)]
+struct SeekParams {
+    rate: f64,
+    /// Active loop (start, end) in seconds
+    loop_region: Option<(f64, f64)>,
+}
+
+/// A loaded audio file. Dropping it stops playback.
+pub struct AudioPlayer {
+    playbin: gst::Element,
+    info: AudioPlaybackInfo,
+    params: Arc<Mutex<SeekParams>>,
+    preserve_pitch: bool,
+    stop_monitor: Arc<AtomicBool>,
+}
+
+impl AudioPlayer {
+    /// Load `path` paused at the start
+    pub fn load(app: tauri::AppHandle, path: &Path) -> anyhow::Result<Self> {
+        gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+        let uri = gst::glib::filename_to_uri(path, None)
+            .map_err(|e| anyhow::anyhow!("Invalid audio path {}: {}", path.display(), e))?;
+        // Audio only: ignore any video stream in combined files
+        let fakesink = gst::ElementFactory::make("fakesink")
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to create fakesink: {}", e))?;
+        let playbin = gst::ElementFactory::make("playbin")
+            .property("uri", uri.as_str())
+            .property("video-sink", &fakesink)
+            .property("audio-filter", &make_scaletempo()?)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to create playbin: {}", e))?;
+
+        playbin
+            .set_state(gst::State::Paused)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {:?}", path.display(), e))?;
+        let (result, _, _) = playbin.state(gst::ClockTime::from_seconds(5));
+        if let Err(e) = result {
+            let _ = playbin.set_state(gst::State::Null);
+            return Err(anyhow::anyhow!("Failed to preroll {}: {:?}", path.display(), e));
+        }
+
+        let duration_secs = playbin
+            .query_duration::<gst::ClockTime>()
+            .map(|d| d.nseconds() as f64 / 1e9)
+            .unwrap_or(0.0);
+        let info = AudioPlaybackInfo {
+            filename: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            duration_secs,
+        };
+
+        let params = Arc::new(Mutex::new(SeekParams { rate: 1.0, loop_region: None }));
+        let stop_monitor = Arc::new(AtomicBool::new(false));
+        {
+            let playbin = playbin.clone();
+            let params = params.clone();
+            let stop = stop_monitor.clone();
+            std::thread::Builder::new()
+                .name("sacho-audio-playback".into())
+                .spawn(move || monitor_loop(app, playbin, params, stop))?;
+        }
+
+        Ok(Self { playbin, info, params, preserve_pitch: true, stop_monitor })
+    }
+
+    pub fn info(&self) -> &AudioPlaybackInfo {
+        &self.info
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.params.lock().rate
+    }
+
+    pub fn loop_region(&self) -> Option<(f64, f64)> {
+        self.params.lock().loop_region
+    }
+
+    pub fn play(&self) -> anyhow::Result<()> {
+        self.playbin
+            .set_state(gst::State::Playing)
+            .map_err(|e| anyhow::anyhow!("Failed to play: {:?}", e))?;
+        Ok(())
+    }
+
+    pub fn pause(&self) -> anyhow::Result<()> {
+        self.playbin
+            .set_state(gst::State::Paused)
+            .map_err(|e| anyhow::anyhow!("Failed to pause: {:?}", e))?;
+        Ok(())
+    }
+
+    pub fn position_secs(&self) -> f64 {
+        self.playbin
+            .query_position::<gst::ClockTime>()
+            .map(|p| p.nseconds() as f64 / 1e9)
+            .unwrap_or(0.0)
+    }
+
+    /// Sample-accurate seek, keeping the current rate and loop
+    pub fn seek(&self, position_secs: f64) -> anyhow::Result<()> {
+        self.seek_with(position_secs, *self.params.lock())
+    }
+
+    /// Change the playback rate from the current position
+    pub fn set_rate(&mut self, rate: f64) -> anyhow::Result<()> {
+        let mut params = *self.params.lock();
+        params.rate = rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE);
+        self.seek_with(self.position_secs(), params)?;
+        self.params.lock().rate = params.rate;
+        Ok(())
+    }
+
+    /// Loop between `start` and `end` seconds, or play through with None.
+    /// Playback jumps to the loop start if currently outside the region.
+    pub fn set_loop(&mut self, region: Option<(f64, f64)>) -> anyhow::Result<()> {
+        if let Some((start, end)) = region {
+            if !start.is_finite() || !end.is_finite() || start < 0.0 || end <= start {
+                return Err(anyhow::anyhow!("Invalid loop region {:.3}–{:.3}s", start, end));
+            }
+        }
+        let mut params = *self.params.lock();
+        params.loop_region = region;
+        self.seek_with(self.position_secs(), params)?;
+        self.params.lock().loop_region = region;
+        Ok(())
+    }
+
+    /// Keep pitch constant when the rate changes. playbin only accepts a new
+    /// audio filter in the NULL state, so the pipeline is rebuilt at the
+    /// current position.
+    pub fn set_preserve_pitch(&mut self, preserve: bool) -> anyhow::Result<()> {
+        if preserve == self.preserve_pitch {
+            return Ok(());
+        }
+        let filter = if preserve { Some(make_scaletempo()?) } else { None };
+
+        let position = self.position_secs();
+        let (_, state, _) = self.playbin.state(gst::ClockTime::ZERO);
+        let _ = self.playbin.set_state(gst::State::Null);
+        self.playbin.set_property("audio-filter", filter.as_ref());
+        self.playbin
+            .set_state(gst::State::Paused)
+            .map_err(|e| anyhow::anyhow!("Failed to reopen audio: {:?}", e))?;
+        let _ = self.playbin.state(gst::ClockTime::from_seconds(5));
+        self.seek_with(position, *self.params.lock())?;
+        if state == gst::State::Playing {
+            self.play()?;
+        }
+        self.preserve_pitch = preserve;
+        Ok(())
+    }
+
+    pub fn preserve_pitch(&self) -> bool {
+        self.preserve_pitch
+    }
+
+    /// Output volume in dB (0 = unity)
+    pub fn set_volume_db(&self, volume_db: f64) {
+        let linear = 10f64.powf(volume_db.clamp(-60.0, 12.0) / 20.0);
+        self.playbin.set_property("volume", linear);
+    }
+
+    fn seek_with(&self, position_secs: f64, params: SeekParams) -> anyhow::Result<()> {
+        let max = if self.info.duration_secs > 0.0 { self.info.duration_secs } else { f64::MAX };
+        segment_seek(&self.playbin, position_secs.clamp(0.0, max), params, true)
+            .map_err(|e| anyhow::anyhow!("Seek failed: {:?}", e))
+    }
+}
+
+fn clock_time(secs: f64) -> gst::ClockTime {
+    gst::ClockTime::from_nseconds((secs.max(0.0) * 1e9) as u64)
+}
+
+/// Seek honouring the rate and loop. With a loop the seek is a segment seek
+/// ending at the loop end, so the pipeline posts SEGMENT_DONE instead of EOS.
+fn segment_seek(
+    playbin: &gst::Element,
+    position_secs: f64,
+    params: SeekParams,
+    flush: bool,
+) -> Result<(), gst::glib::BoolError> {
+    let mut flags = gst::SeekFlags::ACCURATE;
+    if flush {
+        flags |= gst::SeekFlags::FLUSH;
+    }
+    match params.loop_region {
+        Some((start, end)) => {
+            let position = if position_secs < start || position_secs >= end { start } else { position_secs };
+            playbin.seek(
+                params.rate,
+                flags | gst::SeekFlags::SEGMENT,
+                gst::SeekType::Set,
+                clock_time(position),
+                gst::SeekType::Set,
+                clock_time(end),
+            )
+        }
+        None => playbin.seek(
+            params.rate,
+            flags,
+            gst::SeekType::Set,
+            clock_time(position_secs),
+            gst::SeekType::None,
+            gst::ClockTime::NONE,
+        ),
+    }
+}
+
+fn make_scaletempo() -> anyhow::Result<gst::Element> {
+    gst::ElementFactory::make("scaletempo")
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create scaletempo: {}", e))
+}
+
+impl Drop for AudioPlayer {
+    fn drop(&mut self) {
+        self.stop_monitor.store(true, Ordering::Relaxed);
+        let _ = self.playbin.set_state(gst::State::Null);
+    }
+}
+
+/// Report position while playing, re-arm loops, and forward end-of-stream/errors
+fn monitor_loop(
+    app: tauri::AppHandle,
+    playbin: gst::Element,
+    params: Arc<Mutex<SeekParams>>,
+    stop: Arc<AtomicBool>,
+) {
+    let Some(bus) = playbin.bus() else { return };
+
+    while !stop.load(Ordering::Relaxed) {
+        if let Some(msg) = bus.timed_pop_filtered(
+            gst::ClockTime::from_mseconds(POSITION_INTERVAL.as_millis() as u64),
+            &[gst::MessageType::Eos, gst::MessageType::Error, gst::MessageType::SegmentDone],
+        ) {
+            match msg.view() {
+                gst::MessageView::SegmentDone(_) => {
+                    let params = *params.lock();
+                    if let Some((start, _)) = params.loop_region {
+                        // Non-flushing so the loop start follows the end without a gap
+                        if let Err(e) = segment_seek(&playbin, start, params, false) {
+                            log::warn!("Audio loop seek failed: {:?}", e);
+                        }
+                        let _ = app.emit("audio-playback-looped", start);
+                    }
+                }
+                gst::MessageView::Eos(_) => {
+                    let _ = playbin.set_state(gst::State::Paused);
+                    let _ = app.emit("audio-playback-ended", ());
+                }
+                gst::MessageView::Error(err) => {
+                    log::error!("Audio playback error: {} ({:?})", err.error(), err.debug());
+                    let _ = playbin.set_state(gst::State::Paused);
+                    let _ = app.emit("audio-playback-ended", ());
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if playbin.current_state() != gst::State::Playing {
+            continue;
+        }
+        if let Some(position) = playbin.query_position::<gst::ClockTime>() {
+            let _ = app.emit("audio-playback-position", AudioPlaybackPosition {
+                position_secs: position.nseconds() as f64 / 1e9,
+                playing: true,
+            });
+        }
+    }
+}
+
+/// Managed state holding the loaded player, if any
+pub type AudioPlaybackState = parking_lot::Mutex<Option<AudioPlayer>>;
@@ -0,0 +1,9 @@
+// Session review playback in the backend
+//
+// The webview's <audio> element can't decode 32-bit FLAC and only seeks to
+// the nearest packet, so review playback of recorded audio runs through
+// GStreamer here and the frontend follows the reported position.
+
+pub mod audio;
+
+pub use audio::{AudioPlaybackInfo, AudioPlaybackState, AudioPlayer};
@@ -0,0 +1,254 @@
+// Sleep/resume handling — rebuilds device pipelines after the computer wakes
+//
+// USB cameras (and some audio interfaces) come back from sleep with new
+// handles, leaving the old pipelines dead until a manual restart. On suspend
+// the active recording is finalized and video pipelines are released; on
+// resume, after a grace period for USB re-enumeration, all pipelines are
+// rebuilt through the same `_device-needs-restart` round-trip the health
+// checker uses (audio streams must be rebuilt on the thread that owns them).
+//
+// Windows delivers suspend/resume through PowerRegisterSuspendResumeNotification.
+// Elsewhere there is no dependency-free notification, so a watchdog thread
+// detects resume from a wall-clock jump: a thread that sleeps for a couple of
+// seconds but wakes to find much more time has passed was suspended.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, Sender};
+use parking_lot::{Mutex, RwLock};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::Config;
+use crate::devices::health::{check_active_device_health, DeviceNeedsRestartPayload};
+use crate::devices::DeviceManager;
+use crate::notifications;
+use crate::recording::MidiMonitor;
+
+/// How long after the restart to wait before checking which devices came back
+const RETURN_CHECK_SECS: u64 = 10;
+
+/// Set from suspend until the post-resume check finishes. The health checker
+/// skips video stall detection meanwhile, since pipelines are intentionally
+/// down and would otherwise be reported as disconnected.
+static RECOVERING: AtomicBool = AtomicBool::new(false);
+
+/// Sender used by the OS callback (Windows) or watchdog thread
+static POWER_EVENTS: OnceLock<Sender<PowerEvent>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PowerEvent {
+    Suspend,
+    Resume,
+}
+
+/// Whether pipelines are down or being rebuilt because of sleep/resume
+pub fn is_recovering() -> bool {
+    RECOVERING.load(Ordering::Relaxed)
+}
+
+/// Start listening for power events. Must be called after the MIDI monitor
+/// is managed.
+pub fn start_power_watcher(app: AppHandle) {
+    let (tx, rx) = crossbeam_channel::unbounded();
+    if POWER_EVENTS.set(tx).is_err() {
+        return;
+    }
+    platform::register();
+
+    std::thread::Builder::new()
+        .name("sacho-power".into())
+        .spawn(move || power_event_loop(app, rx))
+        .expect("Failed to spawn power event thread");
+}
+
+fn send(event: PowerEvent) {
+    if let Some(tx) = POWER_EVENTS.get() {
+        let _ = tx.send(event);
+    }
+}
+
+fn power_event_loop(app: AppHandle, rx: Receiver<PowerEvent>) {
+    while let Ok(event) = rx.recv() {
+        let enabled = app.state::<RwLock<Config>>().read().recover_after_sleep;
+        if !enabled {
+            continue;
+        }
+        match event {
+            PowerEvent::Suspend => on_suspend(&app),
+            PowerEvent::Resume => on_resume(&app, &rx),
+        }
+    }
+}
+
+/// Finalize any recording and release cameras before the system sleeps
+fn on_suspend(app: &AppHandle) {
+    println!("[Power] System suspending, releasing devices");
+    RECOVERING.store(true, Ordering::Relaxed);
+
+    let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+    let monitor = monitor.lock();
+    if monitor.is_recording() {
+        println!("[Power] Finalizing recording before sleep");
+        let _ = monitor.manual_stop_recording();
+    }
+    monitor.video_manager().lock().stop();
+}
+
+fn on_resume(app: &AppHandle, rx: &Receiver<PowerEvent>) {
+    RECOVERING.store(true, Ordering::Relaxed);
+    let grace = app.state::<RwLock<Config>>().read().resume_grace_secs;
+    println!("[Power] System resumed, rebuilding pipelines in {}s", grace);
+    std::thread::sleep(Duration::from_secs(grace as u64));
+
+    // The system may have gone back to sleep during the grace period
+    if rx.try_iter().any(|e| e == PowerEvent::Suspend) {
+        on_suspend(app);
+        return;
+    }
+
+    {
+        // A recording that spanned an undetected suspend has a gap; end it
+        // cleanly rather than restarting pipelines underneath it
+        let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+        let monitor = monitor.lock();
+        if monitor.is_recording() {
+            println!("[Power] Finalizing recording interrupted by sleep");
+            let _ = monitor.manual_stop_recording();
+        }
+    }
+
+    let _ = app.emit(
+        "_device-needs-restart",
+        DeviceNeedsRestartPayload {
+            device_types: vec!["midi".into(), "audio".into(), "video".into()],
+        },
+    );
+
+    std::thread::sleep(Duration::from_secs(RETURN_CHECK_SECS));
+    let missing = missing_devices(app);
+    RECOVERING.store(false, Ordering::Relaxed);
+
+    if missing.is_empty() {
+        println!("[Power] All devices recovered after resume");
+    } else {
+        println!("[Power] Devices did not return after resume: {:?}", missing);
+        notifications::notify_devices_not_recovered(app, &missing);
+    }
+}
+
+/// Names of selected devices that are not working after the restart
+fn missing_devices(app: &AppHandle) -> Vec<String> {
+    let mut missing_ids: HashSet<String> = check_active_device_health(app);
+
+    let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+    let video_manager = monitor.lock().video_manager();
+    let frame_counts = video_manager.lock().get_frame_counts();
+    let config = app.state::<RwLock<Config>>();
+    let config = config.read();
+    for id in &config.selected_video_devices {
+        if frame_counts.get(id).copied().unwrap_or(0) == 0 {
+            missing_ids.insert(id.clone());
+        }
+    }
+
+    let dm = app.state::<RwLock<DeviceManager>>();
+    let dm = dm.read();
+    let mut names: Vec<String> = missing_ids
+        .into_iter()
+        .map(|id| {
+            dm.midi_devices
+                .iter()
+                .find(|d| d.id == id)
+                .map(|d| d.name.clone())
+                .or_else(|| dm.video_devices.iter().find(|d| d.id == id).map(|d| d.name.clone()))
+                // Audio IDs are device names
+                .unwrap_or(id)
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+
+    use windows_sys::Win32::System::Power::{
+        PowerRegisterSuspendResumeNotification, DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS,
+    };
+
+    use super::{send, PowerEvent};
+
+    // From WinUser.h
+    const DEVICE_NOTIFY_CALLBACK: u32 = 2;
+    const PBT_APMSUSPEND: u32 = 0x0004;
+    const PBT_APMRESUMEAUTOMATIC: u32 = 0x0012;
+
+    unsafe extern "system" fn on_power_event(
+        _context: *const c_void,
+        event_type: u32,
+        _setting: *const c_void,
+    ) -> u32 {
+        match event_type {
+            PBT_APMSUSPEND => send(PowerEvent::Suspend),
+            // Sent on every resume, whether or not a user is present
+            PBT_APMRESUMEAUTOMATIC => send(PowerEvent::Resume),
+            _ => {}
+        }
+        0
+    }
+
+    pub fn register() {
+        // Windows keeps a pointer to the parameters for the lifetime of the
+        // registration, which is the lifetime of the process
+        let params = Box::leak(Box::new(DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS {
+            Callback: Some(on_power_event),
+            Context: std::ptr::null_mut(),
+        }));
+        let mut registration = std::ptr::null_mut();
+        let result = unsafe {
+            PowerRegisterSuspendResumeNotification(
+                DEVICE_NOTIFY_CALLBACK,
+                params as *mut DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS as *mut c_void,
+                &mut registration,
+            )
+        };
+        if result != 0 {
+            println!("[Power] Failed to register for suspend/resume notifications: {}", result);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use std::time::{Duration, SystemTime};
+
+    use super::{send, PowerEvent};
+
+    const TICK: Duration = Duration::from_secs(2);
+    /// Extra wall-clock time across one tick that counts as a suspend
+    const SUSPEND_GAP: Duration = Duration::from_secs(15);
+
+    pub fn register() {
+        std::thread::Builder::new()
+            .name("sacho-resume-watchdog".into())
+            .spawn(|| {
+                let mut last = SystemTime::now();
+                loop {
+                    std::thread::sleep(TICK);
+                    let now = SystemTime::now();
+                    // A backwards clock change gives Err; just resynchronize
+                    if let Ok(elapsed) = now.duration_since(last) {
+                        if elapsed > TICK + SUSPEND_GAP {
+                            send(PowerEvent::Resume);
+                        }
+                    }
+                    last = now;
+                }
+            })
+            .expect("Failed to spawn resume watchdog thread");
+    }
+}
@@ -0,0 +1,311 @@
+// Privacy pause ("do not record")
+//
+// For when the room is used for conversations rather than music. While paused,
+// triggers are ignored entirely and, optionally, camera pipelines are shut
+// down so no frame is captured even into pre-roll. A pause ends at a chosen
+// time (or when the user resumes), after which cameras are rebuilt through the
+// usual `_device-needs-restart` round-trip. The active pause survives an app
+// restart, and every paused interval is logged to privacy.json in the config
+// folder.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::devices::health::DeviceNeedsRestartPayload;
+use crate::recording::MidiMonitor;
+
+/// Local hour a pause "until tomorrow" ends at
+const TOMORROW_RESUME_HOUR: u32 = 6;
+
+/// Paused intervals kept in the log
+const MAX_LOGGED_PAUSES: usize = 500;
+
+/// How often the resume timer checks the clock (wall time, so sleep is handled)
+const RESUME_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+const PRIVACY_FILE_NAME: &str = "privacy.json";
+
+/// The active pause, if any
+static ACTIVE: Mutex<Option<PrivacyPause>> = Mutex::new(None);
+
+/// Mirrors `ACTIVE.is_some()` for the trigger path, which shouldn't take a lock
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Mirrors `ACTIVE.cameras_released`
+static CAMERAS_RELEASED: AtomicBool = AtomicBool::new(false);
+
+/// Bumped on every pause/resume so a stale resume timer exits
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// How long to pause for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseLength {
+    Minutes(u32),
+    /// Until the next morning at TOMORROW_RESUME_HOUR
+    UntilTomorrow,
+    /// Until resumed by hand
+    Indefinite,
+}
+
+impl PauseLength {
+    fn end_time(self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            PauseLength::Minutes(minutes) => Some(now + chrono::Duration::minutes(minutes.max(1) as i64)),
+            PauseLength::UntilTomorrow => {
+                // The next morning: later today when paused after midnight
+                let today = now.with_timezone(&Local).date_naive();
+                [Some(today), today.succ_opt()]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|day| day.and_hms_opt(TOMORROW_RESUME_HOUR, 0, 0))
+                    .filter_map(|t| t.and_local_timezone(Local).earliest())
+                    .map(|t| t.with_timezone(&Utc))
+                    .find(|t| *t > now)
+            }
+            PauseLength::Indefinite => None,
+        }
+    }
+}
+
+/// A pause in progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyPause {
+    pub started_at: DateTime<Utc>,
+    /// When triggers re-arm on their own (None = until resumed)
+    pub until: Option<DateTime<Utc>>,
+    /// Camera pipelines were shut down for the pause
+    pub cameras_released: bool,
+}
+
+/// A finished pause in the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PausedInterval {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub cameras_released: bool,
+    /// Ended by the timer rather than by the user
+    pub auto_resumed: bool,
+}
+
+/// Contents of privacy.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PrivacyFile {
+    #[serde(default)]
+    active: Option<PrivacyPause>,
+    #[serde(default)]
+    history: Vec<PausedInterval>,
+}
+
+/// Status reported to the frontend and tray
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivacyStatus {
+    pub paused: bool,
+    pub pause: Option<PrivacyPause>,
+    /// Most recent intervals first
+    pub history: Vec<PausedInterval>,
+}
+
+/// True while triggers are disarmed
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::Relaxed)
+}
+
+/// True while cameras should stay off
+pub fn cameras_released() -> bool {
+    CAMERAS_RELEASED.load(Ordering::Relaxed)
+}
+
+fn privacy_file_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .app_config_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join(PRIVACY_FILE_NAME)
+}
+
+fn read_file(app: &AppHandle) -> PrivacyFile {
+    std::fs::read_to_string(privacy_file_path(app))
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn write_file(app: &AppHandle, file: &PrivacyFile) {
+    let path = privacy_file_path(app);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    match serde_json::to_string_pretty(file) {
+        Ok(json) => {
+            if let Err(e) = std::fs::write(&path, json) {
+                println!("[Privacy] Failed to write {}: {}", path.display(), e);
+            }
+        }
+        Err(e) => println!("[Privacy] Failed to serialize privacy log: {}", e),
+    }
+}
+
+fn set_active(pause: Option<PrivacyPause>) {
+    PAUSED.store(pause.is_some(), Ordering::Relaxed);
+    CAMERAS_RELEASED.store(pause.as_ref().map(|p| p.cameras_released).unwrap_or(false), Ordering::Relaxed);
+    *ACTIVE.lock() = pause;
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Restore a pause that was active when the app last quit. Must run before the
+/// monitor starts so cameras stay off.
+pub fn restore(app: &AppHandle) {
+    let mut file = read_file(app);
+    let Some(pause) = file.active.take() else {
+        return;
+    };
+    let now = Utc::now();
+    if pause.until.map(|until| until <= now).unwrap_or(false) {
+        // Expired while the app wasn't running
+        push_history(&mut file, &pause, pause.until.unwrap_or(now), true);
+        write_file(app, &file);
+        println!("[Privacy] Pause expired while Sacho was closed");
+        return;
+    }
+
+    println!("[Privacy] Restoring privacy pause (until {:?})", pause.until);
+    let until = pause.until;
+    set_active(Some(pause));
+    if until.is_some() {
+        spawn_resume_timer(app.clone());
+    }
+}
+
+/// Disarm triggers (and optionally shut down cameras) for `length`. An active
+/// recording is finalized first. Replaces any pause already in progress.
+pub fn pause(app: &AppHandle, length: PauseLength, release_cameras: bool) -> PrivacyStatus {
+    let now = Utc::now();
+    let mut file = read_file(app);
+    // Keep the original start time when extending a pause
+    let started_at = ACTIVE.lock().as_ref().map(|p| p.started_at).unwrap_or(now);
+    let was_released = cameras_released();
+    let pause = PrivacyPause {
+        started_at,
+        until: length.end_time(now),
+        cameras_released: release_cameras || was_released,
+    };
+    file.active = Some(pause.clone());
+    write_file(app, &file);
+    set_active(Some(pause.clone()));
+    println!(
+        "[Privacy] Paused until {} (cameras {})",
+        pause.until.map(|t| t.with_timezone(&Local).to_rfc3339()).unwrap_or_else(|| "resumed".into()),
+        if pause.cameras_released { "off" } else { "on" }
+    );
+
+    {
+        let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+        let monitor = monitor.lock();
+        if monitor.is_recording() {
+            println!("[Privacy] Finalizing recording before pausing");
+            let _ = monitor.manual_stop_recording();
+        }
+        if pause.cameras_released && !was_released {
+            monitor.video_manager().lock().stop();
+        }
+    }
+
+    if pause.until.is_some() {
+        spawn_resume_timer(app.clone());
+    }
+    notify_changed(app)
+}
+
+/// Re-arm triggers and bring cameras back
+pub fn resume(app: &AppHandle) -> PrivacyStatus {
+    end_pause(app, false);
+    notify_changed(app)
+}
+
+fn end_pause(app: &AppHandle, auto_resumed: bool) {
+    let Some(pause) = ACTIVE.lock().clone() else {
+        return;
+    };
+    let mut file = read_file(app);
+    file.active = None;
+    push_history(&mut file, &pause, Utc::now(), auto_resumed);
+    write_file(app, &file);
+    set_active(None);
+    println!("[Privacy] Resumed{}", if auto_resumed { " (timer)" } else { "" });
+
+    if pause.cameras_released {
+        let _ = app.emit(
+            "_device-needs-restart",
+            DeviceNeedsRestartPayload {
+                device_types: vec!["video".into()],
+            },
+        );
+    }
+    if auto_resumed {
+        crate::notifications::notify_privacy_resumed(app);
+    }
+}
+
+fn push_history(file: &mut PrivacyFile, pause: &PrivacyPause, ended_at: DateTime<Utc>, auto_resumed: bool) {
+    file.history.push(PausedInterval {
+        started_at: pause.started_at,
+        ended_at,
+        cameras_released: pause.cameras_released,
+        auto_resumed,
+    });
+    if file.history.len() > MAX_LOGGED_PAUSES {
+        let excess = file.history.len() - MAX_LOGGED_PAUSES;
+        file.history.drain(..excess);
+    }
+}
+
+/// Wait for the active pause's end time, then resume. Exits early if the
+/// pause is replaced or ended in the meantime.
+fn spawn_resume_timer(app: AppHandle) {
+    let generation = GENERATION.load(Ordering::Relaxed);
+    std::thread::Builder::new()
+        .name("sacho-privacy".into())
+        .spawn(move || loop {
+            std::thread::sleep(RESUME_CHECK_INTERVAL);
+            if GENERATION.load(Ordering::Relaxed) != generation {
+                return;
+            }
+            let until = ACTIVE.lock().as_ref().and_then(|p| p.until);
+            match until {
+                Some(until) if until <= Utc::now() => {
+                    end_pause(&app, true);
+                    notify_changed(&app);
+                    return;
+                }
+                Some(_) => {}
+                None => return,
+            }
+        })
+        .expect("Failed to spawn privacy timer thread");
+}
+
+pub fn status(app: &AppHandle) -> PrivacyStatus {
+    let mut history = read_file(app).history;
+    history.reverse();
+    let pause = ACTIVE.lock().clone();
+    PrivacyStatus {
+        paused: pause.is_some(),
+        pause,
+        history,
+    }
+}
+
+/// Tell the frontend and tray about a change
+fn notify_changed(app: &AppHandle) -> PrivacyStatus {
+    let status = status(app);
+    crate::tray::update_privacy_state(app, status.paused);
+    let _ = app.emit("privacy-pause-changed", &status);
+    status
+}
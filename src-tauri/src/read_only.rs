@@ -0,0 +1,143 @@
+// Read-only library mode — browse a library without touching it
+//
+// A second machine mounting the same storage folder (e.g. from a NAS) should
+// be able to browse, search and play back sessions without any risk of
+// writing to them. In read-only mode the monitor never opens devices, side
+// files, proxies and schema markers are never written, background proxy and
+// transcode work is skipped, and only browse and playback commands run. The index database lives
+// in the local app data dir, so keeping it up to date stays allowed.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::ipc::Invoke;
+use tauri::Runtime;
+
+use crate::config::Config;
+
+/// Command-line flag that opens the library read-only
+pub const READ_ONLY_FLAG: &str = "--read-only";
+
+/// Set once at startup from the flag or config
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Commands that only browse, search or play back the library, or read and
+/// change this machine's own settings and index. Everything else (recording,
+/// opening capture devices, anything that writes into session folders) is
+/// rejected, so a command added later stays blocked until it's listed here.
+const ALLOWED_COMMANDS: &[&str] = &[
+    // Devices (enumeration only; nothing is opened)
+    "get_device_aliases",
+    "get_audio_devices",
+    "get_sample_rate_warnings",
+    "get_midi_devices",
+    "get_midi_output_ports",
+    "get_supported_midi_access_modes",
+    "get_video_devices",
+    "get_disconnected_devices",
+    "get_devices_in_use",
+    "get_encoder_availability",
+    "get_virtual_camera_support",
+    "audit_gstreamer",
+    // Recording state (always idle)
+    "get_recording_state",
+    "get_recording_stats",
+    "get_privacy_status",
+    "get_trigger_history",
+    // Browsing
+    "get_sessions",
+    "count_sessions",
+    "get_session_detail",
+    "get_session_segments",
+    "get_session_dynamics",
+    "get_startup_latency_stats",
+    "score_against_reference",
+    "get_custom_field_keys",
+    "get_custom_field_values",
+    "rescan_sessions",
+    "get_transcode_status",
+    "list_share_links",
+    "get_paired_devices",
+    // Similarity (features are cached in the local index)
+    "import_midi_folder",
+    "get_midi_imports",
+    "get_similar_files",
+    "clear_midi_imports",
+    "get_recording_similarity_files",
+    "get_similar_sessions",
+    "get_session_similar_preview",
+    "get_similarity_map",
+    "export_similarity_data",
+    "cancel_feature_extraction",
+    // Playback
+    "set_playback_active",
+    "read_session_file",
+    "check_video_codec",
+    "get_video_info",
+    "get_video_frame",
+    "get_video_filmstrip",
+    "get_video_frames_batch",
+    "get_video_frame_timestamps",
+    "open_video_stream",
+    "video_stream_play",
+    "video_stream_pause",
+    "video_stream_seek",
+    "video_stream_set_loop",
+    "close_video_stream",
+    "audio_playback_load",
+    "audio_playback_play",
+    "audio_playback_pause",
+    "audio_playback_seek",
+    "audio_playback_set_rate",
+    "audio_playback_set_preserve_pitch",
+    "audio_playback_set_loop",
+    "audio_playback_set_volume",
+    "audio_playback_position",
+    "audio_playback_unload",
+    // This machine's settings and index (needed to leave read-only mode)
+    "get_config",
+    "update_config",
+    "preview_filename_template",
+    "reset_cache",
+    "reset_settings",
+    "vacuum_database",
+    "get_app_stats",
+    "get_battery_status",
+    "get_autostart_info",
+    "get_pending_crash_reports",
+    "dismiss_crash_report",
+    "check_for_updates",
+    "get_available_update",
+    "install_update",
+];
+
+pub fn init(config: &Config) -> bool {
+    let enabled = config.read_only_library || std::env::args().any(|arg| arg == READ_ONLY_FLAG);
+    READ_ONLY.store(enabled, Ordering::SeqCst);
+    if enabled {
+        println!("[Sacho] Library opened read-only");
+    }
+    enabled
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::SeqCst)
+}
+
+/// Error for an operation refused in read-only mode
+pub fn refusal() -> String {
+    "Sacho is in read-only mode; the library can't be changed".to_string()
+}
+
+/// Wrap the command handler so commands not on the allow-list are rejected
+/// in read-only mode
+pub fn guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        if is_read_only() && !ALLOWED_COMMANDS.contains(&invoke.message.command()) {
+            invoke.resolver.reject(refusal());
+            return true;
+        }
+        handler(invoke)
+    }
+}
@@ -0,0 +1,286 @@
+// Lens-cap / camera-off detection
+//
+// A camera left capped, switched off behind a capture card, or pointed at a
+// dark room still delivers frames, so nothing looks wrong until someone opens
+// the file. After a recording starts, a few frames spread over the first
+// couple of seconds are measured for brightness on a background thread. If
+// every one is near-black and flat, the user is warned right away and the
+// file is flagged in the session's quality report.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use parking_lot::Mutex;
+
+use super::video::BufferedFrame;
+
+/// Frames measured per recording
+const PROBE_FRAMES: usize = 5;
+
+/// Spacing between measured frames
+const PROBE_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Mean luma (0-255) at or below which a frame counts as black. Studio-range
+/// black is 16; sensor noise behind a lens cap lifts it a little.
+const MAX_BLANK_MEAN: f64 = 24.0;
+
+/// Luma standard deviation below which a dark frame counts as featureless
+const MAX_BLANK_STDDEV: f64 = 6.0;
+
+/// Every Nth pixel is sampled
+const SAMPLE_STEP: usize = 7;
+
+/// Compressed frames are decoded at this size for measuring
+const DECODE_WIDTH: i32 = 160;
+const DECODE_HEIGHT: i32 = 90;
+
+/// Brightness of one frame
+#[derive(Debug, Clone, Copy)]
+pub struct LumaStats {
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl LumaStats {
+    fn from_samples(samples: impl Iterator<Item = f64>) -> Option<Self> {
+        let (mut n, mut sum, mut sum_sq) = (0usize, 0.0f64, 0.0f64);
+        for v in samples {
+            n += 1;
+            sum += v;
+            sum_sq += v * v;
+        }
+        if n == 0 {
+            return None;
+        }
+        let mean = sum / n as f64;
+        let variance = (sum_sq / n as f64 - mean * mean).max(0.0);
+        Some(Self { mean, stddev: variance.sqrt() })
+    }
+
+    pub fn is_blank(&self) -> bool {
+        self.mean <= MAX_BLANK_MEAN && self.stddev <= MAX_BLANK_STDDEV
+    }
+}
+
+/// Outcome of the probe for one recording
+#[derive(Debug, Clone, Copy)]
+pub struct BlankVerdict {
+    pub likely_blank: bool,
+    /// Brightest mean luma among the measured frames
+    pub mean_luma: f64,
+}
+
+/// Collects frames at the start of a recording and measures them
+pub struct BlankFrameProbe {
+    format: String,
+    width: u32,
+    height: u32,
+    next_at: Instant,
+    pending: Vec<BufferedFrame>,
+    verdict: Arc<Mutex<Option<BlankVerdict>>>,
+    launched: bool,
+    reported: bool,
+}
+
+impl BlankFrameProbe {
+    /// `format` is the source format (raw pixel format or "MJPEG")
+    pub fn new(format: &str, width: u32, height: u32) -> Self {
+        Self {
+            format: format.to_string(),
+            width,
+            height,
+            next_at: Instant::now(),
+            pending: Vec::new(),
+            verdict: Arc::new(Mutex::new(None)),
+            launched: false,
+            reported: false,
+        }
+    }
+
+    /// Whether frames in this format can be measured at all
+    pub fn supports(format: &str) -> bool {
+        format == "MJPEG" || raw_layout(format).is_some()
+    }
+
+    /// Offer frames as they pass through the poller
+    pub fn offer(&mut self, frames: &[BufferedFrame]) {
+        if self.launched {
+            return;
+        }
+        for frame in frames {
+            if frame.wall_time >= self.next_at && !frame.data.is_empty() {
+                self.pending.push(frame.clone());
+                self.next_at = frame.wall_time + PROBE_INTERVAL;
+                if self.pending.len() >= PROBE_FRAMES {
+                    self.launch();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn launch(&mut self) {
+        self.launched = true;
+        let frames = std::mem::take(&mut self.pending);
+        let format = self.format.clone();
+        let (width, height) = (self.width, self.height);
+        let verdict = self.verdict.clone();
+        let _ = std::thread::Builder::new()
+            .name("sacho-blank-probe".into())
+            .spawn(move || {
+                let stats: Vec<LumaStats> = if format == "MJPEG" {
+                    decode_jpeg_luma(&frames).unwrap_or_else(|e| {
+                        println!("[Video] Blank-frame check could not decode frames: {}", e);
+                        Vec::new()
+                    })
+                } else {
+                    frames
+                        .iter()
+                        .filter_map(|f| {
+                            let format = f.pixel_format.as_deref().unwrap_or(&format);
+                            raw_luma_stats(&f.data, format, width, height)
+                        })
+                        .collect()
+                };
+                if stats.is_empty() {
+                    return;
+                }
+                let mean_luma = stats.iter().map(|s| s.mean).fold(0.0, f64::max);
+                *verdict.lock() = Some(BlankVerdict {
+                    likely_blank: stats.iter().all(LumaStats::is_blank),
+                    mean_luma,
+                });
+            });
+    }
+
+    /// The verdict, once measured
+    pub fn verdict(&self) -> Option<BlankVerdict> {
+        *self.verdict.lock()
+    }
+
+    /// A blank verdict not yet reported to the user (returned once)
+    pub fn take_blank_warning(&mut self) -> Option<BlankVerdict> {
+        if self.reported {
+            return None;
+        }
+        let verdict = self.verdict().filter(|v| v.likely_blank)?;
+        self.reported = true;
+        Some(verdict)
+    }
+}
+
+/// Where luma lives in a raw frame
+enum RawLayout {
+    /// Luma plane first, one byte per pixel
+    Planar,
+    /// Packed 4:2:2 with luma at this byte offset in each 2-byte pair
+    Packed422(usize),
+    /// Packed RGB with this many bytes per pixel and the alpha/padding byte, if any
+    Rgb { bytes: usize, skip: Option<usize> },
+}
+
+fn raw_layout(format: &str) -> Option<RawLayout> {
+    match format {
+        "NV12" | "NV21" | "NV16" | "I420" | "YV12" | "Y41B" | "Y42B" | "Y444" | "GRAY8" => Some(RawLayout::Planar),
+        "YUY2" | "YUYV" | "YVYU" => Some(RawLayout::Packed422(0)),
+        "UYVY" | "VYUY" => Some(RawLayout::Packed422(1)),
+        "RGB" | "BGR" => Some(RawLayout::Rgb { bytes: 3, skip: None }),
+        "RGBx" | "BGRx" | "RGBA" | "BGRA" | "xRGB" | "xBGR" | "ARGB" | "ABGR" => Some(RawLayout::Rgb {
+            bytes: 4,
+            skip: format.find(|c| c == 'x' || c == 'A'),
+        }),
+        _ => None,
+    }
+}
+
+/// Luma statistics of a raw frame, sampling every few pixels
+pub fn raw_luma_stats(data: &[u8], format: &str, width: u32, height: u32) -> Option<LumaStats> {
+    let pixels = width as usize * height as usize;
+    match raw_layout(format)? {
+        RawLayout::Planar => {
+            let plane = data.get(..pixels.min(data.len()))?;
+            LumaStats::from_samples(plane.iter().step_by(SAMPLE_STEP).map(|&y| y as f64))
+        }
+        RawLayout::Packed422(offset) => LumaStats::from_samples(
+            data.chunks_exact(2)
+                .step_by(SAMPLE_STEP)
+                .map(|pair| pair[offset] as f64),
+        ),
+        RawLayout::Rgb { bytes, skip } => LumaStats::from_samples(
+            data.chunks_exact(bytes).step_by(SAMPLE_STEP).map(|px| {
+                let (sum, count) = px
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| Some(*i) != skip)
+                    .fold((0u32, 0u32), |(s, c), (_, &v)| (s + v as u32, c + 1));
+                sum as f64 / count.max(1) as f64
+            }),
+        ),
+    }
+}
+
+/// Decode JPEG frames to small grayscale images and measure them
+fn decode_jpeg_luma(frames: &[BufferedFrame]) -> anyhow::Result<Vec<LumaStats>> {
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+    let pipeline = gst::Pipeline::new();
+    let appsrc = gst_app::AppSrc::builder()
+        .caps(&gst::Caps::builder("image/jpeg").build())
+        .format(gst::Format::Time)
+        .build();
+    let jpegdec = gst::ElementFactory::make("jpegdec").build()?;
+    let videoconvert = gst::ElementFactory::make("videoconvert").build()?;
+    let videoscale = gst::ElementFactory::make("videoscale").build()?;
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("video/x-raw")
+                .field("format", "GRAY8")
+                .field("width", DECODE_WIDTH)
+                .field("height", DECODE_HEIGHT)
+                .build(),
+        )
+        .build()?;
+    let appsink = gst_app::AppSink::builder().sync(false).build();
+
+    pipeline.add_many([
+        appsrc.upcast_ref(),
+        &jpegdec,
+        &videoconvert,
+        &videoscale,
+        &capsfilter,
+        appsink.upcast_ref(),
+    ])?;
+    gst::Element::link_many([
+        appsrc.upcast_ref(),
+        &jpegdec,
+        &videoconvert,
+        &videoscale,
+        &capsfilter,
+        appsink.upcast_ref(),
+    ])?;
+    pipeline.set_state(gst::State::Playing)?;
+
+    for (i, frame) in frames.iter().enumerate() {
+        let mut buffer = gst::Buffer::from_slice(frame.data.clone());
+        if let Some(buffer) = buffer.get_mut() {
+            buffer.set_pts(gst::ClockTime::from_mseconds(i as u64 * 100));
+        }
+        appsrc.push_buffer(buffer)?;
+    }
+    let _ = appsrc.end_of_stream();
+
+    let mut stats = Vec::new();
+    while let Some(sample) = appsink.try_pull_sample(gst::ClockTime::from_seconds(2)) {
+        let Some(buffer) = sample.buffer() else { continue };
+        let Ok(map) = buffer.map_readable() else { continue };
+        if let Some(s) = LumaStats::from_samples(map.as_slice().iter().map(|&y| y as f64)) {
+            stats.push(s);
+        }
+    }
+    let _ = pipeline.set_state(gst::State::Null);
+    Ok(stats)
+}
@@ -0,0 +1,236 @@
+// Sync offset calibration
+//
+// The user records a short take containing one sharp event seen or heard by
+// every device: a clap near the microphones, a flash in view of the cameras,
+// or a MIDI note that lights an LED in shot. Each file's first occurrence of
+// the event is located and compared against a reference stream (MIDI if
+// present, since it carries no capture latency, else the first audio file).
+// The differences are the residual per-device latencies, which can be folded
+// into `Config::device_sync_offsets_ms` and applied by `start_recording`.
+
+use std::path::Path;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use serde::Serialize;
+
+use crate::session::SessionMetadata;
+use crate::similarity::{audio_features, midi_parser};
+
+/// Onset detection window (5ms at the analysis rate)
+const ONSET_WINDOW: usize = (audio_features::ANALYSIS_RATE / 200) as usize;
+
+/// An onset is the first window reaching this fraction of the loudest window
+const ONSET_THRESHOLD: f32 = 0.3;
+
+/// Frames are shrunk to this size before measuring brightness
+const LUMA_WIDTH: i32 = 160;
+const LUMA_HEIGHT: i32 = 90;
+
+/// Smallest mean-luma jump (0-255) accepted as a flash
+const MIN_FLASH_DELTA: f64 = 20.0;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamKind {
+    Audio,
+    Midi,
+    Video,
+}
+
+/// Where the calibration event was found in one recorded file
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncMeasurement {
+    pub device_name: String,
+    pub kind: StreamKind,
+    pub filename: String,
+    /// Event time within the file, None if no event was detected
+    pub event_secs: Option<f64>,
+    /// Residual latency relative to the reference (positive = late)
+    pub offset_ms: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalibrationReport {
+    /// Device every offset is measured against
+    pub reference: Option<String>,
+    pub measurements: Vec<SyncMeasurement>,
+    /// Whether the offsets were saved to the config
+    pub applied: bool,
+}
+
+/// Measure the residual offset of every file in a calibration session
+pub fn analyze_session(session: &SessionMetadata) -> CalibrationReport {
+    let mut measurements = Vec::new();
+
+    for file in &session.midi_files {
+        let event_secs = first_midi_note_secs(&session.path.join(&file.filename))
+            .map_err(|e| log::warn!("Calibration: {}: {}", file.filename, e))
+            .ok()
+            .flatten();
+        measurements.push(measurement(&file.device_name, StreamKind::Midi, &file.filename, event_secs));
+    }
+    for file in &session.audio_files {
+        let event_secs = audio_onset_secs(&session.path.join(&file.filename))
+            .map_err(|e| log::warn!("Calibration: {}: {}", file.filename, e))
+            .ok()
+            .flatten();
+        measurements.push(measurement(&file.device_name, StreamKind::Audio, &file.filename, event_secs));
+    }
+    for file in &session.video_files {
+        let event_secs = video_flash_secs(&session.path.join(&file.filename))
+            .map_err(|e| log::warn!("Calibration: {}: {}", file.filename, e))
+            .ok()
+            .flatten();
+        measurements.push(measurement(&file.device_name, StreamKind::Video, &file.filename, event_secs));
+    }
+
+    // MIDI first, then audio: the first stream with a detected event
+    let reference = measurements
+        .iter()
+        .filter(|m| m.kind != StreamKind::Video)
+        .find_map(|m| m.event_secs.map(|secs| (m.device_name.clone(), secs)));
+
+    if let Some((_, reference_secs)) = &reference {
+        for m in &mut measurements {
+            m.offset_ms = m
+                .event_secs
+                .map(|secs| ((secs - reference_secs) * 1000.0).round() as i32);
+        }
+    }
+
+    CalibrationReport {
+        reference: reference.map(|(name, _)| name),
+        measurements,
+        applied: false,
+    }
+}
+
+fn measurement(device_name: &str, kind: StreamKind, filename: &str, event_secs: Option<f64>) -> SyncMeasurement {
+    SyncMeasurement {
+        device_name: device_name.to_string(),
+        kind,
+        filename: filename.to_string(),
+        event_secs,
+        offset_ms: None,
+    }
+}
+
+/// Time of the earliest note-on in a MIDI file
+fn first_midi_note_secs(path: &Path) -> anyhow::Result<Option<f64>> {
+    let parsed = midi_parser::parse_midi(path)?;
+    Ok(parsed
+        .events
+        .iter()
+        .map(|note| note.start_tick)
+        .min()
+        .map(|tick| midi_parser::tick_to_seconds(tick, parsed.ticks_per_beat, &parsed.tempo_map)))
+}
+
+/// Time of the first sharp onset (clap) in an audio file
+fn audio_onset_secs(path: &Path) -> anyhow::Result<Option<f64>> {
+    let samples = audio_features::decode_mono(path)?;
+    let rms: Vec<f32> = samples
+        .chunks(ONSET_WINDOW)
+        .map(|w| (w.iter().map(|s| s * s).sum::<f32>() / w.len() as f32).sqrt())
+        .collect();
+    let peak = rms.iter().cloned().fold(0.0f32, f32::max);
+    if peak <= 0.0 {
+        return Ok(None);
+    }
+    Ok(rms
+        .iter()
+        .position(|&r| r >= peak * ONSET_THRESHOLD)
+        .map(|i| (i * ONSET_WINDOW) as f64 / audio_features::ANALYSIS_RATE as f64))
+}
+
+/// Time of the frame with the largest brightness increase (flash or LED)
+fn video_flash_secs(path: &Path) -> anyhow::Result<Option<f64>> {
+    let lumas = decode_mean_luma(path)?;
+    let best = lumas
+        .windows(2)
+        .map(|pair| (pair[1].0, pair[1].1 - pair[0].1))
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    Ok(best.filter(|(_, delta)| *delta >= MIN_FLASH_DELTA).map(|(secs, _)| secs))
+}
+
+/// Decode a video to (timestamp, mean luma) per frame
+fn decode_mean_luma(path: &Path) -> anyhow::Result<Vec<(f64, f64)>> {
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+    let pipeline = gst::Pipeline::new();
+    let filesrc = gst::ElementFactory::make("filesrc")
+        .property("location", path.to_string_lossy().to_string())
+        .build()?;
+    let decodebin = gst::ElementFactory::make("decodebin").build()?;
+    let videoconvert = gst::ElementFactory::make("videoconvert").build()?;
+    let videoscale = gst::ElementFactory::make("videoscale").build()?;
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("video/x-raw")
+                .field("format", "GRAY8")
+                .field("width", LUMA_WIDTH)
+                .field("height", LUMA_HEIGHT)
+                .build(),
+        )
+        .build()?;
+    let appsink = gst_app::AppSink::builder().sync(false).build();
+
+    pipeline.add_many([
+        &filesrc,
+        &decodebin,
+        &videoconvert,
+        &videoscale,
+        &capsfilter,
+        appsink.upcast_ref(),
+    ])?;
+    filesrc.link(&decodebin)?;
+    gst::Element::link_many([&videoconvert, &videoscale, &capsfilter, appsink.upcast_ref()])?;
+
+    let videoconvert_weak = videoconvert.downgrade();
+    decodebin.connect_pad_added(move |_decodebin, src_pad| {
+        let Some(videoconvert) = videoconvert_weak.upgrade() else {
+            return;
+        };
+        let is_video = src_pad
+            .current_caps()
+            .unwrap_or_else(|| src_pad.query_caps(None))
+            .structure(0)
+            .map(|s| s.name().as_str().starts_with("video/"))
+            .unwrap_or(false);
+        if !is_video {
+            return;
+        }
+        if let Some(sink_pad) = videoconvert.static_pad("sink") {
+            if !sink_pad.is_linked() {
+                if let Err(e) = src_pad.link(&sink_pad) {
+                    log::warn!("Calibration: failed to link video pad: {:?}", e);
+                }
+            }
+        }
+    });
+
+    pipeline.set_state(gst::State::Playing)?;
+
+    let mut lumas = Vec::new();
+    while let Some(sample) = appsink.try_pull_sample(gst::ClockTime::from_seconds(5)) {
+        let Some(buffer) = sample.buffer() else { continue };
+        let Some(pts) = buffer.pts() else { continue };
+        let Ok(map) = buffer.map_readable() else { continue };
+        let pixels = map.as_slice();
+        if pixels.is_empty() {
+            continue;
+        }
+        let mean = pixels.iter().map(|&p| p as u64).sum::<u64>() as f64 / pixels.len() as f64;
+        lumas.push((pts.nseconds() as f64 / 1e9, mean));
+    }
+
+    let _ = pipeline.set_state(gst::State::Null);
+
+    if lumas.is_empty() {
+        return Err(anyhow::anyhow!("No video decoded from {}", path.display()));
+    }
+    Ok(lumas)
+}
@@ -0,0 +1,84 @@
+// Time source for pre-roll trimming, trigger metering, idle timeout and sync math
+//
+// Production code uses `SystemClock`. Tests inject a `ManualClock` and step it
+// explicitly, so timing-dependent behavior (which pre-roll frames survive a
+// trim, when the idle timeout fires, how the audio/video offset is computed)
+// can be reproduced exactly instead of depending on real sleeps.
+
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+
+/// A monotonic time source
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+
+    /// Time since `earlier`, zero if `earlier` is in the future
+    fn elapsed(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real monotonic clock
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Shared handle to the system clock
+pub fn system() -> SharedClock {
+    static SYSTEM: OnceLock<SharedClock> = OnceLock::new();
+    SYSTEM.get_or_init(|| Arc::new(SystemClock)).clone()
+}
+
+/// A clock that only moves when told to.
+/// `Instant` can't be constructed from an arbitrary value, so virtual time is
+/// a fixed origin plus an offset that tests advance.
+#[derive(Debug)]
+pub struct ManualClock {
+    origin: Instant,
+    offset: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            offset: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move virtual time forward
+    pub fn advance(&self, by: Duration) {
+        *self.offset.lock() += by;
+    }
+
+    /// Virtual time elapsed since the clock was created
+    pub fn offset(&self) -> Duration {
+        *self.offset.lock()
+    }
+
+    /// Convenience for injecting into components that take a `SharedClock`
+    pub fn shared(self: &Arc<Self>) -> SharedClock {
+        self.clone()
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.origin + *self.offset.lock()
+    }
+}
@@ -0,0 +1,89 @@
+// MIDI output feedback to trigger devices (recording state LEDs)
+
+use midir::{MidiOutput, MidiOutputConnection};
+
+use crate::config::{MidiFeedbackConfig, MidiFeedbackMessage};
+
+/// Output connections to trigger devices that receive recording-state feedback.
+///
+/// Output ports are matched to trigger inputs by port name, since MIDI input
+/// and output port indices are enumerated independently.
+pub struct MidiFeedback {
+    connections: Vec<(String, MidiOutputConnection)>,
+    start_message: Option<MidiFeedbackMessage>,
+    stop_message: Option<MidiFeedbackMessage>,
+}
+
+impl MidiFeedback {
+    /// Open output ports for the given trigger device port names.
+    /// Returns None if feedback is disabled or no matching output port exists.
+    pub fn connect(config: &MidiFeedbackConfig, trigger_port_names: &[String]) -> Option<Self> {
+        if !config.enabled || trigger_port_names.is_empty() {
+            return None;
+        }
+
+        let mut connections = Vec::new();
+        for port_name in trigger_port_names {
+            let midi_out = match MidiOutput::new("sacho-feedback") {
+                Ok(m) => m,
+                Err(e) => {
+                    println!("[Sacho] Failed to create MIDI output: {}", e);
+                    return None;
+                }
+            };
+            let port = midi_out
+                .ports()
+                .into_iter()
+                .find(|p| midi_out.port_name(p).ok().as_deref() == Some(port_name.as_str()));
+            let Some(port) = port else {
+                println!("[Sacho] No MIDI output port for trigger {}, feedback disabled for it", port_name);
+                continue;
+            };
+            match midi_out.connect(&port, "sacho-feedback") {
+                Ok(conn) => {
+                    println!("[Sacho] Connected MIDI feedback output: {}", port_name);
+                    connections.push((port_name.clone(), conn));
+                }
+                Err(e) => {
+                    println!("[Sacho] Failed to connect MIDI feedback output {}: {}", port_name, e);
+                }
+            }
+        }
+
+        if connections.is_empty() {
+            return None;
+        }
+
+        let mut feedback = Self {
+            connections,
+            start_message: config.start_message.clone(),
+            stop_message: config.stop_message.clone(),
+        };
+        // Show the armed (idle) state as soon as the controller is connected
+        feedback.send_stop();
+        Some(feedback)
+    }
+
+    /// Send the recording-started message to all connected trigger devices
+    pub fn send_start(&mut self) {
+        if let Some(message) = self.start_message.clone() {
+            self.send(&message);
+        }
+    }
+
+    /// Send the recording-stopped message to all connected trigger devices
+    pub fn send_stop(&mut self) {
+        if let Some(message) = self.stop_message.clone() {
+            self.send(&message);
+        }
+    }
+
+    fn send(&mut self, message: &MidiFeedbackMessage) {
+        let bytes = message.to_bytes();
+        for (port_name, conn) in self.connections.iter_mut() {
+            if let Err(e) = conn.send(&bytes) {
+                println!("[Sacho] Failed to send MIDI feedback to {}: {}", port_name, e);
+            }
+        }
+    }
+}
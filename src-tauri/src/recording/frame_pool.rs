@@ -0,0 +1,93 @@
+// Capture frame pool
+//
+// Each video sample used to be copied out of its GStreamer buffer into a
+// fresh Vec in the appsink callback, then cloned again whenever it was handed
+// to a writer or encoder. Frames now carry a `FrameData`: a reference-counted
+// handle to one pooled allocation. The pre-roll buffer, the poll thread and
+// the writers share that single copy, GStreamer buffers wrap it without
+// copying, and once the last holder lets go the allocation goes back to the
+// camera's pool for the next sample.
+
+use std::ops::Deref;
+use std::sync::{Arc, Weak};
+
+use parking_lot::Mutex;
+
+/// Idle allocations kept per pool. In steady state the pre-roll buffer frees
+/// one frame for every frame it takes, so a handful covers encoder and writer
+/// queues draining in bursts.
+const MAX_IDLE_BUFFERS: usize = 16;
+
+struct PoolInner {
+    free: Mutex<Vec<Vec<u8>>>,
+}
+
+/// Recycles frame allocations for one capture pipeline. Cheap to clone; the
+/// appsink callback owns one and frames hold a weak link back to it, so frames
+/// outliving the pipeline simply free their memory.
+#[derive(Clone)]
+pub struct FramePool {
+    inner: Arc<PoolInner>,
+}
+
+impl FramePool {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                free: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Copy a mapped sample into a recycled allocation
+    pub fn copy_from(&self, bytes: &[u8]) -> FrameData {
+        let mut data = self.inner.free.lock().pop().unwrap_or_default();
+        data.clear();
+        data.extend_from_slice(bytes);
+        FrameData(Arc::new(PooledBuffer {
+            data,
+            pool: Arc::downgrade(&self.inner),
+        }))
+    }
+}
+
+impl Default for FramePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct PooledBuffer {
+    data: Vec<u8>,
+    pool: Weak<PoolInner>,
+}
+
+impl Drop for PooledBuffer {
+    fn drop(&mut self) {
+        let Some(pool) = self.pool.upgrade() else { return };
+        let mut free = pool.free.lock();
+        if free.len() < MAX_IDLE_BUFFERS {
+            free.push(std::mem::take(&mut self.data));
+        }
+    }
+}
+
+/// Shared, immutable frame bytes. Cloning bumps a reference count; the bytes
+/// are never copied. Implements `AsRef<[u8]>` so it can back a GStreamer
+/// buffer directly via `gst::Buffer::from_slice`.
+#[derive(Clone)]
+pub struct FrameData(Arc<PooledBuffer>);
+
+impl Deref for FrameData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0.data
+    }
+}
+
+impl AsRef<[u8]> for FrameData {
+    fn as_ref(&self) -> &[u8] {
+        &self.0.data
+    }
+}
@@ -0,0 +1,320 @@
+// Loopback latency measurement
+//
+// Emits a test event and times how long it takes to come back in on an
+// audio input:
+//
+// - Audio loopback: a click is played on an output that is cabled (or
+//   audible) to the input. The click's playback moment comes from the
+//   output's reported latency, so what is measured is the input's own
+//   latency; the full round trip is reported alongside it.
+// - MIDI to audio: a note is sent to a MIDI port whose synth feeds the
+//   input. This measures how far the input's audio lags the MIDI, which is
+//   exactly the correction `start_recording` needs (plus the synth's own
+//   response time, usually a few milliseconds).
+//
+// Several trials are run and the median is used. Opens its own streams, so
+// it works whether or not the devices are currently selected.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Trials per measurement
+const TRIALS: usize = 5;
+
+/// Longest wait for the event to come back
+const TRIAL_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Quiet time between trials, so the previous click has died away
+const TRIAL_GAP: Duration = Duration::from_millis(400);
+
+/// Input listened to before the first trial to set the detection threshold
+const NOISE_WINDOW: Duration = Duration::from_millis(300);
+
+/// The event must be this many times louder than the background peak
+const NOISE_MARGIN: f32 = 4.0;
+
+/// Lowest detection threshold (about -34 dBFS)
+const MIN_THRESHOLD: f32 = 0.02;
+
+/// Click length and pitch
+const CLICK_SECS: f64 = 0.005;
+const CLICK_HZ: f64 = 1000.0;
+
+/// MIDI test note (middle C, full velocity) and how long it is held
+const TEST_NOTE: u8 = 60;
+const TEST_NOTE_HOLD: Duration = Duration::from_millis(150);
+
+/// What emits the test event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LatencySource {
+    /// A click on an audio output (None = system default output)
+    AudioOutput { device: Option<String> },
+    /// A note on a MIDI output port
+    MidiOutput { port: String },
+}
+
+/// Result of a latency measurement on one input
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencyReport {
+    pub input_device: String,
+    pub source: LatencySource,
+    /// Per-trial latency in ms (None where nothing was detected)
+    pub trials: Vec<Option<f64>>,
+    /// Median latency of the input relative to the emitted event
+    pub latency_ms: Option<f64>,
+    /// Median time from handing the click to the output to hearing it back
+    /// (audio loopback only)
+    pub round_trip_ms: Option<f64>,
+    /// Spread between the fastest and slowest detected trial
+    pub jitter_ms: Option<f64>,
+    /// Correction to use for this input when recording
+    pub suggested_offset_ms: Option<i32>,
+    /// Whether the suggestion was saved to the config
+    pub applied: bool,
+}
+
+/// Shared between the test driver and the stream callbacks
+#[derive(Default)]
+struct Probe {
+    /// Largest sample seen while listening to the background
+    noise_peak: f32,
+    listening_for_noise: bool,
+    threshold: f32,
+    /// Output callback should write a click
+    click_requested: bool,
+    /// When the click was handed to the output
+    written_at: Option<Instant>,
+    /// When the event left the output (or was sent over MIDI)
+    emitted_at: Option<Instant>,
+    /// When the event was captured by the input
+    detected_at: Option<Instant>,
+}
+
+/// Run a latency measurement on `input_device`
+pub fn measure_latency(input_device: &str, source: LatencySource) -> anyhow::Result<LatencyReport> {
+    let host = cpal::default_host();
+    let input = host
+        .input_devices()?
+        .find(|d| d.name().map(|n| n == input_device).unwrap_or(false))
+        .ok_or_else(|| anyhow::anyhow!("Audio device not found: {}", input_device))?;
+
+    let probe = Arc::new(Mutex::new(Probe {
+        listening_for_noise: true,
+        ..Default::default()
+    }));
+
+    let input_stream = build_input_probe(&input, probe.clone())?;
+    input_stream.play()?;
+
+    let mut midi_connection = None;
+    let output_stream = match &source {
+        LatencySource::AudioOutput { device } => {
+            let output = match device {
+                Some(name) => host
+                    .output_devices()?
+                    .find(|d| d.name().map(|n| &n == name).unwrap_or(false))
+                    .ok_or_else(|| anyhow::anyhow!("Audio output not found: {}", name))?,
+                None => host
+                    .default_output_device()
+                    .ok_or_else(|| anyhow::anyhow!("No default audio output"))?,
+            };
+            let stream = build_click_output(&output, probe.clone())?;
+            stream.play()?;
+            Some(stream)
+        }
+        LatencySource::MidiOutput { port } => {
+            midi_connection = Some(connect_midi_output(port)?);
+            None
+        }
+    };
+
+    std::thread::sleep(NOISE_WINDOW);
+    {
+        let mut p = probe.lock();
+        p.listening_for_noise = false;
+        p.threshold = (p.noise_peak * NOISE_MARGIN).max(MIN_THRESHOLD);
+    }
+
+    let mut trials = Vec::with_capacity(TRIALS);
+    let mut round_trips = Vec::new();
+    for _ in 0..TRIALS {
+        {
+            let mut p = probe.lock();
+            p.written_at = None;
+            p.emitted_at = None;
+            p.detected_at = None;
+        }
+
+        let trial_start = Instant::now();
+        match midi_connection.as_mut() {
+            Some(conn) => {
+                conn.send(&[0x90, TEST_NOTE, 127])?;
+                probe.lock().emitted_at = Some(trial_start);
+            }
+            None => probe.lock().click_requested = true,
+        }
+
+        let deadline = Instant::now() + TRIAL_TIMEOUT;
+        while Instant::now() < deadline && probe.lock().detected_at.is_none() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        if let Some(conn) = midi_connection.as_mut() {
+            std::thread::sleep(TEST_NOTE_HOLD.saturating_sub(trial_start.elapsed()));
+            let _ = conn.send(&[0x80, TEST_NOTE, 0]);
+        }
+
+        let p = probe.lock();
+        let latency = match (p.emitted_at, p.detected_at) {
+            (Some(emitted), Some(detected)) => Some(signed_ms(detected, emitted)),
+            _ => None,
+        };
+        if let (Some(written), Some(detected)) = (p.written_at, p.detected_at) {
+            round_trips.push(signed_ms(detected, written));
+        }
+        trials.push(latency);
+        drop(p);
+
+        std::thread::sleep(TRIAL_GAP);
+    }
+
+    drop(output_stream);
+    drop(input_stream);
+
+    let mut detected: Vec<f64> = trials.iter().flatten().copied().collect();
+    detected.sort_by(|a, b| a.total_cmp(b));
+    round_trips.sort_by(|a, b| a.total_cmp(b));
+
+    let latency_ms = median(&detected);
+    let report = LatencyReport {
+        input_device: input_device.to_string(),
+        source,
+        jitter_ms: (detected.len() > 1).then(|| detected[detected.len() - 1] - detected[0]),
+        latency_ms,
+        round_trip_ms: median(&round_trips),
+        suggested_offset_ms: latency_ms.map(|ms| ms.round() as i32),
+        trials,
+        applied: false,
+    };
+    println!(
+        "[Latency] {}: {}/{} trials detected, latency {:?} ms, round trip {:?} ms",
+        input_device,
+        detected.len(),
+        TRIALS,
+        report.latency_ms,
+        report.round_trip_ms
+    );
+    Ok(report)
+}
+
+fn median(sorted: &[f64]) -> Option<f64> {
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(sorted[n / 2]),
+        n => Some((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0),
+    }
+}
+
+/// `later - earlier` in milliseconds, negative if `later` is earlier
+fn signed_ms(later: Instant, earlier: Instant) -> f64 {
+    match later.checked_duration_since(earlier) {
+        Some(d) => d.as_secs_f64() * 1000.0,
+        None => -(earlier.duration_since(later).as_secs_f64() * 1000.0),
+    }
+}
+
+/// Input stream that records the background level, then the capture time of
+/// the first sample over the threshold after an event was emitted
+fn build_input_probe(device: &cpal::Device, probe: Arc<Mutex<Probe>>) -> anyhow::Result<cpal::Stream> {
+    let supported_config = device.default_input_config()?;
+    let channels = supported_config.channels() as usize;
+    let sample_rate = supported_config.sample_rate().0 as f64;
+
+    let stream = device.build_input_stream(
+        &supported_config.into(),
+        move |data: &[f32], _: &cpal::InputCallbackInfo| {
+            let arrived_at = Instant::now();
+            let mut p = probe.lock();
+            if p.listening_for_noise {
+                let peak = data.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+                p.noise_peak = p.noise_peak.max(peak);
+                return;
+            }
+            let Some(emitted_at) = p.emitted_at else { return };
+            if p.detected_at.is_some() {
+                return;
+            }
+
+            let frames = data.len() / channels.max(1);
+            let buffer_secs = frames as f64 / sample_rate;
+            let buffer_start = arrived_at
+                .checked_sub(Duration::from_secs_f64(buffer_secs))
+                .unwrap_or(arrived_at);
+            for (i, frame) in data.chunks(channels.max(1)).enumerate() {
+                if frame.iter().any(|s| s.abs() >= p.threshold) {
+                    let captured_at = buffer_start + Duration::from_secs_f64(i as f64 / sample_rate);
+                    // Sound from before the event is background, not the event
+                    if captured_at >= emitted_at {
+                        p.detected_at = Some(captured_at);
+                    }
+                    break;
+                }
+            }
+        },
+        |err| println!("[Latency] Input error: {}", err),
+        None,
+    )?;
+    Ok(stream)
+}
+
+/// Output stream that plays silence, and a click when one is requested
+fn build_click_output(device: &cpal::Device, probe: Arc<Mutex<Probe>>) -> anyhow::Result<cpal::Stream> {
+    let supported_config = device.default_output_config()?;
+    let channels = supported_config.channels() as usize;
+    let sample_rate = supported_config.sample_rate().0 as f64;
+    let click_frames = (CLICK_SECS * sample_rate) as usize;
+
+    let stream = device.build_output_stream(
+        &supported_config.into(),
+        move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+            data.fill(0.0);
+            let mut p = probe.lock();
+            if !p.click_requested {
+                return;
+            }
+            p.click_requested = false;
+
+            for (i, frame) in data.chunks_mut(channels.max(1)).take(click_frames).enumerate() {
+                let sample = (2.0 * std::f64::consts::PI * CLICK_HZ * i as f64 / sample_rate).sin() as f32;
+                frame.fill(sample * 0.9);
+            }
+
+            // The click reaches the speaker/cable when this buffer is played
+            let now = Instant::now();
+            let timestamp = info.timestamp();
+            let output_latency = timestamp.playback.duration_since(&timestamp.callback).unwrap_or_default();
+            p.written_at = Some(now);
+            p.emitted_at = Some(now + output_latency);
+        },
+        |err| println!("[Latency] Output error: {}", err),
+        None,
+    )?;
+    Ok(stream)
+}
+
+fn connect_midi_output(port_name: &str) -> anyhow::Result<midir::MidiOutputConnection> {
+    let midi_out = midir::MidiOutput::new("sacho-latency")?;
+    let port = midi_out
+        .ports()
+        .into_iter()
+        .find(|p| midi_out.port_name(p).ok().as_deref() == Some(port_name))
+        .ok_or_else(|| anyhow::anyhow!("MIDI output not found: {}", port_name))?;
+    midi_out
+        .connect(&port, "sacho-latency")
+        .map_err(|e| anyhow::anyhow!("Failed to open MIDI output {}: {}", port_name, e))
+}
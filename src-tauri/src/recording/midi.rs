@@ -6,6 +6,8 @@ use parking_lot::Mutex;
 use std::path::PathBuf;
 use chrono::{DateTime, Utc};
 
+use crate::session::ChannelProgram;
+
 /// MIDI event with timestamp
 #[derive(Debug, Clone)]
 pub struct TimestampedMidiEvent {
@@ -13,6 +15,49 @@ pub struct TimestampedMidiEvent {
     pub data: Vec<u8>,
 }
 
+/// Last bank select and program change seen on each channel of a device
+#[derive(Debug, Clone, Default)]
+pub struct MidiProgramTracker {
+    channels: [ChannelSelection; 16],
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ChannelSelection {
+    bank_msb: Option<u8>,
+    bank_lsb: Option<u8>,
+    program: Option<u8>,
+}
+
+impl MidiProgramTracker {
+    /// Update from a raw MIDI message; anything but bank select and program change is ignored
+    pub fn observe(&mut self, data: &[u8]) {
+        let Some(&status) = data.first() else { return };
+        let channel = &mut self.channels[(status & 0x0F) as usize];
+        match (status & 0xF0, data.get(1), data.get(2)) {
+            (0xB0, Some(0), Some(&value)) => channel.bank_msb = Some(value),
+            (0xB0, Some(32), Some(&value)) => channel.bank_lsb = Some(value),
+            (0xC0, Some(&program), _) => channel.program = Some(program),
+            _ => {}
+        }
+    }
+
+    /// Channels that have had a program change
+    pub fn snapshot(&self) -> Vec<ChannelProgram> {
+        self.channels
+            .iter()
+            .enumerate()
+            .filter_map(|(channel, sel)| {
+                sel.program.map(|program| ChannelProgram {
+                    channel: channel as u8,
+                    bank_msb: sel.bank_msb,
+                    bank_lsb: sel.bank_lsb,
+                    program,
+                })
+            })
+            .collect()
+    }
+}
+
 /// MIDI capture configuration
 #[derive(Debug, Clone)]
 pub struct MidiCaptureConfig {
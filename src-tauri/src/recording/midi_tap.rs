@@ -0,0 +1,326 @@
+// Live MIDI event tap for the inspector panel
+//
+// Lets users confirm their keyboard actually reaches Sacho without recording
+// a test session. The monitor's MIDI callbacks hand every message to
+// `observe`, which is a single uncontended read lock while no tap is running.
+// Decoding, filtering and event emission happen on the tap's own thread, and
+// events are batched (at most ~30 emits per second) so a flood of clock or
+// pitch-bend messages can't swamp the frontend.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+/// Messages buffered between the MIDI callbacks and the tap thread. Beyond
+/// this, messages are dropped (the tap is for inspection, not recording).
+const TAP_CHANNEL_CAPACITY: usize = 4096;
+
+/// How often batched events are emitted
+const EMIT_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Most events per batch; the rest of a burst is counted as dropped
+const MAX_EVENTS_PER_BATCH: usize = 256;
+
+/// The running tap, if any
+static TAP: RwLock<Option<MidiEventTap>> = RwLock::new(None);
+
+/// Kind of a decoded MIDI message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MidiEventKind {
+    NoteOn,
+    NoteOff,
+    PolyAftertouch,
+    ControlChange,
+    ProgramChange,
+    ChannelAftertouch,
+    PitchBend,
+    SysEx,
+    /// Clock, start/stop/continue, active sensing, etc.
+    Realtime,
+    /// Other system common messages (MTC, song position, ...)
+    System,
+}
+
+/// Which events the inspector wants to see. Empty lists mean "all".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MidiTapFilter {
+    /// MIDI port names
+    pub devices: Vec<String>,
+    /// Channels 1-16
+    pub channels: Vec<u8>,
+    pub kinds: Vec<MidiEventKind>,
+    /// Include realtime messages (clock ticks arrive 24x per beat, so they
+    /// are hidden unless asked for)
+    pub include_realtime: bool,
+}
+
+impl MidiTapFilter {
+    fn matches(&self, event: &MidiTapEvent) -> bool {
+        if event.kind == MidiEventKind::Realtime && !self.include_realtime {
+            return false;
+        }
+        if !self.devices.is_empty() && !self.devices.contains(&event.device) {
+            return false;
+        }
+        if !self.channels.is_empty() {
+            match event.channel {
+                Some(ch) if self.channels.contains(&ch) => {}
+                _ => return false,
+            }
+        }
+        self.kinds.is_empty() || self.kinds.contains(&event.kind)
+    }
+}
+
+/// A decoded MIDI message, sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct MidiTapEvent {
+    /// Port name the message arrived on
+    pub device: String,
+    /// Driver timestamp in microseconds
+    pub timestamp_us: u64,
+    pub kind: MidiEventKind,
+    /// 1-16 for channel messages
+    pub channel: Option<u8>,
+    /// Note number (notes, poly aftertouch) or controller number (CC)
+    pub number: Option<u8>,
+    /// Velocity, pressure, controller value or program; 0-16383 for pitch bend
+    pub value: Option<u16>,
+    /// Human-readable summary, e.g. "Note On C4 vel 96" or "CC 64 Sustain = 127"
+    pub description: String,
+    pub raw: Vec<u8>,
+}
+
+/// Event payload for `midi-tap-events`
+#[derive(Clone, Serialize)]
+struct MidiTapBatch {
+    events: Vec<MidiTapEvent>,
+    /// Messages dropped since the previous batch (channel full or batch cap)
+    dropped: u64,
+}
+
+struct RawMidiMessage {
+    device: String,
+    timestamp_us: u64,
+    data: Vec<u8>,
+}
+
+struct MidiEventTap {
+    sender: Sender<RawMidiMessage>,
+    dropped: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+}
+
+/// Start (or restart with a new filter) the event tap
+pub fn start(app: AppHandle, filter: MidiTapFilter) {
+    stop();
+
+    let (sender, receiver) = crossbeam_channel::bounded(TAP_CHANNEL_CAPACITY);
+    let dropped = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    {
+        let dropped = dropped.clone();
+        let stop = stop.clone();
+        std::thread::Builder::new()
+            .name("sacho-midi-tap".into())
+            .spawn(move || tap_loop(app, filter, receiver, dropped, stop))
+            .expect("Failed to spawn MIDI tap thread");
+    }
+
+    *TAP.write() = Some(MidiEventTap { sender, dropped, stop });
+    println!("[Sacho] MIDI event tap started");
+}
+
+/// Stop the event tap (no-op if it isn't running)
+pub fn stop() {
+    if let Some(tap) = TAP.write().take() {
+        tap.stop.store(true, Ordering::Relaxed);
+        println!("[Sacho] MIDI event tap stopped");
+    }
+}
+
+pub fn is_running() -> bool {
+    TAP.read().is_some()
+}
+
+/// Called from the MIDI input callbacks for every message. Never blocks.
+pub fn observe(device: &str, timestamp_us: u64, data: &[u8]) {
+    let tap = TAP.read();
+    let Some(tap) = tap.as_ref() else { return };
+    let message = RawMidiMessage {
+        device: device.to_string(),
+        timestamp_us,
+        data: data.to_vec(),
+    };
+    if tap.sender.try_send(message).is_err() {
+        tap.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn tap_loop(
+    app: AppHandle,
+    filter: MidiTapFilter,
+    receiver: Receiver<RawMidiMessage>,
+    dropped: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+) {
+    let mut batch: Vec<MidiTapEvent> = Vec::new();
+    let mut last_emit = Instant::now();
+
+    while !stop.load(Ordering::Relaxed) {
+        match receiver.recv_timeout(EMIT_INTERVAL) {
+            Ok(message) => {
+                let event = decode(message.device, message.timestamp_us, &message.data);
+                if filter.matches(&event) {
+                    if batch.len() < MAX_EVENTS_PER_BATCH {
+                        batch.push(event);
+                    } else {
+                        dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if last_emit.elapsed() >= EMIT_INTERVAL {
+            let dropped_count = dropped.swap(0, Ordering::Relaxed);
+            if !batch.is_empty() || dropped_count > 0 {
+                let _ = app.emit(
+                    "midi-tap-events",
+                    MidiTapBatch {
+                        events: std::mem::take(&mut batch),
+                        dropped: dropped_count,
+                    },
+                );
+            }
+            last_emit = Instant::now();
+        }
+    }
+}
+
+/// Decode one raw MIDI message
+pub fn decode(device: String, timestamp_us: u64, data: &[u8]) -> MidiTapEvent {
+    let status = data.first().copied().unwrap_or(0);
+    let d1 = data.get(1).copied().unwrap_or(0);
+    let d2 = data.get(2).copied().unwrap_or(0);
+    let channel = (status < 0xF0).then_some((status & 0x0F) + 1);
+
+    let (kind, number, value, description) = match status & 0xF0 {
+        // Note on with velocity 0 is a note off by convention
+        0x90 if d2 > 0 => (
+            MidiEventKind::NoteOn,
+            Some(d1),
+            Some(d2 as u16),
+            format!("Note On {} vel {}", note_name(d1), d2),
+        ),
+        0x80 | 0x90 => (
+            MidiEventKind::NoteOff,
+            Some(d1),
+            Some(d2 as u16),
+            format!("Note Off {}", note_name(d1)),
+        ),
+        0xA0 => (
+            MidiEventKind::PolyAftertouch,
+            Some(d1),
+            Some(d2 as u16),
+            format!("Aftertouch {} = {}", note_name(d1), d2),
+        ),
+        0xB0 => {
+            let description = match controller_name(d1) {
+                Some(name) => format!("CC {} {} = {}", d1, name, d2),
+                None => format!("CC {} = {}", d1, d2),
+            };
+            (MidiEventKind::ControlChange, Some(d1), Some(d2 as u16), description)
+        }
+        0xC0 => (
+            MidiEventKind::ProgramChange,
+            None,
+            Some(d1 as u16),
+            format!("Program {}", d1 as u16 + 1),
+        ),
+        0xD0 => (
+            MidiEventKind::ChannelAftertouch,
+            None,
+            Some(d1 as u16),
+            format!("Channel Pressure = {}", d1),
+        ),
+        0xE0 => {
+            let bend = ((d2 as u16) << 7) | d1 as u16;
+            (
+                MidiEventKind::PitchBend,
+                None,
+                Some(bend),
+                format!("Pitch Bend {:+}", bend as i32 - 8192),
+            )
+        }
+        _ => match status {
+            0xF0 => (
+                MidiEventKind::SysEx,
+                None,
+                None,
+                format!("SysEx ({} bytes)", data.len()),
+            ),
+            0xF8 => (MidiEventKind::Realtime, None, None, "Clock".to_string()),
+            0xFA => (MidiEventKind::Realtime, None, None, "Start".to_string()),
+            0xFB => (MidiEventKind::Realtime, None, None, "Continue".to_string()),
+            0xFC => (MidiEventKind::Realtime, None, None, "Stop".to_string()),
+            0xFE => (MidiEventKind::Realtime, None, None, "Active Sensing".to_string()),
+            0xFF => (MidiEventKind::Realtime, None, None, "Reset".to_string()),
+            _ => (
+                MidiEventKind::System,
+                None,
+                None,
+                format!("System 0x{:02X}", status),
+            ),
+        },
+    };
+
+    MidiTapEvent {
+        device,
+        timestamp_us,
+        kind,
+        channel,
+        number,
+        value,
+        description,
+        raw: data.to_vec(),
+    }
+}
+
+/// Note name with octave, middle C (60) = C4
+pub fn note_name(note: u8) -> String {
+    const NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+    let octave = note as i32 / 12 - 1;
+    format!("{}{}", NAMES[note as usize % 12], octave)
+}
+
+/// Names of the common controllers
+fn controller_name(cc: u8) -> Option<&'static str> {
+    Some(match cc {
+        0 => "Bank Select",
+        1 => "Modulation",
+        2 => "Breath",
+        4 => "Foot",
+        7 => "Volume",
+        10 => "Pan",
+        11 => "Expression",
+        32 => "Bank Select LSB",
+        64 => "Sustain",
+        65 => "Portamento",
+        66 => "Sostenuto",
+        67 => "Soft Pedal",
+        120 => "All Sound Off",
+        121 => "Reset Controllers",
+        123 => "All Notes Off",
+        _ => return None,
+    })
+}
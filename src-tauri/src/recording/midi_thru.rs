@@ -0,0 +1,124 @@
+// MIDI thru routing while monitoring
+//
+// A controller without its own sound needs a software instrument to be
+// heard, but a separate thru utility and Sacho end up fighting over the
+// input port (WinMM ports can only be opened once). Instead, Sacho forwards
+// the chosen input to an output port itself, straight from the MIDI callback
+// so the synth sees the note before the capture state is even locked.
+//
+// The same forwarding backs the "virtual tap" access mode: Sacho holds a
+// single-client port and republishes it on a virtual port for the DAW.
+
+use midir::{MidiOutput, MidiOutputConnection};
+use parking_lot::Mutex;
+
+use crate::config::MidiThruConfig;
+
+/// An open route from one input device to an output (or virtual) port
+pub struct MidiThru {
+    output_port: String,
+    connection: Mutex<MidiOutputConnection>,
+    /// Bit n set = channel n (0-based) is forwarded
+    channel_mask: u16,
+}
+
+impl MidiThru {
+    /// Open the configured output port. Returns None if thru is disabled,
+    /// unconfigured or the port can't be opened.
+    pub fn connect(config: &MidiThruConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let output_port = config.output_port.as_ref()?;
+        config.input_device.as_ref()?;
+
+        let midi_out = match MidiOutput::new("sacho-thru") {
+            Ok(m) => m,
+            Err(e) => {
+                println!("[Sacho] Failed to create MIDI output for thru: {}", e);
+                return None;
+            }
+        };
+        let port = midi_out
+            .ports()
+            .into_iter()
+            .find(|p| midi_out.port_name(p).ok().as_deref() == Some(output_port.as_str()));
+        let Some(port) = port else {
+            println!("[Sacho] MIDI thru output {} not found", output_port);
+            return None;
+        };
+        let connection = match midi_out.connect(&port, "sacho-thru") {
+            Ok(conn) => conn,
+            Err(e) => {
+                println!("[Sacho] Failed to connect MIDI thru output {}: {}", output_port, e);
+                return None;
+            }
+        };
+        println!("[Sacho] MIDI thru: {:?} -> {}", config.input_device, output_port);
+
+        Some(Self {
+            output_port: output_port.clone(),
+            connection: Mutex::new(connection),
+            channel_mask: channel_mask(&config.channels),
+        })
+    }
+
+    /// Create a virtual output port republishing everything from `device_name`.
+    /// Only CoreMIDI and ALSA can create virtual ports.
+    #[cfg(unix)]
+    pub fn virtual_port(device_name: &str) -> Option<Self> {
+        use midir::os::unix::VirtualOutput;
+
+        let port_name = format!("{} (Sacho)", device_name);
+        let midi_out = match MidiOutput::new("sacho-tap") {
+            Ok(m) => m,
+            Err(e) => {
+                println!("[Sacho] Failed to create MIDI output for virtual tap: {}", e);
+                return None;
+            }
+        };
+        match midi_out.create_virtual(&port_name) {
+            Ok(connection) => {
+                println!("[Sacho] Virtual tap: {} -> {}", device_name, port_name);
+                Some(Self {
+                    output_port: port_name,
+                    connection: Mutex::new(connection),
+                    channel_mask: u16::MAX,
+                })
+            }
+            Err(e) => {
+                println!("[Sacho] Failed to create virtual port {}: {}", port_name, e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn virtual_port(device_name: &str) -> Option<Self> {
+        println!("[Sacho] Virtual MIDI ports aren't supported on this platform ({})", device_name);
+        None
+    }
+
+    /// Forward a message if it passes the channel filter. System messages
+    /// (SysEx, clock, transport) carry no channel and always pass.
+    pub fn forward(&self, message: &[u8]) {
+        let Some(&status) = message.first() else { return };
+        if (0x80..0xF0).contains(&status) && self.channel_mask & (1 << (status & 0x0F)) == 0 {
+            return;
+        }
+        if let Err(e) = self.connection.lock().send(message) {
+            println!("[Sacho] MIDI thru to {} failed: {}", self.output_port, e);
+        }
+    }
+}
+
+/// Channels to forward as a bit mask; an empty list forwards every channel
+fn channel_mask(channels: &[u8]) -> u16 {
+    if channels.is_empty() {
+        return u16::MAX;
+    }
+    channels
+        .iter()
+        .filter(|&&c| c < 16)
+        .fold(0, |mask, &c| mask | (1 << c))
+}
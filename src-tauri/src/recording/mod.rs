@@ -1,9 +1,28 @@
 // Recording modules
 
+pub mod blank_frames;
+pub mod calibration;
+pub mod clock;
+pub mod feedback;
+pub mod frame_pool;
+pub mod latency;
 pub mod midi;
+pub mod midi_tap;
+pub mod midi_thru;
 pub mod monitor;
+pub mod noise_floor;
+pub mod playback_guard;
+pub mod preflight;
 pub mod preroll;
+pub mod processing;
+pub mod quality;
+pub mod sample_rate;
+pub mod system_load;
+pub mod tempo;
+pub mod trigger_log;
 pub mod video;
+pub mod wav;
+pub mod virtual_camera;
 
 pub use monitor::MidiMonitor;
 pub use preroll::{MidiPrerollBuffer, AudioPrerollBuffer};
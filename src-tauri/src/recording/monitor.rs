@@ -1,9 +1,9 @@
 // MIDI monitoring service that triggers automatic recording
 
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::io::{Write, Seek, SeekFrom};
 use std::collections::HashMap;
 use parking_lot::{RwLock, Mutex};
@@ -11,41 +11,92 @@ use midir::{MidiInput, MidiInputConnection};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use tauri::{AppHandle, Manager, Emitter};
 
-use crate::config::Config;
+use crate::config::{Config, MidiAccessMode};
+use crate::encoding::stream_out::{RtmpStreamer, StreamAudioSettings, StreamSettings, StreamState, StreamStatus, StreamTap};
 use crate::devices::DeviceManager;
 use crate::recording::RecordingState;
+use crate::recording::clock::{self, SharedClock};
+use crate::recording::feedback::MidiFeedback;
 use crate::recording::midi::TimestampedMidiEvent;
-use crate::recording::preroll::{MidiPrerollBuffer, AudioPrerollBuffer, MAX_PRE_ROLL_SECS, MAX_PRE_ROLL_SECS_ENCODED};
+use crate::recording::midi_tap;
+use crate::recording::midi_thru::MidiThru;
+use crate::recording::preroll::{MidiPrerollBuffer, AudioPrerollBuffer, MAX_AUDIO_MIDI_PRE_ROLL_SECS};
+use crate::recording::processing::AudioProcessor;
+use crate::recording::quality::{AudioQualityMeter, DropoutDetector};
+use crate::recording::playback_guard;
+use crate::recording::trigger_log::{self, TriggerCause, TriggerOutcome, TriggerSource};
 use crate::recording::video::VideoCaptureManager;
 use crate::session::{SessionMetadata, SessionDatabase, MidiFileInfo, AudioFileInfo};
 use crate::notifications;
 
+/// Load the GStreamer plugins behind `AudioStreamWriter` while armed. The
+/// first writer otherwise loads them from disk at the trigger, on the
+/// recording's critical path.
+fn preload_audio_writer_plugins(audio_format: &crate::config::AudioFormat) {
+    use gstreamer as gst;
+    use gstreamer::prelude::*;
+
+    let mut names = vec!["appsrc", "audioconvert", "audioresample", "capsfilter"];
+    match audio_format {
+        crate::config::AudioFormat::Flac => names.extend(["flacenc", "filesink"]),
+        crate::config::AudioFormat::Wav => names.push("appsink"),
+    }
+    for name in names {
+        let loaded = gst::ElementFactory::find(name).and_then(|factory| factory.load().ok());
+        if loaded.is_none() {
+            println!("[Sacho] Could not preload GStreamer element {}", name);
+        }
+    }
+}
+
 /// Streaming audio writer that pipes samples to disk via GStreamer.
-/// Pipeline: appsrc(F32LE) ! audioconvert ! audioresample ! capsfilter ! encoder(flacenc/wavenc) ! filesink
+/// FLAC: appsrc(F32LE) ! audioconvert ! audioresample ! capsfilter ! flacenc ! filesink
+/// WAV:  appsrc(F32LE) ! audioconvert ! audioresample ! capsfilter ! appsink -> WavFileWriter
 pub struct AudioStreamWriter {
     pipeline: gstreamer::Pipeline,
     appsrc: gstreamer_app::AppSrc,
+    /// WAV file fed by the appsink (None for FLAC)
+    wav: Option<Arc<Mutex<Option<crate::recording::wav::WavFileWriter>>>>,
+    /// Wall-clock time of the first sample, for the WAV origination time
+    first_sample_at: Option<chrono::DateTime<chrono::Local>>,
     file_path: PathBuf,
     filename: String,
     device_name: String,
+    /// Channels written to the file
     channels: u16,
+    /// Channels in the interleaved input from the device
+    source_channels: u16,
+    /// Input channels extracted for this file (None = all, in order)
+    channel_selection: Option<Vec<u16>>,
     /// Native input sample rate from cpal
     native_rate: u32,
+    /// Sample rate written to the file
+    output_rate: u32,
+    /// Clipping, peak and DC offset of the recorded signal, plus dropouts
+    quality: AudioQualityMeter,
     /// Total frames pushed (for PTS / duration calculation)
     frames_pushed: u64,
+    /// When the first samples reached the pipeline, for startup latency
+    first_write_at: Option<Instant>,
+    /// Where the file starts on the session timeline (pre-roll came up short)
+    start_offset_secs: f64,
 }
 
 impl AudioStreamWriter {
     /// Create and start a new streaming audio writer.
+    ///
+    /// `channel_selection` picks input channels for a stem file (one for
+    /// mono, two for a stereo pair); None records every channel.
     pub fn new(
         session_path: &PathBuf,
         filename: &str,
         device_name: &str,
-        channels: u16,
+        source_channels: u16,
+        channel_selection: Option<&[u16]>,
         native_rate: u32,
         audio_format: &crate::config::AudioFormat,
         bit_depth: &crate::config::AudioBitDepth,
-        sample_rate_setting: &crate::config::AudioSampleRate,
+        output_rate: u32,
     ) -> anyhow::Result<Self> {
         use gstreamer as gst;
         use gstreamer::prelude::*;
@@ -53,8 +104,9 @@ impl AudioStreamWriter {
         use gstreamer_audio as gst_audio;
         
         let file_path = session_path.join(filename);
-        let output_rate = sample_rate_setting.target_rate().unwrap_or(native_rate);
-        
+        let channels = channel_selection
+            .map(|selection| selection.len() as u16)
+            .unwrap_or(source_channels);
         // Input caps: F32LE at the device's native rate
         let input_info = gst_audio::AudioInfo::builder(gst_audio::AudioFormat::F32le, native_rate, channels as u32)
             .build()
@@ -100,35 +152,87 @@ impl AudioStreamWriter {
             .build()
             .map_err(|_| anyhow::anyhow!("Failed to create capsfilter element"))?;
         
-        // Encoder: flacenc or wavenc
+        let media_tags = crate::encoding::tags::MediaTags::new(session_path, device_name);
+        let mut wav = None;
         let encoder_name = match audio_format {
-            crate::config::AudioFormat::Flac => "flacenc",
-            crate::config::AudioFormat::Wav => "wavenc",
+            crate::config::AudioFormat::Flac => {
+                let encoder = gst::ElementFactory::make("flacenc")
+                    .name("encoder")
+                    .build()
+                    .map_err(|_| anyhow::anyhow!("Failed to create flacenc element"))?;
+                
+                // For 32-bit FLAC, disable the Subset restriction (Subset limits to 24-bit max)
+                if matches!(bit_depth, crate::config::AudioBitDepth::Float32) {
+                    encoder.set_property("streamable-subset", false);
+                }
+                
+                let filesink = gst::ElementFactory::make("filesink")
+                    .name("sink")
+                    .property("location", file_path.to_str().unwrap_or("output"))
+                    .build()
+                    .map_err(|_| anyhow::anyhow!("Failed to create filesink element"))?;
+                
+                // Assemble and link
+                pipeline.add_many([appsrc.upcast_ref(), &audioconvert, &audioresample, &capsfilter, &encoder, &filesink])
+                    .map_err(|e| anyhow::anyhow!("Failed to add elements to pipeline: {}", e))?;
+                
+                gst::Element::link_many([appsrc.upcast_ref(), &audioconvert, &audioresample, &capsfilter, &encoder, &filesink])
+                    .map_err(|e| anyhow::anyhow!("Failed to link pipeline elements: {}", e))?;
+                
+                // Provenance tags (FLAC writes them with the header, so before the first buffer)
+                crate::encoding::tags::apply_to_pipeline(&pipeline, &media_tags.to_tag_list());
+                "flacenc"
+            }
+            crate::config::AudioFormat::Wav => {
+                // wavenc's 32-bit sizes can't describe files past 4 GB, so
+                // the converted samples are written by WavFileWriter instead
+                let format = crate::recording::wav::WavFormat {
+                    channels,
+                    sample_rate: output_rate,
+                    bits_per_sample: match bit_depth {
+                        crate::config::AudioBitDepth::Int16 => 16,
+                        crate::config::AudioBitDepth::Int24 => 24,
+                        crate::config::AudioBitDepth::Float32 => 32,
+                    },
+                    float: matches!(bit_depth, crate::config::AudioBitDepth::Float32),
+                };
+                let writer = crate::recording::wav::WavFileWriter::create(&file_path, format, media_tags)
+                    .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", filename, e))?;
+                let writer = Arc::new(Mutex::new(Some(writer)));
+                
+                let appsink = gst_app::AppSink::builder()
+                    .name("sink")
+                    .sync(false)
+                    .build();
+                let sink_writer = writer.clone();
+                let sink_name = filename.to_string();
+                appsink.set_callbacks(
+                    gst_app::AppSinkCallbacks::builder()
+                        .new_sample(move |sink| {
+                            let sample = sink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                            let buffer = sample.buffer().ok_or(gst::FlowError::Error)?;
+                            let map = buffer.map_readable().map_err(|_| gst::FlowError::Error)?;
+                            if let Some(writer) = sink_writer.lock().as_mut() {
+                                if let Err(e) = writer.write(map.as_slice()) {
+                                    println!("[Sacho] WAV write error for {}: {}", sink_name, e);
+                                    return Err(gst::FlowError::Error);
+                                }
+                            }
+                            Ok(gst::FlowSuccess::Ok)
+                        })
+                        .build(),
+                );
+                
+                pipeline.add_many([appsrc.upcast_ref(), &audioconvert, &audioresample, &capsfilter, appsink.upcast_ref()])
+                    .map_err(|e| anyhow::anyhow!("Failed to add elements to pipeline: {}", e))?;
+                
+                gst::Element::link_many([appsrc.upcast_ref(), &audioconvert, &audioresample, &capsfilter, appsink.upcast_ref()])
+                    .map_err(|e| anyhow::anyhow!("Failed to link pipeline elements: {}", e))?;
+                
+                wav = Some(writer);
+                "wav"
+            }
         };
-        let encoder = gst::ElementFactory::make(encoder_name)
-            .name("encoder")
-            .build()
-            .map_err(|_| anyhow::anyhow!("Failed to create {} element", encoder_name))?;
-        
-        // For 32-bit FLAC, disable the Subset restriction (Subset limits to 24-bit max)
-        if matches!(audio_format, crate::config::AudioFormat::Flac)
-            && matches!(bit_depth, crate::config::AudioBitDepth::Float32)
-        {
-            encoder.set_property("streamable-subset", false);
-        }
-        
-        let filesink = gst::ElementFactory::make("filesink")
-            .name("sink")
-            .property("location", file_path.to_str().unwrap_or("output"))
-            .build()
-            .map_err(|_| anyhow::anyhow!("Failed to create filesink element"))?;
-        
-        // Assemble and link
-        pipeline.add_many([appsrc.upcast_ref(), &audioconvert, &audioresample, &capsfilter, &encoder, &filesink])
-            .map_err(|e| anyhow::anyhow!("Failed to add elements to pipeline: {}", e))?;
-        
-        gst::Element::link_many([appsrc.upcast_ref(), &audioconvert, &audioresample, &capsfilter, &encoder, &filesink])
-            .map_err(|e| anyhow::anyhow!("Failed to link pipeline elements: {}", e))?;
         
         // Start the pipeline
         pipeline.set_state(gst::State::Playing)
@@ -140,17 +244,79 @@ impl AudioStreamWriter {
         Ok(Self {
             pipeline,
             appsrc,
+            wav,
+            first_sample_at: None,
             file_path,
             filename: filename.to_string(),
             device_name: device_name.to_string(),
             channels,
+            source_channels,
+            channel_selection: channel_selection.map(|selection| selection.to_vec()),
+            quality: AudioQualityMeter::new(channels),
             native_rate,
+            output_rate,
             frames_pushed: 0,
+            first_write_at: None,
+            start_offset_secs: 0.0,
         })
     }
     
-    /// Push interleaved f32 samples to the pipeline.
+    /// Push interleaved f32 samples (in the device's channel layout) to the pipeline.
     pub fn push_samples(&mut self, data: &[f32]) {
+        if let Some(selection) = &self.channel_selection {
+            let source_channels = self.source_channels as usize;
+            let extracted: Vec<f32> = data
+                .chunks_exact(source_channels)
+                .flat_map(|frame| selection.iter().map(move |&ch| frame[ch as usize]))
+                .collect();
+            self.quality.process(&extracted);
+            self.push_interleaved(&extracted);
+        } else {
+            self.quality.process(data);
+            self.push_interleaved(data);
+        }
+    }
+
+    /// Fill a gap in the device's capture timeline with silence so the file
+    /// stays in step with the session clock, and log it
+    pub fn conceal_dropout(&mut self, gap: Duration) {
+        let at_secs = self.frames_pushed as f64 / self.native_rate as f64;
+        let concealed = gap.min(crate::recording::quality::MAX_CONCEALMENT);
+        let frames = (concealed.as_secs_f64() * self.native_rate as f64).round() as usize;
+        self.push_silence_frames(frames);
+        self.quality.note_dropout(at_secs, gap, concealed);
+        println!(
+            "[Sacho] Audio dropout in {} at {:.3}s: {:.1}ms gap, filled {} frames of silence",
+            self.filename, at_secs, gap.as_secs_f64() * 1000.0, frames
+        );
+    }
+
+    fn is_flac(&self) -> bool {
+        self.wav.is_none()
+    }
+
+    /// Frames written to the file, at the output rate
+    fn output_frames(&self) -> u64 {
+        self.frames_pushed * self.output_rate as u64 / self.native_rate as u64
+    }
+
+    /// Signal health so far
+    pub fn quality(&self) -> crate::session::AudioQuality {
+        self.quality.report(&self.filename, &self.device_name)
+    }
+
+    /// Audio pushed so far, for a recording checkpoint
+    pub fn progress(&self) -> crate::session::FileProgress {
+        crate::session::FileProgress {
+            filename: self.filename.clone(),
+            device_name: self.device_name.clone(),
+            duration_secs: self.frames_pushed as f64 / self.native_rate as f64,
+            event_count: 0,
+        }
+    }
+    
+    /// Push samples already in this file's channel layout.
+    fn push_interleaved(&mut self, data: &[f32]) {
         use gstreamer as gst;
         
         if data.is_empty() {
@@ -159,6 +325,15 @@ impl AudioStreamWriter {
         
         let num_frames = data.len() / self.channels as usize;
         
+        if self.first_write_at.is_none() {
+            self.first_write_at = Some(Instant::now());
+        }
+        if self.first_sample_at.is_none() {
+            // The device delivered this block as its last sample arrived
+            let block = chrono::Duration::nanoseconds(num_frames as i64 * 1_000_000_000 / self.native_rate as i64);
+            self.first_sample_at = Some(chrono::Local::now() - block);
+        }
+        
         // Calculate PTS and duration based on frames pushed so far
         let pts_ns = self.frames_pushed * 1_000_000_000 / self.native_rate as u64;
         let duration_ns = num_frames as u64 * 1_000_000_000 / self.native_rate as u64;
@@ -182,10 +357,13 @@ impl AudioStreamWriter {
     
     /// Push silence for padding (e.g., to match video duration).
     pub fn push_silence(&mut self, duration_secs: f64) {
-        let num_frames = (duration_secs * self.native_rate as f64) as usize;
+        self.push_silence_frames((duration_secs * self.native_rate as f64) as usize);
+    }
+    
+    fn push_silence_frames(&mut self, num_frames: usize) {
         let total_samples = num_frames * self.channels as usize;
         let silence = vec![0.0f32; total_samples];
-        self.push_samples(&silence);
+        self.push_interleaved(&silence);
     }
     
     /// Finalize the stream: send EOS, wait for completion, return file info.
@@ -217,6 +395,12 @@ impl AudioStreamWriter {
         
         self.pipeline.set_state(gst::State::Null).ok();
         
+        if let Some(writer) = self.wav.as_ref().and_then(|wav| wav.lock().take()) {
+            if let Err(e) = writer.finish(self.first_sample_at) {
+                println!("[Sacho] Failed to finalize {}: {}", self.filename, e);
+            }
+        }
+        
         let size = std::fs::metadata(&self.file_path)
             .map(|m| m.len())
             .unwrap_or(0);
@@ -228,6 +412,7 @@ impl AudioStreamWriter {
             filename: self.filename,
             device_name: self.device_name,
             duration_secs,
+            start_offset_secs: self.start_offset_secs,
         })
     }
 }
@@ -236,8 +421,145 @@ impl AudioStreamWriter {
 /// Writes SMF (Standard MIDI File) format 0 with one track.
 /// The MTrk length is a placeholder until finish() patches it.
 /// If the app crashes, repair_midi_file() can fix the header.
+///
+/// All file IO happens on a dedicated writer thread. The MIDI callback only
+/// pushes events onto an unbounded lock-free queue, so a slow disk (a NAS
+/// storage path, a spun-down drive) delays the file, never the callback and
+/// the timestamps it takes. A writer dropped without finish() still
+/// completes its file in the background.
 pub struct MidiStreamWriter {
-    file: std::fs::File,
+    sender: crossbeam_channel::Sender<TimestampedMidiEvent>,
+    thread: std::thread::JoinHandle<MidiFileInfo>,
+    filename: String,
+    device_name: String,
+    /// Events queued since the file was opened, for checkpoints
+    events_queued: usize,
+    /// Events queued but not yet written by the writer thread
+    backlog: Arc<AtomicUsize>,
+    /// Whether the falling-behind warning has been logged for this file
+    backlog_warned: bool,
+    /// Bank/program selections written at tick 0
+    programs: Vec<crate::session::ChannelProgram>,
+}
+
+impl MidiStreamWriter {
+    /// Queued events at which the writer counts as falling behind the disk
+    const BACKLOG_WARNING: usize = 10_000;
+
+    /// Create the file and write its header, then start the writer thread.
+    /// Fails up front if the file can't be created, before any event is queued.
+    pub fn new(
+        session_path: &PathBuf,
+        filename: &str,
+        device_name: &str,
+        file_config: &crate::config::MidiFileConfig,
+    ) -> anyhow::Result<Self> {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let backlog = Arc::new(AtomicUsize::new(0));
+        let file_path = session_path.join(filename);
+        let file = MidiTrackFile::create(
+            &file_path,
+            filename.to_string(),
+            device_name.to_string(),
+            file_config.ticks_per_quarter,
+        )?;
+        let thread_backlog = backlog.clone();
+        let tempo_map = file_config.tempo_map;
+        let thread = std::thread::Builder::new()
+            .name(format!("midi-writer-{}", device_name))
+            .spawn(move || {
+                let info = file.run(receiver, &thread_backlog);
+                if tempo_map && !info.needs_repair {
+                    match super::tempo::apply_tempo_map(&file_path) {
+                        Ok(Some(bpm)) => println!("[Sacho] {}: tempo map written ({:.1} BPM)", info.filename, bpm),
+                        Ok(None) => println!("[Sacho] {}: no steady tempo detected, left at 120 BPM", info.filename),
+                        Err(e) => println!("[Sacho] {}: failed to write tempo map: {}", info.filename, e),
+                    }
+                }
+                info
+            })?;
+
+        Ok(Self {
+            sender,
+            thread,
+            filename: filename.to_string(),
+            device_name: device_name.to_string(),
+            events_queued: 0,
+            backlog,
+            backlog_warned: false,
+            programs: Vec::new(),
+        })
+    }
+
+    /// Write bank select and program change for each channel at tick 0, so a
+    /// DAW plays the file with the sound the performer had selected.
+    /// Must be called before any other event is pushed.
+    pub fn write_programs(&mut self, programs: &[crate::session::ChannelProgram]) {
+        for program in programs {
+            // Readable note of the selection (text meta event) for DAWs that list them
+            let text = format!(
+                "Program ch{} bank {}:{} program {}",
+                program.channel + 1,
+                program.bank_msb.unwrap_or(0),
+                program.bank_lsb.unwrap_or(0),
+                program.program + 1
+            );
+            let mut meta = vec![0xFF, 0x01];
+            meta.extend(encode_variable_length(text.len() as u32));
+            meta.extend(text.as_bytes());
+            self.push_event(&TimestampedMidiEvent { timestamp_us: 0, data: meta });
+            for data in program.to_messages() {
+                self.push_event(&TimestampedMidiEvent { timestamp_us: 0, data });
+            }
+        }
+        self.programs = programs.to_vec();
+    }
+
+    /// Queue a single MIDI event for the writer thread. Never blocks.
+    pub fn push_event(&mut self, event: &TimestampedMidiEvent) {
+        // Fails only if the writer thread has died; finish() reports it
+        if self.sender.send(event.clone()).is_err() {
+            return;
+        }
+        self.events_queued += 1;
+        let queued = self.backlog.fetch_add(1, Ordering::Relaxed) + 1;
+        if queued >= Self::BACKLOG_WARNING && !self.backlog_warned {
+            self.backlog_warned = true;
+            println!(
+                "[Sacho] MIDI writer for {} is {} events behind; the recording folder's disk is slow",
+                self.device_name, queued
+            );
+        }
+    }
+
+    /// Events written so far, for a recording checkpoint
+    pub fn progress(&self) -> crate::session::FileProgress {
+        let written = self.events_queued.saturating_sub(self.backlog.load(Ordering::Relaxed));
+        crate::session::FileProgress {
+            filename: self.filename.clone(),
+            device_name: self.device_name.clone(),
+            duration_secs: 0.0,
+            event_count: written,
+        }
+    }
+
+    /// Finalize: let the writer thread drain its queue, write the end-of-track
+    /// marker and patch the MTrk length.
+    pub fn finish(self) -> anyhow::Result<MidiFileInfo> {
+        let Self { sender, thread, device_name, programs, .. } = self;
+        // Dropping the sender ends the writer thread's loop once it's drained
+        drop(sender);
+        let mut info = thread
+            .join()
+            .map_err(|_| anyhow::anyhow!("MIDI writer thread for {} panicked", device_name))?;
+        info.programs = programs;
+        Ok(info)
+    }
+}
+
+/// The file side of a [`MidiStreamWriter`], owned by its writer thread
+struct MidiTrackFile {
+    file: std::io::BufWriter<std::fs::File>,
     filename: String,
     device_name: String,
     last_tick: u64,
@@ -247,26 +569,34 @@ pub struct MidiStreamWriter {
     ticks_per_us: f64,
     /// Last time the file was flushed to disk
     last_flush: Instant,
+    /// Last time the file was synced past the OS cache (record-safe mode)
+    last_sync: Instant,
     /// Count of write errors (logged on first occurrence, summarized in finish())
     write_errors: u32,
 }
 
-impl MidiStreamWriter {
-    /// MIDI timing: 480 ticks per quarter note at 120 BPM (500000 us per beat)
-    const TICKS_PER_QUARTER: u16 = 480;
+impl MidiTrackFile {
+    /// MIDI timing while recording: 120 BPM (500000 us per beat), at the
+    /// configured ticks per quarter note
     const US_PER_QUARTER: f64 = 500_000.0;
-    
-    /// Create a new MIDI stream writer and write the file header.
-    pub fn new(session_path: &PathBuf, filename: &str, device_name: &str) -> anyhow::Result<Self> {
-        let file_path = session_path.join(filename);
-        let mut file = std::fs::File::create(&file_path)?;
+    /// Flush periodically to balance crash safety and I/O overhead
+    const FLUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Create the file and write its header.
+    fn create(
+        file_path: &PathBuf,
+        filename: String,
+        device_name: String,
+        ticks_per_quarter: u16,
+    ) -> anyhow::Result<Self> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(file_path)?);
         
         // MThd header
         file.write_all(b"MThd")?;
         file.write_all(&[0, 0, 0, 6])?;           // Header length
         file.write_all(&[0, 0])?;                   // Format 0
         file.write_all(&[0, 1])?;                   // 1 track
-        file.write_all(&Self::TICKS_PER_QUARTER.to_be_bytes())?;
+        file.write_all(&ticks_per_quarter.to_be_bytes())?;
         
         // MTrk header with placeholder length
         file.write_all(b"MTrk")?;
@@ -278,25 +608,54 @@ impl MidiStreamWriter {
         
         Ok(Self {
             file,
-            filename: filename.to_string(),
-            device_name: device_name.to_string(),
+            filename,
+            device_name,
             last_tick: 0,
             event_count: 0,
             track_data_bytes: 0,
-            ticks_per_us: Self::TICKS_PER_QUARTER as f64 / Self::US_PER_QUARTER,
+            ticks_per_us: ticks_per_quarter as f64 / Self::US_PER_QUARTER,
             last_flush: Instant::now(),
+            last_sync: Instant::now(),
             write_errors: 0,
         })
     }
 
-    /// Push a single MIDI event to the file.
-    pub fn push_event(&mut self, event: &TimestampedMidiEvent) {
+    /// Write queued events until the sender is dropped, then finish the file.
+    /// Everything already queued is written, in order, before finishing.
+    fn run(
+        mut self,
+        receiver: crossbeam_channel::Receiver<TimestampedMidiEvent>,
+        backlog: &AtomicUsize,
+    ) -> MidiFileInfo {
+        loop {
+            match receiver.recv_timeout(Self::FLUSH_INTERVAL) {
+                Ok(event) => {
+                    self.write_event(&event);
+                    backlog.fetch_sub(1, Ordering::Relaxed);
+                }
+                Err(crossbeam_channel::RecvTimeoutError::Timeout) => {}
+                Err(crossbeam_channel::RecvTimeoutError::Disconnected) => break,
+            }
+            // While behind, keep writing and leave flushing to the buffer
+            if self.last_flush.elapsed() >= Self::FLUSH_INTERVAL && receiver.is_empty() {
+                let _ = self.file.flush();
+                self.last_flush = Instant::now();
+                if crate::battery::is_safe_mode() && self.last_sync.elapsed() >= crate::battery::SYNC_INTERVAL {
+                    let _ = self.file.get_ref().sync_data();
+                    self.last_sync = Instant::now();
+                }
+            }
+        }
+        self.finish()
+    }
+
+    fn write_event(&mut self, event: &TimestampedMidiEvent) {
         let tick = (event.timestamp_us as f64 * self.ticks_per_us) as u64;
         let delta = tick.saturating_sub(self.last_tick);
         self.last_tick = tick;
 
         // Write variable-length delta time
-        let delta_bytes = Self::encode_variable_length(delta as u32);
+        let delta_bytes = encode_variable_length(delta as u32);
         if let Err(e) = self.file.write_all(&delta_bytes) {
             self.write_errors += 1;
             if self.write_errors == 1 {
@@ -316,26 +675,20 @@ impl MidiStreamWriter {
         
         self.track_data_bytes += delta_bytes.len() as u32 + event.data.len() as u32;
         self.event_count += 1;
-        
-        // Flush periodically (every 100ms) to balance crash safety and I/O overhead
-        if self.last_flush.elapsed() >= Duration::from_millis(100) {
-            let _ = self.file.flush();
-            self.last_flush = Instant::now();
-        }
     }
     
-    /// Finalize: write end-of-track marker and patch the MTrk length.
-    pub fn finish(mut self) -> anyhow::Result<MidiFileInfo> {
-        // Write end-of-track: delta=0, meta event FF 2F 00
-        self.file.write_all(&[0x00, 0xFF, 0x2F, 0x00])?;
-        self.track_data_bytes += 4;
-        
-        // Patch MTrk length at byte offset 18
-        self.file.seek(SeekFrom::Start(18))?;
-        self.file.write_all(&self.track_data_bytes.to_be_bytes())?;
-        self.file.flush()?;
+    /// Write end-of-track and patch the MTrk length. Returns the file info
+    /// even when the disk refused the final writes, flagged for repair.
+    fn finish(mut self) -> MidiFileInfo {
+        let needs_repair = match self.patch_track_length() {
+            Ok(()) => false,
+            Err(e) => {
+                println!("[Sacho] Failed to finalize MIDI {}: {}", self.filename, e);
+                true
+            }
+        };
         
-        let size = self.file.metadata().map(|m| m.len()).unwrap_or(0);
+        let size = self.file.get_ref().metadata().map(|m| m.len()).unwrap_or(0);
 
         if self.write_errors > 0 {
             println!("[Sacho] MIDI stream for {} had {} write errors", self.device_name, self.write_errors);
@@ -344,26 +697,39 @@ impl MidiStreamWriter {
         println!("[Sacho] MIDI stream finished: {} ({} events, {} bytes)",
             self.filename, self.event_count, size);
         
-        Ok(MidiFileInfo {
+        MidiFileInfo {
             filename: self.filename,
             device_name: self.device_name,
             event_count: self.event_count,
-            needs_repair: false,
-        })
+            needs_repair,
+            programs: Vec::new(),
+            start_offset_secs: 0.0,
+        }
     }
-    
-    /// Encode a value as MIDI variable-length quantity.
-    fn encode_variable_length(mut value: u32) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(4);
-        bytes.push((value & 0x7F) as u8);
+
+    fn patch_track_length(&mut self) -> std::io::Result<()> {
+        // Write end-of-track: delta=0, meta event FF 2F 00
+        self.file.write_all(&[0x00, 0xFF, 0x2F, 0x00])?;
+        self.track_data_bytes += 4;
+        
+        // Patch MTrk length at byte offset 18
+        self.file.seek(SeekFrom::Start(18))?;
+        self.file.write_all(&self.track_data_bytes.to_be_bytes())?;
+        self.file.flush()
+    }
+}
+
+/// Encode a value as MIDI variable-length quantity.
+fn encode_variable_length(mut value: u32) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4);
+    bytes.push((value & 0x7F) as u8);
+    value >>= 7;
+    while value > 0 {
+        bytes.push(((value & 0x7F) | 0x80) as u8);
         value >>= 7;
-        while value > 0 {
-            bytes.push(((value & 0x7F) | 0x80) as u8);
-            value >>= 7;
-        }
-        bytes.reverse();
-        bytes
     }
+    bytes.reverse();
+    bytes
 }
 
 // ============================================================================
@@ -417,6 +783,25 @@ pub fn repair_midi_file_on_disk(file_path: &PathBuf) -> anyhow::Result<usize> {
     if &header[0..4] != b"MThd" || &header[14..18] != b"MTrk" {
         return Err(anyhow::anyhow!("Not a valid MIDI file"));
     }
+    // Sacho writes format 0 with a single track; anything else isn't ours to fix
+    if header[4..8] != [0, 0, 0, 6] || header[8..10] != [0, 0] || header[10..12] != [0, 1] {
+        return Err(anyhow::anyhow!("Unexpected MIDI header (not a Sacho recording)"));
+    }
+
+    // A crash can cut the last event short. Appending end-of-track after a
+    // partial event would make players read the marker as that event's data,
+    // so drop the torn tail first.
+    let mut track = Vec::new();
+    file.read_to_end(&mut track)?;
+    let complete = complete_track_prefix_len(&track);
+    let file_size = if complete < track.len() {
+        let new_size = 22 + complete as u64;
+        file.set_len(new_size)?;
+        println!("[Sacho] Dropped {} bytes of incomplete MIDI event", track.len() - complete);
+        new_size
+    } else {
+        file_size
+    };
     
     // Check if end-of-track marker (FF 2F 00) exists at end of file
     let has_eot = if file_size >= 25 {
@@ -451,32 +836,93 @@ pub fn repair_midi_file_on_disk(file_path: &PathBuf) -> anyhow::Result<usize> {
     Ok(event_count)
 }
 
-/// Check if a WAV file has a valid RIFF header (chunk sizes match file size).
-/// WAV structure: RIFF[4] size[4] WAVE[4] ... fmt [4] ... data[4] size[4] ...
+/// Length of the longest prefix of MTrk data that ends on an event boundary.
+///
+/// Only trims an event that runs past the end of the data. If the data can't
+/// be parsed earlier (e.g. an unexpected byte), the whole track is kept, since
+/// trimming there would throw away events a lenient player could still read.
+fn complete_track_prefix_len(track: &[u8]) -> usize {
+    // Read a variable-length quantity starting at `pos`; None if it runs off the end
+    fn read_vlq(track: &[u8], mut pos: usize) -> Option<(u32, usize)> {
+        let mut value: u32 = 0;
+        for _ in 0..4 {
+            let byte = *track.get(pos)?;
+            pos += 1;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Some((value, pos));
+            }
+        }
+        Some((value, pos))
+    }
+
+    let mut pos = 0;
+    let mut running_status: Option<u8> = None;
+    while pos < track.len() {
+        let event_start = pos;
+        let Some((_, after_delta)) = read_vlq(track, pos) else { return event_start };
+        let Some(&first) = track.get(after_delta) else { return event_start };
+
+        let end = match first {
+            0xFF => {
+                // Meta: FF type len data
+                match read_vlq(track, after_delta + 2) {
+                    Some((len, data_start)) if after_delta + 1 < track.len() => data_start + len as usize,
+                    _ => return event_start,
+                }
+            }
+            0xF0 | 0xF7 => {
+                // SysEx or escape: F0/F7 len data. The data may hold F7
+                // bytes of its own, or end without one, so only the length
+                // says where the event ends.
+                match read_vlq(track, after_delta + 1) {
+                    Some((len, data_start)) => data_start + len as usize,
+                    None => return event_start,
+                }
+            }
+            0x80..=0xEF => {
+                running_status = Some(first);
+                after_delta + 1 + channel_message_data_len(first)
+            }
+            _ => match running_status {
+                Some(status) => after_delta + channel_message_data_len(status),
+                // Unparseable; keep everything
+                None => return track.len(),
+            },
+        };
+
+        if end > track.len() {
+            return event_start;
+        }
+        pos = end;
+    }
+    track.len()
+}
+
+/// Number of data bytes following a channel message status byte
+fn channel_message_data_len(status: u8) -> usize {
+    match status & 0xF0 {
+        0xC0 | 0xD0 => 1,
+        _ => 2,
+    }
+}
+
+/// Check if a WAV file has a valid header (RIFF or RF64 size matches file size).
 pub fn wav_file_needs_repair(file_path: &PathBuf) -> bool {
-    use std::io::Read;
-    
     let Ok(mut file) = std::fs::File::open(file_path) else { return false; };
     let Ok(meta) = file.metadata() else { return false; };
     let file_size = meta.len();
     
     if file_size < 44 { return false; } // Minimum WAV header
     
-    let mut header = [0u8; 12];
-    if file.read_exact(&mut header).is_err() { return false; }
-    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" { return false; }
-    
-    let stored_riff_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
-    let expected_riff_size = (file_size - 8) as u32;
-    
-    stored_riff_size != expected_riff_size
+    let Ok(layout) = crate::recording::wav::read_layout(&mut file) else { return false; };
+    layout.riff_size != file_size - 8
 }
 
-/// Repair a WAV file by fixing the RIFF and data chunk sizes.
+/// Repair a WAV file by fixing the RIFF and data chunk sizes, promoting it
+/// to RF64 when the data runs past 4 GB.
 /// Returns (channels, sample_rate, duration_secs, size_bytes).
 pub fn repair_wav_file(file_path: &PathBuf) -> anyhow::Result<(u16, u32, f64, u64)> {
-    use std::io::Read;
-    
     let mut file = std::fs::OpenOptions::new()
         .read(true).write(true).open(file_path)?;
     let file_size = file.metadata()?.len();
@@ -485,66 +931,23 @@ pub fn repair_wav_file(file_path: &PathBuf) -> anyhow::Result<(u16, u32, f64, u6
         return Err(anyhow::anyhow!("File too small to be a valid WAV file"));
     }
     
-    // Read and verify RIFF header
-    let mut header = [0u8; 12];
-    file.read_exact(&mut header)?;
-    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
-        return Err(anyhow::anyhow!("Not a valid WAV file"));
-    }
-    
-    // Scan chunks to find fmt and data
-    let mut channels: u16 = 0;
-    let mut sample_rate: u32 = 0;
-    let mut bits_per_sample: u16 = 0;
-    let mut data_chunk_offset: u64 = 0;
-    
-    let mut pos: u64 = 12;
-    loop {
-        file.seek(SeekFrom::Start(pos))?;
-        let mut chunk_header = [0u8; 8];
-        if file.read_exact(&mut chunk_header).is_err() { break; }
-        
-        let chunk_id = &chunk_header[0..4];
-        let chunk_size = u32::from_le_bytes([chunk_header[4], chunk_header[5], chunk_header[6], chunk_header[7]]);
-        
-        if chunk_id == b"fmt " {
-            let mut fmt = [0u8; 16];
-            file.read_exact(&mut fmt)?;
-            channels = u16::from_le_bytes([fmt[2], fmt[3]]);
-            sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
-            bits_per_sample = u16::from_le_bytes([fmt[14], fmt[15]]);
-        } else if chunk_id == b"data" {
-            data_chunk_offset = pos;
-            break;
-        }
-        
-        // Move to next chunk (chunk size + 8 for header, padded to even)
-        pos += 8 + chunk_size as u64;
-        if chunk_size % 2 != 0 { pos += 1; } // WAV chunks are 2-byte aligned
-    }
+    let layout = crate::recording::wav::read_layout(&mut file)?;
     
-    if data_chunk_offset == 0 || channels == 0 {
-        return Err(anyhow::anyhow!("Could not find fmt/data chunks"));
+    // Calculate correct sizes. A crash mid-write can leave a partial sample
+    // frame at the end; drop it so the data chunk holds whole frames.
+    let block_align = layout.block_align();
+    let mut data_len = file_size - layout.data_offset - 8;
+    if block_align > 0 && data_len % block_align != 0 {
+        data_len -= data_len % block_align;
+        file.set_len(layout.data_offset + 8 + data_len)?;
     }
-    
-    // Calculate correct sizes
-    let data_size = (file_size - data_chunk_offset - 8) as u32;
-    let riff_size = (file_size - 8) as u32;
-    
-    // Patch RIFF size (bytes 4-7)
-    file.seek(SeekFrom::Start(4))?;
-    file.write_all(&riff_size.to_le_bytes())?;
-    
-    // Patch data chunk size (4 bytes after "data" tag)
-    file.seek(SeekFrom::Start(data_chunk_offset + 4))?;
-    file.write_all(&data_size.to_le_bytes())?;
-    file.flush()?;
+    let file_size = layout.data_offset + 8 + data_len;
+    crate::recording::wav::write_sizes(&mut file, &layout, data_len)?;
     
     // Calculate duration
-    let bytes_per_sample = bits_per_sample as u32 / 8;
-    let bytes_per_frame = bytes_per_sample * channels as u32;
-    let duration_secs = if bytes_per_frame > 0 && sample_rate > 0 {
-        data_size as f64 / (sample_rate as f64 * bytes_per_frame as f64)
+    let (channels, sample_rate) = (layout.channels, layout.sample_rate);
+    let duration_secs = if block_align > 0 && sample_rate > 0 {
+        data_len as f64 / (sample_rate as f64 * block_align as f64)
     } else {
         0.0
     };
@@ -677,6 +1080,9 @@ pub fn repair_flac_file(file_path: &PathBuf) -> anyhow::Result<(u16, u32, f64, u
     } else {
         0
     };
+    if total_samples == 0 {
+        return Err(anyhow::anyhow!("No complete FLAC frames to recover"));
+    }
     
     {
         let mut file = std::fs::OpenOptions::new()
@@ -1113,6 +1519,8 @@ pub struct AudioTriggerState {
     pub current_rms: f32,
     /// Max of recent_rms (3s peak hold), read by level poller
     pub current_peak_level: f32,
+    /// Time source for the peak-hold window (injectable for tests)
+    clock: SharedClock,
 }
 
 impl AudioTriggerState {
@@ -1126,20 +1534,27 @@ impl AudioTriggerState {
             recent_rms: std::collections::VecDeque::new(),
             current_rms: 0.0,
             current_peak_level: 0.0,
+            clock: clock::system(),
         }
     }
 
-    /// Process incoming audio samples. Returns true if RMS exceeds threshold
-    /// at a 50ms window boundary.
-    pub fn process_samples(&mut self, data: &[f32]) -> bool {
-        let mut triggered = false;
+    /// Use `clock` instead of the system clock for the peak-hold window
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Process incoming audio samples. Returns the loudest 50ms window RMS
+    /// that exceeded the threshold, if any.
+    pub fn process_samples(&mut self, data: &[f32]) -> Option<f32> {
+        let mut triggered: Option<f32> = None;
         for &sample in data {
             self.window_sum_sq += (sample as f64) * (sample as f64);
             self.window_sample_count += 1;
 
             if self.window_sample_count >= self.samples_per_window {
                 let rms = (self.window_sum_sq / self.window_sample_count as f64).sqrt() as f32;
-                let now = Instant::now();
+                let now = self.clock.now();
 
                 self.recent_rms.push_back((now, rms));
                 // Trim entries older than 3 seconds
@@ -1161,7 +1576,7 @@ impl AudioTriggerState {
                 self.window_sample_count = 0;
 
                 if rms > self.threshold as f32 {
-                    triggered = true;
+                    triggered = Some(triggered.map_or(rms, |prev: f32| prev.max(rms)));
                 }
             }
         }
@@ -1180,8 +1595,9 @@ pub struct CaptureState {
     pub recording_started_at: Option<Instant>,
     /// Streaming MIDI writers (one per recording device, keyed by port name)
     pub midi_writers: HashMap<String, MidiStreamWriter>,
-    /// Streaming audio writers (one per device, Some when recording)
-    pub audio_writers: Vec<Option<AudioStreamWriter>>,
+    /// Streaming audio writers per device (one, or one per channel stem;
+    /// empty when not recording)
+    pub audio_writers: Vec<Vec<AudioStreamWriter>>,
     /// Pre-roll buffer for MIDI events (used when not recording)
     pub midi_preroll: MidiPrerollBuffer,
     /// Pre-roll buffers for audio (one per device, used when not recording)
@@ -1190,11 +1606,50 @@ pub struct CaptureState {
     pub audio_trigger_states: Vec<AudioTriggerState>,
     /// Pre-roll duration in seconds
     pub pre_roll_secs: u32,
+    /// How far into the session the video files start (audio/MIDI pre-roll
+    /// longer than the video pre-roll)
+    pub video_lead: Duration,
     /// MIDI timestamp offset in microseconds (equals sync_preroll_duration)
     /// This is added to real-time MIDI timestamps to align with pre-roll content
     pub midi_timestamp_offset_us: u64,
+    /// Per-device latency corrections for this recording (device name -> ms)
+    pub sync_offsets_ms: HashMap<String, i32>,
+    /// Connected MIDI ports that are Bluetooth LE devices
+    pub bluetooth_midi_ports: Vec<String>,
+    /// File name templates for this recording
+    pub filename_templates: crate::config::FilenameTemplates,
+    /// MIDI file resolution and tempo map setting for this recording
+    pub midi_file: crate::config::MidiFileConfig,
+    /// Take number of this recording's session
+    pub take: u32,
+    /// Bank/program per channel at the start of this recording (device name -> channels)
+    pub start_programs: HashMap<String, Vec<crate::session::ChannelProgram>>,
     /// Flag to stop the recording lock heartbeat thread
     pub heartbeat_stop: Option<Arc<AtomicBool>>,
+    /// MIDI output feedback to trigger devices (None when disabled)
+    pub midi_feedback: Option<MidiFeedback>,
+    /// Live RTMP stream running alongside this recording
+    pub rtmp_streamer: Option<RtmpStreamer>,
+    /// Audio device feeding the live stream (index into audio_prerolls)
+    pub stream_audio_tap: Option<(usize, StreamTap)>,
+    /// Time source for recording timestamps and sync math
+    pub clock: SharedClock,
+    /// When the trigger that started this recording was detected
+    pub triggered_at: Option<Instant>,
+    /// Trigger-to-first-write times measured so far in this recording
+    pub startup_latency: Vec<crate::session::WriterLatency>,
+    /// Record devices in shared access mode, opened only while recording
+    pub shared_midi_inputs: Vec<SharedMidiInput>,
+    /// Connections to the shared devices for the current recording
+    pub shared_midi_connections: Vec<MidiInputConnection<()>>,
+}
+
+/// A MIDI record device that Sacho leaves free between recordings
+#[derive(Clone)]
+pub struct SharedMidiInput {
+    pub port_name: String,
+    /// Builds the callback for each new connection
+    pub handler: Arc<dyn Fn() -> Box<dyn FnMut(u64, &[u8]) + Send> + Send + Sync>,
 }
 
 impl CaptureState {
@@ -1211,11 +1666,34 @@ impl CaptureState {
             audio_prerolls: Vec::new(),
             audio_trigger_states: Vec::new(),
             pre_roll_secs,
+            video_lead: Duration::ZERO,
             midi_timestamp_offset_us: 0,
+            sync_offsets_ms: HashMap::new(),
+            bluetooth_midi_ports: Vec::new(),
+            filename_templates: crate::config::FilenameTemplates::default(),
+            midi_file: crate::config::MidiFileConfig::default(),
+            take: 1,
+            start_programs: HashMap::new(),
             heartbeat_stop: None,
+            midi_feedback: None,
+            rtmp_streamer: None,
+            stream_audio_tap: None,
+            clock: clock::system(),
+            triggered_at: None,
+            startup_latency: Vec::new(),
+            shared_midi_inputs: Vec::new(),
+            shared_midi_connections: Vec::new(),
         }
     }
     
+    /// Use `clock` instead of the system clock, here and in the MIDI
+    /// pre-roll (audio trigger states take it when they're added)
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.midi_preroll.set_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
     /// Check if we should capture to pre-roll (not recording, or starting)
     pub fn should_use_preroll(&self) -> bool {
         !self.is_recording || self.is_starting
@@ -1225,18 +1703,50 @@ impl CaptureState {
     pub fn push_midi_event(&mut self, device_name: &str, event: TimestampedMidiEvent) {
         if !self.midi_writers.contains_key(device_name) {
             if let Some(session_path) = self.session_path.clone() {
-                let safe_name = crate::session::sanitize_device_name(device_name);
-                let filename = format!("midi_{}.mid", safe_name);
-                match MidiStreamWriter::new(&session_path, &filename, device_name) {
-                    Ok(writer) => { self.midi_writers.insert(device_name.to_string(), writer); }
-                    Err(e) => { println!("[Sacho] Failed to create MIDI writer for {}: {}", device_name, e); }
-                }
+                self.open_midi_writer(&session_path, device_name);
             }
         }
+        self.midi_preroll.observe_program(device_name, &event.data);
+        let event = self.sync_corrected(device_name, event);
         if let Some(writer) = self.midi_writers.get_mut(device_name) {
             writer.push_event(&event);
         }
     }
+
+    /// Create a device's MIDI file, starting with its selected programs
+    fn open_midi_writer(&mut self, session_path: &PathBuf, device_name: &str) {
+        let label = crate::devices::aliases::label(crate::devices::aliases::DeviceKind::Midi, device_name, None);
+        let filename = crate::session::filenames::midi_filename(
+            &self.filename_templates,
+            &crate::session::filenames::FileNameContext {
+                device: &label,
+                codec: "midi",
+                channels: None,
+                take: self.take,
+            },
+        );
+        match MidiStreamWriter::new(session_path, &filename, &label, &self.midi_file) {
+            Ok(mut writer) => {
+                if let Some(programs) = self.start_programs.get(device_name) {
+                    writer.write_programs(programs);
+                }
+                self.midi_writers.insert(device_name.to_string(), writer);
+            }
+            Err(e) => { println!("[Sacho] Failed to create MIDI writer for {}: {}", device_name, e); }
+        }
+    }
+
+    /// Shift a MIDI event by its device's latency correction
+    fn sync_corrected(&self, device_name: &str, mut event: TimestampedMidiEvent) -> TimestampedMidiEvent {
+        let offset_ms = crate::config::lookup_sync_offset_ms(&self.sync_offsets_ms, device_name);
+        let offset_us = offset_ms.unsigned_abs() as u64 * 1000;
+        event.timestamp_us = if offset_ms > 0 {
+            event.timestamp_us.saturating_sub(offset_us)
+        } else {
+            event.timestamp_us + offset_us
+        };
+        event
+    }
 }
 
 impl Default for CaptureState {
@@ -1253,8 +1763,23 @@ impl Default for CaptureState {
             audio_prerolls: Vec::new(),
             audio_trigger_states: Vec::new(),
             pre_roll_secs: 2,
+            video_lead: Duration::ZERO,
             midi_timestamp_offset_us: 0,
+            sync_offsets_ms: HashMap::new(),
+            bluetooth_midi_ports: Vec::new(),
+            filename_templates: crate::config::FilenameTemplates::default(),
+            midi_file: crate::config::MidiFileConfig::default(),
+            take: 1,
+            start_programs: HashMap::new(),
             heartbeat_stop: None,
+            midi_feedback: None,
+            rtmp_streamer: None,
+            stream_audio_tap: None,
+            clock: clock::system(),
+            triggered_at: None,
+            startup_latency: Vec::new(),
+            shared_midi_inputs: Vec::new(),
+            shared_midi_connections: Vec::new(),
         }
     }
 }
@@ -1267,13 +1792,34 @@ impl Default for CaptureState {
 // Arc<Mutex<>>, the Tauri command handlers should always call from the same thread.
 use std::cell::RefCell;
 thread_local! {
-    static AUDIO_STREAMS: RefCell<Vec<cpal::Stream>> = RefCell::new(Vec::new());
+    static AUDIO_STREAMS: RefCell<Vec<AudioStream>> = RefCell::new(Vec::new());
+    /// Level meter on the playback monitor device (not counted as an audio input)
+    static PLAYBACK_MONITOR_STREAM: RefCell<Option<cpal::Stream>> = const { RefCell::new(None) };
+}
+
+/// An open audio input. Dropping it stops the callbacks.
+#[allow(dead_code)] // held only to keep the stream alive
+enum AudioStream {
+    Cpal(cpal::Stream),
+    #[cfg(feature = "test-harness")]
+    Fake(crate::test_harness::fake_devices::FakeAudioStream),
+}
+
+/// An open MIDI input. Dropping it disconnects.
+#[allow(dead_code)] // held only to keep the connection open
+enum MidiConnection {
+    Port(MidiInputConnection<()>),
+    #[cfg(feature = "test-harness")]
+    Fake(crate::test_harness::fake_devices::FakeMidiConnection),
 }
 
 /// Manages background MIDI monitoring and automatic recording
 pub struct MidiMonitor {
-    trigger_connections: Vec<MidiInputConnection<()>>,
-    capture_connections: Vec<MidiInputConnection<()>>,
+    trigger_connections: Vec<MidiConnection>,
+    capture_connections: Vec<MidiConnection>,
+    /// Thru and virtual-tap outputs opened with the MIDI connections, keyed
+    /// by the input port name they forward
+    midi_forwards: Vec<(String, Arc<MidiThru>)>,
     app_handle: AppHandle,
     last_event_time: Arc<RwLock<Option<Instant>>>,
     is_monitoring: Arc<RwLock<bool>>,
@@ -1292,27 +1838,35 @@ pub struct MidiMonitor {
     /// Handle for the device health checker background thread
     health_checker_handle: Option<std::thread::JoinHandle<()>>,
     health_checker_stop: Arc<AtomicBool>,
+    /// Time source shared with the capture state and idle checker
+    clock: SharedClock,
 }
 
 impl MidiMonitor {
     /// Create a new MIDI monitor
     pub fn new(app_handle: AppHandle) -> Self {
-        // Get pre-roll duration from config
+        Self::with_clock(app_handle, clock::system())
+    }
+
+    /// Create a MIDI monitor whose pre-roll, trigger, idle and sync timing
+    /// all run on `clock`
+    pub fn with_clock(app_handle: AppHandle, clock: SharedClock) -> Self {
+        // Get video pre-roll duration from config
         let pre_roll_secs = {
             let config_state = app_handle.state::<RwLock<Config>>();
             let config = config_state.read();
-            let limit = if config.encode_during_preroll { MAX_PRE_ROLL_SECS_ENCODED } else { MAX_PRE_ROLL_SECS };
-            config.pre_roll_secs.min(limit)
+            config.effective_video_pre_roll_secs()
         };
         
         Self {
             trigger_connections: Vec::new(),
             capture_connections: Vec::new(),
+            midi_forwards: Vec::new(),
             app_handle,
             last_event_time: Arc::new(RwLock::new(None)),
             is_monitoring: Arc::new(RwLock::new(false)),
-            capture_state: Arc::new(Mutex::new(CaptureState::default())),
-            video_manager: Arc::new(Mutex::new(VideoCaptureManager::new(pre_roll_secs))),
+            capture_state: Arc::new(Mutex::new(CaptureState::default().with_clock(clock.clone()))),
+            video_manager: Arc::new(Mutex::new(VideoCaptureManager::with_clock(pre_roll_secs, clock.clone()))),
             video_poller_handle: None,
             idle_checker_handle: None,
             audio_level_poller_handle: None,
@@ -1321,6 +1875,7 @@ impl MidiMonitor {
             audio_poller_stop: Arc::new(AtomicBool::new(false)),
             health_checker_handle: None,
             health_checker_stop: Arc::new(AtomicBool::new(false)),
+            clock,
         }
     }
     
@@ -1334,22 +1889,27 @@ impl MidiMonitor {
         // Stop any existing monitoring
         self.stop();
 
+        if crate::read_only::is_read_only() {
+            println!("[Sacho] Read-only mode, not opening devices");
+            return Ok(());
+        }
+
         let config = self.app_handle.state::<RwLock<Config>>();
         let config = config.read().clone();
 
-        // Determine pre-roll limit based on encode_during_preroll setting
-        let pre_roll_limit = if config.encode_during_preroll { MAX_PRE_ROLL_SECS_ENCODED } else { MAX_PRE_ROLL_SECS };
-
-        // Update pre-roll duration from config
+        // Update audio/MIDI pre-roll duration from config
         {
-            let pre_roll = config.pre_roll_secs.min(pre_roll_limit);
+            let pre_roll = config.effective_audio_midi_pre_roll_secs();
             let mut state = self.capture_state.lock();
             state.pre_roll_secs = pre_roll;
-            state.midi_preroll.set_duration_with_limit(pre_roll, pre_roll_limit);
+            state.midi_preroll.set_duration_with_limit(pre_roll, MAX_AUDIO_MIDI_PRE_ROLL_SECS);
         }
 
         self.start_midi(&config)?;
-        let (_audio_count, has_audio_triggers) = self.start_audio(&config)?;
+        let (started_audio, has_audio_triggers) = self.start_audio(&config)?;
+        if started_audio > 0 {
+            preload_audio_writer_plugins(&config.audio_format);
+        }
         let video_count = self.start_video_pipeline(&config)?;
 
         let audio_count = AUDIO_STREAMS.with(|streams| streams.borrow().len());
@@ -1390,85 +1950,84 @@ impl MidiMonitor {
     fn start_midi(&mut self, config: &Config) -> anyhow::Result<()> {
         println!("[Sacho] Trigger MIDI devices: {:?}", config.trigger_midi_devices);
         println!("[Sacho] Record MIDI devices: {:?}", config.selected_midi_devices);
-        println!("[Sacho] Pre-roll: {} seconds", config.pre_roll_secs);
+        println!("[Sacho] Pre-roll: {}s audio/MIDI, {}s video",
+            config.audio_midi_pre_roll_secs, config.video_pre_roll_secs);
 
         let midi_in = MidiInput::new("sacho-enum")?;
         let ports = midi_in.ports();
 
         // Build port info map
         let mut port_info: Vec<(usize, String)> = Vec::new();
+        let mut bluetooth_ports = Vec::new();
         for (idx, port) in ports.iter().enumerate() {
             if let Ok(name) = midi_in.port_name(port) {
+                if crate::devices::bluetooth::is_bluetooth_port(&port.id(), &name) {
+                    bluetooth_ports.push(name.clone());
+                }
                 port_info.push((idx, name));
             }
         }
 
         println!("[Sacho] Available MIDI ports: {:?}", port_info);
+        let names: Vec<String> = port_info.iter().map(|(_, name)| name.clone()).collect();
+        let port_info: Vec<(usize, String, String)> = port_info
+            .into_iter()
+            .zip(crate::devices::midi_port_ids(&names))
+            .map(|((idx, name), id)| (idx, name, id))
+            .collect();
+        if !bluetooth_ports.is_empty() {
+            println!("[Sacho] Bluetooth MIDI ports: {:?}", bluetooth_ports);
+        }
+        self.capture_state.lock().bluetooth_midi_ports = bluetooth_ports;
 
-        // Connect to trigger devices
-        for (port_index, port_name) in &port_info {
-            let device_id = format!("midi-{}", port_index);
+        // Open thru and virtual-tap outputs before the inputs, so their
+        // handlers can forward
+        self.midi_forwards.clear();
+        if let Some((_, port_name, _)) = config.midi_thru.input_device.as_ref()
+            .and_then(|input| port_info.iter().find(|(_, _, id)| id == input))
+        {
+            if let Some(thru) = MidiThru::connect(&config.midi_thru) {
+                self.midi_forwards.push((port_name.clone(), Arc::new(thru)));
+            }
+        }
+        for (_, port_name, device_id) in &port_info {
+            let used = config.trigger_midi_devices.contains(device_id)
+                || config.selected_midi_devices.contains(device_id);
+            if used && config.midi_access_mode(device_id) == MidiAccessMode::VirtualTap {
+                if let Some(tap) = MidiThru::virtual_port(port_name) {
+                    self.midi_forwards.push((port_name.clone(), Arc::new(tap)));
+                }
+            }
+        }
+
+        // Ports Sacho holds while monitoring
+        let mut held_ports: Vec<String> = Vec::new();
 
-            if config.trigger_midi_devices.contains(&device_id) {
+        // Connect to trigger devices
+        for (port_index, port_name, device_id) in &port_info {
+            if config.trigger_midi_devices.contains(device_id) {
                 println!("[Sacho] Connecting trigger: {} ({})", port_name, device_id);
+                if config.midi_access_mode(device_id) == MidiAccessMode::Shared {
+                    println!("[Sacho] {} triggers recordings, so it's held while monitoring despite shared access", port_name);
+                }
 
                 let midi_in = MidiInput::new("sacho-trigger")?;
                 let ports = midi_in.ports();
 
                 if let Some(port) = ports.get(*port_index) {
-                    let app_handle = self.app_handle.clone();
-                    let last_event_time = self.last_event_time.clone();
-                    let capture_state = self.capture_state.clone();
-                    let video_manager = self.video_manager.clone();
-                    let port_name_clone = port_name.clone();
                     // Only store MIDI events if this trigger device is also selected for recording
-                    let also_record = config.selected_midi_devices.contains(&device_id);
+                    let also_record = config.selected_midi_devices.contains(device_id);
+                    let mut handler = self.trigger_midi_handler(port_name.clone(), also_record);
 
                     match midi_in.connect(
                         port,
                         "sacho-trigger",
-                        move |timestamp_us, message, _| {
-                            // Only store events if this device is also marked for recording
-                            if also_record {
-                                let mut state = capture_state.lock();
-
-                                // Use pre-roll if not recording OR if recording is starting (video init)
-                                if state.should_use_preroll() {
-                                    // Store in pre-roll buffer with driver timestamp for accurate timing
-                                    let event = TimestampedMidiEvent {
-                                        timestamp_us: 0,
-                                        data: message.to_vec(),
-                                    };
-                                    state.midi_preroll.push(port_name_clone.clone(), event, timestamp_us);
-                                } else {
-                                    // Recording is active, stream to disk
-                                    let rel_time = state.start_time
-                                        .map(|st| st.elapsed().as_micros() as u64 + state.midi_timestamp_offset_us)
-                                        .unwrap_or(state.midi_timestamp_offset_us);
-                                    state.push_midi_event(
-                                        &port_name_clone,
-                                        TimestampedMidiEvent {
-                                            timestamp_us: rel_time,
-                                            data: message.to_vec(),
-                                        },
-                                    );
-                                }
-                            }
-
-                            // Check for note-on to trigger recording
-                            if message.len() >= 3 {
-                                let status = message[0] & 0xF0;
-                                let velocity = message[2];
-
-                                if status == 0x90 && velocity > 0 {
-                                    handle_trigger(&app_handle, &last_event_time, &capture_state, &video_manager);
-                                }
-                            }
-                        },
+                        move |timestamp_us, message, _| handler(timestamp_us, message),
                         (),
                     ) {
                         Ok(conn) => {
-                            self.trigger_connections.push(conn);
+                            self.trigger_connections.push(MidiConnection::Port(conn));
+                            held_ports.push(port_name.clone());
                             println!("[Sacho] Connected to trigger: {}", port_name);
                         }
                         Err(e) => {
@@ -1479,69 +2038,48 @@ impl MidiMonitor {
             }
         }
 
-        // Connect to record devices (that aren't already triggers)
-        for (port_index, port_name) in &port_info {
-            let device_id = format!("midi-{}", port_index);
+        // Open feedback outputs for the connected trigger devices
+        let trigger_port_names: Vec<String> = port_info.iter()
+            .filter(|(_, _, id)| config.trigger_midi_devices.contains(id))
+            .map(|(_, name, _)| name.clone())
+            .collect();
+        self.capture_state.lock().midi_feedback = MidiFeedback::connect(&config.midi_feedback, &trigger_port_names);
 
+        // Connect to record devices (that aren't already triggers). Shared
+        // ones are only opened while a recording runs.
+        let mut shared_inputs = Vec::new();
+        for (port_index, port_name, device_id) in &port_info {
             // Skip if already connected as trigger
-            if config.trigger_midi_devices.contains(&device_id) {
+            if config.trigger_midi_devices.contains(device_id) {
+                continue;
+            }
+
+            if config.selected_midi_devices.contains(device_id)
+                && config.midi_access_mode(device_id) == MidiAccessMode::Shared
+            {
+                println!("[Sacho] {} is shared, opening it only while recording", port_name);
+                shared_inputs.push(self.shared_midi_input(port_name.clone()));
                 continue;
             }
 
-            if config.selected_midi_devices.contains(&device_id) {
+            if config.selected_midi_devices.contains(device_id) {
                 println!("[Sacho] Connecting record device: {} ({})", port_name, device_id);
 
                 let midi_in = MidiInput::new("sacho-record")?;
                 let ports = midi_in.ports();
 
                 if let Some(port) = ports.get(*port_index) {
-                    let capture_state = self.capture_state.clone();
-                    let last_event_time = self.last_event_time.clone();
-                    let port_name_clone = port_name.clone();
+                    let mut handler = self.record_midi_handler(port_name.clone());
 
                     match midi_in.connect(
                         port,
                         "sacho-record",
-                        move |timestamp_us, message, _| {
-                            let mut state = capture_state.lock();
-
-                            // Update last event time for idle detection (even during pre-roll)
-                            if message.len() >= 3 {
-                                let status = message[0] & 0xF0;
-                                if status == 0x90 || status == 0x80 {
-                                    *last_event_time.write() = Some(Instant::now());
-                                }
-                            }
-
-                            // Use pre-roll if not recording OR if recording is starting (video init)
-                            if state.should_use_preroll() {
-                                // Store in pre-roll buffer with driver timestamp for accurate timing
-                                state.midi_preroll.push(
-                                    port_name_clone.clone(),
-                                    TimestampedMidiEvent {
-                                        timestamp_us: 0,
-                                        data: message.to_vec(),
-                                    },
-                                    timestamp_us,
-                                );
-                            } else {
-                                // Recording is active, stream to disk
-                                let rel_time = state.start_time
-                                    .map(|st| st.elapsed().as_micros() as u64 + state.midi_timestamp_offset_us)
-                                    .unwrap_or(state.midi_timestamp_offset_us);
-                                state.push_midi_event(
-                                    &port_name_clone,
-                                    TimestampedMidiEvent {
-                                        timestamp_us: rel_time,
-                                        data: message.to_vec(),
-                                    },
-                                );
-                            }
-                        },
+                        move |timestamp_us, message, _| handler(timestamp_us, message),
                         (),
                     ) {
                         Ok(conn) => {
-                            self.capture_connections.push(conn);
+                            self.capture_connections.push(MidiConnection::Port(conn));
+                            held_ports.push(port_name.clone());
                             println!("[Sacho] Connected to record device: {}", port_name);
                         }
                         Err(e) => {
@@ -1552,17 +2090,160 @@ impl MidiMonitor {
             }
         }
 
+        self.capture_state.lock().shared_midi_inputs = shared_inputs;
+
+        // The thru input may not be held by Sacho yet; it needs a connection
+        // to forward from
+        let thru_port = config.midi_thru.input_device.as_ref()
+            .and_then(|input| port_info.iter().find(|(_, _, id)| id == input))
+            .map(|(_, name, _)| name.clone())
+            .filter(|name| self.midi_forwards.iter().any(|(port, _)| port == name));
+        if let Some(thru_port) = thru_port {
+            let connected = held_ports.contains(&thru_port);
+            let port_index = port_info.iter().find(|(_, name, _)| *name == thru_port).map(|(idx, _, _)| *idx);
+            if let (false, Some(port_index)) = (connected, port_index) {
+                let midi_in = MidiInput::new("sacho-thru")?;
+                let ports = midi_in.ports();
+                if let Some(port) = ports.get(port_index) {
+                    let mut handler = self.thru_midi_handler(thru_port.clone());
+                    match midi_in.connect(
+                        port,
+                        "sacho-thru",
+                        move |timestamp_us, message, _| handler(timestamp_us, message),
+                        (),
+                    ) {
+                        Ok(conn) => {
+                            self.capture_connections.push(MidiConnection::Port(conn));
+                            println!("[Sacho] Connected to thru input: {}", thru_port);
+                        }
+                        Err(e) => {
+                            println!("[Sacho] Failed to connect thru input {}: {}", thru_port, e);
+                        }
+                    }
+                }
+            }
+        }
+
+        #[cfg(feature = "test-harness")]
+        self.connect_fake_midi(config);
+
         Ok(())
     }
 
-    /// Start audio capture streams. Returns (audio_count, has_audio_triggers).
+    /// Thru and virtual-tap outputs for messages from `port_name`
+    fn forwards_for(&self, port_name: &str) -> Vec<Arc<MidiThru>> {
+        self.midi_forwards.iter()
+            .filter(|(input, _)| input == port_name)
+            .map(|(_, forward)| forward.clone())
+            .collect()
+    }
+
+    /// Handler for a thru input that is neither triggering nor recording
+    fn thru_midi_handler(&self, port_name: String) -> impl FnMut(u64, &[u8]) + Send + 'static {
+        let forwards = self.forwards_for(&port_name);
+
+        move |timestamp_us, message| {
+            for forward in &forwards {
+                forward.forward(message);
+            }
+            midi_tap::observe(&port_name, timestamp_us, message);
+        }
+    }
+
+    /// Handler for messages from a trigger device. Events are also recorded
+    /// when the device is selected for recording.
+    fn trigger_midi_handler(&self, port_name: String, also_record: bool) -> impl FnMut(u64, &[u8]) + Send + 'static {
+        let forwards = self.forwards_for(&port_name);
+        let app_handle = self.app_handle.clone();
+        let last_event_time = self.last_event_time.clone();
+        let capture_state = self.capture_state.clone();
+        let video_manager = self.video_manager.clone();
+        let clock = self.clock.clone();
+
+        move |timestamp_us, message| {
+            for forward in &forwards {
+                forward.forward(message);
+            }
+            midi_tap::observe(&port_name, timestamp_us, message);
+
+            // Only store events if this device is also marked for recording
+            if also_record {
+                let mut state = capture_state.lock();
+
+                // Use pre-roll if not recording OR if recording is starting (video init)
+                if state.should_use_preroll() {
+                    // Store in pre-roll buffer with driver timestamp for accurate timing
+                    let event = TimestampedMidiEvent {
+                        timestamp_us: 0,
+                        data: message.to_vec(),
+                    };
+                    state.midi_preroll.push(port_name.clone(), event, timestamp_us);
+                } else {
+                    // Recording is active, stream to disk
+                    let rel_time = state.start_time
+                        .map(|st| state.clock.elapsed(st).as_micros() as u64 + state.midi_timestamp_offset_us)
+                        .unwrap_or(state.midi_timestamp_offset_us);
+                    state.push_midi_event(
+                        &port_name,
+                        TimestampedMidiEvent {
+                            timestamp_us: rel_time,
+                            data: message.to_vec(),
+                        },
+                    );
+                }
+            }
+
+            // Check for note-on to trigger recording
+            if message.len() >= 3 {
+                let status = message[0] & 0xF0;
+                let velocity = message[2];
+
+                if status == 0x90 && velocity > 0 {
+                    let cause = TriggerCause::midi(&port_name, velocity);
+                    handle_trigger(&app_handle, &last_event_time, &capture_state, &video_manager, &clock, &cause);
+                }
+            }
+        }
+    }
+
+    /// Handler for messages from a record-only device
+    fn record_midi_handler(&self, port_name: String) -> impl FnMut(u64, &[u8]) + Send + 'static {
+        record_midi_callback(
+            port_name.clone(),
+            self.capture_state.clone(),
+            self.last_event_time.clone(),
+            self.forwards_for(&port_name),
+        )
+    }
+
+    /// A shared record device, connected by `start_recording` with a fresh
+    /// record handler each time
+    fn shared_midi_input(&self, port_name: String) -> SharedMidiInput {
+        let capture_state = self.capture_state.clone();
+        let last_event_time = self.last_event_time.clone();
+        let forwards = self.forwards_for(&port_name);
+        let name = port_name.clone();
+        SharedMidiInput {
+            port_name,
+            handler: Arc::new(move || {
+                Box::new(record_midi_callback(
+                    name.clone(),
+                    capture_state.clone(),
+                    last_event_time.clone(),
+                    forwards.clone(),
+                )) as Box<dyn FnMut(u64, &[u8]) + Send>
+            }),
+        }
+    }
+
+    /// Start audio capture streams. Returns (audio_count, has_audio_triggers).
     fn start_audio(&mut self, config: &Config) -> anyhow::Result<(usize, bool)> {
         println!("[Sacho] Audio record devices: {:?}", config.selected_audio_devices);
         println!("[Sacho] Audio trigger devices: {:?}", config.trigger_audio_devices);
 
-        let pre_roll_limit = if config.encode_during_preroll { MAX_PRE_ROLL_SECS_ENCODED } else { MAX_PRE_ROLL_SECS };
+        let pre_roll_limit = MAX_AUDIO_MIDI_PRE_ROLL_SECS;
         let host = cpal::default_host();
-        let pre_roll_secs = config.pre_roll_secs.min(pre_roll_limit);
+        let pre_roll_secs = config.effective_audio_midi_pre_roll_secs();
 
         // Build union of audio devices that need a cpal stream
         let mut audio_device_roles: HashMap<String, (bool, bool)> = HashMap::new(); // (is_record, is_trigger)
@@ -1572,8 +2253,17 @@ impl MidiMonitor {
         for name in &config.trigger_audio_devices {
             audio_device_roles.entry(name.clone()).or_insert((false, false)).1 = true;
         }
+        // Devices outside their record schedule stay closed until it opens
+        audio_device_roles.retain(|name, _| {
+            let off = config.is_scheduled_off(name);
+            if off {
+                println!("[Sacho] Audio device {} is outside its schedule, not opening", name);
+            }
+            !off
+        });
         let audio_trigger_thresholds = config.audio_trigger_thresholds.clone();
-        let has_audio_triggers = !config.trigger_audio_devices.is_empty();
+        let has_audio_triggers = audio_device_roles.values().any(|&(_, is_trigger)| is_trigger);
+        let mut contention = Vec::new();
 
         if let Ok(audio_devices) = host.input_devices() {
             for device in audio_devices {
@@ -1595,75 +2285,35 @@ impl MidiMonitor {
                         let sample_rate = supported_config.sample_rate().0;
                         let channels = supported_config.channels();
 
-                        // Create pre-roll buffer and writer slot only for record devices
-                        let buffer_index = if is_record {
-                            let mut state = self.capture_state.lock();
-
-                            state.audio_prerolls.push(AudioPrerollBuffer::with_limit(
-                                device_name.clone(),
-                                sample_rate,
-                                channels,
-                                pre_roll_secs,
-                                pre_roll_limit,
-                            ));
-                            state.audio_writers.push(None);
-
-                            Some(state.audio_prerolls.len() - 1)
-                        } else {
-                            None
-                        };
-
-                        // Create trigger state for trigger devices
-                        let trigger_index = if is_trigger {
-                            let threshold = audio_trigger_thresholds
-                                .get(&device_name)
-                                .copied()
-                                .unwrap_or(0.1); // Default threshold
-                            let mut state = self.capture_state.lock();
-                            state.audio_trigger_states.push(AudioTriggerState::new(
-                                device_name.clone(),
-                                threshold,
-                                sample_rate,
-                                channels,
-                            ));
-                            Some(state.audio_trigger_states.len() - 1)
-                        } else {
-                            None
-                        };
-
-                        let capture_state = self.capture_state.clone();
-                        let app_handle = self.app_handle.clone();
-                        let last_event_time = self.last_event_time.clone();
-                        let video_manager = self.video_manager.clone();
+                        let threshold = audio_trigger_thresholds
+                            .get(&device_name)
+                            .copied()
+                            .unwrap_or(0.1); // Default threshold
+                        let (buffer_index, trigger_index) = self.register_audio_device(
+                            &device_name,
+                            sample_rate,
+                            channels,
+                            is_record.then_some((pre_roll_secs, pre_roll_limit)),
+                            is_trigger.then_some(threshold),
+                        );
+                        let processor = config
+                            .audio_processing
+                            .get(&device_name)
+                            .and_then(|settings| AudioProcessor::new(settings, channels));
+                        if processor.is_some() {
+                            println!("[Sacho] Audio processing enabled for {}", device_name);
+                        }
+                        let mut handler = self.audio_data_handler(
+                            buffer_index,
+                            trigger_index,
+                            processor,
+                            DropoutDetector::new(sample_rate, channels),
+                        );
 
                         match device.build_input_stream(
                             &supported_config.into(),
-                            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                                let should_trigger = {
-                                    let mut state = capture_state.lock();
-
-                                    // Route audio to preroll/writer if this is a record device
-                                    if let Some(idx) = buffer_index {
-                                        if state.should_use_preroll() {
-                                            if let Some(preroll) = state.audio_prerolls.get_mut(idx) {
-                                                preroll.push_samples(data);
-                                            }
-                                        } else if let Some(Some(writer)) = state.audio_writers.get_mut(idx) {
-                                            writer.push_samples(data);
-                                        }
-                                    }
-
-                                    // Compute amplitude if this is a trigger device
-                                    if let Some(idx) = trigger_index {
-                                        state.audio_trigger_states[idx].process_samples(data)
-                                    } else {
-                                        false
-                                    }
-                                }; // lock released
-
-                                if should_trigger {
-                                    handle_trigger(&app_handle, &last_event_time, &capture_state, &video_manager);
-                                }
+                            move |data: &[f32], info: &cpal::InputCallbackInfo| {
+                                handler(data, Some(info.timestamp().capture))
                             },
                             |err| {
                                 println!("[Sacho] Audio error: {}", err);
@@ -1673,7 +2323,7 @@ impl MidiMonitor {
                             Ok(stream) => {
                                 if stream.play().is_ok() {
                                     AUDIO_STREAMS.with(|streams| {
-                                        streams.borrow_mut().push(stream);
+                                        streams.borrow_mut().push(AudioStream::Cpal(stream));
                                     });
                                     println!("[Sacho] Audio {} ready: {} ({}Hz, {}ch, {}s pre-roll)",
                                         role_str, device_name, sample_rate, channels, pre_roll_secs);
@@ -1681,23 +2331,232 @@ impl MidiMonitor {
                             }
                             Err(e) => {
                                 println!("[Sacho] Failed to create audio stream for {}: {}", device_name, e);
+                                contention.extend(crate::devices::contention::classify_start_failure(
+                                    &device_name, &device_name, "audio", &e.to_string(),
+                                ));
                             }
                         }
                     }
                 }
             }
         }
+        crate::devices::contention::report(&self.app_handle, "audio", contention);
+
+        // Listen for playback only when there is an audio trigger to suppress
+        if has_audio_triggers && config.suppress_triggers_during_playback {
+            if let Some(monitor_device) = &config.playback_monitor_device {
+                match playback_guard::open_monitor_stream(monitor_device, config.playback_monitor_threshold) {
+                    Ok(stream) => {
+                        PLAYBACK_MONITOR_STREAM.with(|slot| *slot.borrow_mut() = Some(stream));
+                        println!("[Sacho] Playback monitor ready: {}", monitor_device);
+                    }
+                    Err(e) => println!("[Sacho] Failed to open playback monitor {}: {}", monitor_device, e),
+                }
+            }
+        }
+
+        #[cfg(feature = "test-harness")]
+        self.start_fake_audio(&audio_device_roles, &audio_trigger_thresholds, &config.audio_processing, pre_roll_secs, pre_roll_limit);
 
         let audio_count = AUDIO_STREAMS.with(|streams| streams.borrow().len());
         Ok((audio_count, has_audio_triggers))
     }
 
+    /// Create the pre-roll buffer and writer slot (record devices) and the
+    /// trigger state (trigger devices) for an audio input.
+    /// Returns (buffer_index, trigger_index).
+    fn register_audio_device(
+        &self,
+        device_name: &str,
+        sample_rate: u32,
+        channels: u16,
+        record: Option<(u32, u32)>,
+        trigger_threshold: Option<f64>,
+    ) -> (Option<usize>, Option<usize>) {
+        let mut state = self.capture_state.lock();
+
+        let buffer_index = record.map(|(pre_roll_secs, pre_roll_limit)| {
+            state.audio_prerolls.push(AudioPrerollBuffer::with_limit(
+                device_name.to_string(),
+                sample_rate,
+                channels,
+                pre_roll_secs,
+                pre_roll_limit,
+            ));
+            state.audio_writers.push(Vec::new());
+            state.audio_prerolls.len() - 1
+        });
+
+        let trigger_index = trigger_threshold.map(|threshold| {
+            let trigger = AudioTriggerState::new(device_name.to_string(), threshold, sample_rate, channels)
+                .with_clock(state.clock.clone());
+            state.audio_trigger_states.push(trigger);
+            state.audio_trigger_states.len() - 1
+        });
+
+        (buffer_index, trigger_index)
+    }
+
+    /// Callback for a block of interleaved samples from an audio input, with
+    /// its capture timestamp when the backend provides one.
+    /// `processor` runs on recorded audio only; triggers see the raw input.
+    fn audio_data_handler(
+        &self,
+        buffer_index: Option<usize>,
+        trigger_index: Option<usize>,
+        processor: Option<AudioProcessor>,
+        mut dropouts: DropoutDetector,
+    ) -> impl FnMut(&[f32], Option<cpal::StreamInstant>) + Send + 'static {
+        let capture_state = self.capture_state.clone();
+        let app_handle = self.app_handle.clone();
+        let last_event_time = self.last_event_time.clone();
+        let video_manager = self.video_manager.clone();
+        let clock = self.clock.clone();
+
+        let mut processed: Vec<f32> = Vec::new();
+
+        move |raw: &[f32], capture: Option<cpal::StreamInstant>| {
+            let gap = capture.and_then(|capture| dropouts.check(capture, raw.len()));
+            let data = match &processor {
+                Some(processor) if buffer_index.is_some() => {
+                    processed.clear();
+                    processed.extend_from_slice(raw);
+                    processor.process(&mut processed);
+                    processed.as_slice()
+                }
+                _ => raw,
+            };
+
+            let trigger_cause = {
+                let mut state = capture_state.lock();
+
+                // Route audio to preroll/writer if this is a record device
+                if let Some(idx) = buffer_index {
+                    if state.should_use_preroll() {
+                        if let Some(preroll) = state.audio_prerolls.get_mut(idx) {
+                            if let Some(gap) = gap {
+                                let concealed = gap.min(crate::recording::quality::MAX_CONCEALMENT);
+                                preroll.push_silence_frames(
+                                    (concealed.as_secs_f64() * preroll.sample_rate() as f64).round() as usize,
+                                );
+                            }
+                            preroll.push_samples(data);
+                        }
+                    } else {
+                        if let Some(writers) = state.audio_writers.get_mut(idx) {
+                            for writer in writers.iter_mut() {
+                                if let Some(gap) = gap {
+                                    writer.conceal_dropout(gap);
+                                }
+                                writer.push_samples(data);
+                            }
+                        }
+                        if let Some((tap_idx, tap)) = &state.stream_audio_tap {
+                            if *tap_idx == idx {
+                                tap.push_audio(data);
+                            }
+                        }
+                    }
+                }
+
+                // Compute amplitude if this is a trigger device
+                trigger_index.and_then(|idx| {
+                    let trigger = &mut state.audio_trigger_states[idx];
+                    trigger
+                        .process_samples(raw)
+                        .map(|rms| TriggerCause::audio(&trigger.device_name, rms, trigger.threshold))
+                })
+            }; // lock released
+
+            if let Some(cause) = trigger_cause {
+                handle_trigger(&app_handle, &last_event_time, &capture_state, &video_manager, &clock, &cause);
+            }
+        }
+    }
+
+    /// Connect simulated MIDI devices (test harness only)
+    #[cfg(feature = "test-harness")]
+    fn connect_fake_midi(&mut self, config: &Config) {
+        use crate::test_harness::fake_devices::{self, FakeMidiConnection};
+
+        for device in fake_devices::midi_devices() {
+            if config.trigger_midi_devices.contains(&device.id) {
+                let also_record = config.selected_midi_devices.contains(&device.id);
+                let handler = self.trigger_midi_handler(device.name.clone(), also_record);
+                self.trigger_connections
+                    .push(MidiConnection::Fake(FakeMidiConnection::connect(&device.name, handler)));
+                println!("[Sacho] Connected to simulated trigger: {}", device.name);
+            } else if config.selected_midi_devices.contains(&device.id) {
+                let handler = self.record_midi_handler(device.name.clone());
+                self.capture_connections
+                    .push(MidiConnection::Fake(FakeMidiConnection::connect(&device.name, handler)));
+                println!("[Sacho] Connected to simulated record device: {}", device.name);
+            }
+        }
+    }
+
+    /// Start simulated audio inputs (test harness only)
+    #[cfg(feature = "test-harness")]
+    fn start_fake_audio(
+        &mut self,
+        audio_device_roles: &HashMap<String, (bool, bool)>,
+        audio_trigger_thresholds: &HashMap<String, f64>,
+        audio_processing: &HashMap<String, crate::config::AudioProcessing>,
+        pre_roll_secs: u32,
+        pre_roll_limit: u32,
+    ) {
+        use crate::test_harness::fake_devices::{self, FakeAudioStream};
+
+        for device in fake_devices::audio_sources() {
+            let Some(&(is_record, is_trigger)) = audio_device_roles.get(&device.name) else {
+                continue;
+            };
+            let threshold = audio_trigger_thresholds.get(&device.name).copied().unwrap_or(0.1);
+            let (buffer_index, trigger_index) = self.register_audio_device(
+                &device.name,
+                device.sample_rate,
+                device.channels,
+                is_record.then_some((pre_roll_secs, pre_roll_limit)),
+                is_trigger.then_some(threshold),
+            );
+            let processor = audio_processing
+                .get(&device.name)
+                .and_then(|settings| AudioProcessor::new(settings, device.channels));
+            let mut handler = self.audio_data_handler(
+                buffer_index,
+                trigger_index,
+                processor,
+                DropoutDetector::new(device.sample_rate, device.channels),
+            );
+            let stream = FakeAudioStream::start(&device, move |data| handler(data, None));
+            AUDIO_STREAMS.with(|streams| {
+                streams.borrow_mut().push(AudioStream::Fake(stream));
+            });
+            println!("[Sacho] Simulated audio ready: {} ({}Hz, {}ch)", device.name, device.sample_rate, device.channels);
+        }
+    }
+
     /// Start video capture pipelines. Returns the number of active video pipelines.
     fn start_video_pipeline(&mut self, config: &Config) -> anyhow::Result<usize> {
-        let pre_roll_limit = if config.encode_during_preroll { MAX_PRE_ROLL_SECS_ENCODED } else { MAX_PRE_ROLL_SECS };
+        // Cameras stay off for the rest of a privacy pause
+        if crate::privacy::cameras_released() {
+            println!("[Sacho] Privacy pause active, cameras left off");
+            return Ok(0);
+        }
+
         let encode_during_preroll = config.encode_during_preroll;
-        let selected_video = config.selected_video_devices.clone();
-        let pre_roll = config.pre_roll_secs.min(pre_roll_limit);
+        let selected_video: Vec<String> = config.selected_video_devices
+            .iter()
+            .filter(|device_id| {
+                let off = config.is_scheduled_off(device_id);
+                if off {
+                    println!("[Sacho] Video device {} is outside its schedule, not opening", device_id);
+                }
+                !off
+            })
+            .cloned()
+            .collect();
+        let pre_roll = config.effective_video_pre_roll_secs();
 
         // Look up per-device config and name for each selected video device
         let device_manager = self.app_handle.state::<RwLock<DeviceManager>>();
@@ -1737,18 +2596,54 @@ impl MidiMonitor {
             })
             .collect();
 
+        let known_devices = devices.video_devices.clone();
         drop(devices); // Release device manager lock
 
         let mut video_mgr = self.video_manager.lock();
         video_mgr.set_preroll_duration(pre_roll);
         video_mgr.set_encode_during_preroll(encode_during_preroll);
+        video_mgr.set_prewarm_writers(config.prewarm_video_writer);
+        video_mgr.set_known_devices(known_devices);
 
         if !video_with_info.is_empty() {
             if let Err(e) = video_mgr.start(&video_with_info, config.preferred_video_container) {
                 println!("[Sacho] Failed to start video capture: {}", e);
             }
         }
-        Ok(video_mgr.pipeline_count())
+
+        if config.virtual_camera.enabled && video_mgr.pipeline_count() > 0 {
+            let vcam = &config.virtual_camera;
+            match video_mgr.attach_virtual_camera(
+                vcam.video_device.as_deref(),
+                &selected_video,
+                &vcam.output_device,
+                vcam.stats_overlay,
+            ) {
+                Ok(name) => println!("[Sacho] Virtual camera publishing {}", name),
+                Err(e) => {
+                    println!("[Sacho] Virtual camera unavailable: {}", e);
+                    let _ = self.app_handle.emit("virtual-camera-error", e.to_string());
+                }
+            }
+        }
+        let pipeline_count = video_mgr.pipeline_count();
+        let failures = video_mgr.take_start_failures();
+        let fallbacks = video_mgr.active_fallbacks();
+        drop(video_mgr);
+
+        for fallback in &fallbacks {
+            notifications::notify_video_fallback(&self.app_handle, fallback);
+        }
+
+        let contention = failures
+            .iter()
+            .filter_map(|f| {
+                crate::devices::contention::classify_start_failure(&f.device_id, &f.device_name, "video", &f.error)
+            })
+            .collect();
+        crate::devices::contention::report(&self.app_handle, "video", contention);
+
+        Ok(pipeline_count)
     }
     
     /// Start background thread to poll video frames
@@ -1771,6 +2666,23 @@ impl MidiMonitor {
                         for warning in warnings {
                             let _ = app_handle.emit("video-fps-warning", warning);
                         }
+
+                        for failover in mgr.collect_encoder_failovers() {
+                            notifications::notify_encoder_failover(&app_handle, &failover);
+                            let _ = app_handle.emit("video-encoder-failover", failover);
+                        }
+
+                        for adaptation in mgr.collect_pending_adaptations() {
+                            notifications::notify_encoding_adapted(&app_handle, &adaptation);
+                            let _ = app_handle.emit("video-encoding-adapted", adaptation);
+                        }
+
+                        for warning in mgr.collect_blank_warnings() {
+                            println!("[Sacho] {} is delivering black frames (mean luma {:.1})",
+                                warning.device_name, warning.mean_luma);
+                            notifications::notify_video_blank(&app_handle, &warning.device_name);
+                            let _ = app_handle.emit("video-blank-warning", warning);
+                        }
                     }
                     std::thread::sleep(Duration::from_millis(10)); // Poll at ~100Hz
                 }
@@ -1831,10 +2743,17 @@ impl MidiMonitor {
     fn stop_midi(&mut self) {
         self.trigger_connections.clear();
         self.capture_connections.clear();
+        self.midi_forwards.clear();
+
+        // Dropping a connection waits for its callback, which takes the lock
+        let shared_connections = std::mem::take(&mut self.capture_state.lock().shared_midi_connections);
+        drop(shared_connections);
 
         let mut state = self.capture_state.lock();
+        state.shared_midi_inputs.clear();
         state.midi_writers.clear();
         state.midi_preroll.clear();
+        state.midi_feedback = None;
     }
 
     /// Stop only the audio streams and clear audio capture state
@@ -1849,6 +2768,7 @@ impl MidiMonitor {
         AUDIO_STREAMS.with(|streams| {
             streams.borrow_mut().clear();
         });
+        PLAYBACK_MONITOR_STREAM.with(|stream| stream.borrow_mut().take());
 
         // Clear audio capture state
         let mut state = self.capture_state.lock();
@@ -1982,8 +2902,48 @@ impl MidiMonitor {
         Ok(())
     }
     
+    /// Test a proposed video config on the real device. Capture is paused for
+    /// the duration since the device can only be opened once.
+    pub fn preflight_video(
+        &mut self,
+        device_id: &str,
+        dev_config: &crate::config::VideoDeviceConfig,
+    ) -> Result<super::preflight::VideoPreflightReport, String> {
+        if self.is_recording() || self.is_starting() {
+            return Err("Can't test a video device while recording".to_string());
+        }
+
+        let device_name = {
+            let device_manager = self.app_handle.state::<RwLock<DeviceManager>>();
+            let devices = device_manager.read();
+            devices.video_devices.iter()
+                .find(|d| d.id == device_id)
+                .map(|d| d.name.clone())
+                .ok_or_else(|| format!("Video device not found: {}", device_id))?
+        };
+        let preferred_container = self.app_handle.state::<RwLock<Config>>().read().preferred_video_container;
+
+        let was_capturing = self.video_manager.lock().pipeline_count() > 0;
+        if was_capturing {
+            self.stop_video();
+        }
+
+        let report = super::preflight::run_video_preflight(device_id, &device_name, dev_config, preferred_container);
+
+        if was_capturing {
+            if let Err(e) = self.restart_video() {
+                println!("[Sacho] Failed to restart video after preflight: {}", e);
+            }
+        }
+        Ok(report)
+    }
+
     /// Manually start recording (same as MIDI trigger but without waiting for MIDI)
     pub fn manual_start_recording(&self) -> Result<(), String> {
+        if crate::read_only::is_read_only() {
+            return Err(crate::read_only::refusal());
+        }
+
         // Check that at least one device is active
         let midi_count = self.trigger_connections.len() + self.capture_connections.len();
         let audio_count = AUDIO_STREAMS.with(|streams| streams.borrow().len());
@@ -1996,9 +2956,12 @@ impl MidiMonitor {
         // Atomically check and set is_starting to prevent race conditions
         {
             let mut state = self.capture_state.lock();
-            if state.is_recording || state.is_starting {
+            if state.is_recording {
                 return Err("Already recording".to_string());
             }
+            if state.is_starting {
+                return Err("A recording is already starting".to_string());
+            }
             state.is_starting = true;
         }
         
@@ -2013,7 +2976,7 @@ impl MidiMonitor {
         *self.last_event_time.write() = None;
         
         // Start recording (synchronous for manual start so caller knows when it's ready)
-        start_recording(&self.app_handle, &self.capture_state, &self.video_manager);
+        start_recording(&self.app_handle, &self.capture_state, &self.video_manager, Instant::now());
         
         Ok(())
     }
@@ -2039,6 +3002,18 @@ impl MidiMonitor {
     pub fn is_recording(&self) -> bool {
         self.capture_state.lock().is_recording
     }
+
+    /// Check if a recording is in the middle of starting
+    pub fn is_starting(&self) -> bool {
+        self.capture_state.lock().is_starting
+    }
+
+    /// Current audio trigger levels as (device name, RMS, 3s peak)
+    pub fn audio_levels(&self) -> Vec<(String, f32, f32)> {
+        self.capture_state.lock().audio_trigger_states.iter()
+            .map(|ts| (ts.device_name.clone(), ts.current_rms, ts.current_peak_level))
+            .collect()
+    }
     
     /// Start idle timeout checker thread
     fn start_idle_checker(&mut self) {
@@ -2048,6 +3023,7 @@ impl MidiMonitor {
         let stop_flag = self.idle_checker_stop.clone();
         let capture_state = self.capture_state.clone();
         let video_manager = self.video_manager.clone();
+        let clock = self.clock.clone();
 
         let handle = std::thread::Builder::new()
             .name("sacho-idle-checker".into())
@@ -2072,13 +3048,13 @@ impl MidiMonitor {
                         // This prevents a stale last_event_time from immediately stopping
                         // a recording that took a while to initialize (e.g., slow camera)
                         if let Some(started_at) = recording_started_at {
-                            if started_at.elapsed() < Duration::from_secs(idle_timeout as u64) {
+                            if clock.elapsed(started_at) < Duration::from_secs(idle_timeout as u64) {
                                 continue;
                             }
                         }
 
                         if let Some(last_time) = *last_event_time.read() {
-                            if last_time.elapsed() >= Duration::from_secs(idle_timeout as u64) {
+                            if clock.elapsed(last_time) >= Duration::from_secs(idle_timeout as u64) {
                                 println!("[Sacho] Idle timeout ({} sec), stopping recording", idle_timeout);
                                 stop_recording(&app_handle, &capture_state, &video_manager);
                             }
@@ -2105,9 +3081,29 @@ fn handle_trigger(
     last_event_time: &Arc<RwLock<Option<Instant>>>,
     capture_state: &Arc<Mutex<CaptureState>>,
     video_manager: &Arc<Mutex<VideoCaptureManager>>,
+    clock: &SharedClock,
+    cause: &TriggerCause,
 ) {
+    let triggered_at = Instant::now();
+
+    // Nothing counts as a trigger or as activity during a privacy pause
+    if crate::privacy::is_paused() {
+        trigger_log::record(cause, TriggerOutcome::PrivacyPaused);
+        return;
+    }
+
+    // Sound from the speakers is neither a trigger nor activity that keeps a
+    // recording going
+    if cause.source == TriggerSource::Audio && playback_guard::is_playback_active() {
+        let config = app_handle.state::<RwLock<Config>>();
+        if config.read().suppress_triggers_during_playback {
+            trigger_log::record(cause, TriggerOutcome::SuppressedPlayback);
+            return;
+        }
+    }
+
     // Update last event time
-    *last_event_time.write() = Some(Instant::now());
+    *last_event_time.write() = Some(clock.now());
     
     // Check if the global recording state allows starting
     // (e.g., we're not in Initializing mode from a device config change)
@@ -2116,6 +3112,7 @@ fn handle_trigger(
         let state = recording_state.read();
         if state.status == crate::recording::RecordingStatus::Initializing {
             // Silently ignore MIDI triggers during device reinitialization
+            trigger_log::record(cause, TriggerOutcome::Initializing);
             return;
         }
     }
@@ -2128,6 +3125,7 @@ fn handle_trigger(
             && config_read.selected_midi_devices.is_empty()
             && config_read.selected_video_devices.is_empty()
         {
+            trigger_log::record(cause, TriggerOutcome::NoDevicesSelected);
             return;
         }
     }
@@ -2143,7 +3141,10 @@ fn handle_trigger(
         }
     };
     
-    if should_start {
+    if !should_start {
+        trigger_log::record(cause, TriggerOutcome::AlreadyRecording);
+    } else {
+        trigger_log::record(cause, TriggerOutcome::Started);
         println!("[Sacho] Trigger -> starting recording (async)");
         
         // Spawn recording start on a separate thread so MIDI callback isn't blocked
@@ -2152,58 +3153,196 @@ fn handle_trigger(
         let capture_state = capture_state.clone();
         let video_manager = video_manager.clone();
         std::thread::spawn(move || {
-            start_recording(&app_handle, &capture_state, &video_manager);
+            start_recording(&app_handle, &capture_state, &video_manager, triggered_at);
         });
     }
 }
 
-/// Start recording
+/// Payload of the "session-timeline-warnings" event
+#[derive(Debug, Clone, serde::Serialize)]
+struct TimelineWarningsEvent {
+    session_id: String,
+    warnings: Vec<crate::session::TimelineWarning>,
+}
+
+/// Payload of the "session-quality-issues" event
+#[derive(Debug, Clone, serde::Serialize)]
+struct QualityIssuesEvent {
+    session_id: String,
+    quality: crate::session::SessionQuality,
+}
+
+/// Payload of the "session-chapters" event
+#[derive(Debug, Clone, serde::Serialize)]
+struct ChaptersEvent {
+    session_id: String,
+    chapters: Vec<crate::session::Chapter>,
+}
+
+/// Detect chapters in a finished session's MIDI, save them with the session
+/// and write them into its Matroska video files
+fn write_session_chapters(app_handle: &AppHandle, metadata: &SessionMetadata) {
+    let midi_paths: Vec<PathBuf> = metadata
+        .midi_files
+        .iter()
+        .map(|m| metadata.path.join(&m.filename))
+        .collect();
+    let refs: Vec<&std::path::Path> = midi_paths.iter().map(|p| p.as_path()).collect();
+    let chapters = crate::similarity::chapters::detect_chapters(&refs, metadata.duration_secs);
+    if chapters.is_empty() {
+        return;
+    }
+    println!("[Sacho] Detected {} chapters in {}", chapters.len(), metadata.id);
+
+    if let Err(e) = crate::session::write_session_chapters(&metadata.path, &chapters) {
+        println!("[Sacho] Failed to save chapters: {}", e);
+        return;
+    }
+    for video in &metadata.video_files {
+        let path = metadata.path.join(&video.filename);
+        if !crate::encoding::chapters::supports_chapters(&path) {
+            continue;
+        }
+        if let Err(e) = crate::encoding::chapters::embed_chapters(&path, &chapters, video.start_offset_secs) {
+            println!("[Sacho] Failed to write chapters into {}: {}", video.filename, e);
+        }
+    }
+
+    let _ = app_handle.emit("session-chapters", ChaptersEvent {
+        session_id: metadata.id.clone(),
+        chapters,
+    });
+}
+
+fn writer_latency(
+    stream: crate::session::LatencyStream,
+    device_name: &str,
+    triggered_at: Instant,
+    first_write: Instant,
+) -> crate::session::WriterLatency {
+    crate::session::WriterLatency {
+        stream,
+        device_name: device_name.to_string(),
+        latency_ms: first_write.saturating_duration_since(triggered_at).as_secs_f64() * 1000.0,
+    }
+}
+
+/// Callback recording a MIDI device's messages (pre-roll while idle,
+/// streamed to its file while recording), after forwarding them
+fn record_midi_callback(
+    port_name: String,
+    capture_state: Arc<Mutex<CaptureState>>,
+    last_event_time: Arc<RwLock<Option<Instant>>>,
+    forwards: Vec<Arc<MidiThru>>,
+) -> impl FnMut(u64, &[u8]) + Send + 'static {
+    move |timestamp_us, message| {
+        for forward in &forwards {
+            forward.forward(message);
+        }
+        midi_tap::observe(&port_name, timestamp_us, message);
+
+        let mut state = capture_state.lock();
+
+        // Update last event time for idle detection (even during pre-roll)
+        if message.len() >= 3 {
+            let status = message[0] & 0xF0;
+            if status == 0x90 || status == 0x80 {
+                *last_event_time.write() = Some(state.clock.now());
+            }
+        }
+
+        // Use pre-roll if not recording OR if recording is starting (video init)
+        if state.should_use_preroll() {
+            // Store in pre-roll buffer with driver timestamp for accurate timing
+            state.midi_preroll.push(
+                port_name.clone(),
+                TimestampedMidiEvent {
+                    timestamp_us: 0,
+                    data: message.to_vec(),
+                },
+                timestamp_us,
+            );
+        } else {
+            // Recording is active, stream to disk
+            let rel_time = state.start_time
+                .map(|st| state.clock.elapsed(st).as_micros() as u64 + state.midi_timestamp_offset_us)
+                .unwrap_or(state.midi_timestamp_offset_us);
+            state.push_midi_event(
+                &port_name,
+                TimestampedMidiEvent {
+                    timestamp_us: rel_time,
+                    data: message.to_vec(),
+                },
+            );
+        }
+    }
+}
+
+/// Start recording. `triggered_at` is when the trigger (or manual start)
+/// was detected, the reference for startup latency.
 fn start_recording(
     app_handle: &AppHandle, 
     capture_state: &Arc<Mutex<CaptureState>>,
     video_manager: &Arc<Mutex<VideoCaptureManager>>,
+    triggered_at: Instant,
 ) {
-    let config = app_handle.state::<RwLock<Config>>();
-    let config_read = config.read().clone();
-    
-    let now = chrono::Local::now();
-    let timestamp = now.format("%Y-%m-%d_%H-%M-%S").to_string();
-    let tz_abbr = crate::session::local_timezone_abbreviation(&now);
-    let folder_name = format!("{} {}", timestamp, tz_abbr);
-    let session_path = config_read.storage_path.join(&folder_name);
-    
-    if let Err(e) = std::fs::create_dir_all(&session_path) {
-        println!("[Sacho] Failed to create session folder: {}", e);
-        // Reset is_starting flag so future recording attempts can work
+    // Don't open new files while the app is finalizing to exit
+    if crate::shutdown::is_shutting_down() {
+        println!("[Sacho] Shutting down, not starting a new recording");
         capture_state.lock().is_starting = false;
         return;
     }
 
-    // Create recording lock file
-    if let Err(e) = crate::session::create_recording_lock(&session_path) {
-        println!("[Sacho] Warning: Failed to create recording lock: {}", e);
+    let config = app_handle.state::<RwLock<Config>>();
+    let config_read = config.read().clone();
+    
+    // Claim a fresh, locked folder so two recordings can never share files
+    let session_root = crate::session::select_new_session_root(&config_read);
+    let session_path = match crate::session::claim_session_folder(&session_root, chrono::Local::now()) {
+        Ok(path) => path,
+        Err(e) => {
+            println!("[Sacho] Failed to create session folder: {}", e);
+            // Reset is_starting flag so future recording attempts can work
+            capture_state.lock().is_starting = false;
+            return;
+        }
+    };
+    if let Err(e) = crate::session::write_session_owner(&session_path) {
+        println!("[Sacho] Warning: Failed to record session owner: {}", e);
+    }
+    if crate::session::ensure_session_uid(&session_path).is_none() {
+        println!("[Sacho] Warning: Failed to assign session uid");
+    }
+    let fallbacks = video_manager.lock().active_fallbacks();
+    if let Err(e) = crate::session::write_video_fallbacks(&session_path, &fallbacks) {
+        println!("[Sacho] Warning: Failed to record video fallbacks: {}", e);
     }
 
-    // Spawn heartbeat thread to refresh lock every 60 seconds
+    // Spawn heartbeat thread to refresh the lock and checkpoint progress
+    // every 30 seconds
     let heartbeat_path = session_path.clone();
     let heartbeat_stop = Arc::new(AtomicBool::new(false));
     let heartbeat_flag = heartbeat_stop.clone();
+    let heartbeat_state = capture_state.clone();
+    let heartbeat_video = video_manager.clone();
     std::thread::spawn(move || {
         while !heartbeat_flag.load(Ordering::Relaxed) {
-            std::thread::sleep(Duration::from_secs(60));
+            std::thread::sleep(CHECKPOINT_INTERVAL);
             if heartbeat_flag.load(Ordering::Relaxed) { break; }
             crate::session::touch_recording_lock(&heartbeat_path);
+            write_recording_checkpoint(&heartbeat_state, &heartbeat_video, &heartbeat_path);
         }
     });
 
     // Capture the instant BEFORE video starts - this is our sync reference point
     // The video pre-roll duration is relative to this instant
-    let video_start_instant = Instant::now();
+    let clock = capture_state.lock().clock.clone();
+    let video_start_instant = clock.now();
     
     // Start video recording (this captures pre-roll and begins file writing)
-    let video_preroll_duration = {
+    let (video_preroll_duration, video_device_names) = {
         let mut mgr = video_manager.lock();
-        match mgr.start_recording(&session_path) {
+        let duration = match mgr.start_recording(&session_path, &config_read.filename_templates) {
             Ok(duration) => {
                 println!("[Sacho] Video recording started with {:?} pre-roll", duration);
                 Some(duration)
@@ -2212,11 +3351,21 @@ fn start_recording(
                 println!("[Sacho] Failed to start video recording: {}", e);
                 None
             }
-        }
+        };
+        (duration, mgr.device_names())
     };
     
+    // Native bit depth per audio device, looked up before taking the capture lock
+    let native_bit_depths: HashMap<String, Option<crate::config::AudioBitDepth>> = app_handle
+        .state::<RwLock<DeviceManager>>()
+        .read()
+        .audio_devices
+        .iter()
+        .map(|d| (d.name.clone(), d.native_bit_depth.clone()))
+        .collect();
+
     // Capture a single trigger instant for consistent timing across all streams
-    let trigger_instant = Instant::now();
+    let trigger_instant = clock.now();
     
     // Initialize capture state and drain pre-roll buffers
     {
@@ -2228,6 +3377,7 @@ fn start_recording(
         let audio_preroll_duration = state.audio_prerolls.first().map(|_preroll| {
             configured_preroll
         });
+        let has_midi_recording = !config_read.selected_midi_devices.is_empty();
         
         // SYNC FIX: Calculate the correct audio pre-roll to align with video
         // 
@@ -2239,27 +3389,76 @@ fn start_recording(
         //
         // Total audio pre-roll = video_preroll + delay_since_video_start
         // This ensures the first video frame and first audio sample represent the same moment
-        let delay_since_video_start = video_start_instant.elapsed();
-        
-        let sync_preroll_duration = match (audio_preroll_duration, video_preroll_duration) {
+        let delay_since_video_start = clock.elapsed(video_start_instant);
+
+        // Calibrated camera latency: a late camera's first frame shows an
+        // earlier moment, so the other streams need that much more pre-roll.
+        // With several cameras the pre-roll can only match one, so use the mean.
+        state.sync_offsets_ms = config_read.device_sync_offsets_ms.clone();
+        // Bluetooth MIDI arrives a connection interval or two late; assume
+        // the typical delay unless the device has been calibrated
+        for port in state.bluetooth_midi_ports.clone() {
+            if config_read.sync_offset_ms(&port) == 0 {
+                state.sync_offsets_ms.insert(port, crate::devices::bluetooth::DEFAULT_LATENCY_MS);
+            }
+        }
+        state.filename_templates = config_read.filename_templates.clone();
+        state.midi_file = config_read.midi_file.clone();
+        state.take = crate::session::filenames::take_number(&session_path);
+        let video_offsets: Vec<i32> = video_device_names
+            .iter()
+            .map(|name| config_read.sync_offset_ms(name))
+            .collect();
+        let video_offset_ms = if video_offsets.is_empty() {
+            0
+        } else {
+            video_offsets.iter().sum::<i32>() / video_offsets.len() as i32
+        };
+        let apply_video_offset = |dur: Duration| {
+            if video_offset_ms >= 0 {
+                dur + Duration::from_millis(video_offset_ms as u64)
+            } else {
+                dur.saturating_sub(Duration::from_millis(video_offset_ms.unsigned_abs() as u64))
+            }
+        };
+
+        // Audio and MIDI pre-roll may be longer than video's. The session
+        // then starts where the audio/MIDI pre-roll does, and the video files
+        // start `video_lead` into it.
+        let (sync_preroll_duration, video_lead) = match (audio_preroll_duration, video_preroll_duration) {
             (Some(audio_dur), Some(video_dur)) => {
                 // Add the delay since video STARTED to get the correct audio pre-roll
                 // This accounts for the ~340ms that video processing takes
-                let adjusted_video_dur = video_dur + delay_since_video_start;
-                // Use the minimum to avoid requesting more audio than we have
-                let sync_dur = audio_dur.min(adjusted_video_dur);
+                let adjusted_video_dur = apply_video_offset(video_dur + delay_since_video_start);
+                // The audio pre-roll can't be stretched, so it sets the
+                // session start. Whatever it reaches back past the first video
+                // frame becomes the video's lead.
+                let video_lead = audio_dur.saturating_sub(adjusted_video_dur);
+                let sync_dur = audio_dur;
                 
-                println!("[Sacho] SYNC: video_preroll={:?}, delay={:?}, adjusted={:?}, audio={:?}, using={:?}", 
-                    video_dur, delay_since_video_start, adjusted_video_dur, audio_dur, sync_dur);
-                Some(sync_dur)
+                println!("[Sacho] SYNC: video_preroll={:?}, delay={:?}, adjusted={:?}, audio={:?}, using={:?}, video_lead={:?}", 
+                    video_dur, delay_since_video_start, adjusted_video_dur, audio_dur, sync_dur, video_lead);
+                (Some(sync_dur), video_lead)
             }
-            (Some(audio_dur), None) => Some(audio_dur), // No video, use audio
-            (None, Some(video_dur)) => Some(video_dur + delay_since_video_start), // No audio, use adjusted video
-            (None, None) => None,
+            (Some(audio_dur), None) => (Some(audio_dur), Duration::ZERO), // No video, use audio
+            (None, Some(video_dur)) => {
+                // No audio: MIDI can reach back as far as its own pre-roll
+                let adjusted_video_dur = apply_video_offset(video_dur + delay_since_video_start);
+                let video_lead = if has_midi_recording {
+                    configured_preroll.saturating_sub(adjusted_video_dur)
+                } else {
+                    Duration::ZERO
+                };
+                (Some(adjusted_video_dur + video_lead), video_lead)
+            }
+            (None, None) => (None, Duration::ZERO),
         };
+        state.video_lead = video_lead;
         
         // Drain pre-roll MIDI buffer with sync duration
         // This ensures MIDI timestamps align with the synchronized pre-roll start
+        // Programs selected before the pre-roll window are written at tick 0
+        state.start_programs = state.midi_preroll.programs_before(sync_preroll_duration);
         let preroll_events = state.midi_preroll.drain_with_audio_sync(sync_preroll_duration);
         let midi_preroll_count = preroll_events.len();
         
@@ -2267,27 +3466,42 @@ fn start_recording(
         state.midi_writers.clear();
         for (device_name, _event) in &preroll_events {
             if !state.midi_writers.contains_key(device_name.as_str()) {
-                let safe_name = crate::session::sanitize_device_name(device_name);
-                let filename = format!("midi_{}.mid", safe_name);
-                match MidiStreamWriter::new(&session_path, &filename, device_name) {
-                    Ok(writer) => { state.midi_writers.insert(device_name.clone(), writer); }
-                    Err(e) => { println!("[Sacho] Failed to create MIDI writer for {}: {}", device_name, e); }
-                }
+                state.open_midi_writer(&session_path, device_name);
             }
         }
         for (device_name, event) in preroll_events {
+            let event = state.sync_corrected(&device_name, event);
             if let Some(writer) = state.midi_writers.get_mut(&device_name) {
                 writer.push_event(&event);
             }
         }
+        // Only writers flushed with pre-roll count: one opened later, on a
+        // device's first live event, times the player rather than startup
+        state.triggered_at = Some(triggered_at);
+        let midi_latency: Vec<_> = state
+            .midi_writers
+            .keys()
+            .map(|device| writer_latency(crate::session::LatencyStream::Midi, device, triggered_at, Instant::now()))
+            .collect();
+        state.startup_latency = midi_latency;
         
         // Create streaming audio writers and drain pre-roll into them
         // Read audio format config
         let audio_format = config_read.audio_format.clone();
-        let (bit_depth, sample_rate_setting) = match audio_format {
-            crate::config::AudioFormat::Wav => (config_read.wav_bit_depth.clone(), config_read.wav_sample_rate.clone()),
-            crate::config::AudioFormat::Flac => (config_read.flac_bit_depth.clone(), config_read.flac_sample_rate.clone()),
-        };
+        
+        // Devices at different rates are either written at one common rate
+        // or reported, so the mismatch isn't discovered in the DAW
+        let device_rates: Vec<crate::recording::sample_rate::DeviceRate> = state.audio_prerolls.iter()
+            .map(|p| crate::recording::sample_rate::DeviceRate {
+                device_name: p.device_name().to_string(),
+                rate: p.sample_rate(),
+            })
+            .collect();
+        let rates: Vec<u32> = device_rates.iter().map(|d| d.rate).collect();
+        let common_rate = crate::recording::sample_rate::common_rate(&config_read, &rates);
+        for warning in crate::recording::sample_rate::check(&config_read, &device_rates) {
+            println!("[Sacho] Sample rate: {:?}", warning);
+        }
         
         let extension = match audio_format {
             crate::config::AudioFormat::Wav => "wav",
@@ -2298,9 +3512,28 @@ fn start_recording(
         let mut audio_preroll_samples = 0;
         
         for i in 0..num_audio_devices {
-            // Drain pre-roll samples
+            // Drain pre-roll samples. A late device drops its latency from the
+            // start of the pre-roll; an early one keeps that much more.
+            let mut start_offset = Duration::ZERO;
             let preroll_samples = if let Some(sync_dur) = sync_preroll_duration {
-                state.audio_prerolls[i].drain_duration(sync_dur)
+                let offset_ms = config_read.sync_offset_ms(state.audio_prerolls[i].device_name());
+                let offset = Duration::from_millis(offset_ms.unsigned_abs() as u64);
+                let device_dur = if offset_ms > 0 {
+                    sync_dur.saturating_sub(offset)
+                } else {
+                    sync_dur + offset
+                };
+                let samples = state.audio_prerolls[i].drain_duration(device_dur);
+                // A buffer holding less than asked for (device opened just
+                // before the trigger) starts the file that much later
+                let frames = samples.len() / state.audio_prerolls[i].channels().max(1) as usize;
+                let drained = Duration::from_secs_f64(frames as f64 / state.audio_prerolls[i].sample_rate() as f64);
+                let short = device_dur.saturating_sub(drained);
+                // Ignore the rounding to whole frames
+                if short >= Duration::from_millis(1) {
+                    start_offset = short;
+                }
+                samples
             } else {
                 state.audio_prerolls[i].drain()
             };
@@ -2309,25 +3542,58 @@ fn start_recording(
             // Create streaming writer using device info from preroll buffer
             let dev_name = state.audio_prerolls[i].device_name().to_string();
 
-            // Build filename with embedded device name
-            let safe_name = crate::session::sanitize_device_name(&dev_name);
-            let filename = format!("audio_{}.{}", safe_name, extension);
+            // Build filename from the template, with the device's alias if it has one
+            let label = crate::devices::aliases::label(crate::devices::aliases::DeviceKind::Audio, &dev_name, None);
             let native_rate = state.audio_prerolls[i].sample_rate();
+            let output_rate = crate::recording::sample_rate::output_rate(&config_read, native_rate, common_rate);
             let channels = state.audio_prerolls[i].channels();
+            let bit_depth = config_read.bit_depth_for(native_bit_depths.get(&dev_name).and_then(|d| d.as_ref()));
+            println!("[Sacho] Recording {} at {:?}", dev_name, bit_depth);
+            let filename_for = |device: &str, channels: u16| {
+                crate::session::filenames::audio_filename(
+                    &state.filename_templates,
+                    &crate::session::filenames::FileNameContext {
+                        device,
+                        codec: extension,
+                        channels: Some(channels),
+                        take: state.take,
+                    },
+                    extension,
+                )
+            };
+
+            // One file per configured stem, or a single interleaved file
+            let stems = config_read.audio_channel_stems(&dev_name, channels);
+            let outputs: Vec<(String, Option<&[u16]>)> = if stems.is_empty() {
+                vec![(filename_for(&label, channels), None)]
+            } else {
+                stems
+                    .iter()
+                    .map(|stem| {
+                        (
+                            filename_for(&format!("{}_{}", label, stem.label), stem.channels.len() as u16),
+                            Some(stem.channels.as_slice()),
+                        )
+                    })
+                    .collect()
+            };
             
-            match AudioStreamWriter::new(
-                &session_path, &filename, &dev_name, channels, native_rate,
-                &audio_format, &bit_depth, &sample_rate_setting,
-            ) {
-                Ok(mut writer) => {
-                    // Push drained pre-roll samples into the streaming writer
-                    if !preroll_samples.is_empty() {
-                        writer.push_samples(&preroll_samples);
+            for (filename, selection) in outputs {
+                match AudioStreamWriter::new(
+                    &session_path, &filename, &label, channels, selection, native_rate,
+                    &audio_format, &bit_depth, output_rate,
+                ) {
+                    Ok(mut writer) => {
+                        writer.start_offset_secs = start_offset.as_secs_f64();
+                        // Push drained pre-roll samples into the streaming writer
+                        if !preroll_samples.is_empty() {
+                            writer.push_samples(&preroll_samples);
+                        }
+                        state.audio_writers[i].push(writer);
+                    }
+                    Err(e) => {
+                        println!("[Sacho] Failed to create audio writer {} for {}: {}", filename, dev_name, e);
                     }
-                    state.audio_writers[i] = Some(writer);
-                }
-                Err(e) => {
-                    println!("[Sacho] Failed to create audio writer for {}: {}", dev_name, e);
                 }
             }
         }
@@ -2345,13 +3611,18 @@ fn start_recording(
         // Switch from "starting" to "recording" - now new events go directly to midi_events
         state.is_starting = false;
         state.is_recording = true;
-        state.recording_started_at = Some(Instant::now());
+        state.recording_started_at = Some(clock.now());
         state.heartbeat_stop = Some(heartbeat_stop);
+        if let Some(feedback) = state.midi_feedback.as_mut() {
+            feedback.send_start();
+        }
         
         println!("[Sacho] Recording started with {} pre-roll MIDI events, {} pre-roll audio samples (sync pre-roll: {:?})", 
             midi_preroll_count, audio_preroll_samples, sync_preroll_duration);
     }
     
+    connect_shared_midi(capture_state);
+    
     // Update recording state
     let active_devices = {
         let recording_state = app_handle.state::<RwLock<RecordingState>>();
@@ -2375,19 +3646,180 @@ fn start_recording(
         notifications::notify_recording_started(app_handle, &active_devices);
     }
     
+    if config_read.rtmp_stream.enabled {
+        start_stream(app_handle, &config_read, capture_state, video_manager);
+    }
+
     crate::tray::update_tray_state(app_handle, crate::tray::TrayState::Recording);
     let _ = app_handle.emit("recording-started", session_path.to_string_lossy().to_string());
     println!("[Sacho] Recording started: {:?}", session_path);
 }
 
+/// Open the shared-access MIDI devices for the recording that just started.
+/// Their callbacks take the capture lock, so this runs without it held.
+fn connect_shared_midi(capture_state: &Arc<Mutex<CaptureState>>) {
+    let inputs = capture_state.lock().shared_midi_inputs.clone();
+    if inputs.is_empty() {
+        return;
+    }
+    let mut connections = Vec::new();
+    for input in inputs {
+        let midi_in = match MidiInput::new("sacho-shared") {
+            Ok(m) => m,
+            Err(e) => {
+                println!("[Sacho] Failed to create MIDI input for {}: {}", input.port_name, e);
+                continue;
+            }
+        };
+        let port = midi_in
+            .ports()
+            .into_iter()
+            .find(|p| midi_in.port_name(p).ok().as_deref() == Some(input.port_name.as_str()));
+        let Some(port) = port else {
+            println!("[Sacho] Shared MIDI device {} not found", input.port_name);
+            continue;
+        };
+        let mut handler = (input.handler)();
+        match midi_in.connect(
+            &port,
+            "sacho-shared",
+            move |timestamp_us, message, _| handler(timestamp_us, message),
+            (),
+        ) {
+            Ok(conn) => {
+                println!("[Sacho] Connected to shared MIDI device: {}", input.port_name);
+                connections.push(conn);
+            }
+            Err(e) => {
+                // Most likely another application holds the port right now
+                println!("[Sacho] Shared MIDI device {} unavailable: {}", input.port_name, e);
+            }
+        }
+    }
+    capture_state.lock().shared_midi_connections = connections;
+}
+
+/// Start the live stream for a recording that just began. The stream has
+/// its own pipeline, so any failure here leaves the recording untouched.
+fn start_stream(
+    app_handle: &AppHandle,
+    config: &Config,
+    capture_state: &Arc<Mutex<CaptureState>>,
+    video_manager: &Arc<Mutex<VideoCaptureManager>>,
+) {
+    let stream_config = &config.rtmp_stream;
+    if stream_config.url.is_empty() {
+        println!("[Sacho] Streaming enabled but no URL is set");
+        return;
+    }
+    let source = video_manager
+        .lock()
+        .stream_source(stream_config.video_device.as_deref(), &config.selected_video_devices);
+    let Some((video_device_id, width, height, fps)) = source else {
+        println!("[Sacho] Streaming enabled but no video device is capturing");
+        return;
+    };
+
+    let audio = stream_config.audio_device.as_ref().and_then(|name| {
+        let state = capture_state.lock();
+        let index = state.audio_prerolls.iter().position(|p| p.device_name() == name)?;
+        let preroll = &state.audio_prerolls[index];
+        Some((index, StreamAudioSettings {
+            sample_rate: preroll.sample_rate(),
+            channels: preroll.channels(),
+            bitrate_kbps: stream_config.audio_bitrate_kbps,
+        }))
+    });
+    if stream_config.audio_device.is_some() && audio.is_none() {
+        println!("[Sacho] Stream audio device {:?} is not recording; streaming video only", stream_config.audio_device);
+    }
+
+    let settings = StreamSettings {
+        url: stream_config.url.clone(),
+        width,
+        height,
+        fps,
+        video_bitrate_kbps: stream_config.video_bitrate_kbps,
+        audio: audio.as_ref().map(|(_, settings)| settings.clone()),
+    };
+    let status_handle = app_handle.clone();
+    let streamer = match RtmpStreamer::start(settings, move |status: &StreamStatus| {
+        let _ = status_handle.emit("stream-status", status);
+    }) {
+        Ok(streamer) => streamer,
+        Err(e) => {
+            println!("[Sacho] Failed to start stream: {}", e);
+            let _ = app_handle.emit("stream-status", StreamStatus {
+                state: StreamState::Stopped,
+                message: Some(e.to_string()),
+                reconnects: 0,
+            });
+            return;
+        }
+    };
+
+    video_manager.lock().set_stream_tap(&video_device_id, streamer.tap());
+    let mut state = capture_state.lock();
+    if !state.is_recording {
+        // Recording already stopped while the stream was starting
+        drop(state);
+        streamer.stop();
+        video_manager.lock().clear_stream_tap();
+        return;
+    }
+    state.stream_audio_tap = audio.map(|(index, _)| (index, streamer.tap()));
+    state.rtmp_streamer = Some(streamer);
+}
+
+/// How often a recording in progress saves a metadata checkpoint
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Save how far each file has got and how the streams line up, for
+/// recovering a session that never gets finalized
+fn write_recording_checkpoint(
+    capture_state: &Arc<Mutex<CaptureState>>,
+    video_manager: &Arc<Mutex<VideoCaptureManager>>,
+    session_path: &Path,
+) {
+    let mut checkpoint = {
+        let state = capture_state.lock();
+        if !state.is_recording || state.session_path.as_deref() != Some(session_path) {
+            return;
+        }
+        crate::session::RecordingCheckpoint {
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            elapsed_secs: state.start_time
+                .map(|st| state.clock.elapsed(st).as_secs_f64())
+                .unwrap_or(0.0),
+            video_start_offset_secs: state.video_lead.as_secs_f64(),
+            sync_offsets_ms: state.sync_offsets_ms.clone(),
+            audio: state.audio_writers.iter().flatten().map(|w| w.progress()).collect(),
+            midi: state.midi_writers.values().map(|w| w.progress()).collect(),
+            video: Vec::new(),
+        }
+    };
+    checkpoint.video = video_manager.lock().recording_progress();
+    if let Err(e) = crate::session::write_checkpoint(session_path, &checkpoint) {
+        println!("[Sacho] Failed to write recording checkpoint: {}", e);
+    }
+}
+
 /// Stop recording and save files
 fn stop_recording(
     app_handle: &AppHandle, 
     capture_state: &Arc<Mutex<CaptureState>>,
     video_manager: &Arc<Mutex<VideoCaptureManager>>,
 ) {
+    // Lets a concurrent shutdown wait until the files below are complete
+    let _finalizing = crate::shutdown::begin_finalize();
+
+    // Hand shared MIDI ports back first. Dropping a connection waits for its
+    // callback, which takes the capture lock, so it's dropped outside it.
+    let shared_connections = std::mem::take(&mut capture_state.lock().shared_midi_connections);
+    drop(shared_connections);
+
     // First, extract what we need from capture_state
-    let (session_path, midi_writers, audio_writers, duration_secs) = {
+    let (session_path, midi_writers, audio_writers, duration_secs, video_lead, triggered_at, mut startup_latency, sync) = {
         let mut state = capture_state.lock();
         if !state.is_recording {
             return;
@@ -2399,26 +3831,59 @@ fn stop_recording(
         }
 
         let duration = state.start_time
-            .map(|st| st.elapsed().as_secs_f64())
+            .map(|st| state.clock.elapsed(st).as_secs_f64())
             .unwrap_or(0.0);
 
         let path = state.session_path.take();
+        let video_lead = std::mem::take(&mut state.video_lead);
+        let sync = crate::session::SessionSync {
+            preroll_secs: state.midi_timestamp_offset_us as f64 / 1_000_000.0,
+            midi_timestamp_offset_us: state.midi_timestamp_offset_us,
+            device_offsets_ms: state.sync_offsets_ms.clone(),
+        };
 
         // Take MIDI writers out of the state
         let midi_ws: HashMap<String, MidiStreamWriter> = std::mem::take(&mut state.midi_writers);
 
-        // Take audio writers out of the state (replace with None)
-        let audio_ws: Vec<Option<AudioStreamWriter>> = state.audio_writers.iter_mut()
-            .map(|w| w.take())
+        // Take audio writers out of the state (leaving empty slots)
+        let audio_ws: Vec<AudioStreamWriter> = state.audio_writers.iter_mut()
+            .flat_map(std::mem::take)
             .collect();
 
+        // Audio startup latency, once per device (stems share the first write)
+        let triggered_at = state.triggered_at.take();
+        let mut startup_latency = std::mem::take(&mut state.startup_latency);
+        if let Some(triggered_at) = triggered_at {
+            for writer in &audio_ws {
+                let Some(first_write) = writer.first_write_at else { continue };
+                let counted = startup_latency.iter().any(|l| {
+                    l.stream == crate::session::LatencyStream::Audio && l.device_name == writer.device_name
+                });
+                if !counted {
+                    startup_latency.push(writer_latency(
+                        crate::session::LatencyStream::Audio,
+                        &writer.device_name,
+                        triggered_at,
+                        first_write,
+                    ));
+                }
+            }
+        }
+
         state.is_recording = false;
         state.is_starting = false;
         state.start_time = None;
         state.recording_started_at = None;
         state.midi_timestamp_offset_us = 0;
+        if let Some(feedback) = state.midi_feedback.as_mut() {
+            feedback.send_stop();
+        }
+        if let Some(streamer) = state.rtmp_streamer.take() {
+            streamer.stop();
+        }
+        state.stream_audio_tap = None;
 
-        (path, midi_ws, audio_ws, duration)
+        (path, midi_ws, audio_ws, duration, video_lead, triggered_at, startup_latency, sync)
     };
     
     let Some(session_path) = session_path else {
@@ -2449,13 +3914,64 @@ fn stop_recording(
     crate::tray::update_tray_state(app_handle, crate::tray::TrayState::Idle);
     
     // Stop video recording and get video files
-    let video_files = {
+    let (mut video_files, frame_rate_reports, video_quality, encoding_adaptations) = {
         let mut mgr = video_manager.lock();
-        mgr.stop_recording()
+        if let Some(triggered_at) = triggered_at {
+            for (device_name, first_write) in mgr.first_writes() {
+                if let Some(first_write) = first_write {
+                    startup_latency.push(writer_latency(
+                        crate::session::LatencyStream::Video,
+                        &device_name,
+                        triggered_at,
+                        first_write,
+                    ));
+                }
+            }
+        }
+        let files = mgr.stop_recording();
+        (files, mgr.collect_frame_rate_reports(), mgr.collect_quality_reports(), mgr.collect_adaptations())
     };
+    if !video_lead.is_zero() {
+        for video in video_files.iter_mut() {
+            video.start_offset_secs = video_lead.as_secs_f64();
+        }
+        if let Err(e) = crate::session::write_video_offsets(&session_path, &video_files) {
+            println!("[Sacho] Failed to write video start offsets: {}", e);
+        }
+    }
+    if let Err(e) = crate::session::write_video_color(&session_path, &video_files) {
+        println!("[Sacho] Failed to write video color info: {}", e);
+    }
+    if let Err(e) = crate::session::write_video_streams(&session_path, &video_files) {
+        println!("[Sacho] Failed to write video stream info: {}", e);
+    }
+    if let Err(e) = crate::session::write_frame_rate_reports(&session_path, &frame_rate_reports) {
+        println!("[Sacho] Failed to write frame rate reports: {}", e);
+    }
+    if let Err(e) = crate::session::write_encoding_adaptations(&session_path, &encoding_adaptations) {
+        println!("[Sacho] Failed to write encoding adaptations: {}", e);
+    }
+    for latency in &startup_latency {
+        println!("[Sacho] Startup latency {:?} {}: {:.1}ms", latency.stream, latency.device_name, latency.latency_ms);
+    }
+    if let Err(e) = crate::session::write_startup_latency(&session_path, &startup_latency) {
+        println!("[Sacho] Failed to write startup latency: {}", e);
+    }
+    
+    let audio_rates: Vec<crate::session::AudioRate> = audio_writers.iter()
+        .map(|w| crate::session::AudioRate {
+            filename: w.filename.clone(),
+            device_name: w.device_name.clone(),
+            device_rate: w.native_rate,
+            file_rate: w.output_rate,
+        })
+        .collect();
+    if let Err(e) = crate::session::write_audio_rates(&session_path, &audio_rates) {
+        println!("[Sacho] Failed to write audio sample rates: {}", e);
+    }
     
     let midi_writer_count = midi_writers.len();
-    let audio_writer_count = audio_writers.iter().filter(|w| w.is_some()).count();
+    let audio_writer_count = audio_writers.len();
     println!("[Sacho] Stopping recording, {} MIDI streams, {} audio streams, {} video files", 
         midi_writer_count, audio_writer_count, video_files.len());
     
@@ -2468,37 +3984,61 @@ fn stop_recording(
         }
     }
     
-    // Calculate max video duration for potential audio padding
+    // Calculate max video end time for potential audio padding
     let video_max_duration = video_files.iter()
-        .map(|f| f.duration_secs)
+        .map(|f| f.start_offset_secs + f.duration_secs)
         .fold(0.0f64, |a, b| a.max(b));
     
     let target_duration = duration_secs.max(video_max_duration);
     
     // Finalize audio writers: pad if needed, then finish (EOS + flush to disk)
     let mut audio_files = Vec::new();
-    for writer_opt in audio_writers.into_iter() {
-        if let Some(mut writer) = writer_opt {
-            // Pad with silence if video is longer
-            let writer_duration = writer.frames_pushed as f64 / writer.native_rate as f64;
-            if writer_duration < target_duration - 0.1 {
-                let padding_secs = target_duration - writer_duration;
-                writer.push_silence(padding_secs);
-                println!("[Sacho] Padded audio {} with {:.2}s of silence", writer.filename, padding_secs);
+    let mut padding_warnings = Vec::new();
+    let mut quality = crate::session::SessionQuality {
+        audio: Vec::new(),
+        video: video_quality,
+    };
+    for mut writer in audio_writers.into_iter() {
+        // Pad with silence if video is longer
+        let writer_duration = writer.frames_pushed as f64 / writer.native_rate as f64;
+        let writer_end = writer.start_offset_secs + writer_duration;
+        if writer_end < target_duration - 0.1 {
+            let padding_secs = target_duration - writer_end;
+            writer.push_silence(padding_secs);
+            println!("[Sacho] Padded audio {} with {:.2}s of silence", writer.filename, padding_secs);
+            if padding_secs > crate::session::validation::DURATION_TOLERANCE_SECS {
+                padding_warnings.push(crate::session::TimelineWarning {
+                    kind: crate::session::TimelineWarningKind::AudioPadded,
+                    filename: writer.filename.clone(),
+                    at_secs: Some(writer_end),
+                    message: format!("Audio ended {:.1}s early and was padded with silence", padding_secs),
+                });
             }
-            
-            match writer.finish() {
-                Ok(info) => audio_files.push(info),
-                Err(e) => println!("[Sacho] Failed to finalize audio: {}", e),
+        }
+        
+        let mut audio_quality = writer.quality();
+        let verify = writer.is_flac().then(|| (writer.file_path.clone(), writer.output_frames(), writer.output_rate));
+        match writer.finish() {
+            Ok(info) => {
+                if let Some((path, expected_frames, rate)) = verify {
+                    audio_quality.decode_check = Some(crate::recording::quality::verify_flac(&path, expected_frames, rate));
+                }
+                audio_files.push(info);
+                quality.audio.push(audio_quality);
             }
+            Err(e) => println!("[Sacho] Failed to finalize audio: {}", e),
         }
     }
     
     // Update overall duration to include audio
     let audio_max_duration = audio_files.iter()
-        .map(|f| f.duration_secs)
+        .map(|f| f.start_offset_secs + f.duration_secs)
         .fold(0.0f64, |a, b| a.max(b));
     let duration_secs = target_duration.max(audio_max_duration);
+
+    if let Err(e) = crate::session::write_session_sync(&session_path, &sync, &audio_files, &midi_files) {
+        println!("[Sacho] Failed to write stream alignment: {}", e);
+    }
     
     // Combine audio+video into a single container if configured (exactly 1 of each)
     {
@@ -2507,6 +4047,8 @@ fn stop_recording(
         if config_read.combine_audio_video
             && video_files.len() == 1
             && audio_files.len() == 1
+            && video_files[0].start_offset_secs == 0.0
+            && audio_files[0].start_offset_secs == 0.0
         {
             let video_path = session_path.join(&video_files[0].filename);
             let audio_path = session_path.join(&audio_files[0].filename);
@@ -2543,8 +4085,9 @@ fn stop_recording(
         .unwrap_or("")
         .to_string();
     
-    let metadata = SessionMetadata {
+    let mut metadata = SessionMetadata {
         id: session_id.clone(),
+        uid: crate::session::read_session_uid(&session_path),
         timestamp: crate::session::parse_session_timestamp(&session_id)
             .unwrap_or_else(chrono::Utc::now),
         duration_secs,
@@ -2557,30 +4100,136 @@ fn stop_recording(
         recording_in_progress: false,
         recording_lock_updated_at: None,
         recording_lock_is_local: false,
+        archived: false,
+        favorite: false,
+        tags: crate::session::read_session_tags(&session_path),
+        custom_fields: crate::session::read_custom_fields(&session_path),
+        loops: Vec::new(),
+        chapters: Vec::new(),
+        timeline_warnings: Vec::new(),
+        owner: crate::session::read_session_owner(&session_path).map(|o| o.user),
+        video_fallbacks: crate::session::read_video_fallbacks(&session_path),
+        frame_rate_reports,
+        encoding_adaptations,
+        startup_latency,
+        audio_rates,
+        sync: Some(sync),
+        quality: Some(quality.clone()),
+        schema_version: crate::session::SESSION_SCHEMA_VERSION,
     };
     
+    {
+        let rules = app_handle.state::<RwLock<Config>>().read().auto_tag_rules.clone();
+        crate::session::auto_tags::apply(&rules, &mut metadata);
+    }
+
     let db = app_handle.state::<SessionDatabase>();
     if let Err(e) = db.upsert_session(&metadata) {
         println!("[Sacho] Failed to index session: {}", e);
     }
     
-    // Send desktop notification
+    // Send desktop notification (the quick-review one replaces it)
     let config = app_handle.state::<RwLock<Config>>();
-    if config.read().notify_recording_stop {
+    let (notify_stop, quick_review) = {
+        let config = config.read();
+        (config.notify_recording_stop, config.quick_review)
+    };
+    if quick_review == crate::config::QuickReviewMode::Notify {
+        notifications::notify_quick_review(app_handle, &metadata.id, duration_secs);
+    } else if notify_stop {
         let folder_name = session_path.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("session");
-        notifications::notify_recording_stopped(app_handle, duration_secs, folder_name);
+        notifications::notify_recording_stopped(app_handle, &metadata.id, duration_secs, folder_name);
     }
     
     let _ = app_handle.emit("recording-stopped", serde_json::to_string(&metadata).unwrap_or_default());
+    if quick_review == crate::config::QuickReviewMode::Open {
+        notifications::open_session_for_review(app_handle, &metadata.id, false);
+    }
     println!("[Sacho] Recording stopped, duration: {} sec", duration_secs);
 
     // Remove recording lock file (files are finalized, safe to remove)
+    crate::session::remove_checkpoint(&session_path);
     crate::session::remove_recording_lock(&session_path);
 
-    // Compute similarity features for sessions with MIDI
-    if !metadata.midi_files.is_empty() {
+    // Check the finalized files for desync (reads every video packet, so off this thread)
+    {
+        let handle = app_handle.clone();
+        let metadata = metadata.clone();
+        std::thread::spawn(move || {
+            let mut warnings = padding_warnings;
+            warnings.extend(crate::session::validation::validate_timeline(&metadata));
+
+            let mut quality = quality;
+            crate::recording::quality::apply_timeline_gaps(&mut quality, &warnings);
+            if let Err(e) = crate::session::write_quality(&metadata.path, &quality) {
+                println!("[Sacho] Failed to save quality report: {}", e);
+            }
+            if quality.has_issues() {
+                let _ = handle.emit("session-quality-issues", QualityIssuesEvent {
+                    session_id: metadata.id.clone(),
+                    quality,
+                });
+            }
+
+            if warnings.is_empty() {
+                return;
+            }
+            for warning in &warnings {
+                println!("[Sacho] Timeline warning in {}: {}", warning.filename, warning.message);
+            }
+            if let Err(e) = crate::session::write_timeline_warnings(&metadata.path, &warnings) {
+                println!("[Sacho] Failed to save timeline warnings: {}", e);
+            }
+            let _ = handle.emit("session-timeline-warnings", TimelineWarningsEvent {
+                session_id: metadata.id.clone(),
+                warnings,
+            });
+        });
+    }
+
+    // Chapters, proxies and filmstrips in the background. Chapters come
+    // first since writing them rewrites the Matroska files the others read.
+    {
+        let handle = app_handle.clone();
+        let metadata = metadata.clone();
+        let (detect_chapters, generate_proxies, generate_filmstrips) = {
+            let config_read = config.read();
+            (
+                config_read.detect_chapters,
+                config_read.generate_video_proxies,
+                config_read.generate_video_filmstrips,
+            )
+        };
+        std::thread::spawn(move || {
+            if detect_chapters && !metadata.midi_files.is_empty() {
+                write_session_chapters(&handle, &metadata);
+            }
+
+            // Scrubbing proxies for heavy codecs (skipped per file if not needed)
+            if generate_proxies {
+                if let Some(queue) = handle.try_state::<crate::video::proxy::ProxyQueue>() {
+                    for video in &metadata.video_files {
+                        queue.enqueue(metadata.path.join(&video.filename));
+                    }
+                }
+            }
+
+            // Timeline filmstrips, one video at a time
+            if generate_filmstrips {
+                for video in &metadata.video_files {
+                    let video = metadata.path.join(&video.filename);
+                    if let Err(e) = crate::video::filmstrip::ensure_filmstrip(&video) {
+                        println!("[Sacho] Failed to generate filmstrip for {}: {}", video.display(), e);
+                    }
+                }
+            }
+        });
+    }
+
+    // Compute similarity features (MIDI, or audio chroma for audio-only sessions)
+    if !metadata.midi_files.is_empty() || !metadata.audio_files.is_empty() {
         let handle = app_handle.clone();
         let sid = session_id.clone();
         let spath = session_path.clone();
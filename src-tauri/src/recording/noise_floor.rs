@@ -0,0 +1,117 @@
+// Audio trigger calibration
+//
+// Listens to an input for a few seconds while nothing is being played,
+// measures the room's noise floor in the same 50ms RMS windows the trigger
+// uses, and suggests a threshold far enough above the loudest background
+// window that fans, hum and traffic don't start a recording.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// Default listening time
+pub const DEFAULT_CALIBRATION_SECS: u32 = 5;
+
+/// Longest listening time accepted
+pub const MAX_CALIBRATION_SECS: u32 = 30;
+
+/// Headroom above the 99th percentile window (x2 = +6 dB)
+const PERCENTILE_MARGIN: f32 = 2.0;
+
+/// Headroom above the single loudest window (x1.4 = +3 dB)
+const PEAK_MARGIN: f32 = 1.4;
+
+/// Suggestions are kept within the range of the threshold slider (-60..-3 dBFS)
+const MIN_THRESHOLD: f32 = 0.001;
+const MAX_THRESHOLD: f32 = 0.708;
+
+/// Noise floor measurement and the threshold derived from it
+#[derive(Debug, Clone, Serialize)]
+pub struct NoiseFloorReport {
+    pub device_name: String,
+    pub duration_secs: f64,
+    /// Number of 50ms windows measured
+    pub windows: usize,
+    pub min_rms: f32,
+    pub median_rms: f32,
+    pub p95_rms: f32,
+    pub p99_rms: f32,
+    pub max_rms: f32,
+    /// Suggested trigger threshold (linear RMS, 0.0-1.0)
+    pub suggested_threshold: f64,
+    /// True if the suggestion was saved as the device's threshold
+    pub applied: bool,
+}
+
+/// Open the input named `device_name` and measure its background level for
+/// `duration`. Opens its own stream, so it works whether or not the device is
+/// currently selected.
+pub fn measure_noise_floor(device_name: &str, duration: Duration) -> anyhow::Result<NoiseFloorReport> {
+    let host = cpal::default_host();
+    let device = host
+        .input_devices()?
+        .find(|d| d.name().map(|n| n == device_name).unwrap_or(false))
+        .ok_or_else(|| anyhow::anyhow!("Audio device not found: {}", device_name))?;
+    let supported_config = device.default_input_config()?;
+    let samples_per_window =
+        (supported_config.sample_rate().0 as usize * supported_config.channels() as usize / 20).max(1);
+
+    let windows: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
+    let windows_cb = windows.clone();
+    let mut sum_sq = 0.0f64;
+    let mut count = 0usize;
+
+    let stream = device.build_input_stream(
+        &supported_config.into(),
+        move |data: &[f32], _: &cpal::InputCallbackInfo| {
+            for &sample in data {
+                sum_sq += (sample as f64) * (sample as f64);
+                count += 1;
+                if count >= samples_per_window {
+                    windows_cb.lock().push((sum_sq / count as f64).sqrt() as f32);
+                    sum_sq = 0.0;
+                    count = 0;
+                }
+            }
+        },
+        |err| println!("[Calibrate] Audio error: {}", err),
+        None,
+    )?;
+    stream.play()?;
+    std::thread::sleep(duration);
+    drop(stream);
+
+    let mut levels = std::mem::take(&mut *windows.lock());
+    if levels.is_empty() {
+        anyhow::bail!("No audio arrived from {}", device_name);
+    }
+    levels.sort_by(|a, b| a.total_cmp(b));
+
+    let percentile = |p: f64| levels[((levels.len() - 1) as f64 * p).round() as usize];
+    let p99 = percentile(0.99);
+    let max = *levels.last().unwrap();
+    let suggested = (p99 * PERCENTILE_MARGIN)
+        .max(max * PEAK_MARGIN)
+        .clamp(MIN_THRESHOLD, MAX_THRESHOLD);
+
+    let report = NoiseFloorReport {
+        device_name: device_name.to_string(),
+        duration_secs: duration.as_secs_f64(),
+        windows: levels.len(),
+        min_rms: levels[0],
+        median_rms: percentile(0.5),
+        p95_rms: percentile(0.95),
+        p99_rms: p99,
+        max_rms: max,
+        suggested_threshold: suggested as f64,
+        applied: false,
+    };
+    println!(
+        "[Calibrate] {}: noise floor median {:.5}, p99 {:.5}, max {:.5} -> threshold {:.4}",
+        device_name, report.median_rms, report.p99_rms, report.max_rms, report.suggested_threshold
+    );
+    Ok(report)
+}
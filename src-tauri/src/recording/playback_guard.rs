@@ -0,0 +1,87 @@
+// Playback detection for audio trigger suppression
+//
+// A take played back through the monitors reaches the trigger microphone and
+// would start a new recording of itself. Two signals count as playback: the
+// session player in Sacho reporting that it is playing, and a configured
+// monitor device (a loopback of the speaker feed) carrying signal. Both hold
+// for a short tail afterwards so the room's decay doesn't slip through.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use parking_lot::Mutex;
+
+/// Triggers stay suppressed this long after playback stops
+const RELEASE_TAIL: Duration = Duration::from_millis(750);
+
+/// Set while the session player is playing
+static APP_PLAYBACK: AtomicBool = AtomicBool::new(false);
+
+/// When either signal last indicated playback
+static LAST_PLAYBACK: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Report whether the session player is playing
+pub fn set_app_playback(active: bool) {
+    APP_PLAYBACK.store(active, Ordering::Relaxed);
+    // Start the release tail from the moment playback stops
+    *LAST_PLAYBACK.lock() = Some(Instant::now());
+}
+
+/// True while something is playing through the speakers (or just stopped)
+pub fn is_playback_active() -> bool {
+    APP_PLAYBACK.load(Ordering::Relaxed)
+        || LAST_PLAYBACK
+            .lock()
+            .map(|t| t.elapsed() < RELEASE_TAIL)
+            .unwrap_or(false)
+}
+
+fn note_monitor_signal() {
+    *LAST_PLAYBACK.lock() = Some(Instant::now());
+}
+
+/// Open a level meter on the playback monitor device. Inputs are searched
+/// first, then outputs (loopback capture). The stream must be kept alive for
+/// as long as monitoring should run.
+pub fn open_monitor_stream(device_name: &str, threshold: f64) -> anyhow::Result<cpal::Stream> {
+    let host = cpal::default_host();
+    let matches = |d: &cpal::Device| d.name().map(|n| n == device_name).unwrap_or(false);
+    let (device, supported_config) = if let Some(device) = host.input_devices()?.find(matches) {
+        let config = device.default_input_config()?;
+        (device, config)
+    } else if let Some(device) = host.output_devices()?.find(matches) {
+        let config = device.default_output_config()?;
+        (device, config)
+    } else {
+        anyhow::bail!("Playback monitor device not found: {}", device_name);
+    };
+
+    // 50ms windows, as for the triggers
+    let samples_per_window =
+        (supported_config.sample_rate().0 as usize * supported_config.channels() as usize / 20).max(1);
+    let threshold_sq = threshold * threshold;
+    let mut sum_sq = 0.0f64;
+    let mut count = 0usize;
+
+    let stream = device.build_input_stream(
+        &supported_config.into(),
+        move |data: &[f32], _: &cpal::InputCallbackInfo| {
+            for &sample in data {
+                sum_sq += (sample as f64) * (sample as f64);
+                count += 1;
+                if count >= samples_per_window {
+                    if sum_sq / count as f64 > threshold_sq {
+                        note_monitor_signal();
+                    }
+                    sum_sq = 0.0;
+                    count = 0;
+                }
+            }
+        },
+        |err| println!("[Sacho] Playback monitor error: {}", err),
+        None,
+    )?;
+    stream.play()?;
+    Ok(stream)
+}
@@ -0,0 +1,226 @@
+// Video preflight check
+//
+// `validate_video_device_config` only asks whether the device advertises the
+// requested caps. Plenty of cameras advertise modes they can't deliver (USB
+// bandwidth, low light stretching the exposure), and a software encoder may
+// fall behind at a resolution the machine can't keep up with. The preflight
+// runs the real pipeline for a few seconds, records into a throwaway folder,
+// and reports what actually happened so the user can fix the config before a
+// session depends on it.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::config::VideoDeviceConfig;
+use crate::encoding::ContainerFormat;
+
+use super::video::VideoCapturePipeline;
+
+/// Longest wait for the first frame after the pipeline starts
+const FIRST_FRAME_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the test recording runs
+const RECORD_DURATION: Duration = Duration::from_secs(4);
+
+/// Poll interval while recording (the monitor's poller runs at a similar rate)
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Measured fps below this fraction of the requested fps is reported
+const MIN_FPS_RATIO: f64 = 0.9;
+
+/// Dropped frames above this fraction of captured frames are reported
+const MAX_DROP_RATIO: f64 = 0.02;
+
+/// A problem found by the preflight
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightWarning {
+    /// Stable identifier for the frontend: "pipeline_failed", "no_frames",
+    /// "dimension_mismatch", "fps_low", "encoder_dropping", "encoder_slow"
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoPreflightReport {
+    pub device_id: String,
+    /// True if no warnings were raised
+    pub ok: bool,
+    pub requested_width: u32,
+    pub requested_height: u32,
+    pub requested_fps: f64,
+    /// Mode negotiated with the source, if the pipeline got that far
+    pub negotiated_width: Option<u32>,
+    pub negotiated_height: Option<u32>,
+    pub negotiated_fps: Option<f64>,
+    /// Frames per second actually delivered by the source during the test
+    pub measured_fps: f64,
+    pub frames_captured: u64,
+    pub frames_written: u64,
+    pub frames_dropped: u64,
+    pub warnings: Vec<PreflightWarning>,
+}
+
+impl VideoPreflightReport {
+    fn warn(&mut self, code: &str, message: String) {
+        println!("[Preflight] {}: {}", self.device_id, message);
+        self.warnings.push(PreflightWarning { code: code.to_string(), message });
+        self.ok = false;
+    }
+}
+
+/// Run the proposed config for a few seconds and measure it. The device must
+/// not be open elsewhere (the monitor releases it first).
+pub fn run_video_preflight(
+    device_id: &str,
+    device_name: &str,
+    dev_config: &VideoDeviceConfig,
+    preferred_container: ContainerFormat,
+) -> VideoPreflightReport {
+    let mut report = VideoPreflightReport {
+        device_id: device_id.to_string(),
+        ok: true,
+        requested_width: dev_config.source_width,
+        requested_height: dev_config.source_height,
+        requested_fps: dev_config.source_fps,
+        negotiated_width: None,
+        negotiated_height: None,
+        negotiated_fps: None,
+        measured_fps: 0.0,
+        frames_captured: 0,
+        frames_written: 0,
+        frames_dropped: 0,
+        warnings: Vec::new(),
+    };
+
+    // No pre-roll: every frame captured during the test should be written
+    let pipeline = VideoCapturePipeline::from_device_config(
+        device_id,
+        device_name,
+        dev_config,
+        0,
+        false,
+        preferred_container,
+    );
+    let mut pipeline = match pipeline {
+        Ok(pipeline) => pipeline,
+        Err(e) => {
+            report.warn("pipeline_failed", format!("Could not build the capture pipeline: {}", e));
+            return report;
+        }
+    };
+    if let Err(e) = pipeline.start() {
+        report.warn("pipeline_failed", format!("Could not start the capture pipeline: {}", e));
+        return report;
+    }
+
+    // Wait for the source to deliver something
+    let started = Instant::now();
+    while pipeline.frames_captured() == 0 && started.elapsed() < FIRST_FRAME_TIMEOUT {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    if pipeline.frames_captured() == 0 {
+        report.warn(
+            "no_frames",
+            format!(
+                "No frames arrived within {}s. The device may be in use or unable to deliver this mode.",
+                FIRST_FRAME_TIMEOUT.as_secs()
+            ),
+        );
+        let _ = pipeline.stop();
+        return report;
+    }
+
+    if let Some((width, height, fps)) = pipeline.negotiated_mode() {
+        report.negotiated_width = Some(width);
+        report.negotiated_height = Some(height);
+        report.negotiated_fps = (fps > 0.0).then_some(fps);
+        if width != dev_config.source_width || height != dev_config.source_height {
+            report.warn(
+                "dimension_mismatch",
+                format!(
+                    "Requested {}x{} but the device delivers {}x{}",
+                    dev_config.source_width, dev_config.source_height, width, height
+                ),
+            );
+        }
+    }
+
+    let temp_dir = std::env::temp_dir().join(format!("sacho-preflight-{}", uuid::Uuid::new_v4().simple()));
+    if let Err(e) = std::fs::create_dir_all(&temp_dir) {
+        report.warn("pipeline_failed", format!("Could not create a test folder: {}", e));
+        let _ = pipeline.stop();
+        return report;
+    }
+
+    let output_path = temp_dir.join(format!("video_{}.mkv", crate::session::sanitize_device_name(device_name)));
+    if let Err(e) = pipeline.start_recording(output_path) {
+        report.warn("pipeline_failed", format!("Could not start the test recording: {}", e));
+        let _ = pipeline.stop();
+        let _ = std::fs::remove_dir_all(&temp_dir);
+        return report;
+    }
+
+    let captured_before = pipeline.frames_captured();
+    let recording_started = Instant::now();
+    while recording_started.elapsed() < RECORD_DURATION {
+        if let Err(e) = pipeline.poll() {
+            report.warn("pipeline_failed", format!("Pipeline error while recording: {}", e));
+            break;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    let elapsed = recording_started.elapsed().as_secs_f64();
+    let captured = pipeline.frames_captured().saturating_sub(captured_before);
+
+    if let Err(e) = pipeline.stop_recording() {
+        report.warn("pipeline_failed", format!("Could not finalize the test recording: {}", e));
+    }
+    let _ = pipeline.stop();
+    let _ = std::fs::remove_dir_all(&temp_dir);
+
+    report.frames_captured = captured;
+    report.frames_written = pipeline.frames_written();
+    report.frames_dropped = pipeline.frames_dropped();
+    report.measured_fps = if elapsed > 0.0 { captured as f64 / elapsed } else { 0.0 };
+
+    let expected_fps = report.negotiated_fps.unwrap_or(dev_config.source_fps);
+    if expected_fps > 0.0 && report.measured_fps < expected_fps * MIN_FPS_RATIO {
+        report.warn(
+            "fps_low",
+            format!(
+                "The device delivered {:.1} fps instead of {:.1} fps. Try more light, a lower resolution, or another format.",
+                report.measured_fps, expected_fps
+            ),
+        );
+    }
+
+    if captured > 0 {
+        let dropped_ratio = report.frames_dropped as f64 / captured as f64;
+        if dropped_ratio > MAX_DROP_RATIO {
+            report.warn(
+                "encoder_dropping",
+                format!(
+                    "The encoder dropped {} of {} frames ({:.1}%). Try a faster preset or a hardware encoder.",
+                    report.frames_dropped,
+                    captured,
+                    dropped_ratio * 100.0
+                ),
+            );
+        } else if (report.frames_written as f64) < captured as f64 * MIN_FPS_RATIO {
+            report.warn(
+                "encoder_slow",
+                format!(
+                    "Only {} of {} captured frames were written. The encoder may not keep up with this mode.",
+                    report.frames_written, captured
+                ),
+            );
+        }
+    }
+
+    println!(
+        "[Preflight] {}: {:.1} fps measured, {} captured, {} written, {} dropped",
+        device_id, report.measured_fps, captured, report.frames_written, report.frames_dropped
+    );
+    report
+}
@@ -1,10 +1,12 @@
 // Pre-roll buffer management for MIDI and audio
 // Maintains rolling buffers of recent data to include when recording starts
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::time::{Duration, Instant};
 
-use super::midi::TimestampedMidiEvent;
+use super::clock::{self, SharedClock};
+use super::midi::{MidiProgramTracker, TimestampedMidiEvent};
+use crate::session::ChannelProgram;
 
 /// Maximum pre-roll duration when encoding during pre-roll is OFF
 pub const MAX_PRE_ROLL_SECS: u32 = 5;
@@ -13,6 +15,13 @@ pub const MAX_PRE_ROLL_SECS: u32 = 5;
 /// Encoded frames are much smaller than raw, so we can afford a longer window.
 pub const MAX_PRE_ROLL_SECS_ENCODED: u32 = 30;
 
+/// Maximum audio/MIDI pre-roll duration. Samples and events are cheap to
+/// buffer, so this doesn't depend on how video is handled.
+pub const MAX_AUDIO_MIDI_PRE_ROLL_SECS: u32 = 30;
+
+/// MIDI events per second of pre-roll reserved up front
+const MIDI_EVENTS_PER_SEC: usize = 256;
+
 // ============================================================================
 // MIDI Pre-roll Buffer
 // ============================================================================
@@ -32,6 +41,10 @@ pub struct BufferedMidiEvent {
 pub struct MidiPrerollBuffer {
     events: VecDeque<BufferedMidiEvent>,
     max_duration: Duration,
+    /// Bank/program state per device from events no longer in the buffer
+    programs: HashMap<String, MidiProgramTracker>,
+    /// Time source for stamping and trimming (injectable for tests)
+    clock: SharedClock,
 }
 
 impl MidiPrerollBuffer {
@@ -41,10 +54,24 @@ impl MidiPrerollBuffer {
     
     pub fn with_limit(max_secs: u32, limit: u32) -> Self {
         Self {
-            events: VecDeque::new(),
+            // Room for dense playing, so the buffer doesn't grow while armed
+            events: VecDeque::with_capacity(MIDI_EVENTS_PER_SEC * max_secs.min(limit) as usize),
             max_duration: Duration::from_secs(max_secs.min(limit) as u64),
+            programs: HashMap::new(),
+            clock: clock::system(),
         }
     }
+
+    /// Use `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Switch the time source of a buffer that may already hold events
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
     
     pub fn set_duration(&mut self, secs: u32) {
         self.set_duration_with_limit(secs, MAX_PRE_ROLL_SECS);
@@ -53,13 +80,15 @@ impl MidiPrerollBuffer {
     pub fn set_duration_with_limit(&mut self, secs: u32, limit: u32) {
         self.max_duration = Duration::from_secs(secs.min(limit) as u64);
         self.trim();
+        let wanted = MIDI_EVENTS_PER_SEC * secs.min(limit) as usize;
+        self.events.reserve(wanted.saturating_sub(self.events.len()));
     }
     
     pub fn push(&mut self, device_name: String, event: TimestampedMidiEvent, driver_timestamp_us: u64) {
         self.events.push_back(BufferedMidiEvent {
             device_name: device_name.clone(),
             event,
-            wall_time: Instant::now(),
+            wall_time: self.clock.now(),
             driver_timestamp_us,
         });
         self.trim();
@@ -68,15 +97,46 @@ impl MidiPrerollBuffer {
     }
     
     fn trim(&mut self) {
-        let cutoff = Instant::now() - self.max_duration;
+        let cutoff = self.clock.now() - self.max_duration;
         while let Some(front) = self.events.front() {
             if front.wall_time < cutoff {
-                self.events.pop_front();
+                if let Some(old) = self.events.pop_front() {
+                    self.observe_program(&old.device_name, &old.event.data);
+                }
             } else {
                 break;
             }
         }
     }
+
+    /// Track bank select / program change for events that bypass the buffer
+    /// (while recording) or leave it
+    pub fn observe_program(&mut self, device_name: &str, data: &[u8]) {
+        if let Some(tracker) = self.programs.get_mut(device_name) {
+            tracker.observe(data);
+        } else {
+            let mut tracker = MidiProgramTracker::default();
+            tracker.observe(data);
+            self.programs.insert(device_name.to_string(), tracker);
+        }
+    }
+
+    /// Programs in effect at the start of a pre-roll window of `window`
+    /// (None = the whole buffer), per device. Call before draining.
+    pub fn programs_before(&self, window: Option<Duration>) -> HashMap<String, Vec<ChannelProgram>> {
+        let mut programs = self.programs.clone();
+        if let Some(window) = window {
+            let now = self.clock.now();
+            for e in self.events.iter().filter(|e| now.duration_since(e.wall_time) > window) {
+                programs.entry(e.device_name.clone()).or_default().observe(&e.event.data);
+            }
+        }
+        programs
+            .into_iter()
+            .map(|(device, tracker)| (device, tracker.snapshot()))
+            .filter(|(_, snapshot)| !snapshot.is_empty())
+            .collect()
+    }
     
     /// Drain all buffered events, returning them with adjusted timestamps.
     /// 
@@ -88,7 +148,10 @@ impl MidiPrerollBuffer {
     /// If `audio_preroll_duration` is None, falls back to making the first event timestamp 0.
     pub fn drain_with_audio_sync(&mut self, audio_preroll_duration: Option<Duration>) -> Vec<(String, TimestampedMidiEvent)> {
         let events: Vec<_> = self.events.drain(..).collect();
-        let now = Instant::now();
+        let now = self.clock.now();
+        for e in &events {
+            self.observe_program(&e.device_name, &e.event.data);
+        }
         
         println!("[Sacho PreRoll] Draining {} pre-roll MIDI events", events.len());
         
@@ -167,7 +230,9 @@ impl MidiPrerollBuffer {
     }
     
     pub fn clear(&mut self) {
-        self.events.clear();
+        for e in std::mem::take(&mut self.events) {
+            self.observe_program(&e.device_name, &e.event.data);
+        }
     }
 
     /// Remove all buffered events from a specific device (on disconnect)
@@ -201,13 +266,15 @@ impl AudioPrerollBuffer {
     
     pub fn with_limit(device_name: String, sample_rate: u32, channels: u16, max_secs: u32, limit: u32) -> Self {
         let max_samples = (sample_rate as usize) * (channels as usize) * (max_secs.min(limit) as usize);
-        Self {
-            samples: VecDeque::with_capacity(max_samples),
+        let mut buffer = Self {
+            samples: VecDeque::new(),
             max_samples,
             sample_rate,
             channels,
             device_name,
-        }
+        };
+        buffer.preallocate();
+        buffer
     }
     
     pub fn set_duration(&mut self, secs: u32) {
@@ -217,6 +284,26 @@ impl AudioPrerollBuffer {
     pub fn set_duration_with_limit(&mut self, secs: u32, limit: u32) {
         self.max_samples = (self.sample_rate as usize) * (self.channels as usize) * (secs.min(limit) as usize);
         self.trim();
+        self.preallocate();
+    }
+
+    /// Reserve the full window and touch every page now, while arming. A
+    /// reservation alone is only committed by the OS as it's first written,
+    /// which would put page faults in the audio callback for the first
+    /// `max_secs` of monitoring.
+    fn preallocate(&mut self) {
+        // trim() drops from the front, so one spare frame keeps a push of a
+        // full window from reallocating
+        let capacity = self.max_samples + self.channels as usize;
+        if self.samples.capacity() >= capacity {
+            return;
+        }
+        let buffered: Vec<f32> = self.samples.drain(..).collect();
+        let mut samples = VecDeque::with_capacity(capacity);
+        samples.resize(capacity, 0.0);
+        samples.clear();
+        samples.extend(buffered);
+        self.samples = samples;
     }
     
     pub fn push_samples(&mut self, samples: &[f32]) {
@@ -224,6 +311,13 @@ impl AudioPrerollBuffer {
         self.trim();
     }
     
+    /// Fill a gap in the input with `frames` frames of silence
+    pub fn push_silence_frames(&mut self, frames: usize) {
+        let samples = (frames * self.channels as usize).min(self.max_samples);
+        self.samples.extend(std::iter::repeat(0.0).take(samples));
+        self.trim();
+    }
+    
     fn trim(&mut self) {
         while self.samples.len() > self.max_samples {
             self.samples.pop_front();
@@ -285,3 +379,52 @@ impl AudioPrerollBuffer {
         self.channels
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::recording::clock::ManualClock;
+
+    fn note_on(note: u8) -> TimestampedMidiEvent {
+        TimestampedMidiEvent { timestamp_us: 0, data: vec![0x90, note, 100] }
+    }
+
+    #[test]
+    fn midi_preroll_trims_on_virtual_time() {
+        let clock = Arc::new(ManualClock::new());
+        let mut buffer = MidiPrerollBuffer::new(2).with_clock(clock.shared());
+
+        buffer.push("Keys".into(), note_on(60), 0);
+        clock.advance(Duration::from_millis(1500));
+        buffer.push("Keys".into(), note_on(62), 1_500_000);
+        clock.advance(Duration::from_millis(1000));
+        // 2.5 s in: the first note is older than the 2 s window
+        buffer.push("Keys".into(), note_on(64), 2_500_000);
+
+        let drained = buffer.drain();
+        let notes: Vec<u8> = drained.iter().map(|(_, e)| e.data[1]).collect();
+        assert_eq!(notes, vec![62, 64]);
+        let timestamps: Vec<u64> = drained.iter().map(|(_, e)| e.timestamp_us).collect();
+        assert_eq!(timestamps, vec![0, 1_000_000]);
+    }
+
+    #[test]
+    fn midi_preroll_syncs_to_audio_window_on_virtual_time() {
+        let clock = Arc::new(ManualClock::new());
+        let mut buffer = MidiPrerollBuffer::new(5).with_clock(clock.shared());
+
+        buffer.push("Keys".into(), note_on(60), 0);
+        clock.advance(Duration::from_secs(2));
+        buffer.push("Keys".into(), note_on(62), 2_000_000);
+        clock.advance(Duration::from_millis(500));
+
+        // A 1 s audio pre-roll keeps only the note played 0.5 s before the
+        // trigger, placed 0.5 s into the window
+        let drained = buffer.drain_with_audio_sync(Some(Duration::from_secs(1)));
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].1.data[1], 62);
+        assert_eq!(drained[0].1.timestamp_us, 500_000);
+    }
+}
@@ -0,0 +1,77 @@
+// Per-device audio processing applied before pre-roll and the file writers
+//
+// Covers the cases users otherwise fix in every take after the fact: an M/S
+// microphone pair recorded as mid/side, a stereo image that needs narrowing or
+// widening, and a channel wired with reversed polarity. Everything runs in
+// place on the interleaved f32 block from the device callback.
+
+use crate::config::AudioProcessing;
+
+/// Processing resolved for one input's channel count
+#[derive(Debug, Clone)]
+pub struct AudioProcessor {
+    channels: usize,
+    /// Per-channel gain of 1.0 or -1.0
+    polarity: Vec<f32>,
+    ms_decode: bool,
+    stereo_width: f32,
+}
+
+impl AudioProcessor {
+    /// Build a processor for an input with `channels` channels. Returns None
+    /// if the settings would leave the audio untouched. M/S decode and width
+    /// need a stereo pair and are ignored on mono inputs.
+    pub fn new(settings: &AudioProcessing, channels: u16) -> Option<Self> {
+        let channels = channels as usize;
+        if channels == 0 {
+            return None;
+        }
+        let polarity: Vec<f32> = (0..channels)
+            .map(|ch| if settings.invert_polarity.contains(&(ch as u16)) { -1.0 } else { 1.0 })
+            .collect();
+        let stereo = channels >= 2;
+        let processor = Self {
+            channels,
+            polarity,
+            ms_decode: stereo && settings.ms_decode,
+            stereo_width: if stereo { settings.stereo_width } else { 1.0 },
+        };
+        if processor.is_identity() {
+            None
+        } else {
+            Some(processor)
+        }
+    }
+
+    fn is_identity(&self) -> bool {
+        !self.ms_decode && self.stereo_width == 1.0 && self.polarity.iter().all(|&g| g > 0.0)
+    }
+
+    /// Process a block of interleaved samples in place
+    pub fn process(&self, data: &mut [f32]) {
+        for frame in data.chunks_exact_mut(self.channels) {
+            for (sample, gain) in frame.iter_mut().zip(&self.polarity) {
+                *sample *= gain;
+            }
+            if self.channels < 2 {
+                continue;
+            }
+
+            let (mut left, mut right) = (frame[0], frame[1]);
+            if self.ms_decode {
+                // Unity-gain decode: L = M + S, R = M - S
+                let (mid, side) = (left, right);
+                left = mid + side;
+                right = mid - side;
+            }
+            if self.stereo_width != 1.0 {
+                let mid = (left + right) * 0.5;
+                let side = (left - right) * 0.5 * self.stereo_width;
+                left = mid + side;
+                right = mid - side;
+            }
+            frame[0] = left;
+            frame[1] = right;
+        }
+    }
+}
@@ -0,0 +1,243 @@
+// Recording-quality measurement
+//
+// Audio writers meter every sample they receive (clipping, peak, DC offset)
+// and each audio input watches its capture timestamps for gaps, which are
+// filled with the same length of silence so the file keeps its place against
+// video. Video pipelines count dropped frames and encoder stalls. The results
+// are folded into the session's quality report at finalize so a clipped or
+// glitchy take is flagged in review right away.
+//
+// Finished FLAC files are also decoded back once (flacdec into a counting
+// sink, far faster than real time) because encoder glitches have produced
+// files with undecodable stretches that nothing noticed until playback.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::session::{AudioQuality, DecodeCheck, DropoutEvent, SessionQuality, TimelineWarning, TimelineWarningKind};
+
+/// Samples at or beyond this magnitude count as clipped
+const CLIP_LEVEL: f32 = 0.999;
+
+/// Gaps shorter than this are timestamp jitter, not lost audio
+const MIN_DROPOUT: Duration = Duration::from_millis(3);
+
+/// Longest gap filled with silence. Anything longer is a clock jump or a
+/// device that went away, not an xrun.
+pub const MAX_CONCEALMENT: Duration = Duration::from_secs(10);
+
+/// Concealment events kept per file (the totals keep counting past this)
+const MAX_DROPOUT_EVENTS: usize = 100;
+
+/// Running signal statistics for one audio file
+#[derive(Debug, Clone)]
+pub struct AudioQualityMeter {
+    channels: usize,
+    clipped_samples: u64,
+    peak: f32,
+    channel_sums: Vec<f64>,
+    frames: u64,
+    dropouts: u32,
+    dropout_secs: f64,
+    dropout_events: Vec<DropoutEvent>,
+}
+
+impl AudioQualityMeter {
+    pub fn new(channels: u16) -> Self {
+        let channels = (channels as usize).max(1);
+        Self {
+            channels,
+            clipped_samples: 0,
+            peak: 0.0,
+            channel_sums: vec![0.0; channels],
+            frames: 0,
+            dropouts: 0,
+            dropout_secs: 0.0,
+            dropout_events: Vec::new(),
+        }
+    }
+
+    /// Meter a block of interleaved samples
+    pub fn process(&mut self, data: &[f32]) {
+        for frame in data.chunks_exact(self.channels) {
+            for (sum, &sample) in self.channel_sums.iter_mut().zip(frame) {
+                let magnitude = sample.abs();
+                if magnitude >= CLIP_LEVEL {
+                    self.clipped_samples += 1;
+                }
+                self.peak = self.peak.max(magnitude);
+                *sum += sample as f64;
+            }
+            self.frames += 1;
+        }
+    }
+
+    /// Record a gap in the capture timeline found `at_secs` into the file
+    /// and how much of it was filled with silence
+    pub fn note_dropout(&mut self, at_secs: f64, gap: Duration, concealed: Duration) {
+        self.dropouts += 1;
+        self.dropout_secs += gap.as_secs_f64();
+        if self.dropout_events.len() < MAX_DROPOUT_EVENTS {
+            self.dropout_events.push(DropoutEvent {
+                at_secs,
+                gap_ms: gap.as_secs_f64() * 1000.0,
+                concealed_ms: concealed.as_secs_f64() * 1000.0,
+            });
+        }
+    }
+
+    pub fn report(&self, filename: &str, device_name: &str) -> AudioQuality {
+        let dc_offset = if self.frames == 0 {
+            0.0
+        } else {
+            self.channel_sums
+                .iter()
+                .map(|sum| (sum / self.frames as f64) as f32)
+                .fold(0.0f32, |worst, mean| if mean.abs() > worst.abs() { mean } else { worst })
+        };
+        AudioQuality {
+            filename: filename.to_string(),
+            device_name: device_name.to_string(),
+            clipped_samples: self.clipped_samples,
+            peak: self.peak,
+            dc_offset,
+            dropouts: self.dropouts,
+            dropout_secs: self.dropout_secs,
+            dropout_events: self.dropout_events.clone(),
+            decode_check: None,
+        }
+    }
+}
+
+/// Watches an input's capture timestamps for blocks that arrive later than
+/// the audio already delivered accounts for
+#[derive(Debug)]
+pub struct DropoutDetector {
+    sample_rate: u32,
+    channels: usize,
+    expected_next: Option<cpal::StreamInstant>,
+}
+
+impl DropoutDetector {
+    pub fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            sample_rate,
+            channels: (channels as usize).max(1),
+            expected_next: None,
+        }
+    }
+
+    /// Check a block captured at `capture` holding `samples` interleaved
+    /// samples. Returns the length of the gap before it, if any.
+    pub fn check(&mut self, capture: cpal::StreamInstant, samples: usize) -> Option<Duration> {
+        let frames = samples / self.channels;
+        let block = Duration::from_secs_f64(frames as f64 / self.sample_rate as f64);
+        let gap = self
+            .expected_next
+            .and_then(|expected| capture.duration_since(&expected))
+            .filter(|gap| *gap >= MIN_DROPOUT.max(block / 2));
+        self.expected_next = capture.add(block);
+        gap
+    }
+}
+
+/// Fold the timestamp gaps found by the timeline check into the video entries
+pub fn apply_timeline_gaps(quality: &mut SessionQuality, warnings: &[TimelineWarning]) {
+    for video in quality.video.iter_mut() {
+        video.timestamp_gaps = warnings
+            .iter()
+            .filter(|w| w.kind == TimelineWarningKind::Gap && w.filename == video.filename)
+            .count() as u32;
+    }
+}
+
+/// Decoded length may differ from what was encoded by the resampler's
+/// rounding; anything beyond this is lost audio
+const DECODE_TOLERANCE_SECS: f64 = 0.05;
+
+/// Decode a finished FLAC file and compare its length with the
+/// `expected_frames` (at `sample_rate`) that went into the encoder
+pub fn verify_flac(path: &Path, expected_frames: u64, sample_rate: u32) -> DecodeCheck {
+    let (decoded_frames, error) = match decode_flac(path, expected_frames as f64 / sample_rate.max(1) as f64) {
+        Ok(result) => result,
+        Err(e) => (0, Some(e.to_string())),
+    };
+    let tolerance = (DECODE_TOLERANCE_SECS * sample_rate as f64) as u64;
+    let passed = error.is_none() && decoded_frames.abs_diff(expected_frames) <= tolerance;
+    if !passed {
+        println!(
+            "[Sacho] FLAC verification failed for {}: decoded {} of {} frames{}",
+            path.display(),
+            decoded_frames,
+            expected_frames,
+            error.as_deref().map(|e| format!(" ({})", e)).unwrap_or_default()
+        );
+    }
+    DecodeCheck { passed, expected_frames, decoded_frames, error }
+}
+
+/// Run filesrc ! flacparse ! flacdec ! fakesink, counting decoded frames.
+/// Decoder warnings (lost sync, bad frames) are returned with the count.
+fn decode_flac(path: &Path, duration_secs: f64) -> anyhow::Result<(u64, Option<String>)> {
+    use gstreamer as gst;
+    use gstreamer::prelude::*;
+
+    let pipeline = gst::parse::launch("filesrc name=src ! flacparse ! flacdec name=dec ! fakesink sync=false")
+    .map_err(|e| anyhow::anyhow!("Failed to build verify pipeline: {}", e))?
+    .downcast::<gst::Pipeline>()
+    .map_err(|_| anyhow::anyhow!("Verify pipeline is not a pipeline"))?;
+    let src = pipeline.by_name("src").ok_or_else(|| anyhow::anyhow!("No filesrc"))?;
+    src.set_property("location", path.to_string_lossy().to_string());
+    let dec = pipeline.by_name("dec").ok_or_else(|| anyhow::anyhow!("No flacdec"))?;
+    let dec_src = dec.static_pad("src").ok_or_else(|| anyhow::anyhow!("No flacdec src pad"))?;
+
+    let bytes = Arc::new(AtomicU64::new(0));
+    let counted = bytes.clone();
+    dec_src.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+        if let Some(buffer) = info.buffer() {
+            counted.fetch_add(buffer.size() as u64, Ordering::Relaxed);
+        }
+        gst::PadProbeReturn::Ok
+    });
+
+    pipeline.set_state(gst::State::Playing)
+        .map_err(|e| anyhow::anyhow!("Failed to start verify pipeline: {}", e))?;
+
+    let bus = pipeline.bus().ok_or_else(|| anyhow::anyhow!("No pipeline bus"))?;
+    let timeout = gst::ClockTime::from_seconds((duration_secs / 20.0).max(30.0) as u64);
+    let mut problem = None;
+    let mut finished = false;
+    for msg in bus.iter_timed(timeout) {
+        match msg.view() {
+            gst::MessageView::Eos(..) => {
+                finished = true;
+                break;
+            }
+            gst::MessageView::Error(err) => {
+                problem = Some(err.error().to_string());
+                break;
+            }
+            gst::MessageView::Warning(warning) => {
+                problem.get_or_insert_with(|| warning.error().to_string());
+            }
+            _ => {}
+        }
+    }
+    if !finished && problem.is_none() {
+        problem = Some("Decoding timed out".to_string());
+    }
+
+    let bytes_per_frame = dec_src
+        .current_caps()
+        .and_then(|caps| gstreamer_audio::AudioInfo::from_caps(&caps).ok())
+        .map(|info| info.bpf() as u64)
+        .unwrap_or(0);
+    pipeline.set_state(gst::State::Null).ok();
+
+    if bytes_per_frame == 0 {
+        anyhow::bail!(problem.unwrap_or_else(|| "Decoder produced no audio".to_string()));
+    }
+    Ok((bytes.load(Ordering::Relaxed) / bytes_per_frame, problem))
+}
@@ -0,0 +1,73 @@
+// Sample-rate checks
+//
+// Each audio device is captured at the rate its driver opens it with. A
+// device whose rate differs from the configured output rate gets resampled,
+// and devices running at different rates produce files that don't line up
+// sample-for-sample once imported into a DAW. Both cases are reported before
+// recording starts. With "resample to a common rate" on, a passthrough
+// session with mixed rates is written at the highest of them instead.
+
+use serde::Serialize;
+
+use crate::config::Config;
+
+/// Capture rate of one recorded device
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceRate {
+    pub device_name: String,
+    pub rate: u32,
+}
+
+/// A rate problem worth telling the user about before they record
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SampleRateWarning {
+    /// The device runs at a different rate than the configured output and
+    /// will be resampled
+    Resampled {
+        device_name: String,
+        device_rate: u32,
+        target_rate: u32,
+    },
+    /// Recorded devices run at different rates and the files will keep them
+    MixedRates { devices: Vec<DeviceRate> },
+}
+
+/// Rate every file in a session is written at when the devices disagree and
+/// resampling to a common rate is on (the highest, so nothing is downsampled)
+pub fn common_rate(config: &Config, rates: &[u32]) -> Option<u32> {
+    if !config.resample_to_common_rate || config.audio_sample_rate().target_rate().is_some() {
+        return None;
+    }
+    let highest = rates.iter().copied().max()?;
+    rates.iter().any(|&r| r != highest).then_some(highest)
+}
+
+/// Rate a device's file is written at
+pub fn output_rate(config: &Config, native_rate: u32, common: Option<u32>) -> u32 {
+    config.audio_sample_rate().target_rate().or(common).unwrap_or(native_rate)
+}
+
+/// Warnings for the given devices under the current settings
+pub fn check(config: &Config, devices: &[DeviceRate]) -> Vec<SampleRateWarning> {
+    let rates: Vec<u32> = devices.iter().map(|d| d.rate).collect();
+    let common = common_rate(config, &rates);
+
+    let mut warnings: Vec<SampleRateWarning> = devices
+        .iter()
+        .filter_map(|d| {
+            let target = output_rate(config, d.rate, common);
+            (target != d.rate).then(|| SampleRateWarning::Resampled {
+                device_name: d.device_name.clone(),
+                device_rate: d.rate,
+                target_rate: target,
+            })
+        })
+        .collect();
+
+    let mixed = rates.iter().any(|&r| Some(&r) != rates.first());
+    if mixed && config.audio_sample_rate().target_rate().is_none() && common.is_none() {
+        warnings.push(SampleRateWarning::MixedRates { devices: devices.to_vec() });
+    }
+    warnings
+}
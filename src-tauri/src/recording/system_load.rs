@@ -0,0 +1,99 @@
+// System load sampling for adaptive video encoding
+//
+// While recording, the video poller samples overall CPU usage and the
+// hottest temperature sensor every couple of seconds. An encoder that drops
+// frames on a busy or overheating machine is stepped down before it stalls
+// outright (see `VideoCapturePipeline::adapt_encoding`).
+
+use std::time::{Duration, Instant};
+
+use sysinfo::{Components, CpuRefreshKind, RefreshKind, System};
+
+/// Minimum time between samples (sysinfo needs a gap to measure CPU usage)
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// CPU usage at which dropped frames are blamed on the machine being busy
+const BUSY_CPU_PERCENT: f32 = 90.0;
+
+/// Degrees below a sensor's critical temperature at which CPUs start to
+/// throttle themselves
+const THROTTLE_MARGIN_C: f32 = 5.0;
+
+/// Throttling threshold for sensors that don't report a critical temperature
+const DEFAULT_THROTTLE_C: f32 = 95.0;
+
+/// One reading of the machine's load
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoadSample {
+    /// Overall CPU usage in percent (None before the first full interval)
+    pub cpu_percent: Option<f32>,
+    /// Hottest temperature sensor reading in degrees C
+    pub temperature_c: Option<f32>,
+    /// A sensor is at or near its critical temperature
+    pub thermal_throttling: bool,
+}
+
+impl LoadSample {
+    /// Whether the machine is too busy or too hot to keep up with encoding
+    pub fn is_strained(&self) -> bool {
+        self.thermal_throttling || self.cpu_percent.is_some_and(|cpu| cpu >= BUSY_CPU_PERCENT)
+    }
+}
+
+pub struct SystemLoad {
+    system: System,
+    components: Components,
+    last_sample: Option<Instant>,
+    latest: LoadSample,
+}
+
+impl SystemLoad {
+    pub fn new() -> Self {
+        Self {
+            system: System::new_with_specifics(RefreshKind::nothing().with_cpu(CpuRefreshKind::nothing().with_cpu_usage())),
+            components: Components::new_with_refreshed_list(),
+            last_sample: None,
+            latest: LoadSample::default(),
+        }
+    }
+
+    /// A new sample if the interval has passed since the last one
+    pub fn sample(&mut self) -> Option<LoadSample> {
+        if self.last_sample.is_some_and(|t| t.elapsed() < SAMPLE_INTERVAL) {
+            return None;
+        }
+        let first = self.last_sample.is_none();
+        self.last_sample = Some(Instant::now());
+
+        self.system.refresh_cpu_usage();
+        self.components.refresh(false);
+
+        let mut temperature_c: Option<f32> = None;
+        let mut thermal_throttling = false;
+        for component in self.components.iter() {
+            let Some(temp) = component.temperature().filter(|t| t.is_finite() && *t > 0.0) else {
+                continue;
+            };
+            temperature_c = Some(temperature_c.map_or(temp, |hottest| hottest.max(temp)));
+            let limit = component
+                .critical()
+                .filter(|c| *c > THROTTLE_MARGIN_C)
+                .map(|c| c - THROTTLE_MARGIN_C)
+                .unwrap_or(DEFAULT_THROTTLE_C);
+            thermal_throttling |= temp >= limit;
+        }
+
+        self.latest = LoadSample {
+            // The first refresh has nothing to compare against
+            cpu_percent: (!first).then(|| self.system.global_cpu_usage()),
+            temperature_c,
+            thermal_throttling,
+        };
+        Some(self.latest)
+    }
+
+    /// The most recent sample
+    pub fn latest(&self) -> LoadSample {
+        self.latest
+    }
+}
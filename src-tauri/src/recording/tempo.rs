@@ -0,0 +1,264 @@
+// Tempo map for recorded MIDI
+//
+// Recorded MIDI is timestamped in wall-clock time and written at a nominal
+// 120 BPM, so in a DAW the notes float free of the bar lines. When enabled,
+// the finished file's note onsets are scored against candidate beat grids to
+// estimate the tempo section by section (a performance that drifts gets a
+// tempo map, not one average), then every event is re-timed onto that grid
+// and the tempo changes are written as meta events. Each event still sounds
+// at the same moment; only the tick grid under it moves.
+
+use std::path::Path;
+
+use midly::num::{u24, u28};
+use midly::{MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+
+/// Tempo search range; faster or slower playing lands on a multiple
+const MIN_BPM: f64 = 60.0;
+const MAX_BPM: f64 = 180.0;
+const BPM_STEP: f64 = 0.25;
+/// Where the octave ambiguity (half/double tempo) is resolved towards
+const PREFERRED_BPM: f64 = 110.0;
+/// Onsets needed before a tempo is trusted
+const MIN_ONSETS: usize = 16;
+/// Note-ons closer than this count as one onset (chords, rolled chords)
+const CHORD_WINDOW_SECS: f64 = 0.05;
+/// Onset pairs further apart than this are not compared
+const MAX_PAIR_SPAN_SECS: f64 = 2.0;
+/// Tolerance of a grid match, in beats
+const GRID_SIGMA: f64 = 0.06;
+/// Best grid score over the mean score of all candidates; below this the
+/// playing has no steady pulse (rubato, free improvisation)
+const MIN_CONFIDENCE: f64 = 1.25;
+/// Length of the sections the tempo is estimated over
+const SECTION_SECS: f64 = 16.0;
+/// How far a section may stray from the overall tempo
+const SECTION_RANGE: f64 = 0.12;
+/// Sections within this ratio of the current tempo don't start a new one
+const MERGE_TOLERANCE: f64 = 0.03;
+
+/// Nominal tempo files are recorded at
+const RECORDED_US_PER_QUARTER: f64 = 500_000.0;
+
+/// A stretch of the performance at one tempo
+#[derive(Debug, Clone, Copy)]
+pub struct TempoSection {
+    pub start_secs: f64,
+    pub bpm: f64,
+}
+
+/// Estimate the tempo of a performance from its note onsets (seconds,
+/// sorted). Empty when there are too few notes or no steady pulse.
+pub fn detect_tempo(onsets: &[f64]) -> Vec<TempoSection> {
+    if onsets.len() < MIN_ONSETS {
+        return Vec::new();
+    }
+    let Some(overall) = estimate_bpm(onsets, MIN_BPM, MAX_BPM) else {
+        return Vec::new();
+    };
+
+    let first = onsets[0];
+    let last = onsets[onsets.len() - 1];
+    let mut sections: Vec<TempoSection> = Vec::new();
+    let mut start = first;
+    while start <= last {
+        let end = start + SECTION_SECS;
+        let window: Vec<f64> = onsets.iter().copied().filter(|&t| t >= start && t < end).collect();
+        let bpm = if window.len() >= MIN_ONSETS / 2 {
+            estimate_bpm(&window, overall * (1.0 - SECTION_RANGE), overall * (1.0 + SECTION_RANGE))
+        } else {
+            None
+        };
+        let bpm = bpm.unwrap_or_else(|| sections.last().map(|s| s.bpm).unwrap_or(overall));
+        let same_as_current = sections
+            .last()
+            .is_some_and(|current| (bpm / current.bpm - 1.0).abs() < MERGE_TOLERANCE);
+        if !same_as_current {
+            sections.push(TempoSection {
+                start_secs: start,
+                bpm: (bpm * 100.0).round() / 100.0,
+            });
+        }
+        start = end;
+    }
+    sections
+}
+
+/// Best-fitting tempo in a range, or None when no grid fits clearly better
+/// than the rest
+fn estimate_bpm(onsets: &[f64], min_bpm: f64, max_bpm: f64) -> Option<f64> {
+    let mut best: Option<(f64, f64)> = None;
+    let mut total = 0.0;
+    let mut count = 0usize;
+    let mut best_raw = 0.0;
+    let mut bpm = min_bpm;
+    while bpm <= max_bpm {
+        let raw = grid_score(onsets, bpm);
+        total += raw;
+        count += 1;
+        // Mild preference for a moderate tempo settles half/double-time ties
+        let weighted = raw * (-(bpm / PREFERRED_BPM).ln().powi(2) / 0.5).exp();
+        if best.map_or(true, |(_, score)| weighted > score) {
+            best = Some((bpm, weighted));
+            best_raw = raw;
+        }
+        bpm += BPM_STEP;
+    }
+    let (bpm, _) = best?;
+    let mean = total / count.max(1) as f64;
+    (mean > 0.0 && best_raw / mean >= MIN_CONFIDENCE).then_some(bpm)
+}
+
+/// How well the gaps between nearby onsets fall on whole (and, at half
+/// weight, half) beats of a tempo
+fn grid_score(onsets: &[f64], bpm: f64) -> f64 {
+    let beat = 60.0 / bpm;
+    let fit = |off: f64| (-(off * off) / (2.0 * GRID_SIGMA * GRID_SIGMA)).exp();
+    let mut score = 0.0;
+    for (i, &a) in onsets.iter().enumerate() {
+        for &b in &onsets[i + 1..] {
+            let gap = b - a;
+            if gap > MAX_PAIR_SPAN_SECS {
+                break;
+            }
+            let beats = gap / beat;
+            let off_beat = (beats - beats.round()).abs();
+            let off_half = (beats * 2.0 - (beats * 2.0).round()).abs() / 2.0;
+            score += fit(off_beat) + 0.5 * fit(off_half);
+        }
+    }
+    score
+}
+
+/// Onset times in seconds, with near-simultaneous note-ons merged
+fn collect_onsets(times: &[f64], track: &[TrackEvent]) -> Vec<f64> {
+    let mut onsets: Vec<f64> = Vec::new();
+    for (event, &secs) in track.iter().zip(times) {
+        let TrackEventKind::Midi { message: MidiMessage::NoteOn { vel, .. }, .. } = event.kind else {
+            continue;
+        };
+        if vel.as_int() == 0 {
+            continue;
+        }
+        if onsets.last().is_some_and(|&last| secs - last < CHORD_WINDOW_SECS) {
+            continue;
+        }
+        onsets.push(secs);
+    }
+    onsets
+}
+
+/// A span of the rewritten file at one tempo, starting at a whole beat
+struct GridSegment {
+    start_secs: f64,
+    start_tick: u64,
+    us_per_quarter: u32,
+}
+
+/// Lay the detected sections out on whole beats. The grid is anchored at
+/// the first onset: the pre-roll before it becomes a lead-in of whole beats
+/// (or is absorbed when it's shorter than half a beat).
+fn build_grid(sections: &[TempoSection], first_onset: f64, ppq: u16) -> Vec<GridSegment> {
+    let us_per_quarter = |bpm: f64| (60_000_000.0 / bpm).round() as u32;
+
+    let mut grid = Vec::new();
+    let opening = us_per_quarter(sections[0].bpm);
+    let lead_in_beats = (first_onset * 1_000_000.0 / opening as f64).round();
+    let (mut secs, mut tick) = if lead_in_beats >= 1.0 {
+        grid.push(GridSegment {
+            start_secs: 0.0,
+            start_tick: 0,
+            us_per_quarter: (first_onset * 1_000_000.0 / lead_in_beats).round() as u32,
+        });
+        (first_onset, lead_in_beats as u64 * ppq as u64)
+    } else {
+        (0.0, 0)
+    };
+    grid.push(GridSegment { start_secs: secs, start_tick: tick, us_per_quarter: opening });
+
+    for section in &sections[1..] {
+        let current = grid[grid.len() - 1].us_per_quarter;
+        // Move the change to the nearest beat of the running tempo
+        let beats = ((section.start_secs - secs) * 1_000_000.0 / current as f64).round().max(1.0);
+        secs += beats * current as f64 / 1_000_000.0;
+        tick += beats as u64 * ppq as u64;
+        grid.push(GridSegment {
+            start_secs: secs,
+            start_tick: tick,
+            us_per_quarter: us_per_quarter(section.bpm),
+        });
+    }
+    grid
+}
+
+fn secs_to_tick(grid: &[GridSegment], secs: f64, ppq: u16) -> u64 {
+    let segment = grid
+        .iter()
+        .rev()
+        .find(|s| s.start_secs <= secs)
+        .unwrap_or(&grid[0]);
+    let offset = (secs - segment.start_secs).max(0.0) * 1_000_000.0 / segment.us_per_quarter as f64 * ppq as f64;
+    segment.start_tick + offset.round() as u64
+}
+
+/// Detect the tempo of a just-recorded MIDI file and rewrite it onto a
+/// matching tempo map. Returns the opening BPM, or None when the file was
+/// left at its nominal 120 BPM (too few notes, no steady pulse, or already
+/// carrying a tempo map).
+pub fn apply_tempo_map(path: &Path) -> anyhow::Result<Option<f64>> {
+    let data = std::fs::read(path)?;
+    let smf = Smf::parse(&data)?;
+    let Timing::Metrical(ppq) = smf.header.timing else {
+        anyhow::bail!("MIDI file uses timecode timing");
+    };
+    let ppq = ppq.as_int();
+    let [track] = smf.tracks.as_slice() else {
+        anyhow::bail!("Expected a single-track MIDI file");
+    };
+    if track.iter().any(|e| matches!(e.kind, TrackEventKind::Meta(MetaMessage::Tempo(_)))) {
+        return Ok(None);
+    }
+
+    // Recorded files carry no tempo events, so every tick is the same length
+    let secs_per_tick = RECORDED_US_PER_QUARTER / 1_000_000.0 / ppq as f64;
+    let mut tick = 0u64;
+    let times: Vec<f64> = track
+        .iter()
+        .map(|e| {
+            tick += e.delta.as_int() as u64;
+            tick as f64 * secs_per_tick
+        })
+        .collect();
+
+    let onsets = collect_onsets(&times, track);
+    let sections = detect_tempo(&onsets);
+    if sections.is_empty() {
+        return Ok(None);
+    }
+    let grid = build_grid(&sections, onsets[0], ppq);
+
+    let mut events: Vec<TrackEvent> = Vec::with_capacity(track.len() + grid.len());
+    let mut last_tick = 0u64;
+    let mut push = |events: &mut Vec<TrackEvent>, at: u64, kind| {
+        let delta = at.saturating_sub(last_tick);
+        last_tick = last_tick.max(at);
+        events.push(TrackEvent { delta: u28::new(delta as u32), kind });
+    };
+    let mut segments = grid.iter().peekable();
+    for (event, &secs) in track.iter().zip(&times) {
+        let at = secs_to_tick(&grid, secs, ppq);
+        // Tempo changes go ahead of the events they apply to
+        while let Some(segment) = segments.next_if(|s| s.start_tick <= at) {
+            push(&mut events, segment.start_tick, TrackEventKind::Meta(MetaMessage::Tempo(u24::new(segment.us_per_quarter))));
+        }
+        push(&mut events, at, event.kind);
+    }
+
+    let mut rewritten = Smf::new(smf.header);
+    rewritten.tracks.push(events);
+    let temp_path = path.with_extension("mid.tmp");
+    rewritten.save(&temp_path)?;
+    std::fs::rename(&temp_path, path)?;
+
+    Ok(Some(sections[0].bpm))
+}
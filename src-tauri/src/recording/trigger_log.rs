@@ -0,0 +1,165 @@
+// Trigger history
+//
+// Keeps a rolling log of every note-on and above-threshold audio window that
+// reached the trigger logic, with what it measured and what happened, so a
+// recording nobody remembers starting can be traced back to the device and
+// level that caused it. Audio above the threshold arrives every 50ms while
+// someone plays, so repeats of the same outcome from the same device are
+// folded into one entry.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+/// Entries kept; older ones are dropped first
+const MAX_ENTRIES: usize = 500;
+
+/// Repeats closer together than this are folded into the earlier entry
+const COALESCE_WINDOW: Duration = Duration::from_secs(1);
+
+/// How many recent entries are searched for one to fold a repeat into (several
+/// trigger devices can be active at once)
+const COALESCE_LOOKBACK: usize = 8;
+
+static HISTORY: Mutex<VecDeque<TriggerEvaluation>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerSource {
+    Midi,
+    Audio,
+}
+
+/// What the trigger logic did with an evaluation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerOutcome {
+    /// A recording was started
+    Started,
+    /// A recording was already running or starting (keeps it from going idle)
+    AlreadyRecording,
+    /// Devices were being reinitialized
+    Initializing,
+    /// No device is selected for recording
+    NoDevicesSelected,
+    /// Audio trigger ignored because something was playing through the speakers
+    SuppressedPlayback,
+    /// Triggers are disarmed by a privacy pause
+    PrivacyPaused,
+}
+
+/// What set off a trigger evaluation
+#[derive(Debug, Clone)]
+pub struct TriggerCause {
+    pub source: TriggerSource,
+    pub device: String,
+    /// Note-on velocity (0-127) or window RMS (0.0-1.0)
+    pub value: f64,
+    /// RMS threshold for audio triggers
+    pub threshold: Option<f64>,
+}
+
+impl TriggerCause {
+    pub fn midi(device: &str, velocity: u8) -> Self {
+        Self {
+            source: TriggerSource::Midi,
+            device: device.to_string(),
+            value: velocity as f64,
+            threshold: None,
+        }
+    }
+
+    pub fn audio(device: &str, rms: f32, threshold: f64) -> Self {
+        Self {
+            source: TriggerSource::Audio,
+            device: device.to_string(),
+            value: rms as f64,
+            threshold: Some(threshold),
+        }
+    }
+}
+
+/// One entry in the trigger history
+#[derive(Debug, Clone, Serialize)]
+pub struct TriggerEvaluation {
+    /// When the first evaluation of this entry happened
+    pub at: DateTime<Utc>,
+    /// When the last folded repeat happened
+    pub last_at: DateTime<Utc>,
+    pub source: TriggerSource,
+    pub device: String,
+    /// Highest velocity or RMS seen across the folded repeats
+    pub value: f64,
+    pub threshold: Option<f64>,
+    pub outcome: TriggerOutcome,
+    /// Number of evaluations folded into this entry
+    pub count: u32,
+    #[serde(skip)]
+    updated: Instant,
+}
+
+/// Record the outcome of a trigger evaluation
+pub fn record(cause: &TriggerCause, outcome: TriggerOutcome) {
+    let now = Utc::now();
+    let mut history = HISTORY.lock();
+
+    // A start is always its own entry so it can't be hidden in a run of repeats
+    if outcome != TriggerOutcome::Started {
+        let repeat = history.iter_mut().rev().take(COALESCE_LOOKBACK).find(|entry| {
+            entry.outcome == outcome
+                && entry.source == cause.source
+                && entry.device == cause.device
+                && entry.updated.elapsed() < COALESCE_WINDOW
+        });
+        if let Some(entry) = repeat {
+            entry.last_at = now;
+            entry.value = entry.value.max(cause.value);
+            entry.count += 1;
+            entry.updated = Instant::now();
+            return;
+        }
+    }
+
+    if outcome == TriggerOutcome::Started {
+        println!(
+            "[Trigger] {} ({:?}) started a recording: value {:.4}{}",
+            cause.device,
+            cause.source,
+            cause.value,
+            cause.threshold.map(|t| format!(", threshold {:.4}", t)).unwrap_or_default()
+        );
+    }
+
+    if history.len() >= MAX_ENTRIES {
+        history.pop_front();
+    }
+    history.push_back(TriggerEvaluation {
+        at: now,
+        last_at: now,
+        source: cause.source,
+        device: cause.device.clone(),
+        value: cause.value,
+        threshold: cause.threshold,
+        outcome,
+        count: 1,
+        updated: Instant::now(),
+    });
+}
+
+/// The most recent entries, newest first
+pub fn history(limit: Option<usize>) -> Vec<TriggerEvaluation> {
+    let history = HISTORY.lock();
+    history
+        .iter()
+        .rev()
+        .take(limit.unwrap_or(MAX_ENTRIES))
+        .cloned()
+        .collect()
+}
+
+pub fn clear() {
+    HISTORY.lock().clear();
+}
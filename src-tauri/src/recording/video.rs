@@ -9,7 +9,7 @@
 
 use parking_lot::Mutex;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -19,8 +19,14 @@ use gstreamer::prelude::*;
 use gstreamer_app as gst_app;
 
 use crate::encoding::{AsyncVideoEncoder, EncoderConfig, HardwareEncoderType, RawVideoFrame};
-use crate::session::VideoFileInfo;
-
+use crate::encoding::stream_out::StreamTap;
+use super::blank_frames::BlankFrameProbe;
+use super::frame_pool::{FrameData, FramePool};
+use super::system_load::{LoadSample, SystemLoad};
+use super::virtual_camera::VirtualCameraOutput;
+use crate::session::{AdaptationStep, EncodingAdaptation, VideoFileInfo};
+
+use super::clock::{self, SharedClock};
 use super::preroll::MAX_PRE_ROLL_SECS_ENCODED;
 
 /// Warning emitted when a video device delivers frames at a significantly
@@ -32,6 +38,15 @@ pub struct VideoFpsWarning {
     pub expected_fps: f64,
 }
 
+/// Warning emitted when a camera delivers near-black frames at recording start
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct BlankVideoWarning {
+    pub device_id: String,
+    pub device_name: String,
+    /// Brightest mean luma (0-255) among the measured frames
+    pub mean_luma: f64,
+}
+
 /// Error type for video capture operations
 #[derive(Debug, thiserror::Error)]
 pub enum VideoError {
@@ -56,8 +71,9 @@ pub type Result<T> = std::result::Result<T, VideoError>;
 /// Buffered video frame with timestamp
 #[derive(Clone)]
 pub struct BufferedFrame {
-    /// Frame data (encoded, e.g., MJPEG or raw)
-    pub data: Vec<u8>,
+    /// Frame data (encoded, e.g., MJPEG or raw), shared rather than copied
+    /// between the pre-roll buffer, the poll thread and writers
+    pub data: FrameData,
     /// Presentation timestamp in nanoseconds
     pub pts: u64,
     /// Duration in nanoseconds
@@ -84,6 +100,8 @@ pub struct VideoPrerollBuffer {
     /// Maximum buffer size in bytes (to prevent unbounded memory usage)
     max_bytes: usize,
     current_bytes: usize,
+    /// Time source for trimming (injectable for tests)
+    clock: SharedClock,
 }
 
 impl VideoPrerollBuffer {
@@ -109,15 +127,28 @@ impl VideoPrerollBuffer {
         let max_bytes = (bytes_per_sec as f64 * total_secs) as usize;
 
         Self {
-            frames: std::collections::VecDeque::new(),
+            // Frame slots for the whole window up front (up to 60 fps), so the
+            // queue doesn't reallocate while the buffer first fills
+            frames: std::collections::VecDeque::with_capacity((total_secs * 60.0) as usize),
             max_duration: Duration::from_secs(max_duration_secs as u64),
             headroom,
             bytes_per_sec,
             max_bytes,
             current_bytes: 0,
+            clock: clock::system(),
         }
     }
 
+    /// Use `clock` instead of the system clock for stamping and trimming
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
+    /// Current time on the buffer's clock, for stamping a frame's `wall_time`
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
     /// Push a new frame, trimming old frames if necessary
     pub fn push(&mut self, frame: BufferedFrame) {
         let frame_size = frame.data.len();
@@ -136,7 +167,7 @@ impl VideoPrerollBuffer {
         }
 
         let retention = self.max_duration + self.headroom;
-        let cutoff = Instant::now() - retention;
+        let cutoff = self.clock.now() - retention;
 
         // Trim by time (retaining headroom beyond max_duration)
         while let Some(front) = self.frames.front() {
@@ -278,6 +309,125 @@ pub struct VideoCapturePipeline {
     frames_at_last_check: u64,
     /// Whether we've already emitted a FPS mismatch warning
     fps_warning_emitted: bool,
+    /// Live stream fed with copies of recorded frames (None when not streaming)
+    stream_tap: Option<StreamTap>,
+    /// Loopback camera republishing every captured frame (shared with the appsink callback)
+    virtual_camera: Arc<Mutex<Option<VirtualCameraOutput>>>,
+    /// Segments of the current recording closed by an encoder failover
+    /// (MKV path, duration), finalized in `take_failover_segments`
+    failover_segments: Vec<(PathBuf, Duration)>,
+    /// Time source for pre-roll stamping and trimming
+    clock: SharedClock,
+    /// How many of those segments were split off to limit what a power
+    /// loss on low battery can cost, rather than by a failover
+    battery_splits: usize,
+    /// When the segment being written started
+    segment_start: Option<Instant>,
+    /// Failovers not yet reported to the frontend
+    pending_failovers: Vec<EncoderFailover>,
+    /// Keyframe spacing for encoding pipelines
+    keyframe_interval: crate::config::KeyframeInterval,
+    /// Maximum consecutive B-frames (None = encoder default)
+    b_frames: Option<u32>,
+    /// Conform encoded output to a constant frame rate
+    constant_frame_rate: bool,
+    /// Content hint for encoder tuning
+    content_type: crate::encoding::ContentType,
+    /// Region encoded at higher quality
+    roi: Option<crate::encoding::RegionOfInterest>,
+    /// Pre-roll encoder's videorate counters when the recording started
+    frame_rate_baseline: Option<crate::encoding::FrameRateStats>,
+    /// Conformance report for the last recording, taken by the manager
+    frame_rate_report: Option<crate::session::FrameRateReport>,
+    /// Encoder stalls (a second or more with no frames accepted) during this recording
+    encoder_stalls: u32,
+    /// Capture health of the last recording, taken by the manager at stop
+    quality_report: Option<crate::session::VideoQuality>,
+    /// Brightness check of the first seconds of the current recording
+    blank_probe: Option<BlankFrameProbe>,
+    /// Only every Nth frame is fed to the raw encoder (raised by adaptive
+    /// encoding, reset when the recording stops)
+    feed_divisor: u32,
+    /// Frames offered to the raw encoder this recording, for `feed_divisor`
+    feed_counter: u64,
+    /// (preset, effort) from before adaptive encoding lowered them
+    configured_preset: Option<(u8, u8)>,
+    /// Most recent system load reading from the manager
+    load: LoadSample,
+    /// `total_frames_dropped` at the previous load reading
+    dropped_at_load_check: u64,
+    /// Adaptations made during this recording, taken by the manager at stop
+    adaptations: Vec<EncodingAdaptation>,
+    /// Adaptations not yet reported to the frontend
+    pending_adaptations: Vec<EncodingAdaptation>,
+    /// Counters at the previous `live_stats` call
+    stats_sample: Option<StatsSample>,
+    /// Counters at the previous virtual camera overlay update
+    overlay_sample: Option<StatsSample>,
+    /// Build the next recording's file writer while armed
+    prewarm_writer: bool,
+    /// Writer pipeline built ahead of the next trigger (no file yet)
+    prewarmed_writer: Option<VideoWriter>,
+    /// When the current recording's first frame reached its writer or encoder
+    first_write_at: Option<Instant>,
+}
+
+/// Encoder failovers allowed within one recording
+const MAX_ENCODER_FAILOVERS: usize = 3;
+
+/// Consecutive all-dropped polls (~1s) counted as one encoder stall
+const STALL_POLLS: u32 = 30;
+
+/// Adaptive encoding feeds at most every Nth frame to the encoder
+const MAX_FEED_DIVISOR: u32 = 4;
+
+/// Live statistics for one capture pipeline, for the stats panel. Rates are
+/// measured since the previous request and are None on the first one.
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct VideoPipelineStats {
+    pub device_id: String,
+    pub device_name: String,
+    pub recording: bool,
+    /// Encoder backend, None for passthrough
+    pub encoder: Option<String>,
+    /// Output bitrate in kbit/s
+    pub bitrate_kbps: Option<f64>,
+    /// Frames encoded (or written, for passthrough) per second
+    pub encode_fps: Option<f64>,
+    /// Frames delivered by the camera per second
+    pub capture_fps: Option<f64>,
+    /// Frames waiting for the encoder
+    pub queue_frames: u64,
+    /// Frames the encoder queue holds before dropping
+    pub queue_capacity: u64,
+    pub frames_written: u64,
+    pub frames_dropped: u64,
+}
+
+/// How often the virtual camera's stats overlay is refreshed
+const OVERLAY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Counters at the previous stats request
+#[derive(Clone, Copy)]
+struct StatsSample {
+    at: Instant,
+    bytes: u64,
+    frames_out: u64,
+    frames_captured: u64,
+}
+
+/// Reported when an encoder failed mid-recording and was replaced
+#[derive(serde::Serialize, Clone, Debug)]
+pub struct EncoderFailover {
+    pub device_name: String,
+    pub failed_encoder: String,
+    pub fallback_encoder: String,
+    pub error: String,
+    /// Seconds into the recording the new segment starts (None when the
+    /// failure happened during pre-roll)
+    pub at_secs: Option<f64>,
+    /// File the recording continues in
+    pub segment_filename: Option<String>,
 }
 
 /// Generic video file writer that handles different codecs and containers
@@ -290,6 +440,13 @@ struct VideoWriter {
     /// Tracks the end of the last written frame (PTS + duration, in nanoseconds)
     /// for accurate content duration reporting.
     last_pts_end_ns: u64,
+    /// filesink, whose location is set when the writer starts
+    filesink: gst::Element,
+    /// Codec and caps the writer was built for (to match a pre-warmed writer)
+    codec: crate::encoding::VideoCodec,
+    width: u32,
+    height: u32,
+    fps: f64,
 }
 
 impl VideoWriter {
@@ -302,6 +459,20 @@ impl VideoWriter {
         width: u32,
         height: u32,
         fps: f64,
+    ) -> Result<Self> {
+        let mut writer = Self::prepare(codec, width, height, fps)?;
+        writer.start(path)?;
+        Ok(writer)
+    }
+
+    /// Build the writer pipeline without a file. Element creation (and the
+    /// plugin loading behind it on first use) is the slow part of starting a
+    /// writer; a pipeline prepared while armed only needs `start` at the trigger.
+    fn prepare(
+        codec: crate::encoding::VideoCodec,
+        width: u32,
+        height: u32,
+        fps: f64,
     ) -> Result<Self> {
         use crate::encoding::encoder::fps_to_gst_fraction;
 
@@ -336,7 +507,6 @@ impl VideoWriter {
         muxer.set_property("writing-app", "Sacho");
 
         let filesink = gst::ElementFactory::make("filesink")
-            .property("location", path.to_string_lossy().to_string())
             .property("async", false)
             .build()
             .map_err(|e| VideoError::Pipeline(format!("Failed to create filesink: {}", e)))?;
@@ -383,21 +553,48 @@ impl VideoWriter {
                 .map_err(|e| VideoError::Pipeline(format!("Failed to link elements: {}", e)))?;
         }
 
+        // READY allocates the elements' resources; the file is only opened
+        // on the way to PAUSED, so the location can still be set
+        pipeline.set_state(gst::State::Ready)?;
+
+        Ok(Self {
+            pipeline,
+            appsrc,
+            output_path: PathBuf::new(),
+            last_pts_end_ns: 0,
+            filesink,
+            codec,
+            width,
+            height,
+            fps,
+        })
+    }
+
+    /// Whether this writer was built for the given codec and caps
+    fn matches(&self, codec: crate::encoding::VideoCodec, width: u32, height: u32, fps: f64) -> bool {
+        self.codec == codec && self.width == width && self.height == height && self.fps == fps
+    }
+
+    /// Point the writer at `path` and start the pipeline
+    fn start(&mut self, path: &PathBuf) -> Result<()> {
+        self.filesink
+            .set_property("location", path.to_string_lossy().to_string());
+        self.output_path = path.clone();
+
         println!("[Video]   Elements linked, starting pipeline...");
 
         // Start pipeline with async state change (don't block)
-        pipeline.set_state(gst::State::Playing)?;
+        self.pipeline.set_state(gst::State::Playing)?;
 
         // Don't wait for state change - appsrc with is_live=true doesn't need preroll
         // The pipeline will transition to PLAYING when we push the first buffer
         println!("[Video] Writer pipeline started");
+        Ok(())
+    }
 
-        Ok(Self {
-            pipeline,
-            appsrc,
-            output_path: path.clone(),
-            last_pts_end_ns: 0,
-        })
+    /// Provenance tags, written by matroskamux when the file is finished
+    fn set_tags(&self, tags: &gst::TagList) {
+        crate::encoding::tags::apply_to_pipeline(&self.pipeline, tags);
     }
 
     fn write_frame(&mut self, frame: &BufferedFrame, pts_offset: Option<u64>) -> Result<()> {
@@ -514,30 +711,43 @@ struct PrerollEncoderOutput {
     pts_offset: Option<u64>,
     /// Target codec (needed for VideoWriter creation)
     target_codec: crate::encoding::VideoCodec,
+    /// Encoded frames and bytes produced since the encoder started
+    frames_encoded: u64,
+    bytes_encoded: u64,
+    /// Time source for stamping and trimming
+    clock: SharedClock,
 }
 
 impl PrerollEncoderOutput {
     fn new(
         max_duration_secs: u32,
         target_codec: crate::encoding::VideoCodec,
-        keyframe_interval_secs: u32,
+        keyframe_duration: Duration,
+        clock: SharedClock,
     ) -> Self {
         Self {
             buffer: std::collections::VecDeque::new(),
             max_duration: Duration::from_secs(max_duration_secs as u64),
-            keyframe_duration: Duration::from_secs(keyframe_interval_secs as u64),
+            keyframe_duration,
             current_bytes: 0,
             active_writer: None,
             pts_offset: None,
             target_codec,
+            frames_encoded: 0,
+            bytes_encoded: 0,
+            clock,
         }
     }
 
     /// Push an encoded frame. Routes to either the ring buffer or the active writer.
     fn push_encoded_frame(&mut self, frame: BufferedFrame) {
+        self.frames_encoded += 1;
+        self.bytes_encoded += frame.data.len() as u64;
         if let Some(ref mut writer) = self.active_writer {
-            // Recording active: write to file
-            if let Err(e) = writer.write_frame(&frame, self.pts_offset) {
+            // Recording active: write to file. A writer opened after an
+            // encoder failover starts its timeline at its first frame.
+            let pts_offset = *self.pts_offset.get_or_insert(frame.pts);
+            if let Err(e) = writer.write_frame(&frame, Some(pts_offset)) {
                 println!(
                     "[PrerollEncoder] Warning: Failed to write frame to writer: {}",
                     e
@@ -558,7 +768,7 @@ impl PrerollEncoderOutput {
         // we retain ~7 s of frames by time, then strip to the first keyframe,
         // leaving ≥5 s of usable pre-roll.
         let retention = self.max_duration + self.keyframe_duration;
-        let cutoff = Instant::now() - retention;
+        let cutoff = self.clock.now() - retention;
         while let Some(front) = self.buffer.front() {
             if front.wall_time < cutoff {
                 if let Some(removed) = self.buffer.pop_front() {
@@ -620,27 +830,28 @@ struct PrerollVideoEncoder {
     appsrc: gst_app::AppSrc,
     /// Shared output state (ring buffer / active writer)
     output: Arc<Mutex<PrerollEncoderOutput>>,
+    /// Encoder backend in use
+    hw_type: HardwareEncoderType,
 }
 
 impl PrerollVideoEncoder {
+    /// `config` comes from `VideoCapturePipeline::raw_encoder_config`.
+    /// `hw_type` of None picks the best available encoder
     fn new(
         width: u32,
         height: u32,
         fps: f64,
-        target_codec: crate::encoding::VideoCodec,
-        preset_level: u8,
-        effort_level: u8,
-        video_bit_depth: Option<u8>,
+        config: EncoderConfig,
         max_preroll_secs: u32,
-        target_width: Option<u32>,
-        target_height: Option<u32>,
-        target_fps: Option<f64>,
+        hw_type: Option<HardwareEncoderType>,
+        clock: SharedClock,
     ) -> Result<Self> {
-        use crate::encoding::encoder::{
-            detect_best_encoder_for_codec, AsyncVideoEncoder, EncoderConfig,
-        };
+        use crate::encoding::encoder::detect_best_encoder_for_codec;
 
-        let hw_type = detect_best_encoder_for_codec(target_codec)
+        let target_codec = config.target_codec;
+        let target_fps = config.target_fps;
+        let hw_type = hw_type
+            .or_else(|| detect_best_encoder_for_codec(target_codec))
             .ok_or_else(|| VideoError::Pipeline(
                 format!("No encoder available for {}", target_codec.display_name())
             ))?;
@@ -650,58 +861,25 @@ impl PrerollVideoEncoder {
             target_codec.display_name()
         );
 
-        let effective_fps = target_fps.unwrap_or(fps);
-        let config = EncoderConfig {
-            keyframe_interval: (effective_fps * 2.0).round() as u32,
-            target_codec,
-            preset_level,
-            effort_level,
-            video_bit_depth,
-            target_width,
-            target_height,
-            target_fps,
-        };
-
         // Create the common pipeline start (appsrc -> queue -> videoconvert [-> scale] [-> rate])
-        let pixel_format = crate::encoding::intermediate_format_for_codec(target_codec, video_bit_depth);
+        let pixel_format = crate::encoding::intermediate_format_for_codec(target_codec, config.video_bit_depth);
         let (pipeline, appsrc, chain_tail) =
             AsyncVideoEncoder::create_common_pipeline_start_with_target(
                 width,
                 height,
                 fps,
-                target_width,
-                target_height,
+                config.target_width,
+                config.target_height,
                 target_fps,
                 pixel_format,
+                config.colorimetry.as_deref(),
+                config.constant_frame_rate,
             )
             .map_err(|e| VideoError::Pipeline(format!("PrerollEncoder pipeline: {}", e)))?;
 
         // Create encoder element based on target codec
-        let encoder = match target_codec {
-            crate::encoding::VideoCodec::Av1 => {
-                AsyncVideoEncoder::create_av1_encoder(hw_type, &config)
-            }
-            crate::encoding::VideoCodec::Vp8 => {
-                AsyncVideoEncoder::create_vp8_encoder(hw_type, &config)
-            }
-            crate::encoding::VideoCodec::Vp9 => {
-                AsyncVideoEncoder::create_vp9_encoder(hw_type, &config)
-            }
-
-            crate::encoding::VideoCodec::Ffv1 => {
-                AsyncVideoEncoder::create_ffv1_encoder(hw_type, &config)
-            }
-            crate::encoding::VideoCodec::H264 => {
-                AsyncVideoEncoder::create_h264_encoder(hw_type, &config)
-            }
-            _ => {
-                return Err(VideoError::Pipeline(format!(
-                    "Unsupported codec for preroll encoding: {:?}",
-                    target_codec
-                )))
-            }
-        }
-        .map_err(|e| VideoError::Pipeline(format!("PrerollEncoder encoder: {}", e)))?;
+        let encoder = AsyncVideoEncoder::create_encoder_for_codec(hw_type, &config)
+            .map_err(|e| VideoError::Pipeline(format!("PrerollEncoder encoder: {}", e)))?;
 
         // Create appsink for encoded output
         let appsink = gst_app::AppSink::builder()
@@ -719,19 +897,26 @@ impl PrerollVideoEncoder {
             VideoError::Pipeline(format!("Failed to link PrerollEncoder elements: {}", e))
         })?;
 
-        // Create shared output.
-        // The keyframe interval is `fps * 2` frames = 2 seconds.
-        let keyframe_interval_secs = 2;
+        // Create shared output. Trimming keeps one GOP of headroom, so it
+        // follows the configured keyframe interval at the encoding fps.
+        let encode_fps = target_fps.unwrap_or(fps);
+        let keyframe_duration = if encode_fps > 0.0 {
+            Duration::from_secs_f64(config.keyframe_interval as f64 / encode_fps)
+        } else {
+            Duration::from_secs(2)
+        };
         let output = Arc::new(Mutex::new(PrerollEncoderOutput::new(
             max_preroll_secs,
             target_codec,
-            keyframe_interval_secs,
+            keyframe_duration,
+            clock,
         )));
 
         // Set up appsink callback to route encoded frames
         let output_clone = output.clone();
         // Compute default frame duration from target fps (fallback when buffer lacks duration metadata)
         let enc_default_duration_ns = (1_000_000_000.0 / target_fps.unwrap_or(fps)).round() as u64;
+        let frame_pool = FramePool::new();
         appsink.set_callbacks(
             gst_app::AppSinkCallbacks::builder()
                 .new_sample(move |sink| {
@@ -747,16 +932,17 @@ impl PrerollVideoEncoder {
                                     buffer.flags().contains(gst::BufferFlags::DELTA_UNIT);
 
                                 if let Ok(map) = buffer.map_readable() {
-                                    let data = map.as_slice().to_vec();
+                                    let data = frame_pool.copy_from(map.as_slice());
+                                    let mut output = output_clone.lock();
                                     let frame = BufferedFrame {
                                         data,
                                         pts,
                                         duration,
-                                        wall_time: Instant::now(),
+                                        wall_time: output.clock.now(),
                                         pixel_format: None, // Encoded, no pixel format
                                         is_delta_unit: is_delta,
                                     };
-                                    output_clone.lock().push_encoded_frame(frame);
+                                    output.push_encoded_frame(frame);
                                 }
                             }
                             Ok(gst::FlowSuccess::Ok)
@@ -784,9 +970,31 @@ impl PrerollVideoEncoder {
             pipeline,
             appsrc,
             output,
+            hw_type,
         })
     }
 
+    /// videorate counters since the encoder started (None without a rate stage)
+    fn frame_rate_stats(&self) -> Option<crate::encoding::FrameRateStats> {
+        crate::encoding::FrameRateStats::from_pipeline(&self.pipeline)
+    }
+
+    /// Return the first error the encoding pipeline has posted, if any
+    fn take_error(&self) -> Option<String> {
+        let msg = self.pipeline.bus()?.pop_filtered(&[gst::MessageType::Error])?;
+        match msg.view() {
+            gst::MessageView::Error(err) => Some(format!("{} ({:?})", err.error(), err.debug())),
+            _ => None,
+        }
+    }
+
+    /// Switch the running encoder to the preset in `config` (see
+    /// [`crate::encoding::encoder::switch_preset`])
+    fn switch_preset(&self, config: &EncoderConfig) -> Result<crate::encoding::encoder::PresetSwitch> {
+        crate::encoding::encoder::switch_preset(&self.pipeline, config, self.hw_type)
+            .map_err(|e| VideoError::Pipeline(format!("PrerollEncoder preset switch: {}", e)))
+    }
+
     /// Push a raw frame to be encoded.
     /// Non-blocking: if the pipeline can't accept the frame, it is silently dropped.
     fn push_frame(&self, frame: &BufferedFrame) {
@@ -822,6 +1030,11 @@ impl VideoCapturePipeline {
         device_name_hint: &str,
         matched_device: Option<gstreamer::Device>,
     ) -> Result<(gst::Element, String)> {
+        #[cfg(feature = "test-harness")]
+        if let Some(fake) = crate::test_harness::fake_devices::create_video_source(device_id) {
+            return Ok(fake);
+        }
+
         // Use the matched device (from caps lookup) or fall back to any stored device
         let gst_device =
             matched_device.or_else(|| crate::devices::enumeration::get_gst_device(device_id));
@@ -913,6 +1126,111 @@ impl VideoCapturePipeline {
         Ok((source, device_name))
     }
 
+    /// Build (but don't start) the capture pipeline for a device config:
+    /// direct capture for passthrough, decode + re-encode otherwise
+    pub fn from_device_config(
+        device_id: &str,
+        device_name: &str,
+        dev_config: &crate::config::VideoDeviceConfig,
+        pre_roll_secs: u32,
+        encode_during_preroll: bool,
+        preferred_container: crate::encoding::ContainerFormat,
+    ) -> Result<Self> {
+        let index = device_index(device_id);
+        let source_format = &dev_config.source_format;
+
+        let mut pipeline = if dev_config.passthrough {
+            Self::new_webcam(
+                index,
+                device_name,
+                device_id,
+                source_format,
+                dev_config.source_width,
+                dev_config.source_height,
+                dev_config.source_fps,
+                pre_roll_secs,
+            )?
+        } else {
+            Self::new_webcam_raw(
+                index,
+                device_name,
+                device_id,
+                source_format,
+                dev_config.source_width,
+                dev_config.source_height,
+                dev_config.source_fps,
+                pre_roll_secs,
+                dev_config.encoding_codec,
+                dev_config.encoder_type,
+                dev_config.preset_level,
+                dev_config.video_bit_depth,
+                encode_during_preroll,
+            )?
+        };
+
+        // Set container format from global preference + codec-specific rules
+        pipeline.container_format = dev_config.effective_container(preferred_container);
+        // Set target resolution/fps for encoding pipelines
+        // Resolve "Match Source" sentinels (0 / 0.0 → source values)
+        if !dev_config.passthrough {
+            let resolved = dev_config.resolved();
+            pipeline.target_width = resolved.target_width;
+            pipeline.target_height = resolved.target_height;
+            pipeline.target_fps = resolved.target_fps;
+            pipeline.effort_level = dev_config.effort_level;
+            pipeline.keyframe_interval = dev_config.keyframe_interval;
+            pipeline.b_frames = dev_config.b_frames;
+            pipeline.constant_frame_rate = dev_config.constant_frame_rate;
+            pipeline.content_type = dev_config.content_type;
+            pipeline.roi = dev_config.roi;
+        }
+        Ok(pipeline)
+    }
+
+    /// Frames delivered by the source since the pipeline started
+    pub fn frames_captured(&self) -> u64 {
+        self.frame_counter.load(Ordering::Relaxed)
+    }
+
+    /// Frames written to the current (or last) recording
+    pub fn frames_written(&self) -> u64 {
+        self.frames_written
+    }
+
+    /// Frames dropped because the encoder couldn't keep up during the
+    /// current (or last) recording
+    pub fn frames_dropped(&self) -> u64 {
+        self.total_frames_dropped
+    }
+
+    /// Width, height and framerate actually negotiated with the source
+    pub fn negotiated_mode(&self) -> Option<(u32, u32, f64)> {
+        let caps = self.appsink.static_pad("sink")?.current_caps()?;
+        let s = caps.structure(0)?;
+        let width = s.get::<i32>("width").ok()? as u32;
+        let height = s.get::<i32>("height").ok()? as u32;
+        let fps = s
+            .get::<gst::Fraction>("framerate")
+            .ok()
+            .filter(|f| f.denom() != 0)
+            .map(|f| f.numer() as f64 / f.denom() as f64)
+            .unwrap_or(0.0);
+        Some((width, height, fps))
+    }
+
+    /// Bit depth and colorimetry of the frames handed to the encoder. None for
+    /// passthrough pipelines or before caps are negotiated.
+    pub fn color_info(&self) -> Option<crate::session::VideoColorInfo> {
+        if !self.is_encoding {
+            return None;
+        }
+        let caps = self.appsink.static_pad("sink")?.current_caps()?;
+        let s = caps.structure(0)?;
+        let format = s.get::<String>("format").ok()?;
+        let colorimetry = s.get::<String>("colorimetry").ok();
+        Some(crate::session::VideoColorInfo::new(&format, colorimetry))
+    }
+
     /// Create a new capture pipeline for a webcam device with passthrough
     ///
     /// This pipeline captures video directly from the camera without re-encoding,
@@ -1037,8 +1355,11 @@ impl VideoCapturePipeline {
         let needs_frames_clone = needs_frames.clone();
         let frame_counter = Arc::new(std::sync::atomic::AtomicU64::new(0));
         let frame_counter_clone = frame_counter.clone();
+        let virtual_camera: Arc<Mutex<Option<VirtualCameraOutput>>> = Arc::new(Mutex::new(None));
+        let virtual_camera_clone = virtual_camera.clone();
         // Compute default frame duration from source fps (fallback when buffer lacks duration metadata)
         let default_duration_ns = (1_000_000_000.0 / source_fps).round() as u64;
+        let frame_pool = FramePool::new();
 
         appsink.set_callbacks(
             gst_app::AppSinkCallbacks::builder()
@@ -1048,6 +1369,10 @@ impl VideoCapturePipeline {
                         Ok(sample) => {
                             frame_counter_clone.fetch_add(1, Ordering::Relaxed);
 
+                            if let Some(output) = virtual_camera_clone.lock().as_ref() {
+                                output.push_sample(&sample);
+                            }
+
                             if !needs_frames_clone.load(Ordering::Relaxed) {
                                 // Discard: no pre-roll needed and not recording
                                 return Ok(gst::FlowSuccess::Ok);
@@ -1064,17 +1389,18 @@ impl VideoCapturePipeline {
                                     buffer.flags().contains(gst::BufferFlags::DELTA_UNIT);
 
                                 if let Ok(map) = buffer.map_readable() {
-                                    let data = map.as_slice().to_vec();
+                                    let data = frame_pool.copy_from(map.as_slice());
 
+                                    let mut preroll = preroll_clone.lock();
                                     let frame = BufferedFrame {
                                         data,
                                         pts,
                                         duration,
-                                        wall_time: Instant::now(),
+                                        wall_time: preroll.now(),
                                         pixel_format: None, // Pre-encoded, no pixel format
                                         is_delta_unit: is_delta,
                                     };
-                                    preroll_clone.lock().push(frame);
+                                    preroll.push(frame);
                                 }
                             }
                             Ok(gst::FlowSuccess::Ok)
@@ -1128,6 +1454,35 @@ impl VideoCapturePipeline {
             fps_check_start: Instant::now(),
             frames_at_last_check: 0,
             fps_warning_emitted: false,
+            stream_tap: None,
+            virtual_camera,
+            failover_segments: Vec::new(),
+            clock: clock::system(),
+            battery_splits: 0,
+            segment_start: None,
+            pending_failovers: Vec::new(),
+            keyframe_interval: Default::default(),
+            b_frames: None,
+            constant_frame_rate: false,
+            content_type: Default::default(),
+            roi: None,
+            frame_rate_baseline: None,
+            frame_rate_report: None,
+            encoder_stalls: 0,
+            quality_report: None,
+            blank_probe: None,
+            feed_divisor: 1,
+            feed_counter: 0,
+            configured_preset: None,
+            load: LoadSample::default(),
+            dropped_at_load_check: 0,
+            adaptations: Vec::new(),
+            pending_adaptations: Vec::new(),
+            stats_sample: None,
+            overlay_sample: None,
+            prewarm_writer: false,
+            prewarmed_writer: None,
+            first_write_at: None,
         })
     }
 
@@ -1335,9 +1690,28 @@ impl VideoCapturePipeline {
             "[Video] source_format={}, intermediate_format={}, encoding_codec={:?}",
             source_format, intermediate_fmt, effective_codec
         );
-        let output_caps = gst::Caps::builder("video/x-raw")
-            .field("format", intermediate_fmt)
-            .build();
+        // Keep HDR colorimetry through videoconvert when the intermediate can
+        // hold it. Without this, caps negotiation is free to settle on BT.709.
+        let source_colorimetry = input_caps
+            .structure(0)
+            .and_then(|s| s.get::<String>("colorimetry").ok());
+        let mut output_caps_builder = gst::Caps::builder("video/x-raw").field("format", intermediate_fmt);
+        if let Some(colorimetry) = source_colorimetry.as_deref() {
+            if crate::encoding::is_hdr_colorimetry(colorimetry) {
+                if crate::encoding::is_10bit_format(intermediate_fmt) {
+                    println!("[Video] Preserving HDR colorimetry {} for {}", colorimetry, device_name);
+                    output_caps_builder = output_caps_builder.field("colorimetry", colorimetry);
+                } else {
+                    println!(
+                        "[Video] Warning: {} delivers HDR ({}) but {} is encoded 8-bit; highlights will band",
+                        device_name,
+                        colorimetry,
+                        effective_codec.display_name()
+                    );
+                }
+            }
+        }
+        let output_caps = output_caps_builder.build();
 
         let output_capsfilter = gst::ElementFactory::make("capsfilter")
             .property("caps", output_caps)
@@ -1407,8 +1781,11 @@ impl VideoCapturePipeline {
         let needs_frames_clone = needs_frames.clone();
         let frame_counter = Arc::new(std::sync::atomic::AtomicU64::new(0));
         let frame_counter_clone = frame_counter.clone();
+        let virtual_camera: Arc<Mutex<Option<VirtualCameraOutput>>> = Arc::new(Mutex::new(None));
+        let virtual_camera_clone = virtual_camera.clone();
         // Compute default frame duration from source fps (fallback when buffer lacks duration metadata)
         let default_duration_ns = (1_000_000_000.0 / source_fps).round() as u64;
+        let frame_pool = FramePool::new();
 
         appsink.set_callbacks(
             gst_app::AppSinkCallbacks::builder()
@@ -1418,6 +1795,10 @@ impl VideoCapturePipeline {
                         Ok(sample) => {
                             frame_counter_clone.fetch_add(1, Ordering::Relaxed);
 
+                            if let Some(output) = virtual_camera_clone.lock().as_ref() {
+                                output.push_sample(&sample);
+                            }
+
                             if !needs_frames_clone.load(Ordering::Relaxed) {
                                 // Discard: no pre-roll needed and not recording
                                 return Ok(gst::FlowSuccess::Ok);
@@ -1437,17 +1818,18 @@ impl VideoCapturePipeline {
                                     .and_then(|s| s.get::<String>("format").ok());
 
                                 if let Ok(map) = buffer.map_readable() {
-                                    let data = map.as_slice().to_vec();
+                                    let data = frame_pool.copy_from(map.as_slice());
 
+                                    let mut preroll = preroll_clone.lock();
                                     let frame = BufferedFrame {
                                         data,
                                         pts,
                                         duration,
-                                        wall_time: Instant::now(),
+                                        wall_time: preroll.now(),
                                         pixel_format: pixel_format.clone(),
                                         is_delta_unit: false, // Not relevant for raw capture
                                     };
-                                    preroll_clone.lock().push(frame);
+                                    preroll.push(frame);
                                 }
                             }
                             Ok(gst::FlowSuccess::Ok)
@@ -1499,6 +1881,35 @@ impl VideoCapturePipeline {
             fps_check_start: Instant::now(),
             frames_at_last_check: 0,
             fps_warning_emitted: false,
+            stream_tap: None,
+            virtual_camera,
+            failover_segments: Vec::new(),
+            clock: clock::system(),
+            battery_splits: 0,
+            segment_start: None,
+            pending_failovers: Vec::new(),
+            keyframe_interval: Default::default(),
+            b_frames: None,
+            constant_frame_rate: false,
+            content_type: Default::default(),
+            roi: None,
+            frame_rate_baseline: None,
+            frame_rate_report: None,
+            encoder_stalls: 0,
+            quality_report: None,
+            blank_probe: None,
+            feed_divisor: 1,
+            feed_counter: 0,
+            configured_preset: None,
+            load: LoadSample::default(),
+            dropped_at_load_check: 0,
+            adaptations: Vec::new(),
+            pending_adaptations: Vec::new(),
+            stats_sample: None,
+            overlay_sample: None,
+            prewarm_writer: false,
+            prewarmed_writer: None,
+            first_write_at: None,
         })
     }
 
@@ -1679,35 +2090,14 @@ impl VideoCapturePipeline {
                 .encoding_codec
                 .unwrap_or_else(|| crate::encoding::get_recommended_codec());
 
-            // Compute target dimensions for preroll encoder
-            let pe_tw = if self.target_width != self.width {
-                Some(self.target_width)
-            } else {
-                None
-            };
-            let pe_th = if self.target_height != self.height {
-                Some(self.target_height)
-            } else {
-                None
-            };
-            let pe_tf = if (self.target_fps - self.fps).abs() > 0.01 {
-                Some(self.target_fps)
-            } else {
-                None
-            };
-
             match PrerollVideoEncoder::new(
                 self.width,
                 self.height,
                 self.fps,
-                target_codec,
-                self.preset_level,
-                self.effort_level,
-                self.video_bit_depth,
+                self.raw_encoder_config(target_codec),
                 self.pre_roll_secs,
-                pe_tw,
-                pe_th,
-                pe_tf,
+                None,
+                self.clock.clone(),
             ) {
                 Ok(encoder) => {
                     let output = encoder.output.clone();
@@ -1731,9 +2121,80 @@ impl VideoCapturePipeline {
             }
         }
 
+        self.prewarm_next_writer();
+
         Ok(())
     }
 
+    /// Run pre-roll stamping and trimming on `clock` (the monitor's), here
+    /// and in any pre-roll encoder started later
+    pub fn set_clock(&mut self, clock: SharedClock) {
+        self.preroll_buffer.lock().set_clock(clock.clone());
+        self.clock = clock;
+    }
+
+    /// Build the file writer pipeline for the next recording ahead of the
+    /// trigger, when enabled. Raw video encoded at record time goes through
+    /// `AsyncVideoEncoder`, which isn't pre-warmed.
+    pub fn set_prewarm_writer(&mut self, enabled: bool) {
+        self.prewarm_writer = enabled;
+        if !enabled {
+            self.prewarmed_writer = None;
+        }
+    }
+
+    /// Codec and caps of the `VideoWriter` the next recording would use, if
+    /// it uses one
+    fn writer_params(&self) -> Option<(crate::encoding::VideoCodec, u32, u32, f64)> {
+        if self.encode_during_preroll {
+            let output = self.preroll_encoder_output.as_ref()?;
+            let codec = output.lock().target_codec;
+            Some((codec, self.target_width, self.target_height, self.target_fps))
+        } else if self.is_encoding {
+            None
+        } else {
+            let (writer_caps_name, _) = crate::encoding::format_to_gst_caps(&self.source_format);
+            let codec = crate::encoding::VideoCodec::from_gst_caps_name(writer_caps_name)
+                .unwrap_or(crate::encoding::VideoCodec::Mjpeg);
+            Some((codec, self.width, self.height, self.fps))
+        }
+    }
+
+    fn prewarm_next_writer(&mut self) {
+        if !self.prewarm_writer || self.prewarmed_writer.is_some() {
+            return;
+        }
+        let Some((codec, width, height, fps)) = self.writer_params() else {
+            return;
+        };
+        match VideoWriter::prepare(codec, width, height, fps) {
+            Ok(writer) => {
+                println!("[Video] Pre-warmed {} writer for {}", codec.display_name(), self.device_name);
+                self.prewarmed_writer = Some(writer);
+            }
+            Err(e) => println!("[Video] Failed to pre-warm writer for {}: {}", self.device_name, e),
+        }
+    }
+
+    /// Start a writer at `path`, using the pre-warmed one when it was built
+    /// for the same codec and caps
+    fn open_writer(
+        &mut self,
+        path: &PathBuf,
+        codec: crate::encoding::VideoCodec,
+        width: u32,
+        height: u32,
+        fps: f64,
+    ) -> Result<VideoWriter> {
+        if let Some(mut writer) = self.prewarmed_writer.take() {
+            if writer.matches(codec, width, height, fps) {
+                writer.start(path)?;
+                return Ok(writer);
+            }
+        }
+        VideoWriter::new(path, codec, width, height, fps)
+    }
+
     /// Stop the capture pipeline
     pub fn stop(&self) -> Result<()> {
         self.pipeline.set_state(gst::State::Null)?;
@@ -1829,13 +2290,9 @@ impl VideoCapturePipeline {
 
             // Create the writer OUTSIDE the lock (pipeline creation takes a moment)
             // Use target dimensions since the preroll encoder outputs at target resolution/fps
-            let mut writer = VideoWriter::new(
-                &output_path,
-                target_codec,
-                self.target_width,
-                self.target_height,
-                self.target_fps,
-            )?;
+            let (width, height, fps) = (self.target_width, self.target_height, self.target_fps);
+            let mut writer = self.open_writer(&output_path, target_codec, width, height, fps)?;
+            writer.set_tags(&self.media_tags(&output_path));
 
             // Lock the output, drain, write pre-roll, and atomically switch to recording
             let mut output = self.preroll_encoder_output.as_ref().unwrap().lock();
@@ -1876,10 +2333,14 @@ impl VideoCapturePipeline {
             self.recording_path = Some(output_path);
             self.recording_start = Some(Instant::now());
             self.frames_written = encoded_frames.len() as u64;
+            self.first_write_at = (!encoded_frames.is_empty()).then(Instant::now);
             self.is_recording = true;
             self.needs_frames.store(true, Ordering::Relaxed);
             self.consecutive_full_drops = 0;
             self.total_frames_dropped = 0;
+            self.failover_segments.clear();
+            self.battery_splits = 0;
+            self.segment_start = Some(Instant::now());
 
             println!(
                 "[Video] Started recording (encode-during-preroll), pre-roll: {:?}",
@@ -1893,33 +2354,8 @@ impl VideoCapturePipeline {
                 .unwrap_or_else(|| crate::encoding::get_recommended_codec());
 
             // Encoding pipeline - use async encoder
-            // Use target dimensions if they differ from source
-            let use_target_w = if self.target_width != self.width {
-                Some(self.target_width)
-            } else {
-                None
-            };
-            let use_target_h = if self.target_height != self.height {
-                Some(self.target_height)
-            } else {
-                None
-            };
-            let use_target_fps = if (self.target_fps - self.fps).abs() > 0.01 {
-                Some(self.target_fps)
-            } else {
-                None
-            };
-
-            let encoder_config = EncoderConfig {
-                keyframe_interval: (self.target_fps * 2.0).round() as u32, // Keyframe every 2 seconds at target fps
-                target_codec,
-                preset_level: self.preset_level,
-                effort_level: self.effort_level,
-                video_bit_depth: self.video_bit_depth,
-                target_width: use_target_w,
-                target_height: use_target_h,
-                target_fps: use_target_fps,
-            };
+            let mut encoder_config = self.raw_encoder_config(target_codec);
+            encoder_config.tags = Some(self.media_tags(&output_path));
 
             // Create encoder with buffer size of ~2 seconds of frames for backpressure
             let buffer_size = (self.fps * 2.0) as usize;
@@ -1978,8 +2414,9 @@ impl VideoCapturePipeline {
             let (writer_caps_name, _) = crate::encoding::format_to_gst_caps(&self.source_format);
             let writer_codec = crate::encoding::VideoCodec::from_gst_caps_name(writer_caps_name)
                 .unwrap_or(crate::encoding::VideoCodec::Mjpeg);
-            let mut writer =
-                VideoWriter::new(&output_path, writer_codec, self.width, self.height, self.fps)?;
+            let (width, height, fps) = (self.width, self.height, self.fps);
+            let mut writer = self.open_writer(&output_path, writer_codec, width, height, fps)?;
+            writer.set_tags(&self.media_tags(&output_path));
 
             // Write pre-roll frames
             for frame in &preroll_frames {
@@ -1993,10 +2430,30 @@ impl VideoCapturePipeline {
         self.recording_path = Some(output_path);
         self.recording_start = Some(Instant::now());
         self.frames_written = preroll_frames.len() as u64;
+        self.first_write_at = (!preroll_frames.is_empty()).then(Instant::now);
         self.is_recording = true;
         self.needs_frames.store(true, Ordering::Relaxed);
         self.consecutive_full_drops = 0;
         self.total_frames_dropped = 0;
+        self.failover_segments.clear();
+        self.battery_splits = 0;
+        self.segment_start = Some(Instant::now());
+        self.frame_rate_report = None;
+        self.encoder_stalls = 0;
+        self.quality_report = None;
+        self.feed_divisor = 1;
+        self.feed_counter = 0;
+        self.dropped_at_load_check = 0;
+        self.adaptations.clear();
+        let frame_format = self.pixel_format.as_deref().unwrap_or(&self.source_format);
+        self.blank_probe = BlankFrameProbe::supports(frame_format)
+            .then(|| BlankFrameProbe::new(frame_format, self.width, self.height));
+        // The pre-roll encoder runs continuously, so its counters are
+        // measured from here
+        self.frame_rate_baseline = self
+            .preroll_encoder
+            .as_ref()
+            .and_then(|encoder| encoder.frame_rate_stats());
 
         println!(
             "[Video] Started recording, pre-roll: {:?}",
@@ -2011,9 +2468,13 @@ impl VideoCapturePipeline {
         if !self.is_recording {
             return Err(VideoError::Pipeline("Not recording".to_string()));
         }
+        // Before the encoders and adaptive preset are torn down
+        let encoder = self.encoder_name();
+        let preset = self.configured_preset.map(|(level, _)| level).unwrap_or(self.preset_level);
 
         // Drain any remaining frames from pre-roll buffer
         let remaining_frames = self.preroll_buffer.lock().drain();
+        let mut frame_rate = None;
 
         let (duration, file_size) = if self.encode_during_preroll
             && self.preroll_encoder_output.is_some()
@@ -2032,6 +2493,15 @@ impl VideoCapturePipeline {
             // Brief pause to let the encoder process the last frames
             std::thread::sleep(std::time::Duration::from_millis(100));
 
+            frame_rate = self
+                .preroll_encoder
+                .as_ref()
+                .and_then(|encoder| encoder.frame_rate_stats())
+                .map(|stats| match self.frame_rate_baseline.take() {
+                    Some(baseline) => stats.since(&baseline),
+                    None => stats,
+                });
+
             // Take the writer out of the output (atomically switches back to ring buffer)
             let writer = {
                 let mut output = self.preroll_encoder_output.as_ref().unwrap().lock();
@@ -2079,6 +2549,7 @@ impl VideoCapturePipeline {
             let stats = encoder
                 .finish()
                 .map_err(|e| VideoError::Pipeline(format!("Failed to finish encoding: {}", e)))?;
+            frame_rate = stats.frame_rate;
 
             (stats.content_duration, stats.bytes_written)
         } else if let Some(mut writer) = self.file_writer.take() {
@@ -2099,40 +2570,8 @@ impl VideoCapturePipeline {
         // FFV1 is skipped: GStreamer bug — matroskademux outputs caps with
         // field name "ffvversion" but matroskamux expects "ffversion", causing
         // not-negotiated error. FFV1 always stays MKV.
-        let mkv_path = self.recording_path.clone();
-        let is_ffv1 = self.encoding_codec == Some(crate::encoding::VideoCodec::Ffv1);
-
-        let (final_path, final_size) = if let Some(ref mkv_path) = mkv_path {
-            if !is_ffv1 && self.container_format != crate::encoding::ContainerFormat::Mkv {
-                // Remux MKV → target container (MP4, WebM)
-                match crate::encoding::AsyncVideoEncoder::remux_to_container(mkv_path, self.container_format) {
-                    Ok((path, size)) => {
-                        println!(
-                            "[Video] Remuxed to {}: {} bytes",
-                            self.container_format.display_name(),
-                            size
-                        );
-                        (path, size)
-                    }
-                    Err(e) => {
-                        println!("[Video] Warning: Failed to remux to {}: {}. Keeping MKV.",
-                            self.container_format.display_name(), e);
-                        (mkv_path.clone(), file_size)
-                    }
-                }
-            } else if !is_ffv1 {
-                // Target is MKV — remux in-place to fix duration header
-                match crate::encoding::AsyncVideoEncoder::remux_to_container(mkv_path, crate::encoding::ContainerFormat::Mkv) {
-                    Ok((path, size)) => (path, size),
-                    Err(e) => {
-                        println!("[Video] Warning: Failed to remux MKV duration: {}. Keeping as-is.", e);
-                        (mkv_path.clone(), file_size)
-                    }
-                }
-            } else {
-                // FFV1: skip remux entirely
-                (mkv_path.clone(), file_size)
-            }
+        let (final_path, final_size) = if let Some(mkv_path) = self.recording_path.clone() {
+            self.finalize_container(&mkv_path, file_size)
         } else {
             // No recording path (shouldn't happen)
             (PathBuf::new(), file_size)
@@ -2157,67 +2596,771 @@ impl VideoCapturePipeline {
         self.recording_path = None;
         self.recording_start = None;
 
+        // Adaptive steps last for one recording; the next starts as configured
+        self.feed_divisor = 1;
+        if let Some((level, effort_level)) = self.configured_preset.take() {
+            self.switch_preset(level, effort_level);
+        }
+
         println!(
             "[Video] Stopped recording {}, duration: {:?}, size: {} bytes",
             filename, duration, final_size
         );
 
+        if let Some(stats) = frame_rate.filter(|_| self.constant_frame_rate) {
+            println!(
+                "[Video] {} conformed to {:.2} fps: {} in, {} out, {} duplicated, {} dropped",
+                filename, self.target_fps, stats.frames_in, stats.frames_out, stats.duplicated, stats.dropped
+            );
+            self.frame_rate_report = Some(crate::session::FrameRateReport {
+                filename: filename.clone(),
+                device_name: self.device_name.clone(),
+                fps: self.target_fps,
+                frames_in: stats.frames_in,
+                frames_out: stats.frames_out,
+                duplicated: stats.duplicated,
+                dropped: stats.dropped,
+            });
+        }
+
+        let blank = self.blank_probe.take().and_then(|probe| probe.verdict());
+        self.quality_report = Some(crate::session::VideoQuality {
+            filename: filename.clone(),
+            device_name: self.device_name.clone(),
+            frames_written: self.frames_written,
+            frames_dropped: self.total_frames_dropped,
+            encoder_stalls: self.encoder_stalls,
+            // Filled in from the timeline check after finalize
+            timestamp_gaps: 0,
+            likely_blank: blank.map(|v| v.likely_blank).unwrap_or(false),
+            mean_luma: blank.map(|v| v.mean_luma),
+        });
+
+        // Ready for the next trigger
+        self.prewarm_next_writer();
+
+        let mut stream = self.stream_info(&final_path, encoder);
+        if stream.encoder.is_some() {
+            stream.preset = Some(preset);
+        }
         Ok(VideoFileInfo {
             filename,
             device_name: self.device_name.clone(),
             duration_secs: duration.as_secs_f64(),
+            proxy_filename: None,
+            color: self.color_info(),
+            start_offset_secs: 0.0,
+            stream: Some(stream),
         })
     }
 
-    /// Check if currently recording
-    pub fn is_recording(&self) -> bool {
-        self.is_recording
+    /// Encoder backend in use, None for passthrough
+    fn encoder_name(&self) -> Option<String> {
+        if let Some(ref preroll) = self.preroll_encoder {
+            Some(preroll.hw_type.display_name().to_string())
+        } else {
+            self.raw_encoder.as_ref().map(|raw| raw.hw_type().display_name().to_string())
+        }
     }
 
-    /// Get pre-roll buffer duration
-    pub fn preroll_duration(&self) -> Duration {
-        if self.encode_during_preroll {
-            if let Some(ref output) = self.preroll_encoder_output {
-                return output.lock().duration();
-            }
+    /// Codec, container and format of a file this pipeline wrote
+    fn stream_info(&self, path: &Path, encoder: Option<String>) -> crate::session::VideoStreamInfo {
+        let codec = if self.is_encoding {
+            self.encoding_codec
+                .unwrap_or_else(crate::encoding::get_recommended_codec)
+                .display_name()
+                .to_string()
+        } else {
+            let (caps_name, _) = crate::encoding::format_to_gst_caps(&self.source_format);
+            crate::encoding::VideoCodec::from_gst_caps_name(caps_name)
+                .map(|c| c.display_name().to_string())
+                .unwrap_or_else(|| self.source_format.clone())
+        };
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+        let container = crate::encoding::ContainerFormat::ALL
+            .iter()
+            .find(|c| c.extension().eq_ignore_ascii_case(extension))
+            .map(|c| c.display_name().to_string())
+            .unwrap_or_else(|| extension.to_uppercase());
+        let (width, height, fps) = if self.is_encoding {
+            (self.target_width, self.target_height, self.target_fps)
+        } else {
+            (self.width, self.height, self.fps)
+        };
+        crate::session::VideoStreamInfo {
+            codec,
+            container,
+            width,
+            height,
+            fps,
+            bit_depth: self.color_info().map(|c| c.bit_depth).unwrap_or(8),
+            encoder,
+            preset: None,
         }
-        self.preroll_buffer.lock().duration()
     }
 
-    /// Drain all frames from the pre-roll buffer.
-    /// Used by the auto-select system to feed frames to a test encoder.
-    pub fn drain_preroll_frames(&self) -> Vec<BufferedFrame> {
-        self.preroll_buffer.lock().drain()
-    }
+    /// Remux a finished MKV recording to the target container.
+    /// FFV1 is skipped: GStreamer bug — matroskademux outputs caps with
+    /// field name "ffvversion" but matroskamux expects "ffversion", causing
+    /// not-negotiated error. FFV1 always stays MKV.
+    fn finalize_container(&self, mkv_path: &PathBuf, file_size: u64) -> (PathBuf, u64) {
+        let is_ffv1 = self.encoding_codec == Some(crate::encoding::VideoCodec::Ffv1);
 
-    /// Set pre-roll duration
-    pub fn set_preroll_duration(&mut self, secs: u32) {
-        self.pre_roll_secs = secs;
-        // Update needs_frames: if not recording, only buffer when pre_roll > 0
-        if !self.is_recording {
-            self.needs_frames.store(secs > 0, Ordering::Relaxed);
-        }
-        if self.encode_during_preroll {
-            // Raw buffer stays at 1 second (staging only)
-            // Update the encoded pre-roll buffer's duration
-            if let Some(ref output) = self.preroll_encoder_output {
-                let clamped = secs.min(MAX_PRE_ROLL_SECS_ENCODED);
-                output.lock().max_duration = Duration::from_secs(clamped as u64);
+        if !is_ffv1 && self.container_format != crate::encoding::ContainerFormat::Mkv {
+            // Remux MKV → target container (MP4, WebM)
+            match crate::encoding::AsyncVideoEncoder::remux_to_container(mkv_path, self.container_format) {
+                Ok((path, size)) => {
+                    println!(
+                        "[Video] Remuxed to {}: {} bytes",
+                        self.container_format.display_name(),
+                        size
+                    );
+                    (path, size)
+                }
+                Err(e) => {
+                    println!("[Video] Warning: Failed to remux to {}: {}. Keeping MKV.",
+                        self.container_format.display_name(), e);
+                    (mkv_path.clone(), file_size)
+                }
+            }
+        } else if !is_ffv1 {
+            // Target is MKV — remux in-place to fix duration header
+            match crate::encoding::AsyncVideoEncoder::remux_to_container(mkv_path, crate::encoding::ContainerFormat::Mkv) {
+                Ok((path, size)) => (path, size),
+                Err(e) => {
+                    println!("[Video] Warning: Failed to remux MKV duration: {}. Keeping as-is.", e);
+                    (mkv_path.clone(), file_size)
+                }
             }
         } else {
-            self.preroll_buffer.lock().set_duration(secs);
+            // FFV1: skip remux entirely
+            (mkv_path.clone(), file_size)
         }
     }
 
-    /// Set the target resolution and fps for encoding (may differ from source).
-    pub fn set_target_resolution(&mut self, width: u32, height: u32, fps: f64) {
-        self.target_width = width;
-        self.target_height = height;
-        self.target_fps = fps;
-    }
-
-    /// Check if the device is delivering frames at a significantly lower rate
-    /// than the negotiated framerate. Returns a warning once after 5 seconds of
+    /// Files of the recording in progress and how long each is so far, for
+    /// a recording checkpoint
+    pub fn recording_progress(&self) -> Vec<crate::session::FileProgress> {
+        if !self.is_recording {
+            return Vec::new();
+        }
+        let current = self
+            .recording_path
+            .as_ref()
+            .map(|path| (path, self.segment_start.map(|t| t.elapsed()).unwrap_or_default()));
+        self.failover_segments
+            .iter()
+            .map(|(path, duration)| (path, *duration))
+            .chain(current)
+            .map(|(path, duration)| crate::session::FileProgress {
+                filename: path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("video")
+                    .to_string(),
+                device_name: self.device_name.clone(),
+                duration_secs: duration.as_secs_f64(),
+                event_count: 0,
+            })
+            .collect()
+    }
+
+    /// Finalize the segments closed by encoder failovers during the last
+    /// recording, in recording order
+    pub fn take_failover_segments(&mut self) -> Vec<VideoFileInfo> {
+        let segments = std::mem::take(&mut self.failover_segments);
+        segments
+            .into_iter()
+            .map(|(mkv_path, duration)| {
+                let file_size = std::fs::metadata(&mkv_path).map(|m| m.len()).unwrap_or(0);
+                let (path, _) = self.finalize_container(&mkv_path, file_size);
+                VideoFileInfo {
+                    filename: path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("video")
+                        .to_string(),
+                    device_name: self.device_name.clone(),
+                    duration_secs: duration.as_secs_f64(),
+                    proxy_filename: None,
+                    color: self.color_info(),
+                    start_offset_secs: 0.0,
+                    // The encoder may have been replaced since this segment
+                    stream: Some(self.stream_info(&path, None)),
+                }
+            })
+            .collect()
+    }
+
+    /// Constant-frame-rate report for the last recording, if conformance was on
+    pub fn take_frame_rate_report(&mut self) -> Option<crate::session::FrameRateReport> {
+        self.frame_rate_report.take()
+    }
+
+    /// Capture-health report for the last recording
+    pub fn take_quality_report(&mut self) -> Option<crate::session::VideoQuality> {
+        self.quality_report.take()
+    }
+
+    /// A near-black verdict for the current recording, reported once
+    pub fn take_blank_warning(&mut self) -> Option<BlankVideoWarning> {
+        let verdict = self.blank_probe.as_mut()?.take_blank_warning()?;
+        Some(BlankVideoWarning {
+            device_id: self.device_id.clone(),
+            device_name: self.device_name.clone(),
+            mean_luma: verdict.mean_luma,
+        })
+    }
+
+    /// Failovers since the last call
+    pub fn take_failovers(&mut self) -> Vec<EncoderFailover> {
+        std::mem::take(&mut self.pending_failovers)
+    }
+
+    /// Live encoder statistics, with rates measured since the previous call
+    pub fn live_stats(&mut self) -> VideoPipelineStats {
+        let (stats, sample) = self.stats_since(self.stats_sample);
+        self.stats_sample = Some(sample);
+        stats
+    }
+
+    /// Refresh the virtual camera's stats overlay about once a second.
+    /// Keeps its own counters so it doesn't disturb the stats panel's rates.
+    fn update_overlay(&mut self) {
+        if !self.virtual_camera.lock().as_ref().is_some_and(|output| output.has_overlay()) {
+            self.overlay_sample = None;
+            return;
+        }
+        if self.overlay_sample.is_some_and(|s| s.at.elapsed() < OVERLAY_INTERVAL) {
+            return;
+        }
+        let (stats, sample) = self.stats_since(self.overlay_sample);
+        self.overlay_sample = Some(sample);
+        if stats.capture_fps.is_none() {
+            return;
+        }
+        let rate = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.1}", v));
+        let mut text = format!(
+            "capture {} / {:.2} fps  encode {} fps",
+            rate(stats.capture_fps),
+            self.fps,
+            rate(stats.encode_fps),
+        );
+        if let Some(kbps) = stats.bitrate_kbps.filter(|&kbps| kbps > 0.0) {
+            text.push_str(&format!("  {:.0} kbit/s", kbps));
+        }
+        if let Some(output) = self.virtual_camera.lock().as_ref() {
+            output.set_overlay_text(&text);
+        }
+    }
+
+    /// Statistics with rates measured since `previous`, and the counters to
+    /// measure the next rates from
+    fn stats_since(&self, previous: Option<StatsSample>) -> (VideoPipelineStats, StatsSample) {
+        let file_size = || {
+            self.recording_path
+                .as_ref()
+                .and_then(|path| std::fs::metadata(path).ok())
+                .map(|m| m.len())
+                .unwrap_or(0)
+        };
+        let (encoder, bytes, frames_out, (queue_frames, queue_capacity)) =
+            if let Some(ref preroll) = self.preroll_encoder {
+                let output = preroll.output.lock();
+                (
+                    Some(preroll.hw_type.display_name().to_string()),
+                    output.bytes_encoded,
+                    output.frames_encoded,
+                    crate::encoding::encoder::encode_queue_level(&preroll.pipeline).unwrap_or_default(),
+                )
+            } else if let Some(ref raw) = self.raw_encoder {
+                (
+                    Some(raw.hw_type().display_name().to_string()),
+                    file_size(),
+                    raw.stats().0,
+                    raw.queue_level(),
+                )
+            } else {
+                (None, file_size(), self.frames_written, (0, 0))
+            };
+
+        let sample = StatsSample {
+            at: Instant::now(),
+            bytes,
+            frames_out,
+            frames_captured: self.frame_counter.load(Ordering::Relaxed),
+        };
+        let rate = |now: u64, before: u64, secs: f64| {
+            // Counters restart with a new encoder or recording
+            (now >= before && secs > 0.0).then(|| (now - before) as f64 / secs)
+        };
+        let (bitrate_kbps, encode_fps, capture_fps) = match previous {
+            Some(prev) => {
+                let secs = sample.at.duration_since(prev.at).as_secs_f64();
+                (
+                    rate(sample.bytes, prev.bytes, secs).map(|bytes_per_sec| bytes_per_sec * 8.0 / 1000.0),
+                    rate(sample.frames_out, prev.frames_out, secs),
+                    rate(sample.frames_captured, prev.frames_captured, secs),
+                )
+            }
+            None => (None, None, None),
+        };
+
+        let stats = VideoPipelineStats {
+            device_id: self.device_id.clone(),
+            device_name: self.device_name.clone(),
+            recording: self.is_recording,
+            encoder,
+            bitrate_kbps,
+            encode_fps,
+            capture_fps,
+            queue_frames,
+            queue_capacity,
+            frames_written: self.frames_written,
+            frames_dropped: self.total_frames_dropped,
+        };
+        (stats, sample)
+    }
+
+    /// Encoding adaptations since the last call
+    pub fn take_pending_adaptations(&mut self) -> Vec<EncodingAdaptation> {
+        std::mem::take(&mut self.pending_adaptations)
+    }
+
+    /// Encoding adaptations made during the last recording
+    pub fn take_adaptations(&mut self) -> Vec<EncodingAdaptation> {
+        std::mem::take(&mut self.adaptations)
+    }
+
+    /// Take a new system load reading. Frames dropped since the previous
+    /// reading on a busy or overheating machine step encoding down.
+    pub fn check_load(&mut self, load: LoadSample) {
+        self.load = load;
+        let dropped = self.total_frames_dropped.saturating_sub(self.dropped_at_load_check);
+        self.dropped_at_load_check = self.total_frames_dropped;
+        if !self.is_recording || dropped == 0 || !load.is_strained() {
+            return;
+        }
+        let reason = if load.thermal_throttling {
+            "Dropping frames while the CPU is overheating"
+        } else {
+            "Dropping frames while the CPU is busy"
+        };
+        self.adapt_encoding(reason);
+    }
+
+    /// Lighten the recording encoder by one step: lower the preset and
+    /// effort, and once they're at the minimum, halve the frames fed to the
+    /// encoder. Returns false when there's nothing left to lower.
+    fn adapt_encoding(&mut self, reason: &str) -> bool {
+        if !self.is_recording || self.raw_encoder.is_none() {
+            return false;
+        }
+
+        let min = crate::encoding::MIN_PRESET;
+        let step = if self.preset_level > min || self.effort_level > min {
+            let (from_preset, from_effort) = (self.preset_level, self.effort_level);
+            self.configured_preset.get_or_insert((from_preset, from_effort));
+            let to_preset = from_preset.saturating_sub(1).max(min);
+            let to_effort = from_effort.saturating_sub(1).max(min);
+            self.switch_preset(to_preset, to_effort);
+            AdaptationStep::Preset { from_preset, to_preset, from_effort, to_effort }
+        } else if self.feed_divisor < MAX_FEED_DIVISOR {
+            let from_fps = self.target_fps / self.feed_divisor as f64;
+            self.feed_divisor *= 2;
+            AdaptationStep::FrameRate {
+                from_fps,
+                to_fps: self.target_fps / self.feed_divisor as f64,
+            }
+        } else {
+            return false;
+        };
+
+        let adaptation = EncodingAdaptation {
+            device_name: self.device_name.clone(),
+            at_secs: self.recording_start.map(|t| t.elapsed().as_secs_f64()).unwrap_or(0.0),
+            step,
+            reason: reason.to_string(),
+            cpu_percent: self.load.cpu_percent,
+            temperature_c: self.load.temperature_c,
+        };
+        println!("[Video] {}: {}, lowering encoding load: {:?}", self.device_name, reason, adaptation.step);
+        self.adaptations.push(adaptation.clone());
+        self.pending_adaptations.push(adaptation);
+        true
+    }
+
+    /// Name to record this device under: its alias, or its own name
+    pub fn label(&self) -> String {
+        let usb_path = crate::devices::aliases::video_usb_path(&self.device_id);
+        crate::devices::aliases::label(crate::devices::aliases::DeviceKind::Video, &self.device_name, usb_path.as_deref())
+    }
+
+    /// Codec of the recorded file, for file names ("mjpeg", "av1", ...)
+    pub fn codec_label(&self) -> String {
+        match self.encoding_codec.filter(|_| self.is_encoding) {
+            Some(codec) => format!("{:?}", codec).to_lowercase(),
+            None => self.source_format.to_lowercase(),
+        }
+    }
+
+    /// Provenance tags for a file this pipeline records into `path`
+    fn media_tags(&self, path: &Path) -> gst::TagList {
+        let session_path = path.parent().unwrap_or(path);
+        crate::encoding::tags::MediaTags::new(session_path, &self.label()).to_tag_list()
+    }
+
+    /// Encoder settings for this pipeline's raw encoding path
+    fn raw_encoder_config(&self, target_codec: crate::encoding::VideoCodec) -> EncoderConfig {
+        // Use target dimensions if they differ from source
+        let use_target_w = if self.target_width != self.width {
+            Some(self.target_width)
+        } else {
+            None
+        };
+        let use_target_h = if self.target_height != self.height {
+            Some(self.target_height)
+        } else {
+            None
+        };
+        let use_target_fps = if (self.target_fps - self.fps).abs() > 0.01 {
+            Some(self.target_fps)
+        } else {
+            None
+        };
+
+        EncoderConfig {
+            keyframe_interval: self.keyframe_interval.frames(self.target_fps),
+            b_frames: self.b_frames,
+            content_type: self.content_type,
+            roi: self.roi,
+            target_codec,
+            preset_level: self.preset_level,
+            effort_level: self.effort_level,
+            video_bit_depth: self.video_bit_depth,
+            target_width: use_target_w,
+            target_height: use_target_h,
+            target_fps: use_target_fps,
+            colorimetry: self.color_info().and_then(|c| c.colorimetry),
+            constant_frame_rate: self.constant_frame_rate,
+            tags: None,
+        }
+    }
+
+    /// Change the encoder preset. A running encoder switches in place when
+    /// every changed property can change while playing; otherwise, and when
+    /// nothing is encoding, the next file or segment uses it.
+    pub fn switch_preset(&mut self, level: u8, effort_level: u8) {
+        if self.preset_level == level && self.effort_level == effort_level {
+            return;
+        }
+        self.preset_level = level;
+        self.effort_level = effort_level;
+        if !self.is_encoding {
+            return;
+        }
+
+        let target_codec = self
+            .encoding_codec
+            .unwrap_or_else(|| crate::encoding::get_recommended_codec());
+        if let Some(ref encoder) = self.preroll_encoder {
+            match encoder.switch_preset(&self.raw_encoder_config(target_codec)) {
+                Ok(switch) => println!("[Video] {} preset {} / effort {}: {:?}", self.device_name, level, effort_level, switch),
+                Err(e) => println!("[Video] {}: {}", self.device_name, e),
+            }
+        } else if let Some(ref encoder) = self.raw_encoder {
+            encoder.request_preset(level, effort_level);
+        }
+    }
+
+    /// Path for the next segment after a failover: "video_Cam.mkv" continues
+    /// in "video_Cam_part2.mkv", then "_part3" and so on
+    fn next_segment_path(&self) -> Option<PathBuf> {
+        let first = self
+            .failover_segments
+            .first()
+            .map(|(path, _)| path)
+            .or(self.recording_path.as_ref())?;
+        let stem = first.file_stem()?.to_string_lossy();
+        let part = self.failover_segments.len() + 2;
+        Some(first.with_file_name(format!("{}_part{}.mkv", stem, part)))
+    }
+
+    /// Close the current segment and note where the recording continues
+    fn begin_failover_segment(&mut self) -> Option<PathBuf> {
+        if self.failover_segments.len() - self.battery_splits >= MAX_ENCODER_FAILOVERS {
+            return None;
+        }
+        let next = self.next_segment_path()?;
+        if let Some(current) = self.recording_path.take() {
+            let duration = self.segment_start.map(|t| t.elapsed()).unwrap_or_default();
+            self.failover_segments.push((current, duration));
+        }
+        self.recording_path = Some(next.clone());
+        self.segment_start = Some(Instant::now());
+        Some(next)
+    }
+
+    fn record_failover(
+        &mut self,
+        failed: HardwareEncoderType,
+        fallback: HardwareEncoderType,
+        error: &str,
+        segment: Option<&PathBuf>,
+    ) {
+        let at_secs = segment.and(self.recording_start).map(|t| t.elapsed().as_secs_f64());
+        let segment_filename = segment
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string());
+        println!(
+            "[Video] {} encoder failed on {} ({}), continuing with {}{}",
+            failed.display_name(),
+            self.device_name,
+            error,
+            fallback.display_name(),
+            segment_filename
+                .as_ref()
+                .map(|f| format!(" in {}", f))
+                .unwrap_or_default()
+        );
+        self.pending_failovers.push(EncoderFailover {
+            device_name: self.device_name.clone(),
+            failed_encoder: failed.display_name().to_string(),
+            fallback_encoder: fallback.display_name().to_string(),
+            error: error.to_string(),
+            at_secs,
+            segment_filename,
+        });
+    }
+
+    /// Close the file being recorded and continue in a new segment, so a
+    /// sudden power loss costs at most the segment still open. Only the
+    /// recording encoder can do this; with encode-during-preroll the file
+    /// stays whole.
+    pub fn split_segment(&mut self) -> Result<bool> {
+        if !self.is_recording || self.segment_start.is_some_and(|t| t.elapsed() < Duration::from_secs(10)) {
+            return Ok(false);
+        }
+        if self.raw_encoder.is_none() {
+            return Ok(false);
+        }
+        let segment = self
+            .begin_failover_segment()
+            .ok_or_else(|| VideoError::Pipeline("No path for the next segment".into()))?;
+        self.battery_splits += 1;
+        let encoder = self.raw_encoder.take().expect("checked above");
+        let hw = encoder.hw_type();
+        if let Err(e) = encoder.finish() {
+            println!("[Video] Warning: Failed to close segment of {}: {}", self.device_name, e);
+        }
+
+        let target_codec = self
+            .encoding_codec
+            .unwrap_or_else(|| crate::encoding::get_recommended_codec());
+        let mut encoder_config = self.raw_encoder_config(target_codec);
+        encoder_config.tags = Some(self.media_tags(&segment));
+        let encoder = AsyncVideoEncoder::new_with_encoder(
+            segment.clone(),
+            self.width,
+            self.height,
+            self.fps,
+            encoder_config,
+            (self.fps * 2.0) as usize,
+            hw,
+        )
+        .map_err(|e| VideoError::Pipeline(format!("Failed to create encoder for the next segment: {}", e)))?;
+        self.raw_encoder = Some(encoder);
+        println!("[Video] {} continues in {:?}", self.device_name, segment.file_name().unwrap_or_default());
+        Ok(true)
+    }
+
+    /// Lighten encoding by one step because the laptop is low on battery
+    pub fn lower_for_battery(&mut self) -> bool {
+        self.adapt_encoding("Running low on battery")
+    }
+
+    /// Replace a failed recording encoder with a fallback backend and keep
+    /// recording in a new segment file
+    fn fail_over_raw_encoder(&mut self, error: &str) -> Result<()> {
+        let Some(failed) = self.raw_encoder.take() else {
+            return Err(VideoError::Pipeline(format!("Encoder error: {}", error)));
+        };
+        let failed_hw = failed.hw_type();
+        let target_codec = self
+            .encoding_codec
+            .unwrap_or_else(|| crate::encoding::get_recommended_codec());
+        let Some(fallback_hw) =
+            crate::encoding::encoder::failover_encoder_for_codec(target_codec, failed_hw)
+        else {
+            return Err(VideoError::Pipeline(format!(
+                "Encoder error: {} (no fallback encoder for {})",
+                error,
+                target_codec.display_name()
+            )));
+        };
+        // Joins the dead encoder thread; what it wrote stays as the first segment
+        drop(failed);
+
+        let segment = self
+            .begin_failover_segment()
+            .ok_or_else(|| VideoError::Pipeline(format!("Encoder error: {} (no more failovers)", error)))?;
+        let mut encoder_config = self.raw_encoder_config(target_codec);
+        encoder_config.tags = Some(self.media_tags(&segment));
+        let encoder = AsyncVideoEncoder::new_with_encoder(
+            segment.clone(),
+            self.width,
+            self.height,
+            self.fps,
+            encoder_config,
+            (self.fps * 2.0) as usize,
+            fallback_hw,
+        )
+        .map_err(|e| VideoError::Pipeline(format!("Failed to create fallback encoder: {}", e)))?;
+
+        self.raw_encoder = Some(encoder);
+        // Later recordings on this pipeline stay off the broken encoder
+        self.encoder_type = Some(fallback_hw);
+        self.record_failover(failed_hw, fallback_hw, error, Some(&segment));
+        Ok(())
+    }
+
+    /// Replace a failed pre-roll encoder. While recording, the segment
+    /// written so far is closed and recording continues in a new file.
+    fn fail_over_preroll_encoder(&mut self, error: &str) -> Result<()> {
+        let Some(failed) = self.preroll_encoder.take() else {
+            return Ok(());
+        };
+        let failed_hw = failed.hw_type;
+        let target_codec = self
+            .encoding_codec
+            .unwrap_or_else(|| crate::encoding::get_recommended_codec());
+        let writer = failed.output.lock().active_writer.take();
+        drop(failed);
+        self.preroll_encoder_output = None;
+
+        let Some(fallback_hw) =
+            crate::encoding::encoder::failover_encoder_for_codec(target_codec, failed_hw)
+        else {
+            if let Some(writer) = writer {
+                let _ = writer.finish();
+            }
+            return Err(VideoError::Pipeline(format!(
+                "Pre-roll encoder error: {} (no fallback encoder for {})",
+                error,
+                target_codec.display_name()
+            )));
+        };
+
+        let encoder = PrerollVideoEncoder::new(
+            self.width,
+            self.height,
+            self.fps,
+            self.raw_encoder_config(target_codec),
+            self.pre_roll_secs,
+            Some(fallback_hw),
+            self.clock.clone(),
+        )?;
+
+        let mut segment = None;
+        if let Some(writer) = writer {
+            if let Err(e) = writer.finish() {
+                println!("[Video] Warning: Failed to close segment after encoder error: {}", e);
+            }
+            let path = self
+                .begin_failover_segment()
+                .ok_or_else(|| VideoError::Pipeline(format!("Encoder error: {} (no more failovers)", error)))?;
+            let new_writer = VideoWriter::new(
+                &path,
+                target_codec,
+                self.target_width,
+                self.target_height,
+                self.target_fps,
+            )?;
+            new_writer.set_tags(&self.media_tags(&path));
+            let mut output = encoder.output.lock();
+            output.pts_offset = None;
+            output.active_writer = Some(new_writer);
+            drop(output);
+            segment = Some(path);
+        }
+
+        self.preroll_encoder_output = Some(encoder.output.clone());
+        self.preroll_encoder = Some(encoder);
+        self.encoder_type = Some(fallback_hw);
+        self.record_failover(failed_hw, fallback_hw, error, segment.as_ref());
+        Ok(())
+    }
+
+    /// Record when the first frame of a recording without pre-roll reached
+    /// the file (checked once per poll, so within a poll interval)
+    fn note_first_write(&mut self) {
+        let written = self.frames_written > 0
+            || self.preroll_encoder_output.as_ref().is_some_and(|output| {
+                output
+                    .lock()
+                    .active_writer
+                    .as_ref()
+                    .is_some_and(|writer| writer.last_pts_end_ns > 0)
+            });
+        if written {
+            self.first_write_at = Some(Instant::now());
+        }
+    }
+
+    /// When the current recording's first frame was written, if it has been
+    pub fn first_write_at(&self) -> Option<Instant> {
+        self.first_write_at
+    }
+
+    /// Check if currently recording
+    pub fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+
+    /// Get pre-roll buffer duration
+    pub fn preroll_duration(&self) -> Duration {
+        if self.encode_during_preroll {
+            if let Some(ref output) = self.preroll_encoder_output {
+                return output.lock().duration();
+            }
+        }
+        self.preroll_buffer.lock().duration()
+    }
+
+    /// Drain all frames from the pre-roll buffer.
+    /// Used by the auto-select system to feed frames to a test encoder.
+    pub fn drain_preroll_frames(&self) -> Vec<BufferedFrame> {
+        self.preroll_buffer.lock().drain()
+    }
+
+    /// Set pre-roll duration
+    pub fn set_preroll_duration(&mut self, secs: u32) {
+        self.pre_roll_secs = secs;
+        // Update needs_frames: if not recording, only buffer when pre_roll > 0
+        if !self.is_recording {
+            self.needs_frames.store(secs > 0, Ordering::Relaxed);
+        }
+        if self.encode_during_preroll {
+            // Raw buffer stays at 1 second (staging only)
+            // Update the encoded pre-roll buffer's duration
+            if let Some(ref output) = self.preroll_encoder_output {
+                let clamped = secs.min(MAX_PRE_ROLL_SECS_ENCODED);
+                output.lock().max_duration = Duration::from_secs(clamped as u64);
+            }
+        } else {
+            self.preroll_buffer.lock().set_duration(secs);
+        }
+    }
+
+    /// Set the target resolution and fps for encoding (may differ from source).
+    pub fn set_target_resolution(&mut self, width: u32, height: u32, fps: f64) {
+        self.target_width = width;
+        self.target_height = height;
+        self.target_fps = fps;
+    }
+
+    /// Check if the device is delivering frames at a significantly lower rate
+    /// than the negotiated framerate. Returns a warning once after 5 seconds of
     /// steady frame delivery (excludes startup latency).
     pub fn check_fps_mismatch(&mut self) -> Option<VideoFpsWarning> {
         if self.fps_warning_emitted {
@@ -2261,19 +3404,42 @@ impl VideoCapturePipeline {
         }
     }
 
+    /// Forward copies of frames to the live stream, if one is attached
+    fn tap_frames(&self, frames: &[BufferedFrame]) {
+        if let Some(ref tap) = self.stream_tap {
+            for frame in frames {
+                let format = frame.pixel_format.as_deref().unwrap_or(&self.source_format);
+                tap.push_video(&frame.data, format, frame.wall_time);
+            }
+        }
+    }
+
     /// Poll for new frames and write to file if recording
     /// This should be called periodically from a background thread
     pub fn poll(&mut self) -> Result<()> {
+        if self.is_recording && self.first_write_at.is_none() {
+            self.note_first_write();
+        }
+
         // When encode_during_preroll is active, we always drain the raw staging
         // buffer and feed frames to the PrerollVideoEncoder -- whether recording
         // or not. The encoder's appsink callback handles routing to the ring
         // buffer (pre-roll) or the active VideoWriter (recording).
         if self.encode_during_preroll && self.preroll_encoder.is_some() {
+            if let Some(error) = self.preroll_encoder.as_ref().and_then(|e| e.take_error()) {
+                self.fail_over_preroll_encoder(&error)?;
+            }
             if let Some(ref encoder) = self.preroll_encoder {
                 let frames = self.preroll_buffer.lock().drain();
                 for frame in &frames {
                     encoder.push_frame(frame);
                 }
+                if self.is_recording {
+                    self.tap_frames(&frames);
+                    if let Some(probe) = self.blank_probe.as_mut() {
+                        probe.offer(&frames);
+                    }
+                }
             }
             return Ok(());
         }
@@ -2284,8 +3450,12 @@ impl VideoCapturePipeline {
 
         // Drain accumulated frames
         let frames = self.preroll_buffer.lock().drain();
+        self.tap_frames(&frames);
+        if let Some(probe) = self.blank_probe.as_mut() {
+            probe.offer(&frames);
+        }
 
-        if let Some(ref encoder) = self.raw_encoder {
+        if self.raw_encoder.is_some() {
             // Raw video - send to encoder (non-blocking)
             let pixel_format = self
                 .pixel_format
@@ -2294,11 +3464,25 @@ impl VideoCapturePipeline {
             let mut frames_sent = 0u64;
             let mut frames_dropped = 0u64;
 
-            for frame in &frames {
+            // Adaptive encoding thins the feed; kept frames stretch to cover
+            // the ones skipped
+            let feed_divisor = self.feed_divisor as u64;
+            let frames: Vec<&BufferedFrame> = frames
+                .iter()
+                .filter(|_| {
+                    self.feed_counter += 1;
+                    (self.feed_counter - 1) % feed_divisor == 0
+                })
+                .collect();
+
+            let mut next = 0;
+            while next < frames.len() {
+                let Some(ref encoder) = self.raw_encoder else { break };
+                let frame = frames[next];
                 let raw_frame = RawVideoFrame {
                     data: frame.data.clone(),
                     pts: frame.pts,
-                    duration: frame.duration,
+                    duration: frame.duration * feed_divisor,
                     width: self.width,
                     height: self.height,
                     format: frame
@@ -2314,9 +3498,13 @@ impl VideoCapturePipeline {
                     Ok(false) => frames_dropped += 1, // Buffer full, frame dropped
                     Err(e) => {
                         println!("[Video] Encoder error: {}", e);
-                        return Err(VideoError::Pipeline(format!("Encoder error: {}", e)));
+                        // Swap to a fallback encoder and retry this frame in
+                        // the new segment
+                        self.fail_over_raw_encoder(&e.to_string())?;
+                        continue;
                     }
                 }
+                next += 1;
             }
 
             self.frames_written += frames_sent;
@@ -2326,6 +3514,13 @@ impl VideoCapturePipeline {
                 // Track consecutive polls where ALL frames were dropped (encoder stalled)
                 if frames_sent == 0 && !frames.is_empty() {
                     self.consecutive_full_drops += 1;
+                    if self.consecutive_full_drops == STALL_POLLS {
+                        self.encoder_stalls += 1;
+                        // Step down and give the lighter settings time to catch up
+                        if self.adapt_encoding("Encoder stalled") {
+                            self.consecutive_full_drops = 0;
+                        }
+                    }
                 } else {
                     self.consecutive_full_drops = 0;
                 }
@@ -2338,8 +3533,8 @@ impl VideoCapturePipeline {
                         frames_dropped, self.total_frames_dropped);
                 }
 
-                // If encoder has been completely stalled for ~5 seconds (e.g., 150 polls at ~30ms),
-                // it's dead — abort gracefully instead of leaking memory
+                // If encoder has been completely stalled for ~5 seconds (e.g., 150 polls at ~30ms)
+                // with nothing left to step down, it's dead — abort gracefully instead of leaking memory
                 if self.consecutive_full_drops > 150 {
                     println!("[Video] ERROR: Encoder stalled for too long ({} consecutive polls with 0 frames accepted, {} total dropped). Aborting.",
                         self.consecutive_full_drops, self.total_frames_dropped);
@@ -2375,25 +3570,89 @@ impl VideoCapturePipeline {
 
 impl Drop for VideoCapturePipeline {
     fn drop(&mut self) {
+        self.virtual_camera.lock().take();
         let _ = self.pipeline.set_state(gst::State::Null);
     }
 }
 
+/// Short description of a capture mode for logs and notifications,
+/// e.g. "MJPEG 1920x1080 @ 30fps"
+pub fn mode_label(config: &crate::config::VideoDeviceConfig) -> String {
+    let fps = config.source_fps;
+    let fps = if (fps - fps.round()).abs() < 0.01 {
+        format!("{:.0}", fps)
+    } else {
+        format!("{:.2}", fps)
+    };
+    format!(
+        "{} {}x{} @ {}fps",
+        config.source_format, config.source_width, config.source_height, fps
+    )
+}
+
+/// Device index is only used on Linux/macOS; Windows uses device_name.
+/// For name-based IDs (video-xxx), we don't have an index.
+fn device_index(device_id: &str) -> u32 {
+    device_id
+        .strip_prefix("webcam-")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or(0)
+}
+
+/// A capture pipeline that could not be created or started
+#[derive(Debug, Clone)]
+pub struct VideoStartFailure {
+    pub device_id: String,
+    pub device_name: String,
+    /// The pipeline error, or the device's own error from a standalone
+    /// try-open when that is more specific
+    pub error: String,
+}
+
+/// One rung of the fallback ladder tried when a device's configured mode
+/// fails to start
+#[derive(Debug, Clone)]
+pub struct VideoFallbackAttempt {
+    pub step: crate::session::VideoFallbackStep,
+    pub config: crate::config::VideoDeviceConfig,
+    /// Matching providers to pass over (AlternateProvider only)
+    pub provider_skip: usize,
+}
+
 /// Manages all video capture pipelines
 pub struct VideoCaptureManager {
     /// Active pipelines by device ID
     pipelines: HashMap<String, VideoCapturePipeline>,
+    /// Devices that failed during the last `start`
+    start_failures: Vec<VideoStartFailure>,
+    /// Enumerated devices, used to build a fallback ladder when a
+    /// configured mode fails
+    known_devices: HashMap<String, crate::devices::VideoDevice>,
+    /// Devices running a fallback mode since the last `start`
+    fallbacks: Vec<crate::session::VideoFallback>,
     /// Pre-roll duration in seconds
     pre_roll_secs: u32,
     /// Is currently recording
     is_recording: bool,
     /// Whether to encode video during pre-roll (encoding pipelines only)
     encode_during_preroll: bool,
+    /// Build each pipeline's file writer while armed
+    prewarm_writers: bool,
+    /// CPU and temperature readings for adaptive encoding
+    system_load: SystemLoad,
+    /// Time source handed to every pipeline
+    clock: SharedClock,
 }
 
 impl VideoCaptureManager {
     /// Create a new video capture manager
     pub fn new(pre_roll_secs: u32) -> Self {
+        Self::with_clock(pre_roll_secs, clock::system())
+    }
+
+    /// Create a video capture manager whose pipelines stamp and trim
+    /// pre-roll on `clock`
+    pub fn with_clock(pre_roll_secs: u32, clock: SharedClock) -> Self {
         // Initialize GStreamer
         if let Err(e) = gst::init() {
             println!("[Video] Warning: Failed to initialize GStreamer: {}", e);
@@ -2401,9 +3660,15 @@ impl VideoCaptureManager {
 
         Self {
             pipelines: HashMap::new(),
+            start_failures: Vec::new(),
+            known_devices: HashMap::new(),
+            fallbacks: Vec::new(),
             pre_roll_secs,
             is_recording: false,
             encode_during_preroll: false,
+            prewarm_writers: false,
+            system_load: SystemLoad::new(),
+            clock,
         }
     }
 
@@ -2412,13 +3677,37 @@ impl VideoCaptureManager {
         self.encode_during_preroll = enabled;
     }
 
-    /// Update the encoder preset level and effort level for a specific device (in-place, no pipeline restart).
+    /// Set whether pipelines build their file writer before the trigger
+    pub fn set_prewarm_writers(&mut self, enabled: bool) {
+        self.prewarm_writers = enabled;
+        for pipeline in self.pipelines.values_mut() {
+            pipeline.set_prewarm_writer(enabled);
+        }
+    }
+
+    /// Set the enumerated devices the next `start` can fall back within
+    pub fn set_known_devices(&mut self, devices: Vec<crate::devices::VideoDevice>) {
+        self.known_devices = devices.into_iter().map(|d| (d.id.clone(), d)).collect();
+    }
+
+    /// Devices that started in a fallback mode
+    pub fn active_fallbacks(&self) -> Vec<crate::session::VideoFallback> {
+        self.fallbacks
+            .iter()
+            .filter(|f| self.pipelines.contains_key(&f.device_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Update the encoder preset level and effort level for a specific device
+    /// (no pipeline restart). A running encoder, including one in the middle
+    /// of a recording, switches right away if it can (see
+    /// [`VideoCapturePipeline::switch_preset`]).
     pub fn update_preset_for_device(&mut self, device_id: &str, level: u8, effort_level: u8) {
         let clamped = level.clamp(crate::encoding::MIN_PRESET, crate::encoding::MAX_PRESET);
         let effort_clamped = effort_level.clamp(crate::encoding::MIN_PRESET, crate::encoding::MAX_PRESET);
         if let Some(pipeline) = self.pipelines.get_mut(device_id) {
-            pipeline.preset_level = clamped;
-            pipeline.effort_level = effort_clamped;
+            pipeline.switch_preset(clamped, effort_clamped);
         }
     }
 
@@ -2432,70 +3721,36 @@ impl VideoCaptureManager {
     ) -> Result<()> {
         // Stop any existing pipelines
         self.stop();
+        self.start_failures.clear();
+        self.fallbacks.clear();
 
         for (device_id, device_name, dev_config) in devices {
-            // Device index is only used on Linux/macOS; Windows uses device_name
-            // For name-based IDs (video-xxx), we don't have an index
-            let index = device_id
-                .strip_prefix("webcam-")
-                .and_then(|s| s.parse::<u32>().ok())
-                .unwrap_or(0);
-
-            let source_format = &dev_config.source_format;
-
-            // Create appropriate pipeline based on passthrough setting
-            let pipeline_result = if dev_config.passthrough {
-                // Passthrough - use direct capture pipeline
-                VideoCapturePipeline::new_webcam(
-                    index,
-                    device_name,
-                    device_id,
-                    source_format,
-                    dev_config.source_width,
-                    dev_config.source_height,
-                    dev_config.source_fps,
-                    self.pre_roll_secs,
-                )
-            } else {
-                // Encoding - decode source and re-encode
-                VideoCapturePipeline::new_webcam_raw(
-                    index,
-                    device_name,
-                    device_id,
-                    source_format,
-                    dev_config.source_width,
-                    dev_config.source_height,
-                    dev_config.source_fps,
-                    self.pre_roll_secs,
-                    dev_config.encoding_codec,
-                    dev_config.encoder_type,
-                    dev_config.preset_level,
-                    dev_config.video_bit_depth,
-                    self.encode_during_preroll,
-                )
-            };
+            let index = device_index(device_id);
+            let pipeline_result = VideoCapturePipeline::from_device_config(
+                device_id,
+                device_name,
+                dev_config,
+                self.pre_roll_secs,
+                self.encode_during_preroll,
+                preferred_container,
+            );
 
             match pipeline_result {
                 Ok(mut pipeline) => {
-                    // Set container format from global preference + codec-specific rules
-                    pipeline.container_format = dev_config.effective_container(preferred_container);
-                    // Set target resolution/fps for encoding pipelines
-                    // Resolve "Match Source" sentinels (0 / 0.0 → source values)
-                    if !dev_config.passthrough {
-                        let resolved = dev_config.resolved();
-                        pipeline.target_width = resolved.target_width;
-                        pipeline.target_height = resolved.target_height;
-                        pipeline.target_fps = resolved.target_fps;
-                        pipeline.effort_level = dev_config.effort_level;
-                    }
+                    pipeline.set_clock(self.clock.clone());
+                    pipeline.set_prewarm_writer(self.prewarm_writers);
                     if let Err(e) = pipeline.start() {
                         println!("[Video] Failed to start pipeline for {}: {}", device_id, e);
+                        // Release the device before probing it on its own
+                        drop(pipeline);
+                        self.recover_start_failure(device_id, device_name, dev_config, index, e.to_string(), preferred_container);
                         continue;
                     }
                     self.pipelines.insert(device_id.clone(), pipeline);
                 }
                 Err(e) => {
                     println!("[Video] Failed to create pipeline for {}: {}", device_id, e);
+                    self.recover_start_failure(device_id, device_name, dev_config, index, e.to_string(), preferred_container);
                 }
             }
         }
@@ -2507,6 +3762,94 @@ impl VideoCaptureManager {
         Ok(())
     }
 
+    /// Handle a device whose configured mode failed. The full pipeline's error
+    /// is usually a generic negotiation failure, so the source is opened again
+    /// on its own to get the device's real error (e.g. "busy" when another app
+    /// holds it). If the device itself opens fine, the fallback ladder is tried
+    /// before giving up on video for this device.
+    fn recover_start_failure(
+        &mut self,
+        device_id: &str,
+        device_name: &str,
+        dev_config: &crate::config::VideoDeviceConfig,
+        index: u32,
+        error: String,
+        preferred_container: crate::encoding::ContainerFormat,
+    ) {
+        let probe_error = VideoCapturePipeline::create_source_element(device_id, index, device_name, None)
+            .ok()
+            .and_then(|(source, _)| crate::devices::contention::probe_video_source(source));
+        if let Some(probe) = probe_error {
+            println!("[Video] Try-open of {} failed: {}", device_name, probe);
+            self.record_start_failure(device_id, device_name, probe);
+            return;
+        }
+
+        let ladder = self
+            .known_devices
+            .get(device_id)
+            .map(|device| device.fallback_ladder(dev_config))
+            .unwrap_or_default();
+        for attempt in ladder {
+            let config = &attempt.config;
+            println!(
+                "[Video] Trying fallback {:?} for {}: {}",
+                attempt.step,
+                device_name,
+                mode_label(config)
+            );
+            let result = crate::devices::enumeration::with_provider_skip(attempt.provider_skip, || {
+                let mut pipeline = VideoCapturePipeline::from_device_config(
+                    device_id,
+                    device_name,
+                    config,
+                    self.pre_roll_secs,
+                    self.encode_during_preroll,
+                    preferred_container,
+                )?;
+                pipeline.set_clock(self.clock.clone());
+                pipeline.set_prewarm_writer(self.prewarm_writers);
+                pipeline.start()?;
+                Ok::<_, VideoError>(pipeline)
+            });
+            match result {
+                Ok(pipeline) => {
+                    let used = if attempt.step == crate::session::VideoFallbackStep::AlternateProvider {
+                        format!("{} (alternate driver)", mode_label(config))
+                    } else {
+                        mode_label(config)
+                    };
+                    println!("[Video] {} started with fallback {}", device_name, used);
+                    self.fallbacks.push(crate::session::VideoFallback {
+                        device_id: device_id.to_string(),
+                        device_name: device_name.to_string(),
+                        step: attempt.step,
+                        requested: mode_label(dev_config),
+                        used,
+                    });
+                    self.pipelines.insert(device_id.to_string(), pipeline);
+                    return;
+                }
+                Err(e) => println!("[Video] Fallback failed for {}: {}", device_name, e),
+            }
+        }
+
+        self.record_start_failure(device_id, device_name, error);
+    }
+
+    fn record_start_failure(&mut self, device_id: &str, device_name: &str, error: String) {
+        self.start_failures.push(VideoStartFailure {
+            device_id: device_id.to_string(),
+            device_name: device_name.to_string(),
+            error,
+        });
+    }
+
+    /// Take the failures from the last `start`
+    pub fn take_start_failures(&mut self) -> Vec<VideoStartFailure> {
+        std::mem::take(&mut self.start_failures)
+    }
+
     /// Stop all capture pipelines
     pub fn stop(&mut self) {
         for (id, pipeline) in self.pipelines.drain() {
@@ -2516,22 +3859,37 @@ impl VideoCaptureManager {
         }
     }
 
-    /// Start recording on all active pipelines
-    pub fn start_recording(&mut self, session_path: &PathBuf) -> Result<Duration> {
+    /// Start recording on all active pipelines, naming files from `templates`
+    pub fn start_recording(
+        &mut self,
+        session_path: &PathBuf,
+        templates: &crate::config::FilenameTemplates,
+    ) -> Result<Duration> {
         if self.is_recording {
             return Err(VideoError::Pipeline("Already recording".to_string()));
         }
 
         let mut max_preroll = Duration::ZERO;
+        let take = crate::session::filenames::take_number(session_path);
 
         for (device_id, pipeline) in self.pipelines.iter_mut() {
             println!("[Video] Processing recording start for: {}", device_id);
 
-            let safe_name = crate::session::sanitize_device_name(&pipeline.device_name);
+            let label = pipeline.label();
+            let codec = pipeline.codec_label();
 
             // Always record to MKV for crash safety. Remuxed to target container in stop_recording().
             let extension = "mkv";
-            let filename = format!("video_{}.{}", safe_name, extension);
+            let filename = crate::session::filenames::video_filename(
+                templates,
+                &crate::session::filenames::FileNameContext {
+                    device: &label,
+                    codec: &codec,
+                    channels: None,
+                    take,
+                },
+                extension,
+            );
 
             let output_path = session_path.join(&filename);
 
@@ -2556,7 +3914,10 @@ impl VideoCaptureManager {
         let mut video_files = Vec::new();
 
         for (device_id, pipeline) in self.pipelines.iter_mut() {
-            match pipeline.stop_recording() {
+            let result = pipeline.stop_recording();
+            // Segments closed by encoder failovers come before the final one
+            video_files.extend(pipeline.take_failover_segments());
+            match result {
                 Ok(info) => {
                     video_files.push(info);
                 }
@@ -2567,15 +3928,74 @@ impl VideoCaptureManager {
         }
 
         self.is_recording = false;
+        self.clear_stream_tap();
         video_files
     }
 
+    /// Pick the pipeline to stream: the named device, else the first of
+    /// `preferred_ids` that is running. Returns (device_id, width, height, fps).
+    pub fn stream_source(&self, device_name: Option<&str>, preferred_ids: &[String]) -> Option<(String, u32, u32, f64)> {
+        let pipeline = match device_name {
+            Some(name) => self.pipelines.values().find(|p| p.device_name == name),
+            None => preferred_ids.iter().find_map(|id| self.pipelines.get(id)),
+        }?;
+        Some((pipeline.device_id.clone(), pipeline.width, pipeline.height, pipeline.fps))
+    }
+
+    /// Attach a live stream to one pipeline
+    pub fn set_stream_tap(&mut self, device_id: &str, tap: StreamTap) {
+        if let Some(pipeline) = self.pipelines.get_mut(device_id) {
+            pipeline.stream_tap = Some(tap);
+        }
+    }
+
+    /// Republish one pipeline's frames as a virtual camera, replacing any
+    /// existing output. Returns the published device's name.
+    pub fn attach_virtual_camera(
+        &mut self,
+        device_name: Option<&str>,
+        preferred_ids: &[String],
+        output_device: &str,
+        stats_overlay: bool,
+    ) -> anyhow::Result<String> {
+        self.detach_virtual_camera();
+        let (device_id, _, _, _) = self
+            .stream_source(device_name, preferred_ids)
+            .ok_or_else(|| anyhow::anyhow!("No capturing video device to publish"))?;
+        let pipeline = self
+            .pipelines
+            .get(&device_id)
+            .ok_or_else(|| anyhow::anyhow!("Video device {} is not running", device_id))?;
+        let output = VirtualCameraOutput::start(output_device, &pipeline.device_name, stats_overlay)?;
+        *pipeline.virtual_camera.lock() = Some(output);
+        Ok(pipeline.device_name.clone())
+    }
+
+    /// Stop the virtual camera output, if any
+    pub fn detach_virtual_camera(&mut self) {
+        for pipeline in self.pipelines.values() {
+            pipeline.virtual_camera.lock().take();
+        }
+    }
+
+    /// Detach the live stream from every pipeline
+    pub fn clear_stream_tap(&mut self) {
+        for pipeline in self.pipelines.values_mut() {
+            pipeline.stream_tap = None;
+        }
+    }
+
     /// Poll all pipelines (call from background thread)
     pub fn poll(&mut self) {
+        let load = if self.is_recording { self.system_load.sample() } else { None };
         for (_, pipeline) in self.pipelines.iter_mut() {
+            if let Some(load) = load {
+                pipeline.check_load(load);
+            }
             if let Err(e) = pipeline.poll() {
                 println!("[Video] Poll error: {}", e);
             }
+            pipeline.update_overlay();
         }
     }
 
@@ -2590,6 +4010,95 @@ impl VideoCaptureManager {
         warnings
     }
 
+    /// Collect encoder failovers from all active pipelines
+    pub fn collect_encoder_failovers(&mut self) -> Vec<EncoderFailover> {
+        self.pipelines
+            .values_mut()
+            .flat_map(|pipeline| pipeline.take_failovers())
+            .collect()
+    }
+
+    /// Live statistics for every running pipeline
+    pub fn live_stats(&mut self) -> Vec<VideoPipelineStats> {
+        let mut stats: Vec<VideoPipelineStats> =
+            self.pipelines.values_mut().map(|pipeline| pipeline.live_stats()).collect();
+        stats.sort_by(|a, b| a.device_name.cmp(&b.device_name));
+        stats
+    }
+
+    /// Encoding adaptations made since the last call
+    pub fn collect_pending_adaptations(&mut self) -> Vec<EncodingAdaptation> {
+        self.pipelines
+            .values_mut()
+            .flat_map(|pipeline| pipeline.take_pending_adaptations())
+            .collect()
+    }
+
+    /// Encoding adaptations made during the recording that just stopped
+    pub fn collect_adaptations(&mut self) -> Vec<EncodingAdaptation> {
+        self.pipelines
+            .values_mut()
+            .flat_map(|pipeline| pipeline.take_adaptations())
+            .collect()
+    }
+
+    /// Cameras found to be delivering black frames since the last call
+    pub fn collect_blank_warnings(&mut self) -> Vec<BlankVideoWarning> {
+        self.pipelines
+            .values_mut()
+            .filter_map(|pipeline| pipeline.take_blank_warning())
+            .collect()
+    }
+
+    /// Lighten every recording pipeline's encoding by one step (low battery)
+    pub fn lower_for_battery(&mut self) {
+        for pipeline in self.pipelines.values_mut() {
+            pipeline.lower_for_battery();
+        }
+    }
+
+    /// Start a new segment file on every recording pipeline (low battery)
+    pub fn split_segments(&mut self) {
+        for pipeline in self.pipelines.values_mut() {
+            if let Err(e) = pipeline.split_segment() {
+                println!("[Video] Failed to start a new segment for {}: {}", pipeline.device_name, e);
+            }
+        }
+    }
+
+    /// Collect constant-frame-rate reports for the recording that just stopped
+    /// (device name, first frame written) for each recording pipeline
+    pub fn first_writes(&self) -> Vec<(String, Option<Instant>)> {
+        self.pipelines
+            .values()
+            .filter(|p| p.is_recording())
+            .map(|p| (p.device_name.clone(), p.first_write_at()))
+            .collect()
+    }
+
+    /// Progress of every recording pipeline's files, for a checkpoint
+    pub fn recording_progress(&self) -> Vec<crate::session::FileProgress> {
+        self.pipelines
+            .values()
+            .flat_map(|pipeline| pipeline.recording_progress())
+            .collect()
+    }
+
+    pub fn collect_frame_rate_reports(&mut self) -> Vec<crate::session::FrameRateReport> {
+        self.pipelines
+            .values_mut()
+            .filter_map(|pipeline| pipeline.take_frame_rate_report())
+            .collect()
+    }
+
+    /// Capture-health reports from the last recording
+    pub fn collect_quality_reports(&mut self) -> Vec<crate::session::VideoQuality> {
+        self.pipelines
+            .values_mut()
+            .filter_map(|pipeline| pipeline.take_quality_report())
+            .collect()
+    }
+
     /// Set pre-roll duration for all pipelines
     pub fn set_preroll_duration(&mut self, secs: u32) {
         self.pre_roll_secs = secs;
@@ -2608,6 +4117,11 @@ impl VideoCaptureManager {
         self.pipelines.len()
     }
 
+    /// Names of the devices with active pipelines
+    pub fn device_names(&self) -> Vec<String> {
+        self.pipelines.values().map(|p| p.device_name.clone()).collect()
+    }
+
     /// Get frame counts for all active pipelines (for health check monitoring)
     pub fn get_frame_counts(&self) -> HashMap<String, u64> {
         self.pipelines
@@ -0,0 +1,211 @@
+// Virtual camera output of a capture pipeline
+//
+// Sacho opens the camera itself, so a video-conference app that wants the
+// same camera fails to negotiate it. Instead, the frames Sacho captures are
+// re-published on a loopback camera device that other apps can open.
+//
+// Each captured sample is handed over by reference (no copy) to a separate
+// pipeline: appsrc -> leaky queue -> decodebin -> videoconvert -> sink. The
+// queue is leaky, so a stalled sink only drops virtual-camera frames and
+// never blocks capture or recording.
+//
+// Optionally a textoverlay draws live capture fps, encode fps and bitrate
+// onto the published frames, so the virtual camera doubles as a preview that
+// shows whether the camera actually delivers the selected mode.
+//
+// Publishing needs a loopback device: v4l2loopback on Linux. Windows and
+// macOS have no GStreamer sink for virtual cameras (they need a signed
+// DirectShow/Media Foundation or CoreMediaIO driver), so `unsupported_reason`
+// reports why the option is unavailable there.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use serde::Serialize;
+
+/// Whether this platform can publish a virtual camera, for the settings UI
+#[derive(Debug, Clone, Serialize)]
+pub struct VirtualCameraSupport {
+    pub supported: bool,
+    /// Why the output is unavailable (None when supported)
+    pub reason: Option<String>,
+}
+
+/// Why virtual camera output can't be used here, if it can't
+pub fn unsupported_reason() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        if gst::init().is_err() || gst::ElementFactory::find("v4l2sink").is_none() {
+            return Some("GStreamer's v4l2sink element is not installed".into());
+        }
+        None
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Some("Virtual camera output needs a virtual camera driver, which is only available through v4l2loopback on Linux".into())
+    }
+}
+
+pub fn support() -> VirtualCameraSupport {
+    let reason = unsupported_reason();
+    VirtualCameraSupport { supported: reason.is_none(), reason }
+}
+
+/// A running virtual camera fed from one capture pipeline
+pub struct VirtualCameraOutput {
+    pipeline: gst::Pipeline,
+    appsrc: gst_app::AppSrc,
+    /// Set once the appsrc caps match the capture caps
+    caps_set: AtomicBool,
+    /// Set after a pipeline error; further samples are ignored
+    failed: Arc<AtomicBool>,
+    /// Stats overlay, when enabled
+    overlay: Option<gst::Element>,
+}
+
+impl VirtualCameraOutput {
+    /// Build the output pipeline writing to `output_device`
+    /// (e.g. /dev/video10 for a v4l2loopback device)
+    pub fn start(output_device: &str, source_name: &str, stats_overlay: bool) -> anyhow::Result<Self> {
+        if let Some(reason) = unsupported_reason() {
+            return Err(anyhow::anyhow!(reason));
+        }
+
+        let pipeline = gst::Pipeline::new();
+        let appsrc = gst_app::AppSrc::builder()
+            .format(gst::Format::Time)
+            .is_live(true)
+            .do_timestamp(true)
+            .stream_type(gst_app::AppStreamType::Stream)
+            .build();
+
+        let queue = gst::ElementFactory::make("queue")
+            .property("max-size-buffers", 2u32)
+            .property_from_str("leaky", "downstream")
+            .build()?;
+        let decodebin = gst::ElementFactory::make("decodebin").build()?;
+        let videoconvert = gst::ElementFactory::make("videoconvert").build()?;
+        // YUY2 is the format conference apps accept most reliably from v4l2loopback
+        let capsfilter = gst::ElementFactory::make("capsfilter")
+            .property("caps", gst::Caps::builder("video/x-raw").field("format", "YUY2").build())
+            .build()?;
+        let sink = create_sink(output_device)?;
+        let overlay = if stats_overlay {
+            Some(
+                gst::ElementFactory::make("textoverlay")
+                    .property_from_str("valignment", "top")
+                    .property_from_str("halignment", "left")
+                    .property("font-desc", "Sans 12")
+                    .property("shaded-background", true)
+                    .build()?,
+            )
+        } else {
+            None
+        };
+
+        pipeline.add_many([appsrc.upcast_ref(), &queue, &decodebin, &videoconvert, &capsfilter, &sink])?;
+        gst::Element::link_many([appsrc.upcast_ref(), &queue, &decodebin])?;
+        match &overlay {
+            Some(overlay) => {
+                pipeline.add(overlay)?;
+                gst::Element::link_many([&videoconvert, overlay, &capsfilter, &sink])?;
+            }
+            None => gst::Element::link_many([&videoconvert, &capsfilter, &sink])?,
+        }
+
+        let videoconvert_weak = videoconvert.downgrade();
+        decodebin.connect_pad_added(move |_decodebin, src_pad| {
+            let Some(videoconvert) = videoconvert_weak.upgrade() else {
+                return;
+            };
+            if let Some(sink_pad) = videoconvert.static_pad("sink") {
+                if !sink_pad.is_linked() {
+                    if let Err(e) = src_pad.link(&sink_pad) {
+                        println!("[VirtualCamera] Failed to link decoded pad: {:?}", e);
+                    }
+                }
+            }
+        });
+
+        // Errors (e.g. the loopback device disappeared) disable the output only
+        let failed = Arc::new(AtomicBool::new(false));
+        if let Some(bus) = pipeline.bus() {
+            let failed = failed.clone();
+            bus.set_sync_handler(move |_bus, msg| {
+                if let gst::MessageView::Error(err) = msg.view() {
+                    println!("[VirtualCamera] Output error: {}", err.error());
+                    failed.store(true, Ordering::Relaxed);
+                }
+                gst::BusSyncReply::Drop
+            });
+        }
+
+        pipeline.set_state(gst::State::Playing)?;
+        println!("[VirtualCamera] Publishing {} on {}", source_name, output_device);
+
+        Ok(Self {
+            pipeline,
+            appsrc,
+            caps_set: AtomicBool::new(false),
+            failed,
+            overlay,
+        })
+    }
+
+    /// Whether the stats overlay is enabled
+    pub fn has_overlay(&self) -> bool {
+        self.overlay.is_some()
+    }
+
+    /// Replace the overlay text (no-op without an overlay)
+    pub fn set_overlay_text(&self, text: &str) {
+        if let Some(ref overlay) = self.overlay {
+            overlay.set_property("text", text);
+        }
+    }
+
+    /// Forward a captured sample. Called from the capture appsink thread,
+    /// so it never blocks: the buffer is shared, not copied.
+    pub fn push_sample(&self, sample: &gst::Sample) {
+        if self.failed.load(Ordering::Relaxed) {
+            return;
+        }
+        let Some(buffer) = sample.buffer_owned() else { return };
+        if !self.caps_set.load(Ordering::Relaxed) {
+            let Some(caps) = sample.caps() else { return };
+            self.appsrc.set_caps(Some(&caps.to_owned()));
+            self.caps_set.store(true, Ordering::Relaxed);
+        }
+        if self.appsrc.push_buffer(buffer).is_err() {
+            self.failed.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for VirtualCameraOutput {
+    fn drop(&mut self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn create_sink(output_device: &str) -> anyhow::Result<gst::Element> {
+    if !std::path::Path::new(output_device).exists() {
+        return Err(anyhow::anyhow!(
+            "{} does not exist (is the v4l2loopback module loaded?)",
+            output_device
+        ));
+    }
+    Ok(gst::ElementFactory::make("v4l2sink")
+        .property("device", output_device)
+        .property("sync", false)
+        .build()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn create_sink(_output_device: &str) -> anyhow::Result<gst::Element> {
+    Err(anyhow::anyhow!("Virtual camera output is not supported on this platform"))
+}
@@ -0,0 +1,355 @@
+// WAV writing with RF64 and Broadcast Wave metadata
+//
+// A plain RIFF header stores its sizes in 32 bits, so a WAV passing 4 GB
+// (about 7 hours of 24-bit stereo at 48 kHz, well under an hour for a
+// 16-channel interface) ends up with wrapped sizes that most tools reject
+// and the repair code could not fix. WAV recordings are written here rather
+// than by wavenc: the header reserves a JUNK chunk the size of an RF64
+// `ds64` chunk right after "WAVE", and a file finalized (or repaired) past
+// 4 GB is promoted to RF64 in place (EBU Tech 3306) without moving the audio.
+// Smaller files stay ordinary RIFF.
+//
+// Every file also carries a Broadcast Wave `bext` chunk (EBU Tech 3285) with
+// the origination date, time and time reference of its first sample, so a
+// DAW can line up the tracks of a long multitrack take by timestamp, and a
+// LIST/INFO chunk with the provenance tags.
+
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use chrono::{DateTime, Local, Timelike};
+
+use crate::encoding::tags::MediaTags;
+
+/// Bytes of a ds64 chunk body: RIFF size, data size, sample count (u64 each)
+/// and an empty chunk-size table (u32)
+const DS64_BODY_LEN: u32 = 28;
+
+/// Fixed part of a bext chunk body, before the coding history
+const BEXT_FIXED_LEN: usize = 602;
+
+/// Size field value meaning "see ds64"
+const RF64_SIZE_MARKER: u32 = u32::MAX;
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Sample layout of a WAV file
+#[derive(Debug, Clone, Copy)]
+pub struct WavFormat {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+    /// IEEE float samples (32-bit) rather than integer PCM
+    pub float: bool,
+}
+
+impl WavFormat {
+    fn block_align(&self) -> u16 {
+        self.channels * (self.bits_per_sample / 8)
+    }
+
+    fn fmt_chunk(&self) -> Vec<u8> {
+        let tag = if self.float { WAVE_FORMAT_IEEE_FLOAT } else { WAVE_FORMAT_PCM };
+        // More than two channels needs the extensible form to be read reliably
+        let extensible = self.channels > 2;
+        let mut body = Vec::with_capacity(40);
+        body.extend_from_slice(&if extensible { WAVE_FORMAT_EXTENSIBLE } else { tag }.to_le_bytes());
+        body.extend_from_slice(&self.channels.to_le_bytes());
+        body.extend_from_slice(&self.sample_rate.to_le_bytes());
+        body.extend_from_slice(&(self.sample_rate * self.block_align() as u32).to_le_bytes());
+        body.extend_from_slice(&self.block_align().to_le_bytes());
+        body.extend_from_slice(&self.bits_per_sample.to_le_bytes());
+        if extensible {
+            body.extend_from_slice(&22u16.to_le_bytes());
+            body.extend_from_slice(&self.bits_per_sample.to_le_bytes());
+            // No speaker assignment: channels are inputs, not speakers
+            body.extend_from_slice(&0u32.to_le_bytes());
+            // Sub-format GUID {0000000X-0000-0010-8000-00AA00389B71}
+            body.extend_from_slice(&(tag as u32).to_le_bytes());
+            body.extend_from_slice(&[0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71]);
+        }
+        chunk(b"fmt ", &body)
+    }
+}
+
+/// Streaming WAV writer. Sizes are placeholders until `finish`, so a file
+/// cut off by a crash is fixed up by `repair_wav_file` like any other.
+pub struct WavFileWriter {
+    file: BufWriter<File>,
+    format: WavFormat,
+    tags: MediaTags,
+    /// Offset of the bext chunk body, rewritten with the real origination time
+    bext_offset: u64,
+    layout: WavLayout,
+    data_len: u64,
+    /// Last time the header sizes were written and synced (record-safe mode)
+    last_sync: Instant,
+}
+
+impl WavFileWriter {
+    /// Create the file and write its header. The origination time starts as
+    /// `tags.recorded_at` and can be corrected in `finish`.
+    pub fn create(path: &Path, format: WavFormat, tags: MediaTags) -> std::io::Result<Self> {
+        let junk = chunk(b"JUNK", &[0u8; DS64_BODY_LEN as usize]);
+        let fmt = format.fmt_chunk();
+        let bext = chunk(b"bext", &bext_body(&tags, format, tags.recorded_at));
+        let info = info_chunk(&tags);
+
+        let mut header = Vec::new();
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(b"WAVE");
+        let ds64_offset = header.len() as u64;
+        header.extend_from_slice(&junk);
+        header.extend_from_slice(&fmt);
+        let bext_offset = header.len() as u64 + 8;
+        header.extend_from_slice(&bext);
+        header.extend_from_slice(&info);
+        let data_offset = header.len() as u64;
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(&header)?;
+        Ok(Self {
+            file,
+            format,
+            tags,
+            bext_offset,
+            layout: WavLayout {
+                rf64: false,
+                channels: format.channels,
+                sample_rate: format.sample_rate,
+                bits_per_sample: format.bits_per_sample,
+                data_offset,
+                ds64_offset: Some(ds64_offset),
+                riff_size: 0,
+            },
+            data_len: 0,
+            last_sync: Instant::now(),
+        })
+    }
+
+    /// Append interleaved samples in the file's format
+    pub fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.file.write_all(bytes)?;
+        self.data_len += bytes.len() as u64;
+        if crate::battery::is_safe_mode() && self.last_sync.elapsed() >= crate::battery::SYNC_INTERVAL {
+            self.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    /// Write the current sizes into the header and force everything to disk,
+    /// so the file is playable up to this point if power is lost
+    fn checkpoint(&mut self) -> std::io::Result<()> {
+        self.last_sync = Instant::now();
+        self.file.flush()?;
+        let file = self.file.get_mut();
+        write_sizes(file, &self.layout, self.data_len).map_err(std::io::Error::other)?;
+        file.seek(SeekFrom::End(0))?;
+        file.sync_data()
+    }
+
+    /// Write the final sizes (promoting to RF64 past 4 GB) and, when known,
+    /// the wall-clock time of the first sample. Returns the file size.
+    pub fn finish(self, origination: Option<DateTime<Local>>) -> anyhow::Result<u64> {
+        let mut file = self.file.into_inner().map_err(|e| e.into_error())?;
+        if let Some(origination) = origination {
+            file.seek(SeekFrom::Start(self.bext_offset))?;
+            file.write_all(&bext_body(&self.tags, self.format, origination))?;
+        }
+        write_sizes(&mut file, &self.layout, self.data_len)?;
+        file.sync_all()?;
+        Ok(self.layout.data_offset + 8 + self.data_len)
+    }
+}
+
+/// Where things are in an existing WAV (RIFF or RF64) file
+#[derive(Debug, Clone)]
+pub struct WavLayout {
+    pub rf64: bool,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+    /// Offset of the data chunk header
+    pub data_offset: u64,
+    /// Offset of the JUNK (or ds64) chunk reserved after "WAVE", if any
+    pub ds64_offset: Option<u64>,
+    /// RIFF size as stored (from ds64 for RF64)
+    pub riff_size: u64,
+}
+
+impl WavLayout {
+    pub fn block_align(&self) -> u64 {
+        (self.bits_per_sample as u64 / 8) * self.channels as u64
+    }
+}
+
+/// Parse the header of a WAV or RF64 file
+pub fn read_layout<R: Read + Seek>(file: &mut R) -> anyhow::Result<WavLayout> {
+    let mut header = [0u8; 12];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header)?;
+    let rf64 = match &header[0..4] {
+        b"RIFF" => false,
+        b"RF64" => true,
+        _ => anyhow::bail!("Not a valid WAV file"),
+    };
+    if &header[8..12] != b"WAVE" {
+        anyhow::bail!("Not a valid WAV file");
+    }
+
+    let mut layout = WavLayout {
+        rf64,
+        channels: 0,
+        sample_rate: 0,
+        bits_per_sample: 0,
+        data_offset: 0,
+        ds64_offset: None,
+        riff_size: u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as u64,
+    };
+
+    let mut pos: u64 = 12;
+    loop {
+        file.seek(SeekFrom::Start(pos))?;
+        let mut chunk_header = [0u8; 8];
+        if file.read_exact(&mut chunk_header).is_err() {
+            break;
+        }
+        let chunk_id = &chunk_header[0..4];
+        let chunk_size = u32::from_le_bytes([chunk_header[4], chunk_header[5], chunk_header[6], chunk_header[7]]);
+
+        if pos == 12 && (chunk_id == b"ds64" || chunk_id == b"JUNK") && chunk_size >= DS64_BODY_LEN {
+            layout.ds64_offset = Some(pos);
+            if chunk_id == b"ds64" {
+                let mut sizes = [0u8; 8];
+                file.read_exact(&mut sizes)?;
+                layout.riff_size = u64::from_le_bytes(sizes);
+            }
+        } else if chunk_id == b"fmt " {
+            let mut fmt = [0u8; 16];
+            file.read_exact(&mut fmt)?;
+            layout.channels = u16::from_le_bytes([fmt[2], fmt[3]]);
+            layout.sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
+            layout.bits_per_sample = u16::from_le_bytes([fmt[14], fmt[15]]);
+        } else if chunk_id == b"data" {
+            layout.data_offset = pos;
+            break;
+        }
+
+        // Chunks are 2-byte aligned
+        pos += 8 + chunk_size as u64;
+        if chunk_size % 2 != 0 {
+            pos += 1;
+        }
+    }
+
+    if layout.data_offset == 0 || layout.channels == 0 {
+        anyhow::bail!("Could not find fmt/data chunks");
+    }
+    Ok(layout)
+}
+
+/// Store the sizes for `data_len` bytes of audio. Past 4 GB the file is
+/// switched to RF64, which needs the reserved JUNK chunk to hold the ds64.
+pub fn write_sizes(file: &mut File, layout: &WavLayout, data_len: u64) -> anyhow::Result<()> {
+    let riff_size = layout.data_offset + 8 + data_len - 8;
+    if !layout.rf64 && riff_size <= u32::MAX as u64 {
+        file.seek(SeekFrom::Start(4))?;
+        file.write_all(&(riff_size as u32).to_le_bytes())?;
+        file.seek(SeekFrom::Start(layout.data_offset + 4))?;
+        file.write_all(&(data_len as u32).to_le_bytes())?;
+        file.flush()?;
+        return Ok(());
+    }
+
+    let Some(ds64_offset) = layout.ds64_offset else {
+        anyhow::bail!("WAV data passes 4 GB and the header has no room for RF64 sizes");
+    };
+    let sample_count = match layout.block_align() {
+        0 => 0,
+        align => data_len / align,
+    };
+    let mut ds64 = Vec::with_capacity(8 + DS64_BODY_LEN as usize);
+    ds64.extend_from_slice(b"ds64");
+    ds64.extend_from_slice(&DS64_BODY_LEN.to_le_bytes());
+    ds64.extend_from_slice(&riff_size.to_le_bytes());
+    ds64.extend_from_slice(&data_len.to_le_bytes());
+    ds64.extend_from_slice(&sample_count.to_le_bytes());
+    ds64.extend_from_slice(&0u32.to_le_bytes());
+
+    file.seek(SeekFrom::Start(ds64_offset))?;
+    file.write_all(&ds64)?;
+    file.seek(SeekFrom::Start(layout.data_offset + 4))?;
+    file.write_all(&RF64_SIZE_MARKER.to_le_bytes())?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(b"RF64")?;
+    file.write_all(&RF64_SIZE_MARKER.to_le_bytes())?;
+    file.flush()?;
+    Ok(())
+}
+
+fn chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8 + body.len() + 1);
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(body);
+    if body.len() % 2 != 0 {
+        out.push(0);
+    }
+    out
+}
+
+/// `s` as a fixed-width, NUL-padded ASCII field
+fn ascii_field(out: &mut Vec<u8>, s: &str, width: usize) {
+    let mut bytes: Vec<u8> = s.chars().map(|c| if c.is_ascii() { c as u8 } else { b'?' }).take(width).collect();
+    bytes.resize(width, 0);
+    out.extend_from_slice(&bytes);
+}
+
+fn bext_body(tags: &MediaTags, format: WavFormat, origination: DateTime<Local>) -> Vec<u8> {
+    let mut body = Vec::with_capacity(BEXT_FIXED_LEN + 64);
+    ascii_field(&mut body, &tags.comment(), 256);
+    ascii_field(&mut body, "Sacho", 32);
+    ascii_field(&mut body, &tags.session_id, 32);
+    ascii_field(&mut body, &origination.format("%Y-%m-%d").to_string(), 10);
+    ascii_field(&mut body, &origination.format("%H:%M:%S").to_string(), 8);
+    // Samples since local midnight at the first sample
+    let since_midnight = origination.num_seconds_from_midnight() as f64
+        + origination.nanosecond() as f64 / 1_000_000_000.0;
+    let time_reference = (since_midnight * format.sample_rate as f64).round() as u64;
+    body.extend_from_slice(&time_reference.to_le_bytes());
+    body.extend_from_slice(&1u16.to_le_bytes());
+    // UMID, loudness fields and reserved space
+    body.resize(BEXT_FIXED_LEN, 0);
+
+    let mode = match format.channels {
+        1 => "mono",
+        2 => "stereo",
+        _ => "multitrack",
+    };
+    let coding = if format.float { "PCM/float" } else { "PCM" };
+    body.extend_from_slice(
+        format!(
+            "A={},F={},W={},M={},T={}\r\n",
+            coding, format.sample_rate, format.bits_per_sample, mode, MediaTags::software()
+        )
+        .as_bytes(),
+    );
+    body
+}
+
+fn info_chunk(tags: &MediaTags) -> Vec<u8> {
+    let mut body = b"INFO".to_vec();
+    for (id, value) in tags.riff_info() {
+        let mut text = value.into_bytes();
+        text.push(0);
+        body.extend_from_slice(&chunk(&id, &text));
+    }
+    chunk(b"LIST", &body)
+}
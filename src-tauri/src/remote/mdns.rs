@@ -0,0 +1,217 @@
+// Minimal mDNS (RFC 6762) responder for advertising a DNS-SD service
+//
+// Only what a phone needs to find the recorder on the LAN: PTR, SRV, TXT and
+// A records for one service instance, sent as announcements at startup and in
+// answer to matching queries. Probing/conflict resolution is skipped; the
+// instance name includes the host name, which is already unique on a LAN.
+
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use socket2::{Domain, Protocol, Socket, Type};
+
+const MDNS_ADDR: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+const MDNS_PORT: u16 = 5353;
+
+/// TTL for announced records (seconds)
+const RECORD_TTL: u32 = 120;
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+/// Cache-flush bit for unique records
+const CLASS_FLUSH: u16 = 0x8000;
+
+/// A service to advertise
+#[derive(Debug, Clone)]
+pub struct ServiceInfo {
+    /// e.g. "_sacho-remote._tcp.local"
+    pub service_type: String,
+    /// Instance label, e.g. "Studio-PC"
+    pub instance: String,
+    /// Host label without ".local"
+    pub host: String,
+    pub port: u16,
+    pub address: Ipv4Addr,
+    /// TXT key=value entries
+    pub txt: Vec<String>,
+}
+
+impl ServiceInfo {
+    fn instance_name(&self) -> String {
+        format!("{}.{}", self.instance, self.service_type)
+    }
+
+    fn host_name(&self) -> String {
+        format!("{}.local", self.host)
+    }
+}
+
+/// Running responder; stops (sending a goodbye) when dropped
+pub struct MdnsAdvertiser {
+    stop: Arc<AtomicBool>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl MdnsAdvertiser {
+    pub fn start(service: ServiceInfo) -> std::io::Result<Self> {
+        let socket = bind_multicast()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let stop = stop.clone();
+            std::thread::Builder::new()
+                .name("sacho-mdns".into())
+                .spawn(move || responder_loop(socket, service, stop))?
+        };
+        Ok(Self { stop, thread: Some(thread) })
+    }
+}
+
+impl Drop for MdnsAdvertiser {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn bind_multicast() -> std::io::Result<UdpSocket> {
+    // Other responders (Bonjour, the Windows DNS client) share port 5353
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, MDNS_PORT)).into())?;
+    socket.join_multicast_v4(&MDNS_ADDR, &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_multicast_ttl_v4(255)?;
+    socket.set_read_timeout(Some(Duration::from_millis(250)))?;
+    Ok(socket.into())
+}
+
+fn responder_loop(socket: UdpSocket, service: ServiceInfo, stop: Arc<AtomicBool>) {
+    let target = SocketAddrV4::new(MDNS_ADDR, MDNS_PORT);
+
+    // Announce twice, a second apart (RFC 6762 section 8.3)
+    let announcement = build_response(&service, RECORD_TTL);
+    for i in 0..2 {
+        let _ = socket.send_to(&announcement, target);
+        if i == 0 {
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    let mut buf = [0u8; 1500];
+    while !stop.load(Ordering::SeqCst) {
+        let Ok((len, _)) = socket.recv_from(&mut buf) else { continue };
+        if query_matches(&buf[..len], &service) {
+            let _ = socket.send_to(&build_response(&service, RECORD_TTL), target);
+        }
+    }
+
+    // Goodbye: the same records with TTL 0
+    let _ = socket.send_to(&build_response(&service, 0), target);
+}
+
+/// True if `packet` is a query with a question about our service
+fn query_matches(packet: &[u8], service: &ServiceInfo) -> bool {
+    if packet.len() < 12 || packet[2] & 0x80 != 0 {
+        return false; // too short, or a response
+    }
+    let questions = u16::from_be_bytes([packet[4], packet[5]]);
+    let names = [service.service_type.clone(), service.instance_name(), service.host_name()];
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        let Some((name, next)) = read_name(packet, pos) else { return false };
+        if next + 4 > packet.len() {
+            return false;
+        }
+        let qtype = u16::from_be_bytes([packet[next], packet[next + 1]]);
+        pos = next + 4;
+        let relevant = matches!(qtype, TYPE_PTR | TYPE_SRV | TYPE_TXT | TYPE_A | TYPE_ANY);
+        if relevant && names.iter().any(|n| n.eq_ignore_ascii_case(&name)) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Decode a (possibly compressed) name starting at `pos`. Returns the dotted
+/// name and the offset just past it in the original position.
+fn read_name(packet: &[u8], mut pos: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    for _ in 0..64 {
+        let len = *packet.get(pos)? as usize;
+        if len == 0 {
+            return Some((labels.join("."), end.unwrap_or(pos + 1)));
+        }
+        if len & 0xC0 == 0xC0 {
+            let pointer = ((len & 0x3F) << 8) | *packet.get(pos + 1)? as usize;
+            end.get_or_insert(pos + 2);
+            pos = pointer;
+            continue;
+        }
+        let label = packet.get(pos + 1..pos + 1 + len)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += 1 + len;
+    }
+    None // pointer loop
+}
+
+fn write_name(out: &mut Vec<u8>, name: &str) {
+    for label in name.split('.').filter(|l| !l.is_empty()) {
+        let bytes = &label.as_bytes()[..label.len().min(63)];
+        out.push(bytes.len() as u8);
+        out.extend_from_slice(bytes);
+    }
+    out.push(0);
+}
+
+fn write_record(out: &mut Vec<u8>, name: &str, rtype: u16, class: u16, ttl: u32, rdata: &[u8]) {
+    write_name(out, name);
+    out.extend_from_slice(&rtype.to_be_bytes());
+    out.extend_from_slice(&class.to_be_bytes());
+    out.extend_from_slice(&ttl.to_be_bytes());
+    out.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+    out.extend_from_slice(rdata);
+}
+
+/// An unsolicited response carrying all four records
+fn build_response(service: &ServiceInfo, ttl: u32) -> Vec<u8> {
+    let mut out = Vec::with_capacity(512);
+    // ID 0, flags: response + authoritative, 0 questions, 4 answers
+    out.extend_from_slice(&[0, 0, 0x84, 0, 0, 0, 0, 4, 0, 0, 0, 0]);
+
+    let instance = service.instance_name();
+    let host = service.host_name();
+
+    let mut ptr = Vec::new();
+    write_name(&mut ptr, &instance);
+    write_record(&mut out, &service.service_type, TYPE_PTR, CLASS_IN, ttl, &ptr);
+
+    let mut srv = Vec::new();
+    srv.extend_from_slice(&0u16.to_be_bytes()); // priority
+    srv.extend_from_slice(&0u16.to_be_bytes()); // weight
+    srv.extend_from_slice(&service.port.to_be_bytes());
+    write_name(&mut srv, &host);
+    write_record(&mut out, &instance, TYPE_SRV, CLASS_IN | CLASS_FLUSH, ttl, &srv);
+
+    let mut txt = Vec::new();
+    for entry in &service.txt {
+        let bytes = &entry.as_bytes()[..entry.len().min(255)];
+        txt.push(bytes.len() as u8);
+        txt.extend_from_slice(bytes);
+    }
+    if txt.is_empty() {
+        txt.push(0);
+    }
+    write_record(&mut out, &instance, TYPE_TXT, CLASS_IN | CLASS_FLUSH, ttl, &txt);
+
+    write_record(&mut out, &host, TYPE_A, CLASS_IN | CLASS_FLUSH, ttl, &service.address.octets());
+    out
+}
@@ -0,0 +1,586 @@
+// Companion phone remote — pairing, discovery and the push protocol
+//
+// The recorder listens on a TCP port and advertises it over mDNS as
+// `_sacho-remote._tcp`. A phone pairs once by entering the six-digit code
+// shown in Settings and receives a long-lived device token; afterwards it
+// connects, authenticates with the token and gets recording state and audio
+// levels pushed to it, and can start/stop recording.
+//
+// Wire format: one JSON object per line in each direction, tagged by "type".
+//
+//   phone -> recorder                      recorder -> phone
+//   {"type":"pair","code","device_name"}   {"type":"paired","device_id","token"}
+//   {"type":"hello","device_id","token"}   {"type":"welcome","server_id","name","version"}
+//   {"type":"levels","enabled":bool}       {"type":"state","status","elapsed_secs","session"}
+//   {"type":"start_recording"}             {"type":"levels","l":[[device,rms,peak],...]}
+//   {"type":"stop_recording"}              {"type":"ack","command"}
+//   {"type":"ping"}                        {"type":"pong"}
+//                                          {"type":"error","message"}
+//
+// State is pushed on every change plus a keepalive every few seconds; levels
+// only while the phone has asked for them, at 10 Hz.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Manager};
+
+use super::mdns::{MdnsAdvertiser, ServiceInfo};
+use super::{constant_time_eq, lan_address, ConnectionLimit};
+use crate::config::Config;
+use crate::recording::{MidiMonitor, RecordingState, RecordingStatus};
+
+/// DNS-SD service type phones browse for
+pub const SERVICE_TYPE: &str = "_sacho-remote._tcp.local";
+
+/// Protocol version advertised in TXT and `welcome`
+const PROTOCOL_VERSION: u32 = 1;
+
+/// How long a pairing code stays valid
+const PAIRING_CODE_TTL: Duration = Duration::from_secs(120);
+
+/// Wrong codes allowed before the pairing code is invalidated
+const MAX_PAIRING_ATTEMPTS: u32 = 5;
+
+/// How often state/levels are sampled for pushing
+const PUSH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// State is re-sent at least this often so the phone can detect a dead link
+const STATE_KEEPALIVE: Duration = Duration::from_secs(5);
+
+/// Unauthenticated connections must say hello (or pair) within this time
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest message line accepted; real messages are a few hundred bytes
+const MAX_LINE_BYTES: u64 = 4096;
+
+/// Phones connected at once (each holds a reader and a pusher thread)
+const MAX_CONNECTIONS: usize = 8;
+
+const PAIRED_DEVICES_FILE: &str = "paired_devices.json";
+
+/// A phone that has been paired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PairedDevice {
+    id: String,
+    name: String,
+    token: String,
+    paired_at: String,
+    #[serde(default)]
+    last_seen: Option<String>,
+}
+
+/// Persisted pairing data (kept out of config.toml since it holds secrets)
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PairingStore {
+    /// Stable ID so phones recognize this recorder across address changes
+    server_id: String,
+    devices: Vec<PairedDevice>,
+}
+
+/// Paired device details for the frontend (no token)
+#[derive(Debug, Clone, Serialize)]
+pub struct PairedDeviceInfo {
+    pub id: String,
+    pub name: String,
+    pub paired_at: String,
+    pub last_seen: Option<String>,
+    pub connected: bool,
+}
+
+/// Shown in Settings while pairing
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingInfo {
+    pub code: String,
+    pub host: String,
+    pub port: u16,
+    pub expires_in_secs: u64,
+}
+
+struct PendingPairing {
+    code: String,
+    expires: Instant,
+    attempts: u32,
+}
+
+struct RemoteServer {
+    port: u16,
+    stop: Arc<AtomicBool>,
+    _mdns: Option<MdnsAdvertiser>,
+}
+
+/// Managed state for the mobile remote
+pub struct MobileRemoteState {
+    inner: Arc<Shared>,
+    server: Mutex<Option<RemoteServer>>,
+}
+
+struct Shared {
+    store: RwLock<PairingStore>,
+    store_path: PathBuf,
+    pairing: Mutex<Option<PendingPairing>>,
+    /// Connected device IDs and how many connections each has
+    connected: Mutex<HashMap<String, usize>>,
+}
+
+impl MobileRemoteState {
+    pub fn new(app: &AppHandle) -> Self {
+        let store_path = app
+            .path()
+            .app_config_dir()
+            .unwrap_or_else(|_| PathBuf::from("."))
+            .join(PAIRED_DEVICES_FILE);
+        let mut store: PairingStore = std::fs::read_to_string(&store_path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        if store.server_id.is_empty() {
+            store.server_id = uuid::Uuid::new_v4().simple().to_string();
+        }
+
+        Self {
+            inner: Arc::new(Shared {
+                store: RwLock::new(store),
+                store_path,
+                pairing: Mutex::new(None),
+                connected: Mutex::new(HashMap::new()),
+            }),
+            server: Mutex::new(None),
+        }
+    }
+
+    /// Start or stop the server to match the config
+    pub fn apply_config(&self, app: &AppHandle) {
+        let (enabled, port) = {
+            let config = app.state::<RwLock<Config>>();
+            let config = config.read();
+            (config.mobile_remote_enabled, config.mobile_remote_port)
+        };
+
+        let mut server = self.server.lock();
+        match (enabled, server.as_ref()) {
+            (true, Some(running)) if running.port == port || port == 0 => {}
+            (true, _) => {
+                if let Some(old) = server.take() {
+                    old.stop.store(true, Ordering::SeqCst);
+                }
+                match start_server(app.clone(), self.inner.clone(), port) {
+                    Ok(started) => *server = Some(started),
+                    Err(e) => println!("[Remote] Failed to start mobile remote: {}", e),
+                }
+            }
+            (false, _) => {
+                if let Some(old) = server.take() {
+                    old.stop.store(true, Ordering::SeqCst);
+                    println!("[Remote] Mobile remote stopped");
+                }
+            }
+        }
+    }
+
+    /// Generate a fresh pairing code
+    pub fn start_pairing(&self) -> Result<PairingInfo, String> {
+        let port = self
+            .server
+            .lock()
+            .as_ref()
+            .map(|s| s.port)
+            .ok_or("The mobile remote is turned off in Settings")?;
+        let host = lan_address().ok_or("No network connection found")?;
+
+        // Six digits from the random bits of a v4 UUID
+        let code = format!("{:06}", uuid::Uuid::new_v4().as_u128() % 1_000_000);
+        *self.inner.pairing.lock() = Some(PendingPairing {
+            code: code.clone(),
+            expires: Instant::now() + PAIRING_CODE_TTL,
+            attempts: 0,
+        });
+
+        Ok(PairingInfo {
+            code,
+            host: host.to_string(),
+            port,
+            expires_in_secs: PAIRING_CODE_TTL.as_secs(),
+        })
+    }
+
+    pub fn cancel_pairing(&self) {
+        *self.inner.pairing.lock() = None;
+    }
+
+    pub fn paired_devices(&self) -> Vec<PairedDeviceInfo> {
+        let connected = self.inner.connected.lock();
+        self.inner
+            .store
+            .read()
+            .devices
+            .iter()
+            .map(|d| PairedDeviceInfo {
+                id: d.id.clone(),
+                name: d.name.clone(),
+                paired_at: d.paired_at.clone(),
+                last_seen: d.last_seen.clone(),
+                connected: connected.get(&d.id).is_some_and(|n| *n > 0),
+            })
+            .collect()
+    }
+
+    /// Forget a device. Its token stops working on the next connection.
+    pub fn unpair(&self, device_id: &str) -> Result<(), String> {
+        {
+            let mut store = self.inner.store.write();
+            let before = store.devices.len();
+            store.devices.retain(|d| d.id != device_id);
+            if store.devices.len() == before {
+                return Err("Device not found".to_string());
+            }
+        }
+        self.inner.save()
+    }
+}
+
+impl Shared {
+    fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.store_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(&*self.store.read()).map_err(|e| e.to_string())?;
+        std::fs::write(&self.store_path, json).map_err(|e| e.to_string())
+    }
+
+    /// Check a pairing code and register the device. None on a wrong or
+    /// expired code.
+    fn pair(&self, code: &str, device_name: &str) -> Option<PairedDevice> {
+        {
+            let mut pairing = self.pairing.lock();
+            let pending = pairing.as_mut()?;
+            if pending.expires < Instant::now() {
+                *pairing = None;
+                return None;
+            }
+            if !constant_time_eq(pending.code.as_bytes(), code.as_bytes()) {
+                pending.attempts += 1;
+                if pending.attempts >= MAX_PAIRING_ATTEMPTS {
+                    println!("[Remote] Too many wrong pairing codes, pairing cancelled");
+                    *pairing = None;
+                }
+                return None;
+            }
+            *pairing = None;
+        }
+
+        let device = PairedDevice {
+            id: uuid::Uuid::new_v4().simple().to_string(),
+            name: device_name.chars().take(64).collect(),
+            token: format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple()),
+            paired_at: Utc::now().to_rfc3339(),
+            last_seen: None,
+        };
+        self.store.write().devices.push(device.clone());
+        if let Err(e) = self.save() {
+            println!("[Remote] Failed to save paired devices: {}", e);
+        }
+        println!("[Remote] Paired {}", device.name);
+        Some(device)
+    }
+
+    fn authenticate(&self, device_id: &str, token: &str) -> bool {
+        let mut store = self.store.write();
+        let Some(device) = store.devices.iter_mut().find(|d| d.id == device_id) else {
+            return false;
+        };
+        if !constant_time_eq(device.token.as_bytes(), token.as_bytes()) {
+            return false;
+        }
+        device.last_seen = Some(Utc::now().to_rfc3339());
+        true
+    }
+}
+
+fn start_server(app: AppHandle, shared: Arc<Shared>, port: u16) -> Result<RemoteServer, String> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .map_err(|e| format!("Failed to listen on port {}: {}", port, e))?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let stop = Arc::new(AtomicBool::new(false));
+    let limit = ConnectionLimit::new(MAX_CONNECTIONS);
+
+    {
+        let stop = stop.clone();
+        std::thread::Builder::new()
+            .name("sacho-remote".into())
+            .spawn(move || {
+                while !stop.load(Ordering::SeqCst) {
+                    match listener.accept() {
+                        Ok((stream, peer)) => {
+                            // Dropping the stream closes it; the phone retries
+                            let Some(slot) = limit.try_acquire() else {
+                                println!("[Remote] Too many connections, refused {}", peer);
+                                continue;
+                            };
+                            let app = app.clone();
+                            let shared = shared.clone();
+                            let stop = stop.clone();
+                            std::thread::spawn(move || {
+                                let _slot = slot;
+                                handle_connection(app, shared, stream, stop);
+                            });
+                        }
+                        Err(_) => std::thread::sleep(Duration::from_millis(100)),
+                    }
+                }
+            })
+            .map_err(|e| e.to_string())?;
+    }
+
+    let host = sysinfo::System::host_name().unwrap_or_else(|| "sacho".to_string());
+    let host_label: String = host
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '-' })
+        .collect();
+    let mdns = match lan_address() {
+        Some(IpAddr::V4(address)) => {
+            let service = ServiceInfo {
+                service_type: SERVICE_TYPE.to_string(),
+                instance: host_label.clone(),
+                host: host_label,
+                port,
+                address,
+                txt: vec![
+                    format!("v={}", PROTOCOL_VERSION),
+                    format!("name={}", host),
+                ],
+            };
+            MdnsAdvertiser::start(service)
+                .map_err(|e| println!("[Remote] mDNS advertisement unavailable: {}", e))
+                .ok()
+        }
+        _ => None,
+    };
+
+    println!("[Remote] Mobile remote listening on port {}", port);
+    Ok(RemoteServer { port, stop, _mdns: mdns })
+}
+
+/// Client -> recorder messages
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Pair { code: String, device_name: String },
+    Hello { device_id: String, token: String },
+    Levels { enabled: bool },
+    StartRecording,
+    StopRecording,
+    Ping,
+}
+
+type SharedWriter = Arc<Mutex<TcpStream>>;
+
+fn send(writer: &SharedWriter, message: serde_json::Value) -> bool {
+    let mut line = message.to_string();
+    line.push('\n');
+    writer.lock().write_all(line.as_bytes()).is_ok()
+}
+
+fn handle_connection(app: AppHandle, shared: Arc<Shared>, stream: TcpStream, server_stop: Arc<AtomicBool>) {
+    let _ = stream.set_nonblocking(false);
+    let handshake_deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+    let Ok(write_half) = stream.try_clone() else { return };
+    let writer: SharedWriter = Arc::new(Mutex::new(write_half));
+    let mut reader = BufReader::new(stream);
+
+    let mut device_id: Option<String> = None;
+    let levels_enabled = Arc::new(AtomicBool::new(false));
+    let connection_closed = Arc::new(AtomicBool::new(false));
+
+    loop {
+        // Until authenticated, every read counts against the handshake
+        // deadline, so a peer trickling lines can't hold its slot
+        if device_id.is_none() {
+            let remaining = handshake_deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                send(&writer, json!({"type": "error", "message": "Handshake timed out"}));
+                break;
+            }
+            let _ = reader.get_ref().set_read_timeout(Some(remaining));
+        }
+
+        // Bounded read: an unauthenticated peer can't grow the line forever
+        let mut line = String::new();
+        match (&mut reader).take(MAX_LINE_BYTES).read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if !line.ends_with('\n') {
+            send(&writer, json!({"type": "error", "message": "Message too long"}));
+            break;
+        }
+        if server_stop.load(Ordering::SeqCst) {
+            break;
+        }
+        let line = line.trim_end();
+        let message = match serde_json::from_str::<ClientMessage>(line) {
+            Ok(message) => message,
+            Err(e) => {
+                send(&writer, json!({"type": "error", "message": format!("Invalid message: {}", e)}));
+                if device_id.is_none() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        let authenticated = device_id.is_some();
+        let ok = match message {
+            ClientMessage::Pair { code, device_name } => match shared.pair(&code, &device_name) {
+                Some(device) => send(&writer, json!({"type": "paired", "device_id": device.id, "token": device.token})),
+                None => {
+                    send(&writer, json!({"type": "error", "message": "Wrong or expired pairing code"}));
+                    // Unauthenticated peers get one try per connection
+                    authenticated
+                }
+            },
+            ClientMessage::Hello { device_id: id, token } => {
+                if authenticated {
+                    true
+                } else if shared.authenticate(&id, &token) {
+                    *shared.connected.lock().entry(id.clone()).or_insert(0) += 1;
+                    device_id = Some(id);
+                    // Authenticated clients may stay idle between commands
+                    let _ = writer.lock().set_read_timeout(None);
+                    let welcome = send(&writer, json!({
+                        "type": "welcome",
+                        "server_id": shared.store.read().server_id,
+                        "name": sysinfo::System::host_name().unwrap_or_default(),
+                        "version": PROTOCOL_VERSION,
+                    }));
+                    spawn_pusher(
+                        app.clone(),
+                        writer.clone(),
+                        levels_enabled.clone(),
+                        connection_closed.clone(),
+                        server_stop.clone(),
+                    );
+                    welcome
+                } else {
+                    send(&writer, json!({"type": "error", "message": "Unknown device; pair again"}));
+                    false
+                }
+            }
+            _ if !authenticated => {
+                send(&writer, json!({"type": "error", "message": "Not authenticated"}));
+                false
+            }
+            ClientMessage::Levels { enabled } => {
+                levels_enabled.store(enabled, Ordering::SeqCst);
+                true
+            }
+            ClientMessage::StartRecording => run_command(&app, &writer, "start_recording", |m| m.manual_start_recording()),
+            ClientMessage::StopRecording => run_command(&app, &writer, "stop_recording", |m| m.manual_stop_recording()),
+            ClientMessage::Ping => send(&writer, json!({"type": "pong"})),
+        };
+        if !ok {
+            break;
+        }
+    }
+
+    connection_closed.store(true, Ordering::SeqCst);
+    if let Some(id) = device_id {
+        let mut connected = shared.connected.lock();
+        if let Some(count) = connected.get_mut(&id) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+fn run_command(
+    app: &AppHandle,
+    writer: &SharedWriter,
+    command: &str,
+    action: impl FnOnce(&MidiMonitor) -> Result<(), String>,
+) -> bool {
+    println!("[Remote] {} requested from phone", command);
+    let monitor = app.state::<Arc<Mutex<MidiMonitor>>>().inner().clone();
+    let result = action(&monitor.lock());
+    match result {
+        Ok(()) => send(writer, json!({"type": "ack", "command": command})),
+        Err(message) => send(writer, json!({"type": "error", "command": command, "message": message})),
+    }
+}
+
+fn status_name(status: &RecordingStatus) -> &'static str {
+    match status {
+        RecordingStatus::Idle => "idle",
+        RecordingStatus::Recording => "recording",
+        RecordingStatus::Stopping => "stopping",
+        RecordingStatus::Initializing => "initializing",
+    }
+}
+
+/// Push state changes and (when enabled) levels until the connection closes
+fn spawn_pusher(
+    app: AppHandle,
+    writer: SharedWriter,
+    levels_enabled: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+    server_stop: Arc<AtomicBool>,
+) {
+    std::thread::spawn(move || {
+        let mut last_state: Option<(RecordingStatus, Option<PathBuf>)> = None;
+        let mut last_state_sent = Instant::now();
+
+        while !closed.load(Ordering::SeqCst) && !server_stop.load(Ordering::SeqCst) {
+            let state = app.state::<RwLock<RecordingState>>().read().clone();
+            let key = (state.status.clone(), state.current_session_path.clone());
+            if last_state.as_ref() != Some(&key) || last_state_sent.elapsed() >= STATE_KEEPALIVE {
+                let session = state
+                    .current_session_path
+                    .as_ref()
+                    .and_then(|p| p.file_name())
+                    .map(|n| n.to_string_lossy().to_string());
+                let elapsed = state
+                    .started_at
+                    .map(|t| (Utc::now() - t).num_milliseconds().max(0) as f64 / 1000.0)
+                    .unwrap_or(0.0);
+                if !send(&writer, json!({
+                    "type": "state",
+                    "status": status_name(&state.status),
+                    "elapsed_secs": elapsed,
+                    "session": session,
+                })) {
+                    break;
+                }
+                last_state = Some(key);
+                last_state_sent = Instant::now();
+            }
+
+            if levels_enabled.load(Ordering::SeqCst) {
+                let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+                // Skip a frame rather than wait behind a start/stop
+                let levels = monitor.try_lock().map(|m| m.audio_levels());
+                if let Some(levels) = levels.filter(|l| !l.is_empty()) {
+                    let compact: Vec<serde_json::Value> = levels
+                        .into_iter()
+                        .map(|(name, rms, peak)| {
+                            json!([name, (rms * 1000.0).round() / 1000.0, (peak * 1000.0).round() / 1000.0])
+                        })
+                        .collect();
+                    if !send(&writer, json!({"type": "levels", "l": compact})) {
+                        break;
+                    }
+                }
+            }
+
+            std::thread::sleep(PUSH_INTERVAL);
+        }
+    });
+}
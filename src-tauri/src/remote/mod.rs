@@ -0,0 +1,56 @@
+// Remote control of the recorder from other devices
+
+pub mod mdns;
+pub mod mobile;
+
+use std::net::{IpAddr, UdpSocket};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The machine's address on the LAN. Connecting a UDP socket sends nothing;
+/// it just makes the OS pick the outbound interface.
+pub fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("192.168.0.1", 80)).ok()?;
+    Some(socket.local_addr().ok()?.ip()).filter(|ip| !ip.is_unspecified())
+}
+
+/// Compare secrets without returning early at the first differing byte, so
+/// response timing doesn't reveal how much of a guess was right
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Caps how many connections a LAN server handles at once, so a flood of
+/// connections can't spawn unbounded threads
+#[derive(Clone)]
+pub struct ConnectionLimit {
+    active: Arc<AtomicUsize>,
+    max: usize,
+}
+
+/// A claimed connection; frees its place in the limit when dropped
+pub struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionLimit {
+    pub fn new(max: usize) -> Self {
+        Self { active: Arc::new(AtomicUsize::new(0)), max }
+    }
+
+    /// Claim a place, or None if the server is at its limit
+    pub fn try_acquire(&self) -> Option<ConnectionSlot> {
+        self.active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| (n < self.max).then_some(n + 1))
+            .ok()
+            .map(|_| ConnectionSlot(self.active.clone()))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,68 @@
+// Per-device record schedules
+//
+// A device with a schedule (Config::device_schedules) is only opened while
+// one of its weekly windows is open: `start_audio` and `start_video_pipeline`
+// skip it otherwise. This watcher notices when a window opens or closes and
+// rebuilds the affected pipelines through the `_device-needs-restart`
+// round-trip. A boundary crossed mid-recording waits until the recording
+// stops, so a take is never cut short or joined halfway by a device.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::Config;
+use crate::devices::health::DeviceNeedsRestartPayload;
+use crate::recording::MidiMonitor;
+
+/// How often the watcher checks the clock
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Scheduled devices that are off right now, as (device type, key)
+fn scheduled_off(config: &Config) -> HashSet<(&'static str, String)> {
+    let audio = config.selected_audio_devices.iter().chain(&config.trigger_audio_devices).map(|key| ("audio", key));
+    let video = config.selected_video_devices.iter().map(|key| ("video", key));
+    audio
+        .chain(video)
+        .filter(|(_, key)| config.is_scheduled_off(key))
+        .map(|(kind, key)| (kind, key.clone()))
+        .collect()
+}
+
+/// Watch schedules for the life of the app
+pub fn start_schedule_watcher(app: AppHandle) {
+    std::thread::Builder::new()
+        .name("sacho-schedule".into())
+        .spawn(move || {
+            let mut applied = scheduled_off(&app.state::<RwLock<Config>>().read());
+            loop {
+                std::thread::sleep(CHECK_INTERVAL);
+                let current = scheduled_off(&app.state::<RwLock<Config>>().read());
+                if current == applied {
+                    continue;
+                }
+                let busy = {
+                    let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+                    let monitor = monitor.lock();
+                    monitor.is_recording() || monitor.is_starting()
+                };
+                if busy {
+                    continue;
+                }
+
+                let mut device_types: Vec<String> = current
+                    .symmetric_difference(&applied)
+                    .map(|(kind, _)| kind.to_string())
+                    .collect();
+                device_types.sort();
+                device_types.dedup();
+                println!("[Sacho] Device schedule changed, restarting {:?}", device_types);
+                let _ = app.emit("_device-needs-restart", DeviceNeedsRestartPayload { device_types });
+                applied = current;
+            }
+        })
+        .expect("Failed to spawn schedule watcher thread");
+}
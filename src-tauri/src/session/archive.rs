@@ -0,0 +1,188 @@
+// Session archiving — swap heavyweight video for proxies to reclaim disk
+//
+// Archiving transcodes each original video to a small proxy, then moves the
+// original to `<archive root>/<session id>/` (or deletes it) and writes an
+// `.sacho_archive.json` manifest into the session folder. The manifest is the
+// source of truth for the archived state, so it survives an index rebuild,
+// and it records where the originals went so they can be restored.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::video::proxy;
+
+pub const ARCHIVE_MANIFEST_NAME: &str = ".sacho_archive.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    /// Original video filename
+    pub filename: String,
+    /// Proxy left in the session folder in its place
+    pub proxy_filename: Option<String>,
+    pub size_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub archived_at: String,
+    /// Folder holding the originals, or None if they were deleted
+    pub archive_dir: Option<PathBuf>,
+    pub files: Vec<ArchivedFile>,
+}
+
+impl ArchiveManifest {
+    /// Bytes freed in the session folder (originals minus proxies)
+    pub fn reclaimed_bytes(&self, session_path: &Path) -> u64 {
+        let originals: u64 = self.files.iter().map(|f| f.size_bytes).sum();
+        let proxies: u64 = self.files.iter()
+            .filter_map(|f| f.proxy_filename.as_ref())
+            .filter_map(|p| std::fs::metadata(session_path.join(p)).ok())
+            .map(|m| m.len())
+            .sum();
+        originals.saturating_sub(proxies)
+    }
+}
+
+pub fn is_archived(session_path: &Path) -> bool {
+    session_path.join(ARCHIVE_MANIFEST_NAME).exists()
+}
+
+pub fn read_archive_manifest(session_path: &Path) -> Option<ArchiveManifest> {
+    let data = std::fs::read_to_string(session_path.join(ARCHIVE_MANIFEST_NAME)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn write_archive_manifest(session_path: &Path, manifest: &ArchiveManifest) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(manifest)?;
+    let temp_path = session_path.join(format!("{}.tmp", ARCHIVE_MANIFEST_NAME));
+    std::fs::write(&temp_path, json)?;
+    std::fs::rename(&temp_path, session_path.join(ARCHIVE_MANIFEST_NAME))?;
+    Ok(())
+}
+
+/// Move a file, falling back to copy + size check + delete across volumes
+fn move_file(src: &Path, dst: &Path) -> anyhow::Result<()> {
+    if std::fs::rename(src, dst).is_ok() {
+        return Ok(());
+    }
+    let expected = std::fs::metadata(src)?.len();
+    let copied = std::fs::copy(src, dst)?;
+    if copied != expected {
+        let _ = std::fs::remove_file(dst);
+        return Err(anyhow::anyhow!(
+            "Incomplete copy of {}: {} of {} bytes",
+            src.display(), copied, expected
+        ));
+    }
+    std::fs::remove_file(src)?;
+    Ok(())
+}
+
+/// Original (non-proxy) video files in a session folder
+fn original_videos(session_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut videos: Vec<PathBuf> = std::fs::read_dir(session_path)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| crate::encoding::is_video_extension(n) && !proxy::is_proxy_file(n))
+                .unwrap_or(false)
+        })
+        .collect();
+    videos.sort();
+    Ok(videos)
+}
+
+/// Archive a session's video. With `archive_root` the originals are moved to
+/// `archive_root/<session id>/`; without it they are deleted.
+///
+/// Proxies are generated for every video before any original is touched, so
+/// a transcode failure leaves the session unchanged.
+pub fn archive_session(
+    session_path: &Path,
+    archive_root: Option<&Path>,
+    proxy_height: u32,
+) -> anyhow::Result<ArchiveManifest> {
+    if is_archived(session_path) {
+        return Err(anyhow::anyhow!("Session is already archived"));
+    }
+    let session_id = session_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid session path"))?;
+
+    let videos = original_videos(session_path)?;
+    if videos.is_empty() {
+        return Err(anyhow::anyhow!("Session has no video to archive"));
+    }
+
+    let mut files = Vec::with_capacity(videos.len());
+    for video in &videos {
+        let proxy_path = match proxy::find_proxy(video) {
+            Some(p) => p,
+            None => proxy::generate_proxy(video, proxy_height)?,
+        };
+        files.push(ArchivedFile {
+            filename: video.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            proxy_filename: proxy_path.file_name().map(|n| n.to_string_lossy().to_string()),
+            size_bytes: std::fs::metadata(video).map(|m| m.len()).unwrap_or(0),
+        });
+    }
+
+    let archive_dir = archive_root.map(|root| root.join(session_id));
+    if let Some(dir) = &archive_dir {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    for (video, file) in videos.iter().zip(&files) {
+        match &archive_dir {
+            Some(dir) => move_file(video, &dir.join(&file.filename))?,
+            None => std::fs::remove_file(video)?,
+        }
+    }
+
+    let manifest = ArchiveManifest {
+        archived_at: Utc::now().to_rfc3339(),
+        archive_dir,
+        files,
+    };
+    write_archive_manifest(session_path, &manifest)?;
+
+    println!(
+        "[Sacho] Archived session {} ({} bytes reclaimed)",
+        session_id,
+        manifest.reclaimed_bytes(session_path)
+    );
+    Ok(manifest)
+}
+
+/// Move archived originals back into the session folder. The proxies are kept
+/// so the restored session still scrubs smoothly.
+pub fn restore_session(session_path: &Path) -> anyhow::Result<()> {
+    let manifest = read_archive_manifest(session_path)
+        .ok_or_else(|| anyhow::anyhow!("Session is not archived"))?;
+    let archive_dir = manifest.archive_dir.as_ref()
+        .ok_or_else(|| anyhow::anyhow!("The original video for this session was deleted when it was archived"))?;
+
+    // Check everything is reachable before moving anything
+    for file in &manifest.files {
+        let src = archive_dir.join(&file.filename);
+        if !src.exists() {
+            return Err(anyhow::anyhow!("Archived file not found: {}", src.display()));
+        }
+    }
+
+    for file in &manifest.files {
+        move_file(&archive_dir.join(&file.filename), &session_path.join(&file.filename))?;
+    }
+
+    std::fs::remove_file(session_path.join(ARCHIVE_MANIFEST_NAME))?;
+    // Remove the now-empty per-session archive folder (fails harmlessly if not empty)
+    let _ = std::fs::remove_dir(archive_dir);
+
+    println!("[Sacho] Restored archived session {}", session_path.display());
+    Ok(())
+}
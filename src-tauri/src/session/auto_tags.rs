@@ -0,0 +1,44 @@
+// Auto-tagging rules
+//
+// Rules from the config tag a session when it's finalized: a rule applies
+// when every condition it sets holds — duration bounds, the local time the
+// session started, and which kinds of files it has. Tags a rule adds are
+// merged with any the session already has and saved to its tags.json, so
+// the user can remove them like any other tag.
+
+use crate::config::AutoTagRule;
+
+use super::SessionMetadata;
+
+/// Tags of the enabled rules that match a session
+pub fn matching_tags(rules: &[AutoTagRule], metadata: &SessionMetadata) -> Vec<String> {
+    let started = metadata.timestamp.with_timezone(&chrono::Local).naive_local();
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter(|rule| rule.min_duration_secs.is_none_or(|min| metadata.duration_secs >= min))
+        .filter(|rule| rule.max_duration_secs.is_none_or(|max| metadata.duration_secs < max))
+        .filter(|rule| rule.started_within.as_ref().is_none_or(|window| window.contains(started)))
+        .filter(|rule| rule.has_audio.is_none_or(|has| has == !metadata.audio_files.is_empty()))
+        .filter(|rule| rule.has_midi.is_none_or(|has| has == !metadata.midi_files.is_empty()))
+        .filter(|rule| rule.has_video.is_none_or(|has| has == !metadata.video_files.is_empty()))
+        .map(|rule| rule.tag.clone())
+        .collect()
+}
+
+/// Add the tags of matching rules to a session's metadata and tags.json
+pub fn apply(rules: &[AutoTagRule], metadata: &mut SessionMetadata) {
+    let tags = matching_tags(rules, metadata);
+    if tags.is_empty() {
+        return;
+    }
+    let mut merged = metadata.tags.clone();
+    merged.extend(tags);
+    match super::write_session_tags(&metadata.path, &merged) {
+        Ok(saved) => {
+            println!("[Sacho] Auto-tagged {}: {}", metadata.id, saved.join(", "));
+            metadata.tags = saved;
+        }
+        Err(e) => println!("[Sacho] Failed to save auto tags for {}: {}", metadata.id, e),
+    }
+}
@@ -1,11 +1,42 @@
 // SQLite session index for fast queries
 
-use super::{SessionMetadata, SessionSummary};
+use super::{CustomFields, SessionMetadata, SessionSummary};
+use crate::devices::aliases::{DeviceAlias, DeviceKind};
 use rusqlite::{Connection, params};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use parking_lot::Mutex;
 use tauri::{AppHandle, Manager};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Schema migrations, applied in order. Migration `i` upgrades the database
+/// to `user_version = i + 1`.
+///
+/// Migrations must be idempotent: databases created before versioning was
+/// introduced report version 0 but may already have some of these applied.
+const MIGRATIONS: &[fn(&Connection) -> rusqlite::Result<()>] = &[
+    migrate_v1_base_schema,
+    migrate_v2_notes_modified_at,
+    migrate_v3_title,
+    migrate_v4_archived,
+    migrate_v5_device_aliases,
+    migrate_v6_favorite,
+    migrate_v7_tags,
+    migrate_v8_size_and_sort_indexes,
+    migrate_v9_video_streams,
+    migrate_v10_session_uid,
+    migrate_v11_custom_fields,
+    migrate_v12_uid_primary_key,
+];
+
+/// Current schema version (number of migrations)
+pub const SCHEMA_VERSION: i32 = MIGRATIONS.len() as i32;
+
+/// Error returned when the database file fails its integrity check
+#[derive(Debug, thiserror::Error)]
+#[error("Session database is corrupt: {0}")]
+pub struct DatabaseCorrupt(String);
 
 /// Session database for fast queries
 /// 
@@ -14,10 +45,18 @@ use chrono::{DateTime, Utc};
 /// which would make all subsequent database operations fail.
 pub struct SessionDatabase {
     conn: Mutex<Connection>,
+    /// Database file path (None for in-memory databases)
+    path: Option<PathBuf>,
+    /// True if the on-disk database was corrupt and has been recreated empty.
+    /// The session index must then be rebuilt from the session folders.
+    recovered_from_corruption: bool,
 }
 
 impl SessionDatabase {
-    /// Open or create the session database
+    /// Open or create the session database.
+    ///
+    /// If the existing file fails its integrity check, it is moved aside as
+    /// `sessions.db.corrupt-<timestamp>` and a fresh database is created.
     pub fn open(app_handle: &AppHandle) -> anyhow::Result<Self> {
         let db_path = app_handle
             .path()
@@ -30,11 +69,42 @@ impl SessionDatabase {
             std::fs::create_dir_all(parent)?;
         }
         
-        let conn = Connection::open(&db_path)?;
-        
-        let db = Self { conn: Mutex::new(conn) };
+        match Self::open_path(&db_path) {
+            Ok(db) => Ok(db),
+            Err(e) if is_corruption_error(&e) => {
+                log::error!("{} - recreating and rebuilding from session folders", e);
+                quarantine_corrupt_database(&db_path)?;
+                let mut db = Self::open_path(&db_path)?;
+                db.recovered_from_corruption = true;
+                Ok(db)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Open a database file, verify its integrity, enable WAL, and migrate
+    fn open_path(db_path: &Path) -> anyhow::Result<Self> {
+        let conn = Connection::open(db_path)?;
+
+        let check: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        if check != "ok" {
+            return Err(DatabaseCorrupt(check).into());
+        }
+
+        // WAL keeps readers (session browser) from blocking on writers (rescan,
+        // recording stop). SQLite checkpoints automatically every 1000 pages;
+        // checkpoint() truncates the WAL after large batch writes.
+        let _mode: String = conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get(0))?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        conn.pragma_update(None, "wal_autocheckpoint", 1000)?;
+
+        let db = Self {
+            conn: Mutex::new(conn),
+            path: Some(db_path.to_path_buf()),
+            recovered_from_corruption: false,
+        };
         db.init_schema()?;
-        
+
         Ok(db)
     }
     
@@ -42,80 +112,89 @@ impl SessionDatabase {
     pub fn open_in_memory() -> anyhow::Result<Self> {
         let conn = Connection::open_in_memory()?;
         
-        let db = Self { conn: Mutex::new(conn) };
+        let db = Self {
+            conn: Mutex::new(conn),
+            path: None,
+            recovered_from_corruption: false,
+        };
         db.init_schema()?;
         
         log::warn!("Using in-memory database - sessions will not persist across restarts");
         
         Ok(db)
     }
+
+    /// Whether the database was recreated after corruption was detected on open
+    pub fn was_recovered_from_corruption(&self) -> bool {
+        self.recovered_from_corruption
+    }
     
-    /// Initialize database schema
+    /// Bring the schema up to `SCHEMA_VERSION`, one migration per transaction
     fn init_schema(&self) -> anyhow::Result<()> {
-        let conn = self.conn.lock();
-        conn.execute_batch(r#"
-            CREATE TABLE IF NOT EXISTS sessions (
-                id TEXT PRIMARY KEY,
-                timestamp TEXT NOT NULL,
-                duration_secs REAL NOT NULL,
-                path TEXT NOT NULL,
-                has_audio INTEGER NOT NULL DEFAULT 0,
-                has_midi INTEGER NOT NULL DEFAULT 0,
-                has_video INTEGER NOT NULL DEFAULT 0,
-                notes TEXT NOT NULL DEFAULT '',
-                notes_modified_at TEXT NOT NULL DEFAULT '',
-                title TEXT
-            );
+        let mut conn = self.conn.lock();
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
 
-            CREATE TABLE IF NOT EXISTS midi_imports (
-                id TEXT PRIMARY KEY,
-                folder_path TEXT NOT NULL,
-                file_name TEXT NOT NULL,
-                file_path TEXT NOT NULL,
-                chunked_features BLOB,
-                has_features INTEGER NOT NULL DEFAULT 0,
-                imported_at TEXT NOT NULL
+        if version > SCHEMA_VERSION {
+            log::warn!(
+                "Session database schema v{} is newer than this build (v{}); continuing",
+                version, SCHEMA_VERSION
             );
+            return Ok(());
+        }
 
-            CREATE TABLE IF NOT EXISTS session_features (
-                session_id TEXT PRIMARY KEY,
-                chunked_features BLOB,
-                has_features INTEGER NOT NULL DEFAULT 0,
-                midi_file_count INTEGER NOT NULL DEFAULT 0,
-                computed_at TEXT NOT NULL
-            );
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version.max(0) as usize) {
+            let target = i as i32 + 1;
+            let tx = conn.transaction()?;
+            migration(&tx)?;
+            tx.pragma_update(None, "user_version", target)?;
+            tx.commit()?;
+        }
 
-            CREATE INDEX IF NOT EXISTS idx_sessions_timestamp ON sessions(timestamp DESC);
-            -- Full-text search for notes
-            CREATE VIRTUAL TABLE IF NOT EXISTS sessions_fts USING fts5(
-                id,
-                notes,
-                content='sessions',
-                content_rowid='rowid'
-            );
-        "#)?;
+        if version < SCHEMA_VERSION {
+            println!("[Sacho] Session database schema v{} -> v{}", version, SCHEMA_VERSION);
+        }
+
+        Ok(())
+    }
 
-        // Migration: add notes_modified_at column for existing databases
-        let has_column: bool = conn
-            .prepare("SELECT COUNT(*) FROM pragma_table_info('sessions') WHERE name = 'notes_modified_at'")?
-            .query_row([], |row| row.get::<_, i64>(0))
-            .map(|count| count > 0)?;
+    /// Current schema version stored in the database
+    pub fn schema_version(&self) -> anyhow::Result<i32> {
+        let conn = self.conn.lock();
+        Ok(conn.query_row("PRAGMA user_version", [], |row| row.get(0))?)
+    }
 
-        if !has_column {
-            conn.execute_batch("ALTER TABLE sessions ADD COLUMN notes_modified_at TEXT NOT NULL DEFAULT ''")?;
+    /// Checkpoint the WAL into the main database file and truncate it.
+    /// No-op for in-memory databases.
+    pub fn checkpoint(&self) -> anyhow::Result<()> {
+        if self.path.is_none() {
+            return Ok(());
         }
+        let conn = self.conn.lock();
+        conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
 
-        // Migration: add title column for existing databases
-        let has_title: bool = conn
-            .prepare("SELECT COUNT(*) FROM pragma_table_info('sessions') WHERE name = 'title'")?
-            .query_row([], |row| row.get::<_, i64>(0))
-            .map(|count| count > 0)?;
-
-        if !has_title {
-            conn.execute_batch("ALTER TABLE sessions ADD COLUMN title TEXT")?;
+    /// Rebuild the database file to reclaim free pages.
+    /// Returns (bytes_before, bytes_after) including the WAL file.
+    pub fn vacuum(&self) -> anyhow::Result<(u64, u64)> {
+        let before = self.file_size();
+        {
+            let conn = self.conn.lock();
+            conn.execute_batch("VACUUM")?;
         }
+        self.checkpoint()?;
+        Ok((before, self.file_size()))
+    }
 
-        Ok(())
+    /// Size of the database file plus its WAL, in bytes
+    fn file_size(&self) -> u64 {
+        let Some(path) = &self.path else { return 0 };
+        let wal_path = PathBuf::from(format!("{}-wal", path.to_string_lossy()));
+        [path.as_path(), wal_path.as_path()]
+            .iter()
+            .filter_map(|p| std::fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum()
     }
     
     /// Insert or update a session
@@ -125,9 +204,11 @@ impl SessionDatabase {
             r#"
             INSERT INTO sessions (
                 id, timestamp, duration_secs, path, has_audio, has_midi, has_video,
-                notes, notes_modified_at, title
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, '', ?9)
+                notes, notes_modified_at, title, archived, favorite, tags, size_bytes,
+                video_streams, uid
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, '', ?9, ?10, ?11, ?12, ?13, ?14, COALESCE(?15, ?16))
             ON CONFLICT(id) DO UPDATE SET
+                uid = COALESCE(?15, sessions.uid),
                 timestamp = excluded.timestamp,
                 duration_secs = excluded.duration_secs,
                 path = excluded.path,
@@ -135,7 +216,12 @@ impl SessionDatabase {
                 has_midi = excluded.has_midi,
                 has_video = excluded.has_video,
                 notes = excluded.notes,
-                title = excluded.title
+                title = excluded.title,
+                archived = excluded.archived,
+                favorite = excluded.favorite,
+                tags = excluded.tags,
+                size_bytes = excluded.size_bytes,
+                video_streams = excluded.video_streams
             "#,
             params![
                 metadata.id,
@@ -147,8 +233,16 @@ impl SessionDatabase {
                 !metadata.video_files.is_empty(),
                 metadata.notes,
                 metadata.title,
+                metadata.archived,
+                metadata.favorite,
+                tags_to_column(&metadata.tags),
+                super::session_size_bytes(&metadata.path) as i64,
+                video_streams_to_column(metadata.video_files.iter().filter_map(|v| v.stream.as_ref())),
+                metadata.uid,
+                new_uid(),
             ],
         )?;
+        replace_session_fields(&conn, &metadata.id, &metadata.custom_fields)?;
 
         Ok(())
     }
@@ -157,7 +251,7 @@ impl SessionDatabase {
     pub fn get_all_existing_sessions(&self) -> anyhow::Result<Vec<ExistingSessionRow>> {
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(
-            "SELECT id, has_audio, has_midi, has_video, notes_modified_at FROM sessions"
+            "SELECT id, has_audio, has_midi, has_video, notes_modified_at, archived, favorite, tags, size_bytes, path, uid FROM sessions"
         )?;
 
         let mut rows_out = Vec::new();
@@ -169,14 +263,34 @@ impl SessionDatabase {
                 has_midi: row.get(2)?,
                 has_video: row.get(3)?,
                 notes_modified_at: row.get(4)?,
+                archived: row.get(5)?,
+                favorite: row.get(6)?,
+                tags: tags_from_column(&row.get::<_, String>(7)?),
+                size_bytes: row.get::<_, i64>(8)? as u64,
+                path: row.get(9)?,
+                uid: row.get(10)?,
+                custom_fields: CustomFields::new(),
             });
         }
+
+        let mut fields: HashMap<String, CustomFields> = HashMap::new();
+        let mut stmt = conn.prepare("SELECT session_id, key, value FROM session_fields")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            fields.entry(row.get(0)?).or_default().insert(row.get(1)?, row.get(2)?);
+        }
+        for row in &mut rows_out {
+            if let Some(f) = fields.remove(&row.id) {
+                row.custom_fields = f;
+            }
+        }
         Ok(rows_out)
     }
 
     /// Sync new, updated, and deleted sessions in a single transaction
     pub fn batch_sync(
         &self,
+        moved: &[(String, String)],
         new: &[SessionIndexData],
         updated: &[UpdatedSessionData],
         deleted_ids: &[&String],
@@ -185,14 +299,22 @@ impl SessionDatabase {
         let tx = conn.transaction()?;
         let mut count = 0;
 
+        // Renamed or moved folders keep their row (and features); the
+        // full scan of the new folder below refreshes the rest
+        for (old_id, new_id) in moved {
+            rekey_session(&tx, old_id, new_id)?;
+        }
+
         for s in new {
             tx.execute(
                 r#"
                 INSERT INTO sessions (
                     id, timestamp, duration_secs, path, has_audio, has_midi, has_video,
-                    notes, notes_modified_at, title
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                    notes, notes_modified_at, title, archived, favorite, tags, size_bytes,
+                    video_streams, uid
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, COALESCE(?16, ?17))
                 ON CONFLICT(id) DO UPDATE SET
+                    uid = COALESCE(?16, sessions.uid),
                     timestamp = excluded.timestamp,
                     duration_secs = excluded.duration_secs,
                     path = excluded.path,
@@ -201,7 +323,12 @@ impl SessionDatabase {
                     has_video = excluded.has_video,
                     notes = excluded.notes,
                     notes_modified_at = excluded.notes_modified_at,
-                    title = excluded.title
+                    title = excluded.title,
+                    archived = excluded.archived,
+                    favorite = excluded.favorite,
+                    tags = excluded.tags,
+                    size_bytes = excluded.size_bytes,
+                    video_streams = excluded.video_streams
                 "#,
                 params![
                     s.id,
@@ -214,8 +341,16 @@ impl SessionDatabase {
                     s.notes,
                     s.notes_modified_at,
                     s.title,
+                    s.archived,
+                    s.favorite,
+                    tags_to_column(&s.tags),
+                    s.size_bytes as i64,
+                    video_streams_to_column(&s.video_streams),
+                    s.uid,
+                    new_uid(),
                 ],
             )?;
+            replace_session_fields(&tx, &s.id, &s.custom_fields)?;
             count += 1;
         }
 
@@ -228,8 +363,12 @@ impl SessionDatabase {
                     has_video = ?3,
                     notes = ?4,
                     notes_modified_at = ?5,
-                    title = ?6
-                WHERE id = ?7
+                    title = ?6,
+                    archived = ?7,
+                    favorite = ?8,
+                    tags = ?9,
+                    size_bytes = ?10
+                WHERE id = ?11
                 "#,
                 params![
                     u.has_audio,
@@ -238,18 +377,33 @@ impl SessionDatabase {
                     u.notes,
                     u.notes_modified_at,
                     u.title,
+                    u.archived,
+                    u.favorite,
+                    tags_to_column(&u.tags),
+                    u.size_bytes as i64,
                     u.id,
                 ],
             )?;
+            // The folder's uid wins unless another row already has it
+            if let Some(uid) = &u.uid {
+                tx.execute("UPDATE OR IGNORE sessions SET uid = ?1 WHERE id = ?2", params![uid, u.id])?;
+            }
+            replace_session_fields(&tx, &u.id, &u.custom_fields)?;
             count += 1;
         }
 
         for id in deleted_ids {
             tx.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
             tx.execute("DELETE FROM session_features WHERE session_id = ?1", params![id])?;
+            tx.execute("DELETE FROM session_fields WHERE session_id = ?1", params![id])?;
             count += 1;
         }
 
+        // The full-text index is keyed by row content, including the folder name
+        if !moved.is_empty() {
+            tx.execute("INSERT INTO sessions_fts(sessions_fts) VALUES('rebuild')", [])?;
+        }
+
         tx.commit()?;
         Ok(count)
     }
@@ -271,18 +425,85 @@ impl SessionDatabase {
 
     /// Rename a session (update ID, path, and title)
     pub fn rename_session(&self, old_id: &str, new_id: &str, new_path: &str) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        rekey_session(&tx, old_id, new_id)?;
+        tx.execute(
+            "UPDATE sessions SET path = ?1, title = ?2 WHERE id = ?3",
+            params![new_path, super::extract_title_from_folder_name(new_id), new_id],
+        )?;
+        tx.execute("INSERT INTO sessions_fts(sessions_fts) VALUES('rebuild')", [])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Update a session's folder path (after moving it to another storage root)
+    pub fn update_session_path(&self, session_id: &str, new_path: &str) -> anyhow::Result<()> {
         let conn = self.conn.lock();
         conn.execute(
-            "UPDATE sessions SET id = ?1, path = ?2, title = ?3 WHERE id = ?4",
-            params![new_id, new_path, super::extract_title_from_folder_name(new_id), old_id],
+            "UPDATE sessions SET path = ?1 WHERE id = ?2",
+            params![new_path, session_id],
         )?;
+        Ok(())
+    }
+
+    /// Set or clear a session's archived flag
+    pub fn set_session_archived(&self, session_id: &str, archived: bool) -> anyhow::Result<()> {
+        let conn = self.conn.lock();
         conn.execute(
-            "UPDATE session_features SET session_id = ?1 WHERE session_id = ?2",
-            params![new_id, old_id],
+            "UPDATE sessions SET archived = ?1 WHERE id = ?2",
+            params![archived, session_id],
         )?;
         Ok(())
     }
 
+    /// Set or clear a session's favorite flag
+    pub fn set_session_favorite(&self, session_id: &str, favorite: bool) -> anyhow::Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE sessions SET favorite = ?1 WHERE id = ?2",
+            params![favorite, session_id],
+        )?;
+        Ok(())
+    }
+
+    /// Replace a session's tags
+    pub fn set_session_tags(&self, session_id: &str, tags: &[String]) -> anyhow::Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE sessions SET tags = ?1 WHERE id = ?2",
+            params![tags_to_column(tags), session_id],
+        )?;
+        Ok(())
+    }
+
+    /// Replace a session's custom fields
+    pub fn set_session_fields(&self, session_id: &str, fields: &CustomFields) -> anyhow::Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        replace_session_fields(&tx, session_id, fields)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Every custom field name in use, for suggestions and filters
+    pub fn custom_field_keys(&self) -> anyhow::Result<Vec<String>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT DISTINCT key FROM session_fields ORDER BY key")?;
+        let keys = stmt.query_map([], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(keys)
+    }
+
+    /// Every value a custom field has across sessions
+    pub fn custom_field_values(&self, key: &str) -> anyhow::Result<Vec<String>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT DISTINCT value FROM session_fields WHERE key = ?1 ORDER BY value",
+        )?;
+        let values = stmt.query_map([key], |row| row.get(0))?.collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(values)
+    }
+
     /// Delete a session from the index
     pub fn delete_session(&self, session_id: &str) -> anyhow::Result<()> {
         let conn = self.conn.lock();
@@ -294,25 +515,117 @@ impl SessionDatabase {
             "DELETE FROM session_features WHERE session_id = ?1",
             params![session_id],
         )?;
+        conn.execute(
+            "DELETE FROM session_fields WHERE session_id = ?1",
+            params![session_id],
+        )?;
         Ok(())
     }
     
-    /// Query sessions with filters
+    /// Query one page of sessions matching a filter, in the filter's sort order
     pub fn query_sessions(&self, filter: &SessionFilter) -> anyhow::Result<Vec<SessionSummary>> {
         let mut sql = String::from(
             r#"
             SELECT s.id, s.timestamp, s.duration_secs, s.has_audio, s.has_midi, s.has_video,
-                   s.notes, s.title
+                   s.notes, s.title, s.archived, s.favorite, s.tags, s.size_bytes,
+                   s.video_streams
             FROM sessions s
-            WHERE 1=1
             "#
         );
+        sql.push_str(&Self::filter_clause(filter));
+
+        let direction = if filter.ascending { "ASC" } else { "DESC" };
+        // Ties (equal durations, all non-favorites) fall back to newest first
+        // so pages don't shuffle between queries
+        let order = match filter.sort {
+            SessionSort::Date => format!("s.timestamp {}", direction),
+            SessionSort::Duration => format!("s.duration_secs {}, s.timestamp DESC", direction),
+            SessionSort::Rating => format!("s.favorite {}, s.timestamp DESC", direction),
+            SessionSort::Size => format!("s.size_bytes {}, s.timestamp DESC", direction),
+        };
+        sql.push_str(&format!(" ORDER BY {}", order));
+
+        // SQLite needs a LIMIT before an OFFSET; -1 means no limit
+        if filter.limit.is_some() || filter.offset.is_some() {
+            let limit = filter.limit.map(|l| l as i64).unwrap_or(-1);
+            sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, filter.offset.unwrap_or(0)));
+        }
+
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&sql)?;
+
+        let mut sessions = Vec::new();
+        let mut rows = stmt.query(rusqlite::params_from_iter(Self::filter_params(filter)))?;
+        while let Some(row) = rows.next()? {
+            sessions.push(Self::map_session_row(row)?);
+        }
 
-        // Build search query if provided
-        let search_pattern = filter.search_query.as_ref().map(|q| format!("%{}%", q));
+        Ok(sessions)
+    }
 
-        if search_pattern.is_some() {
-            sql.push_str(" AND (s.notes LIKE ?1 OR s.title LIKE ?1)");
+    /// Summary of one session, if it's indexed
+    pub fn get_session_summary(&self, session_id: &str) -> anyhow::Result<Option<SessionSummary>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT s.id, s.timestamp, s.duration_secs, s.has_audio, s.has_midi, s.has_video,
+                   s.notes, s.title, s.archived, s.favorite, s.tags, s.size_bytes,
+                   s.video_streams
+            FROM sessions s
+            WHERE s.id = ?1
+            "#
+        )?;
+        let mut rows = stmt.query([session_id])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(Self::map_session_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Number of sessions matching a filter (ignoring its limit and offset)
+    pub fn count_sessions(&self, filter: &SessionFilter) -> anyhow::Result<usize> {
+        let sql = format!("SELECT COUNT(*) FROM sessions s{}", Self::filter_clause(filter));
+        let conn = self.conn.lock();
+        let count: i64 = conn.query_row(
+            &sql,
+            rusqlite::params_from_iter(Self::filter_params(filter)),
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Values bound by [`Self::filter_clause`]: the search pattern, then a
+    /// key and value per custom field
+    fn filter_params(filter: &SessionFilter) -> Vec<String> {
+        let mut params: Vec<String> = filter.search_query.iter().map(|q| format!("%{}%", q)).collect();
+        for (key, value) in &filter.custom_fields {
+            params.push(key.clone());
+            params.push(value.clone());
+        }
+        params
+    }
+
+    /// WHERE clause for a filter; the search pattern, if any, binds to ?1
+    fn filter_clause(filter: &SessionFilter) -> String {
+        let mut sql = String::from(" WHERE 1=1");
+        let mut param = 0;
+
+        if filter.search_query.is_some() {
+            param += 1;
+            sql.push_str(
+                " AND (s.notes LIKE ?1 OR s.title LIKE ?1 OR s.tags LIKE ?1 \
+                 OR EXISTS (SELECT 1 FROM session_fields f WHERE f.session_id = s.id AND f.value LIKE ?1))",
+            );
+        }
+
+        // Matched case-insensitively, through idx_session_fields_key_value
+        for _ in &filter.custom_fields {
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM session_fields f WHERE f.session_id = s.id AND f.key = ?{} AND f.value = ?{})",
+                param + 1,
+                param + 2,
+            ));
+            param += 2;
         }
         
         if filter.has_audio == Some(true) {
@@ -335,34 +648,15 @@ impl SessionDatabase {
             sql.push_str(" AND s.title IS NOT NULL AND s.title != ''");
         }
 
-        sql.push_str(" ORDER BY s.timestamp DESC");
-        
-        if let Some(limit) = filter.limit {
-            sql.push_str(&format!(" LIMIT {}", limit));
+        if let Some(archived) = filter.archived {
+            sql.push_str(if archived { " AND s.archived = 1" } else { " AND s.archived = 0" });
         }
-        
-        if let Some(offset) = filter.offset {
-            sql.push_str(&format!(" OFFSET {}", offset));
+
+        if filter.favorite == Some(true) {
+            sql.push_str(" AND s.favorite = 1");
         }
-        
-        let conn = self.conn.lock();
-        let mut stmt = conn.prepare(&sql)?;
-        
-        let mut sessions = Vec::new();
-        
-        if let Some(ref pattern) = search_pattern {
-            let mut rows = stmt.query([pattern])?;
-            while let Some(row) = rows.next()? {
-                sessions.push(Self::map_session_row(row)?);
-            }
-        } else {
-            let mut rows = stmt.query([])?;
-            while let Some(row) = rows.next()? {
-                sessions.push(Self::map_session_row(row)?);
-            }
-        };
-        
-        Ok(sessions)
+
+        sql
     }
     
     fn map_session_row(row: &rusqlite::Row) -> rusqlite::Result<SessionSummary> {
@@ -383,6 +677,12 @@ impl SessionDatabase {
             has_video: row.get(5)?,
             notes: row.get(6)?,
             title: row.get(7)?,
+            archived: row.get(8)?,
+            favorite: row.get(9)?,
+            tags: tags_from_column(&row.get::<_, String>(10)?),
+            size_bytes: row.get::<_, i64>(11)? as u64,
+            video_streams: video_streams_from_column(&row.get::<_, String>(12)?),
+            offline: false,
         })
     }
     
@@ -483,6 +783,7 @@ impl SessionDatabase {
         conn.execute("INSERT INTO sessions_fts(sessions_fts) VALUES('rebuild')", [])?;
         conn.execute("DELETE FROM midi_imports", [])?;
         conn.execute("DELETE FROM session_features", [])?;
+        conn.execute("DELETE FROM session_fields", [])?;
         conn.execute_batch("VACUUM")?;
         Ok(())
     }
@@ -567,6 +868,76 @@ impl SessionDatabase {
     }
 }
 
+// ============================================================================
+// Device aliases
+// ============================================================================
+
+impl SessionDatabase {
+    /// All device aliases, oldest first
+    pub fn get_device_aliases(&self) -> anyhow::Result<Vec<DeviceAlias>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT id, alias, device_type, name_pattern, usb_path, last_device_id FROM device_aliases ORDER BY id"
+        )?;
+
+        let rows = stmt.query_map([], |row| {
+            let device_type: String = row.get(2)?;
+            let alias = DeviceAlias {
+                id: row.get(0)?,
+                alias: row.get(1)?,
+                device_type: DeviceKind::Audio,
+                name_pattern: row.get(3)?,
+                usb_path: row.get(4)?,
+                last_device_id: row.get(5)?,
+            };
+            // Rows with an unknown type are from a newer version; skip them
+            Ok(DeviceKind::parse(&device_type).map(|device_type| DeviceAlias { device_type, ..alias }))
+        })?;
+
+        let mut aliases = Vec::new();
+        for row in rows {
+            if let Some(alias) = row? {
+                aliases.push(alias);
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Insert an alias (id 0) or update an existing one. Returns its id.
+    pub fn save_device_alias(&self, alias: &DeviceAlias) -> anyhow::Result<i64> {
+        let conn = self.conn.lock();
+        if alias.id == 0 {
+            conn.execute(
+                "INSERT INTO device_aliases (alias, device_type, name_pattern, usb_path, last_device_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![alias.alias, alias.device_type.as_str(), alias.name_pattern, alias.usb_path, alias.last_device_id],
+            )?;
+            Ok(conn.last_insert_rowid())
+        } else {
+            conn.execute(
+                "UPDATE device_aliases SET alias = ?2, device_type = ?3, name_pattern = ?4, usb_path = ?5 WHERE id = ?1",
+                params![alias.id, alias.alias, alias.device_type.as_str(), alias.name_pattern, alias.usb_path],
+            )?;
+            Ok(alias.id)
+        }
+    }
+
+    pub fn delete_device_alias(&self, id: i64) -> anyhow::Result<()> {
+        let conn = self.conn.lock();
+        conn.execute("DELETE FROM device_aliases WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Remember the device ID an alias last matched
+    pub fn set_device_alias_last_id(&self, id: i64, device_id: &str) -> anyhow::Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE device_aliases SET last_device_id = ?2 WHERE id = ?1",
+            params![id, device_id],
+        )?;
+        Ok(())
+    }
+}
+
 /// Filter for session queries
 #[derive(Debug, Clone, Default)]
 pub struct SessionFilter {
@@ -576,13 +947,37 @@ pub struct SessionFilter {
     pub has_video: Option<bool>,
     pub has_notes: Option<bool>,
     pub has_title: Option<bool>,
+    pub archived: Option<bool>,
+    pub favorite: Option<bool>,
+    /// Sessions whose custom fields have all of these values
+    pub custom_fields: CustomFields,
+    pub sort: SessionSort,
+    /// Oldest, shortest, smallest first instead of the reverse
+    pub ascending: bool,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
 }
 
+/// Order of the session list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionSort {
+    #[default]
+    Date,
+    Duration,
+    /// Favorites first (the only rating a session has)
+    Rating,
+    /// Total size of the session's files
+    Size,
+}
+
 /// Lightweight session data for initial index (new sessions only)
 pub struct SessionIndexData {
     pub id: String,
+    /// None if the folder has no uid yet and couldn't be given one; the
+    /// index then generates it
+    pub uid: Option<String>,
+    pub custom_fields: CustomFields,
     pub timestamp: DateTime<Utc>,
     pub path: String,
     pub duration_secs: f64,
@@ -592,6 +987,11 @@ pub struct SessionIndexData {
     pub notes: String,
     pub notes_modified_at: String,
     pub title: Option<String>,
+    pub archived: bool,
+    pub favorite: bool,
+    pub tags: Vec<String>,
+    pub size_bytes: u64,
+    pub video_streams: Vec<super::VideoStreamInfo>,
 }
 
 /// Existing session row for lightweight comparison during rescan
@@ -601,17 +1001,32 @@ pub struct ExistingSessionRow {
     pub has_midi: bool,
     pub has_video: bool,
     pub notes_modified_at: String,
+    pub archived: bool,
+    pub favorite: bool,
+    pub tags: Vec<String>,
+    pub size_bytes: u64,
+    /// Session folder when it was last indexed
+    pub path: String,
+    pub uid: String,
+    pub custom_fields: CustomFields,
 }
 
 /// Tag/notes-only update data (no duration recompute)
 pub struct UpdatedSessionData {
     pub id: String,
+    /// Set when the folder carries a different uid than the index
+    pub uid: Option<String>,
+    pub custom_fields: CustomFields,
     pub has_audio: bool,
     pub has_midi: bool,
     pub has_video: bool,
     pub notes: String,
     pub notes_modified_at: String,
     pub title: Option<String>,
+    pub archived: bool,
+    pub favorite: bool,
+    pub tags: Vec<String>,
+    pub size_bytes: u64,
 }
 
 /// Precomputed features for a recording session (similarity analysis)
@@ -636,3 +1051,299 @@ pub struct MidiImport {
     pub imported_at: String,
 }
 
+
+// ============================================================================
+// Migrations
+// ============================================================================
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> rusqlite::Result<bool> {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |row| row.get::<_, i64>(0),
+    ).map(|count| count > 0)
+}
+
+/// v1: base tables and full-text index
+fn migrate_v1_base_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            timestamp TEXT NOT NULL,
+            duration_secs REAL NOT NULL,
+            path TEXT NOT NULL,
+            has_audio INTEGER NOT NULL DEFAULT 0,
+            has_midi INTEGER NOT NULL DEFAULT 0,
+            has_video INTEGER NOT NULL DEFAULT 0,
+            notes TEXT NOT NULL DEFAULT ''
+        );
+
+        CREATE TABLE IF NOT EXISTS midi_imports (
+            id TEXT PRIMARY KEY,
+            folder_path TEXT NOT NULL,
+            file_name TEXT NOT NULL,
+            file_path TEXT NOT NULL,
+            chunked_features BLOB,
+            has_features INTEGER NOT NULL DEFAULT 0,
+            imported_at TEXT NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS session_features (
+            session_id TEXT PRIMARY KEY,
+            chunked_features BLOB,
+            has_features INTEGER NOT NULL DEFAULT 0,
+            midi_file_count INTEGER NOT NULL DEFAULT 0,
+            computed_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_sessions_timestamp ON sessions(timestamp DESC);
+        -- Full-text search for notes
+        CREATE VIRTUAL TABLE IF NOT EXISTS sessions_fts USING fts5(
+            id,
+            notes,
+            content='sessions',
+            content_rowid='rowid'
+        );
+    "#)
+}
+
+/// v2: notes_modified_at column (detects notes.txt edits during rescan)
+fn migrate_v2_notes_modified_at(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "sessions", "notes_modified_at")? {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN notes_modified_at TEXT NOT NULL DEFAULT ''")?;
+    }
+    Ok(())
+}
+
+/// v3: title column (parsed from folder name)
+fn migrate_v3_title(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "sessions", "title")? {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN title TEXT")?;
+    }
+    Ok(())
+}
+
+/// v4: archived flag (video replaced by proxies, originals moved to cold storage)
+fn migrate_v4_archived(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "sessions", "archived")? {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN archived INTEGER NOT NULL DEFAULT 0")?;
+    }
+    Ok(())
+}
+
+/// v5: device aliases (stable names for devices whose IDs change)
+fn migrate_v5_device_aliases(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS device_aliases (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            alias TEXT NOT NULL,
+            device_type TEXT NOT NULL,
+            name_pattern TEXT NOT NULL,
+            usb_path TEXT,
+            last_device_id TEXT
+        );
+    "#)
+}
+
+/// v6: favorite flag
+fn migrate_v6_favorite(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "sessions", "favorite")? {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0")?;
+    }
+    Ok(())
+}
+
+/// v7: session tags
+fn migrate_v7_tags(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "sessions", "tags")? {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN tags TEXT NOT NULL DEFAULT '[]'")?;
+    }
+    Ok(())
+}
+
+/// v8: total file size per session, and indexes for the sortable columns
+fn migrate_v8_size_and_sort_indexes(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "sessions", "size_bytes")? {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN size_bytes INTEGER NOT NULL DEFAULT 0")?;
+    }
+    conn.execute_batch(
+        r#"
+        CREATE INDEX IF NOT EXISTS idx_sessions_duration ON sessions(duration_secs);
+        CREATE INDEX IF NOT EXISTS idx_sessions_size ON sessions(size_bytes);
+        CREATE INDEX IF NOT EXISTS idx_sessions_favorite ON sessions(favorite, timestamp DESC);
+        "#,
+    )
+}
+
+/// v9: per-file video stream info, shown in the session browser
+fn migrate_v9_video_streams(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "sessions", "video_streams")? {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN video_streams TEXT NOT NULL DEFAULT '[]'")?;
+    }
+    Ok(())
+}
+
+/// v10: stable session uid, so renamed or moved folders keep their row
+fn migrate_v10_session_uid(conn: &Connection) -> rusqlite::Result<()> {
+    if !column_exists(conn, "sessions", "uid")? {
+        conn.execute_batch("ALTER TABLE sessions ADD COLUMN uid TEXT")?;
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_sessions_uid ON sessions(uid)")
+}
+
+/// v11: user-defined fields, one row per field so filters can use the index
+fn migrate_v11_custom_fields(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(r#"
+        CREATE TABLE IF NOT EXISTS session_fields (
+            session_id TEXT NOT NULL,
+            key TEXT NOT NULL COLLATE NOCASE,
+            value TEXT NOT NULL COLLATE NOCASE,
+            PRIMARY KEY (session_id, key)
+        );
+        CREATE INDEX IF NOT EXISTS idx_session_fields_key_value ON session_fields(key, value);
+    "#)
+}
+
+/// Point a session's row and everything keyed by its folder name at a new
+/// folder name. The uid (primary key) stays the same.
+fn rekey_session(conn: &Connection, old_id: &str, new_id: &str) -> rusqlite::Result<()> {
+    conn.execute("UPDATE sessions SET id = ?1 WHERE id = ?2", params![new_id, old_id])?;
+    conn.execute(
+        "UPDATE session_features SET session_id = ?1 WHERE session_id = ?2",
+        params![new_id, old_id],
+    )?;
+    conn.execute(
+        "UPDATE session_fields SET session_id = ?1 WHERE session_id = ?2",
+        params![new_id, old_id],
+    )?;
+    Ok(())
+}
+
+/// A fresh session uid, for rows whose folder couldn't be given one
+fn new_uid() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// v12: the uid becomes the primary key; the folder name stays unique so
+/// commands can keep addressing sessions by it. Rows indexed before uids
+/// existed get one here, written to their folder on the next rescan.
+fn migrate_v12_uid_primary_key(conn: &Connection) -> rusqlite::Result<()> {
+    let pk_is_uid: bool = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info('sessions') WHERE name = 'uid' AND pk = 1",
+        [],
+        |row| row.get::<_, i64>(0),
+    )? > 0;
+    if pk_is_uid {
+        return Ok(());
+    }
+
+    let missing: Vec<String> = conn
+        .prepare("SELECT id FROM sessions WHERE uid IS NULL")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    for id in missing {
+        conn.execute("UPDATE sessions SET uid = ?1 WHERE id = ?2", params![new_uid(), id])?;
+    }
+    // Copies indexed as separate sessions may share a uid; keep the first
+    let duplicates: Vec<String> = conn
+        .prepare("SELECT id FROM sessions WHERE rowid NOT IN (SELECT MIN(rowid) FROM sessions GROUP BY uid)")?
+        .query_map([], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    for id in duplicates {
+        conn.execute("UPDATE sessions SET uid = ?1 WHERE id = ?2", params![new_uid(), id])?;
+    }
+
+    conn.execute_batch(r#"
+        CREATE TABLE sessions_v12 (
+            uid TEXT PRIMARY KEY NOT NULL,
+            id TEXT NOT NULL UNIQUE,
+            timestamp TEXT NOT NULL,
+            duration_secs REAL NOT NULL,
+            path TEXT NOT NULL,
+            has_audio INTEGER NOT NULL DEFAULT 0,
+            has_midi INTEGER NOT NULL DEFAULT 0,
+            has_video INTEGER NOT NULL DEFAULT 0,
+            notes TEXT NOT NULL DEFAULT '',
+            notes_modified_at TEXT NOT NULL DEFAULT '',
+            title TEXT,
+            archived INTEGER NOT NULL DEFAULT 0,
+            favorite INTEGER NOT NULL DEFAULT 0,
+            tags TEXT NOT NULL DEFAULT '[]',
+            size_bytes INTEGER NOT NULL DEFAULT 0,
+            video_streams TEXT NOT NULL DEFAULT '[]'
+        );
+        INSERT INTO sessions_v12 (
+            uid, id, timestamp, duration_secs, path, has_audio, has_midi, has_video,
+            notes, notes_modified_at, title, archived, favorite, tags, size_bytes, video_streams
+        )
+        SELECT
+            uid, id, timestamp, duration_secs, path, has_audio, has_midi, has_video,
+            notes, notes_modified_at, title, archived, favorite, tags, size_bytes, video_streams
+        FROM sessions;
+        DROP TABLE sessions;
+        ALTER TABLE sessions_v12 RENAME TO sessions;
+
+        CREATE INDEX IF NOT EXISTS idx_sessions_timestamp ON sessions(timestamp DESC);
+        CREATE INDEX IF NOT EXISTS idx_sessions_duration ON sessions(duration_secs);
+        CREATE INDEX IF NOT EXISTS idx_sessions_size ON sessions(size_bytes);
+        CREATE INDEX IF NOT EXISTS idx_sessions_favorite ON sessions(favorite, timestamp DESC);
+
+        -- Row ids changed with the rebuild
+        INSERT INTO sessions_fts(sessions_fts) VALUES('rebuild');
+    "#)
+}
+
+/// Replace all custom fields of a session
+fn replace_session_fields(conn: &Connection, session_id: &str, fields: &CustomFields) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM session_fields WHERE session_id = ?1", params![session_id])?;
+    let mut stmt = conn.prepare_cached(
+        "INSERT OR REPLACE INTO session_fields (session_id, key, value) VALUES (?1, ?2, ?3)",
+    )?;
+    for (key, value) in fields {
+        stmt.execute(params![session_id, key, value])?;
+    }
+    Ok(())
+}
+
+/// Tags are stored as a JSON array so search can match them with LIKE
+fn tags_to_column(tags: &[String]) -> String {
+    serde_json::to_string(tags).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn tags_from_column(value: &str) -> Vec<String> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+fn video_streams_to_column<'a>(streams: impl IntoIterator<Item = &'a super::VideoStreamInfo>) -> String {
+    let streams: Vec<_> = streams.into_iter().collect();
+    serde_json::to_string(&streams).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn video_streams_from_column(value: &str) -> Vec<super::VideoStreamInfo> {
+    serde_json::from_str(value).unwrap_or_default()
+}
+
+/// Whether an open error indicates a corrupt or non-database file
+fn is_corruption_error(e: &anyhow::Error) -> bool {
+    if e.downcast_ref::<DatabaseCorrupt>().is_some() {
+        return true;
+    }
+    matches!(
+        e.downcast_ref::<rusqlite::Error>(),
+        Some(rusqlite::Error::SqliteFailure(err, _))
+            if matches!(err.code, rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+    )
+}
+
+/// Move a corrupt database (and its WAL/SHM files) aside so a fresh one can be created
+fn quarantine_corrupt_database(db_path: &Path) -> anyhow::Result<()> {
+    let suffix = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+    for ext in ["", "-wal", "-shm"] {
+        let src = PathBuf::from(format!("{}{}", db_path.to_string_lossy(), ext));
+        if src.exists() {
+            let dst = PathBuf::from(format!("{}{}.corrupt-{}", db_path.to_string_lossy(), ext, suffix));
+            std::fs::rename(&src, &dst)?;
+        }
+    }
+    Ok(())
+}
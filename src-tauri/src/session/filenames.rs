@@ -0,0 +1,160 @@
+// Recorded file names
+//
+// Each kind of recording is named from a template in the config
+// (`audio_{device}`, `midi_{device}`, `video_{device}` by default); the
+// extension is appended by the writer. Placeholders:
+//
+//   {device}    device alias or name
+//   {codec}     flac/wav, the video codec, or "midi"
+//   {channels}  audio channel count (empty for MIDI and video)
+//   {take}      1-based take number among the day's sessions
+//
+// Every template must contain {device} so devices never share a file, and
+// the rendered name is made safe for all supported filesystems.
+
+use std::path::Path;
+
+use crate::config::FilenameTemplates;
+
+pub const DEFAULT_AUDIO_TEMPLATE: &str = "audio_{device}";
+pub const DEFAULT_MIDI_TEMPLATE: &str = "midi_{device}";
+pub const DEFAULT_VIDEO_TEMPLATE: &str = "video_{device}";
+
+const PLACEHOLDERS: &[&str] = &["device", "codec", "channels", "take"];
+
+/// Longest file stem we produce, leaving room for suffixes like "_part2.mkv"
+const MAX_STEM_LEN: usize = 180;
+
+/// Names Windows reserves regardless of extension
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Values substituted into a template
+#[derive(Debug, Clone, Default)]
+pub struct FileNameContext<'a> {
+    pub device: &'a str,
+    pub codec: &'a str,
+    pub channels: Option<u16>,
+    pub take: u32,
+}
+
+/// Make one path component safe on Windows, macOS and Linux: spaces and
+/// characters that are reserved or separators become underscores, trailing
+/// dots and spaces are dropped, and reserved device names get a suffix.
+pub fn sanitize_filename_component(name: &str) -> String {
+    let mut safe: String = name
+        .chars()
+        .map(|c| match c {
+            ' ' | '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    while safe.ends_with('.') {
+        safe.pop();
+    }
+
+    let base = safe.split('.').next().unwrap_or("");
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(base)) {
+        safe.push('_');
+    }
+
+    if safe.len() > MAX_STEM_LEN {
+        let mut end = MAX_STEM_LEN;
+        while !safe.is_char_boundary(end) {
+            end -= 1;
+        }
+        safe.truncate(end);
+    }
+    safe
+}
+
+/// Check a template: balanced braces, known placeholders, no path
+/// separators, and a {device} placeholder
+pub fn validate_template(template: &str) -> Result<(), String> {
+    let template = template.trim();
+    if template.is_empty() {
+        return Err("Template cannot be empty".to_string());
+    }
+    if template.contains(['/', '\\']) {
+        return Err("Template cannot contain / or \\".to_string());
+    }
+
+    let mut rest = template;
+    let mut has_device = false;
+    while let Some(open) = rest.find(['{', '}']) {
+        if rest[open..].starts_with('}') {
+            return Err("Unmatched } in template".to_string());
+        }
+        let after = &rest[open + 1..];
+        let close = after.find('}').ok_or("Unmatched { in template")?;
+        let name = &after[..close];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(format!(
+                "Unknown placeholder {{{}}} (use {})",
+                name,
+                PLACEHOLDERS.iter().map(|p| format!("{{{}}}", p)).collect::<Vec<_>>().join(", ")
+            ));
+        }
+        has_device |= name == "device";
+        rest = &after[close + 1..];
+    }
+
+    if !has_device {
+        return Err("Template must contain {device}".to_string());
+    }
+    Ok(())
+}
+
+/// Render a template into a file name with the given extension. An invalid
+/// template falls back to `fallback` so a bad config never stops a recording.
+pub fn render(template: &str, fallback: &str, ctx: &FileNameContext, extension: &str) -> String {
+    let template = if validate_template(template).is_ok() { template.trim() } else { fallback };
+
+    let channels = ctx.channels.map(|c| c.to_string()).unwrap_or_default();
+    let take = ctx.take.to_string();
+    let stem = template
+        .replace("{device}", ctx.device)
+        .replace("{codec}", ctx.codec)
+        .replace("{channels}", &channels)
+        .replace("{take}", &take);
+
+    format!("{}.{}", sanitize_filename_component(&stem), extension)
+}
+
+pub fn audio_filename(templates: &FilenameTemplates, ctx: &FileNameContext, extension: &str) -> String {
+    render(&templates.audio, DEFAULT_AUDIO_TEMPLATE, ctx, extension)
+}
+
+pub fn midi_filename(templates: &FilenameTemplates, ctx: &FileNameContext) -> String {
+    render(&templates.midi, DEFAULT_MIDI_TEMPLATE, ctx, "mid")
+}
+
+pub fn video_filename(templates: &FilenameTemplates, ctx: &FileNameContext, extension: &str) -> String {
+    render(&templates.video, DEFAULT_VIDEO_TEMPLATE, ctx, extension)
+}
+
+/// Take number of a session: its position among the sessions started on
+/// the same day in the same storage folder
+pub fn take_number(session_path: &Path) -> u32 {
+    let Some(folder) = session_path.file_name().and_then(|n| n.to_str()) else { return 1 };
+    let Some(parent) = session_path.parent() else { return 1 };
+    // Folder names start with "YYYY-MM-DD_HH-MM-SS", so names sort by time
+    let Some(day) = folder.get(..10) else { return 1 };
+
+    let earlier = std::fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                .filter(|name| name.starts_with(day) && name.as_str() < folder)
+                .count()
+        })
+        .unwrap_or(0);
+    earlier as u32 + 1
+}
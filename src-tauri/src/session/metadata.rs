@@ -1,16 +1,17 @@
 // Session metadata structures
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
 
 /// Sanitize a device name for use in filenames.
-/// Replaces spaces, slashes, backslashes, and colons with underscores.
+/// Spaces and characters not allowed in file names become underscores
+/// (see [`super::filenames::sanitize_filename_component`]).
 pub fn sanitize_device_name(name: &str) -> String {
-    name.replace(' ', "_")
-        .replace('/', "_")
-        .replace('\\', "_")
-        .replace(':', "_")
+    super::filenames::sanitize_filename_component(name)
 }
 
 /// Reverse sanitization: replace underscores back to spaces.
@@ -19,11 +20,18 @@ pub fn unsanitize_device_name(sanitized: &str) -> String {
     sanitized.replace('_', " ")
 }
 
+/// User-defined key-value fields of a session, sorted by key
+pub type CustomFields = BTreeMap<String, String>;
+
 /// Complete session metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SessionMetadata {
-    /// Unique session ID
+    /// Unique session ID (the folder name, which changes on rename)
     pub id: String,
+
+    /// Stable identity that survives renames and moves (None until assigned)
+    #[serde(default)]
+    pub uid: Option<String>,
     
     /// When the session was recorded
     pub timestamp: DateTime<Utc>,
@@ -60,6 +68,357 @@ pub struct SessionMetadata {
     /// True if the lock file's hostname matches this machine. Null/false if no lock.
     #[serde(default)]
     pub recording_lock_is_local: bool,
+
+    /// True if the session's video has been archived (only proxies remain)
+    #[serde(default)]
+    pub archived: bool,
+
+    /// Marked as a favorite by the user
+    #[serde(default)]
+    pub favorite: bool,
+
+    /// Labels set by the user or by auto-tag rules at finalize
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// User-defined fields, e.g. "student" or "piece"
+    #[serde(default)]
+    pub custom_fields: CustomFields,
+
+    /// Named A/B loop regions saved for practice review
+    #[serde(default)]
+    pub loops: Vec<LoopRegion>,
+
+    /// Piece boundaries detected in the session's MIDI
+    #[serde(default)]
+    pub chapters: Vec<Chapter>,
+
+    /// Timing inconsistencies found when the recording was finalized
+    #[serde(default)]
+    pub timeline_warnings: Vec<TimelineWarning>,
+
+    /// OS user who recorded the session (None for sessions from before
+    /// ownership was tracked)
+    #[serde(default)]
+    pub owner: Option<String>,
+
+    /// Video devices that recorded with a fallback mode because the
+    /// configured one failed to start
+    #[serde(default)]
+    pub video_fallbacks: Vec<VideoFallback>,
+
+    /// Frames duplicated or dropped to keep encoded video at a constant
+    /// frame rate, per file
+    #[serde(default)]
+    pub frame_rate_reports: Vec<FrameRateReport>,
+
+    /// Steps taken to lighten video encoding because the machine couldn't
+    /// keep up
+    #[serde(default)]
+    pub encoding_adaptations: Vec<EncodingAdaptation>,
+
+    /// How long each writer took from the trigger to its first data
+    #[serde(default)]
+    pub startup_latency: Vec<WriterLatency>,
+
+    /// Rate each audio file was captured at and written at
+    #[serde(default)]
+    pub audio_rates: Vec<AudioRate>,
+
+    /// How the streams were lined up when recording started (None for
+    /// sessions from before it was saved)
+    #[serde(default)]
+    pub sync: Option<SessionSync>,
+
+    /// Signal and capture health measured while recording (None for
+    /// sessions from before it was measured)
+    #[serde(default)]
+    pub quality: Option<SessionQuality>,
+
+    /// Format version of the session folder (see [`SESSION_SCHEMA_VERSION`]);
+    /// higher than this build's when a newer Sacho wrote it
+    #[serde(default)]
+    pub schema_version: u32,
+}
+
+/// A named playback loop within a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopRegion {
+    pub name: String,
+    pub start_secs: f64,
+    pub end_secs: f64,
+}
+
+/// A chapter of a session (one piece, or one section in its own key)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub start_secs: f64,
+    pub end_secs: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineWarningKind {
+    /// A file's duration differs from the session's
+    DurationMismatch,
+    /// Missing time between consecutive video frames
+    Gap,
+    /// Video frames sharing or going back in time
+    Overlap,
+    /// A file's first frame is well after the session start
+    LateStart,
+    /// Audio ran short and was padded with silence
+    AudioPadded,
+}
+
+/// A timing inconsistency in one file of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineWarning {
+    pub kind: TimelineWarningKind,
+    pub filename: String,
+    /// Position in the file the problem starts at, if local
+    pub at_secs: Option<f64>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VideoFallbackStep {
+    LowerFps,
+    LowerResolution,
+    AlternateFormat,
+    AlternateProvider,
+}
+
+/// A video device that started with a different mode than configured
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoFallback {
+    pub device_id: String,
+    pub device_name: String,
+    pub step: VideoFallbackStep,
+    /// Configured mode, e.g. "MJPEG 1920x1080 @ 60fps"
+    pub requested: String,
+    /// Mode that actually started
+    pub used: String,
+}
+
+/// What an encoding adaptation changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AdaptationStep {
+    /// Encoder quality/effort preset lowered
+    Preset { from_preset: u8, to_preset: u8, from_effort: u8, to_effort: u8 },
+    /// Frames fed to the encoder thinned out
+    FrameRate { from_fps: f64, to_fps: f64 },
+}
+
+/// An automatic reduction in video encoding load during a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodingAdaptation {
+    pub device_name: String,
+    /// Seconds into the recording
+    pub at_secs: f64,
+    pub step: AdaptationStep,
+    /// What set it off, e.g. "encoder stalled"
+    pub reason: String,
+    /// System CPU usage (percent) at the time
+    pub cpu_percent: Option<f32>,
+    /// Hottest temperature sensor reading (degrees C)
+    pub temperature_c: Option<f32>,
+}
+
+/// Constant-frame-rate conformance of one encoded video file. Covers the
+/// final segment when an encoder failover split the recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameRateReport {
+    pub filename: String,
+    pub device_name: String,
+    /// Output frame rate the file was conformed to
+    pub fps: f64,
+    pub frames_in: u64,
+    pub frames_out: u64,
+    /// Frames repeated to fill gaps in delivery
+    pub duplicated: u64,
+    /// Frames discarded because they arrived faster than the output rate
+    pub dropped: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum LatencyStream {
+    Midi,
+    Audio,
+    Video,
+}
+
+/// Time from the trigger to a writer's first data. The pre-roll already
+/// covers the moments before the trigger; this startup time is what it has
+/// to make up for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WriterLatency {
+    pub stream: LatencyStream,
+    pub device_name: String,
+    pub latency_ms: f64,
+}
+
+/// Sample rates of one audio file: the device's capture rate and the rate
+/// written to the file (they differ when it was resampled)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioRate {
+    pub filename: String,
+    pub device_name: String,
+    pub device_rate: u32,
+    pub file_rate: u32,
+}
+
+/// How far one file had got at a mid-recording checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileProgress {
+    pub filename: String,
+    pub device_name: String,
+    /// Seconds of audio or video written so far
+    #[serde(default)]
+    pub duration_secs: f64,
+    /// MIDI events written so far
+    #[serde(default)]
+    pub event_count: usize,
+}
+
+/// Snapshot of a recording in progress. Rewritten every 30 seconds and
+/// removed once the session is finalized, so a session left behind by a
+/// crash still knows its durations and how its streams line up.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecordingCheckpoint {
+    pub updated_at: String,
+    pub elapsed_secs: f64,
+    /// Seconds into the session where video starts
+    #[serde(default)]
+    pub video_start_offset_secs: f64,
+    /// Latency offsets applied per device (ms)
+    #[serde(default)]
+    pub sync_offsets_ms: HashMap<String, i32>,
+    #[serde(default)]
+    pub audio: Vec<FileProgress>,
+    #[serde(default)]
+    pub midi: Vec<FileProgress>,
+    #[serde(default)]
+    pub video: Vec<FileProgress>,
+}
+
+/// The alignment computed when a recording started, kept so playback and
+/// re-export can rebuild the exact timeline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionSync {
+    /// Pre-roll every stream was aligned to; the session starts this long
+    /// before the trigger
+    pub preroll_secs: f64,
+    /// Added to live MIDI timestamps so they follow the pre-roll (µs)
+    pub midi_timestamp_offset_us: u64,
+    /// Latency correction applied per device (ms, positive = late)
+    #[serde(default)]
+    pub device_offsets_ms: HashMap<String, i32>,
+}
+
+/// Recording-quality report compiled when a session is finalized
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionQuality {
+    #[serde(default)]
+    pub audio: Vec<AudioQuality>,
+    #[serde(default)]
+    pub video: Vec<VideoQuality>,
+}
+
+impl SessionQuality {
+    /// True if any file shows a problem worth flagging
+    pub fn has_issues(&self) -> bool {
+        self.audio.iter().any(AudioQuality::has_issues) || self.video.iter().any(VideoQuality::has_issues)
+    }
+}
+
+/// Mean level above which a channel is reported as having a DC offset
+pub const DC_OFFSET_WARN_LEVEL: f32 = 0.01;
+
+/// Signal health of one audio file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioQuality {
+    pub filename: String,
+    pub device_name: String,
+    /// Samples at or beyond full scale
+    pub clipped_samples: u64,
+    /// Largest absolute sample value (1.0 = full scale)
+    pub peak: f32,
+    /// Largest per-channel mean (signed)
+    pub dc_offset: f32,
+    /// Gaps in the device's capture timeline
+    pub dropouts: u32,
+    /// Total audio lost to dropouts
+    pub dropout_secs: f64,
+    /// Individual dropouts and the silence inserted for them (first 100)
+    #[serde(default)]
+    pub dropout_events: Vec<DropoutEvent>,
+    /// Decode-back check of a finished FLAC (None for WAV or older sessions)
+    #[serde(default)]
+    pub decode_check: Option<DecodeCheck>,
+}
+
+/// Result of decoding a finished FLAC file back after recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodeCheck {
+    pub passed: bool,
+    /// Sample frames written to the encoder
+    pub expected_frames: u64,
+    /// Sample frames the decoder produced
+    pub decoded_frames: u64,
+    /// Decoder error or warning, if it reported one
+    pub error: Option<String>,
+}
+
+/// A gap in an audio device's capture that was filled with silence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DropoutEvent {
+    /// Position in the file where the silence starts
+    pub at_secs: f64,
+    /// Length of the gap in the capture timeline
+    pub gap_ms: f64,
+    /// Silence inserted (less than the gap only for implausibly long gaps)
+    pub concealed_ms: f64,
+}
+
+impl AudioQuality {
+    pub fn has_issues(&self) -> bool {
+        self.clipped_samples > 0
+            || self.dc_offset.abs() > DC_OFFSET_WARN_LEVEL
+            || self.dropouts > 0
+            || self.decode_check.as_ref().is_some_and(|check| !check.passed)
+    }
+}
+
+/// Capture health of one video recording (listed under its final file when
+/// an encoder failover split it into segments)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VideoQuality {
+    pub filename: String,
+    pub device_name: String,
+    pub frames_written: u64,
+    /// Frames dropped because the encoder couldn't keep up
+    pub frames_dropped: u64,
+    /// Periods of a second or more in which the encoder accepted no frames
+    pub encoder_stalls: u32,
+    /// Discontinuities in the written packet timestamps
+    pub timestamp_gaps: u32,
+    /// The first seconds were near-black (lens cap, camera off, dark room)
+    #[serde(default)]
+    pub likely_blank: bool,
+    /// Brightest mean luma (0-255) of the frames checked, if they could be measured
+    #[serde(default)]
+    pub mean_luma: Option<f64>,
+}
+
+impl VideoQuality {
+    pub fn has_issues(&self) -> bool {
+        self.frames_dropped > 0 || self.encoder_stalls > 0 || self.timestamp_gaps > 0 || self.likely_blank
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +426,10 @@ pub struct AudioFileInfo {
     pub filename: String,
     pub device_name: String,
     pub duration_secs: f64,
+    /// Where the file starts on the session timeline. Non-zero when the
+    /// device's pre-roll buffer held less than the session's pre-roll.
+    #[serde(default)]
+    pub start_offset_secs: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -78,6 +441,37 @@ pub struct MidiFileInfo {
     /// This field is computed at load time, not persisted.
     #[serde(default)]
     pub needs_repair: bool,
+    /// Bank/program per channel when recording started (written at tick 0)
+    #[serde(default)]
+    pub programs: Vec<ChannelProgram>,
+    /// Where tick 0 falls on the session timeline
+    #[serde(default)]
+    pub start_offset_secs: f64,
+}
+
+/// Bank and program a MIDI channel was set to when recording started
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ChannelProgram {
+    /// MIDI channel, 0-15
+    pub channel: u8,
+    pub bank_msb: Option<u8>,
+    pub bank_lsb: Option<u8>,
+    pub program: u8,
+}
+
+impl ChannelProgram {
+    /// Bank select (CC 0 / CC 32) followed by program change, as raw messages
+    pub fn to_messages(&self) -> Vec<Vec<u8>> {
+        let mut messages = Vec::with_capacity(3);
+        if let Some(msb) = self.bank_msb {
+            messages.push(vec![0xB0 | self.channel, 0, msb]);
+        }
+        if let Some(lsb) = self.bank_lsb {
+            messages.push(vec![0xB0 | self.channel, 32, lsb]);
+        }
+        messages.push(vec![0xC0 | self.channel, self.program]);
+        messages
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -85,6 +479,66 @@ pub struct VideoFileInfo {
     pub filename: String,
     pub device_name: String,
     pub duration_secs: f64,
+    /// Low-resolution proxy next to this file (for smooth scrubbing), if generated
+    #[serde(default)]
+    pub proxy_filename: Option<String>,
+    /// Bit depth and colorimetry the file was encoded with (None for
+    /// passthrough recordings and files recorded before this was tracked)
+    #[serde(default)]
+    pub color: Option<VideoColorInfo>,
+    /// Where the file starts on the session timeline. Non-zero when the
+    /// audio/MIDI pre-roll reached back further than the video pre-roll.
+    #[serde(default)]
+    pub start_offset_secs: f64,
+    /// Codec, format and encoder the file was written with (None for files
+    /// recorded before this was tracked)
+    #[serde(default)]
+    pub stream: Option<VideoStreamInfo>,
+}
+
+/// How a video file was written, recorded at finalize so the session
+/// browser doesn't have to probe the file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VideoStreamInfo {
+    /// e.g. "AV1", "H.264", "MJPEG"
+    pub codec: String,
+    /// e.g. "MP4", "MKV"
+    pub container: String,
+    pub width: u32,
+    pub height: u32,
+    pub fps: f64,
+    pub bit_depth: u8,
+    /// Encoder backend, None for passthrough recordings
+    #[serde(default)]
+    pub encoder: Option<String>,
+    /// Quality preset level (1-5) the encoder was configured with
+    #[serde(default)]
+    pub preset: Option<u8>,
+}
+
+/// Color format of an encoded video file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VideoColorInfo {
+    /// Bits per component fed to the encoder (8 or 10)
+    pub bit_depth: u8,
+    /// GStreamer colorimetry string, e.g. "bt709" or "bt2100-pq"
+    pub colorimetry: Option<String>,
+    /// True for PQ or HLG transfer
+    pub hdr: bool,
+}
+
+impl VideoColorInfo {
+    pub fn new(pixel_format: &str, colorimetry: Option<String>) -> Self {
+        let hdr = colorimetry
+            .as_deref()
+            .map(crate::encoding::is_hdr_colorimetry)
+            .unwrap_or(false);
+        Self {
+            bit_depth: crate::encoding::bit_depth_for_format(pixel_format),
+            colorimetry,
+            hdr,
+        }
+    }
 }
 
 /// Session summary for list display
@@ -98,6 +552,22 @@ pub struct SessionSummary {
     pub has_video: bool,
     pub notes: String,
     pub title: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Total size of the session's files
+    #[serde(default)]
+    pub size_bytes: u64,
+    /// Format of each video file, for display without probing
+    #[serde(default)]
+    pub video_streams: Vec<VideoStreamInfo>,
+    /// The session's storage folder is unreachable (drive unplugged, NAS
+    /// offline); the row is shown from the index until it comes back
+    #[serde(default)]
+    pub offline: bool,
 }
 
 impl From<&SessionMetadata> for SessionSummary {
@@ -111,6 +581,187 @@ impl From<&SessionMetadata> for SessionSummary {
             has_video: !meta.video_files.is_empty(),
             notes: meta.notes.clone(),
             title: meta.title.clone(),
+            archived: meta.archived,
+            favorite: meta.favorite,
+            tags: meta.tags.clone(),
+            size_bytes: super::session_size_bytes(&meta.path),
+            video_streams: meta.video_files.iter().filter_map(|v| v.stream.clone()).collect(),
+            offline: false,
+        }
+    }
+}
+
+// ============================================================================
+// Schema versioning
+// ============================================================================
+
+/// Migration `i` upgrades a session folder from version `i + 1` to `i + 2`,
+/// returning whether it changed anything on disk. Folders without a schema
+/// marker are version 1.
+const SESSION_MIGRATIONS: &[fn(&Path) -> anyhow::Result<bool>] = &[
+    migrate_v2_schema_marker,
+];
+
+/// Version of the session folder format written by this build
+pub const SESSION_SCHEMA_VERSION: u32 = SESSION_MIGRATIONS.len() as u32 + 1;
+
+/// Marker recording which format a session folder is in
+pub const SCHEMA_FILE_NAME: &str = ".sacho_schema.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSchema {
+    pub schema_version: u32,
+    /// Sacho version that last wrote the marker
+    #[serde(default)]
+    pub written_by: String,
+}
+
+/// v2 introduced the marker itself and lenient side-file parsing; the side
+/// files kept their shape
+fn migrate_v2_schema_marker(_session_path: &Path) -> anyhow::Result<bool> {
+    Ok(false)
+}
+
+/// Format version of a session folder (1 when it has no marker)
+pub fn read_schema_version(session_path: &Path) -> u32 {
+    std::fs::read_to_string(session_path.join(SCHEMA_FILE_NAME))
+        .ok()
+        .and_then(|data| serde_json::from_str::<SessionSchema>(&data).ok())
+        .map(|schema| schema.schema_version.max(1))
+        .unwrap_or(1)
+}
+
+fn write_schema_marker(session_path: &Path, version: u32) -> anyhow::Result<()> {
+    let schema = SessionSchema {
+        schema_version: version,
+        written_by: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    std::fs::write(session_path.join(SCHEMA_FILE_NAME), serde_json::to_string_pretty(&schema)?)?;
+    Ok(())
+}
+
+/// Bring a session folder up to [`SESSION_SCHEMA_VERSION`]. Folders from a
+/// newer build are left alone; their newer fields survive rewrites (see
+/// [`preserve_unknown_fields`]). The marker is only written when a migration
+/// changed something or `stamp` is set because the caller is writing to the
+/// folder anyway, so scanning a library doesn't touch every folder. Nothing
+/// is migrated in read-only mode.
+/// Returns the folder's version afterwards.
+pub fn migrate_session(session_path: &Path, stamp: bool) -> u32 {
+    let version = read_schema_version(session_path);
+    if crate::read_only::is_read_only() {
+        return version;
+    }
+    if version >= SESSION_SCHEMA_VERSION {
+        if version > SESSION_SCHEMA_VERSION {
+            log::debug!(
+                "Session {} is schema v{}, newer than this build (v{})",
+                session_path.display(), version, SESSION_SCHEMA_VERSION
+            );
+        }
+        return version;
+    }
+
+    let mut reached = version;
+    let mut changed = false;
+    for (i, migration) in SESSION_MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        match migration(session_path) {
+            Ok(did_change) => {
+                changed |= did_change;
+                reached = i as u32 + 2;
+            }
+            Err(e) => {
+                log::warn!("Failed to migrate session {} to v{}: {}", session_path.display(), i + 2, e);
+                break;
+            }
+        }
+    }
+
+    if changed || stamp {
+        if let Err(e) = write_schema_marker(session_path, reached) {
+            log::warn!("Failed to write schema marker for {}: {}", session_path.display(), e);
+            return version;
+        }
+        if reached > version {
+            println!("[Sacho] Session {} schema v{} -> v{}", session_path.display(), version, reached);
+        }
+        return reached;
+    }
+    // Nothing on disk differs from the old version, so it reads as current
+    reached
+}
+
+/// Parse a list side file entry by entry. Entries this build can't read
+/// (from a newer Sacho, or damaged) are skipped instead of losing the list.
+pub fn parse_list_leniently<T: DeserializeOwned>(value: &Value, file_name: &str) -> Vec<T> {
+    let Some(items) = value.as_array() else {
+        log::warn!("{}: expected a list", file_name);
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| match serde_json::from_value(item.clone()) {
+            Ok(parsed) => Some(parsed),
+            Err(e) => {
+                log::warn!("{}: skipping unreadable entry: {}", file_name, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Parse a map side file (keyed by file name) entry by entry, like
+/// [`parse_list_leniently`]
+pub fn parse_map_leniently<T: DeserializeOwned>(value: &Value, file_name: &str) -> HashMap<String, T> {
+    let Some(entries) = value.as_object() else {
+        log::warn!("{}: expected an object", file_name);
+        return HashMap::new();
+    };
+    entries
+        .iter()
+        .filter_map(|(key, item)| match serde_json::from_value(item.clone()) {
+            Ok(parsed) => Some((key.clone(), parsed)),
+            Err(e) => {
+                log::warn!("{}: skipping unreadable entry {}: {}", file_name, key, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Copy fields this build doesn't know about from the previous copy of a
+/// side file into the one about to replace it. In a list, an entry keeps
+/// its unknown fields only while every field written here is unchanged, so
+/// an entry edited by this build drops what a newer build derived from it.
+pub fn preserve_unknown_fields(previous: &Value, updated: &mut Value) {
+    match (previous, updated) {
+        (Value::Object(old), Value::Object(new)) => merge_unknown_fields(old, new),
+        (Value::Array(old), Value::Array(new)) => {
+            let mut used = vec![false; old.len()];
+            for item in new.iter_mut() {
+                let Value::Object(fields) = item else { continue };
+                let matched = old.iter().enumerate().position(|(i, old_item)| {
+                    !used[i]
+                        && old_item
+                            .as_object()
+                            .is_some_and(|old_fields| fields.iter().all(|(k, v)| old_fields.get(k) == Some(v)))
+                });
+                if let Some(i) = matched {
+                    used[i] = true;
+                    if let Value::Object(old_fields) = &old[i] {
+                        merge_unknown_fields(old_fields, fields);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn merge_unknown_fields(old: &Map<String, Value>, new: &mut Map<String, Value>) {
+    for (key, value) in old {
+        if !new.contains_key(key) {
+            new.insert(key.clone(), value.clone());
         }
     }
 }
@@ -3,6 +3,13 @@
 pub mod storage;
 pub mod metadata;
 pub mod database;
+pub mod watcher;
+pub mod archive;
+pub mod validation;
+pub mod replace;
+pub mod report;
+pub mod filenames;
+pub mod auto_tags;
 
 pub use storage::*;
 pub use metadata::*;
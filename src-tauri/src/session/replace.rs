@@ -0,0 +1,174 @@
+// Replacing a damaged session file from a backup
+//
+// When one stream of a session is corrupted (a truncated video after a crash,
+// a bad sector in a WAV), a backup copy of that one file can be swapped in
+// without rebuilding the session. The replacement must be the same kind of
+// file in the same format as what it replaces, and its length has to fit the
+// session: no longer than the other recordings (then it's from a different
+// take) and no shorter than what survived of the damaged file. The swap goes
+// through a temporary copy in the session folder, so a failed copy leaves
+// the damaged file untouched.
+
+use std::path::Path;
+
+use gstreamer_pbutils::prelude::*;
+
+use super::validation::DURATION_TOLERANCE_SECS;
+use super::SessionMetadata;
+
+/// What a file contains, as far as compatibility is concerned
+#[derive(Debug, Clone, PartialEq)]
+struct StreamFormat {
+    /// Caps name of the first stream, e.g. "video/x-h264", "audio/x-flac"
+    codec: String,
+    /// Width × height for video, rate × channels for audio
+    shape: (u32, u32),
+}
+
+/// Swap `filename` in a session for the file at `source`, after checking it
+/// fits. Any proxy made from the damaged video is removed so it's rebuilt.
+pub fn replace_file(session: &SessionMetadata, filename: &str, source: &Path) -> anyhow::Result<()> {
+    // Only a plain name of one of the session's own recordings, so a
+    // "../other session/video.mkv" can't reach outside the folder
+    let is_plain_name = Path::new(filename).file_name().and_then(|n| n.to_str()) == Some(filename)
+        && !filename.contains(['/', '\\']);
+    let is_session_file = session.audio_files.iter().any(|f| f.filename == filename)
+        || session.video_files.iter().any(|f| f.filename == filename)
+        || session.midi_files.iter().any(|f| f.filename == filename);
+    if !is_plain_name || !is_session_file {
+        anyhow::bail!("{} is not in this session", filename);
+    }
+    let target = session.path.join(filename);
+    if !target.exists() {
+        anyhow::bail!("{} is not in this session", filename);
+    }
+    if !source.is_file() {
+        anyhow::bail!("Replacement not found: {}", source.display());
+    }
+    let source_ext = source.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    let target_ext = target.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    if source_ext != target_ext {
+        anyhow::bail!("The replacement is a .{} file, {} is .{}", source_ext, filename, target_ext);
+    }
+
+    if target_ext == "mid" {
+        check_midi(source)?;
+    } else {
+        check_media(session, filename, &target, source)?;
+    }
+
+    // Copy next to the original first so the final step is a rename
+    let temp = session.path.join(format!("{}.replacing", filename));
+    if let Err(e) = std::fs::copy(source, &temp).and_then(|_| std::fs::File::open(&temp)?.sync_all()) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(anyhow::anyhow!("Failed to copy the replacement: {}", e));
+    }
+    if let Err(e) = std::fs::rename(&temp, &target) {
+        let _ = std::fs::remove_file(&temp);
+        return Err(anyhow::anyhow!("Failed to swap in the replacement: {}", e));
+    }
+
+    if crate::encoding::is_video_extension(filename) {
+        if let Some(proxy) = crate::video::proxy::find_proxy(&target) {
+            let _ = std::fs::remove_file(proxy);
+        }
+    }
+
+    println!("[Sacho] Replaced {} in {} from {}", filename, session.id, source.display());
+    Ok(())
+}
+
+fn check_midi(source: &Path) -> anyhow::Result<()> {
+    let data = std::fs::read(source)?;
+    midly::Smf::parse(&data).map_err(|e| anyhow::anyhow!("The replacement is not a valid MIDI file: {}", e))?;
+    Ok(())
+}
+
+/// Compare formats (when the damaged file can still be read) and durations
+fn check_media(session: &SessionMetadata, filename: &str, target: &Path, source: &Path) -> anyhow::Result<()> {
+    let is_video = crate::encoding::is_video_extension(filename);
+    let (replacement, duration) = probe(source, is_video)
+        .map_err(|e| anyhow::anyhow!("Can't read the replacement: {}", e))?;
+
+    match probe(target, is_video) {
+        Ok((original, _)) if original.codec != replacement.codec => {
+            anyhow::bail!("The replacement is {}, {} is {}", replacement.codec, filename, original.codec);
+        }
+        Ok((original, _)) if original.shape != replacement.shape => {
+            let unit = if is_video { "pixels" } else { "Hz/channels" };
+            anyhow::bail!(
+                "The replacement is {}x{} {}, {} is {}x{}",
+                replacement.shape.0, replacement.shape.1, unit, filename, original.shape.0, original.shape.1
+            );
+        }
+        Ok(_) => {}
+        // Too damaged to probe: the extension check is all there is
+        Err(e) => println!("[Sacho] Can't probe damaged {} ({}), checking duration only", filename, e),
+    }
+
+    let offset = session.video_files.iter()
+        .find(|v| v.filename == filename)
+        .map(|v| v.start_offset_secs)
+        .unwrap_or(0.0);
+    let surviving = session.audio_files.iter()
+        .find(|a| a.filename == filename)
+        .map(|a| a.duration_secs)
+        .or_else(|| session.video_files.iter().find(|v| v.filename == filename).map(|v| v.duration_secs))
+        .unwrap_or(0.0);
+    let others_end = session.audio_files.iter()
+        .filter(|a| a.filename != filename)
+        .map(|a| a.duration_secs)
+        .chain(session.video_files.iter()
+            .filter(|v| v.filename != filename && v.proxy_filename.as_deref() != Some(filename))
+            .map(|v| v.start_offset_secs + v.duration_secs))
+        .fold(0.0f64, f64::max);
+
+    if others_end > 0.0 && offset + duration > others_end + DURATION_TOLERANCE_SECS {
+        anyhow::bail!(
+            "The replacement runs {:.1}s, longer than the rest of the session ({:.1}s); it may be from another recording",
+            duration, others_end - offset
+        );
+    }
+    if duration + DURATION_TOLERANCE_SECS < surviving {
+        anyhow::bail!(
+            "The replacement runs {:.1}s, shorter than the {:.1}s left in the damaged file",
+            duration, surviving
+        );
+    }
+    Ok(())
+}
+
+/// Format and duration of the first video (or audio) stream
+fn probe(path: &Path, video: bool) -> anyhow::Result<(StreamFormat, f64)> {
+    let discoverer = super::get_or_create_discoverer()?;
+    let uri = format!("file:///{}", path.to_string_lossy().replace('\\', "/"));
+    let info = discoverer.discover_uri(&uri)
+        .map_err(|e| anyhow::anyhow!("Discovery failed: {}", e))?;
+    let duration = info.duration()
+        .map(|d| d.nseconds() as f64 / 1_000_000_000.0)
+        .ok_or_else(|| anyhow::anyhow!("No duration found"))?;
+
+    let format = if video {
+        let stream = info.video_streams().into_iter().next()
+            .ok_or_else(|| anyhow::anyhow!("No video stream"))?;
+        StreamFormat {
+            codec: caps_name(stream.caps()),
+            shape: (stream.width(), stream.height()),
+        }
+    } else {
+        let stream = info.audio_streams().into_iter().next()
+            .ok_or_else(|| anyhow::anyhow!("No audio stream"))?;
+        StreamFormat {
+            codec: caps_name(stream.caps()),
+            shape: (stream.sample_rate(), stream.channels()),
+        }
+    };
+    Ok((format, duration))
+}
+
+fn caps_name(caps: Option<gstreamer::Caps>) -> String {
+    caps.as_ref()
+        .and_then(|c| c.structure(0))
+        .map(|s| s.name().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
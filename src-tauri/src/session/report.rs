@@ -0,0 +1,344 @@
+// Standalone HTML session report
+//
+// A single `report.html` written into the session folder, for sharing a take
+// with someone who doesn't have Sacho (a teacher, a bandmate). It needs
+// nothing but a browser: metadata, notes and the recording-quality report as
+// text, a waveform of each audio track and a piano roll of each MIDI file as
+// inline SVG, and relative links to the media next to it, so the folder can
+// be zipped or synced and still work.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use super::{SessionMetadata, SessionQuality, TimelineWarning};
+use crate::share::{html_escape, percent_encode};
+
+pub const REPORT_FILE_NAME: &str = "report.html";
+
+/// Drawing size of the waveform and piano roll images
+const IMAGE_WIDTH: u32 = 960;
+const WAVEFORM_HEIGHT: u32 = 120;
+const PIANO_ROLL_HEIGHT: u32 = 240;
+
+/// Pitch range always shown in a piano roll (widened to fit the notes)
+const PIANO_ROLL_MIN_PITCH: u8 = 48;
+const PIANO_ROLL_MAX_PITCH: u8 = 84;
+
+/// Write the report into the session folder. Returns its path.
+pub fn write_session_report(metadata: &SessionMetadata) -> anyhow::Result<PathBuf> {
+    if crate::read_only::is_read_only() {
+        anyhow::bail!(crate::read_only::refusal());
+    }
+    let html = render_report(metadata);
+    let path = metadata.path.join(REPORT_FILE_NAME);
+    std::fs::write(&path, html).map_err(|e| anyhow::anyhow!("Failed to write report: {}", e))?;
+    println!("[Sacho] Wrote session report {}", path.display());
+    Ok(path)
+}
+
+fn render_report(metadata: &SessionMetadata) -> String {
+    let session_path = &metadata.path;
+    let title = metadata.title.clone().unwrap_or_else(|| metadata.id.clone());
+    let mut body = String::new();
+
+    // Overview
+    body.push_str("<table class=\"facts\">\n");
+    let mut fact = |label: &str, value: String| {
+        body.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>\n", label, html_escape(&value)));
+    };
+    fact(
+        "Recorded",
+        metadata.timestamp.with_timezone(&Local).format("%A %e %B %Y, %H:%M").to_string(),
+    );
+    fact("Duration", format_duration(metadata.duration_secs));
+    if let Some(owner) = &metadata.owner {
+        fact("Recorded by", owner.clone());
+    }
+    let counts = [
+        (metadata.audio_files.len(), "audio"),
+        (metadata.midi_files.len(), "MIDI"),
+        (metadata.video_files.len(), "video"),
+    ]
+    .iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, kind)| format!("{} {}", n, kind))
+    .collect::<Vec<_>>()
+    .join(", ");
+    fact("Tracks", if counts.is_empty() { "none".into() } else { counts });
+    body.push_str("</table>\n");
+
+    if !metadata.notes.trim().is_empty() {
+        body.push_str(&format!(
+            "<h2>Notes</h2>\n<p class=\"notes\">{}</p>\n",
+            html_escape(metadata.notes.trim())
+        ));
+    }
+
+    // Audio
+    for audio in &metadata.audio_files {
+        body.push_str(&format!(
+            "<section><h2>{} <span class=\"dim\">· audio · {}</span></h2>\n",
+            html_escape(&audio.device_name),
+            format_duration(audio.duration_secs)
+        ));
+        match waveform_svg(&session_path.join(&audio.filename)) {
+            Ok(svg) => body.push_str(&svg),
+            Err(e) => body.push_str(&format!("<p class=\"dim\">Waveform unavailable: {}</p>\n", html_escape(&e.to_string()))),
+        }
+        body.push_str(&media_link(&audio.filename));
+        body.push_str("</section>\n");
+    }
+
+    // MIDI
+    for midi in &metadata.midi_files {
+        body.push_str(&format!(
+            "<section><h2>{} <span class=\"dim\">· MIDI · {} notes</span></h2>\n",
+            html_escape(&midi.device_name),
+            midi.event_count
+        ));
+        match piano_roll_svg(&session_path.join(&midi.filename), metadata.duration_secs) {
+            Ok(svg) => body.push_str(&svg),
+            Err(e) => body.push_str(&format!("<p class=\"dim\">Piano roll unavailable: {}</p>\n", html_escape(&e.to_string()))),
+        }
+        body.push_str(&media_link(&midi.filename));
+        body.push_str("</section>\n");
+    }
+
+    // Video (archived sessions only have the proxy left)
+    for video in &metadata.video_files {
+        let target = if session_path.join(&video.filename).exists() {
+            Some(video.filename.as_str())
+        } else {
+            video.proxy_filename.as_deref()
+        };
+        body.push_str(&format!(
+            "<section><h2>{} <span class=\"dim\">· video · {}</span></h2>\n",
+            html_escape(&video.device_name),
+            format_duration(video.duration_secs)
+        ));
+        match target {
+            Some(name) => body.push_str(&media_link(name)),
+            None => body.push_str("<p class=\"dim\">Video file not available</p>\n"),
+        }
+        body.push_str("</section>\n");
+    }
+
+    if let Some(quality) = &metadata.quality {
+        body.push_str(&render_quality(quality));
+    }
+    if !metadata.timeline_warnings.is_empty() {
+        body.push_str(&render_timeline_warnings(&metadata.timeline_warnings));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title} · Sacho</title>
+<style>
+body {{ font-family: -apple-system, Roboto, sans-serif; background: #141414; color: #e8e6e3; margin: 0 auto; padding: 1.5rem; max-width: 1000px; }}
+h1 {{ font-size: 1.3rem; font-weight: 400; color: #c9a962; margin: 0 0 1rem; }}
+h2 {{ font-size: 0.95rem; font-weight: 500; margin: 1.5rem 0 0.5rem; }}
+section {{ margin-bottom: 1rem; }}
+table {{ border-collapse: collapse; font-size: 0.85rem; }}
+th, td {{ text-align: left; padding: 0.2rem 1rem 0.2rem 0; vertical-align: top; }}
+th {{ font-weight: 500; opacity: 0.7; }}
+svg {{ display: block; width: 100%; height: auto; background: #1c1c1c; border-radius: 4px; }}
+a {{ color: #c9a962; font-size: 0.85rem; }}
+.dim {{ opacity: 0.6; font-weight: 400; }}
+.notes {{ white-space: pre-wrap; font-size: 0.9rem; }}
+.issue {{ color: #ef4444; }}
+footer {{ margin-top: 2rem; font-size: 0.75rem; opacity: 0.5; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{body}<footer>Generated by Sacho on {generated}</footer>
+</body>
+</html>
+"#,
+        title = html_escape(&title),
+        body = body,
+        generated = Local::now().format("%Y-%m-%d %H:%M"),
+    )
+}
+
+fn media_link(filename: &str) -> String {
+    format!(
+        "<p><a href=\"{}\">{}</a></p>\n",
+        percent_encode(filename),
+        html_escape(filename)
+    )
+}
+
+fn render_quality(quality: &SessionQuality) -> String {
+    let mut out = String::from("<h2>Recording quality</h2>\n<table>\n");
+    out.push_str("<tr><th>Track</th><th>Measurements</th></tr>\n");
+    for audio in &quality.audio {
+        let peak_db = 20.0 * (audio.peak.max(1e-6) as f64).log10();
+        let mut notes = vec![format!("peak {:.1} dBFS", peak_db)];
+        if audio.clipped_samples > 0 {
+            notes.push(format!("{} clipped samples", audio.clipped_samples));
+        }
+        if audio.dc_offset.abs() > super::DC_OFFSET_WARN_LEVEL {
+            notes.push(format!("DC offset {:.3}", audio.dc_offset));
+        }
+        if audio.dropouts > 0 {
+            notes.push(format!("{} dropouts ({:.2}s lost)", audio.dropouts, audio.dropout_secs));
+        }
+        if let Some(check) = audio.decode_check.as_ref().filter(|check| !check.passed) {
+            notes.push(format!(
+                "failed decode check ({} of {} frames{})",
+                check.decoded_frames,
+                check.expected_frames,
+                check.error.as_deref().map(|e| format!(", {}", e)).unwrap_or_default()
+            ));
+        }
+        out.push_str(&quality_row(&audio.device_name, audio.has_issues(), &notes));
+    }
+    for video in &quality.video {
+        let mut notes = vec![format!("{} frames", video.frames_written)];
+        if video.frames_dropped > 0 {
+            notes.push(format!("{} dropped", video.frames_dropped));
+        }
+        if video.encoder_stalls > 0 {
+            notes.push(format!("{} encoder stalls", video.encoder_stalls));
+        }
+        if video.timestamp_gaps > 0 {
+            notes.push(format!("{} timestamp gaps", video.timestamp_gaps));
+        }
+        if video.likely_blank {
+            notes.push("picture was black at the start".into());
+        }
+        out.push_str(&quality_row(&video.device_name, video.has_issues(), &notes));
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+fn quality_row(device: &str, has_issues: bool, notes: &[String]) -> String {
+    format!(
+        "<tr{}><td>{}</td><td>{}</td></tr>\n",
+        if has_issues { " class=\"issue\"" } else { "" },
+        html_escape(device),
+        html_escape(&notes.join(", "))
+    )
+}
+
+fn render_timeline_warnings(warnings: &[TimelineWarning]) -> String {
+    let mut out = String::from("<h2>Timeline warnings</h2>\n<ul>\n");
+    for warning in warnings {
+        let at = warning
+            .at_secs
+            .map(|t| format!(" at {}", format_duration(t)))
+            .unwrap_or_default();
+        out.push_str(&format!(
+            "<li>{}{}: {}</li>\n",
+            html_escape(&warning.filename),
+            at,
+            html_escape(&warning.message)
+        ));
+    }
+    out.push_str("</ul>\n");
+    out
+}
+
+/// Min/max envelope of an audio file, one column per horizontal pixel
+fn waveform_svg(path: &Path) -> anyhow::Result<String> {
+    let samples = crate::similarity::audio_features::decode_mono(path)?;
+    if samples.is_empty() {
+        anyhow::bail!("no audio");
+    }
+    let columns = IMAGE_WIDTH as usize;
+    let per_column = samples.len().div_ceil(columns).max(1);
+    let mid = WAVEFORM_HEIGHT as f32 / 2.0;
+
+    let mut top = String::new();
+    let mut bottom = Vec::new();
+    for (x, chunk) in samples.chunks(per_column).enumerate() {
+        let (lo, hi) = chunk
+            .iter()
+            .fold((0.0f32, 0.0f32), |(lo, hi), &s| (lo.min(s), hi.max(s)));
+        let command = if x == 0 { 'M' } else { 'L' };
+        top.push_str(&format!("{}{} {:.1} ", command, x, mid - hi.clamp(-1.0, 1.0) * mid));
+        bottom.push(format!("L{} {:.1} ", x, mid - lo.clamp(-1.0, 1.0) * mid));
+    }
+    bottom.reverse();
+
+    Ok(format!(
+        "<svg viewBox=\"0 0 {w} {h}\" preserveAspectRatio=\"none\" xmlns=\"http://www.w3.org/2000/svg\">\
+<path d=\"{top}{bottom}Z\" fill=\"#c9a962\"/></svg>\n",
+        w = IMAGE_WIDTH,
+        h = WAVEFORM_HEIGHT,
+        top = top,
+        bottom = bottom.concat(),
+    ))
+}
+
+/// Notes of a MIDI file as bars on a time/pitch grid, shaded by velocity
+fn piano_roll_svg(path: &Path, session_secs: f64) -> anyhow::Result<String> {
+    let parsed = crate::similarity::midi_parser::parse_midi(path)?;
+    let to_secs = |tick: u64| {
+        crate::similarity::midi_parser::tick_to_seconds(tick, parsed.ticks_per_beat, &parsed.tempo_map)
+    };
+    let notes: Vec<(f64, f64, u8, u8)> = parsed
+        .events
+        .iter()
+        .map(|n| {
+            let start = to_secs(n.start_tick);
+            (start, to_secs(n.start_tick + n.duration_ticks) - start, n.pitch, n.velocity)
+        })
+        .collect();
+
+    let length = notes
+        .iter()
+        .map(|(start, dur, _, _)| start + dur)
+        .fold(session_secs, f64::max)
+        .max(1.0);
+    let min_pitch = notes.iter().map(|n| n.2).min().unwrap_or(PIANO_ROLL_MIN_PITCH).min(PIANO_ROLL_MIN_PITCH);
+    let max_pitch = notes.iter().map(|n| n.2).max().unwrap_or(PIANO_ROLL_MAX_PITCH).max(PIANO_ROLL_MAX_PITCH);
+    let rows = (max_pitch - min_pitch) as f64 + 1.0;
+    let row_height = PIANO_ROLL_HEIGHT as f64 / rows;
+    let x_scale = IMAGE_WIDTH as f64 / length;
+
+    let mut svg = format!(
+        "<svg viewBox=\"0 0 {} {}\" preserveAspectRatio=\"none\" xmlns=\"http://www.w3.org/2000/svg\">",
+        IMAGE_WIDTH, PIANO_ROLL_HEIGHT
+    );
+    // C of each octave as a faint guide line
+    for pitch in (min_pitch..=max_pitch).filter(|p| p % 12 == 0) {
+        let y = (max_pitch - pitch) as f64 * row_height + row_height;
+        svg.push_str(&format!(
+            "<line x1=\"0\" x2=\"{}\" y1=\"{:.1}\" y2=\"{:.1}\" stroke=\"#2a2a2a\" stroke-width=\"1\"/>",
+            IMAGE_WIDTH, y, y
+        ));
+    }
+    for (start, duration, pitch, velocity) in &notes {
+        svg.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#c9a962\" fill-opacity=\"{:.2}\"/>",
+            start * x_scale,
+            (max_pitch - pitch) as f64 * row_height,
+            (duration * x_scale).max(1.0),
+            row_height.max(1.0),
+            0.35 + 0.65 * (*velocity as f64 / 127.0)
+        ));
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+fn format_duration(secs: f64) -> String {
+    let total_secs = secs as u64;
+    let hours = total_secs / 3600;
+    let mins = (total_secs % 3600) / 60;
+    let secs = total_secs % 60;
+
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, mins, secs)
+    } else {
+        format!("{}:{:02}", mins, secs)
+    }
+}
@@ -1,12 +1,15 @@
 // Session folder management — directory scan and header parsing
 
-use super::{SessionMetadata, AudioFileInfo, MidiFileInfo, VideoFileInfo};
+use super::{SessionMetadata, CustomFields, AudioFileInfo, MidiFileInfo, VideoFileInfo, LoopRegion, Chapter, TimelineWarning, VideoFallback, VideoColorInfo, FrameRateReport, EncodingAdaptation, SessionQuality, WriterLatency, AudioRate, RecordingCheckpoint, FileProgress, SessionSync, VideoStreamInfo};
 use super::unsanitize_device_name;
-use std::path::Path;
-use std::io::{Read, Seek, SeekFrom};
+use super::metadata::{migrate_session, parse_list_leniently, parse_map_leniently, preserve_unknown_fields};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::io::Read;
 use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, Utc, TimeZone};
 use gstreamer_pbutils;
 use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
 
 // ============================================================================
 // Recording lock file helpers
@@ -21,15 +24,61 @@ pub struct RecordingLockInfo {
     pub updated_at: String,
 }
 
-pub fn create_recording_lock(session_path: &Path) -> anyhow::Result<()> {
+/// Take the recording lock on a session folder. The lock file is created
+/// exclusively, so this fails with `AlreadyExists` if another recording
+/// already holds the folder.
+pub fn create_recording_lock(session_path: &Path) -> std::io::Result<()> {
+    use std::io::Write;
+
     let lock = RecordingLockInfo {
         hostname: sysinfo::System::host_name().unwrap_or_default(),
         pid: std::process::id(),
         updated_at: Utc::now().to_rfc3339(),
     };
     let json = serde_json::to_string_pretty(&lock)?;
-    std::fs::write(session_path.join(LOCK_FILE_NAME), json)?;
-    Ok(())
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(session_path.join(LOCK_FILE_NAME))?;
+    file.write_all(json.as_bytes())
+}
+
+/// Create a new session folder named for `start` under `root` and lock it.
+/// A folder that already exists belongs to another recording (two takes
+/// started within the same second), so a numeric suffix is added ("PST-2")
+/// until a fresh one is claimed; the timestamp itself stays the start time
+/// and no two recordings ever share files.
+pub fn claim_session_folder(root: &Path, start: DateTime<Local>) -> anyhow::Result<PathBuf> {
+    const MAX_ATTEMPTS: u32 = 30;
+
+    std::fs::create_dir_all(root)?;
+    let base_name = format!(
+        "{} {}",
+        start.format("%Y-%m-%d_%H-%M-%S"),
+        local_timezone_abbreviation(&start)
+    );
+    for attempt in 1..=MAX_ATTEMPTS {
+        let folder_name = if attempt == 1 {
+            base_name.clone()
+        } else {
+            format!("{}-{}", base_name, attempt)
+        };
+        let session_path = root.join(folder_name);
+        match std::fs::create_dir(&session_path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+        match create_recording_lock(&session_path) {
+            Ok(()) => return Ok(session_path),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => {
+                let _ = std::fs::remove_dir(&session_path);
+                return Err(e.into());
+            }
+        }
+    }
+    anyhow::bail!("No free session folder name near {}", start.format("%Y-%m-%d_%H-%M-%S"))
 }
 
 pub fn touch_recording_lock(session_path: &Path) {
@@ -61,60 +110,685 @@ pub fn read_recording_lock(session_path: &Path) -> Option<RecordingLockInfo> {
 }
 
 // ============================================================================
-// Read-only header parsing functions
+// Favorites
 // ============================================================================
 
-/// Read WAV duration by parsing RIFF fmt+data chunks (read-only, no patching).
-pub fn read_wav_duration(path: &Path) -> anyhow::Result<f64> {
-    let mut file = std::fs::File::open(path)?;
-    let file_size = file.metadata()?.len();
+/// Empty marker file flagging a session as a favorite. Like the archive
+/// manifest, it lives in the folder so the flag survives an index rebuild.
+pub const FAVORITE_MARKER_NAME: &str = ".sacho_favorite";
 
-    if file_size < 44 {
-        return Err(anyhow::anyhow!("File too small to be a valid WAV file"));
+pub fn is_favorite(session_path: &Path) -> bool {
+    session_path.join(FAVORITE_MARKER_NAME).exists()
+}
+
+pub fn set_favorite(session_path: &Path, favorite: bool) -> std::io::Result<()> {
+    if crate::read_only::is_read_only() {
+        return Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, crate::read_only::refusal()));
     }
+    let marker = session_path.join(FAVORITE_MARKER_NAME);
+    if favorite {
+        std::fs::write(marker, b"")
+    } else if marker.exists() {
+        std::fs::remove_file(marker)
+    } else {
+        Ok(())
+    }
+}
 
-    let mut header = [0u8; 12];
-    file.read_exact(&mut header)?;
-    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
-        return Err(anyhow::anyhow!("Not a valid WAV file"));
+// ============================================================================
+// Tags
+// ============================================================================
+
+pub const TAGS_FILE_NAME: &str = "tags.json";
+
+pub fn read_session_tags(session_path: &Path) -> Vec<String> {
+    read_list_file(session_path, TAGS_FILE_NAME)
+}
+
+/// Save a session's tags, trimmed and without (case-insensitive) duplicates.
+/// Returns the tags as saved; an empty list removes the file.
+pub fn write_session_tags(session_path: &Path, tags: &[String]) -> anyhow::Result<Vec<String>> {
+    let tags = normalize_tags(tags);
+    if tags.is_empty() {
+        let path = session_path.join(TAGS_FILE_NAME);
+        if path.exists() {
+            remove_side_file(&path)?;
+        }
+        return Ok(tags);
     }
+    write_side_file(session_path, TAGS_FILE_NAME, &tags, false)?;
+    Ok(tags)
+}
 
-    let mut channels: u16 = 0;
-    let mut sample_rate: u32 = 0;
-    let mut bits_per_sample: u16 = 0;
-    let mut data_chunk_offset: u64 = 0;
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !out.iter().any(|existing| existing.eq_ignore_ascii_case(tag)) {
+            out.push(tag.to_string());
+        }
+    }
+    out
+}
 
-    let mut pos: u64 = 12;
-    loop {
-        file.seek(SeekFrom::Start(pos))?;
-        let mut chunk_header = [0u8; 8];
-        if file.read_exact(&mut chunk_header).is_err() { break; }
+// ============================================================================
+// Custom fields
+// ============================================================================
 
-        let chunk_id = &chunk_header[0..4];
-        let chunk_size = u32::from_le_bytes([chunk_header[4], chunk_header[5], chunk_header[6], chunk_header[7]]);
+pub const CUSTOM_FIELDS_FILE_NAME: &str = "fields.json";
 
-        if chunk_id == b"fmt " {
-            let mut fmt = [0u8; 16];
-            file.read_exact(&mut fmt)?;
-            channels = u16::from_le_bytes([fmt[2], fmt[3]]);
-            sample_rate = u32::from_le_bytes([fmt[4], fmt[5], fmt[6], fmt[7]]);
-            bits_per_sample = u16::from_le_bytes([fmt[14], fmt[15]]);
-        } else if chunk_id == b"data" {
-            data_chunk_offset = pos;
-            break;
+pub fn read_custom_fields(session_path: &Path) -> CustomFields {
+    read_object_file(session_path, CUSTOM_FIELDS_FILE_NAME).unwrap_or_default()
+}
+
+/// Save a session's custom fields, trimmed, without empty keys or values and
+/// without (case-insensitive) duplicate keys. Returns the fields as saved; an
+/// empty map removes the file.
+pub fn write_custom_fields(session_path: &Path, fields: &CustomFields) -> anyhow::Result<CustomFields> {
+    let fields = normalize_custom_fields(fields);
+    if fields.is_empty() {
+        let path = session_path.join(CUSTOM_FIELDS_FILE_NAME);
+        if path.exists() {
+            remove_side_file(&path)?;
         }
+        return Ok(fields);
+    }
+    write_side_file(session_path, CUSTOM_FIELDS_FILE_NAME, &fields, false)?;
+    Ok(fields)
+}
 
-        pos += 8 + chunk_size as u64;
-        if chunk_size % 2 != 0 { pos += 1; }
+pub fn normalize_custom_fields(fields: &CustomFields) -> CustomFields {
+    let mut out = CustomFields::new();
+    for (key, value) in fields.iter().map(|(k, v)| (k.trim(), v.trim())) {
+        if key.is_empty() || value.is_empty() {
+            continue;
+        }
+        if !out.keys().any(|existing| existing.eq_ignore_ascii_case(key)) {
+            out.insert(key.to_string(), value.to_string());
+        }
     }
+    out
+}
+
+// ============================================================================
+// Side files
+// ============================================================================
+
+fn read_json_value(path: &Path) -> Option<serde_json::Value> {
+    let data = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&data) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::warn!("Failed to parse {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Read a list side file, skipping entries this build can't parse
+fn read_list_file<T: DeserializeOwned>(session_path: &Path, file_name: &str) -> Vec<T> {
+    read_json_value(&session_path.join(file_name))
+        .map(|value| parse_list_leniently(&value, file_name))
+        .unwrap_or_default()
+}
+
+/// Read a side file keyed by file name, skipping entries this build can't parse
+fn read_map_file<T: DeserializeOwned>(session_path: &Path, file_name: &str) -> HashMap<String, T> {
+    read_json_value(&session_path.join(file_name))
+        .map(|value| parse_map_leniently(&value, file_name))
+        .unwrap_or_default()
+}
+
+fn read_object_file<T: DeserializeOwned>(session_path: &Path, file_name: &str) -> Option<T> {
+    serde_json::from_value(read_json_value(&session_path.join(file_name))?).ok()
+}
+
+/// Remove a side file that no longer has anything to hold
+fn remove_side_file(path: &Path) -> anyhow::Result<()> {
+    if crate::read_only::is_read_only() {
+        anyhow::bail!(crate::read_only::refusal());
+    }
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Write a side file and stamp the folder's schema version. With
+/// `keep_unknown`, fields a newer Sacho wrote into the previous copy are
+/// carried over (not for maps keyed by file name, where a missing key means
+/// the entry was removed).
+fn write_side_file<T: Serialize + ?Sized>(
+    session_path: &Path,
+    file_name: &str,
+    value: &T,
+    keep_unknown: bool,
+) -> anyhow::Result<()> {
+    if crate::read_only::is_read_only() {
+        anyhow::bail!(crate::read_only::refusal());
+    }
+    let path = session_path.join(file_name);
+    let mut json = serde_json::to_value(value)?;
+    if keep_unknown {
+        if let Some(previous) = read_json_value(&path) {
+            preserve_unknown_fields(&previous, &mut json);
+        }
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&json)?)?;
+    migrate_session(session_path, true);
+    Ok(())
+}
+
+// ============================================================================
+// Loop regions
+// ============================================================================
+
+pub const LOOPS_FILE_NAME: &str = "loops.json";
+
+pub fn read_session_loops(session_path: &Path) -> Vec<LoopRegion> {
+    read_list_file(session_path, LOOPS_FILE_NAME)
+}
+
+/// Save loop regions to loops.json (removed when there are none)
+pub fn write_session_loops(session_path: &Path, loops: &[LoopRegion]) -> anyhow::Result<()> {
+    let path = session_path.join(LOOPS_FILE_NAME);
+    if loops.is_empty() {
+        if path.exists() {
+            remove_side_file(&path)?;
+        }
+        return Ok(());
+    }
+    write_side_file(session_path, LOOPS_FILE_NAME, loops, true)
+}
+
+// ============================================================================
+// Chapters
+// ============================================================================
+
+pub const CHAPTERS_FILE_NAME: &str = "chapters.json";
+
+pub fn read_session_chapters(session_path: &Path) -> Vec<Chapter> {
+    read_list_file(session_path, CHAPTERS_FILE_NAME)
+}
+
+/// Save detected chapters to chapters.json (removed when there are none)
+pub fn write_session_chapters(session_path: &Path, chapters: &[Chapter]) -> anyhow::Result<()> {
+    let path = session_path.join(CHAPTERS_FILE_NAME);
+    if chapters.is_empty() {
+        if path.exists() {
+            remove_side_file(&path)?;
+        }
+        return Ok(());
+    }
+    write_side_file(session_path, CHAPTERS_FILE_NAME, chapters, true)
+}
+
+// ============================================================================
+// Video fallbacks
+// ============================================================================
+
+pub const VIDEO_FALLBACKS_FILE_NAME: &str = "video_fallbacks.json";
+
+pub fn read_video_fallbacks(session_path: &Path) -> Vec<VideoFallback> {
+    read_list_file(session_path, VIDEO_FALLBACKS_FILE_NAME)
+}
+
+/// Record which devices are running in a fallback mode (nothing is written
+/// when there are none)
+pub fn write_video_fallbacks(session_path: &Path, fallbacks: &[VideoFallback]) -> anyhow::Result<()> {
+    if fallbacks.is_empty() {
+        return Ok(());
+    }
+    write_side_file(session_path, VIDEO_FALLBACKS_FILE_NAME, fallbacks, true)
+}
+
+// ============================================================================
+// Frame rate reports
+// ============================================================================
+
+pub const FRAME_RATE_REPORTS_FILE_NAME: &str = "frame_rate_reports.json";
+
+pub fn read_frame_rate_reports(session_path: &Path) -> Vec<FrameRateReport> {
+    read_list_file(session_path, FRAME_RATE_REPORTS_FILE_NAME)
+}
+
+/// Save constant-frame-rate reports (nothing is written when there are none)
+pub fn write_frame_rate_reports(session_path: &Path, reports: &[FrameRateReport]) -> anyhow::Result<()> {
+    if reports.is_empty() {
+        return Ok(());
+    }
+    write_side_file(session_path, FRAME_RATE_REPORTS_FILE_NAME, reports, true)
+}
+
+// ============================================================================
+// Encoding adaptations
+// ============================================================================
+
+pub const ENCODING_ADAPTATIONS_FILE_NAME: &str = "encoding_adaptations.json";
+
+pub fn read_encoding_adaptations(session_path: &Path) -> Vec<EncodingAdaptation> {
+    read_list_file(session_path, ENCODING_ADAPTATIONS_FILE_NAME)
+}
+
+/// Save the encoding adaptations made during a recording (nothing is
+/// written when there were none)
+pub fn write_encoding_adaptations(session_path: &Path, adaptations: &[EncodingAdaptation]) -> anyhow::Result<()> {
+    if adaptations.is_empty() {
+        return Ok(());
+    }
+    write_side_file(session_path, ENCODING_ADAPTATIONS_FILE_NAME, adaptations, true)
+}
+
+// ============================================================================
+// Startup latency
+// ============================================================================
+
+pub const STARTUP_LATENCY_FILE_NAME: &str = "startup_latency.json";
+
+pub fn read_startup_latency(session_path: &Path) -> Vec<WriterLatency> {
+    read_list_file(session_path, STARTUP_LATENCY_FILE_NAME)
+}
+
+/// Save trigger-to-first-write times (nothing is written when there are none)
+pub fn write_startup_latency(session_path: &Path, latency: &[WriterLatency]) -> anyhow::Result<()> {
+    if latency.is_empty() {
+        return Ok(());
+    }
+    write_side_file(session_path, STARTUP_LATENCY_FILE_NAME, latency, true)
+}
+
+// ============================================================================
+// Stream alignment
+// ============================================================================
+
+pub const SYNC_FILE_NAME: &str = "sync.json";
+
+/// Contents of the sync file: the session's alignment plus the start offset
+/// of each audio/MIDI file that doesn't begin at 0 (video keeps its own file)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SyncFile {
+    #[serde(flatten)]
+    sync: SessionSync,
+    #[serde(default)]
+    file_offsets: HashMap<String, f64>,
+}
+
+pub fn read_session_sync(session_path: &Path) -> Option<SessionSync> {
+    read_object_file::<SyncFile>(session_path, SYNC_FILE_NAME).map(|f| f.sync)
+}
+
+/// Save how the streams were aligned and where each file starts
+pub fn write_session_sync(
+    session_path: &Path,
+    sync: &SessionSync,
+    audio_files: &[AudioFileInfo],
+    midi_files: &[MidiFileInfo],
+) -> anyhow::Result<()> {
+    let file_offsets = audio_files
+        .iter()
+        .map(|f| (&f.filename, f.start_offset_secs))
+        .chain(midi_files.iter().map(|f| (&f.filename, f.start_offset_secs)))
+        .filter(|(_, offset)| *offset != 0.0)
+        .map(|(name, offset)| (name.clone(), offset))
+        .collect();
+    let file = SyncFile { sync: sync.clone(), file_offsets };
+    write_side_file(session_path, SYNC_FILE_NAME, &file, true)
+}
+
+// ============================================================================
+// Recording checkpoints
+// ============================================================================
+
+pub const CHECKPOINT_FILE_NAME: &str = "checkpoint.json";
+
+pub fn read_checkpoint(session_path: &Path) -> Option<RecordingCheckpoint> {
+    read_object_file(session_path, CHECKPOINT_FILE_NAME)
+}
+
+/// Replace the checkpoint through a temp file, so a crash mid-write leaves
+/// the previous one intact
+pub fn write_checkpoint(session_path: &Path, checkpoint: &RecordingCheckpoint) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(checkpoint)?;
+    let temp_path = session_path.join(format!("{}.tmp", CHECKPOINT_FILE_NAME));
+    std::fs::write(&temp_path, json)?;
+    std::fs::rename(&temp_path, session_path.join(CHECKPOINT_FILE_NAME))?;
+    Ok(())
+}
+
+pub fn remove_checkpoint(session_path: &Path) {
+    let _ = std::fs::remove_file(session_path.join(CHECKPOINT_FILE_NAME));
+}
+
+/// Fill in what the files of an unfinalized session can't tell yet: lengths
+/// of files whose headers were never written, MIDI event counts of files
+/// that need repair, and where video starts
+fn apply_checkpoint(
+    checkpoint: &RecordingCheckpoint,
+    audio_files: &mut [AudioFileInfo],
+    midi_files: &mut [MidiFileInfo],
+    video_files: &mut [VideoFileInfo],
+    has_offsets: bool,
+) {
+    let progress = |list: &[FileProgress], filename: &str| {
+        list.iter().find(|p| p.filename == filename).cloned()
+    };
+    for audio in audio_files.iter_mut().filter(|f| f.duration_secs == 0.0) {
+        if let Some(p) = progress(&checkpoint.audio, &audio.filename) {
+            audio.duration_secs = p.duration_secs;
+        }
+    }
+    for midi in midi_files.iter_mut().filter(|f| f.event_count == 0) {
+        if let Some(p) = progress(&checkpoint.midi, &midi.filename) {
+            midi.event_count = p.event_count;
+        }
+    }
+    for video in video_files.iter_mut() {
+        if video.duration_secs == 0.0 {
+            if let Some(p) = progress(&checkpoint.video, &video.filename) {
+                video.duration_secs = p.duration_secs;
+            }
+        }
+        if !has_offsets {
+            video.start_offset_secs = checkpoint.video_start_offset_secs;
+        }
+    }
+}
+
+// ============================================================================
+// Audio sample rates
+// ============================================================================
+
+pub const AUDIO_RATES_FILE_NAME: &str = "audio_rates.json";
+
+pub fn read_audio_rates(session_path: &Path) -> Vec<AudioRate> {
+    read_list_file(session_path, AUDIO_RATES_FILE_NAME)
+}
+
+/// Save the capture and file rate of each audio file
+pub fn write_audio_rates(session_path: &Path, rates: &[AudioRate]) -> anyhow::Result<()> {
+    if rates.is_empty() {
+        return Ok(());
+    }
+    write_side_file(session_path, AUDIO_RATES_FILE_NAME, rates, true)
+}
+
+// ============================================================================
+// Quality report
+// ============================================================================
+
+pub const QUALITY_FILE_NAME: &str = "quality.json";
+
+pub fn read_quality(session_path: &Path) -> Option<SessionQuality> {
+    read_object_file(session_path, QUALITY_FILE_NAME)
+}
 
-    if data_chunk_offset == 0 || channels == 0 || sample_rate == 0 || bits_per_sample == 0 {
+/// Save the recording-quality report
+pub fn write_quality(session_path: &Path, quality: &SessionQuality) -> anyhow::Result<()> {
+    write_side_file(session_path, QUALITY_FILE_NAME, quality, true)
+}
+
+// ============================================================================
+// Video color
+// ============================================================================
+
+pub const VIDEO_COLOR_FILE_NAME: &str = "video_color.json";
+
+/// Color info per video filename
+pub fn read_video_color(session_path: &Path) -> HashMap<String, VideoColorInfo> {
+    read_map_file(session_path, VIDEO_COLOR_FILE_NAME)
+}
+
+/// Record the color format of each encoded file (nothing is written when no
+/// file carries color info)
+pub fn write_video_color(session_path: &Path, video_files: &[VideoFileInfo]) -> anyhow::Result<()> {
+    let colors: HashMap<&str, &VideoColorInfo> = video_files
+        .iter()
+        .filter_map(|v| v.color.as_ref().map(|c| (v.filename.as_str(), c)))
+        .collect();
+    if colors.is_empty() {
+        return Ok(());
+    }
+    write_side_file(session_path, VIDEO_COLOR_FILE_NAME, &colors, false)
+}
+
+// ============================================================================
+// Video stream info
+// ============================================================================
+
+pub const VIDEO_STREAMS_FILE_NAME: &str = "video_streams.json";
+
+/// Stream info per video filename
+pub fn read_video_streams(session_path: &Path) -> HashMap<String, VideoStreamInfo> {
+    read_map_file(session_path, VIDEO_STREAMS_FILE_NAME)
+}
+
+/// Record the codec, format and encoder of each video file
+pub fn write_video_streams(session_path: &Path, video_files: &[VideoFileInfo]) -> anyhow::Result<()> {
+    let streams: HashMap<&str, &VideoStreamInfo> = video_files
+        .iter()
+        .filter_map(|v| v.stream.as_ref().map(|s| (v.filename.as_str(), s)))
+        .collect();
+    if streams.is_empty() {
+        return Ok(());
+    }
+    write_side_file(session_path, VIDEO_STREAMS_FILE_NAME, &streams, false)
+}
+
+// ============================================================================
+// Video start offsets
+// ============================================================================
+
+pub const VIDEO_OFFSETS_FILE_NAME: &str = "video_offsets.json";
+
+/// Session-timeline start per video filename (files not listed start at 0)
+pub fn read_video_offsets(session_path: &Path) -> HashMap<String, f64> {
+    read_map_file(session_path, VIDEO_OFFSETS_FILE_NAME)
+}
+
+/// Save the start offsets of video files that don't start with the session
+/// (nothing is written when they all do)
+pub fn write_video_offsets(session_path: &Path, video_files: &[VideoFileInfo]) -> anyhow::Result<()> {
+    let offsets: HashMap<&str, f64> = video_files
+        .iter()
+        .filter(|v| v.start_offset_secs > 0.0)
+        .map(|v| (v.filename.as_str(), v.start_offset_secs))
+        .collect();
+    if offsets.is_empty() {
+        return Ok(());
+    }
+    write_side_file(session_path, VIDEO_OFFSETS_FILE_NAME, &offsets, false)
+}
+
+// ============================================================================
+// Timeline warnings
+// ============================================================================
+
+pub const TIMELINE_WARNINGS_FILE_NAME: &str = "timeline_warnings.json";
+
+pub fn read_timeline_warnings(session_path: &Path) -> Vec<TimelineWarning> {
+    read_list_file(session_path, TIMELINE_WARNINGS_FILE_NAME)
+}
+
+/// Save finalize-time warnings (removed when there are none)
+pub fn write_timeline_warnings(session_path: &Path, warnings: &[TimelineWarning]) -> anyhow::Result<()> {
+    let path = session_path.join(TIMELINE_WARNINGS_FILE_NAME);
+    if warnings.is_empty() {
+        if path.exists() {
+            remove_side_file(&path)?;
+        }
+        return Ok(());
+    }
+    write_side_file(session_path, TIMELINE_WARNINGS_FILE_NAME, warnings, true)
+}
+
+// ============================================================================
+// Session ownership
+// ============================================================================
+
+pub const OWNER_FILE_NAME: &str = "owner.json";
+
+/// Who recorded a session, for studio machines shared between users
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionOwner {
+    pub user: String,
+    pub hostname: String,
+}
+
+/// Name of the logged-in OS user
+pub fn current_user() -> String {
+    std::env::var("USERNAME")
+        .or_else(|_| std::env::var("USER"))
+        .unwrap_or_default()
+}
+
+/// Record the current user as the owner of a new session
+pub fn write_session_owner(session_path: &Path) -> anyhow::Result<()> {
+    let owner = SessionOwner {
+        user: current_user(),
+        hostname: sysinfo::System::host_name().unwrap_or_default(),
+    };
+    write_side_file(session_path, OWNER_FILE_NAME, &owner, true)
+}
+
+pub fn read_session_owner(session_path: &Path) -> Option<SessionOwner> {
+    read_object_file(session_path, OWNER_FILE_NAME)
+}
+
+/// Whether a session belongs to the current user. Sessions recorded before
+/// ownership was tracked (no owner file) belong to everyone.
+pub fn is_owned_by_current_user(session_path: &Path) -> bool {
+    match read_session_owner(session_path) {
+        Some(owner) => owner.user.eq_ignore_ascii_case(&current_user()),
+        None => true,
+    }
+}
+
+// ============================================================================
+// Session identity
+// ============================================================================
+
+pub const SESSION_UID_FILE_NAME: &str = "session_uid.json";
+
+/// Stable identity of a session, independent of its folder name. It travels
+/// with the folder, so a rename or move on disk is recognized as the same
+/// session rather than one deleted and one new.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionUidFile {
+    uid: String,
+}
+
+pub fn read_session_uid(session_path: &Path) -> Option<String> {
+    read_object_file::<SessionUidFile>(session_path, SESSION_UID_FILE_NAME).map(|f| f.uid)
+}
+
+/// The session's uid, assigning a new one if it has none yet. None if the
+/// folder can't be written (read-only library).
+pub fn ensure_session_uid(session_path: &Path) -> Option<String> {
+    read_session_uid(session_path).or_else(|| reset_session_uid(session_path))
+}
+
+/// Give a session a fresh uid, e.g. a folder copied from another session
+/// that still carries the original's
+pub fn reset_session_uid(session_path: &Path) -> Option<String> {
+    write_session_uid(session_path, &uuid::Uuid::new_v4().to_string())
+}
+
+/// Store a known uid in a session folder, e.g. one the index assigned
+/// before the folder had its own. Returns it if written.
+pub fn write_session_uid(session_path: &Path, uid: &str) -> Option<String> {
+    match write_side_file(session_path, SESSION_UID_FILE_NAME, &SessionUidFile { uid: uid.to_string() }, true) {
+        Ok(()) => Some(uid.to_string()),
+        Err(e) => {
+            log::debug!("Could not assign a uid to {}: {}", session_path.display(), e);
+            None
+        }
+    }
+}
+
+// ============================================================================
+// Storage roots
+// ============================================================================
+
+/// Find the disk that contains `path` and return its available space.
+pub fn disk_free_space(path: &std::path::Path) -> u64 {
+    use sysinfo::Disks;
+    let disks = Disks::new_with_refreshed_list();
+
+    // On Windows, std::fs::canonicalize returns \\?\C:\... (UNC prefix) but
+    // sysinfo mount points are plain C:\. Strip the prefix so starts_with works.
+    let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let canonical_str = canonical.to_string_lossy();
+    let clean_path = if canonical_str.starts_with(r"\\?\") {
+        std::path::PathBuf::from(&canonical_str[4..])
+    } else {
+        canonical
+    };
+
+    // Find the disk whose mount point is the longest prefix of our path
+    let mut best_mount: Option<&std::path::Path> = None;
+    let mut best_free: u64 = 0;
+
+    for disk in disks.list() {
+        let mount = disk.mount_point();
+        if clean_path.starts_with(mount) {
+            let is_better = match best_mount {
+                None => true,
+                Some(prev) => mount.as_os_str().len() > prev.as_os_str().len(),
+            };
+            if is_better {
+                best_mount = Some(mount);
+                best_free = disk.available_space();
+            }
+        }
+    }
+    best_free
+}
+
+/// Pick the storage root a new session should be recorded into, following
+/// `config.storage_routing`. Falls back to the primary root.
+pub fn select_new_session_root(config: &crate::config::Config) -> std::path::PathBuf {
+    use crate::config::StorageRouting;
+
+    if config.additional_storage_paths.is_empty() {
+        return config.primary_storage_root();
+    }
+    // Roots that can't be created/reached (e.g. unmounted NAS) are never chosen
+    let candidates: Vec<std::path::PathBuf> = config.storage_roots()
+        .into_iter()
+        .filter(|root| std::fs::create_dir_all(root).is_ok())
+        .collect();
+
+    let chosen = match config.storage_routing {
+        StorageRouting::Primary => None,
+        StorageRouting::FirstWithFreeSpace => {
+            let min_free = config.storage_min_free_gb as u64 * 1024 * 1024 * 1024;
+            candidates.into_iter().find(|root| disk_free_space(root) >= min_free)
+        }
+        StorageRouting::MostFreeSpace => {
+            candidates.into_iter().max_by_key(|root| disk_free_space(root))
+        }
+    };
+    chosen.unwrap_or_else(|| config.primary_storage_root())
+}
+
+// ============================================================================
+// Read-only header parsing functions
+// ============================================================================
+
+/// Read WAV (or RF64) duration by parsing fmt+data chunks (read-only, no patching).
+pub fn read_wav_duration(path: &Path) -> anyhow::Result<f64> {
+    let mut file = std::fs::File::open(path)?;
+    let file_size = file.metadata()?.len();
+
+    if file_size < 44 {
+        return Err(anyhow::anyhow!("File too small to be a valid WAV file"));
+    }
+
+    let layout = crate::recording::wav::read_layout(&mut file)?;
+    if layout.sample_rate == 0 || layout.bits_per_sample == 0 {
         return Err(anyhow::anyhow!("Could not find fmt/data chunks"));
     }
 
-    let data_size = (file_size - data_chunk_offset - 8) as f64;
-    let bytes_per_frame = (bits_per_sample as f64 / 8.0) * channels as f64;
-    Ok(data_size / (sample_rate as f64 * bytes_per_frame))
+    // Stored sizes may be placeholders after a crash; the file length isn't
+    let data_size = (file_size - layout.data_offset - 8) as f64;
+    Ok(data_size / (layout.sample_rate as f64 * layout.block_align() as f64))
 }
 
 /// Read FLAC duration by parsing STREAMINFO block.
@@ -346,6 +1020,54 @@ pub fn read_video_duration_with_discoverer(
     Ok(duration.nseconds() as f64 / 1_000_000_000.0)
 }
 
+/// Recover a video file's stream description from the file itself, for
+/// sessions that lost `video_streams.json`. Encoder and preset can't be
+/// recovered and are left empty.
+pub fn probe_video_stream(
+    path: &Path,
+    discoverer: &gstreamer_pbutils::Discoverer,
+) -> anyhow::Result<VideoStreamInfo> {
+    let uri = format!("file:///{}", path.to_string_lossy().replace('\\', "/"));
+    let info = discoverer.discover_uri(&uri)
+        .map_err(|e| anyhow::anyhow!("Discovery failed: {}", e))?;
+    let stream = info.video_streams().into_iter().next()
+        .ok_or_else(|| anyhow::anyhow!("No video stream"))?;
+
+    let caps = stream.caps();
+    let structure = caps.as_ref().and_then(|caps| caps.structure(0));
+    let caps_name = structure.map(|s| s.name().to_string()).unwrap_or_default();
+    // Parsers report the luma bit depth for AV1/H.264/VP9; anything else is 8-bit
+    let bit_depth = structure
+        .and_then(|s| s.get::<u32>("bit-depth-luma").ok())
+        .map_or(8, |depth| depth as u8);
+    let codec = crate::encoding::VideoCodec::from_gst_caps_name(&caps_name)
+        .map(|c| c.display_name().to_string())
+        .unwrap_or(caps_name);
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default();
+    let container = crate::encoding::ContainerFormat::ALL
+        .iter()
+        .find(|c| c.extension().eq_ignore_ascii_case(extension))
+        .map(|c| c.display_name().to_string())
+        .unwrap_or_else(|| extension.to_uppercase());
+    let framerate = stream.framerate();
+    let fps = if framerate.denom() > 0 {
+        framerate.numer() as f64 / framerate.denom() as f64
+    } else {
+        0.0
+    };
+
+    Ok(VideoStreamInfo {
+        codec,
+        container,
+        width: stream.width(),
+        height: stream.height(),
+        fps,
+        bit_depth,
+        encoder: None,
+        preset: None,
+    })
+}
+
 /// Create a GStreamer Discoverer, ensuring gstreamer::init() has been called.
 pub fn get_or_create_discoverer() -> anyhow::Result<gstreamer_pbutils::Discoverer> {
     gstreamer::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
@@ -377,6 +1099,31 @@ pub fn count_midi_events(path: &Path) -> anyhow::Result<usize> {
     Ok(count)
 }
 
+/// Bank/program selections at tick 0 of a MIDI file (as written at recording start)
+pub fn read_midi_start_programs(path: &Path) -> Vec<super::ChannelProgram> {
+    let Ok(data) = std::fs::read(path) else { return Vec::new() };
+    let Ok(smf) = midly::Smf::parse(&data) else { return Vec::new() };
+
+    let mut tracker = crate::recording::midi::MidiProgramTracker::default();
+    for track in &smf.tracks {
+        for event in track.iter().take_while(|e| e.delta.as_int() == 0) {
+            if let midly::TrackEventKind::Midi { channel, message } = event.kind {
+                let status = channel.as_int();
+                match message {
+                    midly::MidiMessage::Controller { controller, value } => {
+                        tracker.observe(&[0xB0 | status, controller.as_int(), value.as_int()]);
+                    }
+                    midly::MidiMessage::ProgramChange { program } => {
+                        tracker.observe(&[0xC0 | status, program.as_int()]);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    tracker.snapshot()
+}
+
 // ============================================================================
 // Lightweight scan for session index (rescan_sessions)
 // ============================================================================
@@ -413,6 +1160,12 @@ pub fn scan_session_dir_for_index(
     let mut any_duration_failed = false;
     let mut notes = String::new();
     let mut notes_modified_at = String::new();
+    let mut archived = false;
+    let mut favorite = false;
+    let mut tags = Vec::new();
+    let mut size_bytes = 0u64;
+    let mut uid = None;
+    let mut custom_fields = CustomFields::new();
 
     // Lazy-init a fallback discoverer only if needed and none was provided
     let mut fallback_discoverer: Option<gstreamer_pbutils::Discoverer> = None;
@@ -423,6 +1176,7 @@ pub fn scan_session_dir_for_index(
             Some(n) => n.to_string(),
             None => continue,
         };
+        size_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
 
         if fname == "notes.txt" {
             notes = std::fs::read_to_string(&path).unwrap_or_default();
@@ -435,6 +1189,31 @@ pub fn scan_session_dir_for_index(
             continue;
         }
 
+        if fname == super::archive::ARCHIVE_MANIFEST_NAME {
+            archived = true;
+            continue;
+        }
+
+        if fname == FAVORITE_MARKER_NAME {
+            favorite = true;
+            continue;
+        }
+
+        if fname == TAGS_FILE_NAME {
+            tags = read_session_tags(session_path);
+            continue;
+        }
+
+        if fname == SESSION_UID_FILE_NAME {
+            uid = read_session_uid(session_path);
+            continue;
+        }
+
+        if fname == CUSTOM_FIELDS_FILE_NAME {
+            custom_fields = read_custom_fields(session_path);
+            continue;
+        }
+
         if fname.ends_with(".mid") {
             has_midi = true;
         } else if fname.ends_with(".wav") {
@@ -488,6 +1267,9 @@ pub fn scan_session_dir_for_index(
         durations.into_iter().fold(0.0f64, f64::max)
     };
 
+    let mut video_streams: Vec<(String, VideoStreamInfo)> = read_video_streams(session_path).into_iter().collect();
+    video_streams.sort_by(|a, b| a.0.cmp(&b.0));
+
     // If folder name doesn't match the expected timestamp format, use the full
     // folder name as the title (non-standard folder — title is not editable).
     let title = if parse_session_timestamp(&folder_name).is_some() {
@@ -498,6 +1280,7 @@ pub fn scan_session_dir_for_index(
 
     Ok(SessionIndexData {
         id: folder_name,
+        uid,
         timestamp,
         path: session_path.to_string_lossy().to_string(),
         duration_secs,
@@ -507,9 +1290,29 @@ pub fn scan_session_dir_for_index(
         notes,
         notes_modified_at,
         title,
+        archived,
+        favorite,
+        tags,
+        custom_fields,
+        size_bytes,
+        video_streams: video_streams.into_iter().map(|(_, stream)| stream).collect(),
     })
 }
 
+/// Total size of the files directly inside a session folder
+pub fn session_size_bytes(session_path: &Path) -> u64 {
+    std::fs::read_dir(session_path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| entry.metadata().ok())
+                .filter(|meta| meta.is_file())
+                .map(|meta| meta.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
 // ============================================================================
 // Directory scan → SessionMetadata
 // ============================================================================
@@ -518,12 +1321,18 @@ pub fn scan_session_dir_for_index(
 /// Supports formats:
 ///   "2026-02-25_17-46-00 PST"           → local time with timezone abbreviation
 ///   "2026-02-25_17-46-00 PST - My Song" → with title
+///   "2026-02-25_17-46-00 PST-2"         → second take started that second
 ///   "2026-02-25_01-46-00"               → no timezone → assume UTC (legacy)
 pub fn parse_session_timestamp(folder_name: &str) -> Option<DateTime<Utc>> {
     let timestamp_part = folder_name.split(" - ").next().unwrap_or(folder_name);
 
     // Try splitting off a timezone suffix: "2026-02-25_17-46-00 PST" → ("2026-02-25_17-46-00", "PST")
     if let Some((datetime_str, tz_str)) = timestamp_part.rsplit_once(' ') {
+        // Takes started in the same second get a collision suffix ("PST-2")
+        let tz_str = match tz_str.rsplit_once('-') {
+            Some((tz, n)) if !tz.is_empty() && n.parse::<u32>().is_ok() => tz,
+            _ => tz_str,
+        };
         let naive = NaiveDateTime::parse_from_str(datetime_str, "%Y-%m-%d_%H-%M-%S").ok()?;
         if let Some(offset) = tz_abbr_to_offset(tz_str) {
             // Known timezone — interpret as local time in that timezone
@@ -746,6 +1555,7 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
     let mut audio_files = Vec::new();
     let mut midi_files = Vec::new();
     let mut video_files = Vec::new();
+    let mut proxy_files = Vec::new();
     let mut notes = String::new();
 
     for entry in entries.flatten() {
@@ -760,7 +1570,18 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
             continue;
         }
 
-        if fname == LOCK_FILE_NAME {
+        if fname == LOCK_FILE_NAME
+            || fname == super::archive::ARCHIVE_MANIFEST_NAME
+            || fname == LOOPS_FILE_NAME
+            || fname == CHAPTERS_FILE_NAME
+            || fname == TIMELINE_WARNINGS_FILE_NAME
+            || fname == CHECKPOINT_FILE_NAME
+        {
+            continue;
+        }
+
+        if crate::video::proxy::is_proxy_file(&fname) {
+            proxy_files.push((fname, path));
             continue;
         }
 
@@ -771,10 +1592,10 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
 
             let needs_repair = crate::recording::monitor::midi_file_needs_repair(&path.to_path_buf());
 
-            let event_count = if !needs_repair {
-                count_midi_events(&path).unwrap_or(0)
+            let (event_count, programs) = if !needs_repair {
+                (count_midi_events(&path).unwrap_or(0), read_midi_start_programs(&path))
             } else {
-                0
+                (0, Vec::new())
             };
 
             midi_files.push(MidiFileInfo {
@@ -782,6 +1603,8 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
                 device_name,
                 event_count,
                 needs_repair,
+                programs,
+                start_offset_secs: 0.0,
             });
         } else if fname.ends_with(".wav") {
             let sanitized = fname.trim_start_matches("audio_").trim_end_matches(".wav");
@@ -792,6 +1615,7 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
                 filename: fname,
                 device_name,
                 duration_secs,
+                start_offset_secs: 0.0,
             });
         } else if fname.ends_with(".flac") {
             let sanitized = fname.trim_start_matches("audio_").trim_end_matches(".flac");
@@ -802,6 +1626,7 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
                 filename: fname,
                 device_name,
                 duration_secs,
+                start_offset_secs: 0.0,
             });
         } else if crate::encoding::is_video_extension(&fname) {
             let sanitized = crate::encoding::strip_video_extension(
@@ -820,13 +1645,61 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
                 filename: fname,
                 device_name,
                 duration_secs,
+                proxy_filename: None,
+                color: None,
+                start_offset_secs: 0.0,
+                stream: None,
             });
         }
     }
 
-    // Compute session duration = max of all file durations
-    let max_audio = audio_files.iter().map(|f| f.duration_secs).fold(0.0f64, f64::max);
-    let max_video = video_files.iter().map(|f| f.duration_secs).fold(0.0f64, f64::max);
+    // Attach proxies to their originals. A proxy without an original stands
+    // in for it (archived sessions).
+    for (fname, path) in proxy_files {
+        let stem = crate::video::proxy::original_stem(&fname);
+        let original = video_files.iter_mut()
+            .find(|v| crate::encoding::strip_video_extension(&v.filename) == stem);
+        if let Some(original) = original {
+            original.proxy_filename = Some(fname);
+            continue;
+        }
+        video_files.push(VideoFileInfo {
+            device_name: unsanitize_device_name(stem.trim_start_matches("video_")),
+            duration_secs: read_video_duration(&path).unwrap_or(0.0),
+            filename: fname,
+            proxy_filename: None,
+            color: None,
+            start_offset_secs: 0.0,
+            stream: None,
+        });
+    }
+
+    let colors = read_video_color(session_path);
+    let offsets = read_video_offsets(session_path);
+    let streams = read_video_streams(session_path);
+    for video in video_files.iter_mut() {
+        video.color = colors.get(&video.filename).cloned();
+        video.stream = streams.get(&video.filename).cloned();
+        video.start_offset_secs = offsets.get(&video.filename).copied().unwrap_or(0.0);
+    }
+
+    let sync_file = read_object_file::<SyncFile>(session_path, SYNC_FILE_NAME);
+    if let Some(sync_file) = &sync_file {
+        for audio in audio_files.iter_mut() {
+            audio.start_offset_secs = sync_file.file_offsets.get(&audio.filename).copied().unwrap_or(0.0);
+        }
+        for midi in midi_files.iter_mut() {
+            midi.start_offset_secs = sync_file.file_offsets.get(&midi.filename).copied().unwrap_or(0.0);
+        }
+    }
+
+    if let Some(checkpoint) = read_checkpoint(session_path) {
+        apply_checkpoint(&checkpoint, &mut audio_files, &mut midi_files, &mut video_files, !offsets.is_empty());
+    }
+
+    // Compute session duration = max of all file end times
+    let max_audio = audio_files.iter().map(|f| f.start_offset_secs + f.duration_secs).fold(0.0f64, f64::max);
+    let max_video = video_files.iter().map(|f| f.start_offset_secs + f.duration_secs).fold(0.0f64, f64::max);
     let duration_secs = max_audio.max(max_video);
 
     // If folder name doesn't match the expected timestamp format, use the full
@@ -846,8 +1719,16 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
         .map(|l| l.hostname == sysinfo::System::host_name().unwrap_or_default())
         .unwrap_or(false);
 
+    // Don't touch side files while another process is still writing them
+    let schema_version = if recording_in_progress {
+        super::metadata::read_schema_version(session_path)
+    } else {
+        migrate_session(session_path, false)
+    };
+
     Ok(SessionMetadata {
         id: folder_name,
+        uid: read_session_uid(session_path),
         timestamp,
         duration_secs,
         path: session_path.to_path_buf(),
@@ -859,5 +1740,21 @@ pub fn build_session_from_directory(session_path: &Path) -> anyhow::Result<Sessi
         recording_in_progress,
         recording_lock_updated_at,
         recording_lock_is_local,
+        archived: super::archive::is_archived(session_path),
+        favorite: is_favorite(session_path),
+        tags: read_session_tags(session_path),
+        custom_fields: read_custom_fields(session_path),
+        loops: read_session_loops(session_path),
+        chapters: read_session_chapters(session_path),
+        timeline_warnings: read_timeline_warnings(session_path),
+        owner: read_session_owner(session_path).map(|o| o.user),
+        video_fallbacks: read_video_fallbacks(session_path),
+        frame_rate_reports: read_frame_rate_reports(session_path),
+        encoding_adaptations: read_encoding_adaptations(session_path),
+        startup_latency: read_startup_latency(session_path),
+        audio_rates: read_audio_rates(session_path),
+        sync: sync_file.map(|f| f.sync),
+        quality: read_quality(session_path),
+        schema_version,
     })
 }
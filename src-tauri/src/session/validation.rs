@@ -0,0 +1,190 @@
+// Timeline consistency check run after a recording is finalized
+//
+// Compares each file's declared duration against the session's, looks for
+// MIDI that runs past the end, and scans video packet timestamps (parsed, not
+// decoded) for gaps, overlaps and late starts. Findings are stored next to
+// the session in timeline_warnings.json so desync shows up in review right
+// away instead of being noticed months later.
+
+use std::path::Path;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+
+use super::{SessionMetadata, TimelineWarning, TimelineWarningKind};
+
+/// Duration differences smaller than this are normal start/stop jitter
+pub const DURATION_TOLERANCE_SECS: f64 = 1.0;
+
+/// A gap must be at least this long to be reported...
+const MIN_GAP_SECS: f64 = 0.25;
+
+/// ...and this many times the typical frame interval
+const GAP_INTERVAL_FACTOR: f64 = 4.0;
+
+/// Number of gap/overlap warnings kept per file
+const MAX_WARNINGS_PER_FILE: usize = 10;
+
+/// Check a finalized session's files against each other
+pub fn validate_timeline(session: &SessionMetadata) -> Vec<TimelineWarning> {
+    let mut warnings = Vec::new();
+    let duration = session.duration_secs;
+
+    for file in &session.audio_files {
+        if session.path.join(&file.filename).exists() {
+            check_duration(&mut warnings, &file.filename, file.duration_secs, duration - file.start_offset_secs);
+        }
+    }
+
+    for file in &session.video_files {
+        let path = session.path.join(&file.filename);
+        if !path.exists() {
+            continue;
+        }
+        // A video that starts into the session only has to reach its end
+        check_duration(&mut warnings, &file.filename, file.duration_secs, duration - file.start_offset_secs);
+        match read_video_timestamps(&path) {
+            Ok(timestamps) => check_video_timestamps(&mut warnings, &file.filename, timestamps),
+            Err(e) => log::warn!("Timeline check: failed to read {}: {}", file.filename, e),
+        }
+    }
+
+    for file in &session.midi_files {
+        let path = session.path.join(&file.filename);
+        let Ok(parsed) = crate::similarity::midi_parser::parse_midi(&path) else { continue };
+        let last_tick = parsed.events.iter().map(|n| n.start_tick + n.duration_ticks).max();
+        if let Some(tick) = last_tick {
+            let end = crate::similarity::midi_parser::tick_to_seconds(tick, parsed.ticks_per_beat, &parsed.tempo_map);
+            if end > duration + DURATION_TOLERANCE_SECS {
+                warnings.push(TimelineWarning {
+                    kind: TimelineWarningKind::DurationMismatch,
+                    filename: file.filename.clone(),
+                    at_secs: Some(duration),
+                    message: format!("MIDI runs {:.1}s past the end of the session", end - duration),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+fn check_duration(warnings: &mut Vec<TimelineWarning>, filename: &str, file_secs: f64, session_secs: f64) {
+    if file_secs > 0.0 && (file_secs - session_secs).abs() > DURATION_TOLERANCE_SECS {
+        let (amount, direction) = if file_secs < session_secs {
+            (session_secs - file_secs, "shorter")
+        } else {
+            (file_secs - session_secs, "longer")
+        };
+        warnings.push(TimelineWarning {
+            kind: TimelineWarningKind::DurationMismatch,
+            filename: filename.to_string(),
+            at_secs: None,
+            message: format!("{:.1}s {} than the session ({:.1}s)", amount, direction, session_secs),
+        });
+    }
+}
+
+fn check_video_timestamps(warnings: &mut Vec<TimelineWarning>, filename: &str, mut timestamps: Vec<f64>) {
+    if timestamps.len() < 2 {
+        return;
+    }
+    // Presentation order; encoders with B-frames emit packets out of order
+    timestamps.sort_by(|a, b| a.total_cmp(b));
+
+    if timestamps[0] > MIN_GAP_SECS {
+        warnings.push(TimelineWarning {
+            kind: TimelineWarningKind::LateStart,
+            filename: filename.to_string(),
+            at_secs: Some(0.0),
+            message: format!("First frame is {:.2}s after the start", timestamps[0]),
+        });
+    }
+
+    let intervals: Vec<f64> = timestamps.windows(2).map(|w| w[1] - w[0]).collect();
+    let typical = {
+        let mut sorted = intervals.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        sorted[sorted.len() / 2]
+    };
+    let gap_threshold = (typical * GAP_INTERVAL_FACTOR).max(MIN_GAP_SECS);
+
+    let mut reported = 0;
+    for (i, &interval) in intervals.iter().enumerate() {
+        if reported >= MAX_WARNINGS_PER_FILE {
+            break;
+        }
+        let at = timestamps[i];
+        if interval <= 0.0 {
+            warnings.push(TimelineWarning {
+                kind: TimelineWarningKind::Overlap,
+                filename: filename.to_string(),
+                at_secs: Some(at),
+                message: format!("Duplicate frame timestamp at {:.2}s", at),
+            });
+            reported += 1;
+        } else if interval > gap_threshold {
+            warnings.push(TimelineWarning {
+                kind: TimelineWarningKind::Gap,
+                filename: filename.to_string(),
+                at_secs: Some(at),
+                message: format!("{:.2}s without frames at {:.2}s", interval, at),
+            });
+            reported += 1;
+        }
+    }
+}
+
+/// Packet timestamps (seconds) of the first video stream, without decoding
+pub(crate) fn read_video_timestamps(path: &Path) -> anyhow::Result<Vec<f64>> {
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+    let pipeline = gst::Pipeline::new();
+    let filesrc = gst::ElementFactory::make("filesrc")
+        .property("location", path.to_string_lossy().to_string())
+        .build()?;
+    let parsebin = gst::ElementFactory::make("parsebin").build()?;
+    let appsink = gst_app::AppSink::builder().sync(false).build();
+
+    pipeline.add_many([&filesrc, &parsebin, appsink.upcast_ref()])?;
+    filesrc.link(&parsebin)?;
+
+    let appsink_weak = appsink.downgrade();
+    parsebin.connect_pad_added(move |_parsebin, src_pad| {
+        let Some(appsink) = appsink_weak.upgrade() else {
+            return;
+        };
+        let is_video = src_pad
+            .current_caps()
+            .unwrap_or_else(|| src_pad.query_caps(None))
+            .structure(0)
+            .map(|s| {
+                let name = s.name().as_str();
+                name.starts_with("video/") || name == "image/jpeg"
+            })
+            .unwrap_or(false);
+        if !is_video {
+            return;
+        }
+        if let Some(sink_pad) = appsink.static_pad("sink") {
+            if !sink_pad.is_linked() {
+                if let Err(e) = src_pad.link(&sink_pad) {
+                    log::warn!("Timeline check: failed to link video pad: {:?}", e);
+                }
+            }
+        }
+    });
+
+    pipeline.set_state(gst::State::Playing)?;
+
+    let mut timestamps = Vec::new();
+    while let Some(sample) = appsink.try_pull_sample(gst::ClockTime::from_seconds(5)) {
+        if let Some(pts) = sample.buffer().and_then(|b| b.pts()) {
+            timestamps.push(pts.nseconds() as f64 / 1e9);
+        }
+    }
+
+    let _ = pipeline.set_state(gst::State::Null);
+    Ok(timestamps)
+}
@@ -0,0 +1,248 @@
+// Storage folder watching — indexes sessions added or removed outside the app
+//
+// Filesystem events from every storage root are grouped by top-level session
+// folder and debounced.
+// A folder is only considered settled once no events have arrived for
+// `DEBOUNCE` and its contents (file count, total size, newest mtime) are
+// unchanged between two consecutive checks, so a session that is still being
+// copied in from another machine isn't indexed half-way through. Folders with
+// a live recording lock are deferred until the lock is removed or goes stale.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::Emitter;
+
+use super::{read_recording_lock, LOCK_FILE_NAME};
+
+/// Quiet period after the last event for a folder before it is checked
+const DEBOUNCE: Duration = Duration::from_secs(3);
+
+/// A recording lock not refreshed for this long is considered stale.
+/// The recording heartbeat refreshes the lock every 60 seconds.
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(180);
+
+/// Coarse fingerprint of a folder's contents used to detect in-progress copies
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FolderSnapshot {
+    file_count: usize,
+    total_bytes: u64,
+    newest_mtime: Option<SystemTime>,
+}
+
+impl FolderSnapshot {
+    /// Returns None if the folder no longer exists
+    fn capture(path: &Path) -> Option<Self> {
+        let entries = std::fs::read_dir(path).ok()?;
+        let mut snapshot = Self { file_count: 0, total_bytes: 0, newest_mtime: None };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else { continue };
+            if !meta.is_file() {
+                continue;
+            }
+            snapshot.file_count += 1;
+            snapshot.total_bytes += meta.len();
+            if let Ok(modified) = meta.modified() {
+                if snapshot.newest_mtime.map_or(true, |t| modified > t) {
+                    snapshot.newest_mtime = Some(modified);
+                }
+            }
+        }
+        Some(snapshot)
+    }
+}
+
+struct PendingFolder {
+    last_event: Instant,
+    snapshot: Option<FolderSnapshot>,
+}
+
+/// Watches the storage roots for as long as it is alive.
+/// Dropping it stops the watcher and its debounce thread.
+pub struct StorageWatcher {
+    _watcher: RecommendedWatcher,
+    roots: Vec<PathBuf>,
+}
+
+impl StorageWatcher {
+    /// Start watching the given storage roots. Roots that don't exist are
+    /// skipped. Returns None if no root could be watched.
+    pub fn start(app: tauri::AppHandle, roots: Vec<PathBuf>) -> Option<Self> {
+        let roots: Vec<PathBuf> = roots.into_iter().filter(|r| r.is_dir()).collect();
+        if roots.is_empty() {
+            return None;
+        }
+
+        let (tx, rx) = mpsc::channel::<PathBuf>();
+        let watched = roots.clone();
+        let mut watcher = match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let Ok(event) = res else { return };
+            if event.kind.is_access() {
+                return;
+            }
+            for path in &event.paths {
+                if let Some(folder) = watched.iter().find_map(|root| top_level_folder(root, path)) {
+                    let _ = tx.send(folder);
+                }
+            }
+        }) {
+            Ok(w) => w,
+            Err(e) => {
+                println!("[Sacho] Failed to create storage watcher: {}", e);
+                return None;
+            }
+        };
+
+        let mut watching = 0;
+        for root in &roots {
+            match watcher.watch(root, RecursiveMode::Recursive) {
+                Ok(()) => {
+                    println!("[Sacho] Watching storage folder: {}", root.display());
+                    watching += 1;
+                }
+                Err(e) => println!("[Sacho] Failed to watch storage folder {}: {}", root.display(), e),
+            }
+        }
+        if watching == 0 {
+            return None;
+        }
+
+        std::thread::Builder::new()
+            .name("sacho-storage-watch".into())
+            .spawn(move || debounce_loop(app, rx))
+            .ok()?;
+
+        Some(Self { _watcher: watcher, roots })
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+}
+
+/// Session folder directly under `root` that contains `path`
+fn top_level_folder(root: &Path, path: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(root).ok()?;
+    let first = relative.components().next()?;
+    if first.as_os_str().to_string_lossy().starts_with('.') {
+        return None;
+    }
+    Some(root.join(first))
+}
+
+/// True if the folder holds a recording lock that is still being refreshed
+fn has_live_recording_lock(folder: &Path) -> bool {
+    if !folder.join(LOCK_FILE_NAME).exists() {
+        return false;
+    }
+    let Some(lock) = read_recording_lock(folder) else {
+        // Unreadable lock (possibly mid-write): treat as live and check again later
+        return true;
+    };
+    match chrono::DateTime::parse_from_rfc3339(&lock.updated_at) {
+        Ok(updated) => {
+            let age = chrono::Utc::now().signed_duration_since(updated);
+            age.to_std().map_or(true, |age| age < LOCK_STALE_AFTER)
+        }
+        Err(_) => false,
+    }
+}
+
+fn debounce_loop(app: tauri::AppHandle, rx: mpsc::Receiver<PathBuf>) {
+    let mut pending: HashMap<PathBuf, PendingFolder> = HashMap::new();
+
+    loop {
+        match rx.recv_timeout(Duration::from_secs(1)) {
+            Ok(folder) => {
+                let entry = pending.entry(folder).or_insert(PendingFolder {
+                    last_event: Instant::now(),
+                    snapshot: None,
+                });
+                entry.last_event = Instant::now();
+                entry.snapshot = None;
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            // Watcher dropped (storage path changed or app exiting)
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if pending.is_empty() {
+            continue;
+        }
+
+        let now = Instant::now();
+        let mut all_settled = true;
+        for (path, folder) in pending.iter_mut() {
+            if now.duration_since(folder.last_event) < DEBOUNCE {
+                all_settled = false;
+                continue;
+            }
+            if has_live_recording_lock(path) {
+                folder.last_event = now;
+                all_settled = false;
+                continue;
+            }
+            let snapshot = FolderSnapshot::capture(path);
+            if snapshot.is_none() {
+                // Folder removed: settled
+                continue;
+            }
+            if folder.snapshot != snapshot {
+                // First check, or still changing: compare again after another quiet period
+                folder.snapshot = snapshot;
+                folder.last_event = now;
+                all_settled = false;
+            }
+        }
+
+        if !all_settled {
+            continue;
+        }
+
+        let folder_count = pending.len();
+        pending.clear();
+        log::info!("Storage folder changed externally ({} folders), re-indexing", folder_count);
+
+        match crate::commands::rescan_sessions_blocking(&app, false) {
+            Ok(report) => {
+                let changed = report.indexed_count() + report.missing_sessions.len();
+                if changed > 0 {
+                    if let Err(e) = crate::commands::sync_session_features(&app) {
+                        log::error!("Failed to sync session features after storage change: {}", e);
+                    }
+                    let _ = app.emit("storage-changed", &report);
+                }
+            }
+            Err(e) => log::error!("Failed to re-index after storage change: {}", e),
+        }
+    }
+}
+
+/// Managed state holding the active watcher, if any
+pub type StorageWatcherState = parking_lot::Mutex<Option<StorageWatcher>>;
+
+/// (Re)start or stop the storage watcher to match the current config
+pub fn apply_storage_watch_config(app: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    let (enabled, roots) = {
+        let config = app.state::<parking_lot::RwLock<crate::config::Config>>();
+        let config = config.read();
+        (config.watch_storage, config.storage_roots())
+    };
+
+    let Some(state) = app.try_state::<StorageWatcherState>() else { return };
+    let mut current = state.lock();
+    if enabled && current.as_ref().map(|w| w.roots()) == Some(roots.as_slice()) {
+        return;
+    }
+    // Drop the old watcher before starting a new one
+    *current = None;
+    if enabled {
+        *current = StorageWatcher::start(app.clone(), roots);
+    }
+}
@@ -0,0 +1,780 @@
+// LAN session sharing — read-only session pages served over HTTP
+//
+// `create_share_link` issues a random token for one session and starts a
+// small HTTP server on the LAN interface. Anyone with the link can open a
+// page for that session that plays a phone-friendly proxy of each video and
+// an MP3 of each audio track, and offers the MIDI files for download. Nothing
+// else on disk is reachable: only files belonging to a shared session are
+// served, and only while its link is valid.
+//
+// Phones can't play the recorded MKV/FFV1/FLAC files, so creating a link
+// prepares playable copies in the background: video proxies are generated
+// next to the originals (the same `.proxy.mp4` files review uses) and MP3s
+// go to the app cache. The page reloads itself until they're ready.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::config::Config;
+use crate::remote::{lan_address, ConnectionLimit};
+
+/// Poll interval of the non-blocking accept loop (bounds how long stopping takes)
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Longest time a client may take to send its request headers, in total
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Longest request or header line accepted
+const MAX_LINE_BYTES: u64 = 8192;
+
+/// Most headers accepted in one request
+const MAX_HEADERS: usize = 64;
+
+/// Requests served at once; a page load plus a few media range requests
+/// per listener fits well within it
+const MAX_CONNECTIONS: usize = 16;
+
+/// MP3 bitrate for shared audio (kbps)
+const MP3_BITRATE_KBPS: i32 = 192;
+
+/// A shared session
+#[derive(Debug, Clone)]
+struct ShareLink {
+    session_id: String,
+    title: String,
+    session_path: PathBuf,
+    /// Where MP3 copies of the session's audio are written
+    cache_dir: PathBuf,
+    expires_at: DateTime<Utc>,
+    /// Set while proxies/MP3s are still being generated
+    preparing: Arc<AtomicBool>,
+}
+
+/// Link details returned to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct ShareLinkInfo {
+    pub token: String,
+    pub url: String,
+    pub session_id: String,
+    /// RFC 3339
+    pub expires_at: String,
+}
+
+struct ShareServer {
+    port: u16,
+    stop: Arc<AtomicBool>,
+}
+
+type LinkMap = Arc<RwLock<HashMap<String, ShareLink>>>;
+
+/// Managed state: the running server (if any) and the active links
+pub struct ShareState {
+    server: Mutex<Option<ShareServer>>,
+    links: LinkMap,
+}
+
+impl Default for ShareState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShareState {
+    pub fn new() -> Self {
+        Self {
+            server: Mutex::new(None),
+            links: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Create a link for a session, starting the server if needed
+    pub fn create_link(&self, app: &AppHandle, session_id: &str) -> Result<ShareLinkInfo, String> {
+        let config = app.state::<RwLock<Config>>().read().clone();
+        if !config.lan_share_enabled {
+            return Err("LAN sharing is turned off in Settings".to_string());
+        }
+
+        let session_path = config.session_path(session_id);
+        if !session_path.is_dir() {
+            return Err(format!("Session not found: {}", session_id));
+        }
+
+        let port = self.ensure_server(config.lan_share_port)?;
+        let host = lan_address().ok_or("No network connection found to share on")?;
+
+        let cache_dir = app
+            .path()
+            .app_cache_dir()
+            .map_err(|e| e.to_string())?
+            .join("share")
+            .join(session_id);
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let link = ShareLink {
+            session_id: session_id.to_string(),
+            title: crate::session::extract_title_from_folder_name(session_id)
+                .unwrap_or_else(|| session_id.to_string()),
+            session_path,
+            cache_dir,
+            expires_at: Utc::now() + chrono::Duration::hours(config.share_link_expiry_hours as i64),
+            preparing: Arc::new(AtomicBool::new(true)),
+        };
+
+        {
+            let link = link.clone();
+            std::thread::Builder::new()
+                .name("sacho-share-prepare".into())
+                .spawn(move || {
+                    prepare_media(&link);
+                    link.preparing.store(false, Ordering::SeqCst);
+                })
+                .map_err(|e| format!("Failed to spawn share preparation thread: {}", e))?;
+        }
+
+        let info = ShareLinkInfo {
+            url: format!("http://{}:{}/s/{}", host, port, token),
+            token: token.clone(),
+            session_id: link.session_id.clone(),
+            expires_at: link.expires_at.to_rfc3339(),
+        };
+        self.links.write().insert(token, link);
+        println!("[Share] Created link for {} ({})", session_id, info.url);
+        Ok(info)
+    }
+
+    /// Follow a session whose folder was renamed or moved: links keep working
+    /// and the MP3 cache moves with them
+    pub fn session_moved(&self, app: &AppHandle, old_id: &str, new_id: &str, new_path: &Path) {
+        let Ok(share_dir) = app.path().app_cache_dir().map(|dir| dir.join("share")) else {
+            return;
+        };
+        let old_cache = share_dir.join(old_id);
+        let new_cache = share_dir.join(new_id);
+        if old_id != new_id && old_cache.exists() {
+            let _ = std::fs::remove_dir_all(&new_cache);
+            if let Err(e) = std::fs::rename(&old_cache, &new_cache) {
+                println!("[Share] Failed to move cache for {}: {}", old_id, e);
+            }
+        }
+        for link in self.links.write().values_mut().filter(|link| link.session_id == old_id) {
+            link.session_id = new_id.to_string();
+            link.title = crate::session::extract_title_from_folder_name(new_id)
+                .unwrap_or_else(|| new_id.to_string());
+            link.session_path = new_path.to_path_buf();
+            link.cache_dir = new_cache.clone();
+        }
+    }
+
+    /// Revoke one link. Returns false if it didn't exist.
+    pub fn revoke_link(&self, token: &str) -> bool {
+        self.links.write().remove(token).is_some()
+    }
+
+    /// Active (unexpired) links
+    pub fn list_links(&self) -> Vec<ShareLinkInfo> {
+        let port = self.server.lock().as_ref().map(|s| s.port);
+        let (Some(port), Some(host)) = (port, lan_address()) else {
+            return Vec::new();
+        };
+        let now = Utc::now();
+        self.links
+            .read()
+            .iter()
+            .filter(|(_, link)| link.expires_at > now)
+            .map(|(token, link)| ShareLinkInfo {
+                url: format!("http://{}:{}/s/{}", host, port, token),
+                token: token.clone(),
+                session_id: link.session_id.clone(),
+                expires_at: link.expires_at.to_rfc3339(),
+            })
+            .collect()
+    }
+
+    /// Stop the server and revoke every link
+    pub fn stop(&self) {
+        if let Some(server) = self.server.lock().take() {
+            server.stop.store(true, Ordering::SeqCst);
+            println!("[Share] Server stopped");
+        }
+        self.links.write().clear();
+    }
+
+    fn ensure_server(&self, configured_port: u16) -> Result<u16, String> {
+        let mut server = self.server.lock();
+        if let Some(server) = server.as_ref() {
+            return Ok(server.port);
+        }
+
+        let listener = TcpListener::bind(("0.0.0.0", configured_port))
+            .map_err(|e| format!("Failed to listen on port {}: {}", configured_port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        {
+            let stop = stop.clone();
+            let links = self.links.clone();
+            std::thread::Builder::new()
+                .name("sacho-share".into())
+                .spawn(move || accept_loop(listener, links, stop))
+                .map_err(|e| format!("Failed to spawn share server thread: {}", e))?;
+        }
+
+        println!("[Share] Server listening on port {}", port);
+        *server = Some(ShareServer { port, stop });
+        Ok(port)
+    }
+}
+
+// ============================================================================
+// Media preparation
+// ============================================================================
+
+fn prepare_media(link: &ShareLink) {
+    let Ok(entries) = std::fs::read_dir(&link.session_path) else { return };
+    let files: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+
+    for path in &files {
+        let Some(fname) = path.file_name().and_then(|n| n.to_str()) else { continue };
+        if crate::encoding::is_video_extension(fname)
+            && !crate::video::proxy::is_proxy_file(fname)
+            && crate::video::proxy::find_proxy(path).is_none()
+        {
+            if let Err(e) = crate::video::proxy::generate_proxy(path, crate::video::proxy::DEFAULT_PROXY_HEIGHT) {
+                println!("[Share] Failed to prepare {}: {}", fname, e);
+            }
+        } else if fname.ends_with(".wav") || fname.ends_with(".flac") {
+            let mp3 = link.cache_dir.join(mp3_name(fname));
+            if mp3.exists() {
+                continue;
+            }
+            if let Err(e) = std::fs::create_dir_all(&link.cache_dir)
+                .map_err(anyhow::Error::from)
+                .and_then(|_| transcode_to_mp3(path, &mp3))
+            {
+                println!("[Share] Failed to prepare {}: {}", fname, e);
+            }
+        }
+    }
+}
+
+fn mp3_name(audio_fname: &str) -> String {
+    let stem = Path::new(audio_fname).file_stem().and_then(|s| s.to_str()).unwrap_or(audio_fname);
+    format!("{}.mp3", stem)
+}
+
+/// Transcode an audio file to MP3 (temp file, then rename)
+fn transcode_to_mp3(source: &Path, dest: &Path) -> anyhow::Result<()> {
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+    let temp = dest.with_extension("mp3.tmp");
+
+    let make = |name: &str| {
+        gst::ElementFactory::make(name)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", name, e))
+    };
+    let pipeline = gst::Pipeline::new();
+    let filesrc = gst::ElementFactory::make("filesrc")
+        .property("location", source.to_string_lossy().to_string())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create filesrc: {}", e))?;
+    let decodebin = make("decodebin")?;
+    let audioconvert = make("audioconvert")?;
+    let audioresample = make("audioresample")?;
+    let encoder = make("lamemp3enc")?;
+    encoder.set_property_from_str("target", "bitrate");
+    encoder.set_property("bitrate", MP3_BITRATE_KBPS);
+    let filesink = gst::ElementFactory::make("filesink")
+        .property("location", temp.to_string_lossy().to_string())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create filesink: {}", e))?;
+
+    pipeline
+        .add_many([&filesrc, &decodebin, &audioconvert, &audioresample, &encoder, &filesink])
+        .map_err(|e| anyhow::anyhow!("Failed to add elements: {}", e))?;
+    filesrc.link(&decodebin)
+        .map_err(|e| anyhow::anyhow!("Failed to link filesrc -> decodebin: {}", e))?;
+    gst::Element::link_many([&audioconvert, &audioresample, &encoder, &filesink])
+        .map_err(|e| anyhow::anyhow!("Failed to link MP3 chain: {}", e))?;
+
+    let convert_weak = audioconvert.downgrade();
+    decodebin.connect_pad_added(move |_, src_pad| {
+        if let Some(sink_pad) = convert_weak.upgrade().and_then(|c| c.static_pad("sink")) {
+            if !sink_pad.is_linked() {
+                let _ = src_pad.link(&sink_pad);
+            }
+        }
+    });
+
+    pipeline.set_state(gst::State::Playing)
+        .map_err(|e| anyhow::anyhow!("Failed to start MP3 transcode: {:?}", e))?;
+    let bus = pipeline.bus().ok_or_else(|| anyhow::anyhow!("No pipeline bus for MP3 transcode"))?;
+    let mut result = Err(anyhow::anyhow!("MP3 transcode timed out"));
+    for msg in bus.iter_timed(gst::ClockTime::from_seconds(3600)) {
+        match msg.view() {
+            gst::MessageView::Eos(..) => {
+                result = Ok(());
+                break;
+            }
+            gst::MessageView::Error(err) => {
+                result = Err(anyhow::anyhow!("MP3 transcode error: {}", err.error()));
+                break;
+            }
+            _ => {}
+        }
+    }
+    pipeline.set_state(gst::State::Null).ok();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp);
+        return Err(e);
+    }
+    std::fs::rename(&temp, dest)?;
+    Ok(())
+}
+
+// ============================================================================
+// HTTP
+// ============================================================================
+
+fn accept_loop(listener: TcpListener, links: LinkMap, stop: Arc<AtomicBool>) {
+    let limit = ConnectionLimit::new(MAX_CONNECTIONS);
+    while !stop.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                // Dropping the stream closes it; browsers retry
+                let Some(slot) = limit.try_acquire() else {
+                    println!("[Share] Too many connections, refused {}", peer);
+                    continue;
+                };
+                let links = links.clone();
+                std::thread::spawn(move || {
+                    let _slot = slot;
+                    if let Err(e) = handle_connection(stream, &links) {
+                        println!("[Share] Request failed: {}", e);
+                    }
+                });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+            Err(e) => {
+                println!("[Share] Accept failed: {}", e);
+                std::thread::sleep(ACCEPT_POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    range: Option<String>,
+}
+
+/// Outcome of reading a request head
+enum RequestHead {
+    Request(Request),
+    /// The client closed the connection or sent something unparseable
+    Closed,
+    /// Answer with this status and close
+    Rejected(u16, &'static str),
+}
+
+/// Read one line of at most `MAX_LINE_BYTES`, with the socket timeout set to
+/// what's left before `deadline`. None if the line is too long or time is up.
+fn read_head_line(
+    reader: &mut BufReader<&TcpStream>,
+    deadline: Instant,
+    line: &mut String,
+) -> std::io::Result<Option<usize>> {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining.is_zero() {
+        return Ok(None);
+    }
+    reader.get_ref().set_read_timeout(Some(remaining))?;
+    let read = reader.by_ref().take(MAX_LINE_BYTES).read_line(line)?;
+    if read > 0 && !line.ends_with('\n') {
+        return Ok(None);
+    }
+    Ok(Some(read))
+}
+
+fn read_request(stream: &TcpStream) -> std::io::Result<RequestHead> {
+    const TOO_LARGE: RequestHead = RequestHead::Rejected(431, "Request Header Fields Too Large");
+    let deadline = Instant::now() + REQUEST_TIMEOUT;
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    match read_head_line(&mut reader, deadline, &mut line)? {
+        Some(0) => return Ok(RequestHead::Closed),
+        Some(_) => {}
+        None => return Ok(TOO_LARGE),
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Ok(RequestHead::Rejected(400, "Bad Request"));
+    };
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        range: None,
+    };
+
+    let mut header_count = 0;
+    loop {
+        let mut header = String::new();
+        match read_head_line(&mut reader, deadline, &mut header)? {
+            Some(0) => break,
+            Some(_) if header.trim().is_empty() => break,
+            Some(_) => {}
+            None => return Ok(TOO_LARGE),
+        }
+        header_count += 1;
+        if header_count > MAX_HEADERS {
+            return Ok(TOO_LARGE);
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("range") {
+                request.range = Some(value.trim().to_string());
+            }
+        }
+    }
+    Ok(RequestHead::Request(request))
+}
+
+fn handle_connection(mut stream: TcpStream, links: &LinkMap) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let request = match read_request(&stream) {
+        Ok(RequestHead::Request(request)) => request,
+        Ok(RequestHead::Closed) => return Ok(()),
+        Ok(RequestHead::Rejected(status, message)) => return write_status(&mut stream, status, message),
+        // Timed out before the head was complete
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {
+            return write_status(&mut stream, 408, "Request Timeout");
+        }
+        Err(e) => return Err(e),
+    };
+
+    if request.method != "GET" && request.method != "HEAD" {
+        return write_status(&mut stream, 405, "Method Not Allowed");
+    }
+    let head_only = request.method == "HEAD";
+
+    // /s/<token> or /s/<token>/<file>
+    let path = request.path.split('?').next().unwrap_or("");
+    let mut segments = path.trim_start_matches('/').splitn(3, '/');
+    let (Some("s"), Some(token)) = (segments.next(), segments.next()) else {
+        return write_status(&mut stream, 404, "Not Found");
+    };
+    let file = segments.next().map(percent_decode).filter(|f| !f.is_empty());
+
+    let link = {
+        let links = links.read();
+        links.get(token).cloned()
+    };
+    let link = match link {
+        Some(link) if link.expires_at > Utc::now() => link,
+        Some(_) => {
+            links.write().remove(token);
+            return write_status(&mut stream, 410, "This link has expired");
+        }
+        None => return write_status(&mut stream, 404, "Not Found"),
+    };
+
+    match file {
+        None => {
+            let html = render_page(&link, token);
+            write_response(&mut stream, 200, "text/html; charset=utf-8", html.as_bytes(), head_only)
+        }
+        Some(name) => match shared_file(&link, &name) {
+            Some(path) => serve_file(&mut stream, &path, request.range.as_deref(), head_only),
+            None => write_status(&mut stream, 404, "Not Found"),
+        },
+    }
+}
+
+/// Resolve a requested file name to a file the link is allowed to serve
+fn shared_file(link: &ShareLink, name: &str) -> Option<PathBuf> {
+    if name.contains('/') || name.contains('\\') || name.contains("..") {
+        return None;
+    }
+    let path = if name.ends_with(".mp3") {
+        link.cache_dir.join(name)
+    } else if crate::video::proxy::is_proxy_file(name) || name.ends_with(".mid") {
+        link.session_path.join(name)
+    } else {
+        return None;
+    };
+    path.is_file().then_some(path)
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        Some("mp3") => "audio/mpeg",
+        Some("mid") => "audio/midi",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Serve a file, honouring a single `Range: bytes=` request so phone
+/// browsers can seek in video
+fn serve_file(stream: &mut TcpStream, path: &Path, range: Option<&str>, head_only: bool) -> std::io::Result<()> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    if len == 0 {
+        return write_response(stream, 200, content_type(path), &[], head_only);
+    }
+
+    let (start, end) = match range.and_then(|r| parse_range(r, len)) {
+        Some(bounds) => bounds,
+        None if range.is_some() => {
+            let header = format!(
+                "HTTP/1.1 416 Range Not Satisfiable\r\nContent-Range: bytes */{}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                len
+            );
+            return stream.write_all(header.as_bytes());
+        }
+        None => (0, len - 1),
+    };
+    let body_len = end - start + 1;
+
+    let mut header = if range.is_some() {
+        format!("HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\n", start, end, len)
+    } else {
+        "HTTP/1.1 200 OK\r\n".to_string()
+    };
+    let disposition = if path.extension().is_some_and(|e| e == "mid") {
+        format!(
+            "Content-Disposition: attachment; filename=\"{}\"\r\n",
+            path.file_name().and_then(|n| n.to_str()).unwrap_or("session.mid").replace('"', "")
+        )
+    } else {
+        String::new()
+    };
+    header.push_str(&format!(
+        "Content-Type: {}\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\n{}Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        content_type(path),
+        body_len,
+        disposition
+    ));
+    stream.write_all(header.as_bytes())?;
+    if head_only {
+        return Ok(());
+    }
+
+    file.seek(SeekFrom::Start(start))?;
+    std::io::copy(&mut file.take(body_len), stream)?;
+    Ok(())
+}
+
+/// Parse `bytes=start-end`, `bytes=start-` or `bytes=-suffix` into inclusive bounds
+fn parse_range(range: &str, len: u64) -> Option<(u64, u64)> {
+    let spec = range.strip_prefix("bytes=")?.split(',').next()?.trim();
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = if start.is_empty() {
+        let suffix: u64 = end.parse().ok()?;
+        (len.saturating_sub(suffix), len - 1)
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() { len - 1 } else { end.parse::<u64>().ok()?.min(len - 1) };
+        (start, end)
+    };
+    (start <= end && start < len).then_some((start, end))
+}
+
+fn write_status(stream: &mut TcpStream, status: u16, message: &str) -> std::io::Result<()> {
+    write_response(stream, status, "text/plain; charset=utf-8", message.as_bytes(), false)
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, content_type: &str, body: &[u8], head_only: bool) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        400 => "Bad Request",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        410 => "Gone",
+        431 => "Request Header Fields Too Large",
+        _ => "Error",
+    };
+    let header = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        content_type,
+        body.len()
+    );
+    stream.write_all(header.as_bytes())?;
+    if !head_only {
+        stream.write_all(body)?;
+    }
+    Ok(())
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 3 <= bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+pub(crate) fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub(crate) fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// ============================================================================
+// Page
+// ============================================================================
+
+fn render_page(link: &ShareLink, token: &str) -> String {
+    let list = |dir: &Path, keep: &dyn Fn(&str) -> bool| -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                    .filter(|n| keep(n))
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    };
+    let videos = list(&link.session_path, &|n| crate::video::proxy::is_proxy_file(n));
+    let audio = list(&link.cache_dir, &|n| n.ends_with(".mp3"));
+    let midi = list(&link.session_path, &|n| n.ends_with(".mid"));
+    let preparing = link.preparing.load(Ordering::SeqCst);
+
+    let url = |name: &str| format!("/s/{}/{}", token, percent_encode(name));
+    let label = |name: &str| {
+        let stem = name.split('.').next().unwrap_or(name);
+        let stem = stem
+            .strip_prefix("video_")
+            .or_else(|| stem.strip_prefix("audio_"))
+            .or_else(|| stem.strip_prefix("midi_"))
+            .unwrap_or(stem);
+        html_escape(&crate::session::unsanitize_device_name(stem))
+    };
+
+    let mut body = String::new();
+    for name in &videos {
+        body.push_str(&format!(
+            "<section><h2>{}</h2><video controls playsinline preload=\"metadata\" src=\"{}\"></video></section>\n",
+            label(name),
+            url(name)
+        ));
+    }
+    for name in &audio {
+        body.push_str(&format!(
+            "<section><h2>{}</h2><audio controls preload=\"metadata\" src=\"{}\"></audio></section>\n",
+            label(name),
+            url(name)
+        ));
+    }
+    if !midi.is_empty() {
+        body.push_str("<section><h2>MIDI</h2><ul>\n");
+        for name in &midi {
+            body.push_str(&format!("<li><a href=\"{}\" download>{}</a></li>\n", url(name), html_escape(name)));
+        }
+        body.push_str("</ul></section>\n");
+    }
+    if preparing {
+        body.push_str("<p class=\"note\">Preparing media for playback… this page refreshes automatically.</p>\n");
+    } else if body.is_empty() {
+        body.push_str("<p class=\"note\">This session has nothing to play.</p>\n");
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+{refresh}<title>{title} · Sacho</title>
+<style>
+body {{ font-family: -apple-system, Roboto, sans-serif; background: #141414; color: #e8e6e3; margin: 0; padding: 1rem; }}
+h1 {{ font-size: 1.1rem; font-weight: 400; color: #c9a962; margin: 0 0 1rem; }}
+h2 {{ font-size: 0.85rem; font-weight: 500; margin: 0 0 0.5rem; opacity: 0.8; }}
+section {{ margin-bottom: 1.25rem; }}
+video, audio {{ width: 100%; }}
+a {{ color: #c9a962; }}
+.note {{ opacity: 0.6; font-size: 0.85rem; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{body}</body>
+</html>
+"#,
+        refresh = if preparing { "<meta http-equiv=\"refresh\" content=\"5\">\n" } else { "" },
+        title = html_escape(&link.title),
+        body = body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percent_decode_handles_escapes() {
+        assert_eq!(percent_decode("a%20b"), "a b");
+        assert_eq!(percent_decode("take%201.wav"), "take 1.wav");
+    }
+
+    #[test]
+    fn percent_decode_handles_escape_at_end() {
+        assert_eq!(percent_decode("a%20"), "a ");
+        assert_eq!(percent_decode("%41"), "A");
+    }
+
+    #[test]
+    fn percent_decode_keeps_incomplete_escapes() {
+        assert_eq!(percent_decode("a%2"), "a%2");
+        assert_eq!(percent_decode("a%"), "a%");
+        assert_eq!(percent_decode("a%zz"), "a%zz");
+    }
+
+    #[test]
+    fn percent_decode_round_trips_encode() {
+        let name = "Piano & Vocals (take 2).flac";
+        assert_eq!(percent_decode(&percent_encode(name)), name);
+    }
+}
@@ -0,0 +1,181 @@
+// Graceful shutdown — finalizes in-flight recordings before the process exits
+//
+// Every exit path (tray Quit, Ctrl+C, the uninstaller's --quit) goes through
+// `AppHandle::exit`, which raises `RunEvent::ExitRequested`. If a recording is
+// running, starting, or still being finalized on another thread (e.g. the idle
+// checker just stopped it), the exit is deferred: a shutdown thread stops the
+// recording, waits for the writers and encoders to finish with a bounded
+// timeout, reports progress to the window, and only then exits for real.
+// Anything left unfinished after the timeout is picked up by the crash
+// recovery scan on the next launch.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::Config;
+use crate::recording::MidiMonitor;
+
+/// How often progress is reported while waiting
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Number of `stop_recording` calls currently writing files
+static FINALIZING: AtomicUsize = AtomicUsize::new(0);
+
+/// Set once the shutdown thread has been spawned
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// Set when the shutdown sequence has finished and the next exit request
+/// should be allowed through
+static SHUTDOWN_DONE: AtomicBool = AtomicBool::new(false);
+
+/// Held for the duration of a recording finalization
+pub struct FinalizeGuard(());
+
+impl Drop for FinalizeGuard {
+    fn drop(&mut self) {
+        FINALIZING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Mark a recording as being finalized until the guard is dropped
+pub fn begin_finalize() -> FinalizeGuard {
+    FINALIZING.fetch_add(1, Ordering::SeqCst);
+    FinalizeGuard(())
+}
+
+pub fn is_finalizing() -> bool {
+    FINALIZING.load(Ordering::SeqCst) > 0
+}
+
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// Stage of the shutdown sequence, sent to the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownStage {
+    /// Waiting for a recording that was starting to come up
+    WaitingForStart,
+    /// Stopping the recording and finalizing files
+    Finalizing,
+    /// Finalization didn't finish within the timeout; exiting anyway
+    TimedOut,
+    /// All files are complete; exiting
+    Done,
+}
+
+/// Event payload for `shutdown-progress`
+#[derive(Debug, Clone, Serialize)]
+pub struct ShutdownProgress {
+    pub stage: ShutdownStage,
+    pub elapsed_secs: f64,
+    pub timeout_secs: u32,
+}
+
+/// Called on `RunEvent::ExitRequested`. Returns true if the exit must be
+/// prevented because recordings still need finalizing; the shutdown thread
+/// exits with `code` once they're done.
+pub fn defer_exit(app: &AppHandle, code: Option<i32>) -> bool {
+    if SHUTDOWN_DONE.load(Ordering::SeqCst) {
+        return false;
+    }
+    if SHUTTING_DOWN.load(Ordering::SeqCst) {
+        // Already finalizing; a second Quit shouldn't skip the wait
+        return true;
+    }
+
+    let busy = is_finalizing() || {
+        let monitor = app.state::<Arc<Mutex<MidiMonitor>>>();
+        // A held monitor lock means a start or stop is in progress right now
+        match monitor.try_lock() {
+            Some(monitor) => monitor.is_recording() || monitor.is_starting(),
+            None => true,
+        }
+    };
+    if !busy {
+        return false;
+    }
+
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    let app = app.clone();
+    let code = code.unwrap_or(0);
+    std::thread::Builder::new()
+        .name("sacho-shutdown".into())
+        .spawn(move || run_shutdown(app, code))
+        .expect("Failed to spawn shutdown thread");
+    true
+}
+
+fn run_shutdown(app: AppHandle, code: i32) {
+    let timeout_secs = app.state::<RwLock<Config>>().read().shutdown_timeout_secs;
+    let timeout = Duration::from_secs(timeout_secs as u64);
+    let started = Instant::now();
+    println!("[Shutdown] Recording in progress, finalizing before exit (timeout {}s)", timeout_secs);
+
+    // Bring the window up so the progress dialog is visible
+    crate::agent::show_ui(&app);
+
+    let report = |stage: ShutdownStage| {
+        let _ = app.emit(
+            "shutdown-progress",
+            ShutdownProgress {
+                stage,
+                elapsed_secs: started.elapsed().as_secs_f64(),
+                timeout_secs,
+            },
+        );
+    };
+
+    // A recording that's still starting can't be stopped until it's up
+    let monitor = app.state::<Arc<Mutex<MidiMonitor>>>().inner().clone();
+    while started.elapsed() < timeout && monitor.lock().is_starting() {
+        report(ShutdownStage::WaitingForStart);
+        std::thread::sleep(POLL_INTERVAL);
+    }
+
+    // Stop on a separate thread so progress keeps flowing and the timeout
+    // holds even if finalization stalls
+    let stopper = {
+        let monitor = monitor.clone();
+        std::thread::Builder::new()
+            .name("sacho-shutdown-stop".into())
+            .spawn(move || {
+                let _ = monitor.lock().manual_stop_recording();
+            })
+            .ok()
+    };
+
+    let finished = loop {
+        let stopper_done = stopper.as_ref().map_or(true, |h| h.is_finished());
+        if stopper_done && !is_finalizing() {
+            break true;
+        }
+        if started.elapsed() >= timeout {
+            break false;
+        }
+        report(ShutdownStage::Finalizing);
+        std::thread::sleep(POLL_INTERVAL);
+    };
+
+    if finished {
+        println!("[Shutdown] Recordings finalized in {:.1}s", started.elapsed().as_secs_f64());
+        report(ShutdownStage::Done);
+    } else {
+        println!(
+            "[Shutdown] Finalization did not finish within {}s, exiting anyway (files will be repaired on next launch)",
+            timeout_secs
+        );
+        report(ShutdownStage::TimedOut);
+        // Give the dialog a moment to show the warning
+        std::thread::sleep(Duration::from_secs(2));
+    }
+
+    SHUTDOWN_DONE.store(true, Ordering::SeqCst);
+    app.exit(code);
+}
@@ -0,0 +1,344 @@
+// Note accuracy scoring against a reference score
+//
+// The recorded notes are aligned to the reference with subsequence DTW over
+// onset groups (notes that start together), so the performance can begin
+// anywhere in the session and drift in tempo. Aligned groups are compared
+// pitch by pitch. Timing is measured against a straight-line fit of performed
+// time to score time, so playing uniformly slower than the score isn't
+// counted as lateness.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::midi_parser::{self, MidiParseResult};
+
+/// Performed notes starting within this window form one chord
+const GROUP_SECS: f64 = 0.05;
+
+/// Upper bound on the alignment matrix (reference groups x performed groups)
+const MAX_DTW_CELLS: usize = 40_000_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MissedNote {
+    pub pitch: u8,
+    /// Beat within the measure, from 1
+    pub beat: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MeasureAccuracy {
+    /// Measure number, from 1
+    pub measure: usize,
+    /// Where the measure falls in the recording
+    pub start_secs: f64,
+    pub reference_notes: usize,
+    pub matched_notes: usize,
+    /// Matched share of the reference notes (1.0 for empty measures)
+    pub accuracy: f64,
+    /// Mean absolute timing deviation of matched notes
+    pub mean_timing_deviation_ms: Option<f64>,
+    pub missed: Vec<MissedNote>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccuracyReport {
+    pub reference_notes: usize,
+    pub matched_notes: usize,
+    pub missed_notes: usize,
+    /// Performed notes within the aligned span that aren't in the score
+    pub extra_notes: usize,
+    pub accuracy: f64,
+    pub mean_timing_deviation_ms: f64,
+    /// Performed duration per score duration (> 1 = slower than written)
+    pub tempo_ratio: f64,
+    /// Span of the recording aligned to the score
+    pub performance_start_secs: f64,
+    pub performance_end_secs: f64,
+    pub measures: Vec<MeasureAccuracy>,
+}
+
+/// Reference notes that start on the same tick
+struct ReferenceGroup {
+    tick: u64,
+    time: f64,
+    pitches: Vec<u8>,
+}
+
+/// Performed notes that start together
+struct PerformedGroup {
+    time: f64,
+    pitches: Vec<u8>,
+}
+
+/// Score the session's recorded MIDI against a reference MIDI file
+pub fn score_against_reference(recorded: &[&Path], reference: &Path) -> anyhow::Result<AccuracyReport> {
+    let parsed_reference = midi_parser::parse_midi(reference)?;
+    let (numerator, denominator) = read_time_signature(reference);
+    let reference_groups = group_reference(&parsed_reference);
+    if reference_groups.is_empty() {
+        return Err(anyhow::anyhow!("The reference file has no notes"));
+    }
+
+    let mut performed_notes: Vec<(f64, u8)> = Vec::new();
+    for path in recorded {
+        let parsed = midi_parser::parse_midi(path)?;
+        performed_notes.extend(parsed.events.iter().map(|note| {
+            (midi_parser::tick_to_seconds(note.start_tick, parsed.ticks_per_beat, &parsed.tempo_map), note.pitch)
+        }));
+    }
+    let performed_groups = group_performed(performed_notes);
+    if performed_groups.is_empty() {
+        return Err(anyhow::anyhow!("The session has no recorded notes"));
+    }
+
+    let cells = reference_groups.len() * performed_groups.len();
+    if cells > MAX_DTW_CELLS {
+        return Err(anyhow::anyhow!(
+            "Too many notes to align ({} score chords x {} played chords)",
+            reference_groups.len(),
+            performed_groups.len()
+        ));
+    }
+
+    let path = align(&reference_groups, &performed_groups);
+
+    // Match pitches: each performed note is used at most once
+    let mut used: Vec<Vec<bool>> = performed_groups.iter().map(|g| vec![false; g.pitches.len()]).collect();
+    // (reference group, pitch, performed time if matched)
+    let mut outcomes: Vec<(usize, u8, Option<f64>)> = Vec::new();
+    let mut aligned: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for &(r, p) in &path {
+        aligned.entry(r).or_default().push(p);
+    }
+    for (r, group) in reference_groups.iter().enumerate() {
+        let candidates = aligned.get(&r).cloned().unwrap_or_default();
+        for &pitch in &group.pitches {
+            let hit = candidates.iter().find_map(|&p| {
+                let pitches = &performed_groups[p].pitches;
+                let idx = (0..pitches.len()).find(|&k| pitches[k] == pitch && !used[p][k])?;
+                used[p][idx] = true;
+                Some(performed_groups[p].time)
+            });
+            outcomes.push((r, pitch, hit));
+        }
+    }
+
+    let first_p = path.iter().map(|&(_, p)| p).min().unwrap_or(0);
+    let last_p = path.iter().map(|&(_, p)| p).max().unwrap_or(0);
+    let extra_notes = (first_p..=last_p)
+        .map(|p| used[p].iter().filter(|u| !**u).count())
+        .sum();
+
+    // Tempo fit: performed = a * reference + b
+    let pairs: Vec<(f64, f64)> = outcomes
+        .iter()
+        .filter_map(|&(r, _, hit)| hit.map(|t| (reference_groups[r].time, t)))
+        .collect();
+    let (a, b) = fit_line(&pairs).unwrap_or((1.0, performed_groups[first_p].time - reference_groups[0].time));
+    let deviation_ms = |r: usize, t: f64| (t - (a * reference_groups[r].time + b)) * 1000.0;
+
+    let tpb = parsed_reference.ticks_per_beat as u64;
+    let beat_ticks = (tpb * 4 / denominator.max(1) as u64).max(1);
+    let measure_ticks = beat_ticks * numerator.max(1) as u64;
+    let last_measure = reference_groups.last().map(|g| g.tick / measure_ticks).unwrap_or(0) as usize;
+
+    let mut measures: Vec<MeasureAccuracy> = (0..=last_measure)
+        .map(|m| {
+            let tick = m as u64 * measure_ticks;
+            let ref_time = midi_parser::tick_to_seconds(tick, parsed_reference.ticks_per_beat, &parsed_reference.tempo_map);
+            MeasureAccuracy {
+                measure: m + 1,
+                start_secs: a * ref_time + b,
+                reference_notes: 0,
+                matched_notes: 0,
+                accuracy: 1.0,
+                mean_timing_deviation_ms: None,
+                missed: Vec::new(),
+            }
+        })
+        .collect();
+    let mut deviations: Vec<Vec<f64>> = vec![Vec::new(); measures.len()];
+
+    for &(r, pitch, hit) in &outcomes {
+        let tick = reference_groups[r].tick;
+        let m = (tick / measure_ticks) as usize;
+        let measure = &mut measures[m];
+        measure.reference_notes += 1;
+        match hit {
+            Some(t) => {
+                measure.matched_notes += 1;
+                deviations[m].push(deviation_ms(r, t).abs());
+            }
+            None => measure.missed.push(MissedNote {
+                pitch,
+                beat: (tick % measure_ticks) as f64 / beat_ticks as f64 + 1.0,
+            }),
+        }
+    }
+    for (measure, devs) in measures.iter_mut().zip(&deviations) {
+        if measure.reference_notes > 0 {
+            measure.accuracy = measure.matched_notes as f64 / measure.reference_notes as f64;
+        }
+        if !devs.is_empty() {
+            measure.mean_timing_deviation_ms = Some(devs.iter().sum::<f64>() / devs.len() as f64);
+        }
+    }
+
+    let reference_notes = outcomes.len();
+    let matched_notes = pairs.len();
+    let all_devs: Vec<f64> = deviations.into_iter().flatten().collect();
+
+    Ok(AccuracyReport {
+        reference_notes,
+        matched_notes,
+        missed_notes: reference_notes - matched_notes,
+        extra_notes,
+        accuracy: matched_notes as f64 / reference_notes.max(1) as f64,
+        mean_timing_deviation_ms: if all_devs.is_empty() {
+            0.0
+        } else {
+            all_devs.iter().sum::<f64>() / all_devs.len() as f64
+        },
+        tempo_ratio: a,
+        performance_start_secs: performed_groups[first_p].time,
+        performance_end_secs: performed_groups[last_p].time,
+        measures,
+    })
+}
+
+fn group_reference(parsed: &MidiParseResult) -> Vec<ReferenceGroup> {
+    let mut by_tick: BTreeMap<u64, Vec<u8>> = BTreeMap::new();
+    for note in &parsed.events {
+        by_tick.entry(note.start_tick).or_default().push(note.pitch);
+    }
+    by_tick
+        .into_iter()
+        .map(|(tick, pitches)| ReferenceGroup {
+            tick,
+            time: midi_parser::tick_to_seconds(tick, parsed.ticks_per_beat, &parsed.tempo_map),
+            pitches,
+        })
+        .collect()
+}
+
+fn group_performed(mut notes: Vec<(f64, u8)>) -> Vec<PerformedGroup> {
+    notes.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut groups: Vec<PerformedGroup> = Vec::new();
+    for (time, pitch) in notes {
+        match groups.last_mut() {
+            Some(group) if time - group.time <= GROUP_SECS => group.pitches.push(pitch),
+            _ => groups.push(PerformedGroup { time, pitches: vec![pitch] }),
+        }
+    }
+    groups
+}
+
+/// 1 - Jaccard similarity of two chords' pitches
+fn chord_cost(a: &[u8], b: &[u8]) -> f32 {
+    let common = a.iter().filter(|p| b.contains(p)).count();
+    let union = a.len() + b.len() - common;
+    1.0 - common as f32 / union.max(1) as f32
+}
+
+/// Subsequence DTW: the whole reference against any span of the performance.
+/// Returns the (reference group, performed group) pairs on the best path.
+fn align(reference: &[ReferenceGroup], performed: &[PerformedGroup]) -> Vec<(usize, usize)> {
+    const DIAG: u8 = 0;
+    const UP: u8 = 1; // next reference group, same performed group
+    const LEFT: u8 = 2; // extra performed group
+
+    let n = reference.len();
+    let m = performed.len();
+    let mut steps = vec![DIAG; n * m];
+    // Row i-1 of the cost matrix; the virtual row before the first is all zero
+    let mut prev = vec![0.0f32; m];
+    let mut cur = vec![0.0f32; m];
+
+    for i in 0..n {
+        for j in 0..m {
+            let cost = chord_cost(&reference[i].pitches, &performed[j].pitches);
+            let diag = if j == 0 { if i == 0 { 0.0 } else { f32::INFINITY } } else { prev[j - 1] };
+            let up = if i == 0 { f32::INFINITY } else { prev[j] };
+            let left = if j == 0 { f32::INFINITY } else { cur[j - 1] };
+            let (best, step) = if diag <= up && diag <= left {
+                (diag, DIAG)
+            } else if up <= left {
+                (up, UP)
+            } else {
+                (left, LEFT)
+            };
+            cur[j] = best + cost;
+            steps[i * m + j] = step;
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+
+    // Best end anywhere in the performance
+    let mut j = prev
+        .iter()
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(j, _)| j)
+        .unwrap_or(0);
+    let mut i = n - 1;
+    let mut path = Vec::new();
+    loop {
+        path.push((i, j));
+        if i == 0 && steps[j] != LEFT {
+            break;
+        }
+        match steps[i * m + j] {
+            DIAG => {
+                if i == 0 || j == 0 {
+                    break;
+                }
+                i -= 1;
+                j -= 1;
+            }
+            UP => i -= 1,
+            _ => {
+                if j == 0 {
+                    break;
+                }
+                j -= 1;
+            }
+        }
+    }
+    path.reverse();
+    path
+}
+
+/// Least-squares line through (x, y) points
+fn fit_line(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let var_x: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if var_x <= f64::EPSILON {
+        return None;
+    }
+    let cov: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let a = cov / var_x;
+    Some((a, mean_y - a * mean_x))
+}
+
+/// First time signature in a MIDI file, 4/4 if none
+fn read_time_signature(path: &Path) -> (u8, u8) {
+    let Ok(data) = std::fs::read(path) else { return (4, 4) };
+    let Ok(smf) = midly::Smf::parse(&data) else { return (4, 4) };
+    smf.tracks
+        .iter()
+        .flatten()
+        .find_map(|event| match event.kind {
+            midly::TrackEventKind::Meta(midly::MetaMessage::TimeSignature(num, denom_pow, _, _)) => {
+                Some((num, 1u8.checked_shl(denom_pow as u32).unwrap_or(4)))
+            }
+            _ => None,
+        })
+        .unwrap_or((4, 4))
+}
@@ -0,0 +1,273 @@
+// Audio feature extraction for sessions without MIDI
+//
+// Decodes an audio file to mono at a low analysis rate, computes per-frame
+// chroma with a Goertzel filter bank tuned to equal-tempered pitches, and
+// summarizes the frames into the same chunked harmonic features used for
+// MIDI. Audio-only sessions can then be scored in harmonic mode and placed
+// on the similarity map.
+
+use std::f32::consts::PI;
+use std::path::Path;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+
+use super::features::{l1_normalize, ChunkFeatures, ChunkedFileFeatures, HarmonicFeatures, MIN_NOTE_COUNT};
+
+/// Sample rate audio is decoded to for analysis
+pub const ANALYSIS_RATE: u32 = 11025;
+
+/// Analysis frame length (~370ms at 11.025kHz, enough to resolve semitones at C2)
+const FRAME_SIZE: usize = 4096;
+
+/// Hop between analysis frames
+const HOP_SIZE: usize = 2048;
+
+/// Lowest and highest analyzed MIDI pitch (C2..B6)
+const MIN_PITCH: u8 = 36;
+const MAX_PITCH: u8 = 95;
+
+/// Frames quieter than this RMS are treated as silence
+const SILENCE_RMS: f32 = 0.005;
+
+/// Cap on analyzed audio to bound extraction time for very long sessions
+const MAX_ANALYSIS_SECS: usize = 20 * 60;
+
+/// Same windowing as MIDI chunked features
+const WINDOW_SECS: f64 = 15.0;
+const STRIDE_SECS: f64 = 7.5;
+
+/// Decode an audio file (WAV/FLAC or anything decodebin handles) to mono f32
+/// samples at `ANALYSIS_RATE`.
+pub fn decode_mono(path: &Path) -> anyhow::Result<Vec<f32>> {
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+    let pipeline = gst::Pipeline::new();
+
+    let filesrc = gst::ElementFactory::make("filesrc")
+        .property("location", path.to_string_lossy().to_string())
+        .build()?;
+    let decodebin = gst::ElementFactory::make("decodebin").build()?;
+    let audioconvert = gst::ElementFactory::make("audioconvert").build()?;
+    let audioresample = gst::ElementFactory::make("audioresample").build()?;
+    let caps = gst::Caps::builder("audio/x-raw")
+        .field("format", "F32LE")
+        .field("layout", "interleaved")
+        .field("channels", 1i32)
+        .field("rate", ANALYSIS_RATE as i32)
+        .build();
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .property("caps", &caps)
+        .build()?;
+    let appsink = gst_app::AppSink::builder()
+        .name("sink")
+        .sync(false)
+        .build();
+
+    pipeline.add_many([
+        &filesrc,
+        &decodebin,
+        &audioconvert,
+        &audioresample,
+        &capsfilter,
+        appsink.upcast_ref(),
+    ])?;
+    filesrc.link(&decodebin)?;
+    gst::Element::link_many([&audioconvert, &audioresample, &capsfilter, appsink.upcast_ref()])?;
+
+    // Link the first audio pad from decodebin to audioconvert
+    let audioconvert_weak = audioconvert.downgrade();
+    decodebin.connect_pad_added(move |_decodebin, src_pad| {
+        let Some(audioconvert) = audioconvert_weak.upgrade() else {
+            return;
+        };
+        let caps = src_pad
+            .current_caps()
+            .or_else(|| Some(src_pad.query_caps(None)));
+        let is_audio = caps
+            .as_ref()
+            .and_then(|c| c.structure(0))
+            .map(|s| s.name().as_str().starts_with("audio/"))
+            .unwrap_or(false);
+        if !is_audio {
+            return;
+        }
+        if let Some(sink_pad) = audioconvert.static_pad("sink") {
+            if !sink_pad.is_linked() {
+                if let Err(e) = src_pad.link(&sink_pad) {
+                    log::warn!("Audio features: failed to link audio pad: {:?}", e);
+                }
+            }
+        }
+    });
+
+    pipeline.set_state(gst::State::Playing)?;
+
+    let max_samples = MAX_ANALYSIS_SECS * ANALYSIS_RATE as usize;
+    let mut samples: Vec<f32> = Vec::new();
+    while samples.len() < max_samples {
+        let Some(sample) = appsink.try_pull_sample(gst::ClockTime::from_seconds(5)) else {
+            break;
+        };
+        let Some(buffer) = sample.buffer() else { continue };
+        let Ok(map) = buffer.map_readable() else { continue };
+        samples.extend(
+            map.as_slice()
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+        );
+    }
+
+    // Surface decode errors (e.g. unsupported or truncated file)
+    let error = pipeline.bus().and_then(|bus| {
+        bus.pop_filtered(&[gst::MessageType::Error]).and_then(|msg| match msg.view() {
+            gst::MessageView::Error(err) => Some(format!("{} ({:?})", err.error(), err.debug())),
+            _ => None,
+        })
+    });
+    let _ = pipeline.set_state(gst::State::Null);
+
+    if samples.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No audio decoded from {}{}",
+            path.display(),
+            error.map(|e| format!(": {}", e)).unwrap_or_default()
+        ));
+    }
+
+    samples.truncate(max_samples);
+    Ok(samples)
+}
+
+/// Decode an audio file and extract chunked harmonic features
+pub fn extract_audio_file_features(path: &Path) -> anyhow::Result<ChunkedFileFeatures> {
+    let samples = decode_mono(path)?;
+    Ok(extract_audio_chunked_features(&samples))
+}
+
+/// Per-frame chroma vectors (None for silent frames), one per `HOP_SIZE` samples.
+fn chroma_frames(samples: &[f32]) -> Vec<Option<[f32; 12]>> {
+    let window: Vec<f32> = (0..FRAME_SIZE)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / (FRAME_SIZE - 1) as f32).cos())
+        .collect();
+
+    // Goertzel coefficients for each analyzed pitch
+    let coeffs: Vec<(usize, f32)> = (MIN_PITCH..=MAX_PITCH)
+        .map(|pitch| {
+            let freq = 440.0 * 2f32.powf((pitch as f32 - 69.0) / 12.0);
+            let coeff = 2.0 * (2.0 * PI * freq / ANALYSIS_RATE as f32).cos();
+            ((pitch % 12) as usize, coeff)
+        })
+        .collect();
+
+    let mut frames = Vec::new();
+    let mut windowed = vec![0.0f32; FRAME_SIZE];
+    let mut start = 0;
+    while start + FRAME_SIZE <= samples.len() {
+        let frame = &samples[start..start + FRAME_SIZE];
+        start += HOP_SIZE;
+
+        let rms = (frame.iter().map(|x| x * x).sum::<f32>() / FRAME_SIZE as f32).sqrt();
+        if rms < SILENCE_RMS {
+            frames.push(None);
+            continue;
+        }
+
+        for (w, (x, h)) in windowed.iter_mut().zip(frame.iter().zip(window.iter())) {
+            *w = x * h;
+        }
+
+        let mut chroma = [0.0f32; 12];
+        for &(pc, coeff) in &coeffs {
+            let (mut s1, mut s2) = (0.0f32, 0.0f32);
+            for &x in &windowed {
+                let s0 = x + coeff * s1 - s2;
+                s2 = s1;
+                s1 = s0;
+            }
+            let power = s1 * s1 + s2 * s2 - coeff * s1 * s2;
+            chroma[pc] += power.max(0.0).sqrt();
+        }
+        l1_normalize(&mut chroma);
+        frames.push(Some(chroma));
+    }
+    frames
+}
+
+/// Summarize a run of chroma frames into harmonic features.
+/// Returns None if fewer than `MIN_NOTE_COUNT` frames contain sound.
+fn harmonic_from_frames(frames: &[Option<[f32; 12]>]) -> Option<HarmonicFeatures> {
+    let voiced: Vec<&[f32; 12]> = frames.iter().flatten().collect();
+    if voiced.len() < MIN_NOTE_COUNT {
+        return None;
+    }
+
+    let mut chroma = vec![0.0f32; 12];
+    for frame in &voiced {
+        for (i, v) in frame.iter().enumerate() {
+            chroma[i] += v;
+        }
+    }
+    l1_normalize(&mut chroma);
+
+    // Transitions between dominant pitch classes of consecutive voiced frames
+    let dominant: Vec<usize> = voiced.iter()
+        .map(|frame| {
+            frame.iter()
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(i, _)| i)
+                .unwrap_or(0)
+        })
+        .collect();
+    let mut pc_transitions = vec![0.0f32; 144];
+    for pair in dominant.windows(2) {
+        if pair[0] != pair[1] {
+            pc_transitions[pair[0] * 12 + pair[1]] += 1.0;
+        }
+    }
+    l1_normalize(&mut pc_transitions);
+
+    Some(HarmonicFeatures { chroma, pc_transitions })
+}
+
+/// Extract harmonic features in 15-second overlapping windows (7.5s stride),
+/// matching the MIDI chunking so offsets line up in scoring results.
+pub fn extract_audio_chunked_features(samples: &[f32]) -> ChunkedFileFeatures {
+    let frames = chroma_frames(samples);
+    if frames.is_empty() {
+        return ChunkedFileFeatures { chunks: vec![] };
+    }
+
+    let frame_secs = HOP_SIZE as f64 / ANALYSIS_RATE as f64;
+    let total_duration = frames.len() as f64 * frame_secs;
+    let mut chunks = Vec::new();
+
+    if total_duration < WINDOW_SECS {
+        chunks.push(ChunkFeatures {
+            offset_secs: 0.0,
+            melodic: None,
+            harmonic: harmonic_from_frames(&frames),
+        });
+    } else {
+        let mut start = 0.0;
+        while start < total_duration {
+            let lo = (start / frame_secs) as usize;
+            let hi = (((start + WINDOW_SECS) / frame_secs) as usize).min(frames.len());
+            if hi > lo {
+                let harmonic = harmonic_from_frames(&frames[lo..hi]);
+                if harmonic.is_some() {
+                    chunks.push(ChunkFeatures {
+                        offset_secs: start as f32,
+                        melodic: None,
+                        harmonic,
+                    });
+                }
+            }
+            start += STRIDE_SECS;
+        }
+    }
+
+    ChunkedFileFeatures { chunks }
+}
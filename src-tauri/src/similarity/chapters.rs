@@ -0,0 +1,207 @@
+// Chapter detection within one session
+//
+// A long practice session is usually several pieces with breaks between
+// them. A long rest always starts a new chapter. Within a piece, the music
+// is split into phrases at shorter rests, and each phrase's key is
+// estimated (Krumhansl-Schmuckler on a duration-weighted pitch-class
+// histogram); a phrase that is confidently in a different key from the
+// chapter so far starts a new chapter too.
+
+use std::path::Path;
+
+use super::midi_parser;
+use crate::session::Chapter;
+
+/// Rest that always ends a piece
+const PIECE_REST_SECS: f64 = 10.0;
+
+/// Rest that separates phrases within a piece
+const PHRASE_REST_SECS: f64 = 2.0;
+
+/// A chapter must last this long before a key change can end it
+const MIN_KEY_CHAPTER_SECS: f64 = 30.0;
+
+/// Notes needed before a key estimate is trusted
+const MIN_KEY_NOTES: usize = 24;
+
+/// Correlation a key estimate needs to count as confident
+const MIN_KEY_CORRELATION: f64 = 0.6;
+
+/// Krumhansl-Kessler key profiles, from the tonic up
+const MAJOR_PROFILE: [f64; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const MINOR_PROFILE: [f64; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+const PITCH_NAMES: [&str; 12] = ["C", "C#", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B"];
+
+#[derive(Debug, Clone, Copy)]
+struct Note {
+    start: f64,
+    end: f64,
+    pitch: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Key {
+    tonic: usize,
+    minor: bool,
+}
+
+impl Key {
+    fn name(self) -> String {
+        format!("{} {}", PITCH_NAMES[self.tonic], if self.minor { "minor" } else { "major" })
+    }
+}
+
+/// Notes between rests
+struct Phrase {
+    start: f64,
+    end: f64,
+    histogram: [f64; 12],
+    note_count: usize,
+    /// Whether a piece-length rest comes before this phrase
+    after_long_rest: bool,
+}
+
+/// Detect chapters across the session's MIDI files. Returns nothing when
+/// the whole session is one chapter.
+pub fn detect_chapters(midi_paths: &[&Path], duration_secs: f64) -> Vec<Chapter> {
+    let mut notes: Vec<Note> = Vec::new();
+    for path in midi_paths {
+        let parsed = match midi_parser::parse_midi(path) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                log::warn!("Chapters: failed to parse {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        for note in &parsed.events {
+            let start = midi_parser::tick_to_seconds(note.start_tick, parsed.ticks_per_beat, &parsed.tempo_map);
+            let end = midi_parser::tick_to_seconds(
+                note.start_tick + note.duration_ticks,
+                parsed.ticks_per_beat,
+                &parsed.tempo_map,
+            );
+            notes.push(Note { start, end, pitch: note.pitch });
+        }
+    }
+    notes.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let chapters = find_chapters(&phrases(&notes), duration_secs);
+    if chapters.len() < 2 {
+        return Vec::new();
+    }
+    chapters
+}
+
+fn phrases(notes: &[Note]) -> Vec<Phrase> {
+    let mut phrases: Vec<Phrase> = Vec::new();
+    let mut released_at = f64::NEG_INFINITY;
+    for note in notes {
+        let rest = note.start - released_at;
+        if phrases.is_empty() || rest >= PHRASE_REST_SECS {
+            phrases.push(Phrase {
+                start: note.start,
+                end: note.end,
+                histogram: [0.0; 12],
+                note_count: 0,
+                after_long_rest: !phrases.is_empty() && rest >= PIECE_REST_SECS,
+            });
+        }
+        let phrase = phrases.last_mut().expect("a phrase was just pushed");
+        phrase.end = phrase.end.max(note.end);
+        phrase.histogram[(note.pitch % 12) as usize] += (note.end - note.start).max(0.05);
+        phrase.note_count += 1;
+        released_at = released_at.max(note.end);
+    }
+    phrases
+}
+
+fn find_chapters(phrases: &[Phrase], duration_secs: f64) -> Vec<Chapter> {
+    // (start, key) of each chapter
+    let mut starts: Vec<(f64, Option<Key>)> = Vec::new();
+    let mut histogram = [0.0; 12];
+    let mut note_count = 0;
+
+    for phrase in phrases {
+        let chapter_key = confident_key(&histogram, note_count);
+        let new_chapter = match starts.last() {
+            None => true,
+            Some(_) if phrase.after_long_rest => true,
+            Some(&(chapter_start, _)) => {
+                let phrase_key = confident_key(&phrase.histogram, phrase.note_count);
+                phrase.start - chapter_start >= MIN_KEY_CHAPTER_SECS
+                    && chapter_key.is_some()
+                    && phrase_key.is_some()
+                    && phrase_key != chapter_key
+            }
+        };
+
+        if new_chapter {
+            if let Some(last) = starts.last_mut() {
+                last.1 = chapter_key;
+            }
+            starts.push((phrase.start, None));
+            histogram = [0.0; 12];
+            note_count = 0;
+        }
+        for (total, weight) in histogram.iter_mut().zip(phrase.histogram) {
+            *total += weight;
+        }
+        note_count += phrase.note_count;
+    }
+    if let Some(last) = starts.last_mut() {
+        last.1 = confident_key(&histogram, note_count);
+    }
+
+    let end = duration_secs.max(phrases.last().map(|p| p.end).unwrap_or(0.0));
+    starts
+        .iter()
+        .enumerate()
+        .map(|(i, &(start_secs, key))| Chapter {
+            title: match key {
+                Some(key) => format!("Chapter {} ({})", i + 1, key.name()),
+                None => format!("Chapter {}", i + 1),
+            },
+            start_secs,
+            end_secs: starts.get(i + 1).map(|next| next.0).unwrap_or(end),
+        })
+        .collect()
+}
+
+/// Best-matching key, if there are enough notes and it matches well
+fn confident_key(histogram: &[f64; 12], note_count: usize) -> Option<Key> {
+    if note_count < MIN_KEY_NOTES {
+        return None;
+    }
+    let mut best: Option<(Key, f64)> = None;
+    for tonic in 0..12 {
+        for minor in [false, true] {
+            let profile = if minor { &MINOR_PROFILE } else { &MAJOR_PROFILE };
+            let rotated: Vec<f64> = (0..12).map(|pc| histogram[(pc + tonic) % 12]).collect();
+            let r = correlation(&rotated, profile);
+            if best.map_or(true, |(_, best_r)| r > best_r) {
+                best = Some((Key { tonic, minor }, r));
+            }
+        }
+    }
+    best.filter(|&(_, r)| r >= MIN_KEY_CORRELATION).map(|(key, _)| key)
+}
+
+/// Pearson correlation
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a).powi(2);
+        var_b += (y - mean_b).powi(2);
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        return 0.0;
+    }
+    cov / (var_a * var_b).sqrt()
+}
@@ -0,0 +1,177 @@
+// Playing dynamics of one session
+//
+// Note-on velocities from the session's MIDI give a velocity histogram and a
+// velocity-over-time heat map (one histogram per time window); the first
+// audio file gives a loudness curve (RMS and peak per window). The summary
+// numbers — mean, spread and the 5th-95th percentile range of velocity, and
+// the dynamic range of the audio — are what to compare across sessions to
+// see whether the playing is getting more controlled or more expressive.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::audio_features::{decode_mono, ANALYSIS_RATE};
+use super::midi_parser;
+
+/// Velocity histogram buckets (8 velocity steps each)
+pub const VELOCITY_BUCKETS: usize = 16;
+
+/// Shortest time window; longer sessions use wider windows
+const MIN_WINDOW_SECS: f64 = 5.0;
+
+/// Most windows per curve, so hour-long sessions stay readable
+const MAX_WINDOWS: usize = 240;
+
+/// Audio windows quieter than this are silence, not soft playing
+const SILENCE_DB: f64 = -60.0;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionDynamics {
+    /// Seconds covered by each window of the curves
+    pub window_secs: f64,
+    pub note_count: usize,
+    /// Note-ons per velocity bucket (bucket i holds velocities 8i..8i+7)
+    pub velocity_histogram: Vec<u32>,
+    pub mean_velocity: Option<f64>,
+    /// Standard deviation of velocity
+    pub velocity_spread: Option<f64>,
+    /// 5th and 95th percentile velocity
+    pub velocity_range: Option<(u8, u8)>,
+    /// Velocity histogram per time window (the heat map)
+    pub velocity_windows: Vec<VelocityWindow>,
+    /// Audio file the loudness curve was measured from
+    pub loudness_source: Option<String>,
+    pub loudness: Vec<LoudnessWindow>,
+    /// 95th minus 10th percentile RMS of the non-silent windows
+    pub dynamic_range_db: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VelocityWindow {
+    pub start_secs: f64,
+    pub note_count: u32,
+    pub mean_velocity: Option<f64>,
+    pub histogram: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LoudnessWindow {
+    pub start_secs: f64,
+    pub rms_db: f64,
+    pub peak_db: f64,
+}
+
+/// Analyze the MIDI files and (optionally) one audio file of a session
+/// lasting `duration_secs`
+pub fn analyze(midi_paths: &[&Path], audio_path: Option<&Path>, duration_secs: f64) -> SessionDynamics {
+    let window_secs = MIN_WINDOW_SECS.max(duration_secs / MAX_WINDOWS as f64);
+
+    // (time, velocity) of every note-on
+    let mut notes: Vec<(f64, u8)> = Vec::new();
+    for path in midi_paths {
+        match midi_parser::parse_midi(path) {
+            Ok(parsed) => notes.extend(parsed.events.iter().map(|note| {
+                let time = midi_parser::tick_to_seconds(note.start_tick, parsed.ticks_per_beat, &parsed.tempo_map);
+                (time, note.velocity)
+            })),
+            Err(e) => println!("[Sacho] Dynamics: skipping {}: {}", path.display(), e),
+        }
+    }
+
+    let mut velocity_histogram = vec![0u32; VELOCITY_BUCKETS];
+    for &(_, velocity) in &notes {
+        velocity_histogram[bucket(velocity)] += 1;
+    }
+
+    let note_end = notes.iter().map(|(time, _)| *time).fold(0.0f64, f64::max);
+    let window_count = ((duration_secs.max(note_end) / window_secs).ceil() as usize).max(1);
+    let mut velocity_windows: Vec<VelocityWindow> = (0..window_count)
+        .map(|i| VelocityWindow {
+            start_secs: i as f64 * window_secs,
+            note_count: 0,
+            mean_velocity: None,
+            histogram: vec![0; VELOCITY_BUCKETS],
+        })
+        .collect();
+    let mut window_sums = vec![0u64; window_count];
+    for &(time, velocity) in &notes {
+        let index = ((time / window_secs) as usize).min(window_count - 1);
+        let window = &mut velocity_windows[index];
+        window.note_count += 1;
+        window.histogram[bucket(velocity)] += 1;
+        window_sums[index] += velocity as u64;
+    }
+    for (window, sum) in velocity_windows.iter_mut().zip(window_sums) {
+        if window.note_count > 0 {
+            window.mean_velocity = Some(sum as f64 / window.note_count as f64);
+        }
+    }
+
+    let (mean_velocity, velocity_spread, velocity_range) = if notes.is_empty() {
+        (None, None, None)
+    } else {
+        let n = notes.len() as f64;
+        let mean = notes.iter().map(|(_, v)| *v as f64).sum::<f64>() / n;
+        let variance = notes.iter().map(|(_, v)| (*v as f64 - mean).powi(2)).sum::<f64>() / n;
+        let mut sorted: Vec<u8> = notes.iter().map(|(_, v)| *v).collect();
+        sorted.sort_unstable();
+        (Some(mean), Some(variance.sqrt()), Some((percentile(&sorted, 0.05), percentile(&sorted, 0.95))))
+    };
+
+    let (loudness, loudness_source) = match audio_path {
+        Some(path) => match loudness_curve(path, window_secs) {
+            Ok(curve) => (curve, path.file_name().map(|n| n.to_string_lossy().to_string())),
+            Err(e) => {
+                println!("[Sacho] Dynamics: can't measure loudness of {}: {}", path.display(), e);
+                (Vec::new(), None)
+            }
+        },
+        None => (Vec::new(), None),
+    };
+    let mut levels: Vec<f64> = loudness.iter().map(|w| w.rms_db).filter(|db| *db > SILENCE_DB).collect();
+    levels.sort_by(|a, b| a.total_cmp(b));
+    let dynamic_range_db = (levels.len() >= 2).then(|| percentile(&levels, 0.95) - percentile(&levels, 0.10));
+
+    SessionDynamics {
+        window_secs,
+        note_count: notes.len(),
+        velocity_histogram,
+        mean_velocity,
+        velocity_spread,
+        velocity_range,
+        velocity_windows,
+        loudness_source,
+        loudness,
+        dynamic_range_db,
+    }
+}
+
+fn bucket(velocity: u8) -> usize {
+    (velocity as usize / (128 / VELOCITY_BUCKETS)).min(VELOCITY_BUCKETS - 1)
+}
+
+/// Value at fraction `p` of a sorted, non-empty slice
+fn percentile<T: Copy>(sorted: &[T], p: f64) -> T {
+    sorted[((sorted.len() - 1) as f64 * p).round() as usize]
+}
+
+/// RMS and peak level per window, in dBFS
+fn loudness_curve(path: &Path, window_secs: f64) -> anyhow::Result<Vec<LoudnessWindow>> {
+    let samples = decode_mono(path)?;
+    let window_len = ((window_secs * ANALYSIS_RATE as f64) as usize).max(1);
+    let to_db = |level: f64| 20.0 * level.max(1e-6).log10();
+    Ok(samples
+        .chunks(window_len)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let energy = chunk.iter().map(|s| (*s as f64).powi(2)).sum::<f64>() / chunk.len() as f64;
+            let peak = chunk.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            LoudnessWindow {
+                start_secs: i as f64 * window_secs,
+                rms_db: to_db(energy.sqrt()),
+                peak_db: to_db(peak as f64),
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,82 @@
+// Similarity data export
+//
+// Writes what the similarity map is built from to a CSV file for analysis
+// outside the app (pandas, R, a spreadsheet): one row per session with its
+// title, date and duration, its map coordinates, a k-means cluster and the
+// full summary feature vector — interval histogram, contour trigrams and
+// chroma, the same vector the map's projection is fitted on.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+
+use super::reduction::{SimilarityLayout, SUMMARY_CHROMA, SUMMARY_CONTOURS, SUMMARY_INTERVALS};
+
+/// Most clusters picked automatically
+const MAX_AUTO_CLUSTERS: usize = 12;
+
+/// Session details written next to each row's features
+pub struct ExportSessionInfo {
+    pub title: Option<String>,
+    pub timestamp: String,
+    pub duration_secs: f64,
+}
+
+/// Cluster count used when none is asked for: about sqrt(n / 2)
+pub fn default_cluster_count(sessions: usize) -> usize {
+    ((sessions as f64 / 2.0).sqrt().round() as usize).clamp(1, MAX_AUTO_CLUSTERS)
+}
+
+/// Write every point of the layout to `path`. Returns the number of rows.
+pub fn write_csv(
+    path: &Path,
+    layout: &SimilarityLayout,
+    sessions: &HashMap<String, ExportSessionInfo>,
+    clusters: Option<usize>,
+) -> anyhow::Result<usize> {
+    let entries = layout.entries();
+    let k = clusters.unwrap_or_else(|| default_cluster_count(entries.len()));
+    let assignments = layout.clusters(k);
+
+    let mut out = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut header = vec![
+        "session_id".to_string(),
+        "title".to_string(),
+        "timestamp".to_string(),
+        "duration_secs".to_string(),
+        "x".to_string(),
+        "y".to_string(),
+        "cluster".to_string(),
+    ];
+    header.extend((0..SUMMARY_INTERVALS).map(|i| format!("interval_{}", i as i32 - 12)));
+    header.extend((0..SUMMARY_CONTOURS).map(|i| format!("contour_{}", i)));
+    const PITCH_CLASSES: [&str; 12] = ["C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B"];
+    header.extend(PITCH_CLASSES.iter().take(SUMMARY_CHROMA).map(|pc| format!("chroma_{}", pc)));
+    writeln!(out, "{}", header.join(","))?;
+
+    for (id, vector, [x, y]) in &entries {
+        let info = sessions.get(id);
+        let mut row = vec![
+            csv_field(id),
+            csv_field(info.and_then(|i| i.title.as_deref()).unwrap_or("")),
+            csv_field(info.map(|i| i.timestamp.as_str()).unwrap_or("")),
+            info.map(|i| format!("{:.3}", i.duration_secs)).unwrap_or_default(),
+            format!("{:.6}", x),
+            format!("{:.6}", y),
+            assignments.get(id).map(|c| c.to_string()).unwrap_or_default(),
+        ];
+        row.extend(vector.iter().map(|v| format!("{:.6}", v)));
+        writeln!(out, "{}", row.join(","))?;
+    }
+    out.flush()?;
+    Ok(entries.len())
+}
+
+/// Quote a field if it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,8 +1,13 @@
 // MIDI feature extraction for similarity analysis
 
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use super::melody::{self, MelodyNote};
-use super::midi_parser::{NoteEvent, TempoEvent, tick_to_seconds};
+use super::midi_parser::{self, NoteEvent, TempoEvent, tick_to_seconds};
 
 /// Minimum number of notes required for similarity feature extraction.
 pub const MIN_NOTE_COUNT: usize = 4;
@@ -227,7 +232,7 @@ fn average_harmonic(features: &[&HarmonicFeatures]) -> Option<HarmonicFeatures>
     Some(HarmonicFeatures { chroma, pc_transitions })
 }
 
-fn l1_normalize(arr: &mut [f32]) {
+pub(crate) fn l1_normalize(arr: &mut [f32]) {
     let sum: f32 = arr.iter().sum();
     if sum > 0.0 {
         for v in arr.iter_mut() {
@@ -313,3 +318,71 @@ pub fn extract_chunked_features(
 
     ChunkedFileFeatures { chunks }
 }
+
+// ---- Parallel extraction pipeline ----
+
+/// Returned when a parallel extraction job is cancelled before completion
+#[derive(Debug, thiserror::Error)]
+#[error("Feature extraction cancelled")]
+pub struct ExtractionCancelled;
+
+/// Dedicated worker pool for feature extraction. Leaves one core free so
+/// extraction of a large archive doesn't starve live capture threads.
+fn extraction_pool() -> &'static rayon::ThreadPool {
+    static POOL: OnceLock<rayon::ThreadPool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get().saturating_sub(1))
+            .unwrap_or(1)
+            .max(1);
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("sacho-features-{}", i))
+            .build()
+            .expect("failed to build feature extraction thread pool")
+    })
+}
+
+/// Parse a MIDI file and extract its chunked features.
+pub fn extract_file_features(path: &Path) -> anyhow::Result<ChunkedFileFeatures> {
+    let midi_parser::MidiParseResult { events, ticks_per_beat, tempo_map } = midi_parser::parse_midi(path)?;
+    Ok(extract_chunked_features(&events, ticks_per_beat, &tempo_map))
+}
+
+/// Run `extract` over all `items` on the extraction worker pool.
+///
+/// `on_progress(item, completed, total)` is called after each item finishes.
+/// Workers check `cancel` before starting each item; once it is set, the
+/// remaining items are skipped and `ExtractionCancelled` is returned.
+pub fn extract_parallel<T, R, F, P>(
+    items: &[T],
+    cancel: &AtomicBool,
+    extract: F,
+    on_progress: P,
+) -> Result<Vec<R>, ExtractionCancelled>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    P: Fn(&T, usize, usize) + Sync,
+{
+    let total = items.len();
+    let completed = AtomicUsize::new(0);
+
+    let results: Vec<Option<R>> = extraction_pool().install(|| {
+        items.par_iter().map(|item| {
+            if cancel.load(Ordering::Relaxed) {
+                return None;
+            }
+            let result = extract(item);
+            let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+            on_progress(item, done, total);
+            Some(result)
+        }).collect()
+    });
+
+    if cancel.load(Ordering::Relaxed) {
+        return Err(ExtractionCancelled);
+    }
+    Ok(results.into_iter().flatten().collect())
+}
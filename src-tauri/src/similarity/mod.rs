@@ -3,4 +3,11 @@
 pub mod midi_parser;
 pub mod melody;
 pub mod features;
+pub mod audio_features;
 pub mod scoring;
+pub mod reduction;
+pub mod segments;
+pub mod accuracy;
+pub mod chapters;
+pub mod dynamics;
+pub mod export;
@@ -0,0 +1,347 @@
+// 2D reduction of similarity features for the similarity map view
+//
+// Each file's chunked features are summarized into a single vector and
+// projected onto the top two principal components. The projection basis is
+// only refit when the dataset has grown noticeably since the last fit, so
+// adding one session places a single new point instead of moving the whole map.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::features::ChunkedFileFeatures;
+
+/// Number of power-iteration steps per principal component
+const POWER_ITERATIONS: usize = 50;
+
+/// Minimum number of new points before the basis is refit
+const MIN_REFIT_GROWTH: usize = 4;
+
+/// Lloyd iterations for k-means clustering
+const KMEANS_ITERATIONS: usize = 50;
+
+/// Length of a summary vector, and the column names of its parts
+pub const SUMMARY_INTERVALS: usize = 25;
+pub const SUMMARY_CONTOURS: usize = 125;
+pub const SUMMARY_CHROMA: usize = 12;
+
+/// A single point on the similarity map
+#[derive(Debug, Clone, Serialize)]
+pub struct MapPoint {
+    pub id: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// Projection basis: mean vector, two unit components, and a fixed scale
+/// that maps the fitted points into roughly [-1, 1].
+#[derive(Debug, Clone)]
+struct PcaBasis {
+    mean: Vec<f32>,
+    components: [Vec<f32>; 2],
+    scale: f32,
+}
+
+impl PcaBasis {
+    fn project(&self, v: &[f32]) -> [f32; 2] {
+        let mut out = [0.0f32; 2];
+        for (k, component) in self.components.iter().enumerate() {
+            let dot: f32 = v.iter()
+                .zip(self.mean.iter())
+                .zip(component.iter())
+                .map(|((x, m), c)| (x - m) * c)
+                .sum();
+            out[k] = dot / self.scale;
+        }
+        out
+    }
+}
+
+/// Incrementally maintained 2D layout with a monotonically increasing version.
+#[derive(Default)]
+pub struct SimilarityLayout {
+    version: u64,
+    basis: Option<PcaBasis>,
+    /// Number of vectors the current basis was fit on
+    fitted_count: usize,
+    vectors: HashMap<String, Vec<f32>>,
+    points: HashMap<String, [f32; 2]>,
+}
+
+impl SimilarityLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Current layout version. Bumped on every change to the point set.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// All points in the current layout, sorted by id for stable output
+    pub fn points(&self) -> Vec<MapPoint> {
+        let mut points: Vec<MapPoint> = self.points.iter()
+            .map(|(id, [x, y])| MapPoint { id: id.clone(), x: *x, y: *y })
+            .collect();
+        points.sort_by(|a, b| a.id.cmp(&b.id));
+        points
+    }
+
+    /// Summary vector and map position of every point, sorted by id
+    pub fn entries(&self) -> Vec<(String, Vec<f32>, [f32; 2])> {
+        let mut entries: Vec<(String, Vec<f32>, [f32; 2])> = self.vectors.iter()
+            .map(|(id, v)| (id.clone(), v.clone(), self.points.get(id).copied().unwrap_or([0.0, 0.0])))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+
+    /// Group the summary vectors into `k` clusters (k-means). Returns the
+    /// cluster index of each id; deterministic for the same dataset.
+    pub fn clusters(&self, k: usize) -> HashMap<String, usize> {
+        let mut ids: Vec<&String> = self.vectors.keys().collect();
+        ids.sort();
+        let vectors: Vec<&Vec<f32>> = ids.iter().map(|id| &self.vectors[*id]).collect();
+        kmeans(&vectors, k)
+            .into_iter()
+            .zip(ids)
+            .map(|(cluster, id)| (id.clone(), cluster))
+            .collect()
+    }
+
+    /// Replace the whole dataset and refit the basis.
+    pub fn rebuild(&mut self, items: &[(String, ChunkedFileFeatures)]) {
+        self.vectors = items.iter()
+            .filter_map(|(id, features)| summary_vector(features).map(|v| (id.clone(), v)))
+            .collect();
+        self.refit();
+        self.version += 1;
+    }
+
+    /// Add or replace a single entry. Existing points keep their coordinates
+    /// unless the dataset has grown enough to warrant a refit.
+    pub fn insert(&mut self, id: &str, features: &ChunkedFileFeatures) {
+        let Some(vector) = summary_vector(features) else {
+            self.remove(id);
+            return;
+        };
+        self.vectors.insert(id.to_string(), vector);
+
+        let growth_threshold = self.fitted_count + MIN_REFIT_GROWTH.max(self.fitted_count / 4);
+        if self.vectors.len() < growth_threshold {
+            if let Some(basis) = &self.basis {
+                let point = basis.project(&self.vectors[id]);
+                self.points.insert(id.to_string(), point);
+                self.version += 1;
+                return;
+            }
+        }
+        self.refit();
+        self.version += 1;
+    }
+
+    /// Remove an entry. Returns true if the layout changed.
+    pub fn remove(&mut self, id: &str) -> bool {
+        let removed = self.vectors.remove(id).is_some();
+        self.points.remove(id);
+        if removed {
+            self.version += 1;
+        }
+        removed
+    }
+
+    /// Move an entry to a new id without changing its position.
+    pub fn rename(&mut self, old_id: &str, new_id: &str) -> bool {
+        let Some(vector) = self.vectors.remove(old_id) else {
+            return false;
+        };
+        self.vectors.insert(new_id.to_string(), vector);
+        if let Some(point) = self.points.remove(old_id) {
+            self.points.insert(new_id.to_string(), point);
+        }
+        self.version += 1;
+        true
+    }
+
+    /// Fit a new basis on all vectors and reproject every point.
+    /// Component signs are aligned with the previous basis so the map
+    /// doesn't mirror itself between refits.
+    fn refit(&mut self) {
+        let vectors: Vec<&Vec<f32>> = self.vectors.values().collect();
+        self.basis = fit_basis(&vectors, self.basis.as_ref());
+        self.fitted_count = self.vectors.len();
+        self.points.clear();
+        if let Some(basis) = &self.basis {
+            for (id, v) in &self.vectors {
+                self.points.insert(id.clone(), basis.project(v));
+            }
+        }
+    }
+}
+
+/// Summarize chunked features into one vector: chunk-averaged interval
+/// histogram, contour trigrams, and chroma. Missing parts are left at zero.
+fn summary_vector(features: &ChunkedFileFeatures) -> Option<Vec<f32>> {
+    const INTERVALS: usize = SUMMARY_INTERVALS;
+    const CONTOURS: usize = SUMMARY_CONTOURS;
+    const CHROMA: usize = SUMMARY_CHROMA;
+
+    let mut sum = vec![0.0f32; INTERVALS + CONTOURS + CHROMA];
+    let mut count = 0usize;
+    for chunk in &features.chunks {
+        if chunk.melodic.is_none() && chunk.harmonic.is_none() {
+            continue;
+        }
+        if let Some(m) = &chunk.melodic {
+            for (i, v) in m.interval_histogram.iter().take(INTERVALS).enumerate() { sum[i] += v; }
+            for (i, v) in m.contour_trigrams.iter().take(CONTOURS).enumerate() { sum[INTERVALS + i] += v; }
+        }
+        if let Some(h) = &chunk.harmonic {
+            for (i, v) in h.chroma.iter().take(CHROMA).enumerate() { sum[INTERVALS + CONTOURS + i] += v; }
+        }
+        count += 1;
+    }
+
+    if count == 0 {
+        return None;
+    }
+    for v in &mut sum {
+        *v /= count as f32;
+    }
+    Some(sum)
+}
+
+/// Cluster index per vector. Seeds are picked farthest-first from the
+/// first vector, so the result doesn't change between runs.
+fn kmeans(vectors: &[&Vec<f32>], k: usize) -> Vec<usize> {
+    let k = k.min(vectors.len());
+    if k <= 1 {
+        return vec![0; vectors.len()];
+    }
+    let distance = |a: &[f32], b: &[f32]| -> f32 { a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum() };
+    let nearest = |v: &[f32], centroids: &[Vec<f32>]| -> usize {
+        centroids.iter()
+            .enumerate()
+            .map(|(i, c)| (i, distance(v, c)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    };
+
+    let mut centroids: Vec<Vec<f32>> = vec![vectors[0].clone()];
+    while centroids.len() < k {
+        let farthest = vectors.iter()
+            .max_by(|a, b| {
+                let da = centroids.iter().map(|c| distance(a, c)).fold(f32::MAX, f32::min);
+                let db = centroids.iter().map(|c| distance(b, c)).fold(f32::MAX, f32::min);
+                da.total_cmp(&db)
+            })
+            .expect("vectors is non-empty");
+        centroids.push((*farthest).clone());
+    }
+
+    let dim = vectors[0].len();
+    let mut assignments = vec![0usize; vectors.len()];
+    for _ in 0..KMEANS_ITERATIONS {
+        let next: Vec<usize> = vectors.iter().map(|v| nearest(v, &centroids)).collect();
+        let converged = next == assignments;
+        assignments = next;
+
+        let mut sums = vec![vec![0.0f32; dim]; k];
+        let mut counts = vec![0usize; k];
+        for (v, &cluster) in vectors.iter().zip(&assignments) {
+            for (s, x) in sums[cluster].iter_mut().zip(v.iter()) { *s += x; }
+            counts[cluster] += 1;
+        }
+        for ((centroid, sum), count) in centroids.iter_mut().zip(sums).zip(counts) {
+            // An empty cluster keeps its old centroid
+            if count > 0 {
+                *centroid = sum.into_iter().map(|s| s / count as f32).collect();
+            }
+        }
+        if converged {
+            break;
+        }
+    }
+    assignments
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+}
+
+fn normalize(v: &mut [f32]) -> f32 {
+    let norm = dot(v, v).sqrt();
+    if norm > 0.0 {
+        for x in v.iter_mut() {
+            *x /= norm;
+        }
+    }
+    norm
+}
+
+/// Fit the top two principal components with power iteration on the
+/// (implicit) covariance matrix.
+fn fit_basis(vectors: &[&Vec<f32>], previous: Option<&PcaBasis>) -> Option<PcaBasis> {
+    let dim = vectors.first()?.len();
+    let n = vectors.len() as f32;
+
+    let mut mean = vec![0.0f32; dim];
+    for v in vectors {
+        for (i, x) in v.iter().enumerate() { mean[i] += x; }
+    }
+    for m in &mut mean { *m /= n; }
+
+    let centered: Vec<Vec<f32>> = vectors.iter()
+        .map(|v| v.iter().zip(mean.iter()).map(|(x, m)| x - m).collect())
+        .collect();
+
+    let mut components: [Vec<f32>; 2] = [vec![0.0; dim], vec![0.0; dim]];
+    for k in 0..2 {
+        // Start from the previous component when available for faster, stable convergence
+        let mut v: Vec<f32> = match previous {
+            Some(p) if p.components[k].len() == dim && dot(&p.components[k], &p.components[k]) > 0.0 => {
+                p.components[k].clone()
+            }
+            _ => (0..dim).map(|i| ((i * 7 + k * 13) % 11) as f32 + 1.0).collect(),
+        };
+        normalize(&mut v);
+
+        for _ in 0..POWER_ITERATIONS {
+            let mut w = vec![0.0f32; dim];
+            for row in &centered {
+                let proj = dot(row, &v);
+                for (i, x) in row.iter().enumerate() { w[i] += proj * x; }
+            }
+            // Deflate against earlier components
+            for prev in components.iter().take(k) {
+                let p = dot(&w, prev);
+                for (i, x) in prev.iter().enumerate() { w[i] -= p * x; }
+            }
+            if normalize(&mut w) == 0.0 {
+                v = vec![0.0; dim];
+                break;
+            }
+            v = w;
+        }
+
+        if let Some(p) = previous {
+            if p.components[k].len() == dim && dot(&v, &p.components[k]) < 0.0 {
+                for x in v.iter_mut() { *x = -*x; }
+            }
+        }
+        components[k] = v;
+    }
+
+    let mut basis = PcaBasis { mean, components, scale: 1.0 };
+    let max_abs = vectors.iter()
+        .map(|v| {
+            let [x, y] = basis.project(v);
+            x.abs().max(y.abs())
+        })
+        .fold(0.0f32, f32::max);
+    if max_abs > 0.0 {
+        basis.scale = max_abs;
+    }
+    Some(basis)
+}
@@ -0,0 +1,181 @@
+// Practice-loop detection within one session
+//
+// Practising usually means playing a passage, stopping, and starting it
+// again. Onsets are quantized into a skyline (highest pitch per grid slot);
+// every onset that follows a pause is a candidate restart, keyed by the
+// pitches of the notes that follow it. Keys that recur are passages, and
+// each occurrence is one attempt, ending where the next attempt starts or
+// the playing stops.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+use serde::Serialize;
+
+use super::midi_parser;
+
+/// Onset quantization grid
+const QUANTUM_SECS: f64 = 0.03;
+
+/// Silence after all notes are released that counts as a pause
+const RESTART_SILENCE_SECS: f64 = 0.3;
+
+/// Gap between onsets that counts as a pause even with notes held
+const RESTART_ONSET_GAP_SECS: f64 = 1.0;
+
+/// Silence that ends an attempt even without a following restart
+const ATTEMPT_END_GAP_SECS: f64 = 2.0;
+
+/// Number of opening pitches that identify a passage
+const PATTERN_LEN: usize = 6;
+
+/// A passage must be started at least this many times
+const MIN_ATTEMPTS: usize = 2;
+
+/// Cap on distinct passages reported per session
+const MAX_PASSAGES: usize = 8;
+
+/// Attempts with at least this share of the longest attempt's notes are complete
+const COMPLETE_RATIO: f64 = 0.9;
+
+/// One attempt at a practised passage
+#[derive(Debug, Clone, Serialize)]
+pub struct PracticeSegment {
+    /// Passage index, in order of first appearance
+    pub passage: usize,
+    /// Attempt number within the passage, from 1
+    pub attempt: usize,
+    pub start_secs: f64,
+    pub end_secs: f64,
+    pub note_count: usize,
+    /// Whether this attempt got about as far as the longest one
+    pub complete: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Onset {
+    time: f64,
+    /// Latest release among the notes in this slot
+    end: f64,
+    pitch: u8,
+}
+
+/// Detect practice attempts across the session's MIDI files
+pub fn detect_practice_segments(midi_paths: &[&Path]) -> Vec<PracticeSegment> {
+    let mut notes: Vec<(f64, f64, u8)> = Vec::new();
+    for path in midi_paths {
+        let parsed = match midi_parser::parse_midi(path) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                log::warn!("Practice segments: failed to parse {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        for note in &parsed.events {
+            let start = midi_parser::tick_to_seconds(note.start_tick, parsed.ticks_per_beat, &parsed.tempo_map);
+            let end = midi_parser::tick_to_seconds(
+                note.start_tick + note.duration_ticks,
+                parsed.ticks_per_beat,
+                &parsed.tempo_map,
+            );
+            notes.push((start, end, note.pitch));
+        }
+    }
+    find_segments(&skyline(&notes))
+}
+
+/// Highest pitch per quantized onset slot
+fn skyline(notes: &[(f64, f64, u8)]) -> Vec<Onset> {
+    let mut slots: BTreeMap<i64, Onset> = BTreeMap::new();
+    for &(start, end, pitch) in notes {
+        let slot = (start / QUANTUM_SECS).round() as i64;
+        let onset = slots.entry(slot).or_insert(Onset { time: start, end, pitch });
+        onset.end = onset.end.max(end);
+        if pitch > onset.pitch {
+            onset.pitch = pitch;
+        }
+        onset.time = onset.time.min(start);
+    }
+    slots.into_values().collect()
+}
+
+fn find_segments(onsets: &[Onset]) -> Vec<PracticeSegment> {
+    if onsets.len() < PATTERN_LEN * MIN_ATTEMPTS {
+        return Vec::new();
+    }
+
+    // Pauses: before onset i, nothing was sounding for a while (or nothing new began)
+    let mut paused = vec![false; onsets.len()];
+    paused[0] = true;
+    let mut released_at = onsets[0].end;
+    for i in 1..onsets.len() {
+        let silence = onsets[i].time - released_at;
+        let onset_gap = onsets[i].time - onsets[i - 1].time;
+        paused[i] = silence >= RESTART_SILENCE_SECS || onset_gap >= RESTART_ONSET_GAP_SECS;
+        released_at = released_at.max(onsets[i].end);
+    }
+
+    // Candidate restarts keyed by their opening pitches
+    let mut starts_by_pattern: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    let pitches: Vec<u8> = onsets.iter().map(|o| o.pitch).collect();
+    for i in 0..=(onsets.len() - PATTERN_LEN) {
+        if paused[i] {
+            starts_by_pattern.entry(&pitches[i..i + PATTERN_LEN]).or_default().push(i);
+        }
+    }
+
+    let mut patterns: Vec<Vec<usize>> = starts_by_pattern
+        .into_values()
+        .filter(|starts| starts.len() >= MIN_ATTEMPTS)
+        .collect();
+    // Most-repeated first; ties go to the earlier passage
+    patterns.sort_by(|a, b| b.len().cmp(&a.len()).then(a[0].cmp(&b[0])));
+    patterns.truncate(MAX_PASSAGES);
+    if patterns.is_empty() {
+        return Vec::new();
+    }
+    // Number passages by first appearance
+    patterns.sort_by_key(|starts| starts[0]);
+
+    let mut starts: Vec<(usize, usize)> = patterns
+        .iter()
+        .enumerate()
+        .flat_map(|(passage, starts)| starts.iter().map(move |&i| (i, passage)))
+        .collect();
+    starts.sort();
+    let start_set: HashSet<usize> = starts.iter().map(|(i, _)| *i).collect();
+
+    let mut segments = Vec::with_capacity(starts.len());
+    let mut attempt_counts = vec![0usize; patterns.len()];
+    for &(first, passage) in &starts {
+        // Run until the next attempt or a long silence
+        let mut last = first;
+        let mut end = onsets[first].end;
+        for i in (first + 1)..onsets.len() {
+            if start_set.contains(&i) || onsets[i].time - end >= ATTEMPT_END_GAP_SECS {
+                break;
+            }
+            last = i;
+            end = end.max(onsets[i].end);
+        }
+        attempt_counts[passage] += 1;
+        segments.push(PracticeSegment {
+            passage,
+            attempt: attempt_counts[passage],
+            start_secs: onsets[first].time,
+            end_secs: end,
+            note_count: last - first + 1,
+            complete: false,
+        });
+    }
+
+    let mut longest = vec![0usize; patterns.len()];
+    for segment in &segments {
+        longest[segment.passage] = longest[segment.passage].max(segment.note_count);
+    }
+    for segment in &mut segments {
+        segment.complete = segment.note_count as f64 >= longest[segment.passage] as f64 * COMPLETE_RATIO;
+    }
+
+    segments
+}
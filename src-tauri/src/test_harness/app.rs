@@ -70,7 +70,10 @@ impl TestApp {
             })
             .invoke_handler(tauri::generate_handler![
                 crate::commands::get_audio_devices,
+                crate::commands::get_sample_rate_warnings,
                 crate::commands::get_midi_devices,
+                crate::commands::get_midi_output_ports,
+                crate::commands::get_supported_midi_access_modes,
                 crate::commands::get_video_devices,
                 crate::commands::validate_video_device_config,
                 crate::commands::get_recording_state,
@@ -79,6 +82,7 @@ impl TestApp {
                 crate::commands::get_sessions,
                 crate::commands::get_session_detail,
                 crate::commands::repair_session,
+                crate::commands::rebuild_session_metadata,
                 crate::commands::delete_session,
                 crate::commands::update_session_notes,
                 crate::commands::get_config,
@@ -101,6 +105,8 @@ impl TestApp {
                 crate::commands::get_autostart_info,
                 crate::commands::set_all_users_autostart,
                 crate::commands::simulate_crash,
+                crate::commands::get_pending_crash_reports,
+                crate::commands::dismiss_crash_report,
                 crate::commands::get_app_stats,
             ])
             .build(tauri::generate_context!())
@@ -1,6 +1,8 @@
 use serde::Deserialize;
 use std::path::Path;
 
+use super::fake_devices::{FakeDeviceSet, FAKE_MIDI_LABEL, FAKE_VIDEO_LABEL};
+
 // ── Device config types ──────────────────────────────────────────────
 
 #[derive(Debug, Clone, Deserialize)]
@@ -177,21 +179,47 @@ pub fn load_device_config(dir: &Path) -> TestDeviceConfig {
     }
 }
 
+/// Device config for an installed `FakeDeviceSet`, already resolved.
+/// Uses the first simulated device of each kind.
+pub fn fake_device_config(set: &FakeDeviceSet) -> TestDeviceConfig {
+    TestDeviceConfig {
+        midi: set.midi.first().map(|d| MidiTestDevice {
+            label: FAKE_MIDI_LABEL.to_string(),
+            name_contains: d.name.clone(),
+            role: MidiRole::TriggerAndRecord,
+            resolved_id: Some(d.id.clone()),
+            resolved_name: Some(d.name.clone()),
+        }).into_iter().collect(),
+        // The monitor identifies audio inputs by name
+        audio: set.audio.first().map(|d| AudioTestDevice {
+            label: "fake-audio".to_string(),
+            name_contains: d.name.clone(),
+            resolved_id: Some(d.name.clone()),
+            resolved_name: Some(d.name.clone()),
+        }).into_iter().collect(),
+        video: set.video.first().map(|d| VideoTestDevice {
+            label: FAKE_VIDEO_LABEL.to_string(),
+            name_contains: d.name.clone(),
+            resolved_id: Some(d.id.clone()),
+            resolved_name: Some(d.name.clone()),
+        }).into_iter().collect(),
+        settings: TestSettings::default(),
+    }
+}
+
 /// Resolve declared devices against actual hardware.
 /// Fills resolved_id/resolved_name for each device found on the system.
 pub fn resolve_devices(config: &mut TestDeviceConfig) {
     // Resolve MIDI devices via midir
     if let Ok(midi_in) = midir::MidiInput::new("sacho-test-discovery") {
-        let ports = midi_in.ports();
-        for (idx, port) in ports.iter().enumerate() {
-            if let Ok(name) = midi_in.port_name(port) {
-                for dev in &mut config.midi {
-                    if dev.resolved_id.is_none()
-                        && name.to_lowercase().contains(&dev.name_contains.to_lowercase())
-                    {
-                        dev.resolved_id = Some(format!("midi-{}", idx));
-                        dev.resolved_name = Some(name.clone());
-                    }
+        let names: Vec<String> = midi_in.ports().iter().filter_map(|port| midi_in.port_name(port).ok()).collect();
+        for (name, id) in names.iter().zip(crate::devices::midi_port_ids(&names)) {
+            for dev in &mut config.midi {
+                if dev.resolved_id.is_none()
+                    && name.to_lowercase().contains(&dev.name_contains.to_lowercase())
+                {
+                    dev.resolved_id = Some(id.clone());
+                    dev.resolved_name = Some(name.clone());
                 }
             }
         }
@@ -0,0 +1,450 @@
+// Simulated devices for hardware-free integration tests
+//
+// Installing a `FakeDeviceSet` makes its devices show up everywhere real
+// devices do: the enumerators in `devices::enumeration`, the health checker's
+// name lists, and the video source lookup. The MIDI monitor then connects to
+// them through the same handlers it uses for real ports, so a test exercises
+// trigger -> pre-roll -> record -> finalize exactly as with hardware:
+// - MIDI: `FakeMidiSender` delivers messages straight into the connected
+//   input handler (no loopback driver needed).
+// - Audio: a paced thread feeds sine, noise or silence blocks into the same
+//   sample callback cpal would call.
+// - Video: a live `videotestsrc` stands in for the camera source element.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use gstreamer as gst;
+use parking_lot::{Mutex, RwLock};
+
+use crate::devices::{AudioDevice, CodecCapability, MidiDevice, VideoDevice};
+
+use super::midi_sender::TestMidiSender;
+
+/// Label used for the simulated keyboard in a `TestDeviceConfig`
+pub const FAKE_MIDI_LABEL: &str = "fake-midi";
+/// Label used for the simulated camera in a `TestDeviceConfig`
+pub const FAKE_VIDEO_LABEL: &str = "fake-video";
+
+/// Length of each simulated audio callback block
+const AUDIO_BLOCK: Duration = Duration::from_millis(10);
+
+/// The installed fake devices, if any
+static INSTALLED: RwLock<Option<FakeDeviceSet>> = RwLock::new(None);
+
+/// Handlers of open fake MIDI connections, keyed by connection id
+static MIDI_LISTENERS: Mutex<Vec<MidiListener>> = Mutex::new(Vec::new());
+static NEXT_LISTENER_ID: AtomicU64 = AtomicU64::new(0);
+
+type MidiHandler = Arc<Mutex<Box<dyn FnMut(u64, &[u8]) + Send>>>;
+
+struct MidiListener {
+    id: u64,
+    port_name: String,
+    handler: MidiHandler,
+}
+
+// ── Device descriptions ──────────────────────────────────────────────
+
+/// Waveform produced by a fake audio input
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FakeSignal {
+    Sine { frequency: f32, amplitude: f32 },
+    /// Uniform white noise
+    Noise { amplitude: f32 },
+    Silence,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeMidiDevice {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeAudioDevice {
+    pub name: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+    pub signal: FakeSignal,
+}
+
+#[derive(Debug, Clone)]
+pub struct FakeVideoDevice {
+    pub id: String,
+    pub name: String,
+    /// Raw pixel formats offered (e.g. "I420", "YUY2")
+    pub formats: Vec<String>,
+    /// (width, height), highest first
+    pub resolutions: Vec<(u32, u32)>,
+    pub fps: f64,
+    /// videotestsrc pattern nick (e.g. "smpte", "ball")
+    pub pattern: String,
+}
+
+/// A set of simulated devices to install for a test run
+#[derive(Debug, Clone, Default)]
+pub struct FakeDeviceSet {
+    pub midi: Vec<FakeMidiDevice>,
+    pub audio: Vec<FakeAudioDevice>,
+    pub video: Vec<FakeVideoDevice>,
+}
+
+impl FakeDeviceSet {
+    /// One keyboard, one stereo sine input and one camera
+    pub fn standard() -> Self {
+        Self {
+            midi: vec![FakeMidiDevice {
+                id: "fake-midi-0".into(),
+                name: "Sacho Fake Keyboard".into(),
+            }],
+            audio: vec![FakeAudioDevice {
+                name: "Sacho Fake Sine".into(),
+                sample_rate: 48000,
+                channels: 2,
+                signal: FakeSignal::Sine { frequency: 440.0, amplitude: 0.5 },
+            }],
+            video: vec![FakeVideoDevice {
+                id: "fake-video-0".into(),
+                name: "Sacho Fake Camera".into(),
+                formats: vec!["I420".into(), "YUY2".into(), "NV12".into()],
+                resolutions: vec![(1280, 720), (640, 480)],
+                fps: 30.0,
+                pattern: "ball".into(),
+            }],
+        }
+    }
+}
+
+/// Make the given devices visible to enumeration and the monitor.
+/// Replaces any previously installed set.
+pub fn install(set: FakeDeviceSet) {
+    println!(
+        "  FakeDevices: installed {} MIDI, {} audio, {} video",
+        set.midi.len(),
+        set.audio.len(),
+        set.video.len()
+    );
+    *INSTALLED.write() = Some(set);
+}
+
+pub fn uninstall() {
+    *INSTALLED.write() = None;
+}
+
+pub fn is_installed() -> bool {
+    INSTALLED.read().is_some()
+}
+
+// ── Enumeration hooks ────────────────────────────────────────────────
+
+pub fn midi_devices() -> Vec<FakeMidiDevice> {
+    INSTALLED.read().as_ref().map(|s| s.midi.clone()).unwrap_or_default()
+}
+
+pub fn audio_sources() -> Vec<FakeAudioDevice> {
+    INSTALLED.read().as_ref().map(|s| s.audio.clone()).unwrap_or_default()
+}
+
+pub fn video_sources() -> Vec<FakeVideoDevice> {
+    INSTALLED.read().as_ref().map(|s| s.video.clone()).unwrap_or_default()
+}
+
+/// Fake MIDI devices as `MidiDevice`s (port_index is past any real port)
+pub fn enumerate_midi() -> Vec<MidiDevice> {
+    midi_devices()
+        .into_iter()
+        .enumerate()
+        .map(|(i, d)| MidiDevice {
+            id: d.id,
+            name: d.name,
+            port_index: usize::MAX - i,
+            alias: None,
+            bluetooth: false,
+            default_latency_ms: None,
+        })
+        .collect()
+}
+
+/// Fake audio inputs as `AudioDevice`s (audio IDs are device names)
+pub fn enumerate_audio() -> Vec<AudioDevice> {
+    audio_sources()
+        .into_iter()
+        .map(|d| AudioDevice {
+            id: d.name.clone(),
+            name: d.name,
+            channels: d.channels,
+            sample_rate: d.sample_rate,
+            is_default: false,
+            alias: None,
+            native_bit_depth: None,
+        })
+        .collect()
+}
+
+/// Fake cameras as `VideoDevice`s
+pub fn enumerate_video() -> Vec<VideoDevice> {
+    video_sources()
+        .into_iter()
+        .map(|d| {
+            let modes: Vec<CodecCapability> = d
+                .resolutions
+                .iter()
+                .map(|&(width, height)| CodecCapability {
+                    width,
+                    height,
+                    framerates: vec![d.fps],
+                })
+                .collect();
+            let capabilities: HashMap<String, Vec<CodecCapability>> =
+                d.formats.iter().map(|f| (f.clone(), modes.clone())).collect();
+            VideoDevice {
+                id: d.id,
+                name: d.name,
+                capabilities,
+                usb_path: None,
+                alias: None,
+            }
+        })
+        .collect()
+}
+
+pub fn midi_port_names() -> Vec<String> {
+    midi_devices().into_iter().map(|d| d.name).collect()
+}
+
+pub fn audio_device_names() -> Vec<String> {
+    audio_sources().into_iter().map(|d| d.name).collect()
+}
+
+pub fn video_device_names() -> Vec<String> {
+    video_sources().into_iter().map(|d| d.name).collect()
+}
+
+/// `Some(valid)` if `device_id` is a fake camera, `None` for real devices
+pub fn validate_video_config(device_id: &str, format: &str, width: u32, height: u32, fps: f64) -> Option<bool> {
+    let sources = video_sources();
+    let device = sources.iter().find(|d| d.id == device_id)?;
+    Some(
+        device.formats.iter().any(|f| f == format)
+            && device.resolutions.contains(&(width, height))
+            && (device.fps - fps).abs() < 0.01,
+    )
+}
+
+/// Source element for a fake camera: a live videotestsrc, so timestamps
+/// follow the pipeline clock like a real capture device.
+/// Returns None if `device_id` is not a fake camera.
+pub fn create_video_source(device_id: &str) -> Option<(gst::Element, String)> {
+    let sources = video_sources();
+    let device = sources.iter().find(|d| d.id == device_id)?;
+    let source = gst::ElementFactory::make("videotestsrc")
+        .property("is-live", true)
+        .property_from_str("pattern", &device.pattern)
+        .build()
+        .ok()?;
+    println!("[Video] Using simulated camera '{}' (videotestsrc)", device.name);
+    Some((source, device.name.clone()))
+}
+
+// ── Audio ────────────────────────────────────────────────────────────
+
+/// A running fake audio input. Dropping it stops the feeder thread.
+pub struct FakeAudioStream {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeAudioStream {
+    /// Start delivering interleaved f32 blocks of `device.signal` to
+    /// `callback`, paced in real time.
+    pub fn start<F>(device: &FakeAudioDevice, mut callback: F) -> Self
+    where
+        F: FnMut(&[f32]) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut generator = SignalGenerator::new(device.signal, device.sample_rate);
+        let channels = device.channels as usize;
+        let frames_per_block =
+            (device.sample_rate as u64 * AUDIO_BLOCK.as_millis() as u64 / 1000) as usize;
+
+        let thread = {
+            let stop = stop.clone();
+            std::thread::Builder::new()
+                .name("sacho-fake-audio".into())
+                .spawn(move || {
+                    let mut block = vec![0.0f32; frames_per_block * channels];
+                    let started = Instant::now();
+                    let mut blocks_sent: u32 = 0;
+                    while !stop.load(Ordering::Relaxed) {
+                        for frame in block.chunks_mut(channels) {
+                            frame.fill(generator.next_sample());
+                        }
+                        callback(&block);
+                        blocks_sent += 1;
+                        // Schedule against the start time so pacing doesn't drift
+                        let due = AUDIO_BLOCK * blocks_sent;
+                        if let Some(wait) = due.checked_sub(started.elapsed()) {
+                            std::thread::sleep(wait);
+                        }
+                    }
+                })
+                .expect("Failed to spawn fake audio thread")
+        };
+
+        Self {
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for FakeAudioStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct SignalGenerator {
+    signal: FakeSignal,
+    sample_rate: f32,
+    phase: f32,
+    noise_state: u32,
+}
+
+impl SignalGenerator {
+    fn new(signal: FakeSignal, sample_rate: u32) -> Self {
+        Self {
+            signal,
+            sample_rate: sample_rate as f32,
+            phase: 0.0,
+            noise_state: 0x9E37_79B9,
+        }
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        match self.signal {
+            FakeSignal::Sine { frequency, amplitude } => {
+                let sample = (self.phase * std::f32::consts::TAU).sin() * amplitude;
+                self.phase = (self.phase + frequency / self.sample_rate).fract();
+                sample
+            }
+            FakeSignal::Noise { amplitude } => {
+                // xorshift32: deterministic, so runs are reproducible
+                let mut x = self.noise_state;
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                self.noise_state = x;
+                (x as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+            }
+            FakeSignal::Silence => 0.0,
+        }
+    }
+}
+
+// ── MIDI ─────────────────────────────────────────────────────────────
+
+/// Microseconds since the first fake MIDI message, standing in for the
+/// driver timestamp midir would provide
+fn midi_timestamp_us() -> u64 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_micros() as u64
+}
+
+/// An open connection to a fake MIDI port. Dropping it disconnects.
+pub struct FakeMidiConnection {
+    id: u64,
+}
+
+impl FakeMidiConnection {
+    pub fn connect<F>(port_name: &str, handler: F) -> Self
+    where
+        F: FnMut(u64, &[u8]) + Send + 'static,
+    {
+        let id = NEXT_LISTENER_ID.fetch_add(1, Ordering::Relaxed);
+        MIDI_LISTENERS.lock().push(MidiListener {
+            id,
+            port_name: port_name.to_string(),
+            handler: Arc::new(Mutex::new(Box::new(handler))),
+        });
+        Self { id }
+    }
+}
+
+impl Drop for FakeMidiConnection {
+    fn drop(&mut self) {
+        MIDI_LISTENERS.lock().retain(|l| l.id != self.id);
+    }
+}
+
+/// Sends MIDI messages directly into the monitor's handlers for a fake port
+pub struct FakeMidiSender {
+    port_name: String,
+}
+
+impl FakeMidiSender {
+    /// Sender for the installed fake MIDI device whose name contains
+    /// `name_contains`
+    pub fn connect(name_contains: &str) -> Option<Self> {
+        let needle = name_contains.to_lowercase();
+        let device = midi_devices()
+            .into_iter()
+            .find(|d| d.name.to_lowercase().contains(&needle))?;
+        println!("  FakeMidiSender: sending to '{}'", device.name);
+        Some(Self { port_name: device.name })
+    }
+}
+
+impl TestMidiSender for FakeMidiSender {
+    fn send_message(&mut self, message: &[u8]) {
+        // Clone the handlers out so a handler that stops the monitor (and
+        // drops its connection) doesn't deadlock on the registry
+        let handlers: Vec<MidiHandler> = MIDI_LISTENERS
+            .lock()
+            .iter()
+            .filter(|l| l.port_name == self.port_name)
+            .map(|l| l.handler.clone())
+            .collect();
+        let timestamp_us = midi_timestamp_us();
+        for handler in handlers {
+            (handler.lock())(timestamp_us, message);
+        }
+    }
+}
+
+/// Send a pseudo-random melody (notes within `low..=high`) for `total`,
+/// one note every `interval`. Deterministic for a given seed.
+pub fn play_random_notes(
+    sender: &mut dyn TestMidiSender,
+    seed: u32,
+    low: u8,
+    high: u8,
+    interval: Duration,
+    total: Duration,
+) -> Vec<u8> {
+    let mut state = seed.max(1);
+    let mut played = Vec::new();
+    let span = (high.saturating_sub(low) as u32) + 1;
+    let hold = interval.mul_f32(0.6);
+    let start = Instant::now();
+    while start.elapsed() + interval <= total {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        let note = low + (state % span) as u8;
+        let velocity = 60 + (state >> 8) as u8 % 60;
+        sender.note_on(0, note, velocity);
+        std::thread::sleep(hold);
+        sender.note_off(0, note);
+        std::thread::sleep(interval - hold);
+        played.push(note);
+    }
+    played
+}
@@ -1,5 +1,6 @@
 pub mod app;
 pub mod discovery;
+pub mod fake_devices;
 pub mod midi_sender;
 pub mod permutations;
 pub mod runner;
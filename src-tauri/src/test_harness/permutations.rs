@@ -2,6 +2,7 @@ use crate::config::{AudioFormat, Config};
 use crate::devices::enumeration::enumerate_video_devices;
 
 use super::discovery::TestDeviceConfig;
+use super::fake_devices::FAKE_MIDI_LABEL;
 use super::runner::{Expected, TestCase, TriggerMode};
 
 /// Build the full test matrix from discovered devices.
@@ -9,7 +10,9 @@ use super::runner::{Expected, TestCase, TriggerMode};
 pub fn build_test_matrix(devices: &TestDeviceConfig) -> Vec<TestCase> {
     let mut tests = Vec::new();
 
-    let loopbe = devices.midi_by_label("loopbe1");
+    let loopbe = devices
+        .midi_by_label("loopbe1")
+        .or_else(|| devices.midi_by_label(FAKE_MIDI_LABEL));
     let first_audio = devices.first_audio();
     let video_devices = devices.resolved_video_devices();
 
@@ -206,7 +209,8 @@ pub fn build_test_matrix(devices: &TestDeviceConfig) -> Vec<TestCase> {
 
 fn base_config(pre_roll: u32, idle_timeout: u32) -> Config {
     let mut config = Config::default();
-    config.pre_roll_secs = pre_roll;
+    config.audio_midi_pre_roll_secs = pre_roll;
+    config.video_pre_roll_secs = pre_roll;
     config.idle_timeout_secs = idle_timeout;
     config
 }
@@ -5,7 +5,8 @@ use crate::recording::RecordingStatus;
 
 use super::app::TestApp;
 use super::discovery::TestSettings;
-use super::midi_sender::MidiSender;
+use super::fake_devices::FakeMidiSender;
+use super::midi_sender::{MidiSender, TestMidiSender};
 use super::validators;
 
 // ── Test case types ──────────────────────────────────────────────────
@@ -13,7 +14,8 @@ use super::validators;
 /// How the test triggers recording.
 #[derive(Debug, Clone)]
 pub enum TriggerMode {
-    /// Send MIDI via loopback device with given name_contains.
+    /// Send MIDI to the device whose name contains `loopback_name_contains`:
+    /// a simulated keyboard if one matches, otherwise a loopback port.
     Midi { loopback_name_contains: String },
     /// Use manual_start_recording / manual_stop_recording.
     Manual,
@@ -79,7 +81,13 @@ pub fn run_test(case: &TestCase, keep_sessions: bool) -> TestResult {
     // 4. Trigger recording
     match &case.trigger {
         TriggerMode::Midi { loopback_name_contains } => {
-            let mut sender = match MidiSender::connect(loopback_name_contains) {
+            let sender: Option<Box<dyn TestMidiSender>> =
+                match FakeMidiSender::connect(loopback_name_contains) {
+                    Some(fake) => Some(Box::new(fake)),
+                    None => MidiSender::connect(loopback_name_contains)
+                        .map(|s| Box::new(s) as Box<dyn TestMidiSender>),
+                };
+            let mut sender = match sender {
                 Some(s) => s,
                 None => {
                     app.stop_monitor();
@@ -7,13 +7,25 @@ use tauri::{
     AppHandle,
     Manager, Runtime,
     tray::{TrayIconBuilder, MouseButton, MouseButtonState},
-    menu::{Menu, MenuItem},
+    menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
 };
+use crate::config::Config;
+use crate::i18n::{t, Text};
+use crate::privacy::{self, PauseLength};
+use parking_lot::RwLock;
 
 /// Holds references to tray menu items that need dynamic enable/disable
+/// or relabelling when the language changes
 pub struct TrayMenuItems<R: Runtime> {
+    pub open: MenuItem<R>,
     pub start: MenuItem<R>,
     pub stop: MenuItem<R>,
+    pub quit: MenuItem<R>,
+    pub privacy_menu: Submenu<R>,
+    pub privacy_30m: MenuItem<R>,
+    pub privacy_2h: MenuItem<R>,
+    pub privacy_tomorrow: MenuItem<R>,
+    pub privacy_resume: MenuItem<R>,
 }
 
 /// Tray icon state
@@ -39,15 +51,34 @@ impl From<RecordingStatus> for TrayState {
 /// Create and configure the system tray
 pub fn setup_tray(app: &AppHandle) -> anyhow::Result<()> {
     // Create menu items
-    let open_item = MenuItem::with_id(app, "open", "Open Sacho", true, None::<&str>)?;
-    let start_item = MenuItem::with_id(app, "start", "Start Recording", true, None::<&str>)?;
-    let stop_item = MenuItem::with_id(app, "stop", "Stop Recording", false, None::<&str>)?;
-    let quit_item = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+    let open_item = MenuItem::with_id(app, "open", t(Text::TrayOpen), true, None::<&str>)?;
+    let start_item = MenuItem::with_id(app, "start", t(Text::TrayStartRecording), true, None::<&str>)?;
+    let stop_item = MenuItem::with_id(app, "stop", t(Text::TrayStopRecording), false, None::<&str>)?;
+    let quit_item = MenuItem::with_id(app, "quit", t(Text::TrayQuit), true, None::<&str>)?;
+
+    let privacy_30m = MenuItem::with_id(app, "privacy_30m", t(Text::TrayPause30Minutes), true, None::<&str>)?;
+    let privacy_2h = MenuItem::with_id(app, "privacy_2h", t(Text::TrayPause2Hours), true, None::<&str>)?;
+    let privacy_tomorrow = MenuItem::with_id(app, "privacy_tomorrow", t(Text::TrayPauseUntilTomorrow), true, None::<&str>)?;
+    let privacy_resume = MenuItem::with_id(app, "privacy_resume", t(Text::TrayResumeNow), false, None::<&str>)?;
+    let privacy_menu = Submenu::with_items(app, t(Text::TrayPrivacyPause), true, &[
+        &privacy_30m,
+        &privacy_2h,
+        &privacy_tomorrow,
+        &PredefinedMenuItem::separator(app)?,
+        &privacy_resume,
+    ])?;
 
     // Store references for dynamic enable/disable in update_tray_state
     app.manage(TrayMenuItems {
+        open: open_item.clone(),
         start: start_item.clone(),
         stop: stop_item.clone(),
+        quit: quit_item.clone(),
+        privacy_menu: privacy_menu.clone(),
+        privacy_30m: privacy_30m.clone(),
+        privacy_2h: privacy_2h.clone(),
+        privacy_tomorrow: privacy_tomorrow.clone(),
+        privacy_resume: privacy_resume.clone(),
     });
 
     // Build menu
@@ -55,22 +86,20 @@ pub fn setup_tray(app: &AppHandle) -> anyhow::Result<()> {
         &open_item,
         &start_item,
         &stop_item,
+        &privacy_menu,
         &quit_item,
     ])?;
     
     // Build tray icon with a unique ID for later lookup
     let _tray = TrayIconBuilder::with_id("main-tray")
         .icon(app.default_window_icon().cloned().expect("Failed to load tray icon"))
-        .tooltip("Sacho - Idle")
+        .tooltip(t(Text::TooltipIdle))
         .menu(&menu)
         .show_menu_on_left_click(false)
         .on_menu_event(|app, event| {
             match event.id.as_ref() {
                 "open" => {
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
-                    }
+                    crate::agent::show_ui(app);
                 }
                 "start" => {
                     log::info!("Start recording requested from tray");
@@ -88,6 +117,20 @@ pub fn setup_tray(app: &AppHandle) -> anyhow::Result<()> {
                         log::warn!("Could not stop recording from tray: {}", e);
                     }
                 }
+                "privacy_30m" | "privacy_2h" | "privacy_tomorrow" => {
+                    let length = match event.id.as_ref() {
+                        "privacy_30m" => PauseLength::Minutes(30),
+                        "privacy_2h" => PauseLength::Minutes(120),
+                        _ => PauseLength::UntilTomorrow,
+                    };
+                    let release_cameras = app.state::<RwLock<Config>>().read().privacy_pause_stops_cameras;
+                    log::info!("Privacy pause requested from tray");
+                    privacy::pause(app, length, release_cameras);
+                }
+                "privacy_resume" => {
+                    log::info!("Privacy resume requested from tray");
+                    privacy::resume(app);
+                }
                 "quit" => {
                     app.exit(0);
                 }
@@ -100,14 +143,14 @@ pub fn setup_tray(app: &AppHandle) -> anyhow::Result<()> {
                 button_state: MouseButtonState::Up,
                 .. 
             } = event {
-                if let Some(window) = tray.app_handle().get_webview_window("main") {
-                    let _ = window.show();
-                    let _ = window.set_focus();
-                }
+                crate::agent::show_ui(tray.app_handle());
             }
         })
         .build(app)?;
-    
+
+    // A pause restored at startup happened before the tray existed
+    update_privacy_state(app, privacy::is_paused());
+
     log::info!("System tray initialized");
     
     Ok(())
@@ -117,10 +160,11 @@ pub fn setup_tray(app: &AppHandle) -> anyhow::Result<()> {
 pub fn update_tray_state(app: &AppHandle, state: TrayState) {
     if let Some(tray) = app.tray_by_id("main-tray") {
         let tooltip = match state {
-            TrayState::Idle => "Sacho - Idle",
-            TrayState::Recording => "Sacho - Recording",
-            TrayState::Stopping => "Sacho - Stopping...",
-            TrayState::Initializing => "Sacho - Initializing...",
+            TrayState::Idle if privacy::is_paused() => t(Text::TooltipPaused),
+            TrayState::Idle => t(Text::TooltipIdle),
+            TrayState::Recording => t(Text::TooltipRecording),
+            TrayState::Stopping => t(Text::TooltipStopping),
+            TrayState::Initializing => t(Text::TooltipInitializing),
         };
 
         let _ = tray.set_tooltip(Some(tooltip));
@@ -132,3 +176,31 @@ pub fn update_tray_state(app: &AppHandle, state: TrayState) {
         let _ = items.stop.set_enabled(!is_idle);
     }
 }
+
+/// Update the privacy pause menu and tooltip
+pub fn update_privacy_state(app: &AppHandle, paused: bool) {
+    let Some(items) = app.try_state::<TrayMenuItems<tauri::Wry>>() else {
+        return;
+    };
+    let _ = items.privacy_resume.set_enabled(paused);
+    let status = app.state::<RwLock<crate::recording::RecordingState>>().read().status.clone();
+    update_tray_state(app, status.into());
+}
+
+/// Relabel the menu and tooltip after the language changed
+pub fn update_language(app: &AppHandle) {
+    let Some(items) = app.try_state::<TrayMenuItems<tauri::Wry>>() else {
+        return;
+    };
+    let _ = items.open.set_text(t(Text::TrayOpen));
+    let _ = items.start.set_text(t(Text::TrayStartRecording));
+    let _ = items.stop.set_text(t(Text::TrayStopRecording));
+    let _ = items.quit.set_text(t(Text::TrayQuit));
+    let _ = items.privacy_menu.set_text(t(Text::TrayPrivacyPause));
+    let _ = items.privacy_30m.set_text(t(Text::TrayPause30Minutes));
+    let _ = items.privacy_2h.set_text(t(Text::TrayPause2Hours));
+    let _ = items.privacy_tomorrow.set_text(t(Text::TrayPauseUntilTomorrow));
+    let _ = items.privacy_resume.set_text(t(Text::TrayResumeNow));
+    let status = app.state::<RwLock<crate::recording::RecordingState>>().read().status.clone();
+    update_tray_state(app, status.into());
+}
@@ -0,0 +1,161 @@
+// In-app update checks
+//
+// Studio machines rarely see an app store, so Sacho checks a release feed
+// itself: the Tauri updater's JSON manifest for the configured channel
+// (stable, or beta for staged rollouts), or a mirror set in the config for
+// studios behind a firewall. A newer version is announced once through the
+// normal notification path and offered in Settings with its release notes.
+// Installing downloads the package and verifies its minisign signature
+// against the public key compiled into this build before anything runs;
+// builds made without a key never offer updates. Installs are refused while
+// a recording is in progress.
+
+use std::time::Duration;
+
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_updater::UpdaterExt;
+
+use crate::config::{Config, UpdateChannel};
+use crate::recording::{RecordingState, RecordingStatus};
+
+/// Public key that release packages are signed with, provided by the
+/// release build (`tauri signer generate`)
+pub const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("SACHO_UPDATE_PUBKEY");
+
+const STABLE_FEED: &str = "https://github.com/ShayanPersonal/sacho-re/releases/latest/download/latest.json";
+const BETA_FEED: &str = "https://github.com/ShayanPersonal/sacho-re/releases/download/beta/latest.json";
+
+/// Wait after launch before the first check, so startup isn't slowed down
+const FIRST_CHECK_DELAY: Duration = Duration::from_secs(60);
+
+/// Time between background checks
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A newer version found on the feed
+#[derive(Debug, Clone, Serialize)]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub current_version: String,
+    /// Release notes ("what's new")
+    pub notes: Option<String>,
+    /// Publication date, as given by the feed
+    pub date: Option<String>,
+    pub channel: UpdateChannel,
+}
+
+/// Result of the last check, for the Settings page
+#[derive(Default)]
+pub struct UpdateState {
+    available: Mutex<Option<AvailableUpdate>>,
+    /// Version already announced by a notification
+    notified: Mutex<Option<String>>,
+}
+
+impl UpdateState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn available(&self) -> Option<AvailableUpdate> {
+        self.available.lock().clone()
+    }
+}
+
+/// Feed URL for the configured channel (None when checks are off)
+fn feed_url(config: &Config) -> Option<String> {
+    if let Some(url) = config.update_feed_url.as_ref().filter(|u| !u.trim().is_empty()) {
+        return (config.update_channel != UpdateChannel::Off).then(|| url.trim().to_string());
+    }
+    match config.update_channel {
+        UpdateChannel::Off => None,
+        UpdateChannel::Stable => Some(STABLE_FEED.to_string()),
+        UpdateChannel::Beta => Some(BETA_FEED.to_string()),
+    }
+}
+
+fn updater(app: &AppHandle, url: &str) -> Result<tauri_plugin_updater::Updater, String> {
+    let pubkey = UPDATE_PUBLIC_KEY.ok_or("This build has no update signing key, so it can't install updates")?;
+    let endpoint = url.parse().map_err(|e| format!("Invalid update feed URL {}: {}", url, e))?;
+    app.updater_builder()
+        .pubkey(pubkey)
+        .endpoints(vec![endpoint])
+        .map_err(|e| e.to_string())?
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Ask the feed for a newer version. Records the result for Settings and
+/// emits "update-available" when there is one.
+pub async fn check(app: &AppHandle) -> Result<Option<AvailableUpdate>, String> {
+    let (url, channel) = {
+        let config = app.state::<RwLock<Config>>();
+        let config = config.read();
+        (feed_url(&config), config.update_channel)
+    };
+    let Some(url) = url else {
+        return Ok(None);
+    };
+
+    let update = updater(app, &url)?.check().await.map_err(|e| e.to_string())?;
+    let available = update.map(|update| AvailableUpdate {
+        version: update.version.clone(),
+        current_version: update.current_version.clone(),
+        notes: update.body.clone(),
+        date: update.date.map(|d| d.to_string()),
+        channel,
+    });
+
+    let state = app.state::<UpdateState>();
+    *state.available.lock() = available.clone();
+    if let Some(update) = &available {
+        println!("[Sacho] Update available: {} -> {} ({:?})", update.current_version, update.version, channel);
+        let _ = app.emit("update-available", update);
+    }
+    Ok(available)
+}
+
+/// Download, verify and install the available update, then restart.
+/// Refused while recording.
+pub async fn install(app: &AppHandle) -> Result<(), String> {
+    if app.state::<RwLock<RecordingState>>().read().status != RecordingStatus::Idle {
+        return Err("Finish the current recording before updating".to_string());
+    }
+    let url = feed_url(&app.state::<RwLock<Config>>().read()).ok_or("Update checks are turned off")?;
+    let update = updater(app, &url)?
+        .check()
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Sacho is already up to date")?;
+
+    println!("[Sacho] Downloading update {}", update.version);
+    // Fails without installing anything if the signature doesn't match
+    update
+        .download_and_install(|_, _| {}, || println!("[Sacho] Update downloaded and verified"))
+        .await
+        .map_err(|e| e.to_string())?;
+    app.restart();
+}
+
+/// Check on a timer for the life of the app, notifying once per new version
+pub fn start_update_checker(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(FIRST_CHECK_DELAY).await;
+        loop {
+            match check(&app).await {
+                Ok(Some(update)) => {
+                    let state = app.state::<UpdateState>();
+                    let mut notified = state.notified.lock();
+                    if notified.as_deref() != Some(update.version.as_str()) {
+                        crate::notifications::notify_update_available(&app, &update.version);
+                        *notified = Some(update.version);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => println!("[Sacho] Update check failed: {}", e),
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,336 @@
+// Timeline filmstrips
+//
+// A single sprite sheet of small thumbnails, one every few seconds, so
+// hovering the review timeline can show a preview without asking the demuxer
+// for full frames: `video_Cam.mkv` -> `video_Cam.filmstrip.jpg`, with its
+// layout in `video_Cam.filmstrip.json`. Generated after a recording is
+// finalized, or the first time the filmstrip is asked for. Thumbnails are
+// taken from the proxy when there is one, since it seeks faster (and is all
+// that's left in an archived session).
+//
+// Pipeline: filesrc → decodebin → videoconvert → videoscale → capsfilter → appsink
+//           (paused, one keyframe seek per thumbnail)
+// Sheet:    appsrc → videoconvert → jpegenc → appsink
+
+use std::path::{Path, PathBuf};
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Marker inserted between the original stem and the filmstrip's extension
+pub const FILMSTRIP_MARKER: &str = ".filmstrip";
+
+/// Seconds between thumbnails for videos short enough to stay under MAX_TILES
+const DEFAULT_INTERVAL_SECS: f64 = 2.0;
+
+/// Longer videos get a wider interval rather than a larger sheet
+const MAX_TILES: u32 = 400;
+
+/// Thumbnail height in pixels (width follows the aspect ratio)
+const TILE_HEIGHT: u32 = 72;
+
+/// Thumbnails per sprite sheet row
+const COLUMNS: u32 = 10;
+
+const JPEG_QUALITY: i32 = 70;
+
+/// Bytes per pixel of the RGBx tiles (4, so rows never carry stride padding)
+const BYTES_PER_PIXEL: usize = 4;
+
+/// Only one filmstrip is generated at a time, so a first open racing the
+/// post-recording pass doesn't decode the same file twice
+static GENERATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Layout of a filmstrip sprite sheet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Filmstrip {
+    /// Sprite sheet file name, in the session folder
+    pub sprite_filename: String,
+    /// Thumbnail `i` shows the frame at `i * interval_secs`
+    pub interval_secs: f64,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+}
+
+/// True if `fname` is a filmstrip sprite sheet or its layout file
+pub fn is_filmstrip_file(fname: &str) -> bool {
+    [".jpg", ".json"]
+        .iter()
+        .any(|ext| fname.strip_suffix(ext).is_some_and(|stem| stem.ends_with(FILMSTRIP_MARKER)))
+}
+
+/// Sprite sheet and layout paths for a video
+fn filmstrip_paths(video_path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let fname = video_path.file_name()?.to_str()?;
+    let stem = crate::encoding::strip_video_extension(fname);
+    Some((
+        video_path.with_file_name(format!("{}{}.jpg", stem, FILMSTRIP_MARKER)),
+        video_path.with_file_name(format!("{}{}.json", stem, FILMSTRIP_MARKER)),
+    ))
+}
+
+/// The existing filmstrip for a video, if one has been generated
+pub fn load_filmstrip(video_path: &Path) -> Option<Filmstrip> {
+    let (sprite_path, layout_path) = filmstrip_paths(video_path)?;
+    if !sprite_path.exists() {
+        return None;
+    }
+    let json = std::fs::read_to_string(layout_path).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// The filmstrip for a video, generating it first if needed
+pub fn ensure_filmstrip(video_path: &Path) -> anyhow::Result<Filmstrip> {
+    if let Some(filmstrip) = load_filmstrip(video_path) {
+        return Ok(filmstrip);
+    }
+    // The sprite sheet is written next to the video
+    if crate::read_only::is_read_only() {
+        anyhow::bail!("No filmstrip for {} (not generated in read-only mode)", video_path.display());
+    }
+    let _guard = GENERATE_LOCK.lock();
+    // Another caller may have finished it while we waited
+    if let Some(filmstrip) = load_filmstrip(video_path) {
+        return Ok(filmstrip);
+    }
+    generate_filmstrip(video_path)
+}
+
+/// Extract thumbnails from a video (or its proxy) and write the sprite sheet
+/// and layout next to it. The sheet is written to a temp file first so a
+/// partial one is never served.
+pub fn generate_filmstrip(video_path: &Path) -> anyhow::Result<Filmstrip> {
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+    let (sprite_path, layout_path) =
+        filmstrip_paths(video_path).ok_or_else(|| anyhow::anyhow!("Invalid video path"))?;
+    let source = super::proxy::find_proxy(video_path).unwrap_or_else(|| video_path.to_path_buf());
+    if !source.exists() {
+        anyhow::bail!("Video not found: {}", video_path.display());
+    }
+
+    let (duration_secs, width, height) = probe_source(&source)?;
+    let tile_height = TILE_HEIGHT;
+    let tile_width = ((tile_height as f64 * width as f64 / height.max(1) as f64).round() as u32).max(2) & !1;
+    let interval_secs = DEFAULT_INTERVAL_SECS.max(duration_secs / MAX_TILES as f64);
+    let tile_count = ((duration_secs / interval_secs).ceil() as u32).clamp(1, MAX_TILES);
+    let columns = COLUMNS.min(tile_count);
+    let rows = tile_count.div_ceil(columns);
+
+    let tiles = extract_tiles(&source, tile_width, tile_height, interval_secs, tile_count)?;
+
+    // Lay the tiles out row by row; tiles that couldn't be extracted stay black
+    let sheet_width = (tile_width * columns) as usize;
+    let sheet_height = (tile_height * rows) as usize;
+    let tile_stride = tile_width as usize * BYTES_PER_PIXEL;
+    let sheet_stride = sheet_width * BYTES_PER_PIXEL;
+    let mut sheet = vec![0u8; sheet_stride * sheet_height];
+    for (i, tile) in tiles.iter().enumerate() {
+        let Some(tile) = tile else { continue };
+        let x = (i as u32 % columns) as usize * tile_stride;
+        let y = (i as u32 / columns) as usize * tile_height as usize;
+        for (row, line) in tile.chunks_exact(tile_stride).take(tile_height as usize).enumerate() {
+            let start = (y + row) * sheet_stride + x;
+            sheet[start..start + tile_stride].copy_from_slice(line);
+        }
+    }
+
+    let jpeg = encode_sheet(sheet, sheet_width as i32, sheet_height as i32)?;
+    let temp_path = sprite_path.with_extension("jpg.tmp");
+    std::fs::write(&temp_path, &jpeg)
+        .map_err(|e| anyhow::anyhow!("Failed to write filmstrip: {}", e))?;
+    std::fs::rename(&temp_path, &sprite_path)
+        .map_err(|e| anyhow::anyhow!("Failed to finalize filmstrip: {}", e))?;
+
+    let filmstrip = Filmstrip {
+        sprite_filename: sprite_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        interval_secs,
+        tile_width,
+        tile_height,
+        columns,
+        tile_count,
+    };
+    std::fs::write(&layout_path, serde_json::to_string_pretty(&filmstrip)?)
+        .map_err(|e| anyhow::anyhow!("Failed to write filmstrip layout: {}", e))?;
+
+    let extracted = tiles.iter().filter(|t| t.is_some()).count();
+    println!(
+        "[Sacho] Generated filmstrip {} ({}/{} thumbnails, {} bytes)",
+        sprite_path.display(),
+        extracted,
+        tile_count,
+        jpeg.len()
+    );
+    Ok(filmstrip)
+}
+
+/// Duration and frame size of the first video stream
+fn probe_source(path: &Path) -> anyhow::Result<(f64, u32, u32)> {
+    let discoverer = crate::session::get_or_create_discoverer()?;
+    let uri = format!("file:///{}", path.to_string_lossy().replace('\\', "/"));
+    let info = discoverer
+        .discover_uri(&uri)
+        .map_err(|e| anyhow::anyhow!("Failed to probe {}: {}", path.display(), e))?;
+    let stream = info
+        .video_streams()
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("No video stream in {}", path.display()))?;
+    let duration_secs = info.duration().map(|d| d.seconds_f64()).unwrap_or(0.0);
+    Ok((duration_secs, stream.width(), stream.height()))
+}
+
+/// Seek to each thumbnail time and grab the prerolled frame. Seeks snap to
+/// the nearest keyframe, which for MJPEG and FFV1 is every frame and for the
+/// proxies is at most half a second away.
+fn extract_tiles(
+    source: &Path,
+    tile_width: u32,
+    tile_height: u32,
+    interval_secs: f64,
+    tile_count: u32,
+) -> anyhow::Result<Vec<Option<Vec<u8>>>> {
+    let pipeline = gst::Pipeline::new();
+
+    let filesrc = gst::ElementFactory::make("filesrc")
+        .property("location", source.to_string_lossy().to_string())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create filesrc: {}", e))?;
+    let decodebin = gst::ElementFactory::make("decodebin")
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create decodebin: {}", e))?;
+    let videoconvert = gst::ElementFactory::make("videoconvert")
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create videoconvert: {}", e))?;
+    let videoscale = gst::ElementFactory::make("videoscale")
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create videoscale: {}", e))?;
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("video/x-raw")
+                .field("format", "RGBx")
+                .field("width", tile_width as i32)
+                .field("height", tile_height as i32)
+                .field("pixel-aspect-ratio", gst::Fraction::new(1, 1))
+                .build(),
+        )
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create capsfilter: {}", e))?;
+    let appsink = gst_app::AppSink::builder().sync(false).max_buffers(1).build();
+
+    pipeline
+        .add_many([&filesrc, &decodebin, &videoconvert, &videoscale, &capsfilter, appsink.upcast_ref()])
+        .map_err(|e| anyhow::anyhow!("Failed to add elements: {}", e))?;
+    filesrc
+        .link(&decodebin)
+        .map_err(|e| anyhow::anyhow!("Failed to link filesrc -> decodebin: {}", e))?;
+    gst::Element::link_many([&videoconvert, &videoscale, &capsfilter, appsink.upcast_ref()])
+        .map_err(|e| anyhow::anyhow!("Failed to link filmstrip chain: {}", e))?;
+
+    let convert_weak = videoconvert.downgrade();
+    decodebin.connect_pad_added(move |_decodebin, src_pad| {
+        let is_video = src_pad
+            .current_caps()
+            .and_then(|caps| caps.structure(0).map(|s| s.name().as_str().starts_with("video/")))
+            .unwrap_or(false);
+        if !is_video {
+            return;
+        }
+        if let Some(convert) = convert_weak.upgrade() {
+            if let Some(sink_pad) = convert.static_pad("sink") {
+                if !sink_pad.is_linked() {
+                    if let Err(e) = src_pad.link(&sink_pad) {
+                        println!("[Sacho] Warning: Failed to link filmstrip video pad: {:?}", e);
+                    }
+                }
+            }
+        }
+    });
+
+    pipeline
+        .set_state(gst::State::Paused)
+        .map_err(|e| anyhow::anyhow!("Failed to open video for filmstrip: {:?}", e))?;
+    let (state_result, _, _) = pipeline.state(gst::ClockTime::from_seconds(10));
+    if let Err(e) = state_result {
+        pipeline.set_state(gst::State::Null).ok();
+        anyhow::bail!("Video did not preroll for filmstrip: {:?}", e);
+    }
+
+    let mut tiles = Vec::with_capacity(tile_count as usize);
+    let expected_len = tile_width as usize * tile_height as usize * BYTES_PER_PIXEL;
+    for i in 0..tile_count {
+        let position = gst::ClockTime::from_nseconds((i as f64 * interval_secs * 1e9) as u64);
+        let seeked = pipeline
+            .seek_simple(
+                gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT | gst::SeekFlags::SNAP_NEAREST,
+                position,
+            )
+            .is_ok();
+        let tile = seeked
+            .then(|| appsink.try_pull_preroll(gst::ClockTime::from_seconds(5)))
+            .flatten()
+            .and_then(|sample| {
+                let buffer = sample.buffer()?;
+                let map = buffer.map_readable().ok()?;
+                (map.len() >= expected_len).then(|| map.as_slice()[..expected_len].to_vec())
+            });
+        tiles.push(tile);
+    }
+    pipeline.set_state(gst::State::Null).ok();
+
+    if tiles.iter().all(|t| t.is_none()) {
+        anyhow::bail!("No frames could be extracted from {}", source.display());
+    }
+    Ok(tiles)
+}
+
+/// Encode the composed RGBx sheet as one JPEG
+fn encode_sheet(sheet: Vec<u8>, width: i32, height: i32) -> anyhow::Result<Vec<u8>> {
+    let pipeline = gst::Pipeline::new();
+    let appsrc = gst_app::AppSrc::builder()
+        .caps(
+            &gst::Caps::builder("video/x-raw")
+                .field("format", "RGBx")
+                .field("width", width)
+                .field("height", height)
+                .field("framerate", gst::Fraction::new(0, 1))
+                .build(),
+        )
+        .format(gst::Format::Time)
+        .build();
+    let videoconvert = gst::ElementFactory::make("videoconvert").build()?;
+    let jpegenc = gst::ElementFactory::make("jpegenc")
+        .property("quality", JPEG_QUALITY)
+        .build()?;
+    let appsink = gst_app::AppSink::builder().sync(false).build();
+
+    pipeline.add_many([appsrc.upcast_ref(), &videoconvert, &jpegenc, appsink.upcast_ref()])?;
+    gst::Element::link_many([appsrc.upcast_ref(), &videoconvert, &jpegenc, appsink.upcast_ref()])?;
+    pipeline.set_state(gst::State::Playing)?;
+
+    let mut buffer = gst::Buffer::from_mut_slice(sheet);
+    if let Some(buffer) = buffer.get_mut() {
+        buffer.set_pts(gst::ClockTime::ZERO);
+    }
+    appsrc.push_buffer(buffer)?;
+    let _ = appsrc.end_of_stream();
+
+    let jpeg = appsink
+        .try_pull_sample(gst::ClockTime::from_seconds(10))
+        .and_then(|sample| {
+            let buffer = sample.buffer()?;
+            let map = buffer.map_readable().ok()?;
+            Some(map.as_slice().to_vec())
+        });
+    let _ = pipeline.set_state(gst::State::Null);
+    jpeg.ok_or_else(|| anyhow::anyhow!("Failed to encode filmstrip"))
+}
@@ -0,0 +1,265 @@
+// Downscale-on-decode for the custom frame player
+//
+// MJPEG frames are normally sent to the webview as recorded, which for 4K
+// sources means the webview decodes a full-size JPEG per frame on one core.
+// `ScaledDemuxer` wraps a demuxer and re-encodes frames taller than the
+// preview size through a persistent pipeline, decoding on the GPU where a
+// hardware JPEG decoder is available.
+//
+// Pipeline: appsrc → jpeg decoder → videoconvert → videoscale → capsfilter
+//           → jpegenc → appsink
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app as gst_app;
+
+use super::demux::{VideoDemuxer, VideoFrame, VideoInfo};
+use super::VideoError;
+
+/// JPEG quality for preview frames
+const PREVIEW_JPEG_QUALITY: i32 = 85;
+
+/// How preview frames are produced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewOptions {
+    /// Frames taller than this are downscaled (0 = always full resolution)
+    pub max_height: u32,
+    /// Use a hardware JPEG decoder when one is available
+    pub allow_hardware: bool,
+}
+
+impl PreviewOptions {
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self {
+            max_height: config.video_preview_max_height,
+            allow_hardware: config.hardware_jpeg_decode,
+        }
+    }
+
+    /// Whether frames of this height need scaling
+    pub fn should_scale(&self, height: u32) -> bool {
+        self.max_height > 0 && height > self.max_height
+    }
+}
+
+/// Hardware JPEG decoders to try, in order of preference
+fn hardware_jpeg_decoders() -> &'static [&'static str] {
+    #[cfg(target_os = "windows")]
+    { &["nvjpegdec", "d3d11jpegdec"] }
+    #[cfg(target_os = "linux")]
+    { &["nvjpegdec", "vajpegdec"] }
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    { &[] }
+}
+
+/// JPEG decoder element to use: the first available hardware decoder, or jpegdec
+pub fn select_jpeg_decoder(allow_hardware: bool) -> &'static str {
+    if allow_hardware {
+        if let Some(name) = hardware_jpeg_decoders()
+            .iter()
+            .find(|name| gst::ElementFactory::find(name).is_some())
+        {
+            return name;
+        }
+    }
+    "jpegdec"
+}
+
+/// Persistent JPEG → smaller JPEG pipeline
+pub struct JpegScaler {
+    pipeline: gst::Pipeline,
+    appsrc: gst_app::AppSrc,
+    appsink: gst_app::AppSink,
+    decoder: &'static str,
+}
+
+impl JpegScaler {
+    /// Build a scaler from `width`x`height` JPEGs to `target_height` (aspect preserved)
+    pub fn new(width: u32, height: u32, target_height: u32, allow_hardware: bool) -> Result<Self, VideoError> {
+        let preferred = select_jpeg_decoder(allow_hardware);
+        match Self::build(preferred, width, height, target_height) {
+            Ok(scaler) => Ok(scaler),
+            Err(e) if preferred != "jpegdec" => {
+                log::warn!("JpegScaler: {} unavailable ({}), falling back to jpegdec", preferred, e);
+                Self::build("jpegdec", width, height, target_height)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn build(decoder_name: &'static str, width: u32, height: u32, target_height: u32) -> Result<Self, VideoError> {
+        gst::init().map_err(|e| VideoError::Gst(e.to_string()))?;
+
+        let target_height = target_height.max(2) & !1;
+        let target_width = ((width as u64 * target_height as u64 / height.max(1) as u64) as u32).max(2) & !1;
+
+        let pipeline = gst::Pipeline::new();
+
+        let appsrc = gst_app::AppSrc::builder()
+            .caps(
+                &gst::Caps::builder("image/jpeg")
+                    .field("width", width as i32)
+                    .field("height", height as i32)
+                    .field("framerate", gst::Fraction::new(0, 1))
+                    .build(),
+            )
+            .format(gst::Format::Time)
+            .build();
+        let decoder = gst::ElementFactory::make(decoder_name)
+            .build()
+            .map_err(|e| VideoError::Gst(format!("Failed to create {}: {}", decoder_name, e)))?;
+        let videoconvert = gst::ElementFactory::make("videoconvert")
+            .build()
+            .map_err(|e| VideoError::Gst(format!("Failed to create videoconvert: {}", e)))?;
+        let videoscale = gst::ElementFactory::make("videoscale")
+            .build()
+            .map_err(|e| VideoError::Gst(format!("Failed to create videoscale: {}", e)))?;
+        let capsfilter = gst::ElementFactory::make("capsfilter")
+            .property(
+                "caps",
+                gst::Caps::builder("video/x-raw")
+                    .field("format", "I420")
+                    .field("width", target_width as i32)
+                    .field("height", target_height as i32)
+                    .build(),
+            )
+            .build()
+            .map_err(|e| VideoError::Gst(format!("Failed to create capsfilter: {}", e)))?;
+        let jpegenc = gst::ElementFactory::make("jpegenc")
+            .property("quality", PREVIEW_JPEG_QUALITY)
+            .build()
+            .map_err(|e| VideoError::Gst(format!("Failed to create jpegenc: {}", e)))?;
+        let appsink = gst_app::AppSink::builder()
+            .sync(false)
+            .build();
+
+        pipeline
+            .add_many([
+                appsrc.upcast_ref(),
+                &decoder,
+                &videoconvert,
+                &videoscale,
+                &capsfilter,
+                &jpegenc,
+                appsink.upcast_ref(),
+            ])
+            .map_err(|e| VideoError::Gst(format!("Failed to add elements: {}", e)))?;
+        gst::Element::link_many([
+            appsrc.upcast_ref(),
+            &decoder,
+            &videoconvert,
+            &videoscale,
+            &capsfilter,
+            &jpegenc,
+            appsink.upcast_ref(),
+        ])
+        .map_err(|e| VideoError::Gst(format!("Failed to link scaler pipeline: {}", e)))?;
+
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|e| VideoError::Gst(format!("Failed to start scaler pipeline: {:?}", e)))?;
+
+        log::info!(
+            "JpegScaler: {}x{} -> {}x{} via {}",
+            width, height, target_width, target_height, decoder_name
+        );
+
+        Ok(Self { pipeline, appsrc, appsink, decoder: decoder_name })
+    }
+
+    /// Decoder element in use
+    pub fn decoder(&self) -> &'static str {
+        self.decoder
+    }
+
+    /// Downscale one frame. The output keeps the input's timing.
+    pub fn scale(&self, frame: &VideoFrame) -> Result<VideoFrame, VideoError> {
+        let mut buffer = gst::Buffer::from_slice(frame.data.clone());
+        {
+            let buffer = buffer.get_mut().expect("new buffer is writable");
+            buffer.set_pts(gst::ClockTime::from_mseconds(frame.timestamp_ms));
+            buffer.set_duration(gst::ClockTime::from_mseconds(frame.duration_ms));
+        }
+        self.appsrc
+            .push_buffer(buffer)
+            .map_err(|e| VideoError::Gst(format!("Failed to push frame: {:?}", e)))?;
+
+        let sample = self
+            .appsink
+            .try_pull_sample(gst::ClockTime::from_seconds(2))
+            .ok_or_else(|| VideoError::Gst(format!("{} produced no frame", self.decoder)))?;
+        let out = sample
+            .buffer()
+            .ok_or_else(|| VideoError::Parse("No buffer in sample".into()))?;
+        let map = out
+            .map_readable()
+            .map_err(|e| VideoError::Parse(format!("Failed to map buffer: {}", e)))?;
+
+        Ok(VideoFrame {
+            data: map.as_slice().to_vec(),
+            timestamp_ms: frame.timestamp_ms,
+            duration_ms: frame.duration_ms,
+            is_keyframe: frame.is_keyframe,
+        })
+    }
+}
+
+impl Drop for JpegScaler {
+    fn drop(&mut self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+/// Demuxer wrapper that downscales every frame through a `JpegScaler`.
+/// `info()` still reports the source resolution.
+pub struct ScaledDemuxer {
+    inner: Box<dyn VideoDemuxer>,
+    scaler: JpegScaler,
+}
+
+impl ScaledDemuxer {
+    pub fn new(inner: Box<dyn VideoDemuxer>, options: PreviewOptions) -> Result<Self, VideoError> {
+        let info = inner.info();
+        let scaler = JpegScaler::new(info.width, info.height, options.max_height, options.allow_hardware)?;
+        Ok(Self { inner, scaler })
+    }
+
+    /// Scale a frame, falling back to the original if the scaler fails
+    fn scale(&self, frame: VideoFrame) -> VideoFrame {
+        match self.scaler.scale(&frame) {
+            Ok(scaled) => scaled,
+            Err(e) => {
+                log::warn!("ScaledDemuxer: sending full-size frame: {}", e);
+                frame
+            }
+        }
+    }
+}
+
+impl VideoDemuxer for ScaledDemuxer {
+    fn info(&self) -> &VideoInfo {
+        self.inner.info()
+    }
+
+    fn get_frame_at(&mut self, timestamp_ms: u64) -> Result<VideoFrame, VideoError> {
+        let frame = self.inner.get_frame_at(timestamp_ms)?;
+        Ok(self.scale(frame))
+    }
+
+    fn next_frame(&mut self) -> Result<Option<VideoFrame>, VideoError> {
+        Ok(self.inner.next_frame()?.map(|frame| self.scale(frame)))
+    }
+
+    fn seek(&mut self, timestamp_ms: u64) -> Result<(), VideoError> {
+        self.inner.seek(timestamp_ms)
+    }
+
+    fn get_frames_range(&mut self, start_ms: u64, end_ms: u64) -> Result<Vec<VideoFrame>, VideoError> {
+        let frames = self.inner.get_frames_range(start_ms, end_ms)?;
+        Ok(frames.into_iter().map(|frame| self.scale(frame)).collect())
+    }
+
+    fn get_frame_timestamps(&mut self) -> Result<Vec<u64>, VideoError> {
+        self.inner.get_frame_timestamps()
+    }
+}
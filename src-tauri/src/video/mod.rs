@@ -7,8 +7,13 @@
 // This module is primarily used for MJPEG frame extraction for the custom player.
 
 pub mod demux;
+pub mod filmstrip;
 pub mod gst_decode;
+pub mod jpeg_scale;
 pub mod mjpeg;
+pub mod proxy;
+pub mod stream;
+pub mod transcode;
 
 pub use demux::{VideoDemuxer, VideoFrame, VideoInfo};
 pub use gst_decode::GstDecodeDemuxer;
@@ -114,6 +119,15 @@ pub fn open_video<P: AsRef<Path>>(path: P) -> Result<Box<dyn VideoDemuxer>, Vide
     // and encoded codecs (VP8/VP9/AV1) may use the .mkv extension
     let codec_info = probe_video_codec(path)?;
     
+    // Heavy codecs decode from the low-resolution proxy when one exists,
+    // so scrubbing doesn't decode full-size frames
+    if proxy::codec_needs_proxy(&codec_info.codec) {
+        if let Some(proxy_path) = proxy::find_proxy(path) {
+            let demuxer = GstDecodeDemuxer::open(&proxy_path, &codec_info.codec)?;
+            return Ok(Box::new(demuxer));
+        }
+    }
+    
     match codec_info.codec.as_str() {
         "mjpeg" => {
             let demuxer = MjpegDemuxer::open(path)?;
@@ -132,6 +146,20 @@ pub fn open_video<P: AsRef<Path>>(path: P) -> Result<Box<dyn VideoDemuxer>, Vide
     }
 }
 
+/// Open a video for the custom player, downscaling MJPEG frames taller than
+/// the preview size so large sources don't stall the webview's JPEG decoder
+pub fn open_video_for_preview<P: AsRef<Path>>(
+    path: P,
+    options: jpeg_scale::PreviewOptions,
+) -> Result<Box<dyn VideoDemuxer>, VideoError> {
+    let demuxer = open_video(path)?;
+    let info = demuxer.info();
+    if info.codec != "mjpeg" || !options.should_scale(info.height) {
+        return Ok(demuxer);
+    }
+    Ok(Box::new(jpeg_scale::ScaledDemuxer::new(demuxer, options)?))
+}
+
 /// Error type for video operations
 #[derive(Debug, thiserror::Error)]
 pub enum VideoError {
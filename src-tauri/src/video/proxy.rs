@@ -0,0 +1,339 @@
+// Low-resolution proxy transcoding
+//
+// Produces a small, fast-seeking copy of a recorded video next to the
+// original: `video_Cam.mkv` -> `video_Cam.proxy.mp4` (H.264, platform-native
+// encoders only) or `video_Cam.proxy.webm` (VP8) where no H.264 encoder is
+// available. Proxies are generated in the background for codecs that are too
+// heavy to decode frame-by-frame while scrubbing (FFV1, raw, AV1), and are
+// used for archived sessions in place of the original.
+//
+// Pipeline: filesrc → decodebin → queue → videoconvert → videoscale → capsfilter
+//           → encoder → [h264parse] → mp4mux/webmmux → filesink
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::encoding::{HardwareEncoderType, VideoCodec};
+
+/// Marker inserted between the original stem and the proxy's extension
+pub const PROXY_MARKER: &str = ".proxy";
+
+/// Default proxy height in pixels
+pub const DEFAULT_PROXY_HEIGHT: u32 = 480;
+
+/// Keyframe every N frames so scrubbing never decodes far from a seek point
+const PROXY_KEYFRAME_INTERVAL: u32 = 15;
+
+/// A proxy without its original is only removed once it's this old, so a
+/// sync tool that copies the proxy before the original doesn't lose it
+const ORPHAN_PROXY_MIN_AGE: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// True if `fname` is a proxy file (e.g. "video_Cam.proxy.mp4")
+pub fn is_proxy_file(fname: &str) -> bool {
+    crate::encoding::is_video_extension(fname)
+        && crate::encoding::strip_video_extension(fname).ends_with(PROXY_MARKER)
+}
+
+/// Stem of the original video a proxy belongs to ("video_Cam.proxy.mp4" -> "video_Cam")
+pub fn original_stem(proxy_fname: &str) -> &str {
+    let stem = crate::encoding::strip_video_extension(proxy_fname);
+    stem.strip_suffix(PROXY_MARKER).unwrap_or(stem)
+}
+
+/// Whether a video in this codec benefits from a proxy for scrubbing
+pub fn codec_needs_proxy(codec: &str) -> bool {
+    matches!(codec, "ffv1" | "raw" | "av1")
+}
+
+/// Find an existing proxy for a video file, if one has been generated
+pub fn find_proxy(video_path: &Path) -> Option<PathBuf> {
+    let fname = video_path.file_name()?.to_str()?;
+    let stem = crate::encoding::strip_video_extension(fname);
+    ["mp4", "webm"]
+        .iter()
+        .map(|ext| video_path.with_file_name(format!("{}{}.{}", stem, PROXY_MARKER, ext)))
+        .find(|p| p.exists())
+}
+
+/// Codec, encoder backend, and container extension for proxies on this machine
+fn select_proxy_encoder() -> anyhow::Result<(VideoCodec, HardwareEncoderType, &'static str, &'static str)> {
+    if let Some(hw) = crate::encoding::detect_best_h264_encoder() {
+        if let Some(element) = hw.h264_encoder_element() {
+            return Ok((VideoCodec::H264, hw, element, "mp4"));
+        }
+    }
+    let hw = crate::encoding::detect_best_vp8_encoder();
+    let element = hw
+        .vp8_encoder_element()
+        .ok_or_else(|| anyhow::anyhow!("No H.264 or VP8 encoder available for proxies"))?;
+    Ok((VideoCodec::Vp8, hw, element, "webm"))
+}
+
+/// Transcode `source` into a proxy no taller than `max_height` (never upscaled).
+/// Writes to a temp file first so a partial proxy is never mistaken for a
+/// finished one. Returns the proxy path.
+pub fn generate_proxy(source: &Path, max_height: u32) -> anyhow::Result<PathBuf> {
+    if crate::read_only::is_read_only() {
+        anyhow::bail!(crate::read_only::refusal());
+    }
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+    let (codec, hw_type, encoder_name, extension) = select_proxy_encoder()?;
+
+    let fname = source
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid video path"))?;
+    let stem = crate::encoding::strip_video_extension(fname);
+    let proxy_path = source.with_file_name(format!("{}{}.{}", stem, PROXY_MARKER, extension));
+    let temp_path = source.with_file_name(format!("{}{}.{}.tmp", stem, PROXY_MARKER, extension));
+
+    let target_height = match source_height(source) {
+        Some(h) if h <= max_height => h,
+        _ => max_height,
+    } & !1; // encoders need even dimensions
+
+    let pipeline = gst::Pipeline::new();
+
+    let filesrc = gst::ElementFactory::make("filesrc")
+        .property("location", source.to_string_lossy().to_string())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create filesrc: {}", e))?;
+    let decodebin = gst::ElementFactory::make("decodebin")
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create decodebin: {}", e))?;
+    let queue = gst::ElementFactory::make("queue")
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create queue: {}", e))?;
+    let videoconvert = gst::ElementFactory::make("videoconvert")
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create videoconvert: {}", e))?;
+    let videoscale = gst::ElementFactory::make("videoscale")
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create videoscale: {}", e))?;
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .property(
+            "caps",
+            gst::Caps::builder("video/x-raw")
+                .field("format", "I420")
+                .field("height", target_height as i32)
+                .field("pixel-aspect-ratio", gst::Fraction::new(1, 1))
+                .build(),
+        )
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create capsfilter: {}", e))?;
+    let encoder = gst::ElementFactory::make(encoder_name)
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", encoder_name, e))?;
+    crate::encoding::presets::apply_preset(
+        &encoder,
+        codec,
+        hw_type,
+        crate::encoding::MIN_PRESET,
+        crate::encoding::MIN_PRESET,
+        PROXY_KEYFRAME_INTERVAL,
+    );
+    let mux = match extension {
+        "mp4" => gst::ElementFactory::make("mp4mux").build(),
+        _ => gst::ElementFactory::make("webmmux").build(),
+    }
+    .map_err(|e| anyhow::anyhow!("Failed to create muxer: {}", e))?;
+    let filesink = gst::ElementFactory::make("filesink")
+        .property("location", temp_path.to_string_lossy().to_string())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create filesink: {}", e))?;
+
+    let mut chain = vec![queue.clone(), videoconvert, videoscale, capsfilter, encoder];
+    if codec == VideoCodec::H264 {
+        chain.push(
+            gst::ElementFactory::make("h264parse")
+                .build()
+                .map_err(|e| anyhow::anyhow!("Failed to create h264parse: {}", e))?,
+        );
+    }
+    chain.push(mux);
+    chain.push(filesink);
+
+    pipeline.add_many([&filesrc, &decodebin])
+        .map_err(|e| anyhow::anyhow!("Failed to add elements: {}", e))?;
+    pipeline.add_many(&chain)
+        .map_err(|e| anyhow::anyhow!("Failed to add elements: {}", e))?;
+    filesrc.link(&decodebin)
+        .map_err(|e| anyhow::anyhow!("Failed to link filesrc -> decodebin: {}", e))?;
+    gst::Element::link_many(&chain)
+        .map_err(|e| anyhow::anyhow!("Failed to link proxy chain: {}", e))?;
+
+    // Link the first video pad from decodebin; audio is not included in proxies
+    let queue_weak = queue.downgrade();
+    decodebin.connect_pad_added(move |_decodebin, src_pad| {
+        let is_video = src_pad
+            .current_caps()
+            .and_then(|caps| caps.structure(0).map(|s| s.name().as_str().starts_with("video/")))
+            .unwrap_or(false);
+        if !is_video {
+            return;
+        }
+        if let Some(queue) = queue_weak.upgrade() {
+            if let Some(sink_pad) = queue.static_pad("sink") {
+                if !sink_pad.is_linked() {
+                    if let Err(e) = src_pad.link(&sink_pad) {
+                        println!("[Sacho] Warning: Failed to link proxy video pad: {:?}", e);
+                    }
+                }
+            }
+        }
+    });
+
+    pipeline.set_state(gst::State::Playing)
+        .map_err(|e| anyhow::anyhow!("Failed to start proxy transcode: {:?}", e))?;
+
+    let bus = pipeline.bus().ok_or_else(|| anyhow::anyhow!("No pipeline bus for proxy transcode"))?;
+    let mut result = Err(anyhow::anyhow!("Proxy transcode timed out"));
+    for msg in bus.iter_timed(gst::ClockTime::from_seconds(3600)) {
+        match msg.view() {
+            gst::MessageView::Eos(..) => {
+                result = Ok(());
+                break;
+            }
+            gst::MessageView::Error(err) => {
+                result = Err(anyhow::anyhow!(
+                    "Proxy transcode error: {} ({})",
+                    err.error(),
+                    err.debug().unwrap_or_default()
+                ));
+                break;
+            }
+            _ => {}
+        }
+    }
+    pipeline.set_state(gst::State::Null).ok();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    std::fs::rename(&temp_path, &proxy_path)
+        .map_err(|e| anyhow::anyhow!("Failed to finalize proxy: {}", e))?;
+
+    let size = std::fs::metadata(&proxy_path).map(|m| m.len()).unwrap_or(0);
+    println!("[Sacho] Generated proxy {} ({}p, {} bytes)", proxy_path.display(), target_height, size);
+
+    Ok(proxy_path)
+}
+
+/// Height of the first video stream, if it can be discovered
+fn source_height(path: &Path) -> Option<u32> {
+    let discoverer = crate::session::get_or_create_discoverer().ok()?;
+    let uri = format!("file:///{}", path.to_string_lossy().replace('\\', "/"));
+    let info = discoverer.discover_uri(&uri).ok()?;
+    info.video_streams().first().map(|stream| stream.height())
+}
+
+/// Delete proxies whose original video no longer exists. Archived sessions
+/// are skipped, since there the proxy intentionally replaces the original,
+/// as are recently written proxies and read-only libraries.
+/// Returns the number of proxies removed.
+pub fn remove_orphan_proxies(session_path: &Path) -> usize {
+    if crate::read_only::is_read_only() || crate::session::archive::is_archived(session_path) {
+        return 0;
+    }
+    let Ok(entries) = std::fs::read_dir(session_path) else { return 0 };
+    let names: Vec<String> = entries
+        .flatten()
+        .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+        .collect();
+
+    let mut removed = 0;
+    for proxy_name in names.iter().filter(|n| is_proxy_file(n)) {
+        let stem = original_stem(proxy_name);
+        let has_original = names.iter().any(|n| {
+            !is_proxy_file(n)
+                && crate::encoding::is_video_extension(n)
+                && crate::encoding::strip_video_extension(n) == stem
+        });
+        let proxy_path = session_path.join(proxy_name);
+        let old_enough = std::fs::metadata(&proxy_path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age >= ORPHAN_PROXY_MIN_AGE);
+        if !has_original && old_enough && std::fs::remove_file(&proxy_path).is_ok() {
+            println!("[Sacho] Removed orphaned proxy {}", proxy_name);
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Payload of the "video-proxy-ready" event
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyReady {
+    pub session_path: String,
+    pub filename: String,
+    pub proxy_filename: String,
+}
+
+/// Background proxy generation. Videos are transcoded one at a time on a
+/// dedicated thread so proxy work never competes with itself for the encoder.
+pub struct ProxyQueue {
+    tx: Mutex<mpsc::Sender<PathBuf>>,
+}
+
+impl ProxyQueue {
+    pub fn new(app: tauri::AppHandle) -> Self {
+        let (tx, rx) = mpsc::channel::<PathBuf>();
+        std::thread::Builder::new()
+            .name("sacho-proxy".into())
+            .spawn(move || {
+                for video_path in rx {
+                    process_queued_video(&app, &video_path);
+                }
+            })
+            .expect("Failed to spawn proxy thread");
+        Self { tx: Mutex::new(tx) }
+    }
+
+    /// Queue a video for proxy generation. Videos that already have a proxy
+    /// or don't need one are skipped when they reach the front of the queue.
+    pub fn enqueue(&self, video_path: PathBuf) {
+        if crate::read_only::is_read_only() {
+            return;
+        }
+        let _ = self.tx.lock().send(video_path);
+    }
+}
+
+fn process_queued_video(app: &tauri::AppHandle, video_path: &Path) {
+    if !video_path.exists() || find_proxy(video_path).is_some() {
+        return;
+    }
+    let codec = match super::probe_video_codec(video_path) {
+        Ok(info) => info.codec,
+        Err(e) => {
+            println!("[Sacho] Skipping proxy for {}: {}", video_path.display(), e);
+            return;
+        }
+    };
+    if !codec_needs_proxy(&codec) {
+        return;
+    }
+
+    match generate_proxy(video_path, DEFAULT_PROXY_HEIGHT) {
+        Ok(proxy_path) => {
+            let name = |p: &Path| p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+            let _ = app.emit("video-proxy-ready", ProxyReady {
+                session_path: video_path.parent().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+                filename: name(video_path),
+                proxy_filename: name(&proxy_path),
+            });
+        }
+        Err(e) => println!("[Sacho] Failed to generate proxy for {}: {}", video_path.display(), e),
+    }
+}
@@ -0,0 +1,304 @@
+// Streaming frame delivery for the custom video player
+//
+// Instead of the frontend polling batches of frames (each reopening the
+// demuxer), a stream owns a decoder on its own thread and pushes frames over a
+// Tauri IPC channel at playback rate. The decoder stays open between play,
+// pause and seek requests, so resuming or scrubbing never pays the open cost.
+//
+// One-shot frame requests (`get_video_frame` etc.) share warm demuxers through
+// `DemuxerCache` for the same reason.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use base64::Engine;
+use parking_lot::Mutex;
+use serde::Serialize;
+use tauri::ipc::Channel;
+
+use super::jpeg_scale::PreviewOptions;
+use super::{open_video_for_preview, proxy, VideoDemuxer, VideoError, VideoFrame};
+
+/// How far ahead of the playback clock frames are sent, so the frontend
+/// always has the next frame in hand when it is due
+const STREAM_LEAD: Duration = Duration::from_millis(250);
+
+/// Number of demuxers kept open for one-shot frame requests
+const DEMUXER_CACHE_SIZE: usize = 4;
+
+/// A single frame for playback
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamFrame {
+    /// Base64-encoded JPEG data
+    pub data_base64: String,
+    /// Timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Duration in milliseconds
+    pub duration_ms: u64,
+}
+
+impl From<VideoFrame> for StreamFrame {
+    fn from(frame: VideoFrame) -> Self {
+        Self {
+            data_base64: base64::engine::general_purpose::STANDARD.encode(&frame.data),
+            timestamp_ms: frame.timestamp_ms,
+            duration_ms: frame.duration_ms,
+        }
+    }
+}
+
+/// Message pushed to the frontend over a stream's channel
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StreamMessage {
+    Frame(StreamFrame),
+    /// Playback reached the end of the video
+    Ended,
+    Error { message: String },
+}
+
+enum StreamCommand {
+    Play { from_ms: u64, rate: f64 },
+    Pause,
+    /// Deliver the single frame at this timestamp (scrubbing while paused)
+    Seek(u64),
+    /// Cycle between (start, end) ms while playing, or play through
+    SetLoop(Option<(u64, u64)>),
+}
+
+/// Handle to a stream's decoder thread. Dropping it stops the thread.
+struct StreamHandle {
+    tx: mpsc::Sender<StreamCommand>,
+}
+
+/// Managed state: all open frame streams, by id
+#[derive(Default)]
+pub struct VideoStreams {
+    next_id: AtomicU32,
+    streams: Mutex<HashMap<u32, StreamHandle>>,
+}
+
+impl VideoStreams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open `path` and start a decoder thread delivering frames to `channel`.
+    /// Returns the stream id and the video's info.
+    pub fn open(
+        &self,
+        path: &Path,
+        options: PreviewOptions,
+        channel: Channel<StreamMessage>,
+    ) -> Result<(u32, super::VideoInfo), VideoError> {
+        let demuxer = open_video_for_preview(path, options)?;
+        let info = demuxer.info().clone();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+
+        let (tx, rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name(format!("sacho-video-stream-{}", id))
+            .spawn(move || stream_loop(demuxer, rx, channel))?;
+
+        self.streams.lock().insert(id, StreamHandle { tx });
+        Ok((id, info))
+    }
+
+    pub fn play(&self, id: u32, from_ms: u64, rate: f64) -> Result<(), String> {
+        self.send(id, StreamCommand::Play { from_ms, rate })
+    }
+
+    pub fn pause(&self, id: u32) -> Result<(), String> {
+        self.send(id, StreamCommand::Pause)
+    }
+
+    pub fn seek(&self, id: u32, timestamp_ms: u64) -> Result<(), String> {
+        self.send(id, StreamCommand::Seek(timestamp_ms))
+    }
+
+    pub fn set_loop(&self, id: u32, region: Option<(u64, u64)>) -> Result<(), String> {
+        self.send(id, StreamCommand::SetLoop(region))
+    }
+
+    pub fn close(&self, id: u32) {
+        self.streams.lock().remove(&id);
+    }
+
+    fn send(&self, id: u32, command: StreamCommand) -> Result<(), String> {
+        let streams = self.streams.lock();
+        let stream = streams.get(&id).ok_or_else(|| format!("Video stream {} is not open", id))?;
+        stream.tx.send(command).map_err(|_| format!("Video stream {} has stopped", id))
+    }
+}
+
+/// Decoder thread: idles until told to play or seek, then paces frames
+/// against a wall clock scaled by the playback rate.
+fn stream_loop(
+    mut demuxer: Box<dyn VideoDemuxer>,
+    rx: mpsc::Receiver<StreamCommand>,
+    channel: Channel<StreamMessage>,
+) {
+    // (playback origin in media time, wall clock at origin, rate) while playing
+    let mut playing: Option<(u64, Instant, f64)> = None;
+    // Command that arrived while waiting to send a frame
+    let mut pending: Option<StreamCommand> = None;
+    let mut loop_region: Option<(u64, u64)> = None;
+
+    loop {
+        let command = if pending.is_some() {
+            pending.take()
+        } else if playing.is_some() {
+            match rx.try_recv() {
+                Ok(c) => Some(c),
+                Err(mpsc::TryRecvError::Empty) => None,
+                Err(mpsc::TryRecvError::Disconnected) => return,
+            }
+        } else {
+            match rx.recv() {
+                Ok(c) => Some(c),
+                // Stream closed
+                Err(_) => return,
+            }
+        };
+
+        match command {
+            Some(StreamCommand::Play { from_ms, rate }) => {
+                if let Err(e) = demuxer.seek(from_ms) {
+                    let _ = channel.send(StreamMessage::Error { message: e.to_string() });
+                    continue;
+                }
+                playing = Some((from_ms, Instant::now(), rate.clamp(0.1, 4.0)));
+            }
+            Some(StreamCommand::Pause) => playing = None,
+            Some(StreamCommand::SetLoop(region)) => loop_region = region,
+            Some(StreamCommand::Seek(ts)) => {
+                playing = None;
+                let message = match demuxer.get_frame_at(ts) {
+                    Ok(frame) => StreamMessage::Frame(frame.into()),
+                    Err(e) => StreamMessage::Error { message: e.to_string() },
+                };
+                if channel.send(message).is_err() {
+                    return;
+                }
+            }
+            None => {}
+        }
+
+        let Some((origin_ms, origin, rate)) = playing else { continue };
+
+        let frame = match demuxer.next_frame() {
+            Ok(Some(frame)) => frame,
+            Ok(None) => {
+                playing = None;
+                let _ = channel.send(StreamMessage::Ended);
+                continue;
+            }
+            Err(e) => {
+                playing = None;
+                let _ = channel.send(StreamMessage::Error { message: e.to_string() });
+                continue;
+            }
+        };
+        // Loop end reached: continue from the loop start, keeping the clock
+        // continuous so the first looped frame is due right after the last
+        if let Some((loop_start, loop_end)) = loop_region {
+            if origin_ms < loop_end && frame.timestamp_ms >= loop_end {
+                let loop_at = origin + Duration::from_millis(loop_end - origin_ms).div_f64(rate);
+                if demuxer.seek(loop_start).is_ok() {
+                    playing = Some((loop_start, loop_at, rate));
+                } else {
+                    playing = None;
+                }
+                continue;
+            }
+        }
+
+        // Frames that end before the play position (decoded from the previous keyframe)
+        if frame.timestamp_ms + frame.duration_ms <= origin_ms {
+            continue;
+        }
+
+        // Wait until the frame is within the lead window, staying responsive
+        // to pause/seek while waiting
+        let media_offset = Duration::from_millis(frame.timestamp_ms.saturating_sub(origin_ms)).div_f64(rate);
+        let send_at = (origin + media_offset).checked_sub(STREAM_LEAD).unwrap_or(origin);
+        let wait = send_at.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            match rx.recv_timeout(wait) {
+                Ok(command) => {
+                    // This frame is dropped; any command changes the position
+                    pending = Some(command);
+                    continue;
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        if channel.send(StreamMessage::Frame(frame.into())).is_err() {
+            // Frontend went away (webview reloaded)
+            return;
+        }
+    }
+}
+
+struct CachedDemuxer {
+    path: PathBuf,
+    /// Whether a proxy existed when this was opened; a new proxy means reopen
+    had_proxy: bool,
+    options: PreviewOptions,
+    demuxer: Arc<Mutex<Box<dyn VideoDemuxer>>>,
+}
+
+/// Managed state: recently used demuxers for one-shot frame requests,
+/// most recently used last
+#[derive(Default)]
+pub struct DemuxerCache {
+    entries: Mutex<Vec<CachedDemuxer>>,
+}
+
+impl DemuxerCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a warm demuxer for `path`, opening it if needed
+    pub fn get(&self, path: &Path, options: PreviewOptions) -> Result<Arc<Mutex<Box<dyn VideoDemuxer>>>, VideoError> {
+        let has_proxy = proxy::find_proxy(path).is_some();
+        {
+            let mut entries = self.entries.lock();
+            if let Some(pos) = entries.iter().position(|e| e.path == path) {
+                let entry = entries.remove(pos);
+                if entry.had_proxy == has_proxy && entry.options == options && path.exists() {
+                    let demuxer = entry.demuxer.clone();
+                    entries.push(entry);
+                    return Ok(demuxer);
+                }
+            }
+        }
+
+        // Open outside the lock; opening can take a while for large files
+        let demuxer = Arc::new(Mutex::new(open_video_for_preview(path, options)?));
+        let mut entries = self.entries.lock();
+        entries.retain(|e| e.path != path);
+        if entries.len() >= DEMUXER_CACHE_SIZE {
+            entries.remove(0);
+        }
+        entries.push(CachedDemuxer {
+            path: path.to_path_buf(),
+            had_proxy: has_proxy,
+            options,
+            demuxer: demuxer.clone(),
+        });
+        Ok(demuxer)
+    }
+
+    /// Drop cached demuxers for files in `dir` (before deleting or moving a session)
+    pub fn evict_dir(&self, dir: &Path) {
+        self.entries.lock().retain(|e| !e.path.starts_with(dir));
+    }
+}
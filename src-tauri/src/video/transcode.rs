@@ -0,0 +1,553 @@
+// Re-encoding of legacy MJPEG recordings
+//
+// MJPEG is cheap to record but huge on disk. This walks the storage roots for
+// MJPEG-in-MKV recordings and re-encodes them to AV1 or H.264 one at a time
+// on a background thread, only while nothing is being recorded (a file in
+// progress is paused when recording starts and resumed afterwards).
+//
+// Each file is written to a temp file, then verified against the original:
+// the output must decode as the target codec, have the same number of frames
+// and the same duration. Only then does it take the original's file name, so
+// session metadata and side files stay valid. The original is deleted, or
+// kept next to it as `<name>.mjpeg-original` when deletion is off.
+//
+// The queue lives in memory. Re-scanning after a restart picks up where the
+// last run stopped, since finished files are no longer MJPEG.
+//
+// Pipeline: filesrc → decodebin → queue → videoconvert → capsfilter
+//           → encoder → av1parse/h264parse → matroskamux → filesink
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tauri::{Emitter, Manager};
+
+use crate::encoding::{HardwareEncoderType, VideoCodec};
+use crate::recording::{RecordingState, RecordingStatus};
+
+/// Suffix of an original kept after its re-encode replaced it
+pub const ORIGINAL_SUFFIX: &str = ".mjpeg-original";
+
+/// Temp file suffix while a re-encode is in progress
+const TEMP_SUFFIX: &str = ".transcode.tmp";
+
+/// How often the worker checks for work, pause and recording state
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Frames the output may differ by before verification fails
+/// (encoders may drop a trailing frame when flushing)
+const FRAME_COUNT_TOLERANCE: usize = 2;
+
+/// Keyframe every N frames, matching what the recorder writes
+const TRANSCODE_KEYFRAME_INTERVAL: u32 = 60;
+
+/// Codec recordings are re-encoded to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscodeTarget {
+    Av1,
+    H264,
+}
+
+impl TranscodeTarget {
+    fn codec(self) -> VideoCodec {
+        match self {
+            TranscodeTarget::Av1 => VideoCodec::Av1,
+            TranscodeTarget::H264 => VideoCodec::H264,
+        }
+    }
+
+    /// Codec name as reported by [`super::probe_video_codec`]
+    fn codec_name(self) -> &'static str {
+        match self {
+            TranscodeTarget::Av1 => "av1",
+            TranscodeTarget::H264 => "h264",
+        }
+    }
+}
+
+/// One MJPEG recording waiting to be re-encoded
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscodeJob {
+    pub session_path: PathBuf,
+    pub filename: String,
+    pub size_bytes: u64,
+}
+
+impl TranscodeJob {
+    fn path(&self) -> PathBuf {
+        self.session_path.join(&self.filename)
+    }
+}
+
+/// Snapshot of the queue, for the Settings panel and the
+/// "transcode-status" event
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscodeStatus {
+    pub target: TranscodeTarget,
+    pub delete_originals: bool,
+    pub paused: bool,
+    /// A recording is in progress, so work is on hold
+    pub waiting_for_idle: bool,
+    pub current: Option<TranscodeJob>,
+    /// Progress through the current file (0.0–1.0)
+    pub current_progress: f64,
+    pub pending: usize,
+    pub pending_bytes: u64,
+    pub completed: usize,
+    pub failed: usize,
+    /// Size of the finished originals and of their re-encodes
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+    pub last_error: Option<String>,
+}
+
+struct QueueState {
+    jobs: VecDeque<TranscodeJob>,
+    status: TranscodeStatus,
+    /// Abandon the file in progress (set by `clear`)
+    cancel_current: bool,
+}
+
+/// Background MJPEG re-encoding, one file at a time
+pub struct TranscodeQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+impl TranscodeQueue {
+    pub fn new(app: tauri::AppHandle) -> Self {
+        let state = Arc::new(Mutex::new(QueueState {
+            jobs: VecDeque::new(),
+            status: TranscodeStatus {
+                target: TranscodeTarget::Av1,
+                delete_originals: false,
+                paused: false,
+                waiting_for_idle: false,
+                current: None,
+                current_progress: 0.0,
+                pending: 0,
+                pending_bytes: 0,
+                completed: 0,
+                failed: 0,
+                bytes_before: 0,
+                bytes_after: 0,
+                last_error: None,
+            },
+            cancel_current: false,
+        }));
+        let worker_state = state.clone();
+        std::thread::Builder::new()
+            .name("sacho-transcode".into())
+            .spawn(move || run_worker(&app, &worker_state))
+            .expect("Failed to spawn transcode thread");
+        Self { state }
+    }
+
+    /// Queue `jobs` with the given options, skipping files already queued
+    pub fn enqueue(&self, jobs: Vec<TranscodeJob>, target: TranscodeTarget, delete_originals: bool) {
+        if crate::read_only::is_read_only() {
+            return;
+        }
+        let mut state = self.state.lock();
+        state.status.target = target;
+        state.status.delete_originals = delete_originals;
+        for job in jobs {
+            let current = state.status.current.as_ref().map(|c| c.path());
+            if current.as_ref() != Some(&job.path()) && !state.jobs.iter().any(|j| j.path() == job.path()) {
+                state.jobs.push_back(job);
+            }
+        }
+        update_pending(&mut state);
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.state.lock().status.paused = paused;
+    }
+
+    /// Drop every queued file and abandon the one in progress
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.jobs.clear();
+        if state.status.current.is_some() {
+            state.cancel_current = true;
+        }
+        update_pending(&mut state);
+    }
+
+    pub fn status(&self) -> TranscodeStatus {
+        self.state.lock().status.clone()
+    }
+}
+
+fn update_pending(state: &mut QueueState) {
+    state.status.pending = state.jobs.len();
+    state.status.pending_bytes = state.jobs.iter().map(|j| j.size_bytes).sum();
+}
+
+fn is_recording_idle(app: &tauri::AppHandle) -> bool {
+    app.try_state::<RwLock<RecordingState>>()
+        .map(|s| s.read().status == RecordingStatus::Idle)
+        .unwrap_or(true)
+}
+
+fn emit_status(app: &tauri::AppHandle, state: &Mutex<QueueState>) {
+    let status = state.lock().status.clone();
+    let _ = app.emit("transcode-status", status);
+}
+
+fn run_worker(app: &tauri::AppHandle, state: &Mutex<QueueState>) {
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        let idle = is_recording_idle(app);
+        let job = {
+            let mut s = state.lock();
+            s.status.waiting_for_idle = !idle && !s.jobs.is_empty();
+            if s.status.paused || !idle {
+                continue;
+            }
+            let Some(job) = s.jobs.pop_front() else { continue };
+            s.status.current = Some(job.clone());
+            s.status.current_progress = 0.0;
+            s.cancel_current = false;
+            update_pending(&mut s);
+            job
+        };
+        emit_status(app, state);
+
+        let (target, delete_originals) = {
+            let s = state.lock();
+            (s.status.target, s.status.delete_originals)
+        };
+        let result = process_job(app, state, &job, target, delete_originals);
+
+        {
+            let mut s = state.lock();
+            s.status.current = None;
+            s.status.current_progress = 0.0;
+            match result {
+                Ok(Some(new_size)) => {
+                    s.status.completed += 1;
+                    s.status.bytes_before += job.size_bytes;
+                    s.status.bytes_after += new_size;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    println!("[Sacho] Failed to re-encode {}: {}", job.path().display(), e);
+                    s.status.failed += 1;
+                    s.status.last_error = Some(format!("{}: {}", job.filename, e));
+                }
+            }
+        }
+        emit_status(app, state);
+    }
+}
+
+/// Re-encode one file and swap it in. Returns the new size, or None if the
+/// file was skipped or the job was cancelled.
+fn process_job(
+    app: &tauri::AppHandle,
+    state: &Mutex<QueueState>,
+    job: &TranscodeJob,
+    target: TranscodeTarget,
+    delete_originals: bool,
+) -> anyhow::Result<Option<u64>> {
+    if crate::read_only::is_read_only() {
+        anyhow::bail!(crate::read_only::refusal());
+    }
+    let source = job.path();
+    // The session may have changed since it was scanned
+    if !source.exists()
+        || crate::session::has_recording_lock(&job.session_path)
+        || crate::session::archive::is_archived(&job.session_path)
+        || super::probe_video_codec(&source).map(|info| info.codec != "mjpeg").unwrap_or(true)
+    {
+        return Ok(None);
+    }
+
+    let temp_path = temp_path(&source);
+    let finished = transcode_video(&source, &temp_path, target, |progress| {
+        let idle = is_recording_idle(app);
+        let mut s = state.lock();
+        s.status.current_progress = progress;
+        s.status.waiting_for_idle = !idle;
+        if s.cancel_current {
+            return Control::Cancel;
+        }
+        if s.status.paused || !idle {
+            return Control::Pause;
+        }
+        Control::Continue
+    });
+    match finished {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = std::fs::remove_file(&temp_path);
+            return Ok(None);
+        }
+        Err(e) => {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e);
+        }
+    }
+
+    if let Err(e) = verify_transcode(&source, &temp_path, target) {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    if let Some(cache) = app.try_state::<super::stream::DemuxerCache>() {
+        cache.evict_dir(&job.session_path);
+    }
+    replace_original(&source, &temp_path, delete_originals)?;
+
+    let new_size = std::fs::metadata(&source).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "[Sacho] Re-encoded {} to {:?} ({} -> {} bytes)",
+        source.display(),
+        target,
+        job.size_bytes,
+        new_size
+    );
+
+    // AV1 is slow to scrub, so it gets a proxy like a fresh AV1 recording
+    if super::proxy::codec_needs_proxy(target.codec_name()) {
+        if let Some(proxies) = app.try_state::<super::proxy::ProxyQueue>() {
+            proxies.enqueue(source.clone());
+        }
+    }
+    Ok(Some(new_size))
+}
+
+fn temp_path(source: &Path) -> PathBuf {
+    let mut name = source.file_name().unwrap_or_default().to_os_string();
+    name.push(TEMP_SUFFIX);
+    source.with_file_name(name)
+}
+
+/// Move the verified re-encode into the original's place
+fn replace_original(source: &Path, transcoded: &Path, delete_original: bool) -> anyhow::Result<()> {
+    if !delete_original {
+        let mut kept = source.file_name().unwrap_or_default().to_os_string();
+        kept.push(ORIGINAL_SUFFIX);
+        std::fs::rename(source, source.with_file_name(kept))
+            .map_err(|e| anyhow::anyhow!("Failed to set original aside: {}", e))?;
+    }
+    std::fs::rename(transcoded, source)
+        .map_err(|e| anyhow::anyhow!("Failed to replace original: {}", e))
+}
+
+/// MJPEG-in-MKV recordings across all storage roots, skipping sessions that
+/// are recording or archived
+pub fn find_mjpeg_recordings(roots: &[PathBuf]) -> Vec<TranscodeJob> {
+    let mut jobs = Vec::new();
+    for root in roots {
+        let Ok(sessions) = std::fs::read_dir(root) else { continue };
+        for session in sessions.flatten() {
+            let session_path = session.path();
+            if !session_path.is_dir()
+                || crate::session::has_recording_lock(&session_path)
+                || crate::session::archive::is_archived(&session_path)
+            {
+                continue;
+            }
+            let Ok(files) = std::fs::read_dir(&session_path) else { continue };
+            for file in files.flatten() {
+                let filename = file.file_name().to_string_lossy().to_string();
+                if !filename.to_lowercase().ends_with(".mkv") || super::proxy::is_proxy_file(&filename) {
+                    continue;
+                }
+                let is_mjpeg = super::probe_video_codec(file.path())
+                    .map(|info| info.codec == "mjpeg")
+                    .unwrap_or(false);
+                if is_mjpeg {
+                    jobs.push(TranscodeJob {
+                        session_path: session_path.clone(),
+                        size_bytes: file.metadata().map(|m| m.len()).unwrap_or(0),
+                        filename,
+                    });
+                }
+            }
+        }
+    }
+    jobs.sort_by(|a, b| a.session_path.cmp(&b.session_path).then_with(|| a.filename.cmp(&b.filename)));
+    jobs
+}
+
+/// What a running transcode should do next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Control {
+    Continue,
+    Pause,
+    Cancel,
+}
+
+/// Encoder element and backend for a target on this machine
+fn select_encoder(target: TranscodeTarget) -> anyhow::Result<(HardwareEncoderType, &'static str)> {
+    let codec = target.codec();
+    let hw = crate::encoding::detect_best_encoder_for_codec(codec)
+        .ok_or_else(|| anyhow::anyhow!("No {} encoder available", codec.display_name()))?;
+    let element = match target {
+        TranscodeTarget::Av1 => hw.av1_encoder_element(),
+        TranscodeTarget::H264 => hw.h264_encoder_element(),
+    }
+    .ok_or_else(|| anyhow::anyhow!("No {} encoder available", codec.display_name()))?;
+    Ok((hw, element))
+}
+
+/// Re-encode `source` into `output`. `control` is called with the progress
+/// a few times a second; the pipeline is paused while it returns
+/// `Control::Pause`. Returns false if the transcode was cancelled.
+fn transcode_video(
+    source: &Path,
+    output: &Path,
+    target: TranscodeTarget,
+    mut control: impl FnMut(f64) -> Control,
+) -> anyhow::Result<bool> {
+    gst::init().map_err(|e| anyhow::anyhow!("GStreamer init failed: {}", e))?;
+
+    let codec = target.codec();
+    let (hw_type, encoder_name) = select_encoder(target)?;
+
+    let pipeline = gst::Pipeline::new();
+    let make = |name: &str| {
+        gst::ElementFactory::make(name)
+            .build()
+            .map_err(|e| anyhow::anyhow!("Failed to create {}: {}", name, e))
+    };
+
+    let filesrc = gst::ElementFactory::make("filesrc")
+        .property("location", source.to_string_lossy().to_string())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create filesrc: {}", e))?;
+    let decodebin = make("decodebin")?;
+    let queue = make("queue")?;
+    let videoconvert = make("videoconvert")?;
+    let capsfilter = gst::ElementFactory::make("capsfilter")
+        .property("caps", gst::Caps::builder("video/x-raw").field("format", "I420").build())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create capsfilter: {}", e))?;
+    let encoder = make(encoder_name)?;
+    crate::encoding::presets::apply_preset(
+        &encoder,
+        codec,
+        hw_type,
+        crate::encoding::DEFAULT_PRESET,
+        crate::encoding::DEFAULT_PRESET,
+        TRANSCODE_KEYFRAME_INTERVAL,
+    );
+    let parser = make(codec.gst_parser())?;
+    let mux = make("matroskamux")?;
+    let filesink = gst::ElementFactory::make("filesink")
+        .property("location", output.to_string_lossy().to_string())
+        .build()
+        .map_err(|e| anyhow::anyhow!("Failed to create filesink: {}", e))?;
+
+    let chain = [queue.clone(), videoconvert, capsfilter, encoder, parser, mux, filesink];
+    pipeline.add_many([&filesrc, &decodebin])
+        .map_err(|e| anyhow::anyhow!("Failed to add elements: {}", e))?;
+    pipeline.add_many(&chain)
+        .map_err(|e| anyhow::anyhow!("Failed to add elements: {}", e))?;
+    filesrc.link(&decodebin)
+        .map_err(|e| anyhow::anyhow!("Failed to link filesrc -> decodebin: {}", e))?;
+    gst::Element::link_many(&chain)
+        .map_err(|e| anyhow::anyhow!("Failed to link transcode chain: {}", e))?;
+
+    let queue_weak = queue.downgrade();
+    decodebin.connect_pad_added(move |_decodebin, src_pad| {
+        let is_video = src_pad
+            .current_caps()
+            .and_then(|caps| caps.structure(0).map(|s| s.name().as_str().starts_with("video/")))
+            .unwrap_or(false);
+        if !is_video {
+            return;
+        }
+        if let Some(queue) = queue_weak.upgrade() {
+            if let Some(sink_pad) = queue.static_pad("sink") {
+                if !sink_pad.is_linked() {
+                    if let Err(e) = src_pad.link(&sink_pad) {
+                        println!("[Sacho] Warning: Failed to link transcode video pad: {:?}", e);
+                    }
+                }
+            }
+        }
+    });
+
+    pipeline.set_state(gst::State::Playing)
+        .map_err(|e| anyhow::anyhow!("Failed to start transcode: {:?}", e))?;
+
+    let bus = pipeline.bus().ok_or_else(|| anyhow::anyhow!("No pipeline bus for transcode"))?;
+    let poll = gst::ClockTime::from_mseconds(POLL_INTERVAL.as_millis() as u64);
+    let mut paused = false;
+    let result = loop {
+        if let Some(msg) = bus.timed_pop(poll) {
+            match msg.view() {
+                gst::MessageView::Eos(..) => break Ok(true),
+                gst::MessageView::Error(err) => {
+                    break Err(anyhow::anyhow!(
+                        "Transcode error: {} ({})",
+                        err.error(),
+                        err.debug().unwrap_or_default()
+                    ));
+                }
+                _ => continue,
+            }
+        }
+
+        let position = pipeline.query_position::<gst::ClockTime>();
+        let duration = pipeline.query_duration::<gst::ClockTime>();
+        let progress = match (position, duration) {
+            (Some(pos), Some(dur)) if dur.nseconds() > 0 => {
+                (pos.nseconds() as f64 / dur.nseconds() as f64).clamp(0.0, 1.0)
+            }
+            _ => 0.0,
+        };
+        match control(progress) {
+            Control::Cancel => break Ok(false),
+            Control::Pause if !paused => {
+                let _ = pipeline.set_state(gst::State::Paused);
+                paused = true;
+            }
+            Control::Continue if paused => {
+                let _ = pipeline.set_state(gst::State::Playing);
+                paused = false;
+            }
+            _ => {}
+        }
+    };
+    pipeline.set_state(gst::State::Null).ok();
+    result
+}
+
+/// Check a finished re-encode against its original: target codec, same
+/// frame count, same duration
+fn verify_transcode(source: &Path, output: &Path, target: TranscodeTarget) -> anyhow::Result<()> {
+    let info = super::probe_video_codec(output)
+        .map_err(|e| anyhow::anyhow!("Re-encoded file is unreadable: {}", e))?;
+    if info.codec != target.codec_name() {
+        anyhow::bail!("Re-encoded file is {} instead of {:?}", info.codec, target);
+    }
+
+    let source_frames = crate::session::validation::read_video_timestamps(source)?.len();
+    let output_frames = crate::session::validation::read_video_timestamps(output)?.len();
+    if source_frames.abs_diff(output_frames) > FRAME_COUNT_TOLERANCE {
+        anyhow::bail!("Frame count mismatch: {} in original, {} re-encoded", source_frames, output_frames);
+    }
+
+    let source_duration = crate::session::read_video_duration(source)?;
+    let output_duration = crate::session::read_video_duration(output)?;
+    if (source_duration - output_duration).abs() > crate::session::validation::DURATION_TOLERANCE_SECS {
+        anyhow::bail!(
+            "Duration mismatch: {:.1}s in original, {:.1}s re-encoded",
+            source_duration,
+            output_duration
+        );
+    }
+    Ok(())
+}
@@ -11,9 +11,10 @@
 //!   --verbose             Extra debug output
 //!   --keep-sessions       Don't clean up temp dirs (for debugging)
 //!   --list                List all tests without running them
+//!   --fake-devices        Use simulated MIDI/audio/video devices instead of hardware
 
 use sacho_lib::gstreamer_init;
-use sacho_lib::test_harness::{discovery, permutations, runner};
+use sacho_lib::test_harness::{discovery, fake_devices, permutations, runner};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -21,6 +22,7 @@ fn main() {
     let verbose = args.iter().any(|a| a == "--verbose");
     let keep_sessions = args.iter().any(|a| a == "--keep-sessions");
     let list_only = args.iter().any(|a| a == "--list");
+    let use_fake_devices = args.iter().any(|a| a == "--fake-devices");
 
     let filter = args.iter()
         .position(|a| a == "--filter")
@@ -45,11 +47,19 @@ fn main() {
     // Init GStreamer
     gstreamer_init::init_gstreamer_env();
 
-    // Discover hardware — test_devices.toml lives in the parent crate (src-tauri/)
-    let crate_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    let sacho_root = crate_dir.parent().expect("tools crate must be inside src-tauri/");
-    let mut device_config = discovery::load_device_config(sacho_root);
-    discovery::resolve_devices(&mut device_config);
+    let device_config = if use_fake_devices {
+        // Simulated devices — no hardware or loopback driver required
+        let set = fake_devices::FakeDeviceSet::standard();
+        fake_devices::install(set.clone());
+        discovery::fake_device_config(&set)
+    } else {
+        // Discover hardware — test_devices.toml lives in the parent crate (src-tauri/)
+        let crate_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let sacho_root = crate_dir.parent().expect("tools crate must be inside src-tauri/");
+        let mut device_config = discovery::load_device_config(sacho_root);
+        discovery::resolve_devices(&mut device_config);
+        device_config
+    };
     discovery::print_inventory(&device_config);
 
     // Build test matrix
@@ -0,0 +1,555 @@
+//! Sacho File Repair Fuzzer
+//!
+//! Property checks for the crash-repair functions, which parse untrusted bytes
+//! with manual offsets. Valid files are generated (MIDI through the app's own
+//! `MidiStreamWriter`, WAV by hand, FLAC/MKV through GStreamer), then damaged
+//! by truncation and random byte corruption. For every damaged file:
+//!
+//! - `*_needs_repair` and `repair_*` must not panic
+//! - a repair must either fail cleanly or leave a file that no longer needs
+//!   repair, and (for the in-place repairs) repairing it again must not change it
+//! - MIDI files repaired after truncation must parse; WAV data chunks must
+//!   hold whole frames and reach end of file
+//!
+//! Also round-trips `MidiStreamWriter` (finished and crashed) through midly.
+//!
+//! Usage:
+//!   cargo run -p sacho-tools --bin repair_fuzz [-- [OPTIONS]]
+//!
+//! Options:
+//!   --iterations <n>   Random corruption cases per format (default: 500)
+//!   --seed <n>         Seed for the generator (default: time-based; printed)
+//!   --skip-media       Skip FLAC and MKV (they need GStreamer and are slow)
+//!   --keep-failures    Copy failing inputs to ./repair_fuzz_failures/
+
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+
+use sacho_lib::gstreamer_init;
+use sacho_lib::recording::midi::TimestampedMidiEvent;
+use sacho_lib::recording::monitor::{
+    flac_file_needs_repair, midi_file_needs_repair, repair_flac_file, repair_midi_file_on_disk,
+    repair_video_file, repair_wav_file, video_file_needs_repair, wav_file_needs_repair,
+    MidiStreamWriter,
+};
+
+const DEFAULT_ITERATIONS: usize = 500;
+/// Truncation points tried for the slow (GStreamer-backed) formats
+const MEDIA_TRUNCATIONS: usize = 24;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Deterministic random source
+// ═══════════════════════════════════════════════════════════════════════════════
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        // xorshift64*
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Damage
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Clone)]
+enum Damage {
+    Truncate(usize),
+    /// Flip bytes at these offsets (then truncate, if set)
+    Corrupt(Vec<(usize, u8)>, Option<usize>),
+}
+
+impl Damage {
+    fn apply(&self, valid: &[u8]) -> Vec<u8> {
+        match self {
+            Damage::Truncate(len) => valid[..(*len).min(valid.len())].to_vec(),
+            Damage::Corrupt(flips, truncate) => {
+                let mut bytes = valid.to_vec();
+                for &(offset, value) in flips {
+                    if let Some(b) = bytes.get_mut(offset) {
+                        *b = value;
+                    }
+                }
+                if let Some(len) = truncate {
+                    bytes.truncate(*len);
+                }
+                bytes
+            }
+        }
+    }
+
+    /// Random corruption, biased towards the header where the offsets live
+    fn random(rng: &mut Rng, len: usize, header_len: usize) -> Self {
+        let flips = (0..1 + rng.below(4))
+            .map(|_| {
+                let offset = if rng.below(4) == 0 { rng.below(len) } else { rng.below(header_len.min(len)) };
+                (offset, rng.next() as u8)
+            })
+            .collect();
+        let truncate = (rng.below(2) == 0).then(|| rng.below(len + 1));
+        Damage::Corrupt(flips, truncate)
+    }
+}
+
+/// Every length up to `dense`, then evenly spaced cuts through the rest
+fn truncation_points(len: usize, dense: usize, sparse: usize) -> Vec<usize> {
+    let mut points: Vec<usize> = (0..len.min(dense)).collect();
+    if len > dense {
+        let step = ((len - dense) / sparse.max(1)).max(1);
+        points.extend((dense..len).step_by(step));
+    }
+    points
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Property check
+// ═══════════════════════════════════════════════════════════════════════════════
+
+struct Format {
+    name: &'static str,
+    extension: &'static str,
+    needs_repair: fn(&PathBuf) -> bool,
+    repair: fn(&PathBuf) -> Result<(), String>,
+    /// Extra check on a file successfully repaired after truncation
+    /// (corrupted headers may legitimately "repair" into nonsense)
+    verify: fn(&[u8]) -> Result<(), String>,
+    /// Whether a second repair must leave the file byte-identical. False for
+    /// MKV, which is remuxed and gets a fresh muxing date each time.
+    idempotent: bool,
+}
+
+#[derive(Default)]
+struct Tally {
+    cases: usize,
+    repaired: usize,
+    rejected: usize,
+    failures: Vec<String>,
+}
+
+fn check_case(
+    format: &Format,
+    dir: &Path,
+    valid: &[u8],
+    damage: &Damage,
+    keep_failures: bool,
+    tally: &mut Tally,
+) {
+    let input = damage.apply(valid);
+    let path = dir.join(format!("case.{}", format.extension));
+    std::fs::write(&path, &input).expect("Failed to write test case");
+    tally.cases += 1;
+
+    let outcome = panic::catch_unwind(AssertUnwindSafe(|| -> Result<bool, String> {
+        (format.needs_repair)(&path);
+        if (format.repair)(&path).is_err() {
+            return Ok(false);
+        }
+        let repaired = std::fs::read(&path).map_err(|e| e.to_string())?;
+        if (format.needs_repair)(&path) {
+            return Err("still needs repair after a successful repair".into());
+        }
+        if matches!(damage, Damage::Truncate(_)) {
+            (format.verify)(&repaired)?;
+        }
+        if format.idempotent {
+            (format.repair)(&path).map_err(|e| format!("second repair failed: {}", e))?;
+            if std::fs::read(&path).map_err(|e| e.to_string())? != repaired {
+                return Err("second repair changed the file".into());
+            }
+        }
+        Ok(true)
+    }));
+
+    let failure = match outcome {
+        Ok(Ok(true)) => {
+            tally.repaired += 1;
+            None
+        }
+        Ok(Ok(false)) => {
+            tally.rejected += 1;
+            None
+        }
+        Ok(Err(e)) => Some(e),
+        Err(panic) => Some(format!(
+            "PANIC: {}",
+            panic
+                .downcast_ref::<String>()
+                .cloned()
+                .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+                .unwrap_or_default()
+        )),
+    };
+
+    if let Some(reason) = failure {
+        let message = format!("{} {:?}: {}", format.name, damage, reason);
+        println!("  FAIL {}", message);
+        if keep_failures {
+            let out = PathBuf::from("repair_fuzz_failures");
+            let _ = std::fs::create_dir_all(&out);
+            let name = format!("{}_{}.{}", format.name, tally.failures.len(), format.extension);
+            let _ = std::fs::write(out.join(name), &input);
+        }
+        tally.failures.push(message);
+    }
+}
+
+fn run_format(
+    format: &Format,
+    valid: &[u8],
+    header_len: usize,
+    truncations: Vec<usize>,
+    iterations: usize,
+    rng: &mut Rng,
+    keep_failures: bool,
+) -> Tally {
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let mut tally = Tally::default();
+    for len in truncations {
+        check_case(format, dir.path(), valid, &Damage::Truncate(len), keep_failures, &mut tally);
+    }
+    for _ in 0..iterations {
+        let damage = Damage::random(rng, valid.len(), header_len);
+        check_case(format, dir.path(), valid, &damage, keep_failures, &mut tally);
+    }
+    println!(
+        "  {:<5} {} cases: {} repaired, {} rejected, {} failed",
+        format.name,
+        tally.cases,
+        tally.repaired,
+        tally.rejected,
+        tally.failures.len()
+    );
+    tally
+}
+
+fn no_verify(_: &[u8]) -> Result<(), String> {
+    Ok(())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// MIDI
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn random_midi_events(rng: &mut Rng, count: usize) -> Vec<TimestampedMidiEvent> {
+    let mut timestamp_us = 0u64;
+    (0..count)
+        .map(|_| {
+            timestamp_us += rng.below(200_000) as u64;
+            let channel = rng.below(16) as u8;
+            let data = match rng.below(5) {
+                0 => vec![0x90 | channel, rng.below(128) as u8, 1 + rng.below(127) as u8],
+                1 => vec![0x80 | channel, rng.below(128) as u8, 0],
+                2 => vec![0xB0 | channel, rng.below(128) as u8, rng.below(128) as u8],
+                3 => vec![0xC0 | channel, rng.below(128) as u8],
+                _ => vec![0xE0 | channel, rng.below(128) as u8, rng.below(128) as u8],
+            };
+            TimestampedMidiEvent { timestamp_us, data }
+        })
+        .collect()
+}
+
+/// Channel messages in a parsed file, as raw bytes
+fn parsed_messages(bytes: &[u8]) -> Result<Vec<Vec<u8>>, String> {
+    let smf = midly::Smf::parse(bytes).map_err(|e| format!("midly: {}", e))?;
+    let mut messages = Vec::new();
+    for track in &smf.tracks {
+        for event in track {
+            if let midly::TrackEventKind::Midi { channel, message } = event.kind {
+                let mut raw = Vec::new();
+                midly::live::LiveEvent::Midi { channel, message }
+                    .write_std(&mut raw)
+                    .map_err(|e| e.to_string())?;
+                messages.push(raw);
+            }
+        }
+    }
+    Ok(messages)
+}
+
+fn verify_midi(bytes: &[u8]) -> Result<(), String> {
+    parsed_messages(bytes).map(|_| ())
+}
+
+/// Write events with `MidiStreamWriter`; if `finish` is false the writer is
+/// dropped as if the app crashed
+fn write_midi(dir: &Path, events: &[TimestampedMidiEvent], finish: bool) -> PathBuf {
+    let path = dir.to_path_buf();
+    let mut writer = MidiStreamWriter::new(&path, "roundtrip.mid", "Fuzz").expect("Failed to create MIDI writer");
+    for event in events {
+        writer.push_event(event);
+    }
+    if finish {
+        writer.finish().expect("Failed to finish MIDI writer");
+    }
+    path.join("roundtrip.mid")
+}
+
+fn midi_round_trips(rng: &mut Rng) -> Vec<String> {
+    let mut failures = Vec::new();
+    let dir = tempfile::tempdir().expect("Failed to create temp dir");
+    for round in 0..20 {
+        let events = random_midi_events(rng, 1 + rng.below(400));
+        let expected: Vec<Vec<u8>> = events.iter().map(|e| e.data.clone()).collect();
+
+        // Finished file
+        let path = write_midi(dir.path(), &events, true);
+        let bytes = std::fs::read(&path).unwrap();
+        match parsed_messages(&bytes) {
+            Ok(parsed) if parsed == expected => {}
+            Ok(parsed) => failures.push(format!(
+                "round trip {}: wrote {} events, read back {} (or different bytes)",
+                round,
+                expected.len(),
+                parsed.len()
+            )),
+            Err(e) => failures.push(format!("round trip {}: {}", round, e)),
+        }
+        if midi_file_needs_repair(&path) {
+            failures.push(format!("round trip {}: finished file reported as needing repair", round));
+        }
+
+        // Crashed file: repair must recover every event
+        let path = write_midi(dir.path(), &events, false);
+        if !midi_file_needs_repair(&path) {
+            failures.push(format!("crash {}: unfinished file not detected", round));
+        }
+        if let Err(e) = repair_midi_file_on_disk(&path) {
+            failures.push(format!("crash {}: repair failed: {}", round, e));
+            continue;
+        }
+        match parsed_messages(&std::fs::read(&path).unwrap()) {
+            Ok(parsed) if parsed == expected => {}
+            Ok(parsed) => failures.push(format!(
+                "crash {}: recovered {} of {} events",
+                round,
+                parsed.len(),
+                expected.len()
+            )),
+            Err(e) => failures.push(format!("crash {}: {}", round, e)),
+        }
+    }
+    println!("  MIDI round trips: {} failed", failures.len());
+    failures
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// WAV
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// 16-bit stereo 48 kHz sine, with a LIST chunk before data like many writers emit
+fn make_wav() -> Vec<u8> {
+    let channels: u16 = 2;
+    let sample_rate: u32 = 48000;
+    let frames = sample_rate / 4;
+    let mut data = Vec::with_capacity(frames as usize * 4);
+    for i in 0..frames {
+        let sample = ((i as f32 * 440.0 / sample_rate as f32 * std::f32::consts::TAU).sin() * 16000.0) as i16;
+        for _ in 0..channels {
+            data.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+    let list = b"INFOISFT\x06\x00\x00\x00Sacho\x00";
+
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(4 + 24 + 8 + list.len() as u32 + 8 + data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 4).to_le_bytes());
+    wav.extend_from_slice(&4u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"LIST");
+    wav.extend_from_slice(&(list.len() as u32).to_le_bytes());
+    wav.extend_from_slice(list);
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(&data);
+    wav
+}
+
+/// The data chunk must hold whole frames and end exactly at end of file
+fn verify_wav(bytes: &[u8]) -> Result<(), String> {
+    let data_offset = bytes
+        .windows(4)
+        .position(|w| w == b"data")
+        .ok_or("no data chunk")?;
+    let size_bytes: [u8; 4] = bytes
+        .get(data_offset + 4..data_offset + 8)
+        .and_then(|s| s.try_into().ok())
+        .ok_or("truncated data header")?;
+    let data_size = u32::from_le_bytes(size_bytes) as usize;
+    if data_offset + 8 + data_size != bytes.len() {
+        return Err(format!("data size {} doesn't reach end of file", data_size));
+    }
+    Ok(())
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// FLAC / MKV (generated with GStreamer)
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn render(pipeline: &str, output: &Path) -> Option<Vec<u8>> {
+    let description = format!("{} ! filesink location=\"{}\"", pipeline, output.to_string_lossy().replace('\\', "/"));
+    let pipeline = gst::parse::launch(&description).ok()?;
+    pipeline.set_state(gst::State::Playing).ok()?;
+    let bus = pipeline.bus()?;
+    let mut ok = false;
+    for msg in bus.iter_timed(gst::ClockTime::from_seconds(30)) {
+        match msg.view() {
+            gst::MessageView::Eos(..) => {
+                ok = true;
+                break;
+            }
+            gst::MessageView::Error(err) => {
+                println!("  Could not generate sample file: {}", err.error());
+                break;
+            }
+            _ => {}
+        }
+    }
+    pipeline.set_state(gst::State::Null).ok();
+    if ok { std::fs::read(output).ok() } else { None }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Main
+// ═══════════════════════════════════════════════════════════════════════════════
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    let arg_value = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+            .and_then(|v| v.parse::<u64>().ok())
+    };
+
+    let iterations = arg_value("--iterations").map(|n| n as usize).unwrap_or(DEFAULT_ITERATIONS);
+    let seed = arg_value("--seed").unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(1)
+    });
+    let skip_media = args.iter().any(|a| a == "--skip-media");
+    let keep_failures = args.iter().any(|a| a == "--keep-failures");
+
+    // On Windows, attach to parent console for output
+    #[cfg(windows)]
+    unsafe {
+        use windows_sys::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+
+    println!("\n=== Sacho Repair Fuzzer ===\n");
+    println!("  Seed: {} (rerun with --seed {})\n", seed, seed);
+
+    // Panics are caught and reported per case; keep the default hook quiet
+    panic::set_hook(Box::new(|_| {}));
+
+    let mut rng = Rng(seed.max(1));
+    let mut failures = Vec::new();
+
+    failures.extend(midi_round_trips(&mut rng));
+
+    let midi_format = Format {
+        name: "MIDI",
+        extension: "mid",
+        needs_repair: midi_file_needs_repair,
+        repair: |p| repair_midi_file_on_disk(p).map(|_| ()).map_err(|e| e.to_string()),
+        verify: verify_midi,
+        idempotent: true,
+    };
+    let midi_dir = tempfile::tempdir().expect("Failed to create temp dir");
+    let events = random_midi_events(&mut rng, 300);
+    let valid_midi = std::fs::read(write_midi(midi_dir.path(), &events, true)).unwrap();
+    let truncations = truncation_points(valid_midi.len(), 64, 200);
+    failures.extend(run_format(&midi_format, &valid_midi, 22, truncations, iterations, &mut rng, keep_failures).failures);
+
+    let wav_format = Format {
+        name: "WAV",
+        extension: "wav",
+        needs_repair: wav_file_needs_repair,
+        repair: |p| repair_wav_file(p).map(|_| ()).map_err(|e| e.to_string()),
+        verify: verify_wav,
+        idempotent: true,
+    };
+    let valid_wav = make_wav();
+    let truncations = truncation_points(valid_wav.len(), 96, 200);
+    failures.extend(run_format(&wav_format, &valid_wav, 72, truncations, iterations, &mut rng, keep_failures).failures);
+
+    if skip_media {
+        println!("  Skipping FLAC/MKV (--skip-media)");
+    } else {
+        gstreamer_init::init_gstreamer_env();
+        if let Err(e) = gst::init() {
+            println!("  GStreamer unavailable ({}), skipping FLAC/MKV", e);
+        } else {
+            // Media repairs run a GStreamer pipeline per case; keep the count down
+            let media_iterations = (iterations / 10).max(10);
+            let media_dir = tempfile::tempdir().expect("Failed to create temp dir");
+
+            let flac_format = Format {
+                name: "FLAC",
+                extension: "flac",
+                needs_repair: flac_file_needs_repair,
+                repair: |p| repair_flac_file(p).map(|_| ()).map_err(|e| e.to_string()),
+                verify: no_verify,
+                idempotent: true,
+            };
+            match render(
+                "audiotestsrc num-buffers=100 ! audio/x-raw,rate=48000,channels=2 ! flacenc",
+                &media_dir.path().join("sample.flac"),
+            ) {
+                Some(valid) => {
+                    let truncations = truncation_points(valid.len(), 0, MEDIA_TRUNCATIONS);
+                    failures.extend(run_format(&flac_format, &valid, 42, truncations, media_iterations, &mut rng, keep_failures).failures);
+                }
+                None => println!("  FLAC sample could not be generated, skipping"),
+            }
+
+            let mkv_format = Format {
+                name: "MKV",
+                extension: "mkv",
+                needs_repair: video_file_needs_repair,
+                repair: |p| repair_video_file(p).map(|_| ()).map_err(|e| e.to_string()),
+                verify: no_verify,
+                idempotent: false,
+            };
+            match render(
+                "videotestsrc num-buffers=60 ! video/x-raw,width=320,height=240,framerate=30/1 ! vp8enc deadline=1 ! matroskamux",
+                &media_dir.path().join("sample.mkv"),
+            ) {
+                Some(valid) => {
+                    let truncations = truncation_points(valid.len(), 0, MEDIA_TRUNCATIONS);
+                    failures.extend(run_format(&mkv_format, &valid, 256, truncations, media_iterations, &mut rng, keep_failures).failures);
+                }
+                None => println!("  MKV sample could not be generated, skipping"),
+            }
+        }
+    }
+
+    println!();
+    if failures.is_empty() {
+        println!("=== All repair properties held ===");
+    } else {
+        println!("=== {} failure(s) (seed {}) ===", failures.len(), seed);
+        std::process::exit(1);
+    }
+}
@@ -1,6 +1,6 @@
 // Tauri command bindings
 
-import { invoke } from "@tauri-apps/api/core";
+import { invoke, Channel } from "@tauri-apps/api/core";
 import {
   enable as enableAutostart,
   disable as disableAutostart,
@@ -16,12 +16,21 @@ export interface AudioDevice {
   channels: number;
   sample_rate: number;
   is_default: boolean;
+  /** Alias from a matching device alias rule */
+  alias?: string | null;
+  /** Bit depth the driver delivers, when it reports one */
+  native_bit_depth?: AudioBitDepth | null;
 }
 
 export interface MidiDevice {
   id: string;
   name: string;
   port_index: number;
+  alias?: string | null;
+  /** Bluetooth LE MIDI device */
+  bluetooth?: boolean;
+  /** Latency compensated unless calibrated (set for Bluetooth ports) */
+  default_latency_ms?: number | null;
 }
 
 /** Supported video codecs */
@@ -34,9 +43,23 @@ export type VideoCodec =
   | "ffv1"
   | "h264";
 
+/** What a video device is pointed at, used to tune the encoder */
+export type VideoContentType = "camera" | "screen" | "static";
+
+/** Rectangle in normalized (0-1) frame coordinates */
+export interface RegionOfInterest {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
 /** Supported container formats */
 export type ContainerFormat = "mkv" | "webm" | "mp4";
 
+/** Where new sessions are created when multiple storage roots are configured */
+export type StorageRouting = "primary" | "first_with_free_space" | "most_free_space";
+
 /** Hardware encoder backend types */
 export type HardwareEncoderType =
   | "nvenc"
@@ -61,6 +84,22 @@ export interface VideoDevice {
   /** Per-format capabilities: format string -> list of resolutions with available framerates.
    * Format strings are actual pixel/codec names from GStreamer (e.g. "YUY2", "NV12", "MJPEG", "H264"). */
   capabilities: Record<string, CodecCapability[]>;
+  alias?: string | null;
+  /** Physical port the device is plugged into, when the platform reports it */
+  usb_path?: string | null;
+}
+
+/** A stable, user-chosen name for a device and the rule that recognizes it */
+export interface DeviceAlias {
+  /** 0 for an alias not yet saved */
+  id: number;
+  alias: string;
+  device_type: "audio" | "midi" | "video";
+  /** Device name to match; `*` matches any run of characters */
+  name_pattern: string;
+  /** USB path the device must be on (video only; null = any port) */
+  usb_path: string | null;
+  last_device_id: string | null;
 }
 
 /** Per-device video source configuration. */
@@ -82,11 +121,26 @@ export interface VideoDeviceConfig {
   effort_level: number;
   /** Encoding bit depth for lossless codecs like FFV1. null = 8-bit default. */
   video_bit_depth: number | null;
+  /** Duplicate/drop frames so the encoded file has a constant frame rate */
+  constant_frame_rate: boolean;
+  /** Keyframe spacing when encoding (default 2 seconds) */
+  keyframe_interval: KeyframeInterval;
+  /** Maximum consecutive B-frames. null = preset default. */
+  b_frames: number | null;
+  /** What the device is pointed at, for encoder tuning (AV1 only) */
+  content_type: VideoContentType;
+  /** Region encoded at higher quality, in normalized 0-1 coordinates. null = none. */
+  roi: RegionOfInterest | null;
   target_width: number;
   target_height: number;
   target_fps: number;
 }
 
+export interface KeyframeInterval {
+  unit: "seconds" | "frames";
+  value: number;
+}
+
 /** Check if a video device supports any recording format */
 export function isVideoDeviceSupported(device: VideoDevice): boolean {
   return Object.keys(device.capabilities).length > 0;
@@ -127,7 +181,13 @@ export function isRawFormat(format: string): boolean {
 
 /** Returns true if a GStreamer source format name is 10-bit or higher */
 export function is10BitFormat(format: string): boolean {
-  return format.includes("10");
+  return (
+    format.startsWith("P01") ||
+    format.includes("10") ||
+    format.includes("_12") ||
+    format.includes("_16") ||
+    format.startsWith("GRAY16")
+  );
 }
 
 /** Get a friendly resolution label like "1080p (1920x1080)" */
@@ -293,6 +353,11 @@ export function computeDefaultConfig(
     preset_level: 3,
     effort_level: 3,
     video_bit_depth: null,
+    keyframe_interval: { unit: "seconds", value: 2 },
+    b_frames: null,
+    constant_frame_rate: false,
+    content_type: "camera",
+    roi: null,
     target_width: width,
     target_height: height,
     target_fps: fps,
@@ -306,6 +371,26 @@ export interface VideoFpsWarning {
   expected_fps: number;
 }
 
+/** A camera delivered near-black frames at the start of a recording */
+export interface BlankVideoWarning {
+  device_id: string;
+  device_name: string;
+  /** Brightest mean luma (0-255) among the frames checked */
+  mean_luma: number;
+}
+
+/** A video encoder failed mid-recording and was replaced */
+export interface EncoderFailover {
+  device_name: string;
+  failed_encoder: string;
+  fallback_encoder: string;
+  error: string;
+  /** Seconds into the recording the new segment starts (null during pre-roll) */
+  at_secs: number | null;
+  /** File the recording continues in */
+  segment_filename: string | null;
+}
+
 export interface RecordingState {
   status: "idle" | "recording" | "stopping" | "initializing";
   started_at: string | null;
@@ -325,10 +410,26 @@ export interface SessionSummary {
   has_video: boolean;
   notes: string;
   title: string | null;
+  /** Video replaced by proxies, originals in cold storage */
+  archived: boolean;
+  favorite: boolean;
+  tags: string[];
+  /** Total size of the session's files */
+  size_bytes: number;
+  /** Format of each video file */
+  video_streams: VideoStreamInfo[];
+  /** Storage drive holding the session is unreachable; shown from the index */
+  offline: boolean;
 }
 
+/** User-defined fields of a session, e.g. { student: "Ana", piece: "Clair de lune" } */
+export type CustomFields = Record<string, string>;
+
 export interface SessionMetadata {
   id: string;
+  /** Stable identity that survives folder renames and moves */
+  uid: string | null;
+  custom_fields: CustomFields;
   timestamp: string;
   duration_secs: number;
   path: string;
@@ -340,12 +441,265 @@ export interface SessionMetadata {
   recording_in_progress: boolean;
   recording_lock_updated_at: string | null;
   recording_lock_is_local: boolean;
+  archived: boolean;
+  favorite: boolean;
+  /** Set by the user or by auto-tag rules at finalize */
+  tags: string[];
+  /** Named A/B loop regions saved for practice review */
+  loops: LoopRegion[];
+  /** Piece boundaries detected in the session's MIDI */
+  chapters: Chapter[];
+  /** Timing inconsistencies found when the recording was finalized */
+  timeline_warnings: TimelineWarning[];
+  /** OS user who recorded the session (null for older sessions) */
+  owner: string | null;
+  /** Video devices that recorded in a fallback mode */
+  video_fallbacks: VideoFallback[];
+  /** Constant-frame-rate conformance per encoded video file */
+  frame_rate_reports: FrameRateReport[];
+  /** Steps taken to lighten video encoding when the machine couldn't keep up */
+  encoding_adaptations: EncodingAdaptation[];
+  /** Time from the trigger to each writer's first data */
+  startup_latency: WriterLatency[];
+  /** Capture and file sample rate of each audio file */
+  audio_rates: AudioRate[];
+  /** How the streams were aligned at start (null for older sessions) */
+  sync: SessionSync | null;
+  /** Signal and capture health (null for sessions from before it was measured) */
+  quality: SessionQuality | null;
+  /** Side-file schema the folder was written with (1 for folders predating versioning) */
+  schema_version: number;
+}
+
+export interface SessionQuality {
+  audio: AudioQuality[];
+  video: VideoQuality[];
+}
+
+export interface AudioQuality {
+  filename: string;
+  device_name: string;
+  clipped_samples: number;
+  /** Largest absolute sample (1.0 = full scale) */
+  peak: number;
+  /** Largest per-channel mean */
+  dc_offset: number;
+  dropouts: number;
+  dropout_secs: number;
+  /** Gaps filled with silence (first 100) */
+  dropout_events: DropoutEvent[];
+  /** Decode-back check of a finished FLAC (null for WAV) */
+  decode_check: DecodeCheck | null;
+}
+
+export interface DecodeCheck {
+  passed: boolean;
+  expected_frames: number;
+  decoded_frames: number;
+  error: string | null;
+}
+
+export interface DropoutEvent {
+  /** Position in the file where the silence starts */
+  at_secs: number;
+  gap_ms: number;
+  concealed_ms: number;
+}
+
+export interface VideoQuality {
+  filename: string;
+  device_name: string;
+  frames_written: number;
+  frames_dropped: number;
+  encoder_stalls: number;
+  timestamp_gaps: number;
+  /** The first seconds were near-black (lens cap, camera off) */
+  likely_blank: boolean;
+  mean_luma: number | null;
+}
+
+/** Mean level above which a DC offset is reported (matches the backend) */
+const DC_OFFSET_WARN_LEVEL = 0.01;
+
+/** Human-readable problems in a session's quality report */
+export function qualityIssues(quality: SessionQuality | null): string[] {
+  if (!quality) return [];
+  const issues: string[] = [];
+  for (const a of quality.audio) {
+    if (a.clipped_samples > 0) {
+      issues.push(`${a.filename}: ${a.clipped_samples} clipped samples`);
+    }
+    if (Math.abs(a.dc_offset) > DC_OFFSET_WARN_LEVEL) {
+      issues.push(`${a.filename}: DC offset of ${(a.dc_offset * 100).toFixed(1)}%`);
+    }
+    if (a.dropouts > 0) {
+      issues.push(
+        `${a.filename}: ${a.dropouts} dropouts (${(a.dropout_secs * 1000).toFixed(0)} ms filled with silence)`,
+      );
+    }
+    if (a.decode_check && !a.decode_check.passed) {
+      const detail = a.decode_check.error ? ` (${a.decode_check.error})` : '';
+      issues.push(
+        `${a.filename}: failed to decode back, ${a.decode_check.decoded_frames} of ${a.decode_check.expected_frames} frames readable${detail}`,
+      );
+    }
+  }
+  for (const v of quality.video) {
+    if (v.frames_dropped > 0) {
+      issues.push(`${v.filename}: ${v.frames_dropped} of ${v.frames_written + v.frames_dropped} frames dropped`);
+    }
+    if (v.encoder_stalls > 0) {
+      issues.push(`${v.filename}: encoder stalled ${v.encoder_stalls} times`);
+    }
+    if (v.timestamp_gaps > 0) {
+      issues.push(`${v.filename}: ${v.timestamp_gaps} gaps in the video timeline`);
+    }
+    if (v.likely_blank) {
+      issues.push(`${v.filename}: picture is almost completely black (lens cap or camera off?)`);
+    }
+  }
+  return issues;
+}
+
+export interface FrameRateReport {
+  filename: string;
+  device_name: string;
+  /** Output frame rate the file was conformed to */
+  fps: number;
+  frames_in: number;
+  frames_out: number;
+  duplicated: number;
+  dropped: number;
+}
+
+export type LatencyStream = "midi" | "audio" | "video";
+
+/** Time from the trigger to a writer's first data */
+export interface WriterLatency {
+  stream: LatencyStream;
+  device_name: string;
+  latency_ms: number;
+}
+
+/** Sample rates of one audio file (they differ when it was resampled) */
+export interface AudioRate {
+  filename: string;
+  device_name: string;
+  device_rate: number;
+  file_rate: number;
+}
+
+export interface DeviceRate {
+  device_name: string;
+  rate: number;
+}
+
+/** Pre-record warning about audio device sample rates */
+export type SampleRateWarning =
+  | { kind: "resampled"; device_name: string; device_rate: number; target_rate: number }
+  | { kind: "mixed_rates"; devices: DeviceRate[] };
+
+/** Startup latency of one stream on one device across recent sessions */
+export interface StartupLatencyStats {
+  stream: LatencyStream;
+  device_name: string;
+  /** Sessions with a measurement */
+  sessions: number;
+  median_ms: number;
+  p95_ms: number;
+  max_ms: number;
+  /** The most recent session's measurement */
+  latest_ms: number;
+}
+
+export type AdaptationStep =
+  | { kind: "preset"; from_preset: number; to_preset: number; from_effort: number; to_effort: number }
+  | { kind: "frame_rate"; from_fps: number; to_fps: number };
+
+/** An automatic reduction in video encoding load during a recording */
+export interface EncodingAdaptation {
+  device_name: string;
+  /** Seconds into the recording */
+  at_secs: number;
+  step: AdaptationStep;
+  reason: string;
+  cpu_percent: number | null;
+  temperature_c: number | null;
+}
+
+/** Short description of an adaptation step, e.g. "preset 4 → 3" */
+export function describeAdaptationStep(step: AdaptationStep): string {
+  return step.kind === "preset"
+    ? `preset ${step.from_preset} → ${step.to_preset}`
+    : `${step.from_fps.toFixed(0)} → ${step.to_fps.toFixed(0)} fps`;
+}
+
+export type VideoFallbackStep =
+  | "lower_fps"
+  | "lower_resolution"
+  | "alternate_format"
+  | "alternate_provider";
+
+export interface VideoFallback {
+  device_id: string;
+  device_name: string;
+  step: VideoFallbackStep;
+  /** Configured mode, e.g. "MJPEG 1920x1080 @ 60fps" */
+  requested: string;
+  /** Mode that actually started */
+  used: string;
+}
+
+export interface LoopRegion {
+  name: string;
+  start_secs: number;
+  end_secs: number;
+}
+
+/** A chapter of a session (one piece, or one section in its own key) */
+export interface Chapter {
+  title: string;
+  start_secs: number;
+  end_secs: number;
+}
+
+/** Payload of the "session-chapters" event */
+export interface ChaptersEvent {
+  session_id: string;
+  chapters: Chapter[];
+}
+
+export type TimelineWarningKind =
+  | "duration_mismatch"
+  | "gap"
+  | "overlap"
+  | "late_start"
+  | "audio_padded";
+
+export interface TimelineWarning {
+  kind: TimelineWarningKind;
+  filename: string;
+  /** Position in the file the problem starts at, if local */
+  at_secs: number | null;
+  message: string;
+}
+
+/** Alignment computed when a recording started */
+export interface SessionSync {
+  /** Pre-roll every stream was aligned to (seconds before the trigger) */
+  preroll_secs: number;
+  /** Added to live MIDI timestamps (microseconds) */
+  midi_timestamp_offset_us: number;
+  /** Latency correction per device (ms, positive = late) */
+  device_offsets_ms: Record<string, number>;
 }
 
 export interface AudioFileInfo {
   filename: string;
   device_name: string;
   duration_secs: number;
+  /** Seconds into the session where the file begins */
+  start_offset_secs: number;
 }
 
 export interface MidiFileInfo {
@@ -353,12 +707,60 @@ export interface MidiFileInfo {
   device_name: string;
   event_count: number;
   needs_repair: boolean;
+  /** Bank/program per channel when recording started */
+  programs: ChannelProgram[];
+  /** Seconds into the session where tick 0 falls */
+  start_offset_secs: number;
+}
+
+export interface ChannelProgram {
+  /** MIDI channel, 0-15 */
+  channel: number;
+  bank_msb: number | null;
+  bank_lsb: number | null;
+  program: number;
 }
 
 export interface VideoFileInfo {
   filename: string;
   device_name: string;
   duration_secs: number;
+  /** Seconds into the session where this file begins (video pre-roll shorter than audio/MIDI) */
+  start_offset_secs: number;
+  /** Low-resolution scrubbing proxy next to this file, if generated */
+  proxy_filename?: string | null;
+  /** Bit depth and colorimetry the file was encoded with */
+  color?: VideoColorInfo | null;
+  /** Codec, container and encoder the file was written with */
+  stream?: VideoStreamInfo | null;
+}
+
+/** How a video file was written */
+export interface VideoStreamInfo {
+  /** e.g. "AV1", "H.264", "MJPEG" */
+  codec: string;
+  /** e.g. "MP4", "MKV" */
+  container: string;
+  width: number;
+  height: number;
+  fps: number;
+  bit_depth: number;
+  /** Encoder backend, null for passthrough */
+  encoder: string | null;
+  /** Quality preset level (1-5) */
+  preset: number | null;
+}
+
+export interface VideoColorInfo {
+  bit_depth: number;
+  /** GStreamer colorimetry string, e.g. "bt709" or "bt2100-pq" */
+  colorimetry: string | null;
+  hdr: boolean;
+}
+
+/** Short label like "10-bit HDR" or "8-bit" */
+export function colorLabel(color: VideoColorInfo): string {
+  return `${color.bit_depth}-bit${color.hdr ? " HDR" : ""}`;
 }
 
 export type AudioBitDepth = "int16" | "int24" | "float32";
@@ -370,20 +772,47 @@ export type AudioSampleRate =
   | "rate96000"
   | "rate192000";
 
+/** File name templates. Placeholders: {device}, {codec}, {channels}, {take} */
+export interface FilenameTemplates {
+  audio: string;
+  midi: string;
+  video: string;
+}
+
 export interface Config {
   storage_path: string;
   idle_timeout_secs: number;
-  pre_roll_secs: number;
+  /** Audio and MIDI pre-roll (0-30 seconds) */
+  audio_midi_pre_roll_secs: number;
+  /** Video pre-roll (0-5 seconds, or 0-30 when encoding during pre-roll) */
+  video_pre_roll_secs: number;
   audio_format: "wav" | "flac";
   wav_bit_depth: AudioBitDepth;
   wav_sample_rate: AudioSampleRate;
   flac_bit_depth: AudioBitDepth;
   flac_sample_rate: AudioSampleRate;
+  /** Record each interface at its native bit depth, capped by the setting above */
+  native_bit_depth: boolean;
+  /** With passthrough rates, write all files at the highest device rate when rates differ */
+  resample_to_common_rate: boolean;
   dark_mode: boolean;
+  /** Run capture headless; the window is created on demand (next launch) */
+  agent_mode: boolean;
+  /** Browse and play back only: no monitoring, no writes to sessions (next launch) */
+  read_only_library: boolean;
+  /** GStreamer install to load (next launch) */
+  gstreamer_source: "auto" | "bundled" | "system";
   auto_start: boolean;
   start_minimized: boolean;
+  /** Language of notifications, the tray menu and system dialogs */
+  language: Language;
   notify_recording_start: boolean;
   notify_recording_stop: boolean;
+  quick_review: QuickReviewMode;
+  /** Release channel checked for updates */
+  update_channel: UpdateChannel;
+  /** Feed checked instead of the channel's (e.g. a studio mirror) */
+  update_feed_url: string | null;
   sound_recording_start: boolean;
   sound_recording_stop: boolean;
   sound_volume_start: number;
@@ -398,19 +827,250 @@ export interface Config {
   trigger_midi_devices: string[];
   trigger_audio_devices: string[];
   audio_trigger_thresholds: Record<string, number>;
+  /** Ignore audio triggers while something plays through the speakers */
+  suppress_triggers_during_playback: boolean;
+  /** Loopback device carrying the speaker feed (null = Sacho's own playback only) */
+  playback_monitor_device: string | null;
+  /** RMS level above which the playback monitor counts as playing */
+  playback_monitor_threshold: number;
   selected_video_devices: string[];
   /** Per-device video configuration (device_id -> config) */
   video_device_configs: Record<string, VideoDeviceConfig>;
   /** Whether to encode video during pre-roll (trades compute for memory, allows up to 30s pre-roll) */
   encode_during_preroll: boolean;
+  /** Build camera file writers while armed to shorten trigger-to-first-frame latency */
+  prewarm_video_writer: boolean;
   /** Whether to combine audio and video into a single container file */
   combine_audio_video: boolean;
   /** Preferred video container format. AV1/VP9/H.264 remux to this; FFV1 stays MKV, VP8 stays WebM. */
   preferred_video_container: ContainerFormat;
+  /** MIDI feedback sent to trigger devices on recording start/stop */
+  midi_feedback: MidiFeedbackConfig;
+  /** Forward one MIDI input to an output port while monitoring */
+  midi_thru: MidiThruConfig;
+  /** How each MIDI input is opened (device ID -> mode); unlisted devices are exclusive */
+  midi_access_modes: Record<string, MidiAccessMode>;
+  midi_file: MidiFileConfig;
+  /** Live RTMP stream pushed while recording */
+  rtmp_stream: RtmpStreamConfig;
+  /** Republish a camera as a virtual camera for other apps */
+  virtual_camera: VirtualCameraConfig;
+  /** Watch the storage folder and index externally added/removed sessions */
+  watch_storage: boolean;
+  /** Additional storage roots (e.g. NAS archive); storage_path is the primary */
+  additional_storage_paths: string[];
+  /** Root shared by all users of this machine; each records into a subfolder */
+  shared_storage_path: string | null;
+  /** Index sessions recorded by other users in a shared root */
+  show_other_users_sessions: boolean;
+  storage_routing: StorageRouting;
+  /** Free space (GB) required under "first_with_free_space" routing */
+  storage_min_free_gb: number;
+  /** Cold-storage folder for archived sessions' original video */
+  archive_path: string | null;
+  /** Let paired phones see recording state and start/stop recording */
+  mobile_remote_enabled: boolean;
+  /** Mobile remote port (0 = any free port, found via mDNS) */
+  mobile_remote_port: number;
+  /** Allow sessions to be shared on the local network */
+  lan_share_enabled: boolean;
+  /** Share server port (0 = any free port) */
+  lan_share_port: number;
+  /** Hours until a share link stops working */
+  share_link_expiry_hours: number;
+  /** Generate scrubbing proxies for FFV1/raw/AV1 recordings */
+  generate_video_proxies: boolean;
+  /** Generate timeline thumbnail filmstrips after recording */
+  generate_video_filmstrips: boolean;
+  /** Detect chapters in recorded MIDI and mark them in the session */
+  detect_chapters: boolean;
+  /** Recorded file names, without extension */
+  filename_templates: FilenameTemplates;
+  /** MJPEG frames taller than this are downscaled for review (0 = full size) */
+  video_preview_max_height: number;
+  /** Decode JPEG frames on the GPU when downscaling */
+  hardware_jpeg_decode: boolean;
+  /** Rebuild device pipelines when the computer wakes from sleep */
+  recover_after_sleep: boolean;
+  /** Seconds to wait after waking before rebuilding pipelines */
+  resume_grace_secs: number;
+  /** Sync to disk more often, lower video quality and split video when the battery runs low */
+  battery_safe_mode: boolean;
+  /** Battery charge (percent) at which record-safe mode kicks in */
+  battery_low_percent: number;
+  /** Video segment length in record-safe mode (minutes) */
+  battery_segment_minutes: number;
+  /** Shut down cameras during a privacy pause started from the tray */
+  privacy_pause_stops_cameras: boolean;
+  /** Longest wait on quit for recordings to finish writing (seconds) */
+  shutdown_timeout_secs: number;
+  /** Per-device latency corrections in ms (positive = device is late) */
+  device_sync_offsets_ms: Record<string, number>;
+  /** Per-device stems (device name -> files cut from chosen input channels) */
+  audio_channel_splits: Record<string, AudioChannelStem[]>;
+  /** Per-device M/S decode, stereo width and polarity (device name -> settings) */
+  audio_processing: Record<string, AudioProcessing>;
+  /** Per-device record schedules (video device ID or audio device name -> schedule) */
+  device_schedules: Record<string, DeviceSchedule>;
+  /** Tags applied to sessions when they're finalized */
+  auto_tag_rules: AutoTagRule[];
   device_presets: DevicePreset[];
   current_preset: string | null;
 }
 
+/** A device with a schedule is only opened while one of its windows is open */
+export interface DeviceSchedule {
+  windows: ScheduleWindow[];
+}
+
+export interface ScheduleWindow {
+  /** 0 = Monday .. 6 = Sunday */
+  days: number[];
+  /** Minutes after midnight, local time */
+  start_minute: number;
+  /** Earlier than start_minute for a window running past midnight */
+  end_minute: number;
+}
+
+/** Applies its tag when every condition that's set holds */
+export interface AutoTagRule {
+  tag: string;
+  enabled: boolean;
+  min_duration_secs: number | null;
+  /** Exclusive */
+  max_duration_secs: number | null;
+  /** Session started inside this window (local time) */
+  started_within: ScheduleWindow | null;
+  has_audio: boolean | null;
+  has_midi: boolean | null;
+  has_video: boolean | null;
+}
+
+export interface AudioChannelStem {
+  /** Appended to the filename */
+  label: string;
+  /** Zero-based input channels (one for mono, two for stereo) */
+  channels: number[];
+}
+
+export interface AudioProcessing {
+  /** Decode channels 1/2 from mid/side to left/right */
+  ms_decode: boolean;
+  /** 0 = mono, 1 = unchanged, 2 = widest */
+  stereo_width: number;
+  /** Zero-based channels whose polarity is flipped */
+  invert_polarity: number[];
+}
+
+export type MidiFeedbackMessage =
+  | { type: "note"; channel: number; note: number; velocity: number }
+  | { type: "control_change"; channel: number; controller: number; value: number }
+  | { type: "sysex"; data: number[] };
+
+export interface MidiFeedbackConfig {
+  enabled: boolean;
+  start_message: MidiFeedbackMessage | null;
+  stop_message: MidiFeedbackMessage | null;
+}
+
+/** After a recording stops: nothing, open it in the window, or a notification with Play/Favorite/Delete */
+export type Language = "system" | "en" | "de" | "fr" | "es";
+
+export type QuickReviewMode = "off" | "open" | "notify";
+
+export type UpdateChannel = "off" | "stable" | "beta";
+
+/** A newer version on the update feed (also the `update-available` event payload) */
+export interface AvailableUpdate {
+  version: string;
+  current_version: string;
+  /** Release notes */
+  notes: string | null;
+  date: string | null;
+  channel: UpdateChannel;
+}
+
+/** Check the update feed now; resolves to null when up to date */
+export async function checkForUpdates(): Promise<AvailableUpdate | null> {
+  return invoke("check_for_updates");
+}
+
+/** Update found by the last check */
+export async function getAvailableUpdate(): Promise<AvailableUpdate | null> {
+  return invoke("get_available_update");
+}
+
+/** Download, verify and install the available update; the app restarts */
+export async function installUpdate(): Promise<void> {
+  return invoke("install_update");
+}
+
+/** Payload of the `quick-review` event */
+export interface QuickReviewRequest {
+  session_id: string;
+  /** Start playback once the session is loaded */
+  play: boolean;
+}
+
+/**
+ * exclusive: held while monitoring; shared: opened only while recording
+ * (no trigger, no pre-roll); virtual_tap: held and republished on a virtual port
+ */
+export type MidiAccessMode = "exclusive" | "shared" | "virtual_tap";
+
+export interface MidiThruConfig {
+  enabled: boolean;
+  /** MIDI device ID to forward from */
+  input_device: string | null;
+  /** Output port name to forward to */
+  output_port: string | null;
+  /** 0-based channels to forward; empty forwards all */
+  channels: number[];
+}
+
+export interface MidiFileConfig {
+  /** Ticks per quarter note of recorded MIDI files (96-960) */
+  ticks_per_quarter: number;
+  /** Detect the tempo after recording and write it as a tempo map */
+  tempo_map: boolean;
+}
+
+export interface RtmpStreamConfig {
+  enabled: boolean;
+  /** rtmp:// or rtmps:// URL including the stream key */
+  url: string;
+  /** Video device name to stream (null = first recording video device) */
+  video_device: string | null;
+  /** Audio device name to stream (null = no audio) */
+  audio_device: string | null;
+  video_bitrate_kbps: number;
+  audio_bitrate_kbps: number;
+}
+
+export interface VirtualCameraConfig {
+  enabled: boolean;
+  /** Video device name to publish (null = first selected video device) */
+  video_device: string | null;
+  /** Loopback device path (v4l2loopback on Linux) */
+  output_device: string;
+  /** Draw live capture fps, encode fps and bitrate onto the frames */
+  stats_overlay: boolean;
+}
+
+export interface VirtualCameraSupport {
+  supported: boolean;
+  reason: string | null;
+}
+
+export type StreamState = "connecting" | "live" | "reconnecting" | "stopped";
+
+/** Payload of the "stream-status" event */
+export interface StreamStatus {
+  state: StreamState;
+  message: string | null;
+  reconnects: number;
+}
+
 export interface DevicePreset {
   name: string;
   audio_devices: string[];
@@ -433,10 +1093,21 @@ export interface SessionFilter {
   has_video?: boolean;
   has_notes?: boolean;
   has_title?: boolean;
+  archived?: boolean;
+  favorite?: boolean;
+  /** Custom field values a session must have (case-insensitive) */
+  fields?: CustomFields;
+  /** Defaults to date */
+  sort?: SessionSort;
+  /** Oldest, shortest, smallest first instead of the reverse */
+  ascending?: boolean;
   limit?: number;
   offset?: number;
 }
 
+/** "rating" puts favorites first */
+export type SessionSort = "date" | "duration" | "rating" | "size";
+
 export interface MidiImportInfo {
   id: string;
   file_name: string;
@@ -487,14 +1158,41 @@ export async function refreshAllDevices(): Promise<void> {
   return invoke("refresh_devices");
 }
 
+export async function getDeviceAliases(): Promise<DeviceAlias[]> {
+  return invoke("get_device_aliases");
+}
+
+export async function saveDeviceAlias(alias: DeviceAlias): Promise<DeviceAlias> {
+  return invoke("save_device_alias", { alias });
+}
+
+export async function deleteDeviceAlias(id: number): Promise<void> {
+  return invoke("delete_device_alias", { id });
+}
+
 export async function getAudioDevices(): Promise<AudioDevice[]> {
   return invoke("get_audio_devices");
 }
 
+/** Rate mismatches among the audio devices selected for recording */
+export async function getSampleRateWarnings(): Promise<SampleRateWarning[]> {
+  return invoke("get_sample_rate_warnings");
+}
+
 export async function getMidiDevices(): Promise<MidiDevice[]> {
   return invoke("get_midi_devices");
 }
 
+/** MIDI access modes this platform supports */
+export async function getSupportedMidiAccessModes(): Promise<MidiAccessMode[]> {
+  return invoke("get_supported_midi_access_modes");
+}
+
+/** MIDI output ports available as a thru destination */
+export async function getMidiOutputPorts(): Promise<string[]> {
+  return invoke("get_midi_output_ports");
+}
+
 export async function getVideoDevices(): Promise<VideoDevice[]> {
   return invoke("get_video_devices");
 }
@@ -516,6 +1214,42 @@ export async function validateVideoDeviceConfig(
   });
 }
 
+export interface PreflightWarning {
+  code:
+    | "pipeline_failed"
+    | "no_frames"
+    | "dimension_mismatch"
+    | "fps_low"
+    | "encoder_dropping"
+    | "encoder_slow";
+  message: string;
+}
+
+export interface VideoPreflightReport {
+  device_id: string;
+  ok: boolean;
+  requested_width: number;
+  requested_height: number;
+  requested_fps: number;
+  negotiated_width: number | null;
+  negotiated_height: number | null;
+  negotiated_fps: number | null;
+  measured_fps: number;
+  frames_captured: number;
+  frames_written: number;
+  frames_dropped: number;
+  warnings: PreflightWarning[];
+}
+
+/** Run a proposed config on the device for a few seconds, recording to a
+ *  throwaway file, and report any problems. Video capture pauses meanwhile. */
+export async function preflightVideoDevice(
+  deviceId: string,
+  config: VideoDeviceConfig,
+): Promise<VideoPreflightReport> {
+  return invoke("preflight_video_device", { deviceId, config });
+}
+
 // ============================================================================
 // Encoder Availability
 // ============================================================================
@@ -546,6 +1280,63 @@ export async function getEncoderAvailability(): Promise<EncoderAvailability> {
   return invoke("get_encoder_availability");
 }
 
+export type GstPackage = "core" | "base" | "good" | "bad" | "ugly" | "libav" | "vaapi";
+
+export interface GstElementStatus {
+  name: string;
+  package: GstPackage;
+  role: "source" | "sink" | "encoder" | "decoder" | "parser" | "muxer" | "demuxer" | "utility";
+  feature: string;
+  /** One of several interchangeable elements for its feature */
+  alternative: boolean;
+  present: boolean;
+  plugin: string | null;
+  plugin_file: string | null;
+  /** How to install the element, when missing */
+  hint: string | null;
+}
+
+export interface GstFeatureStatus {
+  feature: string;
+  available: boolean;
+  present: string[];
+  missing: string[];
+}
+
+/** Which GStreamer install is loaded and what its startup checks found */
+export interface GstRuntimeInfo {
+  requested: "auto" | "bundled" | "system";
+  requested_by: "environment" | "config" | "default";
+  active: "bundled" | "system";
+  version: string | null;
+  minimum_version: string;
+  version_ok: boolean;
+  plugin_path: string | null;
+  registry_path: string | null;
+  mismatched_plugins: string[];
+  foreign_plugins: string[];
+  warnings: string[];
+  init_error: string | null;
+}
+
+/** Which GStreamer elements Sacho can use on this machine */
+export interface GstAuditReport {
+  gstreamer_version: string;
+  platform: string;
+  runtime: GstRuntimeInfo | null;
+  elements: GstElementStatus[];
+  features: GstFeatureStatus[];
+  missing_count: number;
+}
+
+export async function auditGstreamer(): Promise<GstAuditReport> {
+  return invoke("audit_gstreamer");
+}
+
+export async function getVirtualCameraSupport(): Promise<VirtualCameraSupport> {
+  return invoke("get_virtual_camera_support");
+}
+
 /** Look up the CodecEncoderInfo for a given codec from the availability object. */
 export function getCodecInfo(availability: EncoderAvailability, codec: VideoCodec): CodecEncoderInfo | null {
   return availability[codec as keyof Pick<EncoderAvailability, "av1" | "vp9" | "vp8" | "h264" | "ffv1">] ?? null;
@@ -618,6 +1409,33 @@ export async function getRecordingState(): Promise<RecordingState> {
   return invoke("get_recording_state");
 }
 
+/** Live statistics for one camera pipeline */
+export interface VideoPipelineStats {
+  device_id: string;
+  device_name: string;
+  recording: boolean;
+  /** Encoder backend, null for passthrough */
+  encoder: string | null;
+  /** Rates since the previous call (null on the first) */
+  bitrate_kbps: number | null;
+  encode_fps: number | null;
+  capture_fps: number | null;
+  queue_frames: number;
+  queue_capacity: number;
+  frames_written: number;
+  frames_dropped: number;
+}
+
+/** Per-camera encoder statistics; rates cover the time since the last call */
+/** Startup latency per stream and device over the last `limit` sessions (default 50) */
+export async function getStartupLatencyStats(limit?: number): Promise<StartupLatencyStats[]> {
+  return invoke("get_startup_latency_stats", { limit: limit ?? null });
+}
+
+export async function getRecordingStats(): Promise<VideoPipelineStats[]> {
+  return invoke("get_recording_stats");
+}
+
 export async function startRecording(): Promise<string> {
   return invoke("start_recording");
 }
@@ -636,12 +1454,118 @@ export async function getSessions(
   return invoke("get_sessions", { filter });
 }
 
+/** Number of sessions matching a filter (its limit and offset are ignored) */
+export async function countSessions(
+  filter: SessionFilter = {},
+): Promise<number> {
+  return invoke("count_sessions", { filter });
+}
+
 export async function getSessionDetail(
   sessionId: string,
 ): Promise<SessionMetadata | null> {
   return invoke("get_session_detail", { sessionId });
 }
 
+/** One attempt at a passage practised repeatedly within a session */
+export interface PracticeSegment {
+  /** Passage index, in order of first appearance */
+  passage: number;
+  /** Attempt number within the passage, from 1 */
+  attempt: number;
+  start_secs: number;
+  end_secs: number;
+  note_count: number;
+  /** Whether the attempt got about as far as the longest one */
+  complete: boolean;
+}
+
+export async function getSessionSegments(
+  sessionId: string,
+): Promise<PracticeSegment[]> {
+  return invoke("get_session_segments", { sessionId });
+}
+
+export interface VelocityWindow {
+  start_secs: number;
+  note_count: number;
+  mean_velocity: number | null;
+  /** Note-ons per velocity bucket (8 velocity steps each) */
+  histogram: number[];
+}
+
+export interface LoudnessWindow {
+  start_secs: number;
+  rms_db: number;
+  peak_db: number;
+}
+
+export interface SessionDynamics {
+  /** Seconds covered by each window of the curves */
+  window_secs: number;
+  note_count: number;
+  /** Note-ons per velocity bucket (bucket i holds velocities 8i..8i+7) */
+  velocity_histogram: number[];
+  mean_velocity: number | null;
+  /** Standard deviation of velocity */
+  velocity_spread: number | null;
+  /** 5th and 95th percentile velocity */
+  velocity_range: [number, number] | null;
+  /** Velocity histogram per time window (heat map columns) */
+  velocity_windows: VelocityWindow[];
+  /** Audio file the loudness curve was measured from */
+  loudness_source: string | null;
+  loudness: LoudnessWindow[];
+  /** 95th minus 10th percentile RMS of the non-silent windows */
+  dynamic_range_db: number | null;
+}
+
+export async function getSessionDynamics(
+  sessionId: string,
+): Promise<SessionDynamics> {
+  return invoke("get_session_dynamics", { sessionId });
+}
+
+export interface MissedNote {
+  pitch: number;
+  /** Beat within the measure, from 1 */
+  beat: number;
+}
+
+export interface MeasureAccuracy {
+  /** Measure number, from 1 */
+  measure: number;
+  /** Where the measure falls in the recording */
+  start_secs: number;
+  reference_notes: number;
+  matched_notes: number;
+  accuracy: number;
+  mean_timing_deviation_ms: number | null;
+  missed: MissedNote[];
+}
+
+export interface AccuracyReport {
+  reference_notes: number;
+  matched_notes: number;
+  missed_notes: number;
+  extra_notes: number;
+  accuracy: number;
+  mean_timing_deviation_ms: number;
+  /** Performed duration per score duration (> 1 = slower than written) */
+  tempo_ratio: number;
+  performance_start_secs: number;
+  performance_end_secs: number;
+  measures: MeasureAccuracy[];
+}
+
+/** Compare the session's MIDI with a reference score MIDI file */
+export async function scoreAgainstReference(
+  sessionId: string,
+  referenceMidiPath: string,
+): Promise<AccuracyReport> {
+  return invoke("score_against_reference", { sessionId, referenceMidiPath });
+}
+
 export async function deleteSession(sessionId: string): Promise<void> {
   return invoke("delete_session", { sessionId });
 }
@@ -652,6 +1576,22 @@ export async function repairSession(
   return invoke("repair_session", { sessionId });
 }
 
+/** Reconstruct a session folder's metadata from its media files */
+export async function rebuildSessionMetadata(
+  path: string,
+): Promise<SessionMetadata> {
+  return invoke("rebuild_session_metadata", { path });
+}
+
+/** Swap a damaged file in a session for a copy from a backup */
+export async function replaceSessionFile(
+  sessionId: string,
+  filename: string,
+  sourcePath: string,
+): Promise<SessionMetadata> {
+  return invoke("replace_session_file", { sessionId, filename, sourcePath });
+}
+
 export async function readSessionFile(
   sessionPath: string,
   filename: string,
@@ -663,6 +1603,40 @@ export async function readSessionFile(
   return new Uint8Array(data);
 }
 
+/** Mark or unmark a session as a favorite */
+export async function setSessionFavorite(
+  sessionId: string,
+  favorite: boolean,
+): Promise<void> {
+  return invoke("set_session_favorite", { sessionId, favorite });
+}
+
+/** Replace a session's tags; resolves to the tags as saved */
+export async function setSessionTags(
+  sessionId: string,
+  tags: string[],
+): Promise<string[]> {
+  return invoke("set_session_tags", { sessionId, tags });
+}
+
+/** Replace a session's custom fields; resolves to the fields as saved */
+export async function setSessionFields(
+  sessionId: string,
+  fields: CustomFields,
+): Promise<CustomFields> {
+  return invoke("set_session_fields", { sessionId, fields });
+}
+
+/** Custom field names used across the library */
+export async function getCustomFieldKeys(): Promise<string[]> {
+  return invoke("get_custom_field_keys");
+}
+
+/** Values a custom field has across the library */
+export async function getCustomFieldValues(key: string): Promise<string[]> {
+  return invoke("get_custom_field_values", { key });
+}
+
 export async function updateSessionNotes(
   sessionId: string,
   notes: string,
@@ -670,6 +1644,14 @@ export async function updateSessionNotes(
   return invoke("update_session_notes", { sessionId, notes });
 }
 
+/** Save a session's loop regions (replaces the existing list) */
+export async function updateSessionLoops(
+  sessionId: string,
+  loops: LoopRegion[],
+): Promise<void> {
+  return invoke("update_session_loops", { sessionId, loops });
+}
+
 export async function renameSession(
   sessionId: string,
   newTitle: string,
@@ -677,6 +1659,135 @@ export async function renameSession(
   return invoke("rename_session", { sessionId, newTitle });
 }
 
+/**
+ * Archive a session: replace its video with low-res proxies and move the
+ * originals to the archive folder (or delete them). Returns bytes reclaimed.
+ */
+/** Shown while a phone is being paired */
+export interface PairingInfo {
+  code: string;
+  host: string;
+  port: number;
+  expires_in_secs: number;
+}
+
+export interface PairedDeviceInfo {
+  id: string;
+  name: string;
+  paired_at: string;
+  last_seen: string | null;
+  connected: boolean;
+}
+
+export async function startMobilePairing(): Promise<PairingInfo> {
+  return invoke("start_mobile_pairing");
+}
+
+export async function cancelMobilePairing(): Promise<void> {
+  await invoke("cancel_mobile_pairing");
+}
+
+export async function getPairedDevices(): Promise<PairedDeviceInfo[]> {
+  return invoke("get_paired_devices");
+}
+
+export async function unpairDevice(deviceId: string): Promise<void> {
+  await invoke("unpair_device", { deviceId });
+}
+
+/** A tokenized LAN link to a read-only session page */
+export interface ShareLinkInfo {
+  token: string;
+  url: string;
+  session_id: string;
+  /** RFC 3339 */
+  expires_at: string;
+}
+
+/** Write report.html into the session folder; returns its path */
+export async function exportSessionReport(sessionId: string): Promise<string> {
+  return invoke("export_session_report", { sessionId });
+}
+
+export async function createShareLink(sessionId: string): Promise<ShareLinkInfo> {
+  return invoke("create_share_link", { sessionId });
+}
+
+export async function revokeShareLink(token: string): Promise<void> {
+  await invoke("revoke_share_link", { token });
+}
+
+export async function listShareLinks(): Promise<ShareLinkInfo[]> {
+  return invoke("list_share_links");
+}
+
+export async function archiveSession(
+  sessionId: string,
+  deleteOriginals: boolean,
+): Promise<number> {
+  return invoke("archive_session", { sessionId, deleteOriginals });
+}
+
+/** Restore an archived session's original video */
+export async function restoreArchivedSession(sessionId: string): Promise<void> {
+  return invoke("restore_archived_session", { sessionId });
+}
+
+export type TranscodeTarget = "av1" | "h264";
+
+export interface TranscodeJob {
+  session_path: string;
+  filename: string;
+  size_bytes: number;
+}
+
+/** State of the background MJPEG re-encode queue */
+export interface TranscodeStatus {
+  target: TranscodeTarget;
+  delete_originals: boolean;
+  paused: boolean;
+  /** A recording is in progress, so work is on hold */
+  waiting_for_idle: boolean;
+  current: TranscodeJob | null;
+  /** Progress through the current file (0–1) */
+  current_progress: number;
+  pending: number;
+  pending_bytes: number;
+  completed: number;
+  failed: number;
+  bytes_before: number;
+  bytes_after: number;
+  last_error: string | null;
+}
+
+/** Queue every MJPEG recording for idle-time re-encoding */
+export async function queueMjpegTranscodes(
+  target: TranscodeTarget,
+  deleteOriginals: boolean,
+): Promise<TranscodeStatus> {
+  return invoke("queue_mjpeg_transcodes", { target, deleteOriginals });
+}
+
+export async function getTranscodeStatus(): Promise<TranscodeStatus> {
+  return invoke("get_transcode_status");
+}
+
+export async function setTranscodePaused(paused: boolean): Promise<TranscodeStatus> {
+  return invoke("set_transcode_paused", { paused });
+}
+
+export async function clearTranscodeQueue(): Promise<TranscodeStatus> {
+  return invoke("clear_transcode_queue");
+}
+
+/** Move a session folder to another configured storage root */
+export async function moveSession(
+  sessionId: string,
+  targetRoot: string,
+): Promise<void> {
+  return invoke("move_session", { sessionId, targetRoot });
+}
+
 // ============================================================================
 // Config Commands
 // ============================================================================
@@ -684,6 +1795,14 @@ export async function renameSession(
 // Track previous auto_start value to avoid unnecessary system calls
 let previousAutoStart: boolean | null = null;
 
+/** Example file name for a template, or an error describing why it is invalid */
+export async function previewFilenameTemplate(
+  kind: "audio" | "midi" | "video",
+  template: string,
+): Promise<string> {
+  return invoke("preview_filename_template", { kind, template });
+}
+
 export async function getConfig(): Promise<Config> {
   const config = await invoke<Config>("get_config");
   // Initialize autostart tracking
@@ -730,6 +1849,255 @@ export async function updateAudioTriggerThresholds(
   await invoke("update_audio_trigger_thresholds", { thresholds });
 }
 
+export interface NoiseFloorReport {
+  device_name: string;
+  duration_secs: number;
+  windows: number;
+  min_rms: number;
+  median_rms: number;
+  p95_rms: number;
+  p99_rms: number;
+  max_rms: number;
+  /** Suggested trigger threshold (linear RMS) */
+  suggested_threshold: number;
+  applied: boolean;
+}
+
+/** Listen to the room through an audio input and suggest a trigger threshold */
+export async function calibrateAudioTrigger(
+  device: string,
+  durationSecs: number | null,
+  apply: boolean,
+): Promise<NoiseFloorReport> {
+  return invoke("calibrate_audio_trigger", { device, durationSecs, apply });
+}
+
+export type TriggerOutcome =
+  | "started"
+  | "already_recording"
+  | "initializing"
+  | "no_devices_selected"
+  | "suppressed_playback"
+  | "privacy_paused";
+
+/** One entry in the trigger history (repeats within a second are folded together) */
+export interface TriggerEvaluation {
+  at: string;
+  last_at: string;
+  source: "midi" | "audio";
+  device: string;
+  /** Highest note-on velocity (0-127) or window RMS (0.0-1.0) */
+  value: number;
+  /** RMS threshold for audio triggers */
+  threshold: number | null;
+  outcome: TriggerOutcome;
+  count: number;
+}
+
+/** Recent trigger evaluations, newest first */
+export async function getTriggerHistory(limit?: number): Promise<TriggerEvaluation[]> {
+  return invoke("get_trigger_history", { limit: limit ?? null });
+}
+
+export async function clearTriggerHistory(): Promise<void> {
+  return invoke("clear_trigger_history");
+}
+
+/** Tell the backend the session player started or stopped (for trigger suppression) */
+export async function setPlaybackActive(active: boolean): Promise<void> {
+  return invoke("set_playback_active", { active });
+}
+
+/** How long a privacy pause lasts */
+export type PauseLength = { minutes: number } | "until_tomorrow" | "indefinite";
+
+export interface PrivacyPause {
+  started_at: string;
+  /** When triggers re-arm on their own (null = until resumed) */
+  until: string | null;
+  cameras_released: boolean;
+}
+
+export interface PausedInterval {
+  started_at: string;
+  ended_at: string;
+  cameras_released: boolean;
+  auto_resumed: boolean;
+}
+
+export interface PrivacyStatus {
+  paused: boolean;
+  pause: PrivacyPause | null;
+  /** Past pauses, most recent first */
+  history: PausedInterval[];
+}
+
+/** Disarm all triggers (and optionally cameras) for a while */
+export async function startPrivacyPause(
+  length: PauseLength,
+  releaseCameras?: boolean,
+): Promise<PrivacyStatus> {
+  return invoke("start_privacy_pause", { length, releaseCameras: releaseCameras ?? null });
+}
+
+export async function resumeFromPrivacyPause(): Promise<PrivacyStatus> {
+  return invoke("resume_from_privacy_pause");
+}
+
+export async function getPrivacyStatus(): Promise<PrivacyStatus> {
+  return invoke("get_privacy_status");
+}
+
+export interface SyncMeasurement {
+  device_name: string;
+  kind: "audio" | "midi" | "video";
+  filename: string;
+  /** Event time within the file, null if nothing was detected */
+  event_secs: number | null;
+  /** Residual latency vs. the reference (positive = late) */
+  offset_ms: number | null;
+}
+
+export interface CalibrationReport {
+  reference: string | null;
+  measurements: SyncMeasurement[];
+  applied: boolean;
+}
+
+/** Measure device latencies from a clap/flash calibration recording */
+export async function calibrateSyncOffsets(
+  sessionId: string,
+  apply: boolean,
+): Promise<CalibrationReport> {
+  return invoke("calibrate_sync_offsets", { sessionId, apply });
+}
+
+/** What emits the test event of a latency measurement */
+export type LatencySource =
+  | { kind: "audio_output"; device: string | null }
+  | { kind: "midi_output"; port: string };
+
+export interface LatencyReport {
+  input_device: string;
+  source: LatencySource;
+  /** Per-trial latency in ms (null where nothing was detected) */
+  trials: (number | null)[];
+  latency_ms: number | null;
+  /** Click output to input round trip (audio loopback only) */
+  round_trip_ms: number | null;
+  jitter_ms: number | null;
+  suggested_offset_ms: number | null;
+  applied: boolean;
+}
+
+/** Measure an audio input's latency with a loopback click or MIDI note */
+export async function measureDeviceLatency(
+  inputDevice: string,
+  source: LatencySource,
+  apply: boolean,
+): Promise<LatencyReport> {
+  return invoke("measure_device_latency", { inputDevice, source, apply });
+}
+
+/** Set one device's sync correction in ms (0 removes it) */
+export async function setDeviceSyncOffset(
+  deviceName: string,
+  offsetMs: number,
+): Promise<void> {
+  await invoke("set_device_sync_offset", { deviceName, offsetMs });
+}
+
+// ============================================================================
+// MIDI Inspector
+// ============================================================================
+
+export type MidiEventKind =
+  | "note_on"
+  | "note_off"
+  | "poly_aftertouch"
+  | "control_change"
+  | "program_change"
+  | "channel_aftertouch"
+  | "pitch_bend"
+  | "sys_ex"
+  | "realtime"
+  | "system";
+
+/** Which events the inspector shows; empty lists mean "all" */
+export interface MidiTapFilter {
+  devices?: string[];
+  channels?: number[];
+  kinds?: MidiEventKind[];
+  include_realtime?: boolean;
+}
+
+export interface MidiTapEvent {
+  device: string;
+  timestamp_us: number;
+  kind: MidiEventKind;
+  channel: number | null;
+  number: number | null;
+  value: number | null;
+  description: string;
+  raw: number[];
+}
+
+/** Payload of the `midi-tap-events` event */
+export interface MidiTapBatch {
+  events: MidiTapEvent[];
+  dropped: number;
+}
+
+export async function startMidiEventTap(filter?: MidiTapFilter): Promise<void> {
+  await invoke("start_midi_event_tap", { filter: filter ?? null });
+}
+
+export async function stopMidiEventTap(): Promise<void> {
+  await invoke("stop_midi_event_tap");
+}
+
+export type ShutdownStage =
+  | "waiting_for_start"
+  | "finalizing"
+  | "timed_out"
+  | "done";
+
+/** Payload of the `shutdown-progress` event */
+export interface ShutdownProgress {
+  stage: ShutdownStage;
+  elapsed_secs: number;
+  timeout_secs: number;
+}
+
+// ============================================================================
+// Crash Reports
+// ============================================================================
+
+export type CrashKind = "panic" | "native_exception" | "unexpected_exit";
+
+/** A crash from an earlier run, kept until the user dismisses it */
+export interface CrashReport {
+  id: string;
+  kind: CrashKind;
+  timestamp: string;
+  app_version: string;
+  os: string;
+  message: string;
+  backtrace: string | null;
+  has_minidump: boolean;
+  run_id: string;
+  /** Folder holding report.json, log.txt and (for native crashes) minidump.dmp */
+  path: string;
+}
+
+export async function getPendingCrashReports(): Promise<CrashReport[]> {
+  return invoke("get_pending_crash_reports");
+}
+
+export async function dismissCrashReport(id: string): Promise<void> {
+  await invoke("dismiss_crash_report", { id });
+}
+
 // ============================================================================
 // Device Health Commands
 // ============================================================================
@@ -746,6 +2114,30 @@ export async function getDisconnectedDevices(): Promise<
   return invoke("get_disconnected_devices");
 }
 
+/** A device that failed to open because another application holds it */
+export interface DeviceContentionInfo {
+  id: string;
+  name: string;
+  device_type: string;
+  likely_holder: string | null;
+  detail: string;
+}
+
+export async function getDevicesInUse(): Promise<DeviceContentionInfo[]> {
+  return invoke("get_devices_in_use");
+}
+
+/** Power source and charge, also emitted as "battery-state" when it changes */
+export interface BatteryStatus {
+  on_battery: boolean;
+  /** Remaining charge, null when unknown or there is no battery */
+  percent: number | null;
+}
+
+export async function getBatteryStatus(): Promise<BatteryStatus> {
+  return invoke("get_battery_status");
+}
+
 export async function restartDevicePipelines(
   deviceTypes: string[],
 ): Promise<void> {
@@ -792,6 +2184,11 @@ export async function importMidiFolder(path: string): Promise<MidiImportInfo[]>
   return invoke("import_midi_folder", { path });
 }
 
+/** Cancel a running MIDI import or session feature sync */
+export async function cancelFeatureExtraction(): Promise<void> {
+  return invoke("cancel_feature_extraction");
+}
+
 export async function getMidiImports(): Promise<MidiImportInfo[]> {
   return invoke("get_midi_imports");
 }
@@ -816,13 +2213,70 @@ export async function getSessionSimilarPreview(sessionId: string): Promise<Sessi
   return invoke("get_session_similar_preview", { sessionId });
 }
 
+export interface MapPoint {
+  id: string;
+  x: number;
+  y: number;
+}
+
+export interface SimilarityMap {
+  version: number;
+  /** False when the passed version is already current (points is empty) */
+  changed: boolean;
+  points: MapPoint[];
+}
+
+/** Fetch the similarity map layout. Listen for "similarity-map-changed" to know when to refetch. */
+export async function getSimilarityMap(version?: number): Promise<SimilarityMap> {
+  return invoke("get_similarity_map", { version: version ?? null });
+}
+
+/**
+ * Write the map's feature vectors, coordinates and k-means clusters to a CSV
+ * file. Resolves to the number of sessions written.
+ */
+export async function exportSimilarityData(path: string, clusters?: number): Promise<number> {
+  return invoke("export_similarity_data", { path, clusters: clusters ?? null });
+}
+
 export interface RescanProgress {
   current: number;
   total: number;
 }
 
-export async function rescanSessions(): Promise<number> {
-  return invoke("rescan_sessions");
+export interface RescanNewSession {
+  id: string;
+  has_audio: boolean;
+  has_midi: boolean;
+  has_video: boolean;
+}
+
+/** Indexed session found under a different folder name */
+export interface RescanMovedSession {
+  from: string;
+  to: string;
+}
+
+export interface RescanChangedSession {
+  id: string;
+  changes: string[];
+}
+
+export interface RescanReport {
+  dry_run: boolean;
+  new_sessions: RescanNewSession[];
+  missing_sessions: string[];
+  /** Folders renamed or moved on disk, kept as the same session */
+  moved_sessions: RescanMovedSession[];
+  /** Indexed sessions on a drive that couldn't be read; kept in the index */
+  offline_sessions: string[];
+  changed_sessions: RescanChangedSession[];
+  needs_repair: string[];
+  skipped_folders: string[];
+}
+
+export async function rescanSessions(dryRun?: boolean): Promise<RescanReport> {
+  return invoke("rescan_sessions", { dryRun: dryRun ?? false });
 }
 
 export async function resetCache(): Promise<number> {
@@ -884,6 +2338,26 @@ export async function getVideoFrame(
   return invoke("get_video_frame", { sessionPath, filename, timestampMs });
 }
 
+/** Layout of a video's thumbnail sprite sheet */
+export interface VideoFilmstrip {
+  /** Sprite sheet file name, in the session folder */
+  sprite_filename: string;
+  /** Thumbnail i shows the frame at i * interval_secs */
+  interval_secs: number;
+  tile_width: number;
+  tile_height: number;
+  columns: number;
+  tile_count: number;
+}
+
+/** Thumbnail filmstrip for timeline scrubbing (generated on first request) */
+export async function getVideoFilmstrip(
+  sessionPath: string,
+  filename: string,
+): Promise<VideoFilmstrip> {
+  return invoke("get_video_filmstrip", { sessionPath, filename });
+}
+
 export async function getVideoFramesBatch(
   sessionPath: string,
   filename: string,
@@ -907,6 +2381,131 @@ export async function getVideoFrameTimestamps(
   return invoke("get_video_frame_timestamps", { sessionPath, filename });
 }
 
+/** Message pushed over a video stream's channel */
+export type VideoStreamMessage =
+  | ({ kind: "frame" } & VideoFrameData)
+  | { kind: "ended" }
+  | { kind: "error"; message: string };
+
+export interface VideoStreamInfo {
+  stream_id: number;
+  info: VideoPlaybackInfo;
+}
+
+/**
+ * Open a video for streamed playback. Frames arrive through `onMessage`
+ * after `videoStreamPlay` or `videoStreamSeek`; the decoder stays warm
+ * until `closeVideoStream`.
+ */
+export async function openVideoStream(
+  sessionPath: string,
+  filename: string,
+  onMessage: (message: VideoStreamMessage) => void,
+): Promise<VideoStreamInfo> {
+  const channel = new Channel<VideoStreamMessage>();
+  channel.onmessage = onMessage;
+  return invoke("open_video_stream", { sessionPath, filename, channel });
+}
+
+export async function videoStreamPlay(
+  streamId: number,
+  fromMs: number,
+  rate?: number,
+): Promise<void> {
+  return invoke("video_stream_play", { streamId, fromMs: Math.floor(fromMs), rate });
+}
+
+export async function videoStreamPause(streamId: number): Promise<void> {
+  return invoke("video_stream_pause", { streamId });
+}
+
+export async function videoStreamSeek(streamId: number, timestampMs: number): Promise<void> {
+  return invoke("video_stream_seek", { streamId, timestampMs: Math.floor(timestampMs) });
+}
+
+/** Cycle streamed playback between two timestamps; pass null to clear */
+export async function videoStreamSetLoop(
+  streamId: number,
+  region: { startMs: number; endMs: number } | null,
+): Promise<void> {
+  return invoke("video_stream_set_loop", {
+    streamId,
+    startMs: region ? Math.floor(region.startMs) : null,
+    endMs: region ? Math.floor(region.endMs) : null,
+  });
+}
+
+export async function closeVideoStream(streamId: number): Promise<void> {
+  return invoke("close_video_stream", { streamId });
+}
+
+// ============================================================================
+// Audio Playback
+// ============================================================================
+
+export interface AudioPlaybackInfo {
+  filename: string;
+  duration_secs: number;
+}
+
+/** Payload of the "audio-playback-position" event */
+export interface AudioPlaybackPosition {
+  position_secs: number;
+  playing: boolean;
+}
+
+/** Load an audio file for sample-accurate backend playback */
+export async function audioPlaybackLoad(
+  sessionPath: string,
+  filename: string,
+): Promise<AudioPlaybackInfo> {
+  return invoke("audio_playback_load", { sessionPath, filename });
+}
+
+export async function audioPlaybackPlay(): Promise<void> {
+  return invoke("audio_playback_play");
+}
+
+export async function audioPlaybackPause(): Promise<void> {
+  return invoke("audio_playback_pause");
+}
+
+export async function audioPlaybackSeek(positionSecs: number): Promise<void> {
+  return invoke("audio_playback_seek", { positionSecs });
+}
+
+/** Set playback rate (0.5–2.0); resolves to the applied rate */
+export async function audioPlaybackSetRate(rate: number): Promise<number> {
+  return invoke("audio_playback_set_rate", { rate });
+}
+
+/** Keep pitch constant when playing faster or slower */
+export async function audioPlaybackSetPreservePitch(preserve: boolean): Promise<void> {
+  return invoke("audio_playback_set_preserve_pitch", { preserve });
+}
+
+/** Loop gaplessly between two positions; pass null to clear */
+export async function audioPlaybackSetLoop(
+  region: { startSecs: number; endSecs: number } | null,
+): Promise<void> {
+  return invoke("audio_playback_set_loop", {
+    startSecs: region?.startSecs ?? null,
+    endSecs: region?.endSecs ?? null,
+  });
+}
+
+export async function audioPlaybackSetVolume(volumeDb: number): Promise<void> {
+  return invoke("audio_playback_set_volume", { volumeDb });
+}
+
+export async function audioPlaybackPosition(): Promise<number> {
+  return invoke("audio_playback_position");
+}
+
+export async function audioPlaybackUnload(): Promise<void> {
+  return invoke("audio_playback_unload");
+}
+
 // ============================================================================
 // App Stats
 // ============================================================================
@@ -926,6 +2525,20 @@ export async function getAppStats(): Promise<AppStats> {
   return invoke<AppStats>("get_app_stats");
 }
 
+// ============================================================================
+// Database Maintenance
+// ============================================================================
+
+export interface VacuumResult {
+  schema_version: number;
+  size_before_bytes: number;
+  size_after_bytes: number;
+}
+
+export async function vacuumDatabase(): Promise<VacuumResult> {
+  return invoke<VacuumResult>("vacuum_database");
+}
+
 // ============================================================================
 // Utility Functions
 // ============================================================================
@@ -2,8 +2,8 @@
 
 import { writable, derived, get } from 'svelte/store';
 import { listen } from '@tauri-apps/api/event';
-import type { AudioDevice, MidiDevice, VideoDevice, VideoDeviceConfig, VideoFpsWarning, AudioTriggerLevel, Config, DisconnectedDeviceInfo } from '$lib/api';
-import { refreshAllDevices, getAudioDevices, getMidiDevices, getVideoDevices, getConfig, updateConfig, updateAudioTriggerThresholds, getDisconnectedDevices, restartDevicePipelines } from '$lib/api';
+import type { MidiAccessMode, AudioDevice, MidiDevice, VideoDevice, VideoDeviceConfig, VideoFpsWarning, EncoderFailover, EncodingAdaptation, BlankVideoWarning, AudioTriggerLevel, Config, DisconnectedDeviceInfo, DeviceContentionInfo } from '$lib/api';
+import { refreshAllDevices, getAudioDevices, getMidiDevices, getVideoDevices, getConfig, updateConfig, updateAudioTriggerThresholds, getDisconnectedDevices, getDevicesInUse, restartDevicePipelines } from '$lib/api';
 import { settings } from './settings';
 import { recordingState, refreshRecordingState } from './recording';
 import { playDisconnectWarningSound } from '$lib/sounds';
@@ -21,6 +21,7 @@ export const videoDevices = writable<VideoDevice[]>([]);
 export const selectedAudioDevices = writable<Set<string>>(new Set());
 export const selectedMidiDevices = writable<Set<string>>(new Set());
 export const triggerMidiDevices = writable<Set<string>>(new Set());
+export const midiAccessModes = writable<Record<string, MidiAccessMode>>({});
 export const selectedVideoDevices = writable<Set<string>>(new Set());
 
 // Audio trigger state
@@ -49,6 +50,9 @@ export const ffv1WarningDevices = derived(
 
 // FPS mismatch warnings from video capture devices
 export const videoFpsWarnings = writable<VideoFpsWarning[]>([]);
+export const videoEncoderFailovers = writable<EncoderFailover[]>([]);
+export const videoEncodingAdaptations = writable<EncodingAdaptation[]>([]);
+export const videoBlankWarnings = writable<BlankVideoWarning[]>([]);
 
 // Disconnected device IDs (from health checker)
 export const disconnectedDevices = writable<Set<string>>(new Set());
@@ -56,6 +60,9 @@ export const disconnectedDevices = writable<Set<string>>(new Set());
 // Full disconnected device info (for banner messages)
 export const disconnectedDeviceInfos = writable<DisconnectedDeviceInfo[]>([]);
 
+// Devices held by another application, keyed by device ID (from pipeline start failures)
+export const devicesInUse = writable<Map<string, DeviceContentionInfo>>(new Map());
+
 // Whether the user has dismissed the disconnect banner (resets when new devices disconnect)
 export const disconnectBannerDismissed = writable(false);
 
@@ -99,6 +106,27 @@ async function setupEventListeners() {
     });
   }));
 
+  eventUnlisteners.push(await listen<EncoderFailover>('video-encoder-failover', (event) => {
+    videoEncoderFailovers.update(failovers => {
+      const filtered = failovers.filter(f => f.device_name !== event.payload.device_name);
+      return [...filtered, event.payload];
+    });
+  }));
+
+  eventUnlisteners.push(await listen<EncodingAdaptation>('video-encoding-adapted', (event) => {
+    videoEncodingAdaptations.update(adaptations => {
+      const filtered = adaptations.filter(a => a.device_name !== event.payload.device_name);
+      return [...filtered, event.payload];
+    });
+  }));
+
+  eventUnlisteners.push(await listen<BlankVideoWarning>('video-blank-warning', (event) => {
+    videoBlankWarnings.update(warnings => {
+      const filtered = warnings.filter(w => w.device_id !== event.payload.device_id);
+      return [...filtered, event.payload];
+    });
+  }));
+
   eventUnlisteners.push(await listen<AudioTriggerLevel[]>('audio-trigger-levels', (event) => {
     audioTriggerLevels.update(levels => {
       const updated = { ...levels };
@@ -132,6 +160,10 @@ async function setupEventListeners() {
     disconnectedDeviceInfos.set(newInfos);
   }));
 
+  eventUnlisteners.push(await listen<{ in_use_devices: DeviceContentionInfo[] }>('device-contention-changed', (event) => {
+    devicesInUse.set(new Map(event.payload.in_use_devices.map(d => [d.id, d])));
+  }));
+
   eventUnlisteners.push(await listen<{ device_types: string[] }>('_device-needs-restart', async (event) => {
     try {
       await restartDevicePipelines(event.payload.device_types);
@@ -205,6 +237,8 @@ export async function refreshDevices() {
   } catch (e) {
     console.error('Failed to re-enumerate devices:', e);
   }
+  // Device aliases may have moved settings to a device's new ID
+  await loadConfig();
   await loadDevices();
 }
 
@@ -217,6 +251,7 @@ export async function loadConfig() {
     selectedAudioDevices.set(new Set(cfg.selected_audio_devices));
     selectedMidiDevices.set(new Set(cfg.selected_midi_devices));
     triggerMidiDevices.set(new Set(cfg.trigger_midi_devices));
+    midiAccessModes.set(cfg.midi_access_modes ?? {});
     triggerAudioDevices.set(new Set(cfg.trigger_audio_devices ?? []));
     audioTriggerThresholds.set(cfg.audio_trigger_thresholds ?? {});
     selectedVideoDevices.set(new Set(cfg.selected_video_devices));
@@ -309,6 +344,7 @@ export async function saveDeviceSelection() {
     selected_audio_devices: Array.from(audioSelected),
     selected_midi_devices: Array.from(midiSelected),
     trigger_midi_devices: Array.from(midiTriggers),
+    midi_access_modes: get(midiAccessModes),
     trigger_audio_devices: Array.from(audioTriggers),
     audio_trigger_thresholds: audioThresholds,
     selected_video_devices: Array.from(videoSelected),
@@ -403,6 +439,15 @@ export function toggleAudioTrigger(deviceId: string) {
   autoSaveDevices();
 }
 
+/** Change how a MIDI port is opened (restarts the MIDI connections) */
+export async function setMidiAccessMode(deviceId: string, mode: MidiAccessMode) {
+  midiAccessModes.update(modes => {
+    const { [deviceId]: _, ...rest } = modes;
+    return mode === 'exclusive' ? rest : { ...rest, [deviceId]: mode };
+  });
+  await saveDeviceSelection();
+}
+
 export function setAudioTriggerThreshold(deviceId: string, threshold: number) {
   audioTriggerThresholds.update(thresholds => ({
     ...thresholds,
@@ -471,6 +516,12 @@ async function initialize() {
   } catch (e) {
     console.error('Failed to load disconnected devices:', e);
   }
+  try {
+    const inUse = await getDevicesInUse();
+    devicesInUse.set(new Map(inUse.map(d => [d.id, d])));
+  } catch (e) {
+    console.error('Failed to load in-use devices:', e);
+  }
   // Clean up stale device IDs after both devices and config are loaded
   await cleanupStaleDeviceIds();
 }
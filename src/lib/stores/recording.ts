@@ -2,8 +2,8 @@
 
 import { writable, derived } from 'svelte/store';
 import { listen } from '@tauri-apps/api/event';
-import type { RecordingState, SessionMetadata } from '$lib/api';
-import { getRecordingState, startRecording, stopRecording } from '$lib/api';
+import type { PrivacyStatus, RecordingState, SessionMetadata, StreamStatus } from '$lib/api';
+import { getPrivacyStatus, getRecordingState, startRecording, stopRecording } from '$lib/api';
 import { addNewSession } from './sessions';
 import { settings } from './settings';
 import { playStartSound, playStopSound } from '$lib/sounds';
@@ -22,6 +22,12 @@ const initialState: RecordingState = {
 
 export const recordingState = writable<RecordingState>(initialState);
 
+// Live stream status (null when streaming is off or has not started)
+export const streamStatus = writable<StreamStatus | null>(null);
+
+// Privacy pause (triggers disarmed), null until loaded
+export const privacyStatus = writable<PrivacyStatus | null>(null);
+
 // Event listener cleanup for HMR — previous listeners are unsubscribed before re-registering
 let eventUnlisteners: (() => void)[] = [];
 
@@ -56,6 +62,14 @@ async function setupEventListeners() {
     }
   }));
 
+  eventUnlisteners.push(await listen<StreamStatus>('stream-status', (event) => {
+    streamStatus.set(event.payload.state === 'stopped' && !event.payload.message ? null : event.payload);
+  }));
+
+  eventUnlisteners.push(await listen<PrivacyStatus>('privacy-pause-changed', (event) => {
+    privacyStatus.set(event.payload);
+  }));
+
   eventUnlisteners.push(await listen('recording-state-changed', async (event) => {
     console.log('Recording state changed:', event.payload);
     await refreshRecordingState();
@@ -135,6 +149,15 @@ export async function doStopRecording() {
   // Note: Session list is updated via the 'recording-stopped' event listener
 }
 
+export async function refreshPrivacyStatus() {
+  try {
+    privacyStatus.set(await getPrivacyStatus());
+  } catch (error) {
+    console.error('Failed to load privacy status:', error);
+  }
+}
+
 // Initialize on import
 setupEventListeners();
 refreshRecordingState();
+refreshPrivacyStatus();
@@ -1,9 +1,10 @@
 // Session list store
 
 import { writable, derived, get } from 'svelte/store';
-import type { SessionSummary, SessionMetadata, SessionFilter, RescanProgress } from '$lib/api';
-import { getSessions, getSessionDetail, deleteSession as apiDeleteSession, updateSessionNotes as apiUpdateNotes, rescanSessions as apiRescanSessions, renameSession as apiRenameSession } from '$lib/api';
+import type { SessionSummary, SessionMetadata, SessionFilter, RescanProgress, RescanReport, QuickReviewRequest } from '$lib/api';
+import { getSessions, countSessions, getSessionDetail, deleteSession as apiDeleteSession, updateSessionNotes as apiUpdateNotes, rescanSessions as apiRescanSessions, renameSession as apiRenameSession, setSessionFavorite as apiSetFavorite, setSessionTags as apiSetTags, setSessionFields as apiSetFields, type CustomFields } from '$lib/api';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import { activeTab } from './navigation';
 
 // Store for session list
 export const sessions = writable<SessionSummary[]>([]);
@@ -23,18 +24,32 @@ export const sessionFilter = writable<SessionFilter>({
   has_title: undefined,
 });
 
+// Sessions matching the filter; `sessions` holds the pages loaded so far
+export const sessionTotal = writable(0);
+
+// Sessions fetched per page as the list scrolls
+const PAGE_SIZE = 500;
+
 // Loading state
 export const isLoading = writable(false);
 
 // Pending seek offset — set before navigating to a session, consumed after MIDI loads
 export const pendingSeekOffset = writable<number | null>(null);
 
+// Start playback once the next selected session loads (quick review "Play")
+export const pendingAutoplay = writable(false);
+
 // Scan progress (non-null only during first-time scan of new sessions)
 export const scanProgress = writable<RescanProgress | null>(null);
 
-// Derived store for grouped sessions by date
-export const groupedSessions = derived(sessions, $sessions => {
+// Derived store for grouped sessions by date. Other sort orders are one
+// ungrouped list (key ''), since dates would repeat across groups.
+export const groupedSessions = derived([sessions, sessionFilter], ([$sessions, $filter]) => {
   const groups: Record<string, SessionSummary[]> = {};
+  if ($filter.sort && $filter.sort !== 'date') {
+    groups[''] = $sessions;
+    return groups;
+  }
   const today = new Date();
   today.setHours(0, 0, 0, 0);
   const yesterday = new Date(today);
@@ -89,8 +104,12 @@ export async function refreshSessions(autoSelectLatest = false) {
     let filter: SessionFilter = {};
     sessionFilter.subscribe(f => filter = f)();
 
-    const sessionList = await getSessions(filter);
+    const [sessionList, total] = await Promise.all([
+      getSessions({ ...filter, limit: PAGE_SIZE, offset: 0 }),
+      countSessions(filter),
+    ]);
     sessions.set(sessionList);
+    sessionTotal.set(total);
 
     // Auto-select the latest session if requested and no session is currently selected
     if (autoSelectLatest && sessionList.length > 0) {
@@ -109,6 +128,27 @@ export async function refreshSessions(autoSelectLatest = false) {
   }
 }
 
+let loadingMore = false;
+
+/** Fetch the next page of the list, if there is one */
+export async function loadMoreSessions() {
+  const loaded = get(sessions).length;
+  if (loadingMore || loaded >= get(sessionTotal)) return;
+  loadingMore = true;
+  try {
+    const page = await getSessions({ ...get(sessionFilter), limit: PAGE_SIZE, offset: loaded });
+    sessions.update(list => {
+      // Drop rows a concurrent add already put in the list
+      const ids = new Set(list.map(s => s.id));
+      return [...list, ...page.filter(s => !ids.has(s.id))];
+    });
+  } catch (error) {
+    console.error('Failed to fetch more sessions:', error);
+  } finally {
+    loadingMore = false;
+  }
+}
+
 /** 
  * Add a newly recorded session to the list without full refresh.
  * Converts SessionMetadata to SessionSummary and prepends to the list.
@@ -124,10 +164,16 @@ export function addNewSession(metadata: SessionMetadata) {
     has_video: (metadata.video_files?.length ?? 0) > 0,
     notes: '',
     title: metadata.title ?? null,
+    archived: metadata.archived ?? false,
+    favorite: metadata.favorite ?? false,
+    tags: metadata.tags ?? [],
+    size_bytes: 0,
+    video_streams: (metadata.video_files ?? []).flatMap((v) => (v.stream ? [v.stream] : [])),
   };
   
   // Prepend to list (newest first)
   sessions.update(list => [summary, ...list]);
+  sessionTotal.update(n => n + 1);
   
   // Auto-select the new session
   selectSession(metadata.id);
@@ -157,19 +203,43 @@ export async function selectSession(sessionId: string | null) {
 export async function deleteSessionById(sessionId: string) {
   try {
     await apiDeleteSession(sessionId);
-    
-    // Remove from local state
-    sessions.update(list => list.filter(s => s.id !== sessionId));
-    
-    // Clear selection if deleted session was selected
-    selectedSessionId.update(id => id === sessionId ? null : id);
-    selectedSession.update(s => s?.id === sessionId ? null : s);
+    removeSessionLocally(sessionId);
   } catch (error) {
     console.error('Failed to delete session:', error);
     throw error;
   }
 }
 
+function removeSessionLocally(sessionId: string) {
+  sessions.update(list => list.filter(s => s.id !== sessionId));
+  sessionTotal.update(n => Math.max(0, n - 1));
+
+  // Clear selection if deleted session was selected
+  selectedSessionId.update(id => id === sessionId ? null : id);
+  selectedSession.update(s => s?.id === sessionId ? null : s);
+}
+
+export async function setFavorite(sessionId: string, favorite: boolean) {
+  await apiSetFavorite(sessionId, favorite);
+  setFavoriteLocally(sessionId, favorite);
+}
+
+function setFavoriteLocally(sessionId: string, favorite: boolean) {
+  sessions.update(list => list.map(s => s.id === sessionId ? { ...s, favorite } : s));
+  selectedSession.update(s => s?.id === sessionId ? { ...s, favorite } : s);
+}
+
+export async function setTags(sessionId: string, tags: string[]) {
+  const saved = await apiSetTags(sessionId, tags);
+  sessions.update(list => list.map(s => s.id === sessionId ? { ...s, tags: saved } : s));
+  selectedSession.update(s => s?.id === sessionId ? { ...s, tags: saved } : s);
+}
+
+export async function setFields(sessionId: string, fields: CustomFields) {
+  const saved = await apiSetFields(sessionId, fields);
+  selectedSession.update(s => s?.id === sessionId ? { ...s, custom_fields: saved } : s);
+}
+
 export function updateFilter(partial: Partial<SessionFilter>) {
   sessionFilter.update(f => ({ ...f, ...partial }));
   refreshSessions();
@@ -217,3 +287,49 @@ export async function renameCurrentSession(oldId: string, newTitle: string) {
 
 // Initialize - auto-select latest session on app start
 refreshSessions(true);
+
+// --- Event Listeners ---
+
+// The backend watches the storage folder and re-indexes on external changes
+// (e.g. sessions copied in from another machine); reload the list when it does.
+async function setupEventListeners() {
+  await listen<RescanReport>('storage-changed', async (event) => {
+    const { missing_sessions, moved_sessions } = event.payload;
+    let filter: SessionFilter = {};
+    sessionFilter.subscribe(f => filter = f)();
+    try {
+      // Keep as many rows as were loaded so the list doesn't jump back
+      const limit = Math.max(PAGE_SIZE, get(sessions).length);
+      const [sessionList, total] = await Promise.all([
+        getSessions({ ...filter, limit, offset: 0 }),
+        countSessions(filter),
+      ]);
+      sessions.set(sessionList);
+      sessionTotal.set(total);
+    } catch (error) {
+      console.error('Failed to reload sessions after storage change:', error);
+    }
+    const selectedId = get(selectedSessionId) ?? '';
+    const moved = moved_sessions.find(m => m.from === selectedId);
+    if (moved) {
+      selectSession(moved.to);
+    } else if (missing_sessions.includes(selectedId)) {
+      selectSession(null);
+    }
+  });
+
+  // Quick review after a recording stops, and its notification's buttons
+  await listen<QuickReviewRequest>('quick-review', (event) => {
+    activeTab.set('sessions');
+    pendingAutoplay.set(event.payload.play);
+    selectSession(event.payload.session_id);
+  });
+  await listen<{ session_id: string; favorite: boolean }>('session-favorite-changed', (event) => {
+    setFavoriteLocally(event.payload.session_id, event.payload.favorite);
+  });
+  await listen<string>('session-deleted', (event) => {
+    removeSessionLocally(event.payload);
+  });
+}
+
+setupEventListeners();
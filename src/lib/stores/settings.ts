@@ -35,7 +35,8 @@ export async function saveSettings(newSettings: Config) {
   // Check if the change affects recording pipelines (requires backend restart)
   const current = get(settings);
   const pipelineAffected = current != null && (
-    current.pre_roll_secs !== newSettings.pre_roll_secs ||
+    current.audio_midi_pre_roll_secs !== newSettings.audio_midi_pre_roll_secs ||
+    current.video_pre_roll_secs !== newSettings.video_pre_roll_secs ||
     current.encode_during_preroll !== newSettings.encode_during_preroll
   );
 